@@ -0,0 +1,228 @@
+use crate::ray::Ray;
+use crate::vector3::Vector3;
+
+/// The smallest thickness of a bounding box along any axis, so flat objects such as quads still
+/// enclose a volume that rays can hit.
+const MIN_THICKNESS: f64 = 1e-4;
+
+/// Represents an axis-aligned bounding box, used to skip objects that a ray cannot hit.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    /// The corner of the box with the smallest coordinates.
+    pub min: Vector3,
+    /// The corner of the box with the largest coordinates.
+    pub max: Vector3,
+}
+
+impl Aabb {
+    /// Creates a new `Aabb` from two opposite corners, padded along the axes on which it is flat.
+    ///
+    /// # Arguments
+    ///
+    /// * `a` - One corner of the box.
+    /// * `b` - The opposite corner of the box.
+    ///
+    /// # Returns
+    ///
+    /// A new `Aabb` instance.
+    pub fn new(a: Vector3, b: Vector3) -> Aabb {
+        let pad = |low: f64, high: f64| {
+            if high - low < MIN_THICKNESS {
+                let middle = (low + high) / 2.0;
+                (middle - MIN_THICKNESS / 2.0, middle + MIN_THICKNESS / 2.0)
+            } else {
+                (low, high)
+            }
+        };
+        let x = pad(a.x.min(b.x), a.x.max(b.x));
+        let y = pad(a.y.min(b.y), a.y.max(b.y));
+        let z = pad(a.z.min(b.z), a.z.max(b.z));
+        Aabb {
+            min: Vector3::new(x.0, y.0, z.0),
+            max: Vector3::new(x.1, y.1, z.1),
+        }
+    }
+
+    /// Creates the smallest `Aabb` enclosing a set of points.
+    ///
+    /// # Arguments
+    ///
+    /// * `points` - The points to enclose, at least one.
+    ///
+    /// # Returns
+    ///
+    /// A new `Aabb` instance.
+    pub fn from_points(points: &[Vector3]) -> Aabb {
+        let (min, max) = points[1..]
+            .iter()
+            .fold((points[0], points[0]), |(min, max), point| {
+                (
+                    Vector3::new(min.x.min(point.x), min.y.min(point.y), min.z.min(point.z)),
+                    Vector3::new(max.x.max(point.x), max.y.max(point.y), max.z.max(point.z)),
+                )
+            });
+        Aabb::new(min, max)
+    }
+
+    /// Returns the smallest box enclosing this box and another one.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The other box.
+    ///
+    /// # Returns
+    ///
+    /// The enclosing `Aabb`.
+    pub fn merge(&self, other: &Aabb) -> Aabb {
+        Aabb {
+            min: Vector3::new(
+                self.min.x.min(other.min.x),
+                self.min.y.min(other.min.y),
+                self.min.z.min(other.min.z),
+            ),
+            max: Vector3::new(
+                self.max.x.max(other.max.x),
+                self.max.y.max(other.max.y),
+                self.max.z.max(other.max.z),
+            ),
+        }
+    }
+
+    /// Returns the area of the surface of the box, proportional to the chance that a random ray
+    /// crossing a larger box containing it also crosses it.
+    pub fn surface_area(&self) -> f64 {
+        let size = self.max - self.min;
+        2.0 * (size.x * size.y + size.y * size.z + size.z * size.x)
+    }
+
+    /// Returns the volume of the box.
+    pub fn volume(&self) -> f64 {
+        let size = self.max - self.min;
+        size.x.max(0.0) * size.y.max(0.0) * size.z.max(0.0)
+    }
+
+    /// Returns the volume of the part of the box inside another one.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The other box.
+    ///
+    /// # Returns
+    ///
+    /// The volume shared by the two boxes, zero if they do not overlap.
+    pub fn overlap_volume(&self, other: &Aabb) -> f64 {
+        let overlap = |min: f64, max: f64, other_min: f64, other_max: f64| {
+            (max.min(other_max) - min.max(other_min)).max(0.0)
+        };
+        overlap(self.min.x, self.max.x, other.min.x, other.max.x)
+            * overlap(self.min.y, self.max.y, other.min.y, other.max.y)
+            * overlap(self.min.z, self.max.z, other.min.z, other.max.z)
+    }
+
+    /// Returns the eight corners of the box.
+    pub fn corners(&self) -> [Vector3; 8] {
+        let (min, max) = (self.min, self.max);
+        [
+            Vector3::new(min.x, min.y, min.z),
+            Vector3::new(max.x, min.y, min.z),
+            Vector3::new(min.x, max.y, min.z),
+            Vector3::new(max.x, max.y, min.z),
+            Vector3::new(min.x, min.y, max.z),
+            Vector3::new(max.x, min.y, max.z),
+            Vector3::new(min.x, max.y, max.z),
+            Vector3::new(max.x, max.y, max.z),
+        ]
+    }
+
+    /// Checks if a ray passes through the box within a given interval, with the slab method.
+    ///
+    /// # Arguments
+    ///
+    /// * `ray` - The ray to test for intersection.
+    /// * `interval` - The range of distances to consider for intersections.
+    ///
+    /// # Returns
+    ///
+    /// `true` if some part of the interval of the ray lies inside the box.
+    pub fn hit(&self, ray: &Ray, interval: (f64, f64)) -> bool {
+        self.clip(ray, interval).is_some()
+    }
+
+    /// Finds the part of the interval of a ray that lies inside the box, with the slab method.
+    ///
+    /// # Arguments
+    ///
+    /// * `ray` - The ray to clip.
+    /// * `interval` - The range of distances to consider.
+    ///
+    /// # Returns
+    ///
+    /// The range of distances along the ray inside the box, or `None` if the ray misses it.
+    pub fn clip(&self, ray: &Ray, interval: (f64, f64)) -> Option<(f64, f64)> {
+        let (mut t_min, mut t_max) = interval;
+        let axes = [
+            (ray.origin.x, ray.direction.x, self.min.x, self.max.x),
+            (ray.origin.y, ray.direction.y, self.min.y, self.max.y),
+            (ray.origin.z, ray.direction.z, self.min.z, self.max.z),
+        ];
+        for (origin, direction, min, max) in axes {
+            if direction == 0.0 {
+                if origin < min || origin > max {
+                    return None;
+                }
+                continue;
+            }
+            let inverse = 1.0 / direction;
+            let (t0, t1) = ((min - origin) * inverse, (max - origin) * inverse);
+            let (near, far) = if t0 < t1 { (t0, t1) } else { (t1, t0) };
+            t_min = t_min.max(near);
+            t_max = t_max.min(far);
+            if t_max < t_min {
+                return None;
+            }
+        }
+        Some((t_min, t_max))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hit() {
+        let bounds = Aabb::new(Vector3::new(1.0, 1.0, 1.0), Vector3::new(-1.0, -1.0, -1.0));
+        assert_eq!(bounds.min, Vector3::new(-1.0, -1.0, -1.0));
+
+        let ray = Ray::new(Vector3::new(0.0, 0.0, -5.0), Vector3::new(0.0, 0.0, 1.0));
+        assert!(bounds.hit(&ray, (0.0, f64::INFINITY)));
+        assert!(!bounds.hit(&ray, (0.0, 3.0)));
+        assert!(!bounds.hit(&ray, (7.0, f64::INFINITY)));
+
+        let away = Ray::new(Vector3::new(0.0, 0.0, -5.0), Vector3::new(0.0, 0.0, -1.0));
+        assert!(!bounds.hit(&away, (0.0, f64::INFINITY)));
+        let beside = Ray::new(Vector3::new(2.0, 0.0, -5.0), Vector3::new(0.0, 0.0, 1.0));
+        assert!(!bounds.hit(&beside, (0.0, f64::INFINITY)));
+        let diagonal = Ray::new(Vector3::new(-3.0, -3.0, -3.0), Vector3::new(1.0, 1.0, 1.0));
+        assert!(bounds.hit(&diagonal, (0.001, f64::INFINITY)));
+
+        let (t_min, t_max) = bounds.clip(&ray, (0.0, f64::INFINITY)).unwrap();
+        assert_eq!((t_min, t_max), (4.0, 6.0));
+        assert_eq!(bounds.clip(&ray, (5.0, 5.5)), Some((5.0, 5.5)));
+    }
+
+    #[test]
+    fn test_flat_boxes_are_padded() {
+        let flat = Aabb::new(Vector3::new(0.0, 2.0, 0.0), Vector3::new(1.0, 2.0, 1.0));
+        assert!(flat.max.y > flat.min.y);
+        let ray = Ray::new(Vector3::new(0.5, 0.0, 0.5), Vector3::new(0.0, 1.0, 0.0));
+        assert!(flat.hit(&ray, (0.0, f64::INFINITY)));
+
+        let merged = flat.merge(&Aabb::from_points(&[
+            Vector3::new(-1.0, 0.0, 3.0),
+            Vector3::new(0.0, 5.0, 0.0),
+        ]));
+        assert!((merged.min - Vector3::new(-1.0, 0.0, 0.0)).length() < 1e-9);
+        assert!((merged.max - Vector3::new(1.0, 5.0, 3.0)).length() < 1e-9);
+    }
+}
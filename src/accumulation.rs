@@ -0,0 +1,421 @@
+//! Accumulation buffers, holding the sums of the samples of every pixel of a render instead of
+//! their averages. Renders of disjoint ranges of samples of the same frame, such as samples 0 to
+//! 249 and 250 to 499 rendered with the same seed on different machines, give independent sums
+//! that add up to a render of all the samples. Merging their accumulation files, with
+//! `merge_files`, gives the image of all the samples, whether they were rendered on different
+//! machines or resumed in different sessions.
+//!
+//! Accumulation files start with the magic bytes `RTACCUM1`, followed by the width and height of
+//! the image, the number of sample ranges and the first and end sample of every range, all as
+//! little-endian 32-bit integers. The sums of every pixel follow row by row: the sums of the colors
+//! and of their squares as little-endian 64-bit floats, then the number of bounces and of covered
+//! samples as little-endian 64-bit integers.
+
+use crate::output::{self, OutputTransform};
+use crate::vector3::Vector3;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// The bytes every accumulation file starts with.
+const MAGIC: &[u8; 8] = b"RTACCUM1";
+
+/// Accumulates the samples of a pixel, keeping the sum of their squares so the noise left in the
+/// pixel can be estimated along with its color.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SampleSums {
+    /// The sum of the sampled colors.
+    pub color: Vector3,
+    /// The sum of the squares of the sampled colors, channel by channel.
+    pub squares: Vector3,
+    /// The total number of bounces of the sampled paths.
+    pub bounces: u64,
+    /// The number of samples whose camera ray hit a visible object.
+    pub covered: u64,
+}
+
+impl SampleSums {
+    /// Adds a sample to the sums.
+    ///
+    /// # Arguments
+    ///
+    /// * `color` - The sampled color.
+    /// * `bounces` - The number of bounces of the sampled path.
+    /// * `covered` - Whether the camera ray of the sample hit a visible object.
+    pub fn add(&mut self, color: Vector3, bounces: u32, covered: bool) {
+        self.color += color;
+        self.squares += color * color;
+        self.bounces += bounces as u64;
+        self.covered += covered as u64;
+    }
+
+    /// Returns the sums of two disjoint sets of samples of the same pixel.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The sums of the other samples.
+    pub fn merge(&self, other: &SampleSums) -> SampleSums {
+        SampleSums {
+            color: self.color + other.color,
+            squares: self.squares + other.squares,
+            bounces: self.bounces + other.bounces,
+            covered: self.covered + other.covered,
+        }
+    }
+
+    /// Estimates the standard error of the mean color of the pixel, channel by channel, from the
+    /// sample variance.
+    ///
+    /// # Arguments
+    ///
+    /// * `samples` - The number of samples summed.
+    ///
+    /// # Returns
+    ///
+    /// The standard error, or zero if fewer than two samples were taken.
+    pub fn standard_error(&self, samples: u32) -> Vector3 {
+        if samples < 2 {
+            return Vector3::default();
+        }
+        let n = samples as f64;
+        let mean = self.color / n;
+        let variance = (self.squares - self.color * mean) / (n - 1.0);
+        let error = |variance: f64| (variance.max(0.0) / n).sqrt();
+        Vector3::new(error(variance.x), error(variance.y), error(variance.z))
+    }
+}
+
+/// Represents the sums of the samples of every pixel of an image, and the ranges of samples they
+/// were taken from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Accumulation {
+    /// The width of the image in pixels.
+    pub width: u32,
+    /// The height of the image in pixels.
+    pub height: u32,
+    /// The disjoint ranges of the indices of the samples summed in every pixel.
+    pub ranges: Vec<Range<u32>>,
+    /// The sums of every pixel, stored row by row starting from the top-left pixel.
+    pub sums: Vec<SampleSums>,
+}
+
+impl Accumulation {
+    /// Creates an empty accumulation buffer for a range of samples.
+    ///
+    /// # Arguments
+    ///
+    /// * `width` - The width of the image in pixels.
+    /// * `height` - The height of the image in pixels.
+    /// * `range` - The range of the indices of the samples summed in every pixel.
+    ///
+    /// # Returns
+    ///
+    /// A new `Accumulation` instance with all sums zero.
+    pub fn new(width: u32, height: u32, range: Range<u32>) -> Accumulation {
+        Accumulation {
+            width,
+            height,
+            ranges: vec![range],
+            sums: vec![SampleSums::default(); (width * height) as usize],
+        }
+    }
+
+    /// Returns the number of samples summed in every pixel.
+    pub fn samples(&self) -> u32 {
+        self.ranges
+            .iter()
+            .map(|range| range.end - range.start)
+            .sum()
+    }
+
+    /// Adds the sums of another buffer of the same image to this one.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The other buffer, whose samples must not overlap the ones of this buffer.
+    ///
+    /// # Returns
+    ///
+    /// `Ok` if the buffers were merged, or a description of why they cannot be.
+    pub fn merge(&mut self, other: &Accumulation) -> Result<(), String> {
+        if (self.width, self.height) != (other.width, other.height) {
+            return Err(format!(
+                "cannot merge a {}x{} image into a {}x{} image",
+                other.width, other.height, self.width, self.height
+            ));
+        }
+        for range in &other.ranges {
+            if let Some(overlap) = self
+                .ranges
+                .iter()
+                .find(|known| known.start < range.end && range.start < known.end)
+            {
+                return Err(format!(
+                    "samples {}..{} overlap samples {}..{}",
+                    range.start, range.end, overlap.start, overlap.end
+                ));
+            }
+        }
+
+        self.ranges.extend(other.ranges.iter().cloned());
+        self.ranges.sort_by_key(|range| range.start);
+        for (sum, other) in self.sums.iter_mut().zip(&other.sums) {
+            *sum = sum.merge(other);
+        }
+        Ok(())
+    }
+
+    /// Returns the averaged linear color of every pixel.
+    pub fn color(&self) -> Vec<Vector3> {
+        let samples = self.samples() as f64;
+        self.sums.iter().map(|sum| sum.color / samples).collect()
+    }
+
+    /// Returns the standard error of the color of every pixel.
+    pub fn error(&self) -> Vec<Vector3> {
+        let samples = self.samples();
+        self.sums
+            .iter()
+            .map(|sum| sum.standard_error(samples))
+            .collect()
+    }
+
+    /// Returns the average number of bounces of the paths traced through every pixel.
+    pub fn bounces(&self) -> Vec<f64> {
+        let samples = self.samples() as f64;
+        self.sums
+            .iter()
+            .map(|sum| sum.bounces as f64 / samples)
+            .collect()
+    }
+
+    /// Returns the fraction of the samples of every pixel whose camera ray hit a visible object.
+    pub fn coverage(&self) -> Vec<f64> {
+        let samples = self.samples() as f64;
+        self.sums
+            .iter()
+            .map(|sum| sum.covered as f64 / samples)
+            .collect()
+    }
+
+    /// Saves the averaged colors of the buffer to an image file.
+    ///
+    /// # Arguments
+    ///
+    /// * `transform` - The output transform encoding the colors of byte formats.
+    /// * `path` - The path of the image file, whose extension gives the format.
+    ///
+    /// # Returns
+    ///
+    /// The result of writing the file.
+    pub fn save_image(&self, transform: &OutputTransform, path: &Path) -> image::ImageResult<()> {
+        output::save_image(&self.color(), self.width, self.height, transform, path)
+    }
+
+    /// Saves the buffer to an accumulation file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the file.
+    ///
+    /// # Returns
+    ///
+    /// An `io::Result` indicating whether the file was written.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut file = BufWriter::new(File::create(path)?);
+        file.write_all(MAGIC)?;
+        let ranges = self
+            .ranges
+            .iter()
+            .flat_map(|range| [range.start, range.end]);
+        for value in [self.width, self.height, self.ranges.len() as u32]
+            .into_iter()
+            .chain(ranges)
+        {
+            file.write_all(&value.to_le_bytes())?;
+        }
+        for sum in &self.sums {
+            for value in [sum.color, sum.squares]
+                .iter()
+                .flat_map(|v| [v.x, v.y, v.z])
+            {
+                file.write_all(&value.to_le_bytes())?;
+            }
+            file.write_all(&sum.bounces.to_le_bytes())?;
+            file.write_all(&sum.covered.to_le_bytes())?;
+        }
+        file.flush()
+    }
+
+    /// Loads a buffer from an accumulation file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the file.
+    ///
+    /// # Returns
+    ///
+    /// The buffer, or a description of why the file could not be read.
+    pub fn load(path: &Path) -> Result<Accumulation, String> {
+        let file = File::open(path).map_err(|e| e.to_string())?;
+        let mut bytes = Vec::new();
+        BufReader::new(file)
+            .read_to_end(&mut bytes)
+            .map_err(|e| e.to_string())?;
+        Self::parse(&bytes)
+    }
+
+    /// Parses the contents of an accumulation file.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The contents of the file.
+    ///
+    /// # Returns
+    ///
+    /// The buffer, or a description of why the contents are invalid.
+    fn parse(bytes: &[u8]) -> Result<Accumulation, String> {
+        let body = bytes
+            .strip_prefix(MAGIC)
+            .ok_or("not an accumulation file")?;
+        let mut words = body
+            .chunks_exact(4)
+            .map(|word| u32::from_le_bytes(word.try_into().expect("chunks of four bytes")));
+        let mut next = || words.next().ok_or("truncated accumulation file");
+        let (width, height, count) = (next()?, next()?, next()?);
+        let mut ranges = Vec::new();
+        for _ in 0..count {
+            let (start, end) = (next()?, next()?);
+            if start >= end {
+                return Err(format!("invalid sample range {}..{}", start, end));
+            }
+            ranges.push(start..end);
+        }
+
+        let header = 4 * (3 + 2 * count as usize);
+        let pixel_bytes = 8 * 8;
+        let pixels = width as usize * height as usize;
+        let data = &body[header.min(body.len())..];
+        if data.len() != pixels * pixel_bytes {
+            return Err(format!(
+                "expected {} bytes of sums for a {}x{} image, found {}",
+                pixels * pixel_bytes,
+                width,
+                height,
+                data.len()
+            ));
+        }
+        let sums = data
+            .chunks_exact(pixel_bytes)
+            .map(|pixel| {
+                let word = |index: usize| -> [u8; 8] {
+                    pixel[8 * index..8 * index + 8]
+                        .try_into()
+                        .expect("words of eight bytes")
+                };
+                let float = |index: usize| f64::from_le_bytes(word(index));
+                SampleSums {
+                    color: Vector3::new(float(0), float(1), float(2)),
+                    squares: Vector3::new(float(3), float(4), float(5)),
+                    bounces: u64::from_le_bytes(word(6)),
+                    covered: u64::from_le_bytes(word(7)),
+                }
+            })
+            .collect();
+        Ok(Accumulation {
+            width,
+            height,
+            ranges,
+            sums,
+        })
+    }
+}
+
+/// Loads accumulation files of disjoint sample ranges of the same image and merges them into the
+/// buffer of all their samples.
+///
+/// # Arguments
+///
+/// * `paths` - The paths of the accumulation files.
+///
+/// # Returns
+///
+/// The merged buffer, or a description of the first file that could not be read or merged.
+pub fn merge_files(paths: &[PathBuf]) -> Result<Accumulation, String> {
+    let load = |path: &PathBuf| {
+        Accumulation::load(path).map_err(|e| format!("failed to read {}: {}", path.display(), e))
+    };
+    let (first, rest) = paths
+        .split_first()
+        .ok_or("no accumulation files to merge")?;
+    let mut merged = load(first)?;
+    for path in rest {
+        merged
+            .merge(&load(path)?)
+            .map_err(|e| format!("failed to merge {}: {}", path.display(), e))?;
+    }
+    Ok(merged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_and_save_accumulations() {
+        let mut first = Accumulation::new(2, 1, 0..2);
+        first.sums[0].add(Vector3::new(1.0, 2.0, 3.0), 1, true);
+        first.sums[0].add(Vector3::new(3.0, 2.0, 1.0), 2, true);
+        let mut second = Accumulation::new(2, 1, 2..4);
+        second.sums[0].add(Vector3::new(0.0, 0.0, 0.0), 3, false);
+        second.sums[0].add(Vector3::new(4.0, 4.0, 4.0), 2, true);
+
+        let mut merged = first.clone();
+        merged.merge(&second).unwrap();
+        assert_eq!(merged.ranges, vec![0..2, 2..4]);
+        assert_eq!(merged.samples(), 4);
+        assert_eq!(merged.color()[0], Vector3::new(2.0, 2.0, 2.0));
+        assert_eq!(merged.bounces()[0], 2.0);
+        assert_eq!(merged.coverage(), vec![0.75, 0.0]);
+
+        // The same samples cannot be counted twice, nor images of different sizes merged.
+        assert!(merged.merge(&second).is_err());
+        assert!(merged.merge(&Accumulation::new(1, 2, 4..5)).is_err());
+
+        let path = std::env::temp_dir().join(format!("accumulation_{}.acc", std::process::id()));
+        merged.save(&path).unwrap();
+        assert_eq!(Accumulation::load(&path).unwrap(), merged);
+        let bytes = std::fs::read(&path).unwrap();
+        assert!(Accumulation::parse(&bytes[..bytes.len() - 1]).is_err());
+        assert!(Accumulation::parse(b"RTACCUM0").is_err());
+
+        // Files merge like the buffers they hold, and the merged colors can be saved as an image.
+        let parts = [0..2, 2..4].map(|range| {
+            let mut part = Accumulation::new(2, 1, range.clone());
+            part.sums[1].add(Vector3::new(range.start as f64, 0.0, 0.0), 0, true);
+            let path = std::env::temp_dir().join(format!(
+                "accumulation_{}_{}.acc",
+                std::process::id(),
+                range.start
+            ));
+            part.save(&path).unwrap();
+            path
+        });
+        let merged = merge_files(&parts).unwrap();
+        assert_eq!(merged.ranges, vec![0..2, 2..4]);
+        assert_eq!(merged.color()[1], Vector3::new(0.5, 0.0, 0.0));
+        assert!(merge_files(&[parts[0].clone(), parts[0].clone()]).is_err());
+        assert!(merge_files(&[parts[0].clone(), path.with_extension("missing")]).is_err());
+        assert!(merge_files(&[]).is_err());
+
+        let image = path.with_extension("png");
+        merged
+            .save_image(&OutputTransform::default(), &image)
+            .unwrap();
+        assert_eq!(image::open(&image).unwrap().width(), 2);
+        for file in parts.iter().chain([&path, &image]) {
+            std::fs::remove_file(file).unwrap();
+        }
+    }
+}
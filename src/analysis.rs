@@ -0,0 +1,289 @@
+//! Checks of a scene for modeling mistakes that only show as artifacts once rendered, such as
+//! objects added twice or coplanar surfaces fighting over which of them is seen.
+
+use crate::camera::Camera;
+use crate::scene::Scene;
+use crate::statistics::SceneStatistics;
+
+/// The largest number of pixels along each side of the image whose rays probe for coincident
+/// surfaces.
+const PROBE_SIZE: u32 = 64;
+
+/// The number of probed pixels in which two surfaces must coincide to be reported, so a medium
+/// scattering by chance right at another surface is not.
+const MIN_COINCIDENT_PIXELS: usize = 2;
+
+/// The fraction of the volume of two bounding boxes that must be shared for their solids to be
+/// reported as overlapping.
+const OVERLAP_THRESHOLD: f64 = 0.9;
+
+/// The ways two objects of a scene can overlap.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OverlapKind {
+    /// The surfaces of the objects coincide where the camera sees them, so which one shows is
+    /// left to rounding errors, giving speckled surfaces.
+    CoincidentSurfaces {
+        /// The number of probed pixels showing both surfaces at the same distance.
+        pixels: usize,
+        /// The number of pixels probed.
+        probes: usize,
+    },
+    /// The objects take up nearly the same space, as when an object is added twice.
+    OverlappingSolids {
+        /// The fraction of the volume of their bounding boxes shared by both.
+        shared: f64,
+    },
+}
+
+/// Describes two objects of a scene that overlap.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Overlap {
+    /// The index of the object added first.
+    pub first: usize,
+    /// The index of the object added second.
+    pub second: usize,
+    /// How the objects overlap.
+    pub kind: OverlapKind,
+}
+
+impl Overlap {
+    /// Describes the overlap, naming the objects.
+    ///
+    /// # Arguments
+    ///
+    /// * `scene` - The scene holding the objects.
+    ///
+    /// # Returns
+    ///
+    /// A sentence describing the overlap.
+    pub fn describe(&self, scene: &Scene) -> String {
+        let (first, second) = (
+            scene.describe_object(self.first),
+            scene.describe_object(self.second),
+        );
+        match self.kind {
+            OverlapKind::CoincidentSurfaces { pixels, probes } => format!(
+                "{} and {} have coincident surfaces in {} of {} probed pixels, set the priority \
+                 of the one meant to be seen",
+                first, second, pixels, probes
+            ),
+            OverlapKind::OverlappingSolids { shared } => format!(
+                "{} and {} overlap, sharing {:.0}% of their bounds",
+                first,
+                second,
+                shared * 100.0
+            ),
+        }
+    }
+}
+
+impl Scene {
+    /// Looks for objects overlapping in ways that give artifacts: surfaces coinciding where the
+    /// camera sees them, found by tracing a grid of pixels, and solids whose bounding boxes
+    /// nearly coincide. Objects without bounds are left out of the second check, and pairs of
+    /// objects with different priorities out of both, as their priorities settle which is seen.
+    ///
+    /// # Arguments
+    ///
+    /// * `camera` - The camera through which the scene is seen.
+    ///
+    /// # Returns
+    ///
+    /// The overlaps found, the coincident surfaces first, each pair reported once.
+    pub fn find_overlaps(&self, camera: &Camera) -> Vec<Overlap> {
+        let objects: Vec<_> = self.objects().collect();
+        let mut overlaps = Vec::new();
+
+        // Surfaces facing the same way within the distance scattered rays skip are treated as
+        // one surface by the renderer, which picks either of them at random unless their
+        // priorities settle which one is seen.
+        let (width, height) = (camera.image_width(), camera.image_height());
+        let step_x = width.div_ceil(PROBE_SIZE).max(1) as usize;
+        let step_y = height.div_ceil(PROBE_SIZE).max(1) as usize;
+        let mut coincident: Vec<((usize, usize), usize)> = Vec::new();
+        let mut probes = 0;
+        for y in (0..height).step_by(step_y) {
+            for x in (0..width).step_by(step_x) {
+                probes += 1;
+                let ray = camera.pixel_ray(x, y);
+                let (near, far) = camera.clip_interval(&ray);
+                let Some((index, record)) = self.hit_object(&ray, (near.max(self.epsilon()), far))
+                else {
+                    continue;
+                };
+                let tolerance = self.epsilon() / ray.direction.length();
+                let interval = (record.t - tolerance, record.t + tolerance);
+                for (other, object) in objects.iter().enumerate() {
+                    if other == index {
+                        continue;
+                    }
+                    let Some(other_record) = object.hit(&ray, interval) else {
+                        continue;
+                    };
+                    if other_record.front_face != record.front_face
+                        || self.priority(other) != self.priority(index)
+                    {
+                        continue;
+                    }
+                    let pair = (index.min(other), index.max(other));
+                    match coincident.iter_mut().find(|(found, _)| *found == pair) {
+                        Some((_, pixels)) => *pixels += 1,
+                        None => coincident.push((pair, 1)),
+                    }
+                }
+            }
+        }
+        coincident.sort_by_key(|&(pair, _)| pair);
+        for ((first, second), pixels) in coincident {
+            if pixels >= MIN_COINCIDENT_PIXELS {
+                overlaps.push(Overlap {
+                    first,
+                    second,
+                    kind: OverlapKind::CoincidentSurfaces { pixels, probes },
+                });
+            }
+        }
+
+        let bounds: Vec<_> = objects
+            .iter()
+            .map(|object| object.bounding_box().filter(|bounds| bounds.volume() > 0.0))
+            .collect();
+        for (first, a) in bounds.iter().enumerate() {
+            let Some(a) = a else {
+                continue;
+            };
+            for (second, b) in bounds.iter().enumerate().skip(first + 1) {
+                let Some(b) = b else {
+                    continue;
+                };
+                let shared_volume = a.overlap_volume(b);
+                let shared = shared_volume / (a.volume() + b.volume() - shared_volume);
+                let reported = overlaps
+                    .iter()
+                    .any(|overlap| overlap.first == first && overlap.second == second);
+                let settled = self.priority(first) != self.priority(second);
+                if shared >= OVERLAP_THRESHOLD && !reported && !settled {
+                    overlaps.push(Overlap {
+                        first,
+                        second,
+                        kind: OverlapKind::OverlappingSolids { shared },
+                    });
+                }
+            }
+        }
+        overlaps
+    }
+
+    /// Describes an object for messages, by its name if it has one, or else by its index and
+    /// the kinds of primitives it is made of.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the object.
+    ///
+    /// # Returns
+    ///
+    /// The description, such as `"floor"` or `"object 3 (ConstantMedium, Sphere)"`.
+    pub fn describe_object(&self, index: usize) -> String {
+        if let Some(name) = self.object_name(index) {
+            return format!("\"{}\"", name);
+        }
+        let mut statistics = SceneStatistics::new();
+        if let Some(object) = self.objects().nth(index) {
+            object.collect_statistics(&mut statistics);
+        }
+        let primitives: Vec<&str> = statistics.primitives.keys().map(String::as_str).collect();
+        format!("object {} ({})", index, primitives.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hit::Hittable;
+    use crate::material::Lambertian;
+    use crate::scene::DEFAULT_LAYER;
+    use crate::shapes::quad::Quad;
+    use crate::shapes::sphere::Sphere;
+    use crate::vector3::Vector3;
+    use std::sync::Arc;
+
+    fn camera() -> Camera {
+        Camera::new(
+            40,
+            1.0,
+            1,
+            1,
+            |_| Vector3::new(0.0, 0.0, 0.0),
+            60.0,
+            Vector3::new(0.0, 1.0, 6.0),
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            0.0,
+            0.0,
+        )
+    }
+
+    fn floor(y: f64) -> Box<dyn Hittable> {
+        Box::new(Quad::new(
+            Vector3::new(-5.0, y, -5.0),
+            Vector3::new(10.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, 10.0),
+            Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5))),
+        ))
+    }
+
+    fn ball(radius: f64) -> Box<dyn Hittable> {
+        Box::new(Sphere::new(
+            Vector3::new(0.0, 1.0, 0.0),
+            radius,
+            Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5))),
+        ))
+    }
+
+    #[test]
+    fn test_clean_scene_has_no_overlaps() {
+        let scene = Scene::new(vec![floor(0.0), ball(1.0)]);
+        assert!(scene.find_overlaps(&camera()).is_empty());
+    }
+
+    #[test]
+    fn test_finds_coincident_floors() {
+        let mut scene = Scene::new(vec![ball(1.0)]);
+        scene.add_named("floor", floor(0.0), DEFAULT_LAYER);
+        scene.add_named("ground", floor(0.0), DEFAULT_LAYER);
+        let overlaps = scene.find_overlaps(&camera());
+        assert_eq!(overlaps.len(), 1);
+        let overlap = overlaps[0];
+        assert_eq!((overlap.first, overlap.second), (1, 2));
+        assert!(matches!(
+            overlap.kind,
+            OverlapKind::CoincidentSurfaces { pixels, .. } if pixels > 100
+        ));
+        assert!(overlap
+            .describe(&scene)
+            .starts_with("\"floor\" and \"ground\""));
+
+        // Giving one of them priority settles which is seen.
+        scene.set_priority("floor", 1).unwrap();
+        assert!(scene.find_overlaps(&camera()).is_empty());
+
+        // A floor slightly below the other is hidden by it.
+        let scene = Scene::new(vec![floor(0.0), floor(-0.01)]);
+        assert!(scene.find_overlaps(&camera()).is_empty());
+    }
+
+    #[test]
+    fn test_finds_duplicated_solids() {
+        // The second ball is hidden inside the first, so only their bounds tell them apart.
+        let scene = Scene::new(vec![floor(0.0), ball(1.0), ball(0.98)]);
+        let overlaps = scene.find_overlaps(&camera());
+        assert_eq!(overlaps.len(), 1);
+        assert_eq!((overlaps[0].first, overlaps[0].second), (1, 2));
+        assert!(matches!(
+            overlaps[0].kind,
+            OverlapKind::OverlappingSolids { shared } if shared > 0.9
+        ));
+        assert_eq!(scene.describe_object(2), "object 2 (Sphere)");
+    }
+}
@@ -0,0 +1,197 @@
+//! Arbitrary output variables (AOVs), images of what the camera sees through every pixel besides
+//! its color: the albedo and normal guiding denoisers, and the depth, the object and material IDs
+//! and the motion vectors used to cut out, adjust and blur parts of a render when compositing.
+
+use crate::palette::hsv_to_rgb;
+use crate::vector3::Vector3;
+
+/// The depth stored for pixels showing the background in floating-point depth images, far beyond
+/// any object, as compositing tools expect a finite value.
+pub const BACKGROUND_DEPTH: f64 = 1e10;
+
+/// The angle in degrees between the hues of consecutive IDs, which keeps the colors of nearby IDs
+/// far apart on the color wheel.
+const GOLDEN_ANGLE: f64 = 137.507_764;
+
+/// The first surface seen through the center of every pixel, stored row by row starting from the
+/// top-left pixel.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Aovs {
+    /// The albedo of the surface, seen through mirrors and glass, or the color of the lights and
+    /// background scaled down to white.
+    pub albedo: Vec<Vector3>,
+    /// The unit shading normal of the surface, facing the camera, or zero for the background.
+    pub normal: Vec<Vector3>,
+    /// The distance from the camera to the surface, or `None` for the background.
+    pub depth: Vec<Option<f64>>,
+    /// The index of the object, or `None` for the background.
+    pub object: Vec<Option<usize>>,
+    /// The ID of the material, numbered in the order in which materials first appear in the image
+    /// row by row, or `None` for the background.
+    pub material: Vec<Option<usize>>,
+    /// How far the surface moves across the image while the shutter is open, in pixels to the
+    /// right in x and down in y, or zero for the background, for still surfaces and for
+    /// projections other than the perspective and orthographic ones.
+    pub motion: Vec<Vector3>,
+}
+
+impl Aovs {
+    /// Returns the normals as colors, each component mapped from [-1, 1] into [0, 1], so normals
+    /// survive formats without negative values. The background stays black.
+    pub fn normal_colors(&self) -> Vec<Vector3> {
+        self.normal
+            .iter()
+            .map(|&normal| {
+                if normal == Vector3::default() {
+                    normal
+                } else {
+                    (normal + Vector3::new(1.0, 1.0, 1.0)) * 0.5
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the depths as gray colors.
+    ///
+    /// # Arguments
+    ///
+    /// * `normalized` - Whether the depths are divided by the farthest one, for 8-bit images,
+    ///   with the background white. Otherwise the depths are kept as they are, with the
+    ///   background at `BACKGROUND_DEPTH`.
+    ///
+    /// # Returns
+    ///
+    /// The depth of every pixel in all three channels.
+    pub fn depth_colors(&self, normalized: bool) -> Vec<Vector3> {
+        let farthest = self.depth.iter().flatten().fold(0.0, |a: f64, &b| a.max(b));
+        self.depth
+            .iter()
+            .map(|depth| {
+                let value = match (depth, normalized) {
+                    (Some(depth), true) if farthest > 0.0 => depth / farthest,
+                    (Some(depth), false) => *depth,
+                    (Some(_), true) => 0.0,
+                    (None, true) => 1.0,
+                    (None, false) => BACKGROUND_DEPTH,
+                };
+                Vector3::new(value, value, value)
+            })
+            .collect()
+    }
+
+    /// Returns the motion vectors as colors.
+    ///
+    /// # Arguments
+    ///
+    /// * `normalized` - Whether the vectors are divided by the longest one and mapped from
+    ///   [-1, 1] into [0, 1], for 8-bit images, with still pixels gray. Otherwise the vectors are
+    ///   kept in pixels as they are.
+    ///
+    /// # Returns
+    ///
+    /// The motion of every pixel in the red and green channels.
+    pub fn motion_colors(&self, normalized: bool) -> Vec<Vector3> {
+        let longest = self.motion.iter().fold(0.0, |a: f64, b| a.max(b.length()));
+        self.motion
+            .iter()
+            .map(|&motion| match normalized {
+                true if longest > 0.0 => {
+                    let motion = motion / longest;
+                    Vector3::new(0.5 + 0.5 * motion.x, 0.5 + 0.5 * motion.y, 0.5)
+                }
+                true => Vector3::new(0.5, 0.5, 0.5),
+                false => motion,
+            })
+            .collect()
+    }
+
+    /// Returns the object IDs as colors, the background black.
+    pub fn object_colors(&self) -> Vec<Vector3> {
+        Self::id_colors(&self.object)
+    }
+
+    /// Returns the material IDs as colors, the background black.
+    pub fn material_colors(&self) -> Vec<Vector3> {
+        Self::id_colors(&self.material)
+    }
+
+    /// Turns IDs into colors, the missing ones black.
+    ///
+    /// # Arguments
+    ///
+    /// * `ids` - The ID of every pixel.
+    ///
+    /// # Returns
+    ///
+    /// The color of every pixel.
+    fn id_colors(ids: &[Option<usize>]) -> Vec<Vector3> {
+        ids.iter()
+            .map(|id| id.map_or_else(Vector3::default, id_color))
+            .collect()
+    }
+}
+
+/// Returns the color of an ID in an ID image, far from the colors of the IDs next to it, so each
+/// object or material can be picked out with a color key.
+///
+/// # Arguments
+///
+/// * `id` - The ID.
+///
+/// # Returns
+///
+/// A saturated color, never black.
+pub fn id_color(id: usize) -> Vector3 {
+    let value = if id.is_multiple_of(2) { 1.0 } else { 0.6 };
+    hsv_to_rgb(id as f64 * GOLDEN_ANGLE, 0.8, value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aov_colors() {
+        let aovs = Aovs {
+            albedo: vec![Vector3::new(0.5, 0.5, 0.5); 3],
+            normal: vec![
+                Vector3::new(0.0, 0.0, 1.0),
+                Vector3::new(-1.0, 0.0, 0.0),
+                Vector3::default(),
+            ],
+            depth: vec![Some(2.0), Some(4.0), None],
+            object: vec![Some(0), Some(1), None],
+            material: vec![Some(0), Some(0), None],
+            motion: vec![
+                Vector3::new(3.0, -4.0, 0.0),
+                Vector3::default(),
+                Vector3::default(),
+            ],
+        };
+
+        let normals = aovs.normal_colors();
+        assert_eq!(normals[0], Vector3::new(0.5, 0.5, 1.0));
+        assert_eq!(normals[1], Vector3::new(0.0, 0.5, 0.5));
+        assert_eq!(normals[2], Vector3::default());
+
+        let depths: Vec<f64> = aovs.depth_colors(true).iter().map(|d| d.x).collect();
+        assert_eq!(depths, vec![0.5, 1.0, 1.0]);
+        let depths: Vec<f64> = aovs.depth_colors(false).iter().map(|d| d.x).collect();
+        assert_eq!(depths, vec![2.0, 4.0, BACKGROUND_DEPTH]);
+
+        // Motion is kept in pixels, or scaled by the longest vector around gray.
+        assert_eq!(aovs.motion_colors(false), aovs.motion);
+        let motion = aovs.motion_colors(true);
+        assert!((motion[0] - Vector3::new(0.8, 0.1, 0.5)).length() < 1e-9);
+        assert_eq!(motion[1], Vector3::new(0.5, 0.5, 0.5));
+
+        // Distinct IDs get distinct colors, and the background stays black.
+        let objects = aovs.object_colors();
+        assert_ne!(objects[0], objects[1]);
+        assert!(objects[0].max() > 0.0 && objects[1].max() > 0.0);
+        assert_eq!(objects[2], Vector3::default());
+        let materials = aovs.material_colors();
+        assert_eq!(materials[0], materials[1]);
+        assert!((0..64).all(|id| id_color(id) != id_color(id + 1)));
+    }
+}
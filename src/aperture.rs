@@ -0,0 +1,154 @@
+//! The aperture of the camera, whose shape is the shape out of focus highlights take: a disk,
+//! a polygon formed by the blades of a diaphragm, or any shape cut out of a mask.
+
+use crate::distribution::Distribution2D;
+use crate::vector3::Vector3;
+use image::DynamicImage;
+use std::f64::consts::PI;
+
+/// Represents the shape of the opening through which the camera sees the scene, sampled to
+/// spread the origins of camera rays when the camera has a defocus blur.
+#[derive(Debug, Clone, Default)]
+pub enum Aperture {
+    /// A round opening, giving circular out of focus highlights.
+    #[default]
+    Circle,
+    /// A regular polygon inscribed in the circle, formed by the straight blades of a diaphragm.
+    Polygon {
+        /// The number of blades, at least 3.
+        blades: u32,
+        /// The angle in radians of the first corner from the right of the image, counterclockwise.
+        rotation: f64,
+    },
+    /// An opening whose transmission across the square enclosing the circle follows the
+    /// brightness of an image, such as a star or heart cut out of a card held before the lens.
+    Mask {
+        /// The transmission of the image, row by row from the bottom.
+        transmission: Distribution2D,
+        /// The half width and half height of the mask, the larger being 1.
+        extent: (f64, f64),
+    },
+}
+
+impl Aperture {
+    /// Creates a new polygonal `Aperture`, such as a hexagon for a lens with six blades.
+    ///
+    /// # Arguments
+    ///
+    /// * `blades` - The number of blades, raised to 3 if lower.
+    /// * `rotation` - The angle in degrees of the first corner from the right of the image,
+    ///   counterclockwise.
+    ///
+    /// # Returns
+    ///
+    /// A new `Aperture` instance.
+    pub fn polygon(blades: u32, rotation: f64) -> Aperture {
+        Aperture::Polygon {
+            blades: blades.max(3),
+            rotation: rotation.to_radians(),
+        }
+    }
+
+    /// Creates a new `Aperture` from a mask image, letting light through its bright parts. The
+    /// image is stretched over the square enclosing the circular aperture along its longer side.
+    ///
+    /// # Arguments
+    ///
+    /// * `image` - The mask, whose luminance is the transmission of the aperture.
+    ///
+    /// # Returns
+    ///
+    /// A new `Aperture` instance.
+    pub fn from_mask(image: &DynamicImage) -> Aperture {
+        let mask = image.to_luma32f();
+        let (width, height) = mask.dimensions();
+        let transmission: Vec<f64> = (0..height)
+            .rev()
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| mask.get_pixel(x, y).0[0] as f64)
+            .collect();
+        let longest = width.max(height) as f64;
+        Aperture::Mask {
+            transmission: Distribution2D::new(&transmission, width as usize),
+            extent: (width as f64 / longest, height as f64 / longest),
+        }
+    }
+
+    /// Picks a point of the aperture, spread evenly over its opening.
+    ///
+    /// # Returns
+    ///
+    /// The point in the plane of the aperture, with x to the right and y up, within the unit
+    /// circle for the circle and polygons and within the unit square for masks.
+    pub fn sample(&self) -> Vector3 {
+        match self {
+            Aperture::Circle => Vector3::random_in_unit_disk(),
+            Aperture::Polygon { blades, rotation } => {
+                // The polygon is a fan of equal triangles around its center, pick one and a
+                // point within it.
+                let wedge = 2.0 * PI / *blades as f64;
+                let start = rotation + wedge * fastrand::u32(0..*blades) as f64;
+                let corner = |angle: f64| Vector3::new(angle.cos(), angle.sin(), 0.0);
+                let (a, b) = (corner(start), corner(start + wedge));
+                let along = fastrand::f64().sqrt();
+                let across = fastrand::f64();
+                along * ((1.0 - across) * a + across * b)
+            }
+            Aperture::Mask {
+                transmission,
+                extent,
+            } => {
+                let (u, v, _) = transmission.sample(fastrand::f64(), fastrand::f64());
+                Vector3::new((2.0 * u - 1.0) * extent.0, (2.0 * v - 1.0) * extent.1, 0.0)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_circle_covers_disk() {
+        let points: Vec<Vector3> = (0..4000).map(|_| Aperture::Circle.sample()).collect();
+        assert!(points.iter().all(|p| p.length() <= 1.0));
+        // Half the area of the unit disk lies within a radius of 1 / sqrt(2).
+        let inner = points.iter().filter(|p| p.length() < 0.5f64.sqrt()).count();
+        assert!((inner as f64 / 4000.0 - 0.5).abs() < 0.05, "{}", inner);
+    }
+
+    #[test]
+    fn test_polygon_aperture() {
+        let hexagon = Aperture::polygon(6, 30.0);
+        // The edges of a hexagon with a corner straight up are vertical, half the radius times
+        // sqrt(3) from the center.
+        let edge = 3.0f64.sqrt() / 2.0;
+        let mut widest: f64 = 0.0;
+        for _ in 0..4000 {
+            let p = hexagon.sample();
+            assert!(p.length() <= 1.0 + 1e-12);
+            assert!(p.x.abs() <= edge + 1e-12);
+            widest = widest.max(p.x.abs());
+        }
+        assert!(widest > 0.95 * edge);
+        assert!(matches!(
+            Aperture::polygon(1, 0.0),
+            Aperture::Polygon { blades: 3, .. }
+        ));
+    }
+
+    #[test]
+    fn test_mask_aperture() {
+        // A wide mask letting light through its top right quarter only.
+        let mut image = image::GrayImage::new(4, 2);
+        image.put_pixel(2, 0, image::Luma([255]));
+        image.put_pixel(3, 0, image::Luma([255]));
+        let aperture = Aperture::from_mask(&DynamicImage::ImageLuma8(image));
+        for _ in 0..1000 {
+            let p = aperture.sample();
+            assert!((0.0..=1.0).contains(&p.x), "{:?}", p);
+            assert!((0.0..=0.5).contains(&p.y), "{:?}", p);
+        }
+    }
+}
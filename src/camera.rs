@@ -1,15 +1,271 @@
-#![allow(dead_code)]
 #![allow(clippy::too_many_arguments)]
 
-use crate::hit::Hittable;
+use crate::accumulation::{Accumulation, SampleSums};
+use crate::aov::Aovs;
+use crate::aperture::Aperture;
+use crate::deep::{merge_fragments, DeepImage, DeepSample, Fragment};
+use crate::denoise::{denoise, Features};
+use crate::environment::Environment;
+use crate::headless::RenderReport;
+use crate::hit::HitRecord;
+use crate::material::{Material, ScatterType};
+use crate::output::{self, OutputTransform, ResponseCurves};
+use crate::parameter_sweep::SweepParameter;
+use crate::pdf::{MixturePdf, Pdf};
+use crate::preview::PreviewBuffer;
+use crate::profiling::hot_span;
 use crate::ray::Ray;
-use crate::utils::linear_to_gamma;
+use crate::scene::{LightPdf, Scene};
+use crate::shutter::Shutter;
+use crate::statistics::PathLengthHistogram;
+use crate::texture::ShadingContext;
 use crate::vector3::Vector3;
 use rayon::prelude::*;
-use std::cmp::Ordering;
+use std::f64::consts::PI;
+use std::fmt;
+use std::fs;
+use std::ops::Range;
+use std::path::Path;
 use std::sync::atomic::AtomicUsize;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
+/// The size of the square tiles in which the image is rendered, in pixels.
+const TILE_SIZE: u32 = 16;
+
+/// The largest difference in any channel between the colors of pixels considered the same when
+/// checking whether the first pass of a render came out black or uniform.
+const BLANK_TOLERANCE: f64 = 1e-4;
+
+/// The largest number of pixels along each side of the image whose rays are traced to find out
+/// whether the camera sees any object, when a render comes out blank.
+const BLANK_PROBE_SIZE: u32 = 32;
+
+/// The factor by which a pixel must outshine the brightest of its neighbors to be taken for a
+/// firefly by the despeckle filter.
+const FIREFLY_RATIO: f64 = 4.0;
+
+/// The largest number of mirror reflections and refractions followed to find the surface whose
+/// albedo guides the denoiser.
+const FEATURE_SPECULAR_DEPTH: u32 = 4;
+
+/// The largest number of surfaces a ray sent towards the lights is followed through to find the
+/// light behind objects casting lightened shadows.
+const SHADOW_STEPS: u32 = 16;
+
+/// The fractions of the resolution, from the coarsest, at which progressive previews show the
+/// whole image before its tiles.
+const PROGRESSIVE_SCALES: [u32; 2] = [8, 4];
+
+/// Represents a rectangular block of pixels rendered as a single task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Tile {
+    /// The left column of the tile.
+    x0: u32,
+    /// The top row of the tile.
+    y0: u32,
+    /// The column past the right edge of the tile.
+    x1: u32,
+    /// The row past the bottom edge of the tile.
+    y1: u32,
+}
+
+impl Tile {
+    /// Returns the number of pixels in the tile.
+    fn pixel_count(&self) -> usize {
+        ((self.x1 - self.x0) * (self.y1 - self.y0)) as usize
+    }
+}
+
+/// Holds what the samples traced through a tile gave.
+struct RenderedTile {
+    /// The sums of the samples of every pixel of the tile, stored row by row.
+    sums: Vec<SampleSums>,
+    /// The lengths of the paths traced through the tile.
+    paths: PathLengthHistogram,
+    /// The time it took to trace the samples.
+    elapsed: Duration,
+}
+
+/// Holds the images produced by a render before they are written to files, each stored row by
+/// row starting from the top-left pixel.
+#[derive(Debug, Clone)]
+pub struct RenderPasses {
+    /// The averaged linear color of every pixel.
+    pub color: Vec<Vector3>,
+    /// The standard error of the color of every pixel, estimated from the spread of its samples.
+    pub error: Vec<Vector3>,
+    /// The average number of bounces of the paths traced through every pixel.
+    pub bounces: Vec<f64>,
+    /// The fraction of the samples of every pixel whose camera ray hit a visible object, used as
+    /// the alpha channel of the image.
+    pub coverage: Vec<f64>,
+    /// The lengths of all the paths traced.
+    pub paths: PathLengthHistogram,
+    /// The sums of the samples of every pixel, before they are averaged.
+    pub accumulation: Accumulation,
+}
+
+/// Summarizes how long the tiles of a render took, to find out how unevenly the work was spread.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TileStatistics {
+    /// The number of tiles.
+    pub count: usize,
+    /// The time taken by the fastest tile.
+    pub fastest: Duration,
+    /// The median time taken by a tile.
+    pub median: Duration,
+    /// The time taken by the slowest tile.
+    pub slowest: Duration,
+    /// The total time spent on all tiles, summed over every thread.
+    pub total: Duration,
+}
+
+impl TileStatistics {
+    /// Computes the statistics of a list of tile times.
+    ///
+    /// # Arguments
+    ///
+    /// * `times` - The time taken by every tile.
+    ///
+    /// # Returns
+    ///
+    /// A new `TileStatistics` instance.
+    pub fn new(times: &[Duration]) -> TileStatistics {
+        let mut sorted = times.to_vec();
+        sorted.sort();
+        TileStatistics {
+            count: sorted.len(),
+            fastest: sorted.first().copied().unwrap_or_default(),
+            median: sorted.get(sorted.len() / 2).copied().unwrap_or_default(),
+            slowest: sorted.last().copied().unwrap_or_default(),
+            total: sorted.iter().sum(),
+        }
+    }
+
+    /// Prints the statistics.
+    pub fn print(&self) {
+        println!(
+            "Tiles: {}, fastest {} ms, median {} ms, slowest {} ms, total {} ms",
+            self.count,
+            self.fastest.as_millis(),
+            self.median.as_millis(),
+            self.slowest.as_millis(),
+            self.total.as_millis()
+        );
+    }
+}
+
+/// How the preview window shows a render in progress.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PreviewStrategy {
+    /// Tiles appear as they finish, first with one sample per pixel and then with all of them.
+    #[default]
+    Tiles,
+    /// The whole image appears at an eighth and then a quarter of its resolution, upscaled,
+    /// before the tiles refine it, so the framing can be judged at once.
+    Progressive,
+}
+
+/// The mapping from the pixels of the image to the directions of camera rays.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Projection {
+    /// A pinhole or thin lens camera with the vertical field of view given to `Camera::new`.
+    #[default]
+    Perspective,
+    /// The whole sphere of directions, with longitude along the width of the image and latitude
+    /// along its height, centered on the viewing direction.
+    Equirectangular,
+    /// A stereographic projection of the sphere of directions around the viewing direction,
+    /// which gives the "little planet" look when the camera looks down at the ground with a very
+    /// wide field of view.
+    Stereographic {
+        /// The angle in degrees between the directions seen at the left and right edges of the
+        /// image, below 360.
+        fov: f64,
+    },
+    /// An equidistant fisheye lens, where the angle between a ray and the viewing direction grows
+    /// in proportion to the distance of its pixel from the center of the image.
+    Fisheye {
+        /// The angle in degrees between the directions seen at the left and right edges of the
+        /// image, up to 360.
+        fov: f64,
+    },
+    /// Parallel rays along the viewing direction, starting from a plane through the center of the
+    /// camera, so objects keep their size however far they are, as in technical drawings.
+    Orthographic {
+        /// The height of the view in scene units.
+        height: f64,
+    },
+}
+
+/// Overrides of the settings a scene chose for its camera, given on the command line.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RenderSettings {
+    /// The width of the image in pixels.
+    pub width: Option<u32>,
+    /// The number of samples per pixel.
+    pub samples: Option<u32>,
+    /// The maximum depth for ray tracing.
+    pub max_depth: Option<u32>,
+    /// The path of the image file.
+    pub output: Option<String>,
+    /// The path of the deep image file written next to the image, if any.
+    pub deep: Option<String>,
+    /// Whether the standard error image is written next to the image.
+    pub error: bool,
+    /// Whether the image of the average number of bounces is written next to the image.
+    pub bounces: bool,
+    /// Whether the albedo, normal, depth and ID images are written next to the image.
+    pub aov: bool,
+    /// Whether the image is written with an alpha channel holding the coverage of its pixels.
+    pub alpha: bool,
+    /// Whether the render is shown in a window while it progresses.
+    pub preview: bool,
+    /// Whether the preview shows coarse images of the whole render before its tiles.
+    pub progressive: bool,
+    /// Whether the scene is checked for overlapping objects before rendering.
+    pub check: bool,
+    /// The largest brightness of a single sample, if samples are clamped.
+    pub max_radiance: Option<f64>,
+    /// Whether isolated bright pixels are removed after rendering.
+    pub despeckle: bool,
+    /// Whether the image is denoised after rendering.
+    pub denoise: bool,
+    /// The response curves finishing the image, such as those of a film stock, if any.
+    pub film: Option<ResponseCurves>,
+    /// The seed making the render reproducible, if any.
+    pub seed: Option<u64>,
+    /// The range of the indices of the samples traced, replacing the number of samples, if any.
+    pub sample_range: Option<Range<u32>>,
+    /// The path of the accumulation file written next to the image, if any.
+    pub accumulation: Option<String>,
+    /// Whether a report of the render is written next to the image.
+    pub report: bool,
+    /// The value of a scene parameter set by a parameter sweep, if any.
+    pub parameter: Option<(SweepParameter, f64)>,
+}
+
+impl RenderSettings {
+    /// Returns the value of a scene parameter, for scenes built for parameter sweeps.
+    ///
+    /// # Arguments
+    ///
+    /// * `parameter` - The parameter.
+    /// * `default` - The value the scene uses when the parameter is not swept.
+    ///
+    /// # Returns
+    ///
+    /// The value set by the sweep, or the default.
+    pub fn parameter(&self, parameter: SweepParameter, default: f64) -> f64 {
+        match self.parameter {
+            Some((swept, value)) if swept == parameter => value,
+            _ => default,
+        }
+    }
+}
+
+#[derive(Clone)]
 pub struct Camera {
     /// The aspect ratio of the camera.
     aspect_ratio: f64,
@@ -19,8 +275,8 @@ pub struct Camera {
     samples_per_pixel: u32,
     /// The maximum depth for ray tracing.
     max_depth: u32,
-    /// The background function that returns a color for a given direction.
-    background: fn(Vector3) -> Vector3,
+    /// The environment giving the color of rays that escape the scene.
+    background: Arc<dyn Environment>,
 
     /// The center of the camera.
     camera_center: Vector3,
@@ -32,12 +288,69 @@ pub struct Camera {
     pixel_delta_v: Vector3,
     /// The location of the top-left pixel.
     pixel00_loc: Vector3,
+    /// The unit vector pointing to the right of the camera.
+    u: Vector3,
+    /// The unit vector pointing up from the camera.
+    v: Vector3,
+    /// The unit vector pointing backwards from the camera, opposite to its viewing direction.
+    w: Vector3,
+    /// The mapping from pixels to ray directions.
+    projection: Projection,
     /// The angle of defocus
     defocus_angle: f64,
     /// The u component of the defocus disk.
     defocus_disk_u: Vector3,
     /// The v component of the defocus disk.
     defocus_disk_v: Vector3,
+    /// The shape of the aperture, scaled to the defocus disk.
+    aperture: Aperture,
+    /// The scene time at which rays are traced, or at which the shutter opens.
+    time: f64,
+    /// The shutter spreading the rays of a pixel over an interval of time, if any.
+    shutter: Option<Shutter>,
+    /// The depth in front of the camera below which objects are not seen by camera rays.
+    near: f64,
+    /// The depth in front of the camera beyond which objects are not seen by camera rays.
+    far: f64,
+    /// Whether rays reflected by perfect mirrors look up the background directly.
+    environment_reflections: bool,
+    /// The seed of the random numbers used for every pixel, or `None` for unseeded renders.
+    seed: Option<u64>,
+    /// The transform turning linear colors into the bytes of the output image.
+    output: OutputTransform,
+    /// The path of the image file written by `render`.
+    output_path: String,
+    /// The path of the deep image file written by `render`, if any.
+    deep_path: Option<String>,
+    /// Whether every image is written along with an estimate of its standard error.
+    error_output: bool,
+    /// Whether every image is written along with the average number of bounces of its pixels.
+    bounces_output: bool,
+    /// Whether every image is written along with the albedo, normal, depth and ID images.
+    aov_output: bool,
+    /// Whether images are written with an alpha channel holding the coverage of their pixels.
+    alpha_output: bool,
+    /// The path of the accumulation file of the sums of the samples written by `render`, if any.
+    accumulation_path: Option<String>,
+    /// Whether every image is written along with a report of its render.
+    report_output: bool,
+    /// Whether the progress and statistics of renders are kept off the standard output.
+    quiet: bool,
+    /// The index of the first sample traced through every pixel, which seeds its random numbers.
+    first_sample: u32,
+    /// Whether renders are shown in a window while they progress.
+    preview: bool,
+    /// How the preview window shows renders in progress.
+    preview_strategy: PreviewStrategy,
+    /// Whether scenes are checked for overlapping objects before rendering.
+    check_overlaps: bool,
+    /// The largest brightness of a single sample, above which samples are scaled down, or
+    /// `None` if samples are kept as traced.
+    max_radiance: Option<f64>,
+    /// Whether isolated pixels much brighter than all their neighbors are removed after a render.
+    despeckle: bool,
+    /// Whether renders are denoised along the albedo and normals of the surfaces they show.
+    denoise: bool,
 }
 
 impl Camera {
@@ -114,156 +427,3039 @@ impl Camera {
             samples_per_pixel,
             image_height,
             max_depth,
-            background,
+            background: Arc::new(background),
 
             camera_center,
             pixel_delta_u,
             pixel_delta_v,
             pixel00_loc,
+            u,
+            v,
+            w,
+            projection: Projection::default(),
             defocus_angle,
             defocus_disk_u,
             defocus_disk_v,
+            aperture: Aperture::default(),
+            time: 0.0,
+            shutter: None,
+            near: 0.0,
+            far: f64::INFINITY,
+            environment_reflections: false,
+            seed: None,
+            output: OutputTransform::default(),
+            output_path: "output.png".to_string(),
+            deep_path: None,
+            error_output: false,
+            bounces_output: false,
+            aov_output: false,
+            alpha_output: false,
+            accumulation_path: None,
+            report_output: false,
+            quiet: false,
+            first_sample: 0,
+            preview: false,
+            preview_strategy: PreviewStrategy::default(),
+            check_overlaps: false,
+            max_radiance: None,
+            despeckle: false,
+            denoise: false,
         }
     }
 
-    /// Returns the center of the pixel at the given coordinates.
+    /// Changes the width of the image, keeping the field of view and the aspect ratio.
     ///
     /// # Arguments
     ///
-    /// * `x` - The x-coordinate of the pixel.
-    /// * `y` - The y-coordinate of the pixel.
+    /// * `image_width` - The width of the image in pixels.
+    pub fn set_image_width(&mut self, image_width: u32) {
+        let image_width = image_width.max(1);
+        let image_height = ((image_width as f64 / self.aspect_ratio) as u32).max(1);
+
+        let viewport_u = self.pixel_delta_u * self.image_width as f64;
+        let viewport_v = self.pixel_delta_v * self.image_height as f64;
+        let viewport_center = self.pixel00_loc - 0.5 * (self.pixel_delta_u + self.pixel_delta_v)
+            + viewport_u / 2.0
+            + viewport_v / 2.0;
+
+        // The viewport keeps its height, and its width follows the new pixel grid.
+        self.pixel_delta_u = viewport_u.normalize() * (viewport_v.length() / image_height as f64);
+        self.pixel_delta_v = viewport_v / image_height as f64;
+        let viewport_upper_left =
+            viewport_center - self.pixel_delta_u * image_width as f64 / 2.0 - viewport_v / 2.0;
+        self.pixel00_loc = viewport_upper_left + 0.5 * (self.pixel_delta_u + self.pixel_delta_v);
+        self.image_width = image_width;
+        self.image_height = image_height;
+    }
+
+    /// Sets the mapping from pixels to ray directions. Rays of the spherical and fisheye
+    /// projections all start at the center of the camera and the parallel rays of the
+    /// orthographic projection start across a plane through it, without defocus blur.
     ///
-    /// # Returns
+    /// # Arguments
     ///
-    /// The center of the pixel as a `Vector3`.
-    fn get_pixel_center(&self, x: u32, y: u32) -> Vector3 {
-        self.pixel00_loc + (x * self.pixel_delta_u) + (y * self.pixel_delta_v)
+    /// * `projection` - The projection.
+    pub fn set_projection(&mut self, projection: Projection) {
+        self.projection = projection;
     }
 
-    /// Returns a ray that passes through the pixel at the given coordinates.
-    /// The ray is randomly offset within the pixel to provide Anti-Aliasing.
+    /// Sets the number of samples per pixel.
     ///
     /// # Arguments
     ///
-    /// * `x` - The x-coordinate of the pixel.
-    /// * `y` - The y-coordinate of the pixel.
+    /// * `samples_per_pixel` - The number of samples per pixel.
+    pub fn set_samples_per_pixel(&mut self, samples_per_pixel: u32) {
+        self.samples_per_pixel = samples_per_pixel;
+    }
+
+    /// Sets the maximum depth for ray tracing.
     ///
-    /// # Returns
+    /// # Arguments
     ///
-    /// A `Ray` that passes through the pixel.
-    fn get_ray(&self, x: u32, y: u32) -> Ray {
-        let offset_x = fastrand::f64() - 0.5;
-        let offset_y = fastrand::f64() - 0.5;
+    /// * `max_depth` - The maximum depth for ray tracing.
+    pub fn set_max_depth(&mut self, max_depth: u32) {
+        self.max_depth = max_depth;
+    }
 
-        let pixel_sample = self.pixel00_loc
-            + ((x as f64 + offset_x) * self.pixel_delta_u)
-            + ((y as f64 + offset_y) * self.pixel_delta_v);
+    /// Sets the path of the image file written by `render`. Renders writing several images add a
+    /// suffix to the file name.
+    ///
+    /// # Arguments
+    ///
+    /// * `output_path` - The path of the image file.
+    pub fn set_output_path(&mut self, output_path: &str) {
+        self.output_path = output_path.to_string();
+    }
 
-        let ray_origin = if self.defocus_angle <= 0.0 {
-            self.camera_center
-        } else {
-            self.defocus_disk_sample()
-        };
+    /// Makes `render` also write a deep image of the scene, traced in a separate pass.
+    ///
+    /// # Arguments
+    ///
+    /// * `deep_path` - The path of the deep OpenEXR file.
+    pub fn set_deep_output(&mut self, deep_path: &str) {
+        self.deep_path = Some(deep_path.to_string());
+    }
 
-        let ray_direction = pixel_sample - ray_origin;
+    /// Makes every image be written along with an image of the standard error of its pixels, saved
+    /// next to it with `_error` added to its name, so converged regions can be told apart from
+    /// noisy ones.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether the standard error images are written.
+    pub fn set_error_output(&mut self, enabled: bool) {
+        self.error_output = enabled;
+    }
 
-        Ray::new(ray_origin, ray_direction)
+    /// Makes every image be written along with a report of its render, such as its number of
+    /// samples and how long it took, saved next to it with the `.report` extension and named
+    /// after the image. Contact sheets label their thumbnails with these reports.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether the reports are written.
+    pub fn set_report_output(&mut self, enabled: bool) {
+        self.report_output = enabled;
     }
 
-    /// Returns a random sample point on the defocus disk.
+    /// Keeps the progress and statistics of renders and the paths of the saved images off the
+    /// standard output, so callers can print their own report there. Warnings and errors are
+    /// still written to the standard error.
     ///
-    /// # Returns
+    /// # Arguments
     ///
-    /// A random sample point on the defocus disk as a `Vector3`.
-    fn defocus_disk_sample(&self) -> Vector3 {
-        let p = Vector3::random_in_unit_disk();
-        self.camera_center + (p.x * self.defocus_disk_u) + (p.y * self.defocus_disk_v)
+    /// * `quiet` - Whether the standard output is left to the caller.
+    pub fn set_quiet(&mut self, quiet: bool) {
+        self.quiet = quiet;
     }
 
-    /// Computes the color of a ray by tracing it through the scene.
-    /// Main ray tracing function. Recursively traces rays through the scene.
+    /// Makes every image be written along with an image of the average number of bounces of the
+    /// paths through its pixels, divided by the maximum depth and saved next to it with
+    /// `_bounces` added to its name, to show where the depth limit cuts paths off.
     ///
     /// # Arguments
     ///
-    /// * `ray` - The ray to trace.
-    /// * `hittable` - The list of objects in the scene.
-    /// * `depth` - The current depth of the ray.
+    /// * `enabled` - Whether the bounces images are written.
+    pub fn set_bounces_output(&mut self, enabled: bool) {
+        self.bounces_output = enabled;
+    }
+
+    /// Makes every image be written along with images of the first surface seen through its
+    /// pixels, for denoising and compositing in other tools: its albedo, normal, distance, and
+    /// object and material IDs, saved next to it with `_albedo`, `_normal`, `_depth`, `_object`
+    /// and `_material` added to its name.
     ///
-    /// # Returns
+    /// # Arguments
     ///
-    /// The color of the ray as a `Vector3`.
-    fn ray_color(&self, ray: &Ray, hittable: &[Box<dyn Hittable>], depth: u32) -> Vector3 {
-        if depth == 0 {
-            return Vector3::new(0.0, 0.0, 0.0);
-        }
+    /// * `enabled` - Whether the auxiliary images are written.
+    pub fn set_aov_output(&mut self, enabled: bool) {
+        self.aov_output = enabled;
+    }
 
-        let min_record = hittable
-            .iter()
-            .filter_map(|hittable| hittable.hit(ray, (0.001, f64::INFINITY)))
-            .min_by(|r1, r2| r1.t.partial_cmp(&r2.t).unwrap_or(Ordering::Equal));
+    /// Makes images be written with an alpha channel holding the fraction of the samples of every
+    /// pixel that hit a visible object, so renders can be composited over other images. The
+    /// background is still rendered into the colors. Radiance HDR files have no alpha channel and
+    /// are written without it.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether images are written with an alpha channel.
+    pub fn set_alpha_output(&mut self, enabled: bool) {
+        self.alpha_output = enabled;
+    }
 
-        if let Some(record) = min_record {
-            let emission_color = record.material.emitted(record.u, record.v, &record.poz);
+    /// Makes renders write the sums of the samples of every pixel to an accumulation file, which
+    /// can be merged with the ones of other ranges of samples of the same image.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the accumulation file.
+    pub fn set_accumulation_output(&mut self, path: &str) {
+        self.accumulation_path = Some(path.to_string());
+    }
 
-            if let Some((scattered, attenuation)) = record.material.scatter(ray, &record) {
-                let scatter_color = attenuation * self.ray_color(&scattered, hittable, depth - 1);
-                scatter_color + emission_color
-            } else if self.max_depth == depth {
-                emission_color / emission_color.max()
-            } else {
-                emission_color
-            }
-        } else {
-            (self.background)(ray.direction)
+    /// Traces only a range of the samples of every pixel, so the samples of an image can be split
+    /// between machines. With the same seed, disjoint ranges give independent samples whose
+    /// accumulation files add up to the whole image, and the same range always gives the same
+    /// samples.
+    ///
+    /// # Arguments
+    ///
+    /// * `range` - The range of the indices of the samples, which must not be empty.
+    pub fn set_sample_range(&mut self, range: Range<u32>) {
+        self.first_sample = range.start;
+        self.samples_per_pixel = range.end.saturating_sub(range.start).max(1);
+    }
+
+    /// Makes renders open a window showing the image as its tiles finish, first with one sample
+    /// per pixel and then with all of them, so a badly aimed camera is noticed early. The window
+    /// needs the `preview` feature; without it, renders only print a warning.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether renders are previewed.
+    pub fn set_preview(&mut self, enabled: bool) {
+        self.preview = enabled;
+    }
+
+    /// Sets how the preview window shows renders in progress.
+    ///
+    /// # Arguments
+    ///
+    /// * `strategy` - Whether tiles are shown as they finish, or coarse images of the whole
+    ///   render first.
+    pub fn set_preview_strategy(&mut self, strategy: PreviewStrategy) {
+        self.preview_strategy = strategy;
+    }
+
+    /// Makes renders check the scene for overlapping objects first, warning about coincident
+    /// surfaces and duplicated solids by name so they can be fixed before a long render.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether scenes are checked.
+    pub fn set_overlap_check(&mut self, enabled: bool) {
+        self.check_overlaps = enabled;
+    }
+
+    /// Clamps the brightness of every sample, so the rare paths finding a small bright light
+    /// through a diffuse bounce do not leave white speckles, known as fireflies. Clamping
+    /// darkens bright highlights and caustics, so the limit is best kept well above the
+    /// brightness of the lit surfaces.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_radiance` - The largest value of any channel of a sample, or `None` to keep
+    ///   samples as traced.
+    pub fn set_max_radiance(&mut self, max_radiance: Option<f64>) {
+        self.max_radiance = max_radiance;
+    }
+
+    /// Makes renders replace pixels more than four times as bright as the brightest of their
+    /// neighbors by the average of the neighbors, removing the fireflies clamping leaves behind
+    /// without darkening the rest of the image. Lights seen as single pixels are removed too.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether renders are despeckled.
+    pub fn set_despeckle(&mut self, enabled: bool) {
+        self.despeckle = enabled;
+    }
+
+    /// Makes renders average every pixel with the neighbors showing the same surface, guided by
+    /// the albedo and normal of the first surface seen through each pixel, so images with few
+    /// samples come out smooth while keeping their edges and textures.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether renders are denoised.
+    pub fn set_denoise(&mut self, enabled: bool) {
+        self.denoise = enabled;
+    }
+
+    /// Scales a sample down to the largest brightness allowed, keeping its hue.
+    ///
+    /// # Arguments
+    ///
+    /// * `color` - The sampled color.
+    ///
+    /// # Returns
+    ///
+    /// The clamped color.
+    fn clamp_sample(&self, color: Vector3) -> Vector3 {
+        match self.max_radiance {
+            Some(limit) if color.max() > limit => color * (limit / color.max()),
+            _ => color,
         }
     }
 
-    /// Renders the scene and saves the image to a file.
+    /// Applies the settings given on the command line over the ones chosen by the scene.
     ///
     /// # Arguments
     ///
-    /// * `hittable` - The list of objects in the scene.
-    pub fn render(&self, hittable: Vec<Box<dyn Hittable>>) {
-        let progress = Arc::new(AtomicUsize::new(10));
-        let total_pixels = (self.image_width * self.image_height) as usize;
+    /// * `settings` - The settings to apply. Settings left to `None` are not changed.
+    pub fn apply_settings(&mut self, settings: &RenderSettings) {
+        if let Some(width) = settings.width {
+            self.set_image_width(width);
+        }
+        if let Some(samples) = settings.samples {
+            self.set_samples_per_pixel(samples);
+        }
+        if let Some(max_depth) = settings.max_depth {
+            self.set_max_depth(max_depth);
+        }
+        if let Some(output) = &settings.output {
+            self.set_output_path(output);
+        }
+        if let Some(deep) = &settings.deep {
+            self.set_deep_output(deep);
+        }
+        if settings.error {
+            self.set_error_output(true);
+        }
+        if settings.bounces {
+            self.set_bounces_output(true);
+        }
+        if settings.aov {
+            self.set_aov_output(true);
+        }
+        if settings.alpha {
+            self.set_alpha_output(true);
+        }
+        if settings.preview {
+            self.set_preview(true);
+        }
+        if settings.progressive {
+            self.set_preview_strategy(PreviewStrategy::Progressive);
+        }
+        if settings.check {
+            self.set_overlap_check(true);
+        }
+        if settings.max_radiance.is_some() {
+            self.set_max_radiance(settings.max_radiance);
+        }
+        if settings.despeckle {
+            self.set_despeckle(true);
+        }
+        if settings.denoise {
+            self.set_denoise(true);
+        }
+        if let Some(curves) = &settings.film {
+            self.set_output_transform(self.output.clone().with_curves(curves.clone()));
+        }
+        if let Some(seed) = settings.seed {
+            self.set_seed(seed);
+        }
+        if let Some(range) = &settings.sample_range {
+            self.set_sample_range(range.clone());
+        }
+        if let Some(accumulation) = &settings.accumulation {
+            self.set_accumulation_output(accumulation);
+        }
+        if settings.report {
+            self.set_report_output(true);
+        }
+    }
 
-        println!("Rendering...");
+    /// Returns the path of an image file written by a render producing several images, made of
+    /// the output path with a suffix added to the file name.
+    ///
+    /// # Arguments
+    ///
+    /// * `suffix` - The suffix, such as a layer name or a frame number.
+    ///
+    /// # Returns
+    ///
+    /// The path of the image file, such as `output_background.png`.
+    pub fn output_path_with_suffix(&self, suffix: &str) -> String {
+        Self::path_with_suffix(&self.output_path, suffix)
+    }
 
-        let mut imgbuf = image::ImageBuffer::new(self.image_width, self.image_height);
-        imgbuf
-            .enumerate_pixels_mut()
-            .par_bridge()
-            .for_each(|(x, y, pixel)| {
-                let mut initial_color = Vector3::default();
+    /// Adds a suffix to the file name of a path, before its extension.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path.
+    /// * `suffix` - The suffix.
+    ///
+    /// # Returns
+    ///
+    /// The path with the suffix, such as `output_background.png` for `output.png`.
+    fn path_with_suffix(path: &str, suffix: &str) -> String {
+        let path = Path::new(path);
+        let stem = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy())
+            .unwrap_or_default();
+        let name = match path.extension() {
+            Some(extension) => format!("{}_{}.{}", stem, suffix, extension.to_string_lossy()),
+            None => format!("{}_{}", stem, suffix),
+        };
+        path.with_file_name(name).to_string_lossy().into_owned()
+    }
 
-                for _s in 0..self.samples_per_pixel {
-                    let ray = self.get_ray(x, y);
-                    let color = self.ray_color(&ray, &hittable, self.max_depth);
-                    initial_color += color;
-                }
-                initial_color = initial_color / self.samples_per_pixel as f64;
+    /// Sets the scene time at which rays are traced, used to render frames of animated scenes.
+    ///
+    /// # Arguments
+    ///
+    /// * `time` - The scene time.
+    pub fn set_time(&mut self, time: f64) {
+        self.time = time;
+    }
 
-                // Apply a linear to gamma transform for gamma 2, clamping and conversion to bytes
-                initial_color = Vector3::new(
-                    255.0 * linear_to_gamma(initial_color.x).clamp(0.0, 1.0),
-                    255.0 * linear_to_gamma(initial_color.y).clamp(0.0, 1.0),
-                    255.0 * linear_to_gamma(initial_color.z).clamp(0.0, 1.0),
-                );
+    /// Sets the environment surrounding the scene, such as an HDR environment map, replacing the
+    /// background function the camera was created with. Rays escaping the scene take its color,
+    /// so it lights the scene as well as filling the background.
+    ///
+    /// # Arguments
+    ///
+    /// * `environment` - The environment.
+    pub fn set_environment(&mut self, environment: Arc<dyn Environment>) {
+        self.background = environment;
+    }
 
-                *pixel = initial_color.to_rgb();
+    /// Sets the shape of the aperture of the camera, which out of focus highlights take when the
+    /// camera has a defocus blur. The aperture spans the defocus disk.
+    ///
+    /// # Arguments
+    ///
+    /// * `aperture` - The shape of the aperture.
+    pub fn set_aperture(&mut self, aperture: Aperture) {
+        self.aperture = aperture;
+    }
 
-                let current_progress = progress.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    /// Sets the shutter of the camera. The rays of a pixel are then traced at times spread after
+    /// the scene time given to `set_time`, blurring time-varying scenes.
+    ///
+    /// # Arguments
+    ///
+    /// * `shutter` - The shutter.
+    pub fn set_shutter(&mut self, shutter: Shutter) {
+        self.shutter = Some(shutter);
+    }
 
-                if current_progress % (total_pixels / 10) == 0 {
-                    println!("Progress: {}%", (current_progress * 100) / total_pixels);
-                }
-            });
+    /// Sets the near and far clipping planes of the camera. Camera rays only see the objects
+    /// between them, so walls in front of the subject can be cut away without editing the
+    /// geometry. Light bouncing off the visible objects still reaches the clipped ones.
+    ///
+    /// # Arguments
+    ///
+    /// * `near` - The depth in front of the camera below which objects are not seen.
+    /// * `far` - The depth in front of the camera beyond which objects are not seen, or
+    ///   `f64::INFINITY` for no far plane.
+    pub fn set_clipping(&mut self, near: f64, far: f64) {
+        self.near = near.max(0.0);
+        self.far = far.max(self.near);
+    }
 
-        let output_name = "output.png";
-        if let Err(e) = imgbuf.save(output_name) {
-            eprintln!("Failed to save image: {}", e);
+    /// Returns the range of distances along a camera ray that lies between the clipping planes.
+    /// With the perspective and orthographic projections the planes are perpendicular to the
+    /// viewing direction; the other projections have no viewing plane, so they clip at distances
+    /// from the camera.
+    ///
+    /// # Arguments
+    ///
+    /// * `ray` - A ray generated by the camera.
+    ///
+    /// # Returns
+    ///
+    /// The range of distances along the ray to consider for intersections.
+    pub fn clip_interval(&self, ray: &Ray) -> (f64, f64) {
+        // The depth covered per unit of distance along the ray.
+        let forward = if self.projection == Projection::Perspective {
+            ray.direction.dot(&-self.w).max(1e-9)
         } else {
-            println!("Successfully saved image to {}", output_name);
+            1.0
+        };
+        (self.near / forward, self.far / forward)
+    }
+
+    /// Moves the camera back along its viewing direction, or forward, until the objects of a scene
+    /// fill the view, keeping the direction, the field of view and the defocus angle, so imported
+    /// models of any size and position can be rendered without guessing where to put the camera.
+    /// The focus plane moves to the center of the scene.
+    ///
+    /// The corners of the box enclosing the scene are fitted in the perspective viewport, so
+    /// scenes with objects without bounds, such as infinite planes, are framed around their
+    /// bounded objects.
+    ///
+    /// # Arguments
+    ///
+    /// * `scene` - The scene to frame.
+    /// * `margin` - The fraction of the width and height of the image left empty on each side of
+    ///   the scene, from 0 to 0.45.
+    pub fn frame_scene(&mut self, scene: &Scene, margin: f64) {
+        let Some(bounds) = scene.bounding_box() else {
+            return;
+        };
+        let center = (bounds.min + bounds.max) / 2.0;
+        let focus_dist = (self.pixel00_loc - self.camera_center).dot(&-self.w);
+        let fill = 1.0 - 2.0 * margin.clamp(0.0, 0.45);
+        let half_width = self.pixel_delta_u.length() * self.image_width as f64 / 2.0;
+        let half_height = self.pixel_delta_v.length() * self.image_height as f64 / 2.0;
+        let tan_x = half_width / focus_dist * fill;
+        let tan_y = half_height / focus_dist * fill;
+
+        // A corner at depth `d` in front of the camera is in view if its distances from the
+        // viewing axis are within `d` times the tangents of the half angles of the view.
+        let distance = bounds
+            .corners()
+            .iter()
+            .map(|corner| {
+                let offset = *corner - center;
+                let ahead = offset.dot(&-self.w);
+                let needed =
+                    (offset.dot(&self.u).abs() / tan_x).max(offset.dot(&self.v).abs() / tan_y);
+                needed - ahead
+            })
+            .fold(0.0, f64::max)
+            .max(1e-3);
+
+        let scale = distance / focus_dist;
+        self.camera_center = center + distance * self.w;
+        self.pixel_delta_u = self.pixel_delta_u * scale;
+        self.pixel_delta_v = self.pixel_delta_v * scale;
+        self.pixel00_loc = center
+            - self.pixel_delta_u * (self.image_width as f64 / 2.0 - 0.5)
+            - self.pixel_delta_v * (self.image_height as f64 / 2.0 - 0.5);
+        self.defocus_disk_u = self.defocus_disk_u * scale;
+        self.defocus_disk_v = self.defocus_disk_v * scale;
+    }
+
+    /// Picks the scene time of a ray through a pixel.
+    ///
+    /// # Arguments
+    ///
+    /// * `y` - The y-coordinate of the pixel, used by rolling shutters.
+    ///
+    /// # Returns
+    ///
+    /// The time of the ray.
+    fn sample_time(&self, y: u32) -> f64 {
+        match &self.shutter {
+            Some(shutter) => self.time + shutter.sample_time(self.shutter_row(y), fastrand::f64()),
+            None => self.time,
         }
     }
+
+    /// Returns the scene times at which the shutter opens and closes for a row of the image.
+    ///
+    /// # Arguments
+    ///
+    /// * `y` - The y-coordinate of the row, used by rolling shutters.
+    ///
+    /// # Returns
+    ///
+    /// The times of the opening and the closing, the same without a shutter.
+    fn shutter_interval(&self, y: u32) -> (f64, f64) {
+        match &self.shutter {
+            Some(shutter) => {
+                let (open, close) = shutter.interval(self.shutter_row(y));
+                (self.time + open, self.time + close)
+            }
+            None => (self.time, self.time),
+        }
+    }
+
+    /// Returns the position of a row of the image for the shutter, from 0 at the top to 1 at the
+    /// bottom.
+    ///
+    /// # Arguments
+    ///
+    /// * `y` - The y-coordinate of the row.
+    fn shutter_row(&self, y: u32) -> f64 {
+        y as f64 / (self.image_height.max(2) - 1) as f64
+    }
+
+    /// Enables or disables the environment reflection fast path for previews. When enabled, rays
+    /// reflected by perfect mirrors look up the background directly instead of being traced
+    /// through the scene, so mirrors reflect only the environment.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether the fast path is used.
+    pub fn set_environment_reflections(&mut self, enabled: bool) {
+        self.environment_reflections = enabled;
+    }
+
+    /// Makes renders exactly reproducible. The random number generator is reseeded for every pixel
+    /// from the seed and the position of the pixel, and the samples of a pixel are summed in a
+    /// fixed order by a single thread, so the image does not depend on the number of threads or
+    /// on the order in which tiles are scheduled.
+    ///
+    /// # Arguments
+    ///
+    /// * `seed` - The seed of the render.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = Some(seed);
+    }
+
+    /// Sets the transform turning linear colors into the bytes of the output image.
+    ///
+    /// # Arguments
+    ///
+    /// * `output` - The output transform.
+    pub fn set_output_transform(&mut self, output: OutputTransform) {
+        self.output = output;
+    }
+
+    /// Derives the seed of the random numbers used by a run of samples of a pixel.
+    ///
+    /// # Arguments
+    ///
+    /// * `seed` - The seed of the render.
+    /// * `x` - The x-coordinate of the pixel.
+    /// * `y` - The y-coordinate of the pixel.
+    /// * `first_sample` - The index of the first sample of the run.
+    ///
+    /// # Returns
+    ///
+    /// The seed of the run, well mixed so that neighbouring pixels get unrelated sequences.
+    fn pixel_seed(seed: u64, x: u32, y: u32, first_sample: u32) -> u64 {
+        // SplitMix64 finalizer applied to the combined inputs.
+        let mut z = seed
+            ^ ((y as u64) << 32 | x as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+            ^ (first_sample as u64).wrapping_mul(0xD1B5_4A32_D192_ED03);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Returns the center of the pixel at the given coordinates.
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - The x-coordinate of the pixel.
+    /// * `y` - The y-coordinate of the pixel.
+    ///
+    /// # Returns
+    ///
+    /// The center of the pixel as a `Vector3`.
+    fn get_pixel_center(&self, x: u32, y: u32) -> Vector3 {
+        self.pixel00_loc + (x * self.pixel_delta_u) + (y * self.pixel_delta_v)
+    }
+
+    /// Returns a ray that passes through the pixel at the given coordinates.
+    /// The ray is randomly offset within the pixel to provide Anti-Aliasing.
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - The x-coordinate of the pixel.
+    /// * `y` - The y-coordinate of the pixel.
+    ///
+    /// # Returns
+    ///
+    /// A `Ray` that passes through the pixel.
+    fn get_ray(&self, x: u32, y: u32) -> Ray {
+        let offset_x = fastrand::f64() - 0.5;
+        let offset_y = fastrand::f64() - 0.5;
+        let (pixel_x, pixel_y) = (x as f64 + offset_x, y as f64 + offset_y);
+
+        let time = self.sample_time(y);
+
+        let mut ray = if self.projection != Projection::Perspective {
+            let direction = self.ray_direction(pixel_x, pixel_y);
+            Ray::with_time(self.ray_origin(pixel_x, pixel_y), direction, time)
+        } else {
+            let pixel_sample =
+                self.pixel00_loc + (pixel_x * self.pixel_delta_u) + (pixel_y * self.pixel_delta_v);
+
+            let ray_origin = if self.defocus_angle <= 0.0 {
+                self.camera_center
+            } else {
+                self.defocus_disk_sample()
+            };
+
+            let ray_direction = pixel_sample - ray_origin;
+            Ray::with_time(ray_origin, ray_direction, time)
+        };
+        ray.primary = true;
+        (ray.footprint, ray.spread) = self.differentials(pixel_x, pixel_y);
+        ray
+    }
+
+    /// Estimates the footprint of the ray through a point of the image from its differentials,
+    /// the rays through the points one pixel to the right and one pixel down, ignoring defocus.
+    ///
+    /// # Arguments
+    ///
+    /// * `pixel_x` - The x-coordinate of the point, in pixels from the center of the left column.
+    /// * `pixel_y` - The y-coordinate of the point, in pixels from the center of the top row.
+    ///
+    /// # Returns
+    ///
+    /// The width of the footprint at the origin of the ray, and how much it widens per unit of
+    /// distance.
+    fn differentials(&self, pixel_x: f64, pixel_y: f64) -> (f64, f64) {
+        let origin = self.ray_origin(pixel_x, pixel_y);
+        let direction = self.ray_direction(pixel_x, pixel_y);
+        [(1.0, 0.0), (0.0, 1.0)].iter().fold(
+            (0.0, 0.0),
+            |(footprint, spread): (f64, f64), (dx, dy)| {
+                let (x, y) = (pixel_x + dx, pixel_y + dy);
+                (
+                    footprint.max((self.ray_origin(x, y) - origin).length()),
+                    spread.max((self.ray_direction(x, y) - direction).length()),
+                )
+            },
+        )
+    }
+
+    /// Returns the direction of the ray from the center of the camera through a point of the image.
+    ///
+    /// # Arguments
+    ///
+    /// * `pixel_x` - The x-coordinate of the point, in pixels from the center of the left column.
+    /// * `pixel_y` - The y-coordinate of the point, in pixels from the center of the top row.
+    ///
+    /// # Returns
+    ///
+    /// The unit direction of the ray.
+    fn ray_direction(&self, pixel_x: f64, pixel_y: f64) -> Vector3 {
+        // The point relative to the center of the image, from -1 to 1 along the width.
+        let half_width = self.image_width as f64 / 2.0;
+        let a = (pixel_x + 0.5 - half_width) / half_width;
+        let b = (self.image_height as f64 / 2.0 - pixel_y - 0.5) / half_width;
+
+        // The direction in the frame of the camera, as (right, up, forward).
+        let (right, up, forward) = match self.projection {
+            Projection::Equirectangular => {
+                let longitude = a * PI;
+                let latitude = (b * PI).clamp(-PI / 2.0, PI / 2.0);
+                (
+                    latitude.cos() * longitude.sin(),
+                    latitude.sin(),
+                    latitude.cos() * longitude.cos(),
+                )
+            }
+            Projection::Stereographic { fov } => {
+                let edge_radius = 2.0 * (fov.clamp(1.0, 359.0).to_radians() / 4.0).tan();
+                let (x, y) = (a * edge_radius, b * edge_radius);
+                let theta = 2.0 * ((x * x + y * y).sqrt() / 2.0).atan();
+                let phi = y.atan2(x);
+                (
+                    theta.sin() * phi.cos(),
+                    theta.sin() * phi.sin(),
+                    theta.cos(),
+                )
+            }
+            Projection::Fisheye { fov } => {
+                let theta = ((a * a + b * b).sqrt() * fov.to_radians() / 2.0).min(PI);
+                let phi = b.atan2(a);
+                (
+                    theta.sin() * phi.cos(),
+                    theta.sin() * phi.sin(),
+                    theta.cos(),
+                )
+            }
+            Projection::Orthographic { .. } => return -self.w,
+            Projection::Perspective => {
+                let pixel = self.pixel00_loc
+                    + (pixel_x * self.pixel_delta_u)
+                    + (pixel_y * self.pixel_delta_v);
+                return (pixel - self.camera_center).normalize();
+            }
+        };
+        (right * self.u + up * self.v - forward * self.w).normalize()
+    }
+
+    /// Returns the origin of the ray through a point of the image, which is the center of the
+    /// camera for every projection but the orthographic one.
+    ///
+    /// # Arguments
+    ///
+    /// * `pixel_x` - The x-coordinate of the point, in pixels from the center of the left column.
+    /// * `pixel_y` - The y-coordinate of the point, in pixels from the center of the top row.
+    ///
+    /// # Returns
+    ///
+    /// The origin of the ray.
+    fn ray_origin(&self, pixel_x: f64, pixel_y: f64) -> Vector3 {
+        match self.projection {
+            Projection::Orthographic { height } => {
+                let scale = height / self.image_height as f64;
+                let right = (pixel_x + 0.5 - self.image_width as f64 / 2.0) * scale;
+                let up = (self.image_height as f64 / 2.0 - pixel_y - 0.5) * scale;
+                self.camera_center + right * self.u + up * self.v
+            }
+            _ => self.camera_center,
+        }
+    }
+
+    /// Finds where a point of the scene is seen in the image, the inverse of `pixel_ray`.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The point of the scene.
+    ///
+    /// # Returns
+    ///
+    /// The coordinates of the point in pixels from the center of the top-left pixel, or `None` if
+    /// the point is behind a perspective camera or the projection is neither perspective nor
+    /// orthographic.
+    fn project(&self, point: &Vector3) -> Option<(f64, f64)> {
+        match self.projection {
+            Projection::Perspective => {
+                // The line from the center of the camera to the point crosses the plane of the
+                // pixels.
+                let normal = self.pixel_delta_u.cross(&self.pixel_delta_v);
+                let direction = *point - self.camera_center;
+                let scale =
+                    (self.pixel00_loc - self.camera_center).dot(&normal) / direction.dot(&normal);
+                if !scale.is_finite() || scale <= 0.0 {
+                    return None;
+                }
+                let offset = self.camera_center + scale * direction - self.pixel00_loc;
+                Some((
+                    offset.dot(&self.pixel_delta_u) / self.pixel_delta_u.dot(&self.pixel_delta_u),
+                    offset.dot(&self.pixel_delta_v) / self.pixel_delta_v.dot(&self.pixel_delta_v),
+                ))
+            }
+            Projection::Orthographic { height } => {
+                let scale = height / self.image_height as f64;
+                let offset = *point - self.camera_center;
+                Some((
+                    offset.dot(&self.u) / scale + self.image_width as f64 / 2.0 - 0.5,
+                    self.image_height as f64 / 2.0 - 0.5 - offset.dot(&self.v) / scale,
+                ))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the ray from the center of the camera through the center of the pixel at the given
+    /// coordinates, without anti-aliasing or defocus, used to query what a pixel shows.
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - The x-coordinate of the pixel.
+    /// * `y` - The y-coordinate of the pixel.
+    ///
+    /// # Returns
+    ///
+    /// A `Ray` that passes through the center of the pixel.
+    pub fn pixel_ray(&self, x: u32, y: u32) -> Ray {
+        let direction = if self.projection != Projection::Perspective {
+            self.ray_direction(x as f64, y as f64)
+        } else {
+            self.get_pixel_center(x, y) - self.camera_center
+        };
+        let origin = self.ray_origin(x as f64, y as f64);
+        let mut ray = Ray::with_time(origin, direction, self.time);
+        ray.primary = true;
+        (ray.footprint, ray.spread) = self.differentials(x as f64, y as f64);
+        ray
+    }
+
+    /// Returns a random sample point on the defocus disk, within the shape of the aperture.
+    ///
+    /// # Returns
+    ///
+    /// A random sample point on the defocus disk as a `Vector3`.
+    fn defocus_disk_sample(&self) -> Vector3 {
+        let p = self.aperture.sample();
+        self.camera_center + (p.x * self.defocus_disk_u) + (p.y * self.defocus_disk_v)
+    }
+
+    /// Computes the color of a ray leaving the camera, like `ray_color`, and whether it hit a
+    /// visible object, which makes up the coverage of its pixel.
+    ///
+    /// # Arguments
+    ///
+    /// * `ray` - The camera ray.
+    /// * `scene` - The scene to trace the ray through.
+    ///
+    /// # Returns
+    ///
+    /// The color of the ray, the number of bounces of the rest of its path, and `true` if it hit
+    /// an object of a visible layer rather than the background or a held out object.
+    fn trace_camera_ray(&self, ray: &Ray, scene: &Scene) -> (Vector3, u32, bool) {
+        if self.max_depth == 0 {
+            return (Vector3::new(0.0, 0.0, 0.0), 0, false);
+        }
+        let (near, far) = self.clip_interval(ray);
+        match scene.hit_object(ray, (near.max(scene.epsilon()), far)) {
+            Some((index, record)) => {
+                let (color, bounces) = self.shade(ray, scene, self.max_depth, index, &record, None);
+                (color, bounces, scene.is_visible(index))
+            }
+            None => (self.background.radiance(&ray.direction), 0, false),
+        }
+    }
+
+    /// Computes the color of a ray by tracing it through the scene.
+    /// Main ray tracing function. Recursively traces rays through the scene.
+    ///
+    /// # Arguments
+    ///
+    /// * `ray` - The ray to trace.
+    /// * `scene` - The scene to trace the ray through.
+    /// * `depth` - The current depth of the ray.
+    /// * `receiver` - The index of the object that scattered the ray, whose light links decide
+    ///   which lights it sees, or `None` for rays leaving the camera.
+    ///
+    /// # Returns
+    ///
+    /// The color of the ray as a `Vector3`, and the number of bounces of the rest of its path.
+    fn ray_color(
+        &self,
+        ray: &Ray,
+        scene: &Scene,
+        depth: u32,
+        receiver: Option<usize>,
+    ) -> (Vector3, u32) {
+        if depth == 0 {
+            return (Vector3::new(0.0, 0.0, 0.0), 0);
+        }
+
+        let (near, far) = if ray.primary {
+            self.clip_interval(ray)
+        } else {
+            (0.0, f64::INFINITY)
+        };
+        if let Some((index, record)) = scene.hit_object(ray, (near.max(scene.epsilon()), far)) {
+            self.shade(ray, scene, depth, index, &record, receiver)
+        } else {
+            (self.background.radiance(&ray.direction), 0)
+        }
+    }
+
+    /// Computes the color of a ray at the point where it hit an object.
+    ///
+    /// # Arguments
+    ///
+    /// * `ray` - The ray that hit the object.
+    /// * `scene` - The scene to trace scattered rays through.
+    /// * `depth` - The current depth of the ray, which must be positive.
+    /// * `index` - The index of the object that was hit.
+    /// * `record` - The record of the hit point.
+    /// * `receiver` - The index of the object that scattered the ray, which only sees the
+    ///   emission of the lights illuminating it, or `None` for rays leaving the camera.
+    ///
+    /// # Returns
+    ///
+    /// The color of the ray as a `Vector3`, and the number of bounces of the rest of its path,
+    /// counting the scattering at the hit point.
+    fn shade(
+        &self,
+        ray: &Ray,
+        scene: &Scene,
+        depth: u32,
+        index: usize,
+        record: &HitRecord,
+        receiver: Option<usize>,
+    ) -> (Vector3, u32) {
+        hot_span!("shade");
+        if depth == self.max_depth && !scene.is_visible(index) {
+            return (Vector3::new(0.0, 0.0, 0.0), 0);
+        }
+
+        let queried;
+        let record = if record.material.surface_queries() {
+            queried = scene.query_surface(record);
+            &queried
+        } else {
+            record
+        };
+        let emission_color = if receiver.is_none_or(|object| scene.illuminates(index, object)) {
+            record.material.emitted(&ShadingContext::new(ray, record))
+        } else {
+            Vector3::new(0.0, 0.0, 0.0)
+        };
+
+        if let Some(scatter_record) = record.material.scatter(ray, record) {
+            let (scatter_color, bounces) = match scatter_record.scatter_type {
+                ScatterType::Specular(mut scattered) => {
+                    scattered.continue_footprint(ray, record.t);
+                    if self.environment_reflections && record.material.is_mirror() {
+                        // The reflected ray still counts as a bounce, as if it had been traced.
+                        if depth == 1 {
+                            (Vector3::new(0.0, 0.0, 0.0), 0)
+                        } else {
+                            (self.background.radiance(&scattered.direction), 0)
+                        }
+                    } else {
+                        self.ray_color(&scattered, scene, depth - 1, Some(index))
+                    }
+                }
+                ScatterType::Pdf(material_pdf) => {
+                    // Half of the directions are sent towards the lights illuminating the object.
+                    let light_pdf =
+                        LightPdf::new(scene, record.shading_point()).with_receiver(index);
+                    let mixture_pdf = MixturePdf::new(&light_pdf, material_pdf.as_ref());
+                    let pdf: &dyn Pdf = if light_pdf.has_lights() {
+                        &mixture_pdf
+                    } else {
+                        material_pdf.as_ref()
+                    };
+
+                    let direction = pdf.generate();
+                    let scattered =
+                        Ray::with_time(record.ray_origin(&direction), direction, ray.time);
+                    let pdf_value = pdf.value(&scattered.direction);
+                    if pdf_value <= 0.0 {
+                        return (emission_color, 0);
+                    }
+
+                    let scattering_pdf = record.material.scattering_pdf(ray, record, &scattered);
+                    if scattering_pdf <= 0.0 {
+                        return (emission_color, 0);
+                    }
+
+                    let (mut color, bounces) =
+                        self.ray_color(&scattered, scene, depth - 1, Some(index));
+                    if scene.has_shadow_densities() {
+                        color += self.shadow_leak(&scattered, scene, index);
+                    }
+                    (color * scattering_pdf / pdf_value, bounces)
+                }
+            };
+            let attenuation = scene.decal_albedo(record, scatter_record.attenuation);
+            (attenuation * scatter_color + emission_color, bounces + 1)
+        } else if self.max_depth == depth && emission_color.max() > 0.0 {
+            (emission_color / emission_color.max(), 0)
+        } else {
+            (emission_color, 0)
+        }
+    }
+
+    /// Computes the light a scattered ray would have brought from a light hidden behind objects
+    /// casting lightened shadows from it, as set by their shadow densities. The objects are
+    /// still shaded by the ray as usual, so this is the light leaking through them: the emission
+    /// of the light scaled by one minus the shadow density of every object in the way.
+    ///
+    /// # Arguments
+    ///
+    /// * `ray` - The scattered ray.
+    /// * `scene` - The scene to trace the ray through.
+    /// * `receiver` - The index of the object that scattered the ray, which only receives light
+    ///   from the lights illuminating it.
+    ///
+    /// # Returns
+    ///
+    /// The light leaking through the objects, or black if the ray reaches a light directly,
+    /// reaches no light, or is blocked by an object casting full shadows.
+    fn shadow_leak(&self, ray: &Ray, scene: &Scene, receiver: usize) -> Vector3 {
+        let black = Vector3::new(0.0, 0.0, 0.0);
+        let mut blockers: Vec<usize> = Vec::new();
+        let mut origin = ray.origin;
+        for _ in 0..SHADOW_STEPS {
+            let probe = Ray::with_time(origin, ray.direction, ray.time);
+            let Some((index, record)) = scene.hit_object(&probe, (scene.epsilon(), f64::INFINITY))
+            else {
+                return black;
+            };
+            if scene.is_light(index) {
+                if blockers.is_empty() || !scene.illuminates(index, receiver) {
+                    return black;
+                }
+                let transmission: f64 = blockers
+                    .iter()
+                    .map(|&blocker| 1.0 - scene.shadow_density(index, blocker))
+                    .product();
+                return transmission
+                    * record
+                        .material
+                        .emitted(&ShadingContext::new(&probe, &record));
+            }
+            if !scene.lightens_shadows(index) {
+                return black;
+            }
+            // The ray leaves a closed object through its far side, which is the same blocker.
+            if !blockers.contains(&index) {
+                blockers.push(index);
+            }
+            origin = record.poz;
+        }
+        black
+    }
+
+    /// Returns the number of samples traced through every pixel.
+    pub fn samples_per_pixel(&self) -> u32 {
+        self.samples_per_pixel
+    }
+
+    /// Returns the width of the rendered image in pixels.
+    pub fn image_width(&self) -> u32 {
+        self.image_width
+    }
+
+    /// Returns the height of the rendered image in pixels.
+    pub fn image_height(&self) -> u32 {
+        self.image_height
+    }
+
+    /// Splits the image into square tiles, row by row starting from the top-left tile.
+    ///
+    /// # Returns
+    ///
+    /// The tiles covering the image.
+    fn tiles(&self) -> Vec<Tile> {
+        let mut tiles = Vec::new();
+        for y0 in (0..self.image_height).step_by(TILE_SIZE as usize) {
+            for x0 in (0..self.image_width).step_by(TILE_SIZE as usize) {
+                tiles.push(Tile {
+                    x0,
+                    y0,
+                    x1: (x0 + TILE_SIZE).min(self.image_width),
+                    y1: (y0 + TILE_SIZE).min(self.image_height),
+                });
+            }
+        }
+        tiles
+    }
+
+    /// Traces a number of samples through every pixel of a tile.
+    ///
+    /// # Arguments
+    ///
+    /// * `tile` - The tile to render.
+    /// * `scene` - The scene to render.
+    /// * `first_sample` - The index of the first sample traced for every pixel.
+    /// * `samples` - The number of samples per pixel.
+    ///
+    /// # Returns
+    ///
+    /// The sums of the samples of every pixel of the tile, the lengths of their paths and the
+    /// time it took.
+    fn render_tile(
+        &self,
+        tile: &Tile,
+        scene: &Scene,
+        first_sample: u32,
+        samples: u32,
+    ) -> RenderedTile {
+        let _span =
+            tracing::debug_span!("render_tile", x = tile.x0, y = tile.y0, samples).entered();
+        let start = Instant::now();
+        let mut sums = Vec::with_capacity(tile.pixel_count());
+        let mut paths = PathLengthHistogram::new(self.max_depth);
+
+        for y in tile.y0..tile.y1 {
+            for x in tile.x0..tile.x1 {
+                if let Some(seed) = self.seed {
+                    fastrand::seed(Self::pixel_seed(seed, x, y, first_sample));
+                }
+                let mut pixel = SampleSums::default();
+                for _s in 0..samples {
+                    let ray = self.get_ray(x, y);
+                    let (color, bounces, covered) = self.trace_camera_ray(&ray, scene);
+                    pixel.add(self.clamp_sample(color), bounces, covered);
+                    paths.add(bounces);
+                }
+                sums.push(pixel);
+            }
+        }
+
+        RenderedTile {
+            sums,
+            paths,
+            elapsed: start.elapsed(),
+        }
+    }
+
+    /// Renders the scene into a buffer of linear colors, without gamma correction or clamping.
+    ///
+    /// The image is rendered in tiles. A first pass traces one sample per pixel and times every
+    /// tile, then the remaining samples are scheduled from the slowest tile to the fastest, so
+    /// expensive regions such as glass or volumes start early instead of leaving most threads
+    /// idle at the end of the render.
+    ///
+    /// # Arguments
+    ///
+    /// * `scene` - The scene to render.
+    ///
+    /// # Returns
+    ///
+    /// The averaged color of every pixel, stored row by row starting from the top-left pixel.
+    pub fn render_linear(&self, scene: &Scene) -> Vec<Vector3> {
+        self.render_passes(scene).color
+    }
+
+    /// Renders the scene into a buffer of linear colors, like `render_linear`, along with the
+    /// standard error of every pixel, the average number of bounces of its paths and a histogram
+    /// of the lengths of all the paths, which is printed.
+    ///
+    /// # Arguments
+    ///
+    /// * `scene` - The scene to render.
+    ///
+    /// # Returns
+    ///
+    /// The images of the render.
+    pub fn render_passes(&self, scene: &Scene) -> RenderPasses {
+        #[cfg(feature = "preview")]
+        if self.preview {
+            // The render runs on its own thread while this one keeps the window responsive.
+            let preview = PreviewBuffer::new(self.image_width, self.image_height);
+            return std::thread::scope(|scope| {
+                let render = scope.spawn(|| self.render_tiles(scene, Some(&preview)));
+                preview.show("Ray Tracer", || render.is_finished());
+                render
+                    .join()
+                    .unwrap_or_else(|e| std::panic::resume_unwind(e))
+            });
+        }
+        #[cfg(not(feature = "preview"))]
+        if self.preview {
+            eprintln!("Built without the `preview` feature, rendering without a preview window");
+        }
+        self.render_tiles(scene, None)
+    }
+
+    /// Renders the images of `render_passes`, writing every tile to a preview as it finishes.
+    ///
+    /// # Arguments
+    ///
+    /// * `scene` - The scene to render.
+    /// * `preview` - The preview showing the render in progress, if any.
+    ///
+    /// # Returns
+    ///
+    /// The images of the render.
+    fn render_tiles(&self, scene: &Scene, preview: Option<&PreviewBuffer>) -> RenderPasses {
+        let _span = tracing::info_span!(
+            "render",
+            width = self.image_width,
+            height = self.image_height,
+            samples = self.samples_per_pixel
+        )
+        .entered();
+        let tiles = self.tiles();
+        let pilot_samples = self.samples_per_pixel.min(1);
+        let remaining_samples = self.samples_per_pixel - pilot_samples;
+
+        if !self.quiet {
+            scene.statistics().print(self.framebuffer_bytes());
+        }
+        if self.check_overlaps {
+            let overlaps = scene.find_overlaps(self);
+            for overlap in &overlaps {
+                eprintln!("Warning: {}", overlap.describe(scene));
+            }
+            self.log(format_args!(
+                "Overlapping objects found: {}",
+                overlaps.len()
+            ));
+        }
+        self.log(format_args!("Rendering..."));
+
+        if let Some(preview) = preview {
+            if self.preview_strategy == PreviewStrategy::Progressive {
+                for scale in PROGRESSIVE_SCALES {
+                    self.preview_coarse(scene, preview, scale);
+                }
+            }
+        }
+
+        let pilot: Vec<RenderedTile> = tiles
+            .par_iter()
+            .with_max_len(1)
+            .map(|tile| {
+                let rendered = self.render_tile(tile, scene, self.first_sample, pilot_samples);
+                if let Some(preview) = preview {
+                    self.preview_tile(preview, tile, &rendered.sums, pilot_samples);
+                }
+                rendered
+            })
+            .collect();
+
+        if pilot_samples > 0 {
+            let colors: Vec<Vector3> = pilot
+                .iter()
+                .flat_map(|tile| tile.sums.iter())
+                .map(|sum| sum.color / pilot_samples as f64)
+                .collect();
+            if let Some(warning) = self.blank_render_warning(scene, &colors) {
+                eprintln!("{}", warning);
+            }
+        }
+
+        let mut order: Vec<usize> = (0..tiles.len()).collect();
+        order.sort_by(|&a, &b| pilot[b].elapsed.cmp(&pilot[a].elapsed));
+
+        let progress = Arc::new(AtomicUsize::new(0));
+        let finished: Vec<(usize, RenderedTile)> = order
+            .par_iter()
+            .with_max_len(1)
+            .map(|&index| {
+                let rendered = self.render_tile(
+                    &tiles[index],
+                    scene,
+                    self.first_sample + pilot_samples,
+                    remaining_samples,
+                );
+                if let Some(preview) = preview {
+                    let totals: Vec<SampleSums> = rendered
+                        .sums
+                        .iter()
+                        .zip(&pilot[index].sums)
+                        .map(|(sum, pilot_sum)| sum.merge(pilot_sum))
+                        .collect();
+                    self.preview_tile(preview, &tiles[index], &totals, self.samples_per_pixel);
+                }
+
+                let done = progress.fetch_add(1, std::sync::atomic::Ordering::Relaxed) + 1;
+                if (done * 10 / tiles.len()) > ((done - 1) * 10 / tiles.len()) {
+                    self.log(format_args!("Progress: {}%", (done * 100) / tiles.len()));
+                }
+
+                (index, rendered)
+            })
+            .collect();
+
+        let mut tile_times: Vec<Duration> = pilot.iter().map(|tile| tile.elapsed).collect();
+        let mut paths = PathLengthHistogram::new(self.max_depth);
+        let mut accumulation = Accumulation::new(
+            self.image_width,
+            self.image_height,
+            self.first_sample..self.first_sample + self.samples_per_pixel,
+        );
+        for (index, rendered) in finished {
+            let tile = &tiles[index];
+            let pilot = &pilot[index];
+            tile_times[index] += rendered.elapsed;
+            paths.merge(&pilot.paths);
+            paths.merge(&rendered.paths);
+
+            let width = (tile.x1 - tile.x0) as usize;
+            for (offset, sum) in rendered.sums.iter().enumerate() {
+                let x = tile.x0 as usize + offset % width;
+                let y = tile.y0 as usize + offset / width;
+                let pixel = y * self.image_width as usize + x;
+                accumulation.sums[pixel] = sum.merge(&pilot.sums[offset]);
+            }
+        }
+        let mut passes = RenderPasses {
+            color: accumulation.color(),
+            error: accumulation.error(),
+            bounces: accumulation.bounces(),
+            coverage: accumulation.coverage(),
+            paths,
+            accumulation,
+        };
+
+        if self.despeckle {
+            let removed = despeckle(&mut passes.color, self.image_width as usize);
+            self.log(format_args!("Removed {} fireflies", removed));
+        }
+        if self.denoise {
+            let _span = tracing::debug_span!("denoise").entered();
+            let features = self.features(scene);
+            passes.color = denoise(
+                &passes.color,
+                &passes.error,
+                &features,
+                self.image_width as usize,
+                self.samples_per_pixel,
+            );
+            self.log(format_args!("Denoised the image"));
+        }
+
+        if !self.quiet {
+            TileStatistics::new(&tile_times).print();
+            passes.paths.print();
+        }
+        passes
+    }
+
+    /// Prints a message about a render on the standard output, unless the camera is quiet.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The message.
+    fn log(&self, message: fmt::Arguments) {
+        if !self.quiet {
+            println!("{}", message);
+        }
+    }
+
+    /// Finds the albedo and normal of the first surface seen through the center of every pixel,
+    /// which guide the denoiser. Mirror reflections and refractions are followed to the surface
+    /// they show, so reflected edges are kept too.
+    ///
+    /// # Arguments
+    ///
+    /// * `scene` - The scene being rendered.
+    ///
+    /// # Returns
+    ///
+    /// The features of every pixel, row by row.
+    fn features(&self, scene: &Scene) -> Features {
+        let pixels: Vec<(u32, u32)> = (0..self.image_height)
+            .flat_map(|y| (0..self.image_width).map(move |x| (x, y)))
+            .collect();
+        let (albedo, normal) = pixels
+            .par_iter()
+            .map(|&(x, y)| self.pixel_features(scene, x, y))
+            .unzip();
+        Features { albedo, normal }
+    }
+
+    /// Finds the first surface seen through the center of every pixel when the shutter opens: its
+    /// albedo and normal, as given to the denoiser, its distance, the index of its object, the ID
+    /// of its material and how far it moves across the image until the shutter closes.
+    ///
+    /// # Arguments
+    ///
+    /// * `scene` - The scene being rendered.
+    ///
+    /// # Returns
+    ///
+    /// The auxiliary images, row by row.
+    pub fn aovs(&self, scene: &Scene) -> Aovs {
+        let Features { albedo, normal } = self.features(scene);
+        let pixels: Vec<(u32, u32)> = (0..self.image_height)
+            .flat_map(|y| (0..self.image_width).map(move |x| (x, y)))
+            .collect();
+        let hits: Vec<Option<(f64, usize, usize, Vector3)>> = pixels
+            .par_iter()
+            .map(|&(x, y)| {
+                let (open, close) = self.shutter_interval(y);
+                let mut ray = self.pixel_ray(x, y);
+                ray.time = open;
+                let (near, far) = self.clip_interval(&ray);
+                let (index, record) = scene.hit_object(&ray, (near.max(scene.epsilon()), far))?;
+                if !scene.is_visible(index) {
+                    return None;
+                }
+                let distance = (record.poz - ray.origin).length();
+                let material = record.material as *const dyn Material as *const () as usize;
+
+                // The point of the surface is followed from the opening to the closing of the
+                // shutter, and both of its places are found in the image.
+                let moved = scene.point_at_time(index, &record.poz, open, close);
+                let motion = match (self.project(&record.poz), self.project(&moved)) {
+                    (Some((x0, y0)), Some((x1, y1))) => Vector3::new(x1 - x0, y1 - y0, 0.0),
+                    _ => Vector3::default(),
+                };
+                Some((distance, index, material, motion))
+            })
+            .collect();
+
+        // Materials are told apart by address and numbered as they first appear, so the IDs
+        // are the same for every render of the scene.
+        let mut materials: Vec<usize> = Vec::new();
+        let mut aovs = Aovs {
+            albedo,
+            normal,
+            ..Aovs::default()
+        };
+        for hit in hits {
+            aovs.depth.push(hit.map(|(distance, _, _, _)| distance));
+            aovs.object.push(hit.map(|(_, index, _, _)| index));
+            aovs.motion
+                .push(hit.map_or_else(Vector3::default, |(_, _, _, motion)| motion));
+            aovs.material.push(hit.map(|(_, _, material, _)| {
+                materials
+                    .iter()
+                    .position(|&known| known == material)
+                    .unwrap_or_else(|| {
+                        materials.push(material);
+                        materials.len() - 1
+                    })
+            }));
+        }
+        aovs
+    }
+
+    /// Finds the albedo and normal of the first surface seen through the center of a pixel.
+    ///
+    /// # Arguments
+    ///
+    /// * `scene` - The scene being rendered.
+    /// * `x` - The x-coordinate of the pixel.
+    /// * `y` - The y-coordinate of the pixel.
+    ///
+    /// # Returns
+    ///
+    /// The albedo, tinted by the mirrors and glass in front of the surface, and the normal of the
+    /// first surface hit, or the background scaled down to white and no normal for rays escaping
+    /// the scene.
+    fn pixel_features(&self, scene: &Scene, x: u32, y: u32) -> (Vector3, Vector3) {
+        // Glass picks reflection or refraction at random, so seeded renders reseed every pixel
+        // to give the denoiser the same features on any number of threads.
+        if let Some(seed) = self.seed {
+            fastrand::seed(Self::pixel_seed(seed, x, y, 0));
+        }
+        let to_white = |color: Vector3| color / color.max().max(1.0);
+        let mut ray = self.pixel_ray(x, y);
+        let mut tint = Vector3::new(1.0, 1.0, 1.0);
+        let mut normal = None;
+        for _ in 0..=FEATURE_SPECULAR_DEPTH {
+            let (near, far) = if ray.primary {
+                self.clip_interval(&ray)
+            } else {
+                (0.0, f64::INFINITY)
+            };
+            let Some((index, record)) = scene.hit_object(&ray, (near.max(scene.epsilon()), far))
+            else {
+                let background = to_white(self.background.radiance(&ray.direction));
+                return (tint * background, normal.unwrap_or_default());
+            };
+            if ray.primary && !scene.is_visible(index) {
+                return (Vector3::default(), record.normal);
+            }
+            let normal = *normal.get_or_insert(record.normal);
+            match record.material.scatter(&ray, &record) {
+                Some(scatter_record) => match scatter_record.scatter_type {
+                    ScatterType::Specular(mut scattered) => {
+                        scattered.continue_footprint(&ray, record.t);
+                        tint = tint * scatter_record.attenuation;
+                        ray = scattered;
+                    }
+                    ScatterType::Pdf(_) => {
+                        let albedo = scene.decal_albedo(&record, scatter_record.attenuation);
+                        return (tint * albedo, normal);
+                    }
+                },
+                None => {
+                    let emitted = record.material.emitted(&ShadingContext::new(&ray, &record));
+                    return (tint * to_white(emitted), normal);
+                }
+            }
+        }
+        (tint, normal.unwrap_or_default())
+    }
+
+    /// Checks whether the first pass of a render came out entirely black or a single color, which
+    /// usually means the camera is pointed away from the scene or its lights give no light, and
+    /// describes what the camera sees to help find out why.
+    ///
+    /// # Arguments
+    ///
+    /// * `scene` - The scene being rendered.
+    /// * `colors` - The colors of the pixels after the first pass, in any order.
+    ///
+    /// # Returns
+    ///
+    /// A warning describing the scene bounds and the camera frustum, or `None` if the pixels
+    /// differ.
+    fn blank_render_warning(&self, scene: &Scene, colors: &[Vector3]) -> Option<String> {
+        let first = *colors.first()?;
+        let close = |a: &Vector3, b: &Vector3| {
+            (a.x - b.x).abs() < BLANK_TOLERANCE
+                && (a.y - b.y).abs() < BLANK_TOLERANCE
+                && (a.z - b.z).abs() < BLANK_TOLERANCE
+        };
+        if !colors.iter().all(|color| close(color, &first)) {
+            return None;
+        }
+        let black = close(&first, &Vector3::default());
+        if !black && colors.len() < 2 {
+            return None;
+        }
+
+        let mut lines = vec![if black {
+            "Warning: the first pass of the render is entirely black.".to_string()
+        } else {
+            format!(
+                "Warning: the first pass of the render is a single color {}.",
+                format_vector(&first)
+            )
+        }];
+
+        lines.push(match scene.bounding_box() {
+            Some(bounds) => format!(
+                "  Scene bounds: {} to {} in {}",
+                format_vector(&bounds.min),
+                format_vector(&bounds.max),
+                scene.unit().name()
+            ),
+            None => "  Scene bounds: none, the scene has no bounded objects".to_string(),
+        });
+
+        let field_of_view = match self.projection {
+            Projection::Perspective => {
+                let height = self.pixel_delta_v.length() * self.image_height as f64;
+                let distance = (self.pixel00_loc - self.camera_center).dot(&-self.w);
+                format!(
+                    "perspective, {:.1} degrees vertical field of view",
+                    (2.0 * (height / 2.0).atan2(distance)).to_degrees()
+                )
+            }
+            Projection::Equirectangular => "equirectangular".to_string(),
+            Projection::Stereographic { fov } => {
+                format!("stereographic, {:.1} degrees field of view", fov)
+            }
+            Projection::Fisheye { fov } => format!("fisheye, {:.1} degrees field of view", fov),
+            Projection::Orthographic { height } => {
+                format!("orthographic, {} units high", height)
+            }
+        };
+        lines.push(format!(
+            "  Camera: at {}, looking towards {}, up {}, {}",
+            format_vector(&self.camera_center),
+            format_vector(&-self.w),
+            format_vector(&self.v),
+            field_of_view
+        ));
+        lines.push(format!(
+            "  Clipping: from {} to {} in front of the camera",
+            self.near, self.far
+        ));
+
+        // Trace the centers of a grid of pixels to tell a camera looking away from the scene
+        // from a scene that is seen but gives no light.
+        let step_x = self.image_width.div_ceil(BLANK_PROBE_SIZE).max(1);
+        let step_y = self.image_height.div_ceil(BLANK_PROBE_SIZE).max(1);
+        let mut probes = 0;
+        let mut hits = 0;
+        for y in (0..self.image_height).step_by(step_y as usize) {
+            for x in (0..self.image_width).step_by(step_x as usize) {
+                let ray = self.pixel_ray(x, y);
+                probes += 1;
+                if scene.hit_object(&ray, self.clip_interval(&ray)).is_some() {
+                    hits += 1;
+                }
+            }
+        }
+        lines.push(format!(
+            "  {} of {} camera rays probed hit an object",
+            hits, probes
+        ));
+
+        lines.push(if hits == 0 {
+            "  The camera is probably pointed away from the scene, or the clipping planes hide it."
+                .to_string()
+        } else if black && !scene.has_lights() {
+            "  The scene has no lights and the background is black.".to_string()
+        } else if black {
+            "  The lights of the scene may have zero intensity or be hidden from the camera."
+                .to_string()
+        } else {
+            "  The objects in view may match the background or be lit evenly.".to_string()
+        });
+        Some(lines.join("\n"))
+    }
+
+    /// Renders the image at a fraction of its resolution, with one sample per pixel, and writes it
+    /// to a preview upscaled to the full resolution, every coarse pixel covering a block of
+    /// pixels. The coarse image is rendered in tiles like the full one, by a copy of the camera
+    /// with fewer pixels.
+    ///
+    /// # Arguments
+    ///
+    /// * `scene` - The scene being rendered.
+    /// * `preview` - The preview to write to.
+    /// * `scale` - The number of pixels along each side of the blocks.
+    fn preview_coarse(&self, scene: &Scene, preview: &PreviewBuffer, scale: u32) {
+        let mut coarse = self.clone();
+        coarse.set_image_width(self.image_width.div_ceil(scale.max(1)));
+        let (width, height) = (coarse.image_width, coarse.image_height);
+        if width >= self.image_width {
+            return;
+        }
+
+        let tiles = coarse.tiles();
+        let rendered: Vec<RenderedTile> = tiles
+            .par_iter()
+            .with_max_len(1)
+            .map(|tile| coarse.render_tile(tile, scene, 0, 1))
+            .collect();
+        let mut colors = vec![image::Rgb([0, 0, 0]); (width * height) as usize];
+        for (tile, rendered) in tiles.iter().zip(&rendered) {
+            let tile_width = (tile.x1 - tile.x0) as usize;
+            for (offset, sum) in rendered.sums.iter().enumerate() {
+                let x = tile.x0 as usize + offset % tile_width;
+                let y = tile.y0 as usize + offset / tile_width;
+                colors[y * width as usize + x] = self.output.apply(sum.color);
+            }
+        }
+
+        let upscaled: Vec<image::Rgb<u8>> = (0..self.image_height)
+            .flat_map(|y| (0..self.image_width).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                let coarse_x = (x * width / self.image_width).min(width - 1);
+                let coarse_y = (y * height / self.image_height).min(height - 1);
+                colors[(coarse_y * width + coarse_x) as usize]
+            })
+            .collect();
+        preview.write(0, 0, self.image_width, &upscaled);
+    }
+
+    /// Writes the colors of a tile to a preview.
+    ///
+    /// # Arguments
+    ///
+    /// * `preview` - The preview to write to.
+    /// * `tile` - The tile.
+    /// * `sums` - The sums of the samples of every pixel of the tile.
+    /// * `samples` - The number of samples in every sum.
+    fn preview_tile(
+        &self,
+        preview: &PreviewBuffer,
+        tile: &Tile,
+        sums: &[SampleSums],
+        samples: u32,
+    ) {
+        let samples = samples.max(1) as f64;
+        let colors: Vec<image::Rgb<u8>> = sums
+            .iter()
+            .map(|sum| self.output.apply(sum.color / samples))
+            .collect();
+        preview.write(tile.x0, tile.y0, tile.x1 - tile.x0, &colors);
+    }
+
+    /// Estimates the memory taken by the images held while rendering: the sums of the samples of
+    /// the pilot pass and of the remaining samples, the colors, standard errors and bounce counts
+    /// of the final image, and the 8-bit output image.
+    ///
+    /// # Returns
+    ///
+    /// The estimated memory, in bytes.
+    pub fn framebuffer_bytes(&self) -> usize {
+        let pixels = (self.image_width * self.image_height) as usize;
+        pixels
+            * (2 * std::mem::size_of::<SampleSums>()
+                + 2 * std::mem::size_of::<Vector3>()
+                + std::mem::size_of::<f64>()
+                + 3)
+    }
+
+    /// Traces the camera samples of a pixel and turns the ones that hit an object into deep
+    /// samples.
+    ///
+    /// # Arguments
+    ///
+    /// * `scene` - The scene to render.
+    /// * `x` - The x-coordinate of the pixel.
+    /// * `y` - The y-coordinate of the pixel.
+    ///
+    /// # Returns
+    ///
+    /// The deep samples of the pixel, sorted from front to back.
+    fn deep_pixel(&self, scene: &Scene, x: u32, y: u32) -> Vec<DeepSample> {
+        if let Some(seed) = self.seed {
+            fastrand::seed(Self::pixel_seed(seed, x, y, 0));
+        }
+        let mut fragments = Vec::new();
+        for _s in 0..self.samples_per_pixel {
+            let ray = self.get_ray(x, y);
+            if let Some((index, record)) = scene.hit_object(&ray, self.clip_interval(&ray)) {
+                let color = if self.max_depth == 0 {
+                    Vector3::new(0.0, 0.0, 0.0)
+                } else {
+                    self.clamp_sample(
+                        self.shade(&ray, scene, self.max_depth, index, &record, None)
+                            .0,
+                    )
+                };
+                fragments.push(Fragment {
+                    object: index,
+                    depth: record.t * ray.direction.length(),
+                    color,
+                });
+            }
+        }
+        merge_fragments(fragments, self.samples_per_pixel)
+    }
+
+    /// Renders a deep image of the scene. Every camera sample that hits an object adds a fragment
+    /// at the distance of its first hit, carrying the color of its whole path, and the fragments
+    /// of a pixel are merged into deep samples. Camera samples that hit nothing are left out, so
+    /// the background is not part of the deep image and shows through when it is composited.
+    ///
+    /// # Arguments
+    ///
+    /// * `scene` - The scene to render.
+    ///
+    /// # Returns
+    ///
+    /// The deep image.
+    pub fn render_deep(&self, scene: &Scene) -> DeepImage {
+        let width = self.image_width;
+        let pixels = (0..width * self.image_height)
+            .into_par_iter()
+            .map(|index| self.deep_pixel(scene, index % width, index / width))
+            .collect();
+        DeepImage::new(width, self.image_height, pixels)
+    }
+
+    /// Renders a deep image of the scene and saves it to the given file.
+    ///
+    /// # Arguments
+    ///
+    /// * `scene` - The scene to render.
+    /// * `output_name` - The path of the deep OpenEXR file.
+    pub fn render_deep_to_file(&self, scene: &Scene, output_name: &str) {
+        self.log(format_args!("Rendering deep image..."));
+        if let Err(e) = self.render_deep(scene).save(Path::new(output_name)) {
+            eprintln!("Failed to save deep image: {}", e);
+        } else {
+            self.log(format_args!(
+                "Successfully saved deep image to {}",
+                output_name
+            ));
+        }
+    }
+
+    /// Renders the scene and saves the image to the output path.
+    ///
+    /// # Arguments
+    ///
+    /// * `scene` - The scene to render.
+    pub fn render(&self, scene: Scene) {
+        self.render_to_file(&scene, &self.output_path);
+        if let Some(deep_path) = &self.deep_path {
+            self.render_deep_to_file(&scene, deep_path);
+        }
+    }
+
+    /// Renders every render layer of the scene on its own, saving each image to the output path
+    /// with the layer name added, such as `output_<layer>.png`, so the layers can be composited
+    /// afterwards.
+    ///
+    /// # Arguments
+    ///
+    /// * `scene` - The scene to render.
+    pub fn render_layers(&self, scene: &mut Scene) {
+        let layers: Vec<String> = scene
+            .layers()
+            .iter()
+            .map(|layer| layer.to_string())
+            .collect();
+        for layer in &layers {
+            scene.set_visible_layers(&[layer]);
+            self.render_to_file(scene, &self.output_path_with_suffix(layer));
+            if let Some(deep_path) = &self.deep_path {
+                self.render_deep_to_file(scene, &Self::path_with_suffix(deep_path, layer));
+            }
+        }
+        scene.show_all_layers();
+    }
+
+    /// Renders the scene and saves the image to the given file. OpenEXR and Radiance HDR files
+    /// keep the linear colors, with highlights brighter than white, while other formats store the
+    /// colors turned into bytes by the output transform. When enabled, the standard error of the
+    /// pixels and their average number of bounces are saved next to the image in the same format,
+    /// with `_error` and `_bounces` added to its name, and so are the auxiliary images.
+    ///
+    /// # Arguments
+    ///
+    /// * `scene` - The scene to render.
+    /// * `output_name` - The path of the image file, whose extension gives the format.
+    pub fn render_to_file(&self, scene: &Scene, output_name: &str) {
+        if let Err(e) = self.render_and_save(scene, output_name) {
+            eprintln!("Failed to save image: {}", e);
+        }
+    }
+
+    /// Renders the scene and saves the image to the given file, like `render_to_file`, returning
+    /// the images of the render so callers can report on it.
+    ///
+    /// # Arguments
+    ///
+    /// * `scene` - The scene to render.
+    /// * `output_name` - The path of the image file, whose extension gives the format.
+    ///
+    /// # Returns
+    ///
+    /// The images of the render, or a description of the error if the image could not be saved.
+    /// Images saved next to it that fail are only reported on the standard error.
+    pub fn render_and_save(
+        &self,
+        scene: &Scene,
+        output_name: &str,
+    ) -> Result<RenderPasses, String> {
+        let start = Instant::now();
+        let passes = self.render_passes(scene);
+
+        let saved = if self.alpha_output {
+            self.save_image_with_alpha(&passes.color, &passes.coverage, output_name)
+        } else {
+            self.save_image(&passes.color, output_name)
+        };
+        if saved.is_ok() {
+            self.log(format_args!("Successfully saved image to {}", output_name));
+        }
+
+        if self.report_output {
+            let path = Path::new(output_name);
+            let name = path.file_stem().unwrap_or_default().to_string_lossy();
+            let report = RenderReport::new(&name, path, self, &passes, start.elapsed());
+            let report_path = path.with_extension("report");
+            if let Err(e) = fs::write(&report_path, format!("{}\n", report)) {
+                eprintln!("Failed to save render report: {}", e);
+            } else {
+                self.log(format_args!(
+                    "Successfully saved render report to {}",
+                    report_path.display()
+                ));
+            }
+        }
+
+        if let Some(accumulation_path) = &self.accumulation_path {
+            if let Err(e) = passes.accumulation.save(Path::new(accumulation_path)) {
+                eprintln!("Failed to save accumulation file: {}", e);
+            } else {
+                self.log(format_args!(
+                    "Successfully saved accumulation file to {}",
+                    accumulation_path
+                ));
+            }
+        }
+
+        if self.error_output {
+            let error_name = Self::path_with_suffix(output_name, "error");
+            if let Err(e) = self.save_image(&passes.error, &error_name) {
+                eprintln!("Failed to save standard error image: {}", e);
+            } else {
+                self.log(format_args!(
+                    "Successfully saved standard error image to {}",
+                    error_name
+                ));
+            }
+        }
+
+        if self.bounces_output {
+            // The average is divided by the maximum depth, so white pixels only had paths cut
+            // off by the depth limit.
+            let bounces: Vec<Vector3> = passes
+                .bounces
+                .iter()
+                .map(|&bounces| {
+                    let fraction = bounces / self.max_depth.max(1) as f64;
+                    Vector3::new(fraction, fraction, fraction)
+                })
+                .collect();
+            let bounces_name = Self::path_with_suffix(output_name, "bounces");
+            if let Err(e) = self.save_image(&bounces, &bounces_name) {
+                eprintln!("Failed to save bounces image: {}", e);
+            } else {
+                self.log(format_args!(
+                    "Successfully saved bounces image to {}",
+                    bounces_name
+                ));
+            }
+        }
+
+        if self.aov_output {
+            // The albedo is a color, saved like the image, while the other images hold data
+            // written as it is.
+            let aovs = self.aovs(scene);
+            let high_dynamic_range = output::is_high_dynamic_range(Path::new(output_name));
+            let images = [
+                ("albedo", aovs.albedo.clone(), true),
+                ("normal", aovs.normal_colors(), false),
+                ("depth", aovs.depth_colors(!high_dynamic_range), false),
+                ("object", aovs.object_colors(), false),
+                ("material", aovs.material_colors(), false),
+                ("motion", aovs.motion_colors(!high_dynamic_range), false),
+            ];
+            for (suffix, pixels, color) in images {
+                let name = Self::path_with_suffix(output_name, suffix);
+                let result = if color {
+                    self.save_image(&pixels, &name)
+                } else {
+                    self.save_data_image(&pixels, &name)
+                };
+                if let Err(e) = result {
+                    eprintln!("Failed to save {} image: {}", suffix, e);
+                } else {
+                    self.log(format_args!(
+                        "Successfully saved {} image to {}",
+                        suffix, name
+                    ));
+                }
+            }
+        }
+        saved.map(|_| passes).map_err(|e| e.to_string())
+    }
+
+    /// Saves a buffer of linear colors to an image file, as floats for high dynamic range formats
+    /// and through the output transform for the others.
+    ///
+    /// # Arguments
+    ///
+    /// * `pixels` - The colors of the image, stored row by row starting from the top-left pixel.
+    /// * `output_name` - The path of the image file, whose extension gives the format.
+    ///
+    /// # Returns
+    ///
+    /// The result of writing the file.
+    fn save_image(&self, pixels: &[Vector3], output_name: &str) -> image::ImageResult<()> {
+        output::save_image(
+            pixels,
+            self.image_width,
+            self.image_height,
+            &self.output,
+            Path::new(output_name),
+        )
+    }
+
+    /// Saves a buffer of linear colors to an image file along with an alpha channel, as floats for
+    /// OpenEXR files and through the output transform for formats storing bytes. Radiance HDR
+    /// files, which have no alpha channel, are saved without it.
+    ///
+    /// # Arguments
+    ///
+    /// * `pixels` - The colors of the image, stored row by row starting from the top-left pixel.
+    /// * `alpha` - The alpha of every pixel, between 0 and 1.
+    /// * `output_name` - The path of the image file, whose extension gives the format.
+    ///
+    /// # Returns
+    ///
+    /// The result of writing the file.
+    fn save_image_with_alpha(
+        &self,
+        pixels: &[Vector3],
+        alpha: &[f64],
+        output_name: &str,
+    ) -> image::ImageResult<()> {
+        let _span = tracing::debug_span!("write_image", path = output_name).entered();
+        let path = Path::new(output_name);
+        if !output::has_alpha_channel(path) {
+            eprintln!(
+                "{} has no alpha channel, saving it without one",
+                output_name
+            );
+            return self.save_image(pixels, output_name);
+        }
+        let index = |x: u32, y: u32| (y * self.image_width + x) as usize;
+
+        if output::is_high_dynamic_range(path) {
+            image::Rgba32FImage::from_fn(self.image_width, self.image_height, |x, y| {
+                let image::Rgb([r, g, b]) = output::to_float_pixel(pixels[index(x, y)]);
+                image::Rgba([r, g, b, alpha[index(x, y)].clamp(0.0, 1.0) as f32])
+            })
+            .save(output_name)
+        } else {
+            image::RgbaImage::from_fn(self.image_width, self.image_height, |x, y| {
+                let image::Rgb([r, g, b]) = self.output.apply(pixels[index(x, y)]);
+                let a = (alpha[index(x, y)].clamp(0.0, 1.0) * 255.0).round() as u8;
+                image::Rgba([r, g, b, a])
+            })
+            .save(output_name)
+        }
+    }
+
+    /// Saves a buffer of data, such as normals or IDs, to an image file, as floats for high
+    /// dynamic range formats and as bytes without the output transform for the others.
+    ///
+    /// # Arguments
+    ///
+    /// * `pixels` - The values of the image, stored row by row starting from the top-left pixel.
+    /// * `output_name` - The path of the image file, whose extension gives the format.
+    ///
+    /// # Returns
+    ///
+    /// The result of writing the file.
+    fn save_data_image(&self, pixels: &[Vector3], output_name: &str) -> image::ImageResult<()> {
+        let _span = tracing::debug_span!("write_image", path = output_name).entered();
+        let pixel = |x: u32, y: u32| pixels[(y * self.image_width + x) as usize];
+
+        if output::is_high_dynamic_range(Path::new(output_name)) {
+            image::Rgb32FImage::from_fn(self.image_width, self.image_height, |x, y| {
+                output::to_float_pixel(pixel(x, y))
+            })
+            .save(output_name)
+        } else {
+            image::ImageBuffer::from_fn(self.image_width, self.image_height, |x, y| {
+                output::to_data_pixel(pixel(x, y))
+            })
+            .save(output_name)
+        }
+    }
+}
+
+/// Formats a vector with a few decimals, for messages.
+///
+/// # Arguments
+///
+/// * `vector` - The vector to format.
+///
+/// # Returns
+///
+/// The vector as `(x, y, z)`.
+fn format_vector(vector: &Vector3) -> String {
+    format!("({:.3}, {:.3}, {:.3})", vector.x, vector.y, vector.z)
+}
+
+/// Replaces the pixels of an image that are isolated fireflies, more than `FIREFLY_RATIO` times
+/// as bright in their brightest channel as the brightest of their neighbors, by the average of
+/// their neighbors. Pixels are compared with their neighbors as rendered, so a firefly does not
+/// hide another next to it.
+///
+/// # Arguments
+///
+/// * `colors` - The colors of the pixels, row by row.
+/// * `width` - The width of the image in pixels.
+///
+/// # Returns
+///
+/// The number of pixels replaced.
+fn despeckle(colors: &mut [Vector3], width: usize) -> usize {
+    if width == 0 {
+        return 0;
+    }
+    let original = colors.to_vec();
+    let height = original.len() / width;
+    let mut removed = 0;
+    for (index, color) in colors.iter_mut().enumerate() {
+        let (x, y) = (index % width, index / width);
+        let mut brightest: f64 = 0.0;
+        let mut sum = Vector3::default();
+        let mut count = 0;
+        for ny in y.saturating_sub(1)..(y + 2).min(height) {
+            for nx in x.saturating_sub(1)..(x + 2).min(width) {
+                if (nx, ny) != (x, y) {
+                    let neighbor = original[ny * width + nx];
+                    brightest = brightest.max(neighbor.max());
+                    sum += neighbor;
+                    count += 1;
+                }
+            }
+        }
+        if count > 0 && color.max() > FIREFLY_RATIO * brightest {
+            *color = sum / count as f64;
+            removed += 1;
+        }
+    }
+    removed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tiles_cover_image_once() {
+        let camera = Camera::new(
+            50,
+            50.0 / 37.0,
+            1,
+            1,
+            |_| Vector3::new(0.0, 0.0, 0.0),
+            90.0,
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, -1.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            0.0,
+            0.0,
+        );
+        let mut covered = vec![0; (camera.image_width * camera.image_height) as usize];
+        for tile in camera.tiles() {
+            for y in tile.y0..tile.y1 {
+                for x in tile.x0..tile.x1 {
+                    covered[(y * camera.image_width + x) as usize] += 1;
+                }
+            }
+        }
+        assert!(covered.iter().all(|&count| count == 1));
+    }
+
+    #[test]
+    fn test_tile_statistics() {
+        let times: Vec<Duration> = [5, 1, 3, 9, 2]
+            .iter()
+            .map(|&ms| Duration::from_millis(ms))
+            .collect();
+        let statistics = TileStatistics::new(&times);
+
+        assert_eq!(statistics.count, 5);
+        assert_eq!(statistics.fastest, Duration::from_millis(1));
+        assert_eq!(statistics.median, Duration::from_millis(3));
+        assert_eq!(statistics.slowest, Duration::from_millis(9));
+        assert_eq!(statistics.total, Duration::from_millis(20));
+    }
+
+    #[test]
+    fn test_settings_override_the_scene() {
+        use crate::output::FilmStock;
+
+        let camera = |width| {
+            Camera::new(
+                width,
+                16.0 / 9.0,
+                10,
+                5,
+                |_| Vector3::new(0.0, 0.0, 0.0),
+                40.0,
+                Vector3::new(1.0, 2.0, 3.0),
+                Vector3::new(0.0, 0.0, -1.0),
+                Vector3::new(0.0, 1.0, 0.0),
+                0.0,
+                10.0,
+            )
+        };
+        let mut resized = camera(400);
+        resized.apply_settings(&RenderSettings {
+            width: Some(160),
+            samples: Some(3),
+            max_depth: None,
+            output: Some("renders/out.png".to_string()),
+            deep: None,
+            error: true,
+            bounces: false,
+            aov: true,
+            alpha: true,
+            preview: false,
+            progressive: true,
+            check: false,
+            max_radiance: Some(20.0),
+            despeckle: false,
+            denoise: true,
+            film: Some(FilmStock::Cinema.curves()),
+            seed: Some(5),
+            sample_range: None,
+            accumulation: Some("renders/out.acc".to_string()),
+            report: true,
+            parameter: Some((SweepParameter::Roughness, 0.2)),
+        });
+
+        // The resized camera traces the same rays as one built at the new width.
+        let expected = camera(160);
+        assert_eq!(resized.image_width(), 160);
+        assert_eq!(resized.image_height(), expected.image_height());
+        for (x, y) in [(0, 0), (159, 89), (80, 45)] {
+            let a = resized.pixel_ray(x, y).direction.normalize();
+            let b = expected.pixel_ray(x, y).direction.normalize();
+            assert!((a - b).length() < 1e-9);
+        }
+        assert_eq!(resized.samples_per_pixel, 3);
+        assert_eq!(resized.max_depth, 5);
+        assert!(resized.error_output && resized.aov_output && resized.alpha_output);
+        assert_eq!(resized.max_radiance, Some(20.0));
+        assert_eq!(resized.seed, Some(5));
+        assert_eq!(
+            resized.accumulation_path.as_deref(),
+            Some("renders/out.acc")
+        );
+        assert!(resized.report_output);
+        assert_ne!(resized.output, OutputTransform::default());
+        assert_eq!(resized.preview_strategy, PreviewStrategy::Progressive);
+        assert_eq!(
+            Path::new(&resized.output_path_with_suffix("shadows")),
+            Path::new("renders/out_shadows.png")
+        );
+    }
+
+    #[test]
+    fn test_progressive_preview() {
+        use crate::material::Lambertian;
+        use crate::shapes::sphere::Sphere;
+
+        let scene = Scene::new(vec![Box::new(Sphere::new(
+            Vector3::new(0.0, 0.0, -5.0),
+            1.0,
+            Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5))),
+        ))]);
+        let mut camera = Camera::new(
+            32,
+            2.0,
+            2,
+            2,
+            |_| Vector3::new(0.2, 0.4, 0.8),
+            30.0,
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, -1.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            0.0,
+            0.0,
+        );
+
+        // The coarse image covers the whole preview in blocks of eight pixels.
+        let preview = PreviewBuffer::new(32, 16);
+        camera.preview_coarse(&scene, &preview, 8);
+        let pixels = preview.pixels();
+        assert!(pixels.iter().all(|&pixel| pixel != 0));
+        for y in 0..8 {
+            for x in 0..8 {
+                assert_eq!(pixels[y * 32 + x], pixels[0]);
+                assert_eq!(pixels[y * 32 + x + 24], pixels[24]);
+            }
+        }
+
+        // The tiles then replace the coarse images with the full render.
+        camera.set_preview_strategy(PreviewStrategy::Progressive);
+        let preview = PreviewBuffer::new(32, 16);
+        let passes = camera.render_tiles(&scene, Some(&preview));
+        let expected: Vec<u32> = passes
+            .color
+            .iter()
+            .map(|&color| {
+                let [r, g, b] = camera.output.apply(color).0;
+                (r as u32) << 16 | (g as u32) << 8 | b as u32
+            })
+            .collect();
+        assert_eq!(preview.pixels(), expected);
+    }
+
+    #[test]
+    fn test_coverage_alpha() {
+        use crate::material::Lambertian;
+        use crate::scene::DEFAULT_LAYER;
+        use crate::shapes::sphere::Sphere;
+
+        // A ball in the middle of the view, before a gray background.
+        let mut scene = Scene::new(Vec::new());
+        scene.add(
+            Box::new(Sphere::new(
+                Vector3::new(0.0, 0.0, -5.0),
+                1.0,
+                Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5))),
+            )),
+            DEFAULT_LAYER,
+        );
+        let mut camera = Camera::new(
+            16,
+            1.0,
+            16,
+            2,
+            |_| Vector3::new(0.5, 0.5, 0.5),
+            30.0,
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, -1.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            0.0,
+            0.0,
+        );
+        let passes = camera.render_passes(&scene);
+        let pixel = |x: usize, y: usize| y * 16 + x;
+        assert_eq!(passes.coverage[pixel(8, 8)], 1.0);
+        assert_eq!(passes.coverage[pixel(0, 0)], 0.0);
+        assert_eq!(passes.color[pixel(0, 0)], Vector3::new(0.5, 0.5, 0.5));
+        assert!(passes
+            .coverage
+            .iter()
+            .any(|&coverage| coverage > 0.0 && coverage < 1.0));
+
+        // The coverage is written into the alpha channel of the image.
+        camera.set_alpha_output(true);
+        let path = std::env::temp_dir().join("raytracer_alpha_test.png");
+        camera
+            .save_image_with_alpha(&passes.color, &passes.coverage, &path.to_string_lossy())
+            .unwrap();
+        let loaded = image::open(&path).unwrap().into_rgba8();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(loaded.get_pixel(8, 8)[3], 255);
+        assert_eq!(loaded.get_pixel(0, 0)[3], 0);
+        assert!(loaded.get_pixel(0, 0)[0] > 0);
+
+        // Held out objects leave holes.
+        scene.set_visible_layers(&["other"]);
+        let passes = camera.render_passes(&scene);
+        assert!(passes.coverage.iter().all(|&coverage| coverage == 0.0));
+    }
+
+    #[test]
+    fn test_aovs() {
+        use crate::material::{Lambertian, Metal};
+        use crate::shapes::sphere::Sphere;
+
+        // Two matte balls sharing a material and a metal one, side by side.
+        let matte = Arc::new(Lambertian::new(Vector3::new(0.2, 0.4, 0.6)));
+        let ball = |x: f64, material: Arc<dyn Material>| -> Box<dyn crate::hit::Hittable> {
+            Box::new(Sphere::new(Vector3::new(x, 0.0, -5.0), 0.8, material))
+        };
+        let scene = Scene::new(vec![
+            ball(-2.0, matte.clone()),
+            ball(0.0, matte),
+            ball(2.0, Arc::new(Metal::new(Vector3::new(0.9, 0.9, 0.9), 0.5))),
+        ]);
+        let camera = Camera::new(
+            30,
+            3.0,
+            1,
+            2,
+            |_| Vector3::new(0.0, 0.0, 0.0),
+            30.0,
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, -1.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            0.0,
+            0.0,
+        );
+        let aovs = camera.aovs(&scene);
+        let pixel = |x: usize| 5 * 30 + x;
+        let (left, middle, right, corner) = (pixel(6), pixel(15), pixel(24), 0);
+
+        assert_eq!(aovs.object[left], Some(0));
+        assert_eq!(aovs.object[middle], Some(1));
+        assert_eq!(aovs.object[right], Some(2));
+        assert_eq!(aovs.object[corner], None);
+        assert_eq!(aovs.material[left], Some(0));
+        assert_eq!(aovs.material[middle], Some(0));
+        assert_eq!(aovs.material[right], Some(1));
+        assert_eq!(aovs.material[corner], None);
+
+        let depth = aovs.depth[middle].unwrap();
+        assert!((depth - 4.2).abs() < 0.05, "{}", depth);
+        assert!(aovs.depth[corner].is_none());
+        assert!(aovs.normal[middle].z > 0.9);
+        assert!((aovs.albedo[middle] - Vector3::new(0.2, 0.4, 0.6)).length() < 1e-9);
+        assert!(aovs
+            .motion
+            .iter()
+            .all(|&motion| motion == Vector3::default()));
+    }
+
+    #[test]
+    fn test_motion_vectors() {
+        use crate::material::Lambertian;
+        use crate::shapes::sphere::Sphere;
+        use crate::transformation::{Motion, MotionKey};
+
+        // A ball moving one unit to the right while the shutter is open, next to a still one.
+        let matte = Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5)));
+        let ball: Arc<dyn crate::hit::Hittable> =
+            Arc::new(Sphere::new(Vector3::new(0.0, 0.0, 0.0), 0.8, matte.clone()));
+        let keys = vec![
+            MotionKey::new(0.0, Vector3::new(0.0, 0.0, -5.0)),
+            MotionKey::new(1.0, Vector3::new(1.0, 0.0, -5.0)),
+        ];
+        let scene = Scene::new(vec![
+            Box::new(Motion::new(ball, keys)),
+            Box::new(Sphere::new(Vector3::new(-2.0, 0.0, -5.0), 0.8, matte)),
+        ]);
+        let mut camera = Camera::new(
+            30,
+            3.0,
+            1,
+            2,
+            |_| Vector3::new(0.0, 0.0, 0.0),
+            30.0,
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, -1.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            0.0,
+            0.0,
+        );
+        camera.set_shutter(Shutter::new(1.0));
+        let aovs = camera.aovs(&scene);
+        let pixel = |x: usize| 5 * 30 + x;
+        let (still, moving, corner) = (pixel(6), pixel(15), 0);
+
+        // One unit at the depth of the surface spans the height of the image over the height of
+        // the view there.
+        let depth = aovs.depth[moving].unwrap();
+        let expected = 10.0 / (2.0 * depth * 15.0_f64.to_radians().tan());
+        let motion = aovs.motion[moving];
+        assert!(
+            (motion.x - expected).abs() < 0.05,
+            "{} {}",
+            motion.x,
+            expected
+        );
+        assert!(motion.y.abs() < 1e-9);
+        assert_eq!(aovs.motion[still], Vector3::default());
+        assert_eq!(aovs.motion[corner], Vector3::default());
+
+        // Points seen through a pixel are found back at its center.
+        for (x, y) in [(0, 0), (15, 5), (29, 9)] {
+            let ray = camera.pixel_ray(x, y);
+            let (px, py) = camera.project(&ray.point_at(3.0)).unwrap();
+            assert!((px - x as f64).abs() < 1e-9 && (py - y as f64).abs() < 1e-9);
+        }
+        assert!(camera.project(&Vector3::new(0.0, 0.0, 1.0)).is_none());
+    }
+
+    #[test]
+    fn test_render_deep() {
+        use crate::hit::Hittable;
+        use crate::material::DiffuseLight;
+        use crate::shapes::quad::Quad;
+
+        // A light covers the left half of the view, one unit away, and nothing is on the right.
+        let light = Quad::new(
+            Vector3::new(-10.0, -10.0, -1.0),
+            Vector3::new(10.0, 0.0, 0.0),
+            Vector3::new(0.0, 20.0, 0.0),
+            Arc::new(DiffuseLight::new(Vector3::new(1.0, 1.0, 1.0))),
+        );
+        let scene = Scene::new(vec![Box::new(light) as Box<dyn Hittable>]);
+        let mut camera = Camera::new(
+            4,
+            1.0,
+            8,
+            4,
+            |_| Vector3::new(0.0, 0.0, 1.0),
+            90.0,
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, -1.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            0.0,
+            0.0,
+        );
+        camera.set_seed(3);
+        let deep = camera.render_deep(&scene);
+
+        let left = deep.pixel(0, 1);
+        assert_eq!(left.len(), 1);
+        assert!(left[0].alpha == 1.0 && left[0].color == Vector3::new(1.0, 1.0, 1.0));
+        assert!(left[0].depth > 1.0 && left[0].depth_back < 2.0);
+        // The background is left out of the deep image.
+        assert!(deep.pixel(3, 1).is_empty());
+    }
+
+    #[test]
+    fn test_spherical_projections() {
+        let mut camera = Camera::new(
+            200,
+            2.0,
+            1,
+            1,
+            |_| Vector3::new(0.0, 0.0, 0.0),
+            90.0,
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, -1.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            0.0,
+            0.0,
+        );
+        let close = |a: Vector3, b: Vector3| (a.normalize() - b).length() < 1e-9;
+
+        camera.set_projection(Projection::Equirectangular);
+        // The center of the image looks forward, the edges look backwards and the top looks up.
+        assert!(close(
+            camera.ray_direction(99.5, 49.5),
+            Vector3::new(0.0, 0.0, -1.0)
+        ));
+        assert!(close(
+            camera.ray_direction(149.5, 49.5),
+            Vector3::new(1.0, 0.0, 0.0)
+        ));
+        assert!(close(
+            camera.ray_direction(-0.5, 49.5),
+            Vector3::new(0.0, 0.0, 1.0)
+        ));
+        assert!(close(
+            camera.ray_direction(99.5, -0.5),
+            Vector3::new(0.0, 1.0, 0.0)
+        ));
+
+        camera.set_projection(Projection::Stereographic { fov: 180.0 });
+        assert!(close(
+            camera.ray_direction(99.5, 49.5),
+            Vector3::new(0.0, 0.0, -1.0)
+        ));
+        // The edges of the image are half the field of view away from the viewing direction.
+        assert!(close(
+            camera.ray_direction(199.5, 49.5),
+            Vector3::new(1.0, 0.0, 0.0)
+        ));
+        assert!(close(
+            camera.ray_direction(-0.5, 49.5),
+            Vector3::new(-1.0, 0.0, 0.0)
+        ));
+        camera.set_projection(Projection::Stereographic { fov: 300.0 });
+        let edge = camera.ray_direction(99.5, -0.5 - 50.0);
+        let angle = edge.normalize().dot(&Vector3::new(0.0, 0.0, -1.0)).acos();
+        assert!((angle.to_degrees() - 150.0).abs() < 1e-9);
+        assert!(camera.pixel_ray(0, 0).direction.y > 0.0);
+
+        // The angle of a fisheye ray grows evenly with the distance from the center.
+        camera.set_projection(Projection::Fisheye { fov: 180.0 });
+        assert!(close(
+            camera.ray_direction(199.5, 49.5),
+            Vector3::new(1.0, 0.0, 0.0)
+        ));
+        let quarter = camera.ray_direction(149.5, 49.5);
+        assert!(close(quarter, Vector3::new(1.0, 0.0, -1.0).normalize()));
+        camera.set_projection(Projection::Fisheye { fov: 360.0 });
+        assert!(close(
+            camera.ray_direction(199.5, 49.5),
+            Vector3::new(0.0, 0.0, 1.0)
+        ));
+    }
+
+    #[test]
+    fn test_orthographic_projection() {
+        let mut camera = Camera::new(
+            40,
+            2.0,
+            1,
+            1,
+            |_| Vector3::new(0.0, 0.0, 0.0),
+            90.0,
+            Vector3::new(0.0, 1.0, 5.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            0.0,
+            0.0,
+        );
+        camera.set_projection(Projection::Orthographic { height: 4.0 });
+
+        // All the rays are parallel, starting across a view 8 units wide and 4 high.
+        let corner = camera.pixel_ray(0, 0);
+        let center = camera.pixel_ray(20, 10);
+        assert_eq!(corner.direction, Vector3::new(0.0, 0.0, -1.0));
+        assert_eq!(center.direction, corner.direction);
+        assert!((corner.origin - Vector3::new(-3.9, 2.9, 5.0)).length() < 1e-9);
+        assert!((center.origin - Vector3::new(0.1, 0.9, 5.0)).length() < 1e-9);
+        assert_eq!(camera.clip_interval(&center), (0.0, f64::INFINITY));
+    }
+
+    #[test]
+    fn test_rolling_shutter_times() {
+        let mut camera = Camera::new(
+            20,
+            2.0,
+            1,
+            1,
+            |_| Vector3::new(0.0, 0.0, 0.0),
+            90.0,
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, -1.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            0.0,
+            0.0,
+        );
+        camera.set_time(1.0);
+        assert_eq!(camera.get_ray(3, 9).time, 1.0);
+
+        camera.set_shutter(Shutter::new(0.1).with_rolling(1.0));
+        for _ in 0..50 {
+            let top = camera.get_ray(3, 0).time;
+            let bottom = camera.get_ray(3, 9).time;
+            assert!((1.0..=1.1).contains(&top), "{}", top);
+            assert!((2.0..=2.1).contains(&bottom), "{}", bottom);
+        }
+        // Queries still look at the scene time itself.
+        assert_eq!(camera.pixel_ray(3, 9).time, 1.0);
+    }
+
+    #[test]
+    fn test_firefly_suppression() {
+        let mut camera = Camera::new(
+            4,
+            1.0,
+            1,
+            1,
+            |_| Vector3::new(0.0, 0.0, 0.0),
+            60.0,
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, -1.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            0.0,
+            1.0,
+        );
+        let bright = Vector3::new(50.0, 25.0, 0.0);
+        assert_eq!(camera.clamp_sample(bright), bright);
+        camera.set_max_radiance(Some(10.0));
+        assert_eq!(camera.clamp_sample(bright), Vector3::new(10.0, 5.0, 0.0));
+        assert_eq!(
+            camera.clamp_sample(Vector3::new(1.0, 2.0, 3.0)),
+            Vector3::new(1.0, 2.0, 3.0)
+        );
+
+        // A lone bright pixel is replaced by its neighbors, while a bright edge is kept.
+        let gray = Vector3::new(0.5, 0.5, 0.5);
+        let mut colors = vec![gray; 16];
+        colors[5] = Vector3::new(30.0, 30.0, 30.0);
+        colors[10] = Vector3::new(1.5, 1.5, 1.5);
+        assert_eq!(despeckle(&mut colors, 4), 1);
+        assert!((colors[5] - Vector3::new(0.625, 0.625, 0.625)).length() < 1e-12);
+        assert_eq!(colors[10], Vector3::new(1.5, 1.5, 1.5));
+
+        let mut edge = vec![gray; 16];
+        for pixel in edge.iter_mut().skip(8) {
+            *pixel = Vector3::new(30.0, 30.0, 30.0);
+        }
+        assert_eq!(despeckle(&mut edge, 4), 0);
+    }
+
+    #[test]
+    fn test_light_links_hold_back_light() {
+        use crate::material::{DiffuseLight, Lambertian};
+        use crate::scene::{LinkMode, DEFAULT_LAYER};
+        use crate::shapes::quad::Quad;
+
+        // A floor lit by a lamp the camera does not see.
+        let mut scene = Scene::new(Vec::new());
+        scene.add_named(
+            "floor",
+            Box::new(Quad::new(
+                Vector3::new(-5.0, 0.0, -5.0),
+                Vector3::new(10.0, 0.0, 0.0),
+                Vector3::new(0.0, 0.0, 10.0),
+                Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5))),
+            )),
+            DEFAULT_LAYER,
+        );
+        scene.add_named(
+            "lamp",
+            Box::new(Quad::new(
+                Vector3::new(-1.0, 3.0, -1.0),
+                Vector3::new(0.0, 0.0, 2.0),
+                Vector3::new(2.0, 0.0, 0.0),
+                Arc::new(DiffuseLight::new(Vector3::new(4.0, 4.0, 4.0))),
+            )),
+            DEFAULT_LAYER,
+        );
+        let camera = Camera::new(
+            8,
+            1.0,
+            4,
+            3,
+            |_| Vector3::new(0.0, 0.0, 0.0),
+            40.0,
+            Vector3::new(0.0, 2.0, 4.0),
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            0.0,
+            0.0,
+        );
+        let brightest = |scene: &Scene| {
+            camera
+                .render_linear(scene)
+                .iter()
+                .map(|color| color.max())
+                .fold(0.0, f64::max)
+        };
+        assert!(brightest(&scene) > 0.0);
+
+        // Neither the light sampled towards the lamp nor the light found by bouncing rays reaches
+        // a floor the lamp does not illuminate.
+        scene
+            .set_light_link("lamp", LinkMode::Exclude, &["floor"])
+            .unwrap();
+        assert_eq!(brightest(&scene), 0.0);
+        scene
+            .set_object_link("floor", LinkMode::Include, &["lamp"])
+            .unwrap();
+        assert_eq!(brightest(&scene), 0.0);
+        scene.clear_light_links();
+        scene
+            .set_object_link("floor", LinkMode::Include, &["lamp"])
+            .unwrap();
+        assert!(brightest(&scene) > 0.0);
+    }
+
+    #[test]
+    fn test_shadow_densities_lift_shadows() {
+        use crate::hit::Hittable;
+        use crate::material::{DiffuseLight, Lambertian, Material};
+        use crate::scene::DEFAULT_LAYER;
+        use crate::shapes::quad::Quad;
+
+        // A floor in the shadow of a wide board hanging below a lamp.
+        let quad = |corner: Vector3, size: f64, material: Arc<dyn Material>| -> Box<dyn Hittable> {
+            Box::new(Quad::new(
+                corner,
+                Vector3::new(0.0, 0.0, size),
+                Vector3::new(size, 0.0, 0.0),
+                material,
+            ))
+        };
+        let gray = Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5)));
+        let mut scene = Scene::new(Vec::new());
+        scene.add_named(
+            "floor",
+            quad(Vector3::new(-5.0, 0.0, -5.0), 10.0, gray.clone()),
+            DEFAULT_LAYER,
+        );
+        scene.add_named(
+            "board",
+            quad(Vector3::new(-5.0, 2.0, -5.0), 10.0, gray),
+            DEFAULT_LAYER,
+        );
+        scene.add_named(
+            "lamp",
+            quad(
+                Vector3::new(-1.0, 3.0, -1.0),
+                2.0,
+                Arc::new(DiffuseLight::new(Vector3::new(4.0, 4.0, 4.0))),
+            ),
+            DEFAULT_LAYER,
+        );
+        let camera = Camera::new(
+            8,
+            1.0,
+            16,
+            2,
+            |_| Vector3::new(0.0, 0.0, 0.0),
+            30.0,
+            Vector3::new(0.0, 1.0, 4.0),
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            0.0,
+            0.0,
+        );
+        let average = |scene: &Scene| {
+            let image = camera.render_linear(scene);
+            image.iter().map(|color| color.x).sum::<f64>() / image.len() as f64
+        };
+        // With two bounces, the floor only receives the light the board lets through.
+        assert_eq!(average(&scene), 0.0);
+
+        scene.set_shadow_density("lamp", &["board"], 0.0).unwrap();
+        let unshadowed = average(&scene);
+        scene.set_shadow_density("lamp", &["board"], 0.5).unwrap();
+        let half = average(&scene);
+        assert!(unshadowed > 0.05, "{}", unshadowed);
+        assert!(
+            (half / unshadowed - 0.5).abs() < 0.2,
+            "{} {}",
+            half,
+            unshadowed
+        );
+    }
+
+    #[test]
+    fn test_blank_render_warning() {
+        use crate::material::Lambertian;
+        use crate::shapes::sphere::Sphere;
+
+        let material = Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5)));
+        let sphere = Sphere::new(Vector3::new(0.0, 0.0, 5.0), 1.0, material);
+        let scene = Scene::new(vec![Box::new(sphere)]);
+        let camera_looking_at = |target: Vector3| {
+            Camera::new(
+                20,
+                1.0,
+                1,
+                1,
+                |_| Vector3::new(0.0, 0.0, 0.0),
+                60.0,
+                Vector3::new(0.0, 0.0, 0.0),
+                target,
+                Vector3::new(0.0, 1.0, 0.0),
+                0.0,
+                0.0,
+            )
+        };
+
+        // Looking away from the sphere sees nothing at all.
+        let away = camera_looking_at(Vector3::new(0.0, 0.0, -1.0));
+        let black = vec![Vector3::new(0.0, 0.0, 0.0); 400];
+        let warning = away.blank_render_warning(&scene, &black).unwrap();
+        assert!(warning.contains("entirely black"));
+        assert!(warning.contains("(-1.000, -1.000, 4.000) to (1.000, 1.000, 6.000)"));
+        assert!(warning.contains("60.0 degrees"));
+        assert!(warning.contains("0 of 400"));
+        assert!(warning.contains("pointed away"));
+
+        // Looking at the sphere sees it, but nothing lights it.
+        let towards = camera_looking_at(Vector3::new(0.0, 0.0, 1.0));
+        let warning = towards.blank_render_warning(&scene, &black).unwrap();
+        assert!(!warning.contains(" 0 of"));
+        assert!(warning.contains("no lights"));
+
+        let gray = vec![Vector3::new(0.2, 0.2, 0.2); 400];
+        let warning = towards.blank_render_warning(&scene, &gray).unwrap();
+        assert!(warning.contains("single color (0.200, 0.200, 0.200)"));
+
+        let mut varied = gray;
+        varied[7] = Vector3::new(0.3, 0.2, 0.2);
+        assert!(towards.blank_render_warning(&scene, &varied).is_none());
+    }
+
+    #[test]
+    fn test_frame_scene() {
+        use crate::material::Lambertian;
+        use crate::shapes::sphere::Sphere;
+
+        let material = Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5)));
+        let near = Sphere::new(Vector3::new(40.0, 10.0, -5.0), 2.0, material.clone());
+        let far = Sphere::new(Vector3::new(48.0, 12.0, -9.0), 3.0, material);
+        let scene = Scene::new(vec![Box::new(near), Box::new(far)]);
+        let mut camera = Camera::new(
+            40,
+            2.0,
+            1,
+            1,
+            |_| Vector3::new(0.0, 0.0, 0.0),
+            50.0,
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(1.0, 0.5, -0.5),
+            Vector3::new(0.0, 1.0, 0.0),
+            0.0,
+            3.0,
+        );
+        let forward = -camera.w;
+        camera.frame_scene(&scene, 0.1);
+        assert!((-camera.w - forward).length() < 1e-12);
+
+        // Every corner of the bounds falls inside the margins, and at least one on them.
+        let focus_dist = (camera.pixel00_loc - camera.camera_center).dot(&-camera.w);
+        let pixel_size = camera.pixel_delta_u.length();
+        let (width, height) = (camera.image_width as f64, camera.image_height as f64);
+        let mut widest: f64 = 0.0;
+        for corner in scene.bounding_box().unwrap().corners() {
+            let offset = corner - camera.camera_center;
+            let depth = offset.dot(&-camera.w);
+            assert!(depth > 0.0);
+            let x = offset.dot(&camera.u) / depth * focus_dist / pixel_size / width;
+            let y = offset.dot(&camera.v) / depth * focus_dist / pixel_size / height;
+            assert!(x.abs() <= 0.4 + 1e-9 && y.abs() <= 0.4 + 1e-9);
+            widest = widest.max(x.abs()).max(y.abs());
+        }
+        assert!((widest - 0.4).abs() < 1e-9);
+
+        // The center of the image looks at the center of the scene.
+        let ray = camera.pixel_ray(20, 10);
+        assert!(scene.hit_object(&ray, (0.001, f64::INFINITY)).is_some());
+    }
+
+    #[test]
+    fn test_clipping_planes() {
+        use crate::material::Lambertian;
+        use crate::shapes::sphere::Sphere;
+
+        let mut camera = Camera::new(
+            20,
+            2.0,
+            1,
+            1,
+            |_| Vector3::new(0.0, 0.0, 0.0),
+            90.0,
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, -1.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            0.0,
+            0.0,
+        );
+        let material = Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5)));
+        let near = Sphere::new(Vector3::new(0.0, 0.0, -3.0), 0.5, material.clone());
+        let far = Sphere::new(Vector3::new(0.0, 0.0, -8.0), 2.0, material);
+        let scene = Scene::new(vec![Box::new(near), Box::new(far)]);
+        assert_eq!(scene.pick(10, 5, &camera).unwrap().object, 0);
+
+        camera.set_clipping(4.0, f64::INFINITY);
+        assert_eq!(scene.pick(10, 5, &camera).unwrap().object, 1);
+        camera.set_clipping(0.0, 4.0);
+        assert_eq!(scene.pick(10, 5, &camera).unwrap().object, 0);
+        camera.set_clipping(4.0, 5.0);
+        assert!(scene.pick(10, 5, &camera).is_none());
+
+        // The planes are perpendicular to the viewing direction, so rays through the corners of
+        // the image reach them further away.
+        let corner = camera.pixel_ray(0, 0);
+        let (t_min, t_max) = camera.clip_interval(&corner);
+        assert!(t_min > 4.0);
+        assert!((corner.point_at(t_min).z + 4.0).abs() < 1e-9);
+        assert!((corner.point_at(t_max).z + 5.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_standard_error() {
+        let mut sums = SampleSums::default();
+        sums.add(Vector3::new(0.0, 1.0, 3.0), 0, true);
+        assert_eq!(sums.standard_error(1), Vector3::default());
+        sums.add(Vector3::new(2.0, 1.0, 3.0), 0, false);
+        // The samples of the first channel have a mean of 1 and a variance of 2.
+        let error = sums.standard_error(2);
+        assert!((error.x - 1.0).abs() < 1e-12);
+        assert_eq!((error.y, error.z), (0.0, 0.0));
+
+        let mut camera = Camera::new(
+            8,
+            1.0,
+            4,
+            2,
+            |_| Vector3::new(0.5, 0.5, 0.5),
+            90.0,
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, -1.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            0.0,
+            1.0,
+        );
+        camera.set_seed(1);
+        let scene = Scene::new(Vec::new());
+        let passes = camera.render_passes(&scene);
+        assert_eq!(passes.color, camera.render_linear(&scene));
+        assert!(passes.error.iter().all(|error| error.length() < 1e-9));
+    }
+
+    #[test]
+    fn test_path_lengths() {
+        use crate::material::Lambertian;
+        use crate::shapes::sphere::Sphere;
+
+        let camera = Camera::new(
+            8,
+            1.0,
+            3,
+            6,
+            |_| Vector3::new(0.5, 0.5, 0.5),
+            90.0,
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, -1.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            0.0,
+            1.0,
+        );
+
+        // Paths escape to the background right away.
+        let empty = camera.render_passes(&Scene::new(Vec::new()));
+        assert_eq!(empty.paths.total(), 8 * 8 * 3);
+        assert_eq!(empty.paths.mean(), 0.0);
+        assert!(empty.bounces.iter().all(|&bounces| bounces == 0.0));
+
+        // Inside a closed diffuse sphere, every path is cut off by the depth limit.
+        let enclosed = camera.render_passes(&Scene::new(vec![Box::new(Sphere::new(
+            Vector3::new(0.0, 0.0, 0.0),
+            10.0,
+            Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5))),
+        ))]));
+        assert_eq!(enclosed.paths.truncated_fraction(), 1.0);
+        assert!(enclosed.bounces.iter().all(|&bounces| bounces == 6.0));
+    }
+
+    #[test]
+    fn test_sample_ranges() {
+        use crate::material::Lambertian;
+        use crate::shapes::sphere::Sphere;
+
+        let scene = Scene::new(vec![Box::new(Sphere::new(
+            Vector3::new(0.0, 0.0, -2.0),
+            1.0,
+            Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5))),
+        ))]);
+        let mut camera = Camera::new(
+            8,
+            1.0,
+            16,
+            4,
+            |_| Vector3::new(0.5, 0.7, 1.0),
+            90.0,
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, -1.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            0.0,
+            1.0,
+        );
+        camera.set_seed(7);
+        let render = |camera: &mut Camera, range: Range<u32>| {
+            camera.set_sample_range(range);
+            camera.render_passes(&scene).accumulation
+        };
+
+        // A range renders as many samples as it covers, and the same samples on every run.
+        let second = render(&mut camera, 4..8);
+        assert_eq!(camera.samples_per_pixel(), 4);
+        assert_eq!(second.ranges, vec![4..8]);
+        assert_eq!(render(&mut camera, 4..8), second);
+
+        // Other ranges trace other samples, which add up to the samples of both.
+        let mut merged = render(&mut camera, 0..4);
+        assert_ne!(merged.sums, second.sums);
+        merged.merge(&second).unwrap();
+        assert_eq!(merged.samples(), 8);
+        assert_eq!(merged.ranges, vec![0..4, 4..8]);
+        assert!(merged.merge(&render(&mut camera, 6..10)).is_err());
+    }
 }
@@ -0,0 +1,441 @@
+//! Parsing of the command-line arguments choosing the scene to render and overriding the
+//! settings of its camera, such as `--scene cornell_box --width 800 --samples 500 -o out.png`.
+
+use crate::camera::RenderSettings;
+use crate::output::ResponseCurves;
+use crate::parameter_sweep::Sweep;
+use std::path::Path;
+
+/// The scene rendered when no scene is given.
+pub const DEFAULT_SCENE: &str = "final_scene_preview";
+
+/// The command merging accumulation files into an image, given in place of a scene.
+pub const MERGE_COMMAND: &str = "merge";
+
+/// The command laying out the renders of a directory as a contact sheet, given in place of a scene.
+pub const CONTACT_SHEET_COMMAND: &str = "contact-sheet";
+
+/// Describes the options accepted on the command line.
+pub const USAGE: &str = "\
+Usage: raytracer [options] [scene]
+       raytracer merge [options] <accumulation>...
+       raytracer contact-sheet [options] <directory>
+
+Merging writes the image of the samples of all the accumulation files to the output, and with
+`--accumulation` their merged sums, so renders of sample ranges can be combined and resumed.
+
+A contact sheet lays out thumbnails of the images of the directory `--width` pixels wide in rows
+of `--columns`, labeled with the samples and render time of the reports saved with `--report`.
+
+Options:
+  -s, --scene <name>      The scene to render
+  -w, --width <pixels>    The width of the image
+  -n, --samples <count>   The number of samples per pixel
+  -d, --max-depth <count> The maximum number of bounces of a ray
+  -o, --output <path>     The path of the image file
+      --job <path>        Render a job file without any interaction and print a report
+      --daemon <directory>
+                          Watch the directory for job files and render them until a file
+                          named `stop` appears in it
+      --daemon-output <directory>
+                          The directory the daemon saves the images in, by default
+                          `output` next to the watched directory
+      --daemon-errors <directory>
+                          The directory the daemon moves failed jobs to, by default
+                          `failed` next to the watched directory
+      --deep <path>       Also write a deep OpenEXR image to the path
+      --error             Also write the standard error of every pixel next to the image
+      --bounces           Also write the average number of bounces of every pixel
+      --aov               Also write the albedo, normal, depth, object and material IDs
+                          and motion vectors
+      --alpha             Write the coverage of every pixel into an alpha channel
+      --preview           Show the image in a window while it renders
+      --progressive       Preview the whole image at 1/8 and 1/4 resolution before its tiles
+      --check             Warn about overlapping objects before rendering
+      --clamp <radiance>  Clamp the brightness of every sample to suppress fireflies
+      --despeckle         Remove isolated bright pixels after rendering
+      --denoise           Smooth the noise of the image along its edges and textures
+      --film <stock>      Finish the image with a film stock (contrast, portrait, slide,
+                          cinema) or the response curves of a .cube file
+      --seed <number>     Render the same image on every run
+      --sample-range <a..b>
+                          Trace only samples a to b - 1 of every pixel, for splitting a
+                          seeded render between machines
+      --accumulation <path>
+                          Also write the sums of the samples of every pixel, for merging
+      --report            Also write the samples and render time next to the image
+      --columns <count>   The number of thumbnails in a row of a contact sheet
+      --sweep <parameter>=<a..b>
+                          Render the scene for values of roughness, ior, light or samples
+                          from a to b, side by side in a labeled strip
+      --steps <count>     The number of values of a sweep
+      --flamegraph <path> Write the time spent in every step of the render as folded stacks,
+                          in builds with the `flamegraph` feature
+  -t, --threads <count>   The number of render threads
+  -l, --list              List the available scenes
+  -h, --help              Print this message";
+
+/// Represents the parsed command-line arguments.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Arguments {
+    /// The name of the scene to render.
+    pub scene: String,
+    /// The settings overriding the ones chosen by the scene for its camera.
+    pub settings: RenderSettings,
+    /// The path of the job file to render without any interaction, if any.
+    pub job: Option<String>,
+    /// The directory watched for job files by the render daemon, if it is started.
+    pub daemon: Option<String>,
+    /// The directory the render daemon saves the images in, or `None` for the default.
+    pub daemon_output: Option<String>,
+    /// The directory the render daemon moves failed jobs to, or `None` for the default.
+    pub daemon_errors: Option<String>,
+    /// The files given after a command, such as the accumulation files to merge.
+    pub inputs: Vec<String>,
+    /// The number of thumbnails in a row of a contact sheet, or `None` for the default.
+    pub columns: Option<u32>,
+    /// The parameter sweep to render instead of a single image, if any.
+    pub sweep: Option<Sweep>,
+    /// The number of render threads, or `None` to use every core.
+    pub threads: Option<usize>,
+    /// The path of the file of folded stacks written by the profiler, if any.
+    pub flamegraph: Option<String>,
+    /// Whether the usage message was asked for.
+    pub help: bool,
+    /// Whether the list of scenes was asked for.
+    pub list: bool,
+}
+
+impl Arguments {
+    /// Parses the command-line arguments. Options take their value either as the next argument
+    /// or after an equals sign, as in `--width=800`.
+    ///
+    /// # Arguments
+    ///
+    /// * `arguments` - The arguments, without the name of the program.
+    ///
+    /// # Returns
+    ///
+    /// The parsed `Arguments`, or a description of the first invalid argument.
+    pub fn parse<I>(arguments: I) -> Result<Arguments, String>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut parsed = Arguments {
+            scene: DEFAULT_SCENE.to_string(),
+            settings: RenderSettings::default(),
+            job: None,
+            daemon: None,
+            daemon_output: None,
+            daemon_errors: None,
+            inputs: Vec::new(),
+            columns: None,
+            sweep: None,
+            threads: None,
+            flamegraph: None,
+            help: false,
+            list: false,
+        };
+        let mut scene = None;
+        let mut steps = None;
+
+        let mut arguments = arguments.into_iter();
+        while let Some(argument) = arguments.next() {
+            let (option, inline_value) = match argument.split_once('=') {
+                Some((option, value)) if argument.starts_with("--") => {
+                    (option.to_string(), Some(value.to_string()))
+                }
+                _ => (argument.clone(), None),
+            };
+            let mut value = || {
+                inline_value
+                    .clone()
+                    .or_else(|| arguments.next())
+                    .ok_or_else(|| format!("missing value for `{}`", option))
+            };
+
+            match option.as_str() {
+                "-h" | "--help" => parsed.help = true,
+                "-l" | "--list" => parsed.list = true,
+                "-s" | "--scene" => scene = Some(value()?),
+                "-w" | "--width" => parsed.settings.width = Some(Self::number(&option, value()?)?),
+                "-n" | "--samples" => {
+                    parsed.settings.samples = Some(Self::number(&option, value()?)?)
+                }
+                "-d" | "--max-depth" => {
+                    parsed.settings.max_depth = Some(Self::number(&option, value()?)?)
+                }
+                "-o" | "--output" => parsed.settings.output = Some(value()?),
+                "--job" => parsed.job = Some(value()?),
+                "--daemon" => parsed.daemon = Some(value()?),
+                "--daemon-output" => parsed.daemon_output = Some(value()?),
+                "--daemon-errors" => parsed.daemon_errors = Some(value()?),
+                "--deep" => parsed.settings.deep = Some(value()?),
+                "--error" => parsed.settings.error = true,
+                "--bounces" => parsed.settings.bounces = true,
+                "--aov" => parsed.settings.aov = true,
+                "--alpha" => parsed.settings.alpha = true,
+                "--preview" => parsed.settings.preview = true,
+                "--progressive" => {
+                    parsed.settings.preview = true;
+                    parsed.settings.progressive = true;
+                }
+                "--check" => parsed.settings.check = true,
+                "--clamp" => {
+                    parsed.settings.max_radiance = Some(Self::positive(&option, value()?)?)
+                }
+                "--despeckle" => parsed.settings.despeckle = true,
+                "--denoise" => parsed.settings.denoise = true,
+                "--film" => {
+                    let value = value()?;
+                    parsed.settings.film = Some(
+                        ResponseCurves::find(&value, Path::new("."))
+                            .map_err(|e| format!("invalid value for `{}`: {}", option, e))?,
+                    )
+                }
+                "--seed" => {
+                    let value = value()?;
+                    parsed.settings.seed = Some(value.parse().map_err(|_| {
+                        format!(
+                            "invalid value for `{}`: expected a whole number, found `{}`",
+                            option, value
+                        )
+                    })?)
+                }
+                "--sample-range" => {
+                    let value = value()?;
+                    let invalid = || {
+                        format!(
+                            "invalid value for `{}`: expected a range such as `0..250`, found `{}`",
+                            option, value
+                        )
+                    };
+                    let (start, end) = value.split_once("..").ok_or_else(invalid)?;
+                    let start: u32 = start.parse().map_err(|_| invalid())?;
+                    let end: u32 = end.parse().map_err(|_| invalid())?;
+                    if start >= end {
+                        return Err(invalid());
+                    }
+                    parsed.settings.sample_range = Some(start..end)
+                }
+                "--accumulation" => parsed.settings.accumulation = Some(value()?),
+                "--report" => parsed.settings.report = true,
+                "--columns" => parsed.columns = Some(Self::number(&option, value()?)?),
+                "--sweep" => {
+                    parsed.sweep = Some(
+                        Sweep::parse(&value()?)
+                            .map_err(|e| format!("invalid value for `{}`: {}", option, e))?,
+                    )
+                }
+                "--steps" => steps = Some(Self::number(&option, value()?)?),
+                "--flamegraph" => parsed.flamegraph = Some(value()?),
+                "-t" | "--threads" => parsed.threads = Some(Self::number(&option, value()?)?),
+                _ if option.starts_with('-') => return Err(format!("unknown option `{}`", option)),
+                _ if scene.is_none() => scene = Some(argument),
+                _ if scene.as_deref() == Some(MERGE_COMMAND) => parsed.inputs.push(argument),
+                _ if scene.as_deref() == Some(CONTACT_SHEET_COMMAND)
+                    && parsed.inputs.is_empty() =>
+                {
+                    parsed.inputs.push(argument)
+                }
+                _ => return Err(format!("unexpected argument `{}`", argument)),
+            }
+        }
+
+        if let Some(scene) = scene {
+            parsed.scene = scene;
+        }
+        if parsed.daemon.is_none()
+            && (parsed.daemon_output.is_some() || parsed.daemon_errors.is_some())
+        {
+            return Err("`--daemon-output` and `--daemon-errors` need a `--daemon`".to_string());
+        }
+        if let Some(steps) = steps {
+            let sweep = parsed.sweep.as_mut().ok_or("`--steps` needs a `--sweep`")?;
+            *sweep = sweep.with_steps(steps);
+        }
+        if parsed.inputs.is_empty() && !parsed.help {
+            if parsed.scene == MERGE_COMMAND {
+                return Err(format!("`{}` needs accumulation files", MERGE_COMMAND));
+            }
+            if parsed.scene == CONTACT_SHEET_COMMAND {
+                return Err(format!("`{}` needs a directory", CONTACT_SHEET_COMMAND));
+            }
+        }
+        Ok(parsed)
+    }
+
+    /// Parses the value of a numeric option.
+    ///
+    /// # Arguments
+    ///
+    /// * `option` - The option, used in the error message.
+    /// * `value` - The text of the value.
+    ///
+    /// # Returns
+    ///
+    /// The parsed value, or a description of why it is invalid. Zero is rejected, as every
+    /// numeric option counts something that cannot be empty.
+    fn number<T>(option: &str, value: String) -> Result<T, String>
+    where
+        T: std::str::FromStr + Default + PartialEq,
+    {
+        match value.parse::<T>() {
+            Ok(number) if number != T::default() => Ok(number),
+            _ => Err(format!(
+                "invalid value for `{}`: expected a positive number, found `{}`",
+                option, value
+            )),
+        }
+    }
+
+    /// Parses the value of an option that takes a positive real number.
+    ///
+    /// # Arguments
+    ///
+    /// * `option` - The option, used in the error message.
+    /// * `value` - The text of the value.
+    ///
+    /// # Returns
+    ///
+    /// The parsed value, or a description of why it is invalid.
+    fn positive(option: &str, value: String) -> Result<f64, String> {
+        match value.parse::<f64>() {
+            Ok(number) if number > 0.0 && number.is_finite() => Ok(number),
+            _ => Err(format!(
+                "invalid value for `{}`: expected a positive number, found `{}`",
+                option, value
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::FilmStock;
+    use crate::parameter_sweep::SweepParameter;
+
+    fn parse(arguments: &str) -> Result<Arguments, String> {
+        Arguments::parse(arguments.split_whitespace().map(str::to_string))
+    }
+
+    #[test]
+    fn test_parse_options() {
+        let arguments = parse(
+            "--scene cornell_box --width 800 --samples=500 -d 12 -o out.png -t 4 --deep deep.exr --error",
+        )
+        .unwrap();
+        assert_eq!(arguments.scene, "cornell_box");
+        assert_eq!(
+            arguments.settings,
+            RenderSettings {
+                width: Some(800),
+                samples: Some(500),
+                max_depth: Some(12),
+                output: Some("out.png".to_string()),
+                deep: Some("deep.exr".to_string()),
+                error: true,
+                bounces: false,
+                aov: false,
+                alpha: false,
+                preview: false,
+                progressive: false,
+                check: false,
+                max_radiance: None,
+                despeckle: false,
+                denoise: false,
+                film: None,
+                seed: None,
+                sample_range: None,
+                accumulation: None,
+                report: false,
+                parameter: None,
+            }
+        );
+        assert_eq!(arguments.threads, Some(4));
+        assert!(!arguments.help && !arguments.list);
+
+        let defaults = parse("").unwrap();
+        assert_eq!(defaults.scene, DEFAULT_SCENE);
+        assert_eq!(defaults.settings, RenderSettings::default());
+        assert_eq!(parse("caustics -n 10").unwrap().scene, "caustics");
+        assert!(parse("--help").unwrap().help);
+        assert!(parse("--bounces").unwrap().settings.bounces);
+        assert!(parse("--aov").unwrap().settings.aov);
+        assert!(parse("--alpha").unwrap().settings.alpha);
+        assert!(parse("--preview").unwrap().settings.preview);
+        let progressive = parse("--progressive").unwrap().settings;
+        assert!(progressive.preview && progressive.progressive);
+        assert!(parse("--check").unwrap().settings.check);
+        let clamped = parse("--clamp 20 --despeckle").unwrap().settings;
+        assert_eq!(clamped.max_radiance, Some(20.0));
+        assert!(clamped.despeckle);
+        assert!(parse("--denoise").unwrap().settings.denoise);
+        assert_eq!(
+            parse("--job farm/frame.job").unwrap().job.as_deref(),
+            Some("farm/frame.job")
+        );
+        let daemon = parse("--daemon farm/jobs --daemon-output farm/images").unwrap();
+        assert_eq!(daemon.daemon.as_deref(), Some("farm/jobs"));
+        assert_eq!(daemon.daemon_output.as_deref(), Some("farm/images"));
+        assert_eq!(daemon.daemon_errors, None);
+        assert_eq!(parse("--seed 0").unwrap().settings.seed, Some(0));
+        assert_eq!(
+            parse("--film slide").unwrap().settings.film,
+            Some(FilmStock::Slide.curves())
+        );
+        assert!(parse("--film velvia").is_err());
+        let split = parse("--sample-range 250..500 --accumulation b.acc").unwrap();
+        assert_eq!(split.settings.sample_range, Some(250..500));
+        assert_eq!(split.settings.accumulation.as_deref(), Some("b.acc"));
+        let merge = parse("merge a.acc -o final.exr b.acc").unwrap();
+        assert_eq!(merge.scene, MERGE_COMMAND);
+        assert_eq!(merge.inputs, vec!["a.acc", "b.acc"]);
+        assert_eq!(merge.settings.output.as_deref(), Some("final.exr"));
+        assert!(parse("--report").unwrap().settings.report);
+        assert_eq!(
+            parse("--flamegraph render.folded")
+                .unwrap()
+                .flamegraph
+                .as_deref(),
+            Some("render.folded")
+        );
+        let sheet = parse("contact-sheet renders --columns 6 -w 200").unwrap();
+        assert_eq!(sheet.scene, CONTACT_SHEET_COMMAND);
+        assert_eq!(sheet.inputs, vec!["renders"]);
+        assert_eq!(sheet.columns, Some(6));
+        assert_eq!(sheet.settings.width, Some(200));
+        let sweep = parse("material_study --sweep ior=1..2 --steps 3").unwrap();
+        assert_eq!(
+            sweep.sweep,
+            Some(Sweep::new(SweepParameter::RefractionIndex, 1.0, 2.0).with_steps(3))
+        );
+        assert_eq!(sweep.scene, "material_study");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse("--width"),
+            Err("missing value for `--width`".to_string())
+        );
+        assert!(parse("--width wide").is_err());
+        assert!(parse("--samples 0").is_err());
+        assert!(parse("--threads -2").is_err());
+        assert!(parse("--clamp 0").is_err());
+        assert!(parse("--clamp=-1").is_err());
+        assert!(parse("--seed lucky").is_err());
+        assert!(parse("--sample-range 250").is_err());
+        assert!(parse("--sample-range 500..250").is_err());
+        assert!(parse("--colour red").is_err());
+        assert!(parse("spheres earth").is_err());
+        assert!(parse("merge -o final.png").is_err());
+        assert!(parse("contact-sheet").is_err());
+        assert!(parse("contact-sheet renders others").is_err());
+        assert!(parse("--columns 0").is_err());
+        assert!(parse("--sweep gloss=0..1").is_err());
+        assert!(parse("--steps 4").is_err());
+        assert!(parse("--daemon-errors failed").is_err());
+    }
+}
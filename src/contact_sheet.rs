@@ -0,0 +1,496 @@
+//! Contact sheets: grids of thumbnails of the renders in a directory, each labeled with the name
+//! of its image and, when a render report was saved next to it with `--report`, its number of
+//! samples per pixel and render time, to compare batch runs over the built-in scenes or sweeps of
+//! settings at a glance.
+//!
+//! Thumbnails are downscaled in linear light, averaging the decoded colors of the pixels they
+//! cover, so fine detail such as noise or thin lines keeps its brightness instead of darkening as
+//! it would if the encoded values were averaged.
+
+use crate::headless::RenderReport;
+use crate::output::{self, Encoding, OutputTransform};
+use crate::vector3::Vector3;
+use image::{Rgb, RgbImage};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The number of thumbnails in a row by default.
+const DEFAULT_COLUMNS: u32 = 4;
+
+/// The width of the thumbnails in pixels by default.
+const DEFAULT_THUMBNAIL_WIDTH: u32 = 240;
+
+/// The space around and between the thumbnails, in pixels.
+const PADDING: u32 = 8;
+
+/// The color of the background of the sheet.
+const BACKGROUND: Rgb<u8> = Rgb([32, 32, 32]);
+
+/// The color of the labels.
+const LABEL_COLOR: Rgb<u8> = Rgb([230, 230, 230]);
+
+/// The width of a glyph of the label font in pixels, before scaling.
+const GLYPH_WIDTH: u32 = 5;
+
+/// The height of a glyph of the label font in pixels, before scaling.
+const GLYPH_HEIGHT: u32 = 7;
+
+/// The rows of the glyphs of the label font, five pixels wide with the leftmost pixel in the
+/// highest bit. Lowercase letters are drawn as uppercase ones, and unknown characters as `?`.
+const GLYPHS: [(char, [u8; 7]); 50] = [
+    ('A', [0x0E, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11]),
+    ('B', [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E]),
+    ('C', [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E]),
+    ('D', [0x1E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x1E]),
+    ('E', [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F]),
+    ('F', [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10]),
+    ('G', [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F]),
+    ('H', [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11]),
+    ('I', [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E]),
+    ('J', [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C]),
+    ('K', [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11]),
+    ('L', [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F]),
+    ('M', [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11]),
+    ('N', [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11]),
+    ('O', [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E]),
+    ('P', [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10]),
+    ('Q', [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D]),
+    ('R', [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11]),
+    ('S', [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E]),
+    ('T', [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04]),
+    ('U', [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E]),
+    ('V', [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04]),
+    ('W', [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A]),
+    ('X', [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11]),
+    ('Y', [0x11, 0x11, 0x11, 0x0A, 0x04, 0x04, 0x04]),
+    ('Z', [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F]),
+    ('0', [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E]),
+    ('1', [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E]),
+    ('2', [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F]),
+    ('3', [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E]),
+    ('4', [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02]),
+    ('5', [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E]),
+    ('6', [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E]),
+    ('7', [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08]),
+    ('8', [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E]),
+    ('9', [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C]),
+    (' ', [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]),
+    ('.', [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C]),
+    (',', [0x00, 0x00, 0x00, 0x00, 0x0C, 0x04, 0x08]),
+    ('_', [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1F]),
+    ('-', [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00]),
+    (':', [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x0C, 0x00]),
+    ('=', [0x00, 0x00, 0x1F, 0x00, 0x1F, 0x00, 0x00]),
+    ('/', [0x00, 0x01, 0x02, 0x04, 0x08, 0x10, 0x00]),
+    ('(', [0x02, 0x04, 0x08, 0x08, 0x08, 0x04, 0x02]),
+    (')', [0x08, 0x04, 0x02, 0x02, 0x02, 0x04, 0x08]),
+    ('%', [0x18, 0x19, 0x02, 0x04, 0x08, 0x13, 0x03]),
+    ('+', [0x00, 0x04, 0x04, 0x1F, 0x04, 0x04, 0x00]),
+    ('#', [0x0A, 0x0A, 0x1F, 0x0A, 0x1F, 0x0A, 0x0A]),
+    ('?', [0x0E, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04]),
+];
+
+/// Lays out the renders of a directory as a grid of labeled thumbnails.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ContactSheet {
+    /// The number of thumbnails in a row.
+    columns: u32,
+    /// The width of the thumbnails in pixels.
+    thumbnail_width: u32,
+}
+
+impl Default for ContactSheet {
+    fn default() -> ContactSheet {
+        ContactSheet::new()
+    }
+}
+
+impl ContactSheet {
+    /// Creates a new contact sheet layout.
+    ///
+    /// # Returns
+    ///
+    /// A new `ContactSheet` instance with four thumbnails 240 pixels wide in a row.
+    pub fn new() -> ContactSheet {
+        ContactSheet {
+            columns: DEFAULT_COLUMNS,
+            thumbnail_width: DEFAULT_THUMBNAIL_WIDTH,
+        }
+    }
+
+    /// Sets the number of thumbnails in a row.
+    ///
+    /// # Arguments
+    ///
+    /// * `columns` - The number of thumbnails in a row, at least one.
+    ///
+    /// # Returns
+    ///
+    /// The updated `ContactSheet` instance.
+    pub fn with_columns(mut self, columns: u32) -> ContactSheet {
+        self.columns = columns.max(1);
+        self
+    }
+
+    /// Sets the width of the thumbnails. Their height follows the aspect ratio of every image.
+    ///
+    /// # Arguments
+    ///
+    /// * `width` - The width of the thumbnails in pixels, at least one.
+    ///
+    /// # Returns
+    ///
+    /// The updated `ContactSheet` instance.
+    pub fn with_thumbnail_width(mut self, width: u32) -> ContactSheet {
+        self.thumbnail_width = width.max(1);
+        self
+    }
+
+    /// Renders the contact sheet of a list of images, in the given order, each labeled with its
+    /// name and the samples and render time of its report. Images that cannot be read are left out
+    /// with a warning.
+    ///
+    /// # Arguments
+    ///
+    /// * `images` - The paths of the images.
+    ///
+    /// # Returns
+    ///
+    /// The contact sheet, or a description of why none of the images could be read.
+    pub fn render(&self, images: &[PathBuf]) -> Result<RgbImage, String> {
+        let labeled: Vec<(PathBuf, Vec<String>)> = images
+            .iter()
+            .map(|path| (path.clone(), label(path)))
+            .collect();
+        self.render_labeled(&labeled)
+    }
+
+    /// Renders the contact sheet of a list of images with the given labels, in the given order.
+    /// Images that cannot be read are left out with a warning.
+    ///
+    /// # Arguments
+    ///
+    /// * `images` - The paths of the images and the lines of their labels.
+    ///
+    /// # Returns
+    ///
+    /// The contact sheet, or a description of why none of the images could be read.
+    pub fn render_labeled(&self, images: &[(PathBuf, Vec<String>)]) -> Result<RgbImage, String> {
+        let thumbnails: Vec<(RgbImage, &Vec<String>)> = images
+            .iter()
+            .filter_map(|(path, label)| match load(path) {
+                Ok(image) => {
+                    let height = (image.height() as u64 * self.thumbnail_width as u64
+                        / image.width().max(1) as u64)
+                        .max(1) as u32;
+                    let thumbnail = downscale(&image, self.thumbnail_width, height);
+                    Some((thumbnail, label))
+                }
+                Err(e) => {
+                    eprintln!("Skipping {}: {}", path.display(), e);
+                    None
+                }
+            })
+            .collect();
+        if thumbnails.is_empty() {
+            return Err("no images to put on the contact sheet".to_string());
+        }
+
+        let scale = (self.thumbnail_width / 160).max(1);
+        let line_height = (GLYPH_HEIGHT + 3) * scale;
+        let lines = thumbnails
+            .iter()
+            .map(|(_, label)| label.len())
+            .max()
+            .unwrap_or(0) as u32;
+        let thumbnail_height = thumbnails
+            .iter()
+            .map(|(t, _)| t.height())
+            .max()
+            .unwrap_or(0);
+        let cell_width = self.thumbnail_width + PADDING;
+        let cell_height = thumbnail_height + PADDING / 2 + lines * line_height + PADDING;
+
+        let columns = self.columns.min(thumbnails.len() as u32);
+        let rows = (thumbnails.len() as u32).div_ceil(columns);
+        let mut sheet = RgbImage::from_pixel(
+            columns * cell_width + PADDING,
+            rows * cell_height + PADDING,
+            BACKGROUND,
+        );
+        let max_characters = (self.thumbnail_width / ((GLYPH_WIDTH + 1) * scale)) as usize;
+        for (index, (thumbnail, label)) in thumbnails.iter().enumerate() {
+            let x = PADDING + (index as u32 % columns) * cell_width;
+            let y = PADDING + (index as u32 / columns) * cell_height;
+            image::imageops::replace(&mut sheet, thumbnail, x as i64, y as i64);
+            for (line, text) in label.iter().enumerate() {
+                let text: String = text.chars().take(max_characters).collect();
+                let line_y = y + thumbnail_height + PADDING / 2 + line as u32 * line_height;
+                draw_text(&mut sheet, x, line_y, &text, scale);
+            }
+        }
+        Ok(sheet)
+    }
+}
+
+/// Lists the images in a directory that can be put on a contact sheet, sorted by name.
+///
+/// # Arguments
+///
+/// * `directory` - The directory of the images.
+///
+/// # Returns
+///
+/// The paths of the images, or a description of why the directory could not be read.
+pub fn images_in(directory: &Path) -> Result<Vec<PathBuf>, String> {
+    let entries = fs::read_dir(directory)
+        .map_err(|e| format!("failed to read {}: {}", directory.display(), e))?;
+    let mut images: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file() && image::ImageFormat::from_path(path).is_ok())
+        .collect();
+    images.sort();
+    Ok(images)
+}
+
+/// Loads an image as 8-bit colors. The linear colors of OpenEXR and Radiance HDR images go
+/// through the default output transform, like renders saved to other formats.
+///
+/// # Arguments
+///
+/// * `path` - The path of the image.
+///
+/// # Returns
+///
+/// The image, or a description of why it could not be read.
+fn load(path: &Path) -> Result<RgbImage, String> {
+    let image = image::open(path).map_err(|e| e.to_string())?;
+    if !output::is_high_dynamic_range(path) {
+        return Ok(image.to_rgb8());
+    }
+
+    let linear = image.to_rgb32f();
+    let transform = OutputTransform::default();
+    Ok(RgbImage::from_fn(
+        linear.width(),
+        linear.height(),
+        |x, y| {
+            let Rgb([r, g, b]) = *linear.get_pixel(x, y);
+            transform.apply(Vector3::new(r as f64, g as f64, b as f64))
+        },
+    ))
+}
+
+/// Returns the lines labeling the thumbnail of an image: its name, or the name of its scene and its
+/// number of samples per pixel and render time if a render report was saved next to the image.
+///
+/// # Arguments
+///
+/// * `path` - The path of the image.
+fn label(path: &Path) -> Vec<String> {
+    let report = fs::read_to_string(path.with_extension("report"))
+        .ok()
+        .and_then(|contents| RenderReport::parse(&contents).ok());
+    let name = match &report {
+        Some(report) => report.scene.clone(),
+        None => path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+    };
+    match report_line(path) {
+        Some(line) => vec![name, line],
+        None => vec![name],
+    }
+}
+
+/// Describes the number of samples per pixel and the render time of an image, as `64 spp, 2.5 s`,
+/// from the render report saved next to it.
+///
+/// # Arguments
+///
+/// * `path` - The path of the image.
+///
+/// # Returns
+///
+/// The description, or `None` if no valid report was saved next to the image.
+pub fn report_line(path: &Path) -> Option<String> {
+    let contents = fs::read_to_string(path.with_extension("report")).ok()?;
+    let report = RenderReport::parse(&contents).ok()?;
+    let milliseconds = report.elapsed.as_millis();
+    let time = if milliseconds < 1000 {
+        format!("{} ms", milliseconds)
+    } else {
+        format!("{:.1} s", milliseconds as f64 / 1000.0)
+    };
+    Some(format!("{} spp, {}", report.samples, time))
+}
+
+/// Downscales an image in linear light: every pixel of the result averages the decoded colors of
+/// the pixels of the image it covers, and the average is encoded again. Images are decoded as
+/// sRGB, the encoding image files are assumed to have.
+///
+/// # Arguments
+///
+/// * `image` - The image.
+/// * `width` - The width of the result in pixels.
+/// * `height` - The height of the result in pixels.
+///
+/// # Returns
+///
+/// The downscaled image. Images smaller than the result are enlarged by repeating their pixels.
+pub fn downscale(image: &RgbImage, width: u32, height: u32) -> RgbImage {
+    let encoding = Encoding::Srgb;
+    let span = |index: u32, size: u32, source: u32| {
+        let start = (index as u64 * source as u64 / size as u64) as u32;
+        let end = ((index as u64 + 1) * source as u64 / size as u64) as u32;
+        start..end.max(start + 1).min(source)
+    };
+
+    RgbImage::from_fn(width, height, |x, y| {
+        let mut sum = [0.0; 3];
+        let mut count = 0.0;
+        for source_y in span(y, height, image.height()) {
+            for source_x in span(x, width, image.width()) {
+                let Rgb(pixel) = image.get_pixel(source_x, source_y);
+                for (sum, &value) in sum.iter_mut().zip(pixel) {
+                    *sum += encoding.decode(value as f64 / 255.0);
+                }
+                count += 1.0;
+            }
+        }
+        Rgb(sum.map(|sum| (encoding.encode(sum / count) * 255.0).round() as u8))
+    })
+}
+
+/// Draws a line of text with the label font.
+///
+/// # Arguments
+///
+/// * `image` - The image to draw on.
+/// * `x` - The left edge of the text in pixels.
+/// * `y` - The top edge of the text in pixels.
+/// * `text` - The text.
+/// * `scale` - The size of a pixel of the font in pixels of the image.
+fn draw_text(image: &mut RgbImage, x: u32, y: u32, text: &str, scale: u32) {
+    for (index, character) in text.chars().enumerate() {
+        let glyph = glyph(character);
+        let left = x + index as u32 * (GLYPH_WIDTH + 1) * scale;
+        for (row, bits) in glyph.iter().enumerate() {
+            for column in 0..GLYPH_WIDTH {
+                if bits & (1 << (GLYPH_WIDTH - 1 - column)) == 0 {
+                    continue;
+                }
+                for dy in 0..scale {
+                    for dx in 0..scale {
+                        let (px, py) = (left + column * scale + dx, y + row as u32 * scale + dy);
+                        if px < image.width() && py < image.height() {
+                            image.put_pixel(px, py, LABEL_COLOR);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Returns the rows of the glyph of a character of the label font.
+///
+/// # Arguments
+///
+/// * `character` - The character.
+fn glyph(character: char) -> [u8; 7] {
+    let character = character.to_ascii_uppercase();
+    GLYPHS
+        .iter()
+        .find(|(known, _)| *known == character)
+        .or_else(|| GLYPHS.iter().find(|(known, _)| *known == '?'))
+        .map(|(_, rows)| *rows)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_downscale_in_linear_light() {
+        // A checkerboard of black and white pixels averages to half the light, which is encoded
+        // brighter than half the value.
+        let checker = RgbImage::from_fn(4, 4, |x, y| {
+            if (x + y) % 2 == 0 {
+                Rgb([255, 255, 255])
+            } else {
+                Rgb([0, 0, 0])
+            }
+        });
+        let small = downscale(&checker, 2, 2);
+        let expected = (Encoding::Srgb.encode(0.5) * 255.0).round() as u8;
+        assert!(small.pixels().all(|pixel| *pixel == Rgb([expected; 3])));
+        assert!(expected > 180);
+
+        // Enlarging repeats pixels.
+        let large = downscale(&checker, 8, 8);
+        assert_eq!(*large.get_pixel(1, 1), Rgb([255, 255, 255]));
+        assert_eq!(*large.get_pixel(2, 0), Rgb([0, 0, 0]));
+    }
+
+    #[test]
+    fn test_contact_sheet() {
+        let directory = std::env::temp_dir().join(format!("contact_sheet_{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let wide = RgbImage::from_pixel(64, 32, Rgb([200, 40, 40]));
+        let square = RgbImage::from_pixel(32, 32, Rgb([40, 200, 40]));
+        wide.save(directory.join("b_wide.png")).unwrap();
+        square.save(directory.join("a_square.png")).unwrap();
+        fs::write(directory.join("b_wide.report"), "samples = 1").unwrap();
+        fs::write(directory.join("notes.txt"), "not an image").unwrap();
+
+        let images = images_in(&directory).unwrap();
+        assert_eq!(
+            images,
+            vec![directory.join("a_square.png"), directory.join("b_wide.png")]
+        );
+        assert_eq!(label(&images[0]), vec!["a_square"]);
+        // Incomplete reports are ignored.
+        assert_eq!(label(&images[1]), vec!["b_wide"]);
+
+        let sheet = ContactSheet::new()
+            .with_columns(3)
+            .with_thumbnail_width(40)
+            .render(&images)
+            .unwrap();
+        // Two thumbnails side by side, the tallest one 40 pixels high, with a line of label.
+        assert_eq!(sheet.width(), 2 * (40 + PADDING) + PADDING);
+        assert_eq!(
+            sheet.height(),
+            40 + PADDING / 2 + GLYPH_HEIGHT + 3 + 2 * PADDING
+        );
+        assert_eq!(
+            *sheet.get_pixel(PADDING + 20, PADDING + 20),
+            Rgb([40, 200, 40])
+        );
+        assert_eq!(
+            *sheet.get_pixel(2 * PADDING + 40 + 20, PADDING + 10),
+            Rgb([200, 40, 40])
+        );
+        assert!(sheet.pixels().any(|pixel| *pixel == LABEL_COLOR));
+
+        assert!(ContactSheet::new()
+            .render(&[directory.join("notes.txt")])
+            .is_err());
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_report_labels() {
+        let directory = std::env::temp_dir().join(format!("report_label_{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let image = directory.join("cornell_box.png");
+        let report = "scene = cornell_box\noutput = cornell_box.png\nwidth = 4\nheight = 4\n\
+            samples = 64\nelapsed_ms = 2500\nmean_error = 0.1\ntruncated_paths = 0\ncoverage = 1";
+        fs::write(image.with_extension("report"), report).unwrap();
+        assert_eq!(label(&image), vec!["cornell_box", "64 spp, 2.5 s"]);
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -0,0 +1,499 @@
+//! A resident render mode that watches a directory for render jobs and processes them one after
+//! the other, keeping the scenes it has built in memory between jobs.
+//!
+//! A job is a text file with the `.job` extension holding `key = value` lines:
+//!
+//! ```text
+//! scene = caustics
+//! width = 400
+//! samples = 100
+//! time = 0.0
+//! output = caustics.png
+//! gamma = srgb
+//! curves = look.cube
+//! ```
+//!
+//! Only `scene` is required, naming any scene that can be rendered from the command line. `output`
+//! is a relative path that cannot leave the directory the images are saved in. `gamma` is either
+//! `srgb` or a gamma value, and `curves` names a `.cube` file of per-channel response curves,
+//! relative to the job directory, or a built-in film stock such as `portrait`. While a job
+//! is processed, its status is written next to it in a file with the `.status` extension, and the
+//! job file is renamed with the `.done` extension once it is finished. Creating a file named
+//! `stop` in the directory shuts the daemon down.
+//!
+//! The images can be collected in an output directory instead of the job directory, and failed
+//! jobs moved together with their status into an error directory, so a render farm only has to
+//! drop jobs into one directory and pick up images from another. On the command line these are
+//! the `output` and `failed` directories next to the job directory unless others are given.
+
+use crate::camera::{Camera, RenderSettings};
+use crate::output::{Encoding, OutputTransform, ResponseCurves};
+use crate::scene::Scene;
+use crate::scene_registry::{self, SceneEntry};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The time between two scans of the job directory.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Represents the settings of a render job.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Job {
+    /// The name of the scene to render.
+    pub scene: String,
+    /// The width of the image in pixels.
+    pub width: u32,
+    /// The number of samples per pixel.
+    pub samples: u32,
+    /// The scene time at which the scene is rendered.
+    pub time: f64,
+    /// The path of the image file, relative to the job directory and inside it.
+    pub output: String,
+    /// The encoding of the linear colors of the image.
+    pub encoding: Encoding,
+    /// The path of the `.cube` file of response curves, relative to the job directory, or the
+    /// name of a film stock, if any.
+    pub curves: Option<String>,
+}
+
+impl Job {
+    /// Parses a job from the contents of a job file.
+    ///
+    /// # Arguments
+    ///
+    /// * `contents` - The `key = value` lines of the job file. Empty lines and lines starting with
+    ///   `#` are ignored.
+    ///
+    /// # Returns
+    ///
+    /// The parsed `Job`, or a description of the first invalid line.
+    pub fn parse(contents: &str) -> Result<Job, String> {
+        let mut scene = None;
+        let mut job = Job {
+            scene: String::new(),
+            width: 400,
+            samples: 100,
+            time: 0.0,
+            output: String::new(),
+            encoding: Encoding::default(),
+            curves: None,
+        };
+
+        for line in contents.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| format!("expected `key = value`, found `{}`", line))?;
+            let (key, value) = (key.trim(), value.trim());
+            let invalid = || format!("invalid value for `{}`: `{}`", key, value);
+
+            match key {
+                "scene" => scene = Some(value.to_string()),
+                "width" => job.width = value.parse().map_err(|_| invalid())?,
+                "samples" => job.samples = value.parse().map_err(|_| invalid())?,
+                "time" => job.time = value.parse().map_err(|_| invalid())?,
+                "output" => job.output = value.to_string(),
+                "gamma" => job.encoding = Encoding::parse(value)?,
+                "curves" => job.curves = Some(value.to_string()),
+                _ => return Err(format!("unknown setting `{}`", key)),
+            }
+        }
+
+        job.scene = scene.ok_or("missing setting `scene`")?;
+        if job.output.is_empty() {
+            job.output = format!("{}.png", job.scene);
+        }
+        // Jobs may come from anyone who can write to the job directory, so they may only save
+        // images inside the output directory.
+        let inside = Path::new(&job.output)
+            .components()
+            .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+        if !inside {
+            return Err(format!(
+                "invalid value for `output`: `{}` is not a relative path inside the output directory",
+                job.output
+            ));
+        }
+        Ok(job)
+    }
+
+    /// Finds the scene rendered by the job.
+    ///
+    /// # Returns
+    ///
+    /// The scene, or a description of why it cannot be found.
+    pub fn entry(&self) -> Result<&'static SceneEntry, String> {
+        scene_registry::find(&self.scene).ok_or_else(|| format!("unknown scene `{}`", self.scene))
+    }
+
+    /// Builds the camera rendering the job, with the size, number of samples, time, encoding and
+    /// response curves it asks for.
+    ///
+    /// # Arguments
+    ///
+    /// * `scene` - The scene of the job, built by its entry.
+    /// * `directory` - The directory `.cube` files are looked up in.
+    /// * `settings` - The settings overriding the ones of the job, such as the width or the film
+    ///   stock.
+    ///
+    /// # Returns
+    ///
+    /// The camera, or a description of why it cannot be built.
+    pub fn camera(
+        &self,
+        scene: &Scene,
+        directory: &Path,
+        settings: &RenderSettings,
+    ) -> Result<Camera, String> {
+        let settings = RenderSettings {
+            width: settings.width.or(Some(self.width)),
+            samples: settings.samples.or(Some(self.samples)),
+            ..settings.clone()
+        };
+        let mut camera = self.entry()?.camera(scene, &settings);
+        camera.set_time(self.time);
+        let mut output = OutputTransform::new(self.encoding);
+        if let Some(film) = &settings.film {
+            output = output.with_curves(film.clone());
+        } else if let Some(curves) = &self.curves {
+            output = output.with_curves(ResponseCurves::find(curves, directory)?);
+        }
+        camera.set_output_transform(output);
+        Ok(camera)
+    }
+}
+
+/// Represents the resident renderer, holding the scenes built by previous jobs.
+pub struct Daemon {
+    /// The directory watched for jobs.
+    directory: PathBuf,
+    /// The scenes built so far, by name.
+    scenes: HashMap<String, Scene>,
+    /// The directory the images are saved in, or `None` to save them in the job directory.
+    output_directory: Option<PathBuf>,
+    /// The directory failed jobs are moved to, or `None` to leave them in the job directory like
+    /// finished ones.
+    error_directory: Option<PathBuf>,
+}
+
+impl Daemon {
+    /// Creates a new `Daemon` watching the given directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `directory` - The directory watched for jobs.
+    ///
+    /// # Returns
+    ///
+    /// A new `Daemon` instance.
+    pub fn new(directory: &Path) -> Daemon {
+        Daemon {
+            directory: directory.to_path_buf(),
+            scenes: HashMap::new(),
+            output_directory: None,
+            error_directory: None,
+        }
+    }
+
+    /// Saves the images of the jobs in the given directory, created when needed, with the
+    /// output paths of the jobs relative to it.
+    ///
+    /// # Arguments
+    ///
+    /// * `directory` - The directory of the images, absolute or relative to the working
+    ///   directory.
+    ///
+    /// # Returns
+    ///
+    /// The `Daemon` instance with the output directory set.
+    pub fn with_output_directory(mut self, directory: &Path) -> Daemon {
+        self.output_directory = Some(directory.to_path_buf());
+        self
+    }
+
+    /// Moves failed jobs into the given directory, created when needed, together with their
+    /// status files.
+    ///
+    /// # Arguments
+    ///
+    /// * `directory` - The directory of the failed jobs, absolute or relative to the working
+    ///   directory.
+    ///
+    /// # Returns
+    ///
+    /// The `Daemon` instance with the error directory set.
+    pub fn with_error_directory(mut self, directory: &Path) -> Daemon {
+        self.error_directory = Some(directory.to_path_buf());
+        self
+    }
+
+    /// Returns the path of a directory next to the job directory, such as the default output and
+    /// error directories, so they are not mistaken for jobs or nested inside the watched
+    /// directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the directory.
+    ///
+    /// # Returns
+    ///
+    /// The path of the directory, which may not exist yet.
+    pub fn sibling_directory(&self, name: &str) -> PathBuf {
+        // Relative paths such as `.` have no parent of their own, so the parent is taken from the
+        // full path of the directory.
+        let directory = fs::canonicalize(&self.directory)
+            .or_else(|_| std::path::absolute(&self.directory))
+            .unwrap_or_else(|_| self.directory.clone());
+        match directory.parent() {
+            Some(parent) => parent.join(name),
+            None => directory.join(name),
+        }
+    }
+
+    /// Watches the directory and processes jobs until a `stop` file appears in it.
+    pub fn run(&mut self) {
+        if let Err(e) = fs::create_dir_all(&self.directory) {
+            eprintln!("Failed to create job directory: {}", e);
+            return;
+        }
+        println!("Watching {} for render jobs", self.directory.display());
+
+        loop {
+            self.process_pending();
+            let stop = self.directory.join("stop");
+            if stop.exists() {
+                let _ = fs::remove_file(stop);
+                println!("Stopping render daemon");
+                return;
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    /// Processes every job currently waiting in the directory, in order of file name.
+    ///
+    /// # Returns
+    ///
+    /// The number of processed jobs.
+    pub fn process_pending(&mut self) -> usize {
+        let mut jobs: Vec<PathBuf> = match fs::read_dir(&self.directory) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.extension().is_some_and(|extension| extension == "job"))
+                .collect(),
+            Err(e) => {
+                eprintln!("Failed to read job directory: {}", e);
+                return 0;
+            }
+        };
+        jobs.sort();
+
+        for path in &jobs {
+            let (status, failed) = match self.process(path) {
+                Ok(message) => (message, false),
+                Err(message) => (format!("failed: {}", message), true),
+            };
+            println!("{}: {}", path.display(), status);
+            Self::write_status(path, &status);
+            match &self.error_directory {
+                Some(errors) if failed => Self::move_to(path, errors),
+                _ => {
+                    let _ = fs::rename(path, path.with_extension("done"));
+                }
+            }
+        }
+        jobs.len()
+    }
+
+    /// Moves a job file and its status file into another directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the job file.
+    /// * `directory` - The directory to move the files to, created when needed.
+    fn move_to(path: &Path, directory: &Path) {
+        let moved = fs::create_dir_all(directory).and_then(|_| {
+            for file in [path.to_path_buf(), path.with_extension("status")] {
+                if let Some(name) = file.file_name() {
+                    fs::rename(&file, directory.join(name))?;
+                }
+            }
+            Ok(())
+        });
+        if let Err(e) = moved {
+            eprintln!("Failed to move {}: {}", path.display(), e);
+        }
+    }
+
+    /// Renders a single job.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the job file.
+    ///
+    /// # Returns
+    ///
+    /// A description of the finished render, or of the reason the job failed.
+    fn process(&mut self, path: &Path) -> Result<String, String> {
+        let contents = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let job = Job::parse(&contents)?;
+        Self::write_status(path, "rendering");
+
+        let start = Instant::now();
+        if !self.scenes.contains_key(&job.scene) {
+            let scene = job.entry()?.scene(&RenderSettings::default())?;
+            self.scenes.insert(job.scene.clone(), scene);
+        }
+        let scene = &self.scenes[&job.scene];
+        let camera = job.camera(scene, &self.directory, &RenderSettings::default())?;
+
+        let directory = self.output_directory.as_ref().unwrap_or(&self.directory);
+        let output = directory.join(&job.output);
+        if let Some(parent) = output.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        camera.render_and_save(scene, &output.to_string_lossy())?;
+        Ok(format!(
+            "done in {} ms, saved to {}",
+            start.elapsed().as_millis(),
+            output.display()
+        ))
+    }
+
+    /// Writes the status of a job next to its job file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the job file.
+    /// * `status` - The status to write.
+    fn write_status(path: &Path, status: &str) {
+        if let Err(e) = fs::write(path.with_extension("status"), status) {
+            eprintln!("Failed to write job status: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_job() {
+        let job = Job::parse("# preview\nscene = caustics\nwidth = 64\n\nsamples=4\ntime = 0.5\n")
+            .unwrap();
+        assert_eq!(
+            job,
+            Job {
+                scene: "caustics".to_string(),
+                width: 64,
+                samples: 4,
+                time: 0.5,
+                output: "caustics.png".to_string(),
+                encoding: Encoding::Gamma(2.2),
+                curves: None,
+            }
+        );
+
+        assert!(Job::parse("width = 64").is_err());
+        assert!(Job::parse("scene = caustics\nwidth = wide").is_err());
+        assert!(Job::parse("scene = caustics\ncolor = red").is_err());
+        assert!(Job::parse("scene = caustics\ngamma = bright").is_err());
+        assert!(Job::parse("scene = caustics\noutput = ./shots/first.png").is_ok());
+        for output in ["../first.png", "shots/../../first.png", "/tmp/first.png"] {
+            assert!(Job::parse(&format!("scene = caustics\noutput = {}", output)).is_err());
+        }
+
+        let job = Job::parse("scene = caustics\ngamma = srgb\ncurves = look.cube").unwrap();
+        assert_eq!(job.encoding, Encoding::Srgb);
+        assert_eq!(job.curves.as_deref(), Some("look.cube"));
+    }
+
+    #[test]
+    fn test_process_pending_jobs() {
+        let directory = std::env::temp_dir().join(format!("render_jobs_{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(
+            directory.join("1.job"),
+            "scene = caustics\nwidth = 8\nsamples = 1\noutput = first.png",
+        )
+        .unwrap();
+        fs::write(directory.join("2.job"), "scene = missing").unwrap();
+        // Every scene of the command line can be rendered by a job.
+        fs::write(
+            directory.join("3.job"),
+            "scene = quads\nwidth = 8\nsamples = 1\noutput = third.png",
+        )
+        .unwrap();
+
+        let mut daemon = Daemon::new(&directory);
+        assert_eq!(daemon.process_pending(), 3);
+        assert_eq!(daemon.process_pending(), 0);
+
+        assert!(directory.join("first.png").exists());
+        assert!(directory.join("third.png").exists());
+        assert!(directory.join("1.done").exists());
+        assert!(fs::read_to_string(directory.join("1.status"))
+            .unwrap()
+            .starts_with("done"));
+        assert!(fs::read_to_string(directory.join("2.status"))
+            .unwrap()
+            .starts_with("failed"));
+        assert_eq!(daemon.scenes.len(), 2);
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_output_and_error_directories() {
+        let farm = std::env::temp_dir().join(format!("render_farm_{}", std::process::id()));
+        let directory = farm.join("jobs");
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(
+            directory.join("1.job"),
+            "scene = caustics\nwidth = 8\nsamples = 1\noutput = shots/first.png",
+        )
+        .unwrap();
+        fs::write(directory.join("2.job"), "scene = missing").unwrap();
+        fs::write(
+            directory.join("3.job"),
+            "scene = caustics\nwidth = 8\nsamples = 1\noutput = first.unknown",
+        )
+        .unwrap();
+
+        let daemon = Daemon::new(&directory);
+        let (output, errors) = (
+            daemon.sibling_directory("output"),
+            daemon.sibling_directory("failed"),
+        );
+        assert_eq!(output, fs::canonicalize(&farm).unwrap().join("output"));
+        // The working directory is watched with its results next to it, not inside it.
+        let working = fs::canonicalize(".").unwrap();
+        assert_eq!(
+            Daemon::new(Path::new(".")).sibling_directory("output"),
+            working.parent().unwrap().join("output")
+        );
+        let mut daemon = daemon
+            .with_output_directory(&output)
+            .with_error_directory(&errors);
+        assert_eq!(daemon.process_pending(), 3);
+
+        assert!(farm.join("output/shots/first.png").exists());
+        assert!(directory.join("1.done").exists());
+        for failed in ["2", "3"] {
+            assert!(farm.join(format!("failed/{}.job", failed)).exists());
+            assert!(
+                fs::read_to_string(farm.join(format!("failed/{}.status", failed)))
+                    .unwrap()
+                    .starts_with("failed")
+            );
+            assert!(!directory.join(format!("{}.status", failed)).exists());
+        }
+
+        fs::remove_dir_all(farm).unwrap();
+    }
+}
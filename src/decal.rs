@@ -0,0 +1,146 @@
+//! Decals, images projected through a box onto whatever surfaces lie within it, so posters,
+//! labels and dirt can be laid on walls and props without giving them texture coordinates.
+
+use crate::texture::{ImageTexture, ShadingContext, Texture, WrapMode};
+use crate::vector3::Vector3;
+
+/// Represents an image projected along the depth of a box, replacing the albedo of the surfaces
+/// it falls on by the colors of the image, blended by their opacity.
+#[derive(Debug)]
+pub struct Decal {
+    /// The center of the projection box.
+    center: Vector3,
+    /// The unit vector towards the right of the image.
+    right: Vector3,
+    /// The unit vector towards the top of the image.
+    up: Vector3,
+    /// The unit direction in which the image is projected.
+    forward: Vector3,
+    /// The half width, half height and half depth of the box.
+    half_size: Vector3,
+    /// The projected image, whose alpha channel is the opacity of the decal.
+    texture: ImageTexture,
+    /// The factor scaling the opacity of the whole decal.
+    opacity: f64,
+}
+
+impl Decal {
+    /// Creates a new `Decal` projecting an image through a box, as a projector would. Surfaces
+    /// within the box facing the projector are painted, those facing away are left alone.
+    ///
+    /// # Arguments
+    ///
+    /// * `center` - The center of the box.
+    /// * `forward` - The direction in which the image is projected, towards the surfaces.
+    /// * `up` - The direction towards the top of the image, which does not have to be
+    ///   perpendicular to `forward`.
+    /// * `size` - The width and height of the image and the depth of the box.
+    /// * `texture` - The image, whose alpha channel is the opacity of the decal. Its borders are
+    ///   clamped, so the image does not bleed into itself across the edges.
+    ///
+    /// # Returns
+    ///
+    /// A new `Decal` instance.
+    pub fn new(
+        center: Vector3,
+        forward: Vector3,
+        up: Vector3,
+        size: Vector3,
+        texture: ImageTexture,
+    ) -> Decal {
+        let forward = forward.normalize();
+        let right = forward.cross(&up).normalize();
+        let up = right.cross(&forward);
+        Decal {
+            center,
+            right,
+            up,
+            forward,
+            half_size: size * 0.5,
+            texture: texture.with_wrap(WrapMode::Clamp, WrapMode::Clamp),
+            opacity: 1.0,
+        }
+    }
+
+    /// Scales the opacity of the whole decal, such as to fade dirt.
+    ///
+    /// # Arguments
+    ///
+    /// * `opacity` - The factor scaling the opacity, between 0 and 1.
+    ///
+    /// # Returns
+    ///
+    /// The updated `Decal` instance.
+    pub fn with_opacity(mut self, opacity: f64) -> Decal {
+        self.opacity = opacity.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Blends the decal over the albedo of a surface point.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The surface point.
+    /// * `normal` - The normal at the point, facing against the incoming ray.
+    /// * `albedo` - The albedo of the surface.
+    ///
+    /// # Returns
+    ///
+    /// The albedo with the decal blended over it, or `albedo` itself if the point lies outside
+    /// the box or faces away from the projector.
+    pub fn apply(&self, point: &Vector3, normal: &Vector3, albedo: Vector3) -> Vector3 {
+        if normal.dot(&self.forward) >= 0.0 {
+            return albedo;
+        }
+        let local = *point - self.center;
+        let x = local.dot(&self.right) / self.half_size.x;
+        let y = local.dot(&self.up) / self.half_size.y;
+        let z = local.dot(&self.forward) / self.half_size.z;
+        if x.abs() > 1.0 || y.abs() > 1.0 || z.abs() > 1.0 {
+            return albedo;
+        }
+
+        let (u, v) = (0.5 * (x + 1.0), 0.5 * (y + 1.0));
+        let alpha = self.texture.alpha(u, v) * self.opacity;
+        let color = self.texture.value(&ShadingContext::from_uv(u, v));
+        albedo * (1.0 - alpha) + color * alpha
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{DynamicImage, Rgba, RgbaImage};
+
+    #[test]
+    fn test_decal_projection() {
+        // A red image whose right half is transparent, projected down onto the floor.
+        let mut image = RgbaImage::new(2, 1);
+        image.put_pixel(0, 0, Rgba([255, 0, 0, 255]));
+        let texture = ImageTexture::from_image(&DynamicImage::ImageRgba8(image));
+        let decal = Decal::new(
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(0.0, -1.0, 0.0),
+            Vector3::new(0.0, 0.0, -1.0),
+            Vector3::new(2.0, 2.0, 0.5),
+            texture,
+        );
+        let up = Vector3::new(0.0, 1.0, 0.0);
+        let gray = Vector3::new(0.5, 0.5, 0.5);
+
+        // The left edge of the image lies towards negative x, as seen by the projector.
+        let painted = decal.apply(&Vector3::new(-0.9, 0.1, 0.0), &up, gray);
+        assert!((painted - Vector3::new(1.0, 0.0, 0.0)).length() < 1e-9);
+        assert_eq!(decal.apply(&Vector3::new(0.9, 0.1, 0.0), &up, gray), gray);
+
+        // Outside the box, and on surfaces facing away from the projector, nothing changes.
+        assert_eq!(decal.apply(&Vector3::new(-0.9, 0.5, 0.0), &up, gray), gray);
+        assert_eq!(decal.apply(&Vector3::new(-0.9, 0.0, 1.5), &up, gray), gray);
+        assert_eq!(decal.apply(&Vector3::new(-0.9, 0.0, 0.0), &-up, gray), gray);
+
+        // A half transparent decal blends over the albedo.
+        let faded = decal.with_opacity(0.5);
+        let blended = faded.apply(&Vector3::new(-0.9, 0.0, 0.0), &up, gray);
+        assert!((blended - Vector3::new(0.75, 0.25, 0.25)).length() < 1e-9);
+    }
+}
@@ -0,0 +1,380 @@
+//! Deep images, which keep several samples at different depths in every pixel instead of a single
+//! color, so elements such as volumes can be composited with other renders by depth. Deep images
+//! are written as single-part deep scanline OpenEXR files.
+
+use crate::vector3::Vector3;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+/// The channels of the deep EXR file, in the alphabetical order the format requires.
+const CHANNELS: [&str; 6] = ["A", "B", "G", "R", "Z", "ZBack"];
+
+/// Represents a sample of a deep pixel: a premultiplied color with an alpha, spanning a range of
+/// depths.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DeepSample {
+    /// The distance from the camera to the front of the sample.
+    pub depth: f64,
+    /// The distance from the camera to the back of the sample.
+    pub depth_back: f64,
+    /// The color of the sample, premultiplied by its alpha.
+    pub color: Vector3,
+    /// The opacity of the sample.
+    pub alpha: f64,
+}
+
+/// Represents the point where a camera sample first hit the scene.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fragment {
+    /// The index of the object that was hit.
+    pub object: usize,
+    /// The distance from the camera to the hit point.
+    pub depth: f64,
+    /// The color carried by the camera sample.
+    pub color: Vector3,
+}
+
+/// Turns the fragments of a pixel into deep samples sorted from front to back. Fragments of the
+/// same object that follow each other in depth are merged into a sample spanning their depths,
+/// such as a slab of a volume or a slanted surface. Each sample covers the share of the camera
+/// samples that produced its fragments, and its alpha is chosen so that compositing the samples
+/// front to back with the over operation gives back the average of the camera samples.
+///
+/// # Arguments
+///
+/// * `fragments` - The fragments of the camera samples that hit the scene.
+/// * `samples` - The number of camera samples traced through the pixel, including the ones that
+///   hit nothing.
+///
+/// # Returns
+///
+/// The deep samples of the pixel.
+pub fn merge_fragments(mut fragments: Vec<Fragment>, samples: u32) -> Vec<DeepSample> {
+    fragments.sort_by(|a, b| a.depth.total_cmp(&b.depth));
+
+    // The fragments of every sample as (object, front, back, summed color, count).
+    let mut groups: Vec<(usize, f64, f64, Vector3, u32)> = Vec::new();
+    for fragment in fragments {
+        match groups.last_mut() {
+            Some((object, _, back, color, count)) if *object == fragment.object => {
+                *back = fragment.depth;
+                *color += fragment.color;
+                *count += 1;
+            }
+            _ => groups.push((
+                fragment.object,
+                fragment.depth,
+                fragment.depth,
+                fragment.color,
+                1,
+            )),
+        }
+    }
+
+    let samples = samples.max(1) as f64;
+    let mut remaining = 1.0;
+    groups
+        .into_iter()
+        .map(|(_, depth, depth_back, color, count)| {
+            let coverage = count as f64 / samples;
+            let sample = DeepSample {
+                depth,
+                depth_back,
+                color: color / (samples * remaining),
+                alpha: (coverage / remaining).min(1.0),
+            };
+            remaining -= coverage;
+            sample
+        })
+        .collect()
+}
+
+/// Represents an image holding a list of deep samples in every pixel.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeepImage {
+    /// The width of the image in pixels.
+    width: u32,
+    /// The height of the image in pixels.
+    height: u32,
+    /// The samples of every pixel, stored row by row starting from the top-left pixel.
+    pixels: Vec<Vec<DeepSample>>,
+}
+
+impl DeepImage {
+    /// Creates a new `DeepImage`.
+    ///
+    /// # Arguments
+    ///
+    /// * `width` - The width of the image in pixels.
+    /// * `height` - The height of the image in pixels.
+    /// * `pixels` - The samples of every pixel, row by row starting from the top-left pixel.
+    ///
+    /// # Returns
+    ///
+    /// A new `DeepImage` instance.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of pixels does not match the size of the image.
+    pub fn new(width: u32, height: u32, pixels: Vec<Vec<DeepSample>>) -> DeepImage {
+        assert_eq!(
+            pixels.len(),
+            (width * height) as usize,
+            "the pixels must fill the image"
+        );
+        DeepImage {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// Returns the samples of a pixel.
+    pub fn pixel(&self, x: u32, y: u32) -> &[DeepSample] {
+        &self.pixels[(y * self.width + x) as usize]
+    }
+
+    /// Composites the samples of a pixel front to back with the over operation.
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - The x-coordinate of the pixel.
+    /// * `y` - The y-coordinate of the pixel.
+    ///
+    /// # Returns
+    ///
+    /// The premultiplied color and the alpha of the pixel.
+    pub fn flatten(&self, x: u32, y: u32) -> (Vector3, f64) {
+        let mut color = Vector3::default();
+        let mut transmittance = 1.0;
+        for sample in self.pixel(x, y) {
+            color += transmittance * sample.color;
+            transmittance *= 1.0 - sample.alpha;
+        }
+        (color, 1.0 - transmittance)
+    }
+
+    /// Writes the image to a deep scanline OpenEXR file, without compression and with every
+    /// channel stored as 32-bit floats.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the file.
+    ///
+    /// # Returns
+    ///
+    /// `Ok` if the file was written, or the error that stopped it.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writer.write_all(&self.to_exr())?;
+        writer.flush()
+    }
+
+    /// Encodes the image as a deep scanline OpenEXR file.
+    ///
+    /// # Returns
+    ///
+    /// The bytes of the file.
+    pub fn to_exr(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&[0x76, 0x2f, 0x31, 0x01]);
+        // Version 2, with the flag marking deep data.
+        bytes.extend_from_slice(&(2u32 | 0x800).to_le_bytes());
+        self.write_header(&mut bytes);
+
+        let chunks: Vec<Vec<u8>> = (0..self.height).map(|y| self.scanline_chunk(y)).collect();
+        let mut offset = (bytes.len() + 8 * chunks.len()) as u64;
+        for chunk in &chunks {
+            bytes.extend_from_slice(&offset.to_le_bytes());
+            offset += chunk.len() as u64;
+        }
+        chunks
+            .iter()
+            .for_each(|chunk| bytes.extend_from_slice(chunk));
+        bytes
+    }
+
+    /// Writes the header attributes of the file, followed by the byte ending the header.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The bytes of the file written so far.
+    fn write_header(&self, bytes: &mut Vec<u8>) {
+        let mut attribute = |name: &str, kind: &str, value: &[u8]| {
+            for text in [name, kind] {
+                bytes.extend_from_slice(text.as_bytes());
+                bytes.push(0);
+            }
+            bytes.extend_from_slice(&(value.len() as i32).to_le_bytes());
+            bytes.extend_from_slice(value);
+        };
+
+        let mut channels = Vec::new();
+        for name in CHANNELS {
+            channels.extend_from_slice(name.as_bytes());
+            channels.push(0);
+            // Float pixels, not perceptually linear, reserved bytes and no subsampling.
+            channels.extend_from_slice(&2i32.to_le_bytes());
+            channels.extend_from_slice(&[0, 0, 0, 0]);
+            channels.extend_from_slice(&1i32.to_le_bytes());
+            channels.extend_from_slice(&1i32.to_le_bytes());
+        }
+        channels.push(0);
+
+        let window: Vec<u8> = [0, 0, self.width as i32 - 1, self.height as i32 - 1]
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect();
+        let max_samples = self.pixels.iter().map(Vec::len).max().unwrap_or(0) as i32;
+
+        attribute("channels", "chlist", &channels);
+        attribute("chunkCount", "int", &(self.height as i32).to_le_bytes());
+        attribute("compression", "compression", &[0]);
+        attribute("dataWindow", "box2i", &window);
+        attribute("displayWindow", "box2i", &window);
+        attribute("lineOrder", "lineOrder", &[0]);
+        attribute("maxSamplesPerPixel", "int", &max_samples.to_le_bytes());
+        attribute("name", "string", b"deep");
+        attribute("pixelAspectRatio", "float", &1.0f32.to_le_bytes());
+        attribute("screenWindowCenter", "v2f", &[0; 8]);
+        attribute("screenWindowWidth", "float", &1.0f32.to_le_bytes());
+        attribute("type", "string", b"deepscanline");
+        attribute("version", "int", &1i32.to_le_bytes());
+        bytes.push(0);
+    }
+
+    /// Encodes the chunk holding a row of the image: the row, the sizes of its tables, the
+    /// cumulative number of samples at the end of every pixel, then the samples of every channel
+    /// one channel after the other.
+    ///
+    /// # Arguments
+    ///
+    /// * `y` - The row.
+    ///
+    /// # Returns
+    ///
+    /// The bytes of the chunk.
+    fn scanline_chunk(&self, y: u32) -> Vec<u8> {
+        let row = &self.pixels[(y * self.width) as usize..((y + 1) * self.width) as usize];
+
+        let mut offsets = Vec::with_capacity(4 * row.len());
+        let mut total = 0i32;
+        for pixel in row {
+            total += pixel.len() as i32;
+            offsets.extend_from_slice(&total.to_le_bytes());
+        }
+
+        let channel_values: [fn(&DeepSample) -> f64; 6] = [
+            |s| s.alpha,
+            |s| s.color.z,
+            |s| s.color.y,
+            |s| s.color.x,
+            |s| s.depth,
+            |s| s.depth_back,
+        ];
+        let mut samples = Vec::with_capacity(4 * CHANNELS.len() * total as usize);
+        for value in channel_values {
+            for sample in row.iter().flatten() {
+                samples.extend_from_slice(&(value(sample) as f32).to_le_bytes());
+            }
+        }
+
+        let mut chunk = Vec::with_capacity(28 + offsets.len() + samples.len());
+        chunk.extend_from_slice(&(y as i32).to_le_bytes());
+        chunk.extend_from_slice(&(offsets.len() as u64).to_le_bytes());
+        chunk.extend_from_slice(&(samples.len() as u64).to_le_bytes());
+        chunk.extend_from_slice(&(samples.len() as u64).to_le_bytes());
+        chunk.extend_from_slice(&offsets);
+        chunk.extend_from_slice(&samples);
+        chunk
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fragment(object: usize, depth: f64, color: f64) -> Fragment {
+        Fragment {
+            object,
+            depth,
+            color: Vector3::new(color, color, color),
+        }
+    }
+
+    #[test]
+    fn test_merge_fragments() {
+        // Two samples hit a wall, one hits a sphere in front of it and one hits nothing.
+        let fragments = vec![
+            fragment(1, 10.0, 0.2),
+            fragment(0, 4.0, 0.8),
+            fragment(1, 10.001, 0.4),
+        ];
+        let samples = merge_fragments(fragments, 4);
+        assert_eq!(samples.len(), 2);
+        assert_eq!((samples[0].depth, samples[0].depth_back), (4.0, 4.0));
+        assert_eq!((samples[1].depth, samples[1].depth_back), (10.0, 10.001));
+
+        let image = DeepImage::new(1, 1, vec![samples]);
+        let (color, alpha) = image.flatten(0, 0);
+        assert!((alpha - 0.75).abs() < 1e-12);
+        assert!((color.x - (0.8 + 0.2 + 0.4) / 4.0).abs() < 1e-12);
+
+        // Fragments of an object are kept apart when another object lies between them.
+        let interleaved = vec![
+            fragment(0, 1.0, 1.0),
+            fragment(1, 2.0, 1.0),
+            fragment(0, 3.0, 1.0),
+        ];
+        assert_eq!(merge_fragments(interleaved, 3).len(), 3);
+        let opaque = merge_fragments(vec![fragment(0, 1.0, 1.0), fragment(1, 2.0, 1.0)], 2);
+        assert!((opaque[1].alpha - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_exr_layout() {
+        let pixels = vec![
+            merge_fragments(vec![fragment(0, 2.0, 0.5)], 1),
+            Vec::new(),
+            merge_fragments(vec![fragment(0, 1.0, 0.5), fragment(1, 3.0, 0.5)], 2),
+            merge_fragments(vec![fragment(0, 1.0, 0.5)], 2),
+        ];
+        let image = DeepImage::new(2, 2, pixels);
+        let bytes = image.to_exr();
+
+        assert_eq!(&bytes[..4], &[0x76, 0x2f, 0x31, 0x01]);
+        assert_eq!(u32::from_le_bytes(bytes[4..8].try_into().unwrap()), 0x802);
+        let text = String::from_utf8_lossy(&bytes);
+        assert!(text.contains("deepscanline") && text.contains("ZBack"));
+
+        // The header ends right before the offset table of the two rows.
+        let header_end =
+            bytes.len() - image.scanline_chunk(0).len() - image.scanline_chunk(1).len() - 16;
+        assert_eq!(bytes[header_end - 1], 0);
+        let read_u64 = |at: usize| u64::from_le_bytes(bytes[at..at + 8].try_into().unwrap());
+        let read_i32 = |at: usize| i32::from_le_bytes(bytes[at..at + 4].try_into().unwrap());
+        let read_f32 = |at: usize| f32::from_le_bytes(bytes[at..at + 4].try_into().unwrap());
+
+        let second_row = read_u64(header_end + 8) as usize;
+        assert_eq!(read_i32(second_row), 1);
+        // The row holds two samples in its first pixel and one in its second.
+        assert_eq!(read_u64(second_row + 4), 8);
+        assert_eq!(read_u64(second_row + 12), 3 * 6 * 4);
+        assert_eq!(read_i32(second_row + 28), 2);
+        assert_eq!(read_i32(second_row + 32), 3);
+        // The alpha channel comes first, then blue.
+        let samples = second_row + 36;
+        assert_eq!(read_f32(samples), 0.5);
+        assert_eq!(read_f32(samples + 4), 1.0);
+        assert_eq!(read_f32(samples + 12), 0.25);
+        // The depths come after the four color channels.
+        assert_eq!(read_f32(samples + 4 * 12), 1.0);
+        assert_eq!(read_f32(samples + 4 * 13), 3.0);
+
+        let first_row = read_u64(header_end) as usize;
+        assert_eq!(read_i32(first_row), 0);
+        assert_eq!(read_i32(first_row + 28), 1);
+        assert_eq!(read_i32(first_row + 32), 1);
+    }
+}
@@ -0,0 +1,224 @@
+//! Edge-preserving denoiser run on a finished render, averaging every pixel with the neighbors
+//! showing the same surface: the albedo and normal of the first surface seen through each pixel
+//! tell surfaces apart, and the estimated noise of the pixels tells noise from shading detail.
+
+use crate::vector3::Vector3;
+use rayon::prelude::*;
+
+/// The number of pixels on each side of a pixel averaged with it.
+const RADIUS: i64 = 4;
+
+/// The standard deviation in pixels of the weight given to neighbors by their distance.
+const SPATIAL_SIGMA: f64 = 2.0;
+
+/// The standard deviation of the weight given to neighbors by the difference of their normals.
+const NORMAL_SIGMA: f64 = 0.25;
+
+/// The standard deviation of the weight given to neighbors by the difference of their albedos.
+const ALBEDO_SIGMA: f64 = 0.1;
+
+/// The number of standard errors by which the shading of two pixels may differ and still be
+/// taken for noise.
+const COLOR_SIGMA: f64 = 2.0;
+
+/// The smallest albedo channel the shading is divided by, so black surfaces keep their colors.
+const MIN_ALBEDO: f64 = 0.01;
+
+/// The features of the first surface seen through every pixel, which guide the denoiser.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Features {
+    /// The albedo of the surface of every pixel, or the color of the lights and background.
+    pub albedo: Vec<Vector3>,
+    /// The unit normal of the surface of every pixel, facing the camera, or zero for the
+    /// background.
+    pub normal: Vec<Vector3>,
+}
+
+/// Denoises a render with a joint bilateral filter. The color of every pixel is divided by its
+/// albedo, so textures are kept sharp, and averaged with the neighbors whose normal and albedo
+/// match and whose shading only differs by about its noise, which keeps shadow edges.
+///
+/// # Arguments
+///
+/// * `color` - The linear colors of the pixels, row by row.
+/// * `error` - The standard error of every pixel.
+/// * `features` - The albedo and normal of every pixel.
+/// * `width` - The width of the image in pixels.
+/// * `samples` - The number of samples per pixel. With a single sample the noise of the pixels is
+///   unknown, so they are averaged along the features alone.
+///
+/// # Returns
+///
+/// The denoised colors of the pixels.
+pub fn denoise(
+    color: &[Vector3],
+    error: &[Vector3],
+    features: &Features,
+    width: usize,
+    samples: u32,
+) -> Vec<Vector3> {
+    if width == 0 {
+        return color.to_vec();
+    }
+    let height = color.len() / width;
+    let divisor = |albedo: Vector3| {
+        Vector3::new(
+            albedo.x.max(MIN_ALBEDO),
+            albedo.y.max(MIN_ALBEDO),
+            albedo.z.max(MIN_ALBEDO),
+        )
+    };
+    let divide = |color: Vector3, albedo: Vector3| {
+        let divisor = divisor(albedo);
+        Vector3::new(
+            color.x / divisor.x,
+            color.y / divisor.y,
+            color.z / divisor.z,
+        )
+    };
+    let shading: Vec<Vector3> = color
+        .iter()
+        .zip(&features.albedo)
+        .map(|(&color, &albedo)| divide(color, albedo))
+        .collect();
+    let noise: Vec<f64> = error
+        .iter()
+        .zip(&features.albedo)
+        .map(|(&error, &albedo)| divide(error, albedo).luminance())
+        .collect();
+    // The error of a few samples is itself noisy: pixels whose samples all missed the light
+    // report none, and fireflies report a lot. The median of the errors around every pixel is
+    // steadier, and is used for the neighbors, while a pixel keeps its own error if larger, so
+    // fireflies are averaged away without being spread over their neighbors.
+    let typical: Vec<f64> = (0..color.len())
+        .map(|pixel| {
+            let (x, y) = (pixel % width, pixel / width);
+            let mut around: Vec<f64> = (y.saturating_sub(1)..(y + 2).min(height))
+                .flat_map(|ny| {
+                    (x.saturating_sub(1)..(x + 2).min(width)).map(move |nx| ny * width + nx)
+                })
+                .map(|neighbor| noise[neighbor])
+                .collect();
+            around.sort_by(f64::total_cmp);
+            around[around.len() / 2]
+        })
+        .collect();
+
+    (0..color.len())
+        .into_par_iter()
+        .map(|pixel| {
+            let (x, y) = ((pixel % width) as i64, (pixel / width) as i64);
+            let mut sum = Vector3::default();
+            let mut total = 0.0;
+            for ny in (y - RADIUS).max(0)..(y + RADIUS + 1).min(height as i64) {
+                for nx in (x - RADIUS).max(0)..(x + RADIUS + 1).min(width as i64) {
+                    let neighbor = ny as usize * width + nx as usize;
+                    let distance = ((nx - x).pow(2) + (ny - y).pow(2)) as f64;
+                    let normal = (features.normal[pixel] - features.normal[neighbor]).length();
+                    let albedo = (features.albedo[pixel] - features.albedo[neighbor]).length();
+                    let mut exponent = distance / (2.0 * SPATIAL_SIGMA * SPATIAL_SIGMA)
+                        + normal * normal / (2.0 * NORMAL_SIGMA * NORMAL_SIGMA)
+                        + albedo * albedo / (2.0 * ALBEDO_SIGMA * ALBEDO_SIGMA);
+                    if samples > 1 {
+                        let difference = (shading[pixel] - shading[neighbor]).luminance();
+                        let variance =
+                            noise[pixel].max(typical[pixel]).powi(2) + typical[neighbor].powi(2);
+                        exponent += if variance > 0.0 {
+                            difference * difference / (2.0 * COLOR_SIGMA * COLOR_SIGMA * variance)
+                        } else if difference == 0.0 {
+                            0.0
+                        } else {
+                            f64::INFINITY
+                        };
+                    }
+                    let weight = (-exponent).exp();
+                    sum += weight * shading[neighbor];
+                    total += weight;
+                }
+            }
+            sum / total * divisor(features.albedo[pixel])
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the features of an image whose left half is dark and right half bright, both
+    /// facing the camera.
+    fn halves(width: usize, height: usize) -> Features {
+        let albedo = (0..width * height)
+            .map(|pixel| {
+                if pixel % width < width / 2 {
+                    Vector3::new(0.2, 0.2, 0.2)
+                } else {
+                    Vector3::new(0.8, 0.8, 0.8)
+                }
+            })
+            .collect();
+        Features {
+            albedo,
+            normal: vec![Vector3::new(0.0, 0.0, 1.0); width * height],
+        }
+    }
+
+    #[test]
+    fn test_denoise_smooths_noise_and_keeps_edges() {
+        let (width, height) = (16, 16);
+        let features = halves(width, height);
+        fastrand::seed(3);
+        let noisy: Vec<Vector3> = features
+            .albedo
+            .iter()
+            .map(|&albedo| albedo * (0.5 + fastrand::f64()))
+            .collect();
+        // The standard deviation of a uniform value over a unit interval is about 0.29.
+        let error: Vec<Vector3> = features.albedo.iter().map(|&albedo| albedo * 0.3).collect();
+        let denoised = denoise(&noisy, &error, &features, width, 16);
+
+        let spread = |colors: &[Vector3]| {
+            let (min, max) = colors
+                .iter()
+                .enumerate()
+                .filter(|(pixel, _)| pixel % width >= width / 2)
+                .fold((f64::MAX, f64::MIN), |(min, max), (_, color)| {
+                    (min.min(color.x), max.max(color.x))
+                });
+            max - min
+        };
+        assert!(spread(&denoised) < 0.5 * spread(&noisy));
+
+        // The dark half does not bleed into the bright half along their edge.
+        for y in 0..height {
+            let left = denoised[y * width + width / 2 - 1].x;
+            let right = denoised[y * width + width / 2].x;
+            assert!(left < 0.4 && right > 0.5, "{} {}", left, right);
+        }
+    }
+
+    #[test]
+    fn test_denoise_keeps_shading_edges() {
+        // A shadow falls across one flat surface, with little noise on either side of it.
+        let (width, height) = (16, 4);
+        let features = Features {
+            albedo: vec![Vector3::new(0.5, 0.5, 0.5); width * height],
+            normal: vec![Vector3::new(0.0, 1.0, 0.0); width * height],
+        };
+        let color: Vec<Vector3> = (0..width * height)
+            .map(|pixel| {
+                let light = if pixel % width < width / 2 { 0.1 } else { 1.0 };
+                Vector3::new(light, light, light) * 0.5
+            })
+            .collect();
+        let error = vec![Vector3::new(0.01, 0.01, 0.01); width * height];
+        let denoised = denoise(&color, &error, &features, width, 64);
+        for (denoised, color) in denoised.iter().zip(&color) {
+            assert!((*denoised - *color).length() < 1e-6);
+        }
+
+        // With a single sample the noise is unknown, so the shadow edge is blurred.
+        let blurred = denoise(&color, &error, &features, width, 1);
+        assert!(blurred[width / 2].x < 0.45);
+    }
+}
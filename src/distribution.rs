@@ -0,0 +1,229 @@
+/// Represents a piecewise-constant probability density over the unit interval, sampled by
+/// inverting its cumulative distribution.
+#[derive(Debug, Clone)]
+pub struct Distribution1D {
+    /// The non-negative value of the function in each of the equal cells of the interval.
+    function: Vec<f64>,
+    /// The cumulative distribution at the start of every cell, followed by 1.
+    cdf: Vec<f64>,
+    /// The integral of the function over the unit interval.
+    integral: f64,
+}
+
+impl Distribution1D {
+    /// Creates a new `Distribution1D` proportional to the given function.
+    /// A function that is zero everywhere gives the uniform density.
+    ///
+    /// # Arguments
+    ///
+    /// * `function` - The non-negative value of the function in each cell.
+    ///
+    /// # Returns
+    ///
+    /// A new `Distribution1D` instance.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `function` is empty.
+    pub fn new(function: Vec<f64>) -> Distribution1D {
+        assert!(
+            !function.is_empty(),
+            "a distribution needs at least one cell"
+        );
+        let count = function.len() as f64;
+        let mut cdf = Vec::with_capacity(function.len() + 1);
+        cdf.push(0.0);
+        for value in &function {
+            cdf.push(cdf.last().unwrap() + value.max(0.0) / count);
+        }
+
+        let integral = *cdf.last().unwrap();
+        if integral > 0.0 {
+            cdf.iter_mut().for_each(|c| *c /= integral);
+        } else {
+            cdf.iter_mut()
+                .enumerate()
+                .for_each(|(i, c)| *c = i as f64 / count);
+        }
+
+        Distribution1D {
+            function,
+            cdf,
+            integral,
+        }
+    }
+
+    /// Returns the number of cells.
+    pub fn cell_count(&self) -> usize {
+        self.function.len()
+    }
+
+    /// Returns the density of a cell.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the cell.
+    ///
+    /// # Returns
+    ///
+    /// The probability density anywhere in the cell.
+    pub fn pdf(&self, index: usize) -> f64 {
+        if self.integral > 0.0 {
+            self.function[index].max(0.0) / self.integral
+        } else {
+            1.0
+        }
+    }
+
+    /// Draws a sample from the density.
+    ///
+    /// # Arguments
+    ///
+    /// * `random` - A uniform random number between 0 and 1.
+    ///
+    /// # Returns
+    ///
+    /// The sampled position in the unit interval, its probability density and the index of its cell.
+    pub fn sample(&self, random: f64) -> (f64, f64, usize) {
+        let index = (self.cdf.partition_point(|&c| c <= random) - 1).min(self.cell_count() - 1);
+        let width = self.cdf[index + 1] - self.cdf[index];
+        let offset = if width > 0.0 {
+            ((random - self.cdf[index]) / width).clamp(0.0, 1.0)
+        } else {
+            0.5
+        };
+        (
+            (index as f64 + offset) / self.cell_count() as f64,
+            self.pdf(index),
+            index,
+        )
+    }
+}
+
+/// Represents a piecewise-constant probability density over the unit square, sampled by choosing
+/// a row from the marginal density and then a position within the row.
+#[derive(Debug, Clone)]
+pub struct Distribution2D {
+    /// The density along u within every row, from v = 0 upwards.
+    conditionals: Vec<Distribution1D>,
+    /// The density of choosing each row.
+    marginal: Distribution1D,
+}
+
+impl Distribution2D {
+    /// Creates a new `Distribution2D` proportional to a grid of values.
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The non-negative values of the grid, stored row by row starting at v = 0.
+    /// * `width` - The number of cells along u.
+    ///
+    /// # Returns
+    ///
+    /// A new `Distribution2D` instance.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of values is not a positive multiple of `width`.
+    pub fn new(values: &[f64], width: usize) -> Distribution2D {
+        assert!(
+            width > 0 && !values.is_empty() && values.len().is_multiple_of(width),
+            "the values must fill whole rows"
+        );
+        let conditionals: Vec<Distribution1D> = values
+            .chunks(width)
+            .map(|row| Distribution1D::new(row.to_vec()))
+            .collect();
+        let marginal = Distribution1D::new(conditionals.iter().map(|row| row.integral).collect());
+        Distribution2D {
+            conditionals,
+            marginal,
+        }
+    }
+
+    /// Draws a sample from the density.
+    ///
+    /// # Arguments
+    ///
+    /// * `random_u` - A uniform random number between 0 and 1, used to choose u.
+    /// * `random_v` - A uniform random number between 0 and 1, used to choose v.
+    ///
+    /// # Returns
+    ///
+    /// The sampled coordinates `(u, v)` and their probability density over the unit square.
+    pub fn sample(&self, random_u: f64, random_v: f64) -> (f64, f64, f64) {
+        let (v, pdf_v, row) = self.marginal.sample(random_v);
+        let (u, pdf_u, _) = self.conditionals[row].sample(random_u);
+        (u, v, pdf_u * pdf_v)
+    }
+
+    /// Returns the density at a point of the unit square.
+    ///
+    /// # Arguments
+    ///
+    /// * `u` - The u-coordinate of the point.
+    /// * `v` - The v-coordinate of the point.
+    ///
+    /// # Returns
+    ///
+    /// The probability density at the point.
+    pub fn pdf(&self, u: f64, v: f64) -> f64 {
+        let cell = |coordinate: f64, count: usize| {
+            ((coordinate * count as f64).max(0.0) as usize).min(count - 1)
+        };
+        let row = cell(v, self.marginal.cell_count());
+        let column = cell(u, self.conditionals[row].cell_count());
+        self.conditionals[row].pdf(column) * self.marginal.pdf(row)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distribution_1d() {
+        let distribution = Distribution1D::new(vec![0.0, 1.0, 3.0, 0.0]);
+        assert_eq!(distribution.pdf(0), 0.0);
+        assert!((distribution.pdf(2) - 3.0).abs() < 1e-12);
+
+        for _ in 0..1000 {
+            let (x, pdf, index) = distribution.sample(fastrand::f64());
+            assert!((0.25..0.75).contains(&x), "{}", x);
+            assert_eq!(index, (x * 4.0) as usize);
+            assert_eq!(pdf, distribution.pdf(index));
+        }
+
+        let uniform = Distribution1D::new(vec![0.0; 3]);
+        let (x, pdf, _) = uniform.sample(0.5);
+        assert!((x - 0.5).abs() < 1e-12 && pdf == 1.0);
+    }
+
+    #[test]
+    fn test_distribution_2d() {
+        let values = [1.0, 0.0, 2.0, 5.0, 0.0, 0.0];
+        let distribution = Distribution2D::new(&values, 3);
+
+        // The density integrates to one over the unit square.
+        let total: f64 = (0..6)
+            .map(|i| {
+                let u = ((i % 3) as f64 + 0.5) / 3.0;
+                let v = ((i / 3) as f64 + 0.5) / 2.0;
+                distribution.pdf(u, v) / 6.0
+            })
+            .sum();
+        assert!((total - 1.0).abs() < 1e-12, "{}", total);
+
+        let mut counts = [0; 6];
+        let samples = 20000;
+        for _ in 0..samples {
+            let (u, v, pdf) = distribution.sample(fastrand::f64(), fastrand::f64());
+            assert!((pdf - distribution.pdf(u, v)).abs() < 1e-9);
+            counts[(v * 2.0) as usize * 3 + (u * 3.0) as usize] += 1;
+        }
+        for (count, value) in counts.iter().zip(values) {
+            let expected = value / 8.0;
+            assert!((*count as f64 / samples as f64 - expected).abs() < 0.02);
+        }
+    }
+}
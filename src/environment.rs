@@ -0,0 +1,192 @@
+//! The environment surrounding a scene, giving the light arriving from every direction that
+//! escapes the scene without hitting an object, from a simple gradient to an equirectangular
+//! high dynamic range image of a real place.
+
+use crate::texture::{ImageTexture, ShadingContext, Texture};
+use crate::vector3::Vector3;
+use image::DynamicImage;
+use std::f64::consts::PI;
+
+/// A trait for environments giving the light that reaches the scene from far away.
+pub trait Environment: Send + Sync {
+    /// Returns the light arriving from a direction.
+    ///
+    /// # Arguments
+    ///
+    /// * `direction` - The direction of a ray leaving the scene, which does not have to be
+    ///   normalized.
+    ///
+    /// # Returns
+    ///
+    /// The radiance as a `Vector3`.
+    fn radiance(&self, direction: &Vector3) -> Vector3;
+}
+
+impl Environment for fn(Vector3) -> Vector3 {
+    fn radiance(&self, direction: &Vector3) -> Vector3 {
+        self(*direction)
+    }
+}
+
+/// Represents an environment map, an equirectangular image of the light arriving from every
+/// direction, usually a high dynamic range photograph (HDRI) of a real place.
+///
+/// The center of the image is seen looking down the negative z-axis, the right of the image
+/// towards the positive x-axis, and the top and bottom rows straight up and down.
+#[derive(Debug)]
+pub struct HdrEnvironmentMap {
+    /// The image, repeating around the vertical axis.
+    texture: ImageTexture,
+    /// The angle in radians by which the environment is turned around the vertical axis.
+    rotation: f64,
+    /// The factor scaling the light of the image.
+    intensity: f64,
+}
+
+impl HdrEnvironmentMap {
+    /// Creates a new `HdrEnvironmentMap` from an image file, such as a Radiance HDR or OpenEXR
+    /// file, whose values are kept linear.
+    ///
+    /// # Arguments
+    ///
+    /// * `file_name` - The name of the image file.
+    ///
+    /// # Returns
+    ///
+    /// A new `HdrEnvironmentMap` instance.
+    pub fn new(file_name: &str) -> HdrEnvironmentMap {
+        HdrEnvironmentMap::from_texture(ImageTexture::new(file_name))
+    }
+
+    /// Creates a new `HdrEnvironmentMap` from decoded image data.
+    ///
+    /// # Arguments
+    ///
+    /// * `image` - The image data.
+    ///
+    /// # Returns
+    ///
+    /// A new `HdrEnvironmentMap` instance.
+    pub fn from_image(image: &DynamicImage) -> HdrEnvironmentMap {
+        HdrEnvironmentMap::from_texture(ImageTexture::from_image(image))
+    }
+
+    /// Creates a new `HdrEnvironmentMap` around an image texture.
+    ///
+    /// # Arguments
+    ///
+    /// * `texture` - The texture holding the image.
+    ///
+    /// # Returns
+    ///
+    /// A new `HdrEnvironmentMap` instance.
+    fn from_texture(texture: ImageTexture) -> HdrEnvironmentMap {
+        HdrEnvironmentMap {
+            texture,
+            rotation: 0.0,
+            intensity: 1.0,
+        }
+    }
+
+    /// Turns the environment around the vertical axis, such as to move the sun of an HDRI.
+    ///
+    /// # Arguments
+    ///
+    /// * `angle` - The angle in degrees, counterclockwise seen from above.
+    ///
+    /// # Returns
+    ///
+    /// The updated `HdrEnvironmentMap` instance.
+    pub fn with_rotation(mut self, angle: f64) -> HdrEnvironmentMap {
+        self.rotation = angle.to_radians();
+        self
+    }
+
+    /// Scales the light of the environment.
+    ///
+    /// # Arguments
+    ///
+    /// * `intensity` - The factor scaling the light of the image.
+    ///
+    /// # Returns
+    ///
+    /// The updated `HdrEnvironmentMap` instance.
+    pub fn with_intensity(mut self, intensity: f64) -> HdrEnvironmentMap {
+        self.intensity = intensity;
+        self
+    }
+
+    /// Finds the texture coordinates of the image seen in a direction.
+    ///
+    /// # Arguments
+    ///
+    /// * `direction` - The direction, which does not have to be normalized.
+    ///
+    /// # Returns
+    ///
+    /// The u-coordinate going once around the vertical axis and the v-coordinate going from the
+    /// bottom to the top.
+    fn uv(&self, direction: &Vector3) -> (f64, f64) {
+        let d = direction.normalize();
+        let longitude = d.x.atan2(-d.z) + self.rotation;
+        let latitude = d.y.clamp(-1.0, 1.0).asin();
+        (
+            (0.5 + longitude / (2.0 * PI)).rem_euclid(1.0),
+            0.5 + latitude / PI,
+        )
+    }
+}
+
+impl Environment for HdrEnvironmentMap {
+    /// Returns the light arriving from a direction, read from the image with bilinear filtering.
+    fn radiance(&self, direction: &Vector3) -> Vector3 {
+        let (u, v) = self.uv(direction);
+        self.texture.value(&ShadingContext::from_uv(u, v)) * self.intensity
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An image whose four columns face backwards, left, forwards and right, with the sky
+    /// brighter than the ground.
+    fn compass() -> HdrEnvironmentMap {
+        let mut image = image::Rgb32FImage::new(4, 2);
+        for (x, y, pixel) in image.enumerate_pixels_mut() {
+            let sky = if y == 0 { 10.0 } else { 1.0 };
+            *pixel = image::Rgb([x as f32, sky, 0.0]);
+        }
+        HdrEnvironmentMap::from_image(&DynamicImage::ImageRgb32F(image))
+    }
+
+    #[test]
+    fn test_environment_directions() {
+        let map = compass();
+        assert_eq!(map.uv(&Vector3::new(0.0, 0.0, -1.0)), (0.5, 0.5));
+        let (u, _) = map.uv(&Vector3::new(1.0, 0.0, 0.0));
+        assert!((u - 0.75).abs() < 1e-12);
+        let (_, v) = map.uv(&Vector3::new(0.0, 3.0, 0.0));
+        assert!((v - 1.0).abs() < 1e-12);
+
+        // Looking up and right blends the two right columns mostly from the sky.
+        let value = map.radiance(&Vector3::new(1.0, 0.9, 0.0));
+        assert!((value.x - 2.5).abs() < 1e-6);
+        assert!(value.y > 5.0);
+
+        // Turning the environment by a quarter brings the right column to the front.
+        let turned = compass().with_rotation(90.0).with_intensity(2.0);
+        let (u, _) = turned.uv(&Vector3::new(0.0, 0.0, -1.0));
+        assert!((u - 0.75).abs() < 1e-12);
+        let front = turned.radiance(&Vector3::new(0.0, 0.0, -1.0));
+        assert!((front - map.radiance(&Vector3::new(1.0, 0.0, 0.0)) * 2.0).length() < 1e-9);
+    }
+
+    #[test]
+    fn test_function_environment() {
+        let gradient: fn(Vector3) -> Vector3 = crate::utils::background_gradient;
+        let environment: &dyn Environment = &gradient;
+        let up = Vector3::new(0.0, 1.0, 0.0);
+        assert_eq!(environment.radiance(&up), gradient(up));
+    }
+}
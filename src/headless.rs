@@ -0,0 +1,219 @@
+//! A non-interactive entry point rendering a job file to an image, for containers and render
+//! farms: it never reads the standard input, never waits for a key press, and returns a report of
+//! the render instead of leaving the caller to parse the console output.
+//!
+//! Job files use the format of the render daemon, described in the `daemon` module.
+
+use crate::camera::{Camera, RenderPasses, RenderSettings};
+use crate::daemon::Job;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+/// Describes a finished headless render.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderReport {
+    /// The name of the rendered scene.
+    pub scene: String,
+    /// The path of the saved image.
+    pub output: PathBuf,
+    /// The width of the image in pixels.
+    pub width: u32,
+    /// The height of the image in pixels.
+    pub height: u32,
+    /// The number of samples per pixel.
+    pub samples: u32,
+    /// The time it took to render and save the image.
+    pub elapsed: Duration,
+    /// The average luminance of the standard error of the pixels, the noise left in the image.
+    pub mean_error: f64,
+    /// The fraction of the paths cut off by the depth limit.
+    pub truncated_paths: f64,
+    /// The average coverage of the pixels, the fraction of the image showing objects.
+    pub coverage: f64,
+}
+
+impl RenderReport {
+    /// Creates the report of a finished render.
+    ///
+    /// # Arguments
+    ///
+    /// * `scene` - The name of the rendered scene.
+    /// * `output` - The path of the saved image.
+    /// * `camera` - The camera the scene was rendered with.
+    /// * `passes` - The images of the render.
+    /// * `elapsed` - The time it took to render and save the image.
+    ///
+    /// # Returns
+    ///
+    /// A new `RenderReport` instance.
+    pub fn new(
+        scene: &str,
+        output: &Path,
+        camera: &Camera,
+        passes: &RenderPasses,
+        elapsed: Duration,
+    ) -> RenderReport {
+        let pixels = passes.color.len().max(1) as f64;
+        RenderReport {
+            scene: scene.to_string(),
+            output: output.to_path_buf(),
+            width: camera.image_width(),
+            height: camera.image_height(),
+            samples: camera.samples_per_pixel(),
+            elapsed,
+            mean_error: passes.error.iter().map(|e| e.luminance()).sum::<f64>() / pixels,
+            truncated_paths: passes.paths.truncated_fraction(),
+            coverage: passes.coverage.iter().sum::<f64>() / pixels,
+        }
+    }
+
+    /// Parses a report written as `key = value` lines by its `Display` implementation, such as
+    /// the report files saved next to images.
+    ///
+    /// # Arguments
+    ///
+    /// * `contents` - The text of the report.
+    ///
+    /// # Returns
+    ///
+    /// The parsed `RenderReport`, or a description of the first missing or invalid value.
+    pub fn parse(contents: &str) -> Result<RenderReport, String> {
+        let values: HashMap<&str, &str> = contents
+            .lines()
+            .filter_map(|line| line.split_once('='))
+            .map(|(key, value)| (key.trim(), value.trim()))
+            .collect();
+        fn field<T: FromStr>(values: &HashMap<&str, &str>, key: &str) -> Result<T, String> {
+            let value = values
+                .get(key)
+                .ok_or_else(|| format!("missing `{}`", key))?;
+            value
+                .parse()
+                .map_err(|_| format!("invalid value for `{}`: `{}`", key, value))
+        }
+
+        Ok(RenderReport {
+            scene: field(&values, "scene")?,
+            output: field(&values, "output")?,
+            width: field(&values, "width")?,
+            height: field(&values, "height")?,
+            samples: field(&values, "samples")?,
+            elapsed: Duration::from_millis(field(&values, "elapsed_ms")?),
+            mean_error: field(&values, "mean_error")?,
+            truncated_paths: field(&values, "truncated_paths")?,
+            coverage: field(&values, "coverage")?,
+        })
+    }
+}
+
+impl fmt::Display for RenderReport {
+    /// Writes the report as `key = value` lines, like the job files.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "scene = {}", self.scene)?;
+        writeln!(f, "output = {}", self.output.display())?;
+        writeln!(f, "width = {}", self.width)?;
+        writeln!(f, "height = {}", self.height)?;
+        writeln!(f, "samples = {}", self.samples)?;
+        writeln!(f, "elapsed_ms = {}", self.elapsed.as_millis())?;
+        writeln!(f, "mean_error = {:.6}", self.mean_error)?;
+        writeln!(f, "truncated_paths = {:.6}", self.truncated_paths)?;
+        write!(f, "coverage = {:.6}", self.coverage)
+    }
+}
+
+/// Renders a job file to an image without any interaction.
+///
+/// # Arguments
+///
+/// * `job_file` - The path of the job file. `.cube` files and the output it names are relative to
+///   its directory.
+/// * `settings` - The settings overriding the ones of the job, such as the width or the number of
+///   samples.
+/// * `output` - The path of the image file, or `None` to use the output of the job.
+///
+/// # Returns
+///
+/// The report of the render, or a description of why the job could not be rendered or saved.
+pub fn render(
+    job_file: &Path,
+    settings: &RenderSettings,
+    output: Option<&Path>,
+) -> Result<RenderReport, String> {
+    let contents = fs::read_to_string(job_file)
+        .map_err(|e| format!("failed to read {}: {}", job_file.display(), e))?;
+    let job = Job::parse(&contents)?;
+    let directory = job_file.parent().unwrap_or(Path::new("."));
+
+    // Scenes placing objects at random are built the same way on every seeded run.
+    if let Some(seed) = settings.seed {
+        fastrand::seed(seed);
+    }
+    let scene = job.entry()?.scene(settings)?;
+    let mut camera = job.camera(&scene, directory, settings)?;
+    // The report is the only output on stdout, so it can be read by the caller.
+    camera.set_quiet(true);
+    let output = output.map_or_else(|| directory.join(&job.output), Path::to_path_buf);
+
+    let start = Instant::now();
+    let passes = camera.render_and_save(&scene, &output.to_string_lossy())?;
+    Ok(RenderReport::new(
+        &job.scene,
+        &output,
+        &camera,
+        &passes,
+        start.elapsed(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_headless_render() {
+        let directory = std::env::temp_dir().join(format!("headless_{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let job_file = directory.join("preview.job");
+        fs::write(
+            &job_file,
+            "scene = cornell_box\nwidth = 64\noutput = box.png",
+        )
+        .unwrap();
+
+        let settings = RenderSettings {
+            width: Some(16),
+            samples: Some(2),
+            ..RenderSettings::default()
+        };
+        let report = render(&job_file, &settings, None).unwrap();
+        assert_eq!(report.output, directory.join("box.png"));
+        assert!(report.output.exists());
+        assert_eq!((report.width, report.height, report.samples), (16, 9, 2));
+        assert!(report.coverage > 0.0 && report.coverage <= 1.0);
+        assert!(report.to_string().contains("scene = cornell_box"));
+        let parsed = RenderReport::parse(&report.to_string()).unwrap();
+        assert_eq!(parsed.samples, 2);
+        assert_eq!(parsed.elapsed.as_millis(), report.elapsed.as_millis());
+        assert!(RenderReport::parse("scene = cornell_box").is_err());
+
+        let elsewhere = directory.join("elsewhere.png");
+        let report = render(&job_file, &settings, Some(&elsewhere)).unwrap();
+        assert_eq!(report.output, elsewhere);
+        assert!(elsewhere.exists());
+
+        assert!(render(&job_file, &settings, Some(&directory.join("box.unknown"))).is_err());
+        assert!(render(&directory.join("absent.job"), &settings, None).is_err());
+        fs::write(&job_file, "scene = framed_model\noutput = model.png").unwrap();
+        let report = render(&job_file, &settings, None).unwrap();
+        assert_eq!(report.output, directory.join("model.png"));
+        assert_eq!((report.width, report.samples), (16, 2));
+        fs::write(&job_file, "scene = missing").unwrap();
+        assert!(render(&job_file, &settings, None).is_err());
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
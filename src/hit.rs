@@ -1,5 +1,9 @@
+use crate::aabb::Aabb;
 use crate::material::Material;
+use crate::material_table::MaterialTable;
+use crate::onb::Onb;
 use crate::ray::Ray;
+use crate::statistics::SceneStatistics;
 use crate::vector3::Vector3;
 #[derive(Debug)]
 /// Represents a record of a hit point in the scene.
@@ -8,6 +12,8 @@ pub struct HitRecord<'a> {
     pub t: f64,
     /// The position of the hit point.
     pub poz: Vector3,
+    /// The position of the hit point in the space of the primitive, before any transformation.
+    pub object_poz: Vector3,
     /// The normal vector at the hit point.
     pub normal: Vector3,
     /// Indicates whether the hit point is on the front face of the object.
@@ -18,6 +24,26 @@ pub struct HitRecord<'a> {
     pub u: f64,
     /// The v-coordinate for texture mapping.
     pub v: f64,
+    /// The derivative of the position with respect to the u-coordinate, pointing where it
+    /// increases along the surface, or zero if the object does not provide it.
+    pub tangent: Vector3,
+    /// The derivative of the position with respect to the v-coordinate, pointing where it
+    /// increases along the surface, or zero if the object does not provide it.
+    pub bitangent: Vector3,
+    /// The fraction of the hemisphere above the hit point blocked by nearby objects, filled in
+    /// by `Scene::query_surface`.
+    pub occlusion: f64,
+    /// How sharply the surface bends around the hit point, from -1 in creases to 1 on edges,
+    /// filled in by `Scene::query_surface`.
+    pub curvature: f64,
+    /// The random value between 0 and 1 of the instance the hit object belongs to, or 0 outside
+    /// of instances.
+    pub variation: f64,
+    /// The color tint of the instance the hit object belongs to, or white outside of instances.
+    pub tint: Vector3,
+    /// The offset from the hit point to the point rays leaving the surface start from, which
+    /// lifts them off the flat triangles of smooth-shaded meshes, or zero for other objects.
+    pub shading_offset: Vector3,
 }
 
 impl<'a> HitRecord<'a> {
@@ -38,14 +64,80 @@ impl<'a> HitRecord<'a> {
         HitRecord {
             t,
             poz,
+            object_poz: poz,
             front_face: true,
             normal: Vector3::new(1.0, 0.0, 0.0),
             material,
             u,
             v,
+            tangent: Vector3::new(0.0, 0.0, 0.0),
+            bitangent: Vector3::new(0.0, 0.0, 0.0),
+            occlusion: 0.0,
+            curvature: 0.0,
+            variation: 0.0,
+            tint: Vector3::new(1.0, 1.0, 1.0),
+            shading_offset: Vector3::new(0.0, 0.0, 0.0),
         }
     }
 
+    /// Returns the point shading happens at, the hit point moved by its shading offset.
+    pub fn shading_point(&self) -> Vector3 {
+        self.poz + self.shading_offset
+    }
+
+    /// Returns the point a ray leaving the surface in a given direction starts from. Rays leaving
+    /// on the side of the normal start at the shading point, so that the flat triangles under a
+    /// smooth-shaded mesh do not shadow them, and the others at the hit point.
+    ///
+    /// # Arguments
+    ///
+    /// * `direction` - The direction of the leaving ray.
+    ///
+    /// # Returns
+    ///
+    /// The origin of the leaving ray.
+    pub fn ray_origin(&self, direction: &Vector3) -> Vector3 {
+        if direction.dot(&self.normal) > 0.0 {
+            self.shading_point()
+        } else {
+            self.poz
+        }
+    }
+
+    /// Sets the derivatives of the position with respect to the texture coordinates, whose
+    /// directions orient normal maps and whose lengths size the footprint of rays in texture
+    /// space.
+    ///
+    /// # Arguments
+    ///
+    /// * `tangent` - The derivative of the position with respect to the u-coordinate.
+    /// * `bitangent` - The derivative of the position with respect to the v-coordinate.
+    pub fn set_tangents(&mut self, tangent: Vector3, bitangent: Vector3) {
+        self.tangent = tangent;
+        self.bitangent = bitangent;
+    }
+
+    /// Builds an orthonormal basis around the normal whose first two axes follow the directions
+    /// in which the texture coordinates increase, as far as they are perpendicular to the normal.
+    /// Objects without tangents get an arbitrary basis around the normal.
+    ///
+    /// # Returns
+    ///
+    /// The basis, with the tangent as `u`, the bitangent as `v` and the normal as `w`.
+    pub fn tangent_frame(&self) -> Onb {
+        let w = self.normal;
+        let tangent = self.tangent - self.tangent.dot(&w) * w;
+        if tangent.is_near_zero() {
+            return Onb::new(&w);
+        }
+        let u = tangent.normalize();
+        // The bitangent only chooses which way the second axis points, so mirrored texture
+        // coordinates keep their orientation.
+        let v = w.cross(&u);
+        let v = if v.dot(&self.bitangent) < 0.0 { -v } else { v };
+        Onb { u, v, w }
+    }
+
     /// Sets the face normal of the hit record based on the ray and outward normal.
     ///
     /// # Arguments
@@ -74,5 +166,107 @@ pub trait Hittable: Send + Sync {
     /// # Returns
     ///
     /// An `Option` containing the `HitRecord` if an intersection is found, or `None` if no intersection is found.
-    fn hit(&self, ray: &Ray, interval: (f64, f64)) -> Option<HitRecord>;
+    fn hit(&self, ray: &Ray, interval: (f64, f64)) -> Option<HitRecord<'_>>;
+
+    /// Returns `true` if the object emits light and should be sampled as a light source.
+    fn is_emissive(&self) -> bool {
+        false
+    }
+
+    /// Returns the total power emitted by the object.
+    ///
+    /// # Returns
+    ///
+    /// The emitted power as a `Vector3`.
+    fn power(&self) -> Vector3 {
+        Vector3::new(0.0, 0.0, 0.0)
+    }
+
+    /// Returns the probability density with which `random` generates a direction.
+    ///
+    /// # Arguments
+    ///
+    /// * `_origin` - The point from which the object is sampled.
+    /// * `_direction` - The direction to evaluate.
+    ///
+    /// # Returns
+    ///
+    /// The probability density with respect to solid angle.
+    fn pdf_value(&self, _origin: &Vector3, _direction: &Vector3) -> f64 {
+        0.0
+    }
+
+    /// Generates a random direction from a point towards the object.
+    ///
+    /// # Arguments
+    ///
+    /// * `_origin` - The point from which the object is sampled.
+    ///
+    /// # Returns
+    ///
+    /// The generated direction.
+    fn random(&self, _origin: &Vector3) -> Vector3 {
+        Vector3::new(1.0, 0.0, 0.0)
+    }
+
+    /// Finds the point of the surface of the object closest to a given point.
+    ///
+    /// # Arguments
+    ///
+    /// * `_point` - The query point.
+    ///
+    /// # Returns
+    ///
+    /// The closest point of the surface, or `None` if the object does not support the query.
+    fn closest_point(&self, _point: &Vector3) -> Option<Vector3> {
+        None
+    }
+
+    /// Follows a point on the surface of the object from one scene time to another, so moving
+    /// objects can report how far their surface travels while the shutter is open.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The point of the surface at the first time.
+    /// * `_from` - The scene time at which the point is given.
+    /// * `_to` - The scene time to follow the point to.
+    ///
+    /// # Returns
+    ///
+    /// The position of the same point of the surface at the second time, which is the point
+    /// itself for objects that do not move.
+    fn point_at_time(&self, point: &Vector3, _from: f64, _to: f64) -> Vector3 {
+        *point
+    }
+
+    /// Returns a box enclosing the object, so groups of objects can skip rays that miss it.
+    ///
+    /// # Returns
+    ///
+    /// The bounding box, or `None` if the object is unbounded or does not report its extent.
+    fn bounding_box(&self) -> Option<Aabb> {
+        None
+    }
+
+    /// Adds the materials of the object to a table, so records of its hits can be stored with a
+    /// handle to their material. Objects made of other objects add the materials of their parts.
+    ///
+    /// # Arguments
+    ///
+    /// * `_table` - The table to add the materials to.
+    fn collect_materials(&self, _table: &mut MaterialTable) {}
+
+    /// Counts the primitives, materials and textures making up the object.
+    /// Objects made of other objects count their parts instead of themselves.
+    ///
+    /// # Arguments
+    ///
+    /// * `statistics` - The statistics to add the object to.
+    fn collect_statistics(&self, statistics: &mut SceneStatistics) {
+        let name = std::any::type_name::<Self>();
+        statistics.add_primitive(
+            name.rsplit("::").next().unwrap_or(name),
+            std::mem::size_of_val(self),
+        );
+    }
 }
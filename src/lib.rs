@@ -0,0 +1,48 @@
+//! A path tracer in the style of Ray Tracing in One Weekend, grown into a renderer of scenes made
+//! of shapes, meshes, volumes and lights, with a command line in `main.rs` built on this library.
+
+pub mod aabb;
+pub mod accumulation;
+pub mod analysis;
+pub mod aov;
+pub mod aperture;
+pub mod camera;
+pub mod cli;
+pub mod contact_sheet;
+pub mod daemon;
+pub mod decal;
+pub mod deep;
+pub mod denoise;
+pub mod distribution;
+pub mod environment;
+pub mod headless;
+pub mod hit;
+pub mod material;
+pub mod material_kind;
+pub mod material_table;
+pub mod microfacet;
+pub mod onb;
+pub mod output;
+pub mod palette;
+pub mod parameter_sweep;
+pub mod pdf;
+pub mod perlin;
+pub mod preview;
+pub mod profiling;
+pub mod query;
+pub mod ray;
+#[cfg(test)]
+mod regression;
+pub mod scatter;
+pub mod scene;
+pub mod scene_graph;
+pub mod scene_registry;
+pub mod scenes;
+pub mod shapes;
+pub mod shutter;
+pub mod statistics;
+pub mod texture;
+pub mod transformation;
+pub mod unit;
+pub mod utils;
+pub mod vector3;
@@ -1,37 +1,236 @@
-mod camera;
-pub mod hit;
-pub mod material;
-mod ray;
-mod scenes;
-mod shapes;
-mod texture;
-pub mod transformation;
-mod utils;
-mod vector3;
-
-use crate::scenes::{
-    checkered_spheres, colored_simple_lights, cornell_box, cornell_smoke, earth, final_scene,
-    quads, simple_lights, spheres,
-};
+use ray_tracer_rust::camera::RenderSettings;
+use ray_tracer_rust::cli::{Arguments, CONTACT_SHEET_COMMAND, MERGE_COMMAND, USAGE};
+use ray_tracer_rust::contact_sheet::ContactSheet;
+use ray_tracer_rust::daemon::Daemon;
+use ray_tracer_rust::output::OutputTransform;
+use ray_tracer_rust::profiling::Profiler;
+use ray_tracer_rust::scene_registry::SCENES;
+use ray_tracer_rust::{accumulation, contact_sheet, headless, scene_registry};
 use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
 use std::time::Instant;
 
+/// Merges the accumulation files given after the `merge` command into the image of all their
+/// samples, and into a merged accumulation file if one is asked for, so the merged render can be
+/// resumed later.
+///
+/// # Arguments
+///
+/// * `arguments` - The parsed command-line arguments.
+///
+/// # Returns
+///
+/// The exit code, telling whether the image was saved.
+fn merge(arguments: &Arguments) -> ExitCode {
+    let inputs: Vec<PathBuf> = arguments.inputs.iter().map(PathBuf::from).collect();
+    let merged = match accumulation::merge_files(&inputs) {
+        Ok(merged) => merged,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let settings = &arguments.settings;
+    let transform = match &settings.film {
+        Some(curves) => OutputTransform::default().with_curves(curves.clone()),
+        None => OutputTransform::default(),
+    };
+    let output = settings.output.as_deref().unwrap_or("output.png");
+    if let Err(e) = merged.save_image(&transform, Path::new(output)) {
+        eprintln!("Failed to save image: {}", e);
+        return ExitCode::FAILURE;
+    }
+    println!(
+        "Merged {} samples per pixel from {} files into {}",
+        merged.samples(),
+        inputs.len(),
+        output
+    );
+
+    if let Some(path) = &settings.accumulation {
+        if let Err(e) = merged.save(Path::new(path)) {
+            eprintln!("Failed to save accumulation file: {}", e);
+            return ExitCode::FAILURE;
+        }
+        println!("Successfully saved accumulation file to {}", path);
+    }
+    ExitCode::SUCCESS
+}
+
+/// Lays out the renders of the directory given after the `contact-sheet` command as a grid of
+/// labeled thumbnails, and saves it to the output.
+///
+/// # Arguments
+///
+/// * `arguments` - The parsed command-line arguments, with the directory as their only input.
+///
+/// # Returns
+///
+/// The exit code, telling whether the contact sheet was saved.
+fn contact_sheet(arguments: &Arguments) -> ExitCode {
+    let directory = Path::new(&arguments.inputs[0]);
+    let output = PathBuf::from(
+        arguments
+            .settings
+            .output
+            .as_deref()
+            .unwrap_or("contact_sheet.png"),
+    );
+    // The sheet of an earlier run may be saved in the same directory.
+    let images: Vec<PathBuf> = match contact_sheet::images_in(directory) {
+        Ok(images) => images.into_iter().filter(|path| *path != output).collect(),
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut layout = ContactSheet::new();
+    if let Some(columns) = arguments.columns {
+        layout = layout.with_columns(columns);
+    }
+    if let Some(width) = arguments.settings.width {
+        layout = layout.with_thumbnail_width(width);
+    }
+    let sheet = match layout.render(&images) {
+        Ok(sheet) => sheet,
+        Err(e) => {
+            eprintln!("Failed to lay out {}: {}", directory.display(), e);
+            return ExitCode::FAILURE;
+        }
+    };
+    if let Err(e) = sheet.save(&output) {
+        eprintln!("Failed to save contact sheet: {}", e);
+        return ExitCode::FAILURE;
+    }
+    println!(
+        "Successfully saved contact sheet of {} images to {}",
+        images.len(),
+        output.display()
+    );
+    ExitCode::SUCCESS
+}
+
 /// Main function
-fn main() {
-    let now = Instant::now();
+fn main() -> ExitCode {
+    let interactive = std::env::args().len() == 1;
+    let arguments = match Arguments::parse(std::env::args().skip(1)) {
+        Ok(arguments) => arguments,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+    if arguments.help {
+        println!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+    if arguments.list {
+        SCENES.iter().for_each(|entry| println!("{}", entry.name));
+        return ExitCode::SUCCESS;
+    }
+
+    // The profiler is kept until the program exits, writing the flame graph when dropped.
+    let _profiler = match Profiler::start(arguments.flamegraph.as_deref().map(Path::new)) {
+        Ok(profiler) => profiler,
+        Err(e) => {
+            eprintln!("Failed to start profiling: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if let Some(threads) = arguments.threads {
+        if let Err(e) = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build_global()
+        {
+            eprintln!("Failed to set the number of threads: {}", e);
+        }
+    }
+
+    if arguments.scene == MERGE_COMMAND {
+        return merge(&arguments);
+    }
+    if arguments.scene == CONTACT_SHEET_COMMAND {
+        return contact_sheet(&arguments);
+    }
+
+    if let Some(directory) = &arguments.daemon {
+        // The images and failed jobs go next to the watched directory unless told otherwise.
+        let daemon = Daemon::new(Path::new(directory));
+        let output = arguments
+            .daemon_output
+            .as_ref()
+            .map_or_else(|| daemon.sibling_directory("output"), PathBuf::from);
+        let errors = arguments
+            .daemon_errors
+            .as_ref()
+            .map_or_else(|| daemon.sibling_directory("failed"), PathBuf::from);
+        daemon
+            .with_output_directory(&output)
+            .with_error_directory(&errors)
+            .run();
+        return ExitCode::SUCCESS;
+    }
 
-    // Scenes to be rendered
-    match 10 {
-        1 => spheres(),
-        2 => checkered_spheres(),
-        3 => earth(),
-        4 => quads(),
-        5 => simple_lights(),
-        6 => colored_simple_lights(),
-        7 => cornell_box(),
-        8 => cornell_smoke(),
-        9 => final_scene(1920, 10000, 5, true),
-        _ => final_scene(400, 250, 10, true),
+    // Job files are rendered for containers and render farms, which get a report and an exit
+    // code instead of a prompt.
+    if let Some(job) = &arguments.job {
+        let output = arguments.settings.output.as_deref().map(Path::new);
+        return match headless::render(Path::new(job), &arguments.settings, output) {
+            Ok(report) => {
+                println!("{}", report);
+                ExitCode::SUCCESS
+            }
+            Err(e) => {
+                eprintln!("Failed to render {}: {}", job, e);
+                ExitCode::FAILURE
+            }
+        };
+    }
+
+    let Some(entry) = scene_registry::find(&arguments.scene) else {
+        eprintln!(
+            "Unknown scene `{}`, use --list to see the available scenes",
+            arguments.scene
+        );
+        return ExitCode::FAILURE;
+    };
+
+    // Scenes placing objects at random are built the same way on every seeded run, and the
+    // camera reseeds the generator for every pixel.
+    if let Some(seed) = arguments.settings.seed {
+        fastrand::seed(seed);
+    }
+
+    // Sweeps render every frame with the same seed, reseeding the generator for each.
+    if let Some(sweep) = &arguments.sweep {
+        let render_frame = |settings: &RenderSettings| {
+            if let Some(seed) = settings.seed {
+                fastrand::seed(seed);
+            }
+            if let Err(e) = entry.render(settings) {
+                eprintln!("Failed to build {}: {}", entry.name, e);
+            }
+        };
+        return match sweep.run(render_frame, &arguments.settings) {
+            Ok(strip) => {
+                println!("Successfully saved sweep to {}", strip.display());
+                ExitCode::SUCCESS
+            }
+            Err(e) => {
+                eprintln!("Failed to render the sweep: {}", e);
+                ExitCode::FAILURE
+            }
+        };
+    }
+
+    let now = Instant::now();
+    if let Err(e) = entry.render(&arguments.settings) {
+        eprintln!("Failed to build {}: {}", entry.name, e);
+        return ExitCode::FAILURE;
     }
 
     println!(
@@ -39,7 +238,12 @@ fn main() {
         now.elapsed().as_millis()
     );
 
-    println!("Press any key to close...");
-    let mut buffer = [0; 1];
-    let _ = io::stdin().read(&mut buffer);
+    // Keep the console open when the program was started without arguments, such as by a
+    // double click.
+    if interactive {
+        println!("Press any key to close...");
+        let mut buffer = [0; 1];
+        let _ = io::stdin().read(&mut buffer);
+    }
+    ExitCode::SUCCESS
 }
@@ -1,9 +1,69 @@
 use crate::hit::HitRecord;
+use crate::microfacet::{ggx_d, ggx_dielectric_bsdf, ggx_g2};
+use crate::onb::Onb;
+use crate::pdf::{
+    henyey_greenstein, CosinePdf, GgxDielectricPdf, GgxPdf, HenyeyGreensteinPdf, Pdf, SpherePdf,
+};
 use crate::ray::Ray;
-use crate::texture::{SolidTexture, Texture};
+use crate::statistics::SceneStatistics;
+use crate::texture::{ShadingContext, SolidTexture, Texture};
 use crate::utils::{reflect, refract};
 use crate::vector3::Vector3;
+use std::f64::consts::PI;
 use std::fmt::Debug;
+use std::sync::Arc;
+
+/// Describes how a ray leaves a surface after scattering.
+pub enum ScatterType {
+    /// The ray follows a single deterministic direction, such as a mirror reflection.
+    Specular(Ray),
+    /// The direction is drawn from a probability density over directions.
+    Pdf(Box<dyn Pdf>),
+}
+
+/// Represents the result of scattering a ray off a material.
+pub struct ScatterRecord {
+    /// The attenuation (color) applied to the light carried by the scattered ray.
+    pub attenuation: Vector3,
+    /// How the scattered ray leaves the surface.
+    pub scatter_type: ScatterType,
+}
+
+impl ScatterRecord {
+    /// Creates a new `ScatterRecord` for a ray following a single deterministic direction.
+    ///
+    /// # Arguments
+    ///
+    /// * `attenuation` - The attenuation applied to the scattered light.
+    /// * `ray` - The scattered ray.
+    ///
+    /// # Returns
+    ///
+    /// A new `ScatterRecord` instance.
+    pub fn specular(attenuation: Vector3, ray: Ray) -> ScatterRecord {
+        ScatterRecord {
+            attenuation,
+            scatter_type: ScatterType::Specular(ray),
+        }
+    }
+
+    /// Creates a new `ScatterRecord` for a direction drawn from a probability density.
+    ///
+    /// # Arguments
+    ///
+    /// * `attenuation` - The attenuation applied to the scattered light.
+    /// * `pdf` - The density from which the scattered direction is drawn.
+    ///
+    /// # Returns
+    ///
+    /// A new `ScatterRecord` instance.
+    pub fn from_pdf(attenuation: Vector3, pdf: Box<dyn Pdf>) -> ScatterRecord {
+        ScatterRecord {
+            attenuation,
+            scatter_type: ScatterType::Pdf(pdf),
+        }
+    }
+}
 
 /// A trait for materials that can scatter rays and emit light
 pub trait Material: Send + Sync + Debug {
@@ -16,23 +76,81 @@ pub trait Material: Send + Sync + Debug {
     ///
     /// # Returns
     ///
-    /// An `Option` containing a tuple of the scattered ray and the attenuation vector, or `None` if no scattering occurs.
-    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<(Ray, Vector3)>;
+    /// An `Option` containing the `ScatterRecord`, or `None` if the ray is absorbed.
+    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<ScatterRecord>;
+
+    /// Returns the density with which the material scatters light from `ray` into `scattered`.
+    /// Multiplied by the attenuation, it gives the BSDF times the cosine of the scattered direction.
+    ///
+    /// # Arguments
+    ///
+    /// * `_ray` - The incoming ray.
+    /// * `_hit_record` - The record of the hit point.
+    /// * `_scattered` - The scattered ray.
+    ///
+    /// # Returns
+    ///
+    /// The scattering density with respect to solid angle.
+    fn scattering_pdf(&self, _ray: &Ray, _hit_record: &HitRecord, _scattered: &Ray) -> f64 {
+        0.0
+    }
 
     /// Returns the emitted light from the material at a given point.
     ///
     /// # Arguments
     ///
-    /// * `_u` - The u-coordinate for texture mapping.
-    /// * `_v` - The v-coordinate for texture mapping.
-    /// * `_p` - The position at which the light is emitted.
+    /// * `_context` - The shading context of the point at which the light is emitted.
     ///
     /// # Returns
     ///
     /// The emitted light as a `Vector3`.
-    fn emitted(&self, _u: f64, _v: f64, _p: &Vector3) -> Vector3 {
+    fn emitted(&self, _context: &ShadingContext) -> Vector3 {
+        Vector3::new(0.0, 0.0, 0.0)
+    }
+
+    /// Returns `true` if the material is a perfect mirror, reflecting every ray into a single
+    /// direction without changing its color other than by a constant attenuation.
+    fn is_mirror(&self) -> bool {
+        false
+    }
+
+    /// Returns `true` if the material emits light, so objects made of it can be sampled as lights.
+    fn is_emissive(&self) -> bool {
+        false
+    }
+
+    /// Returns `true` if the material reads the occlusion or curvature of the points it shades,
+    /// which are then queried from the scene before the points are shaded.
+    fn surface_queries(&self) -> bool {
+        false
+    }
+
+    /// Returns the power emitted by the material per unit of surface area.
+    ///
+    /// # Returns
+    ///
+    /// The emitted power per unit area as a `Vector3`.
+    fn power(&self) -> Vector3 {
         Vector3::new(0.0, 0.0, 0.0)
     }
+
+    /// Returns the name of the material, such as `Lambertian`.
+    fn name(&self) -> &'static str {
+        let name = std::any::type_name::<Self>();
+        name.rsplit("::").next().unwrap_or(name)
+    }
+
+    /// Counts the memory taken by the material and its textures, once per shared material.
+    ///
+    /// # Arguments
+    ///
+    /// * `statistics` - The statistics to add the material to.
+    fn collect_statistics(&self, statistics: &mut SceneStatistics) {
+        statistics.add_material(
+            self as *const Self as *const () as usize,
+            std::mem::size_of_val(self),
+        );
+    }
 }
 
 /// Represents a Lambertian (diffuse) material.
@@ -47,24 +165,32 @@ impl Material for Lambertian {
     ///
     /// # Arguments
     ///
-    /// * `_ray` - The incoming ray.
+    /// * `ray` - The incoming ray.
     /// * `hit_record` - The record of the hit point.
     ///
     /// # Returns
     ///
-    /// An `Option` containing a tuple of the scattered ray and the attenuation vector, or `None` if no scattering occurs.
-    fn scatter(&self, _ray: &Ray, hit_record: &HitRecord) -> Option<(Ray, Vector3)> {
-        let mut scatter_direction = hit_record.normal + Vector3::random_in_unit_sphere();
+    /// An `Option` containing the `ScatterRecord`, with directions drawn from a cosine density.
+    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<ScatterRecord> {
+        let attenuation = self.texture.value(&ShadingContext::new(ray, hit_record));
+        Some(ScatterRecord::from_pdf(
+            attenuation,
+            Box::new(CosinePdf::new(&hit_record.normal)),
+        ))
+    }
 
-        if scatter_direction.is_near_zero() {
-            scatter_direction = hit_record.normal;
-        }
+    fn scattering_pdf(&self, _ray: &Ray, hit_record: &HitRecord, scattered: &Ray) -> f64 {
+        (hit_record.normal.dot(&scattered.direction) / PI).max(0.0)
+    }
+
+    fn surface_queries(&self) -> bool {
+        self.texture.surface_queries()
+    }
 
-        let scattered = Ray::new(hit_record.poz, scatter_direction);
-        let attenuation = self
-            .texture
-            .value(hit_record.u, hit_record.v, &hit_record.poz);
-        Some((scattered, attenuation))
+    fn collect_statistics(&self, statistics: &mut SceneStatistics) {
+        if statistics.add_material(self as *const Self as usize, std::mem::size_of_val(self)) {
+            self.texture.collect_statistics(statistics);
+        }
     }
 }
 
@@ -98,17 +224,18 @@ impl Lambertian {
     }
 }
 
-/// Represents a metallic material.
-#[derive(Debug, Default)]
+/// Represents a metallic material, modeled as a GGX microfacet conductor.
+#[derive(Debug)]
 pub struct Metal {
-    /// The albedo (color) of the material.
-    albedo: Vector3,
-    /// The fuzziness of the reflection.
-    fuzz: f64,
+    /// The texture of the albedo (color) of the material.
+    texture: Box<dyn Texture>,
+    /// The perceptual roughness of the surface, where zero is a perfect mirror.
+    roughness: f64,
 }
 
 impl Material for Metal {
     /// Scatters a ray upon hitting the metallic material.
+    /// The reflected direction is importance sampled from the visible GGX microfacet normals.
     ///
     /// # Arguments
     ///
@@ -117,34 +244,117 @@ impl Material for Metal {
     ///
     /// # Returns
     ///
-    /// An `Option` containing a tuple of the scattered ray and the attenuation vector, or `None` if no scattering occurs
-    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<(Ray, Vector3)> {
-        let mut reflected = reflect(ray.direction, hit_record.normal);
-        reflected = reflected.normalize() + self.fuzz * Vector3::random_in_unit_sphere();
+    /// An `Option` containing the `ScatterRecord`, or `None` if the ray arrives from below the surface.
+    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<ScatterRecord> {
+        let albedo = self.texture.value(&ShadingContext::new(ray, hit_record));
+        if self.is_mirror() {
+            let reflected = reflect(ray.direction, hit_record.normal);
+            return Some(ScatterRecord::specular(
+                albedo,
+                Ray::with_time(hit_record.ray_origin(&reflected), reflected, ray.time),
+            ));
+        }
 
-        let scattered = Ray::new(hit_record.poz, reflected);
-        let attenuation = self.albedo;
+        if ray.direction.dot(&hit_record.normal) >= 0.0 {
+            return None;
+        }
+
+        Some(ScatterRecord::from_pdf(
+            albedo,
+            Box::new(GgxPdf::new(
+                &hit_record.normal,
+                &-ray.direction,
+                self.alpha(),
+            )),
+        ))
+    }
+
+    /// Returns the GGX microfacet BRDF times the cosine of the scattered direction, divided by
+    /// the albedo of the material.
+    fn scattering_pdf(&self, ray: &Ray, hit_record: &HitRecord, scattered: &Ray) -> f64 {
+        let onb = Onb::new(&hit_record.normal);
+        let wo = onb.world_to_local(&-ray.direction);
+        let wi = onb.world_to_local(&scattered.direction);
+        if wo.z <= 0.0 || wi.z <= 0.0 {
+            return 0.0;
+        }
+
+        let alpha = self.alpha();
+        let h = (wo + wi).normalize();
+        ggx_d(&h, alpha) * ggx_g2(&wo, &wi, alpha) / (4.0 * wo.z)
+    }
+
+    /// Returns `true` if the surface is smooth enough to be treated as a perfect mirror.
+    fn is_mirror(&self) -> bool {
+        self.roughness < Self::MIRROR_ROUGHNESS
+    }
+
+    fn surface_queries(&self) -> bool {
+        self.texture.surface_queries()
+    }
 
-        Some((scattered, attenuation))
+    fn collect_statistics(&self, statistics: &mut SceneStatistics) {
+        if statistics.add_material(self as *const Self as usize, std::mem::size_of_val(self)) {
+            self.texture.collect_statistics(statistics);
+        }
     }
 }
 
 impl Metal {
-    /// Creates a new metallic material.
+    /// Perceptual roughness below which the metal is treated as a perfect mirror.
+    const MIRROR_ROUGHNESS: f64 = 1e-3;
+
+    /// Creates a new metallic material from the legacy fuzz parameter.
+    /// The fuzz maps directly onto the perceptual roughness of the surface.
+    ///
+    /// # Arguments
+    ///
+    /// * `albedo` - The color of the material.
+    /// * `fuzz` - The fuzziness of the reflection, between 0 and 1.
+    ///
+    /// # Returns
+    ///
+    /// A new `Metal` instance.
+    pub fn new(albedo: Vector3, fuzz: f64) -> Metal {
+        Metal::from_roughness(albedo, fuzz.clamp(0.0, 1.0))
+    }
+
+    /// Creates a new metallic material with the given perceptual roughness.
+    /// The GGX distribution uses the square of the perceptual roughness as its `alpha`.
     ///
     /// # Arguments
     ///
     /// * `albedo` - The color of the material.
-    /// * `fuzz` - The fuzziness of the reflection.
+    /// * `roughness` - The perceptual roughness of the surface, where zero is a perfect mirror.
+    ///
+    /// # Returns
+    ///
+    /// A new `Metal` instance.
+    pub fn from_roughness(albedo: Vector3, roughness: f64) -> Metal {
+        Metal::from_texture(Box::new(SolidTexture::new(albedo)), roughness)
+    }
+
+    /// Creates a new metallic material whose albedo is read from a texture, such as a checkered
+    /// or image-mapped metal.
+    ///
+    /// # Arguments
+    ///
+    /// * `texture` - The texture of the albedo of the material.
+    /// * `roughness` - The perceptual roughness of the surface, where zero is a perfect mirror.
     ///
     /// # Returns
     ///
     /// A new `Metal` instance.
-    pub fn new(albedo: Vector3, mut fuzz: f64) -> Metal {
-        if fuzz > 1.0 {
-            fuzz = 1.0
+    pub fn from_texture(texture: Box<dyn Texture>, roughness: f64) -> Metal {
+        Metal {
+            texture,
+            roughness: roughness.max(0.0),
         }
-        Metal { albedo, fuzz }
+    }
+
+    /// Returns the `alpha` parameter of the GGX distribution.
+    fn alpha(&self) -> f64 {
+        self.roughness * self.roughness
     }
 }
 
@@ -153,6 +363,12 @@ impl Metal {
 pub struct Dielectric {
     /// The index of refraction of the material.
     refraction_index: f64,
+    /// The texture tinting the light scattered off and through the material, or `None` for clear
+    /// glass.
+    tint: Option<Box<dyn Texture>>,
+    /// The fraction of the light absorbed per unit of distance traveled inside the material, per
+    /// color channel, as the exponent of the Beer–Lambert law.
+    absorption: Vector3,
 }
 
 impl Dielectric {
@@ -166,7 +382,73 @@ impl Dielectric {
     ///
     /// A new `Dielectric` instance.
     pub fn new(refraction_index: f64) -> Dielectric {
-        Dielectric { refraction_index }
+        Dielectric {
+            refraction_index,
+            tint: None,
+            absorption: Vector3::default(),
+        }
+    }
+
+    /// Makes the material absorb light as it travels through it, following the Beer–Lambert law,
+    /// so thick parts of colored glass look darker and more saturated than thin ones. Light is
+    /// absorbed over the distance between the points where a ray enters and leaves the object,
+    /// so objects inside the glass interrupt it.
+    ///
+    /// # Arguments
+    ///
+    /// * `color` - The color light takes on after traveling one unit through the material at a
+    ///   density of 1.
+    /// * `density` - The factor scaling the absorption, where zero is clear glass.
+    ///
+    /// # Returns
+    ///
+    /// The updated `Dielectric` instance.
+    pub fn with_absorption(mut self, color: Vector3, density: f64) -> Dielectric {
+        let coefficient = |channel: f64| -channel.clamp(1e-6, 1.0).ln() * density.max(0.0);
+        self.absorption = Vector3::new(
+            coefficient(color.x),
+            coefficient(color.y),
+            coefficient(color.z),
+        );
+        self
+    }
+
+    /// Computes the fraction of the light reaching a hit point from inside the material after the
+    /// absorption along the way.
+    ///
+    /// # Arguments
+    ///
+    /// * `ray` - The incoming ray.
+    /// * `hit_record` - The record of the hit point.
+    ///
+    /// # Returns
+    ///
+    /// The transmittance per color channel, white for rays arriving from outside.
+    fn transmittance(&self, ray: &Ray, hit_record: &HitRecord) -> Vector3 {
+        if hit_record.front_face || self.absorption == Vector3::default() {
+            return Vector3::new(1.0, 1.0, 1.0);
+        }
+        let distance = (hit_record.poz - ray.origin).length();
+        Vector3::new(
+            (-self.absorption.x * distance).exp(),
+            (-self.absorption.y * distance).exp(),
+            (-self.absorption.z * distance).exp(),
+        )
+    }
+
+    /// Tints the light scattered off and through the material with a texture, like colored or
+    /// stained glass.
+    ///
+    /// # Arguments
+    ///
+    /// * `tint` - The texture of the tint.
+    ///
+    /// # Returns
+    ///
+    /// The updated `Dielectric` instance.
+    pub fn with_tint(mut self, tint: Box<dyn Texture>) -> Dielectric {
+        self.tint = Some(tint);
+        self
     }
 
     /// Computes the reflectance using Schlick's approximation.
@@ -196,9 +478,10 @@ impl Material for Dielectric {
     ///
     /// # Returns
     ///
-    /// An `Option` containing a tuple of the scattered ray and the attenuation vector, or `None` if no scattering occurs.
-    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<(Ray, Vector3)> {
-        let attenuation = Vector3::new(1.0, 1.0, 1.0);
+    /// An `Option` containing the `ScatterRecord` of the reflected or refracted ray.
+    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<ScatterRecord> {
+        let attenuation =
+            tint_value(&self.tint, ray, hit_record) * self.transmittance(ray, hit_record);
         let refraction_ratio = if hit_record.front_face {
             1.0 / self.refraction_index
         } else {
@@ -217,16 +500,213 @@ impl Material for Dielectric {
             refract(ray.direction, hit_record.normal, refraction_ratio)
         };
 
-        let scattered = Ray::new(hit_record.poz, direction);
-        Some((scattered, attenuation))
+        let scattered = Ray::with_time(hit_record.ray_origin(&direction), direction, ray.time);
+        Some(ScatterRecord::specular(attenuation, scattered))
+    }
+
+    fn surface_queries(&self) -> bool {
+        self.tint
+            .as_ref()
+            .is_some_and(|tint| tint.surface_queries())
+    }
+
+    fn collect_statistics(&self, statistics: &mut SceneStatistics) {
+        if statistics.add_material(self as *const Self as usize, std::mem::size_of_val(self)) {
+            if let Some(tint) = &self.tint {
+                tint.collect_statistics(statistics);
+            }
+        }
+    }
+}
+
+/// Returns the tint of a dielectric at a hit point.
+///
+/// # Arguments
+///
+/// * `tint` - The texture of the tint, or `None` for clear glass.
+/// * `ray` - The incoming ray.
+/// * `hit_record` - The record of the hit point.
+///
+/// # Returns
+///
+/// The color of the tint, or white for clear glass.
+fn tint_value(tint: &Option<Box<dyn Texture>>, ray: &Ray, hit_record: &HitRecord) -> Vector3 {
+    match tint {
+        Some(tint) => tint.value(&ShadingContext::new(ray, hit_record)),
+        None => Vector3::new(1.0, 1.0, 1.0),
+    }
+}
+
+/// Represents a rough dielectric, such as frosted or etched glass, modeled as GGX microfacets
+/// each reflecting or refracting light like smooth glass, with the Fresnel reflectance of the
+/// microfacet and the Smith masking-shadowing between them.
+#[derive(Debug, Default)]
+pub struct RoughDielectric {
+    /// The index of refraction of the material.
+    refraction_index: f64,
+    /// The perceptual roughness of the surface, where zero is smooth glass.
+    roughness: f64,
+    /// The texture whose luminance gives the perceptual roughness at every point, replacing
+    /// `roughness`, or `None` for a uniform roughness.
+    roughness_texture: Option<Box<dyn Texture>>,
+    /// The texture tinting the light scattered off and through the material, or `None` for clear
+    /// glass.
+    tint: Option<Box<dyn Texture>>,
+}
+
+impl RoughDielectric {
+    /// Perceptual roughness below which the surface is treated as smooth glass.
+    const SMOOTH_ROUGHNESS: f64 = 1e-3;
+
+    /// Creates a new rough dielectric material.
+    /// The GGX distribution uses the square of the perceptual roughness as its `alpha`.
+    ///
+    /// # Arguments
+    ///
+    /// * `refraction_index` - The index of refraction of the material.
+    /// * `roughness` - The perceptual roughness of the surface, where zero is smooth glass.
+    ///
+    /// # Returns
+    ///
+    /// A new `RoughDielectric` instance.
+    pub fn new(refraction_index: f64, roughness: f64) -> RoughDielectric {
+        RoughDielectric {
+            refraction_index,
+            roughness: roughness.max(0.0),
+            roughness_texture: None,
+            tint: None,
+        }
+    }
+
+    /// Reads the roughness of the surface from a texture, such as glass frosted in a pattern.
+    ///
+    /// # Arguments
+    ///
+    /// * `texture` - The texture whose luminance gives the perceptual roughness at every point.
+    ///   Image textures should be loaded without gamma decoding, for example with
+    ///   `ImageTexture::new_data`.
+    ///
+    /// # Returns
+    ///
+    /// The updated `RoughDielectric` instance.
+    pub fn with_roughness_texture(mut self, texture: Box<dyn Texture>) -> RoughDielectric {
+        self.roughness_texture = Some(texture);
+        self
+    }
+
+    /// Tints the light scattered off and through the material with a texture, like colored or
+    /// stained glass.
+    ///
+    /// # Arguments
+    ///
+    /// * `tint` - The texture of the tint.
+    ///
+    /// # Returns
+    ///
+    /// The updated `RoughDielectric` instance.
+    pub fn with_tint(mut self, tint: Box<dyn Texture>) -> RoughDielectric {
+        self.tint = Some(tint);
+        self
+    }
+
+    /// Returns the perceptual roughness of the surface at a hit point.
+    ///
+    /// # Arguments
+    ///
+    /// * `ray` - The incoming ray.
+    /// * `hit_record` - The record of the hit point.
+    fn roughness(&self, ray: &Ray, hit_record: &HitRecord) -> f64 {
+        match &self.roughness_texture {
+            Some(texture) => texture
+                .value(&ShadingContext::new(ray, hit_record))
+                .luminance()
+                .max(0.0),
+            None => self.roughness,
+        }
+    }
+
+    /// Returns the index of refraction behind the surface divided by the index in front of it.
+    ///
+    /// # Arguments
+    ///
+    /// * `hit_record` - The record of the hit point, telling which side the ray arrives from.
+    fn eta(&self, hit_record: &HitRecord) -> f64 {
+        if hit_record.front_face {
+            self.refraction_index
+        } else {
+            1.0 / self.refraction_index
+        }
+    }
+}
+
+impl Material for RoughDielectric {
+    /// Scatters a ray upon hitting the rough dielectric material. The scattered direction is
+    /// importance sampled from the visible GGX microfacet normals, and either reflected off the
+    /// microfacet or refracted through it.
+    ///
+    /// # Arguments
+    ///
+    /// * `ray` - The incoming ray.
+    /// * `hit_record` - The record of the hit point.
+    ///
+    /// # Returns
+    ///
+    /// An `Option` containing the `ScatterRecord`, specular for smooth surfaces.
+    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<ScatterRecord> {
+        let roughness = self.roughness(ray, hit_record);
+        let attenuation = tint_value(&self.tint, ray, hit_record);
+        if roughness < Self::SMOOTH_ROUGHNESS {
+            let mut record = Dielectric::new(self.refraction_index).scatter(ray, hit_record)?;
+            record.attenuation = attenuation;
+            return Some(record);
+        }
+
+        Some(ScatterRecord::from_pdf(
+            attenuation,
+            Box::new(GgxDielectricPdf::new(
+                &hit_record.normal,
+                &-ray.direction,
+                roughness * roughness,
+                self.eta(hit_record),
+            )),
+        ))
+    }
+
+    /// Returns the GGX microfacet BSDF times the absolute cosine of the scattered direction, on
+    /// either side of the surface.
+    fn scattering_pdf(&self, ray: &Ray, hit_record: &HitRecord, scattered: &Ray) -> f64 {
+        let onb = Onb::new(&hit_record.normal);
+        let wo = onb.world_to_local(&-ray.direction);
+        let wi = onb.world_to_local(&scattered.direction);
+        let roughness = self.roughness(ray, hit_record);
+        ggx_dielectric_bsdf(&wo, &wi, roughness * roughness, self.eta(hit_record))
+    }
+
+    fn surface_queries(&self) -> bool {
+        [&self.roughness_texture, &self.tint]
+            .into_iter()
+            .flatten()
+            .any(|texture| texture.surface_queries())
+    }
+
+    fn collect_statistics(&self, statistics: &mut SceneStatistics) {
+        if statistics.add_material(self as *const Self as usize, std::mem::size_of_val(self)) {
+            for texture in [&self.roughness_texture, &self.tint].into_iter().flatten() {
+                texture.collect_statistics(statistics);
+            }
+        }
     }
 }
 
 /// Represents a diffuse light material.
 #[derive(Debug)]
 pub struct DiffuseLight {
-    /// The texture of the light.
+    /// The texture of the light, emitted from the front face.
     texture: Box<dyn Texture>,
+    /// The texture emitted from the back face, or `None` if both faces emit `texture`.
+    back: Option<Box<dyn Texture>>,
+    /// The factor scaling the emitted light.
+    intensity: f64,
 }
 
 impl DiffuseLight {
@@ -242,6 +722,8 @@ impl DiffuseLight {
     pub fn new(emit: Vector3) -> DiffuseLight {
         DiffuseLight {
             texture: Box::new(SolidTexture::new(emit)),
+            back: None,
+            intensity: 1.0,
         }
     }
 
@@ -255,7 +737,61 @@ impl DiffuseLight {
     ///
     /// A new `DiffuseLight` instance.
     pub fn from_texture(texture: Box<dyn Texture>) -> DiffuseLight {
-        DiffuseLight { texture }
+        DiffuseLight {
+            texture,
+            back: None,
+            intensity: 1.0,
+        }
+    }
+
+    /// Creates a new diffuse light material emitting different textures from its two faces,
+    /// such as a screen showing an image at the front and staying dark at the back.
+    ///
+    /// # Arguments
+    ///
+    /// * `front` - The texture emitted from the front face, on the side of the outward normal.
+    /// * `back` - The texture emitted from the back face.
+    ///
+    /// # Returns
+    ///
+    /// A new `DiffuseLight` instance.
+    pub fn two_sided(front: Box<dyn Texture>, back: Box<dyn Texture>) -> DiffuseLight {
+        DiffuseLight {
+            texture: front,
+            back: Some(back),
+            intensity: 1.0,
+        }
+    }
+
+    /// Scales the light emitted from both faces, so textures holding colors between 0 and 1,
+    /// such as images, can light a scene as brightly as needed.
+    ///
+    /// # Arguments
+    ///
+    /// * `intensity` - The factor scaling the emitted light.
+    ///
+    /// # Returns
+    ///
+    /// The updated `DiffuseLight` instance.
+    pub fn with_intensity(mut self, intensity: f64) -> DiffuseLight {
+        self.intensity = intensity;
+        self
+    }
+
+    /// Returns the texture emitted from the given face.
+    ///
+    /// # Arguments
+    ///
+    /// * `front_face` - Whether the front face is seen.
+    ///
+    /// # Returns
+    ///
+    /// The texture emitted from the face.
+    fn face_texture(&self, front_face: bool) -> &dyn Texture {
+        match &self.back {
+            Some(back) if !front_face => back.as_ref(),
+            _ => self.texture.as_ref(),
+        }
     }
 }
 
@@ -270,23 +806,53 @@ impl Material for DiffuseLight {
     /// # Returns
     ///
     /// Always returns `None`.
-    fn scatter(&self, _ray: &Ray, _hit_record: &HitRecord) -> Option<(Ray, Vector3)> {
+    fn scatter(&self, _ray: &Ray, _hit_record: &HitRecord) -> Option<ScatterRecord> {
         None
     }
 
-    /// Returns the emitted light from the diffuse light material at a given point.
+    /// Returns the emitted light from the diffuse light material at a given point, taken from the
+    /// texture of the face that is seen and scaled by the intensity.
     ///
     /// # Arguments
     ///
-    /// * `u` - The u-coordinate for texture mapping.
-    /// * `v` - The v-coordinate for texture mapping.
-    /// * `p` - The position at which the light is emitted.
+    /// * `context` - The shading context of the point at which the light is emitted.
     ///
     /// # Returns
     ///
     /// The emitted light as a `Vector3`.
-    fn emitted(&self, u: f64, v: f64, p: &Vector3) -> Vector3 {
-        self.texture.value(u, v, p)
+    fn emitted(&self, context: &ShadingContext) -> Vector3 {
+        self.face_texture(context.front_face).value(context) * self.intensity
+    }
+
+    fn is_emissive(&self) -> bool {
+        true
+    }
+
+    /// Returns the power emitted per unit area by a surface emitting the texture uniformly in all
+    /// directions. The average radiance of the texture is estimated on a grid of texture coordinates,
+    /// averaged over both faces of two-sided lights.
+    fn power(&self) -> Vector3 {
+        let steps = 16;
+        let mut radiance = Vector3::default();
+        for i in 0..steps {
+            for j in 0..steps {
+                let u = (i as f64 + 0.5) / steps as f64;
+                let v = (j as f64 + 0.5) / steps as f64;
+                let context = ShadingContext::from_uv(u, v);
+                radiance += self.face_texture(true).value(&context)
+                    + self.face_texture(false).value(&context);
+            }
+        }
+        radiance * (self.intensity * PI / (2 * steps * steps) as f64)
+    }
+
+    fn collect_statistics(&self, statistics: &mut SceneStatistics) {
+        if statistics.add_material(self as *const Self as usize, std::mem::size_of_val(self)) {
+            self.texture.collect_statistics(statistics);
+            if let Some(back) = &self.back {
+                back.collect_statistics(statistics);
+            }
+        }
     }
 }
 
@@ -332,18 +898,949 @@ impl Material for Isotropic {
     ///
     /// # Arguments
     ///
-    /// * `_ray` - The incoming ray.
+    /// * `ray` - The incoming ray.
     /// * `hit_record` - The record of the hit point.
     ///
     /// # Returns
     ///
-    /// An `Option` containing a tuple of the scattered ray and the attenuation vector, or `None` if no scattering occurs.
-    fn scatter(&self, _ray: &Ray, hit_record: &HitRecord) -> Option<(Ray, Vector3)> {
-        let scattered = Ray::new(hit_record.poz, Vector3::random_in_unit_sphere());
+    /// An `Option` containing the `ScatterRecord`, with directions drawn uniformly over the sphere.
+    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<ScatterRecord> {
+        let attenuation = self.texture.value(&ShadingContext::new(ray, hit_record));
+        Some(ScatterRecord::from_pdf(attenuation, Box::new(SpherePdf)))
+    }
+
+    fn scattering_pdf(&self, _ray: &Ray, _hit_record: &HitRecord, _scattered: &Ray) -> f64 {
+        1.0 / (4.0 * PI)
+    }
+
+    fn collect_statistics(&self, statistics: &mut SceneStatistics) {
+        if statistics.add_material(self as *const Self as usize, std::mem::size_of_val(self)) {
+            self.texture.collect_statistics(statistics);
+        }
+    }
+}
+
+/// Represents the particles of an anisotropic medium, such as fog scattering light mostly forward
+/// or dust scattering it mostly back, following the Henyey-Greenstein phase function.
+#[derive(Debug)]
+pub struct HenyeyGreenstein {
+    /// The texture of the material.
+    texture: Box<dyn Texture>,
+    /// The asymmetry of the scattering, the mean cosine of the scattering angle.
+    g: f64,
+}
+
+impl HenyeyGreenstein {
+    /// Creates a new anisotropic medium material with a solid color.
+    ///
+    /// # Arguments
+    ///
+    /// * `albedo` - The color of the material.
+    /// * `g` - The asymmetry of the scattering, from -1 for light scattered straight back to 1
+    ///   for light scattered straight forward. Zero scatters light in all directions alike.
+    ///
+    /// # Returns
+    ///
+    /// A new `HenyeyGreenstein` instance.
+    pub fn new(albedo: Vector3, g: f64) -> HenyeyGreenstein {
+        HenyeyGreenstein::from_texture(Box::new(SolidTexture::new(albedo)), g)
+    }
+
+    /// Creates a new anisotropic medium material with a texture.
+    ///
+    /// # Arguments
+    ///
+    /// * `texture` - The texture of the material.
+    /// * `g` - The asymmetry of the scattering, clamped to -0.99 to 0.99 as the phase function
+    ///   degenerates into a single direction at the extremes.
+    ///
+    /// # Returns
+    ///
+    /// A new `HenyeyGreenstein` instance.
+    pub fn from_texture(texture: Box<dyn Texture>, g: f64) -> HenyeyGreenstein {
+        HenyeyGreenstein {
+            texture,
+            g: g.clamp(-0.99, 0.99),
+        }
+    }
+}
+
+impl Material for HenyeyGreenstein {
+    /// Scatters a ray off a particle of the medium.
+    ///
+    /// # Arguments
+    ///
+    /// * `ray` - The incoming ray.
+    /// * `hit_record` - The record of the hit point.
+    ///
+    /// # Returns
+    ///
+    /// An `Option` containing the `ScatterRecord`, with directions drawn from the phase function
+    /// around the direction of the incoming ray.
+    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<ScatterRecord> {
+        let attenuation = self.texture.value(&ShadingContext::new(ray, hit_record));
+        let pdf = HenyeyGreensteinPdf::new(&ray.direction, self.g);
+        Some(ScatterRecord::from_pdf(attenuation, Box::new(pdf)))
+    }
+
+    fn scattering_pdf(&self, ray: &Ray, _hit_record: &HitRecord, scattered: &Ray) -> f64 {
+        let cos_theta = ray
+            .direction
+            .normalize()
+            .dot(&scattered.direction.normalize());
+        henyey_greenstein(cos_theta, self.g)
+    }
+
+    fn collect_statistics(&self, statistics: &mut SceneStatistics) {
+        if statistics.add_material(self as *const Self as usize, std::mem::size_of_val(self)) {
+            self.texture.collect_statistics(statistics);
+        }
+    }
+}
+
+/// Represents a translucent material scattering light beneath its surface, such as wax, skin or
+/// marble. Light refracts into the object and takes a random walk through it, scattering off the
+/// particles of its inside until it leaves through the surface again, diffusely and often far
+/// from where it entered. Every step of the walk inside counts as a bounce, so objects much
+/// larger than the scattering radius need a high maximum depth.
+///
+/// The object must be closed, so rays inside it hit its surface from the back.
+#[derive(Debug)]
+pub struct Subsurface {
+    /// The texture giving the color of the object seen from outside.
+    texture: Box<dyn Texture>,
+    /// The average distance light travels inside the object between two scatterings, per color
+    /// channel.
+    radius: Vector3,
+    /// The index of refraction of the surface.
+    refraction_index: f64,
+}
+
+impl Subsurface {
+    /// Creates a new subsurface material with a solid color.
+    ///
+    /// # Arguments
+    ///
+    /// * `color` - The color of the object seen from outside.
+    /// * `radius` - The average distance light travels inside the object between two
+    ///   scatterings, per color channel. A larger radius for red than for blue gives the warm
+    ///   glow of skin.
+    ///
+    /// # Returns
+    ///
+    /// A new `Subsurface` instance, with the index of refraction of wax.
+    pub fn new(color: Vector3, radius: Vector3) -> Subsurface {
+        Subsurface::from_texture(Box::new(SolidTexture::new(color)), radius)
+    }
+
+    /// Creates a new subsurface material with a texture, such as the veins of marble.
+    ///
+    /// # Arguments
+    ///
+    /// * `texture` - The texture giving the color of the object seen from outside.
+    /// * `radius` - The average distance light travels inside the object between two
+    ///   scatterings, per color channel.
+    ///
+    /// # Returns
+    ///
+    /// A new `Subsurface` instance, with the index of refraction of wax.
+    pub fn from_texture(texture: Box<dyn Texture>, radius: Vector3) -> Subsurface {
+        let positive = |radius: f64| radius.max(MIN_SUBSURFACE_RADIUS);
+        Subsurface {
+            texture,
+            radius: Vector3::new(positive(radius.x), positive(radius.y), positive(radius.z)),
+            refraction_index: 1.4,
+        }
+    }
+
+    /// Sets the index of refraction of the surface, which gives the strength of its reflections.
+    ///
+    /// # Arguments
+    ///
+    /// * `refraction_index` - The index of refraction.
+    ///
+    /// # Returns
+    ///
+    /// The `Subsurface` instance with the new index of refraction.
+    pub fn with_refraction_index(mut self, refraction_index: f64) -> Subsurface {
+        self.refraction_index = refraction_index;
+        self
+    }
+
+    /// Finds the chance of light surviving a single scattering inside the object that makes the
+    /// whole walk come out with the given color, using the fit of Christensen and Burley.
+    ///
+    /// # Arguments
+    ///
+    /// * `color` - The color of one channel of the object seen from outside.
+    ///
+    /// # Returns
+    ///
+    /// The single-scattering albedo, between 0 and 1.
+    fn single_scattering_albedo(color: f64) -> f64 {
+        let color = color.clamp(0.0, 0.999);
+        let root = 4.09712 + 4.20863 * color
+            - (9.59217 + 41.6808 * color + 17.7126 * color * color).sqrt();
+        (1.0 - root * root).clamp(0.0, 1.0)
+    }
+}
+
+/// The smallest scattering radius of a subsurface material, which keeps its density finite.
+const MIN_SUBSURFACE_RADIUS: f64 = 1e-6;
+
+impl Material for Subsurface {
+    /// Scatters a ray upon hitting the subsurface material. Rays from outside are reflected or
+    /// refracted into the object, like glass. Rays inside travel a random distance, drawn along
+    /// one random color channel: if they scatter before reaching the surface they go on in a
+    /// random direction, and otherwise they leave the object diffusely.
+    ///
+    /// # Arguments
+    ///
+    /// * `ray` - The incoming ray.
+    /// * `hit_record` - The record of the hit point.
+    ///
+    /// # Returns
+    ///
+    /// An `Option` containing the `ScatterRecord` of the next step of the walk.
+    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<ScatterRecord> {
+        if hit_record.front_face {
+            let cos_theta = (-ray.direction).dot(&hit_record.normal).min(1.0);
+            let direction =
+                if Dielectric::reflectance(cos_theta, self.refraction_index) > fastrand::f64() {
+                    reflect(ray.direction, hit_record.normal)
+                } else {
+                    refract(
+                        ray.direction,
+                        hit_record.normal,
+                        1.0 / self.refraction_index,
+                    )
+                };
+            let scattered = Ray::with_time(hit_record.ray_origin(&direction), direction, ray.time);
+            return Some(ScatterRecord::specular(
+                Vector3::new(1.0, 1.0, 1.0),
+                scattered,
+            ));
+        }
+
+        let density = Vector3::new(
+            1.0 / self.radius.x,
+            1.0 / self.radius.y,
+            1.0 / self.radius.z,
+        );
+        let chosen = [density.x, density.y, density.z][fastrand::usize(..3)];
+        let distance = -(1.0 - fastrand::f64()).ln() / chosen;
+        let boundary = (hit_record.poz - ray.origin).length();
+        let transmittance = |distance: f64| {
+            Vector3::new(
+                (-density.x * distance).exp(),
+                (-density.y * distance).exp(),
+                (-density.z * distance).exp(),
+            )
+        };
+        let average = |v: Vector3| (v.x + v.y + v.z) / 3.0;
+
+        if distance < boundary {
+            // The distance was drawn from the average of the densities of the three channels,
+            // so each channel is weighted by its own density over that average.
+            let color = self.texture.value(&ShadingContext::new(ray, hit_record));
+            let albedo = Vector3::new(
+                Self::single_scattering_albedo(color.x),
+                Self::single_scattering_albedo(color.y),
+                Self::single_scattering_albedo(color.z),
+            );
+            let scattered_density = density * transmittance(distance);
+            let attenuation = albedo * scattered_density / average(scattered_density);
+            let scattered = Ray::with_time(
+                ray.point_at(distance),
+                Vector3::random_unit_vector(),
+                ray.time,
+            );
+            Some(ScatterRecord::specular(attenuation, scattered))
+        } else {
+            let surviving = transmittance(boundary);
+            let attenuation = surviving / average(surviving);
+            Some(ScatterRecord::from_pdf(
+                attenuation,
+                Box::new(CosinePdf::new(&-hit_record.normal)),
+            ))
+        }
+    }
+
+    fn scattering_pdf(&self, _ray: &Ray, hit_record: &HitRecord, scattered: &Ray) -> f64 {
+        if hit_record.front_face {
+            return 0.0;
+        }
+        ((-hit_record.normal).dot(&scattered.direction) / PI).max(0.0)
+    }
+
+    fn collect_statistics(&self, statistics: &mut SceneStatistics) {
+        if statistics.add_material(self as *const Self as usize, std::mem::size_of_val(self)) {
+            self.texture.collect_statistics(statistics);
+        }
+    }
+}
+
+/// Represents a principled "uber" material in the style of the Disney and Blender Principled
+/// BSDFs, blending a diffuse base, a glossy specular coat, a metal and a glass from a handful of
+/// parameters, so materials set up in other renderers can be approximated with a single material.
+///
+/// Every scatter picks one of three lobes, with the blend weights as probabilities: metal, with
+/// the weight `metallic`, glass, with the weight `(1 - metallic) * transmission`, and an opaque
+/// dielectric taking the rest, which reflects off its coat with the Fresnel reflectance of the
+/// viewing angle and diffusely otherwise. Only the diffuse lobe samples the lights, as the
+/// glossy lobes are sampled on their own into a single direction.
+#[derive(Debug)]
+pub struct Principled {
+    /// The texture of the base color, the diffuse color of dielectrics, the reflectance of metals
+    /// and the tint of light refracted through glass.
+    texture: Box<dyn Texture>,
+    /// How metallic the surface is, between 0 and 1.
+    metallic: f64,
+    /// The perceptual roughness of the reflections and refractions, where zero is a mirror.
+    roughness: f64,
+    /// The strength of the specular coat of dielectrics, between 0 and 1, where 0.5 gives the
+    /// reflectance of 4% at normal incidence of most plastics.
+    specular: f64,
+    /// How much of the dielectric part of the surface is glass, between 0 and 1.
+    transmission: f64,
+    /// The index of refraction of the glass.
+    refraction_index: f64,
+    /// The color of the light emitted by the surface.
+    emission: Vector3,
+    /// The factor scaling the emitted light.
+    emission_strength: f64,
+}
+
+impl Principled {
+    /// Creates a new principled material with a solid base color.
+    ///
+    /// # Arguments
+    ///
+    /// * `base_color` - The base color of the material.
+    ///
+    /// # Returns
+    ///
+    /// A new `Principled` instance with the defaults of Blender: a non-metallic, opaque surface
+    /// with a roughness of 0.5, a specular of 0.5 and no emission.
+    pub fn new(base_color: Vector3) -> Principled {
+        Principled::from_texture(Box::new(SolidTexture::new(base_color)))
+    }
+
+    /// Creates a new principled material with a base color texture.
+    ///
+    /// # Arguments
+    ///
+    /// * `texture` - The texture of the base color.
+    ///
+    /// # Returns
+    ///
+    /// A new `Principled` instance with the same defaults as `new`.
+    pub fn from_texture(texture: Box<dyn Texture>) -> Principled {
+        Principled {
+            texture,
+            metallic: 0.0,
+            roughness: 0.5,
+            specular: 0.5,
+            transmission: 0.0,
+            refraction_index: 1.45,
+            emission: Vector3::default(),
+            emission_strength: 0.0,
+        }
+    }
+
+    /// Sets how metallic the surface is.
+    ///
+    /// # Arguments
+    ///
+    /// * `metallic` - The metalness, clamped between 0 and 1.
+    ///
+    /// # Returns
+    ///
+    /// The updated `Principled` instance.
+    pub fn with_metallic(mut self, metallic: f64) -> Principled {
+        self.metallic = metallic.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Sets the roughness of the reflections and refractions.
+    ///
+    /// # Arguments
+    ///
+    /// * `roughness` - The perceptual roughness, where zero is a mirror.
+    ///
+    /// # Returns
+    ///
+    /// The updated `Principled` instance.
+    pub fn with_roughness(mut self, roughness: f64) -> Principled {
+        self.roughness = roughness.max(0.0);
+        self
+    }
+
+    /// Sets the strength of the specular coat of dielectrics.
+    ///
+    /// # Arguments
+    ///
+    /// * `specular` - The specular strength, clamped between 0 and 1. The reflectance at normal
+    ///   incidence is 8% of it.
+    ///
+    /// # Returns
+    ///
+    /// The updated `Principled` instance.
+    pub fn with_specular(mut self, specular: f64) -> Principled {
+        self.specular = specular.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Sets how much of the dielectric part of the surface is glass.
+    ///
+    /// # Arguments
+    ///
+    /// * `transmission` - The transmission, clamped between 0 and 1.
+    ///
+    /// # Returns
+    ///
+    /// The updated `Principled` instance.
+    pub fn with_transmission(mut self, transmission: f64) -> Principled {
+        self.transmission = transmission.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Sets the index of refraction of the glass.
+    ///
+    /// # Arguments
+    ///
+    /// * `refraction_index` - The index of refraction.
+    ///
+    /// # Returns
+    ///
+    /// The updated `Principled` instance.
+    pub fn with_refraction_index(mut self, refraction_index: f64) -> Principled {
+        self.refraction_index = refraction_index;
+        self
+    }
+
+    /// Makes the surface emit light from both faces, on top of the light it scatters.
+    ///
+    /// # Arguments
+    ///
+    /// * `color` - The color of the emitted light.
+    /// * `strength` - The factor scaling the emitted light.
+    ///
+    /// # Returns
+    ///
+    /// The updated `Principled` instance.
+    pub fn with_emission(mut self, color: Vector3, strength: f64) -> Principled {
+        self.emission = color;
+        self.emission_strength = strength.max(0.0);
+        self
+    }
+
+    /// Returns the reflectance of the specular coat of dielectrics, with the approximation of
+    /// Schlick.
+    ///
+    /// # Arguments
+    ///
+    /// * `cosine` - The cosine of the angle between the viewing direction and the normal.
+    fn coat_reflectance(&self, cosine: f64) -> f64 {
+        let f0 = 0.08 * self.specular;
+        f0 + (1.0 - f0) * (1.0 - cosine.clamp(0.0, 1.0)).powi(5)
+    }
+
+    /// Draws the scattered ray of a glossy lobe from the density of its scatter record, turning
+    /// the record into a single direction weighted by the BSDF of the lobe over the density.
+    ///
+    /// # Arguments
+    ///
+    /// * `lobe` - The material of the lobe.
+    /// * `ray` - The incoming ray.
+    /// * `hit_record` - The record of the hit point.
+    ///
+    /// # Returns
+    ///
+    /// An `Option` containing the specular `ScatterRecord`, or `None` if the ray is absorbed.
+    fn sample_lobe(
+        lobe: &dyn Material,
+        ray: &Ray,
+        hit_record: &HitRecord,
+    ) -> Option<ScatterRecord> {
+        let record = lobe.scatter(ray, hit_record)?;
+        match record.scatter_type {
+            ScatterType::Specular(_) => Some(record),
+            ScatterType::Pdf(pdf) => {
+                let direction = pdf.generate();
+                let scattered =
+                    Ray::with_time(hit_record.ray_origin(&direction), direction, ray.time);
+                let pdf_value = pdf.value(&scattered.direction);
+                if pdf_value <= 0.0 {
+                    return None;
+                }
+                let weight = lobe.scattering_pdf(ray, hit_record, &scattered) / pdf_value;
+                Some(ScatterRecord::specular(
+                    record.attenuation * weight,
+                    scattered,
+                ))
+            }
+        }
+    }
+
+    /// Scatters a ray off the glass lobe, tinting the refracted light with the base color.
+    ///
+    /// # Arguments
+    ///
+    /// * `ray` - The incoming ray.
+    /// * `hit_record` - The record of the hit point.
+    /// * `base_color` - The base color at the hit point.
+    ///
+    /// # Returns
+    ///
+    /// An `Option` containing the specular `ScatterRecord`, or `None` if the ray is absorbed.
+    fn scatter_glass(
+        &self,
+        ray: &Ray,
+        hit_record: &HitRecord,
+        base_color: Vector3,
+    ) -> Option<ScatterRecord> {
+        let glass = RoughDielectric::new(self.refraction_index, self.roughness);
+        let mut record = Self::sample_lobe(&glass, ray, hit_record)?;
+        if let ScatterType::Specular(scattered) = &record.scatter_type {
+            if scattered.direction.dot(&hit_record.normal) < 0.0 {
+                record.attenuation = record.attenuation * base_color;
+            }
+        }
+        Some(record)
+    }
+}
+
+impl Material for Principled {
+    /// Scatters a ray upon hitting the principled material, off one of its lobes picked at random
+    /// with the blend weights as probabilities. Rays hitting the back of a transmissive surface
+    /// are inside its glass, and always scatter off the glass lobe.
+    ///
+    /// # Arguments
+    ///
+    /// * `ray` - The incoming ray.
+    /// * `hit_record` - The record of the hit point.
+    ///
+    /// # Returns
+    ///
+    /// An `Option` containing the `ScatterRecord`, drawn from a cosine density for diffuse
+    /// scattering and specular otherwise, or `None` if the ray is absorbed.
+    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<ScatterRecord> {
+        let base_color = self.texture.value(&ShadingContext::new(ray, hit_record));
+        if !hit_record.front_face && self.transmission > 0.0 {
+            return self.scatter_glass(ray, hit_record, base_color);
+        }
+
+        let lobe = fastrand::f64();
+        if lobe < self.metallic {
+            let metal = Metal::from_roughness(base_color, self.roughness);
+            return Self::sample_lobe(&metal, ray, hit_record);
+        }
+        if lobe < self.metallic + (1.0 - self.metallic) * self.transmission {
+            return self.scatter_glass(ray, hit_record, base_color);
+        }
+
+        let cos_theta = (-ray.direction).dot(&hit_record.normal);
+        if fastrand::f64() < self.coat_reflectance(cos_theta) {
+            let coat = Metal::from_roughness(Vector3::new(1.0, 1.0, 1.0), self.roughness);
+            return Self::sample_lobe(&coat, ray, hit_record);
+        }
+        Some(ScatterRecord::from_pdf(
+            base_color,
+            Box::new(CosinePdf::new(&hit_record.normal)),
+        ))
+    }
+
+    /// Returns the density of the diffuse lobe, the only one drawn from a density.
+    fn scattering_pdf(&self, _ray: &Ray, hit_record: &HitRecord, scattered: &Ray) -> f64 {
+        (hit_record.normal.dot(&scattered.direction) / PI).max(0.0)
+    }
+
+    /// Returns the emission color scaled by its strength, from both faces.
+    fn emitted(&self, _context: &ShadingContext) -> Vector3 {
+        self.emission * self.emission_strength
+    }
+
+    fn is_emissive(&self) -> bool {
+        self.emission_strength > 0.0 && self.emission.max() > 0.0
+    }
+
+    /// Returns the power emitted per unit area by a surface emitting uniformly from both faces.
+    fn power(&self) -> Vector3 {
+        self.emission * (self.emission_strength * PI)
+    }
+
+    fn surface_queries(&self) -> bool {
+        self.texture.surface_queries()
+    }
+
+    fn collect_statistics(&self, statistics: &mut SceneStatistics) {
+        if statistics.add_material(self as *const Self as usize, std::mem::size_of_val(self)) {
+            self.texture.collect_statistics(statistics);
+        }
+    }
+}
+
+/// Wraps a material to perturb its shading normal with a normal map, so flat surfaces show
+/// detail such as bumps and grooves without extra geometry.
+///
+/// The map stores normals in tangent space, with red along the direction in which the
+/// u-coordinate increases, green along the v-coordinate and blue away from the surface, each
+/// mapped from [-1, 1] to [0, 1]. It should be loaded without gamma decoding, for example with
+/// `ImageTexture::new_data`.
+#[derive(Debug)]
+pub struct NormalMap {
+    /// The material shaded with the perturbed normal.
+    material: Arc<dyn Material>,
+    /// The texture holding the tangent-space normals.
+    map: Box<dyn Texture>,
+    /// The factor scaling how far the normals tilt away from the surface normal.
+    strength: f64,
+}
+
+impl NormalMap {
+    /// Creates a new `NormalMap` applying the map at full strength.
+    ///
+    /// # Arguments
+    ///
+    /// * `material` - The material shaded with the perturbed normal.
+    /// * `map` - The texture holding the tangent-space normals.
+    ///
+    /// # Returns
+    ///
+    /// A new `NormalMap` instance.
+    pub fn new(material: Arc<dyn Material>, map: Box<dyn Texture>) -> NormalMap {
+        NormalMap {
+            material,
+            map,
+            strength: 1.0,
+        }
+    }
+
+    /// Sets how strongly the map tilts the normals, where 0 leaves the surface flat and values
+    /// above 1 exaggerate the detail.
+    ///
+    /// # Arguments
+    ///
+    /// * `strength` - The factor scaling the tilt of the normals.
+    ///
+    /// # Returns
+    ///
+    /// The updated `NormalMap` instance.
+    pub fn with_strength(mut self, strength: f64) -> NormalMap {
+        self.strength = strength;
+        self
+    }
+
+    /// Copies the hit record with its normal replaced by the one read from the map.
+    ///
+    /// # Arguments
+    ///
+    /// * `ray` - The incoming ray.
+    /// * `hit_record` - The record of the hit point.
+    ///
+    /// # Returns
+    ///
+    /// The hit record with the perturbed normal.
+    fn perturb<'a>(&self, ray: &Ray, hit_record: &HitRecord<'a>) -> HitRecord<'a> {
+        let value = self.map.value(&ShadingContext::new(ray, hit_record));
+        let local = Vector3::new(
+            (2.0 * value.x - 1.0) * self.strength,
+            (2.0 * value.y - 1.0) * self.strength,
+            (2.0 * value.z - 1.0).max(0.0),
+        );
+        let normal = hit_record.tangent_frame().local_to_world(&local);
+        // Normals tilted past the horizon of the incoming ray would shade the surface from behind.
+        let normal = if normal.is_near_zero() || normal.dot(&ray.direction) >= 0.0 {
+            hit_record.normal
+        } else {
+            normal.normalize()
+        };
+
+        HitRecord {
+            normal,
+            ..*hit_record
+        }
+    }
+}
+
+impl Material for NormalMap {
+    /// Scatters a ray with the wrapped material, shading the hit point with the perturbed normal.
+    ///
+    /// # Arguments
+    ///
+    /// * `ray` - The incoming ray.
+    /// * `hit_record` - The record of the hit point.
+    ///
+    /// # Returns
+    ///
+    /// An `Option` containing the `ScatterRecord` of the wrapped material.
+    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<ScatterRecord> {
+        self.material.scatter(ray, &self.perturb(ray, hit_record))
+    }
+
+    fn scattering_pdf(&self, ray: &Ray, hit_record: &HitRecord, scattered: &Ray) -> f64 {
+        self.material
+            .scattering_pdf(ray, &self.perturb(ray, hit_record), scattered)
+    }
+
+    fn emitted(&self, context: &ShadingContext) -> Vector3 {
+        self.material.emitted(context)
+    }
+
+    fn is_mirror(&self) -> bool {
+        self.material.is_mirror()
+    }
+
+    fn is_emissive(&self) -> bool {
+        self.material.is_emissive()
+    }
+
+    fn surface_queries(&self) -> bool {
+        self.material.surface_queries() || self.map.surface_queries()
+    }
+
+    fn power(&self) -> Vector3 {
+        self.material.power()
+    }
+
+    fn collect_statistics(&self, statistics: &mut SceneStatistics) {
+        if statistics.add_material(self as *const Self as usize, std::mem::size_of_val(self)) {
+            self.map.collect_statistics(statistics);
+            self.material.collect_statistics(statistics);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_two_sided_light() {
+        let light = DiffuseLight::two_sided(
+            Box::new(SolidTexture::new(Vector3::new(4.0, 2.0, 1.0))),
+            Box::new(SolidTexture::new(Vector3::new(0.0, 0.0, 0.0))),
+        );
+        let mut context = ShadingContext::from_uv(0.5, 0.5);
+
+        context.front_face = true;
+        assert_eq!(light.emitted(&context), Vector3::new(4.0, 2.0, 1.0));
+        context.front_face = false;
+        assert_eq!(light.emitted(&context), Vector3::new(0.0, 0.0, 0.0));
+
+        let one_sided = DiffuseLight::new(Vector3::new(4.0, 2.0, 1.0));
+        assert_eq!(one_sided.emitted(&context), Vector3::new(4.0, 2.0, 1.0));
+        assert!((light.power() - one_sided.power() * 0.5).length() < 1e-12);
+
+        let brighter = DiffuseLight::new(Vector3::new(4.0, 2.0, 1.0)).with_intensity(2.5);
+        assert_eq!(brighter.emitted(&context), Vector3::new(10.0, 5.0, 2.5));
+        assert!((brighter.power() - one_sided.power() * 2.5).length() < 1e-12);
+    }
+
+    #[test]
+    fn test_subsurface_walk() {
+        let albedo = |color| Subsurface::single_scattering_albedo(color);
+        assert!(albedo(0.0).abs() < 1e-4 && albedo(1.0) > 0.999);
+        assert!((0..10).all(|i| albedo(i as f64 / 10.0) < albedo((i + 1) as f64 / 10.0)));
+
+        // A ray from outside is reflected or refracted into the surface facing up the z-axis.
+        let material = Subsurface::new(Vector3::new(0.8, 0.8, 0.8), Vector3::new(0.1, 0.1, 0.1));
+        let ray = Ray::new(Vector3::new(0.0, 0.0, 1.0), Vector3::new(0.3, 0.0, -1.0));
+        let mut record = HitRecord::new(1.0, Vector3::new(0.0, 0.0, 0.0), &material, 0.5, 0.5);
+        record.set_face_normal(&ray, &Vector3::new(0.0, 0.0, 1.0));
+        for _ in 0..32 {
+            let Some(ScatterType::Specular(scattered)) =
+                material.scatter(&ray, &record).map(|s| s.scatter_type)
+            else {
+                panic!("rays entering the surface scatter specularly");
+            };
+            assert!(scattered.direction.z != 0.0);
+        }
+
+        // From inside, a ray crossing ten radii almost always scatters before the surface, and
+        // a ray crossing a millionth of one almost always leaves through it, diffusely.
+        let far = Ray::new(Vector3::new(0.0, 0.0, -1.0), Vector3::new(0.0, 0.0, 1.0));
+        record.set_face_normal(&far, &Vector3::new(0.0, 0.0, 1.0));
+        let scattered = (0..1000)
+            .filter(|_| {
+                matches!(
+                    material.scatter(&far, &record).unwrap().scatter_type,
+                    ScatterType::Specular(_)
+                )
+            })
+            .count();
+        assert!(scattered > 990, "{}", scattered);
+
+        let near = Ray::new(Vector3::new(0.0, 0.0, -1e-7), Vector3::new(0.0, 0.0, 1.0));
+        let scatter = material.scatter(&near, &record).unwrap();
+        assert!(matches!(scatter.scatter_type, ScatterType::Pdf(_)));
+        assert!((scatter.attenuation - Vector3::new(1.0, 1.0, 1.0)).length() < 1e-9);
+        let out = Ray::new(Vector3::new(0.0, 0.0, 0.0), Vector3::new(0.0, 0.0, 1.0));
+        assert!((material.scattering_pdf(&near, &record, &out) - 1.0 / PI).abs() < 1e-12);
+        let back = Ray::new(Vector3::new(0.0, 0.0, 0.0), Vector3::new(0.0, 0.0, -1.0));
+        assert_eq!(material.scattering_pdf(&near, &record, &back), 0.0);
+    }
+
+    #[test]
+    fn test_beer_lambert_absorption() {
+        let glass = Dielectric::new(1.5).with_absorption(Vector3::new(0.5, 1.0, 0.25), 1.0);
+        let normal = Vector3::new(0.0, 0.0, 1.0);
+
+        // Light leaving the glass after two units is absorbed twice as much as after one.
+        let ray = Ray::new(Vector3::new(0.0, 0.0, 0.0), Vector3::new(0.0, 0.0, 1.0));
+        let mut record = HitRecord::new(2.0, Vector3::new(0.0, 0.0, 2.0), &glass, 0.5, 0.5);
+        record.set_face_normal(&ray, &normal);
+        let attenuation = glass.scatter(&ray, &record).unwrap().attenuation;
+        assert!((attenuation - Vector3::new(0.25, 1.0, 0.0625)).length() < 1e-12);
+
+        // Light arriving from outside has not crossed the glass yet.
+        let outside = Ray::new(Vector3::new(0.0, 0.0, 5.0), Vector3::new(0.0, 0.0, -1.0));
+        record.set_face_normal(&outside, &normal);
+        let attenuation = glass.scatter(&outside, &record).unwrap().attenuation;
+        assert_eq!(attenuation, Vector3::new(1.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn test_textured_metal_and_glass() {
+        use crate::texture::CheckerTexture;
+
+        let (odd, even) = (Vector3::new(0.9, 0.6, 0.2), Vector3::new(0.8, 0.8, 0.8));
+        let checker = || Box::new(CheckerTexture::new(1.0, odd, even));
+        let ray = Ray::new(Vector3::new(0.5, 0.5, 2.0), Vector3::new(0.0, 0.0, -1.0));
+        let scatter = |material: &dyn Material, point: Vector3| {
+            let mut record = HitRecord::new(1.0, point, material, 0.5, 0.5);
+            record.set_face_normal(&ray, &Vector3::new(0.0, 0.0, 1.0));
+            material.scatter(&ray, &record).unwrap()
+        };
+        let (even_point, odd_point) = (Vector3::new(0.5, 0.5, 0.5), Vector3::new(1.5, 0.5, 0.5));
+
+        // A checkered mirror reflects the color of the square it is hit on.
+        let metal = Metal::from_texture(checker(), 0.0);
+        assert_eq!(scatter(&metal, even_point).attenuation, even);
+        assert_eq!(scatter(&metal, odd_point).attenuation, odd);
+
+        let stained = Dielectric::new(1.5).with_tint(checker());
+        assert_eq!(scatter(&stained, odd_point).attenuation, odd);
+        assert_eq!(
+            scatter(&Dielectric::new(1.5), odd_point).attenuation,
+            Vector3::new(1.0, 1.0, 1.0)
+        );
+
+        // Glass frosted in a pattern is smooth on the black squares and rough on the gray ones.
+        let frosted = RoughDielectric::new(1.5, 0.0)
+            .with_roughness_texture(Box::new(CheckerTexture::new(
+                1.0,
+                Vector3::new(0.5, 0.5, 0.5),
+                Vector3::new(0.0, 0.0, 0.0),
+            )))
+            .with_tint(checker());
+        let smooth = scatter(&frosted, even_point);
+        assert!(matches!(smooth.scatter_type, ScatterType::Specular(_)));
+        assert_eq!(smooth.attenuation, even);
+        let rough = scatter(&frosted, odd_point);
+        assert!(matches!(rough.scatter_type, ScatterType::Pdf(_)));
+        assert_eq!(rough.attenuation, odd);
+    }
+
+    #[test]
+    fn test_principled_lobes() {
+        let color = Vector3::new(0.9, 0.5, 0.1);
+        let ray = Ray::new(Vector3::new(0.0, 0.0, 1.0), Vector3::new(0.0, 0.0, -1.0));
+        let scatter = |material: &Principled, ray: &Ray| {
+            let mut record = HitRecord::new(1.0, Vector3::new(0.0, 0.0, 0.0), material, 0.5, 0.5);
+            record.set_face_normal(ray, &Vector3::new(0.0, 0.0, 1.0));
+            material.scatter(ray, &record)
+        };
+
+        // A smooth metal is a mirror reflecting the base color.
+        let metal = Principled::new(color)
+            .with_metallic(1.0)
+            .with_roughness(0.0);
+        let record = scatter(&metal, &ray).unwrap();
+        assert_eq!(record.attenuation, color);
+        let ScatterType::Specular(reflected) = record.scatter_type else {
+            panic!("smooth metals reflect specularly");
+        };
+        assert!((reflected.direction - Vector3::new(0.0, 0.0, 1.0)).length() < 1e-9);
+
+        // Without a specular coat, every ray scatters diffusely with the base color.
+        let matte = Principled::new(color).with_specular(0.0);
+        for _ in 0..32 {
+            let record = scatter(&matte, &ray).unwrap();
+            assert!(matches!(record.scatter_type, ScatterType::Pdf(_)));
+            assert_eq!(record.attenuation, color);
+        }
+
+        // Glass refracts most of the light, tinted with the base color, and reflects the rest.
+        let glass = Principled::new(color)
+            .with_transmission(1.0)
+            .with_roughness(0.0)
+            .with_refraction_index(1.5);
+        let refracted = (0..1000)
+            .filter(|_| {
+                let record = scatter(&glass, &ray).unwrap();
+                let ScatterType::Specular(scattered) = record.scatter_type else {
+                    panic!("smooth glass scatters specularly");
+                };
+                if scattered.direction.z < 0.0 {
+                    assert_eq!(record.attenuation, color);
+                    true
+                } else {
+                    assert_eq!(record.attenuation, Vector3::new(1.0, 1.0, 1.0));
+                    false
+                }
+            })
+            .count();
+        assert!(refracted > 920 && refracted < 1000, "{}", refracted);
+
+        // Rough glossy lobes are sampled into a single direction weighted by their BSDF, and
+        // absorb the rays reflected below the surface.
+        let rough = Principled::new(color)
+            .with_metallic(1.0)
+            .with_roughness(0.4);
+        for record in (0..32).filter_map(|_| scatter(&rough, &ray)) {
+            assert!(matches!(record.scatter_type, ScatterType::Specular(_)));
+            assert!(record.attenuation.x <= color.x + 1e-9);
+        }
+
+        let lamp = Principled::new(color).with_emission(Vector3::new(1.0, 0.8, 0.6), 5.0);
+        let context = ShadingContext::from_uv(0.5, 0.5);
+        assert!(lamp.is_emissive() && !matte.is_emissive());
+        assert_eq!(lamp.emitted(&context), Vector3::new(5.0, 4.0, 3.0));
+        assert!((lamp.power() - Vector3::new(5.0, 4.0, 3.0) * PI).length() < 1e-12);
+    }
+
+    /// Returns the normal with which a `NormalMap` reading a constant tangent-space normal shades
+    /// a surface facing up the z-axis, with u along x and v along y.
+    fn mapped_normal(encoded: Vector3, strength: f64, direction: Vector3) -> Vector3 {
+        let inner: Arc<dyn Material> = Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5)));
+        let material =
+            NormalMap::new(inner, Box::new(SolidTexture::new(encoded))).with_strength(strength);
+        let ray = Ray::new(Vector3::new(0.0, 0.0, 1.0), direction);
+        let mut record = HitRecord::new(1.0, Vector3::new(0.0, 0.0, 0.0), &material, 0.5, 0.5);
+        record.set_face_normal(&ray, &Vector3::new(0.0, 0.0, 1.0));
+        record.set_tangents(Vector3::new(2.0, 0.0, 0.0), Vector3::new(0.0, 3.0, 0.0));
+        material.perturb(&ray, &record).normal
+    }
+
+    #[test]
+    fn test_normal_map_perturbs_normal() {
+        let down = Vector3::new(0.0, 0.0, -1.0);
+        let flat = mapped_normal(Vector3::new(0.5, 0.5, 1.0), 1.0, down);
+        assert!((flat - Vector3::new(0.0, 0.0, 1.0)).length() < 1e-12);
+
+        // Red and green tilt the normal towards the tangent and the bitangent.
+        let tilted = mapped_normal(Vector3::new(1.0, 0.5, 1.0), 1.0, down);
+        assert!((tilted - Vector3::new(1.0, 0.0, 1.0).normalize()).length() < 1e-12);
+        let tilted = mapped_normal(Vector3::new(0.5, 0.0, 1.0), 1.0, down);
+        assert!((tilted - Vector3::new(0.0, -1.0, 1.0).normalize()).length() < 1e-12);
+        let flattened = mapped_normal(Vector3::new(1.0, 0.5, 1.0), 0.0, down);
+        assert!((flattened - Vector3::new(0.0, 0.0, 1.0)).length() < 1e-12);
 
-        let attenuation = self
-            .texture
-            .value(hit_record.u, hit_record.v, &hit_record.poz);
-        Some((scattered, attenuation))
+        // A normal facing away from a grazing ray falls back to the surface normal.
+        let grazing = Vector3::new(-1.0, 0.0, -0.1);
+        let away = mapped_normal(Vector3::new(0.0, 0.5, 0.5), 1.0, grazing);
+        assert!((away - Vector3::new(0.0, 0.0, 1.0)).length() < 1e-12);
     }
 }
@@ -0,0 +1,199 @@
+//! A closed enum of the built-in materials, dispatching their methods with a `match` instead of
+//! a virtual call to each material type, with a `Custom` variant for any other material.
+//!
+//! Objects still hold their material as an `Arc<dyn Material>`, so a `MaterialKind` is reached
+//! through one virtual call like any material. Every object made of one then calls the same
+//! implementation, which the branch predictor learns at once, and the variants are matched inside
+//! it, where the compiler can inline the methods of each built-in material. Whether this beats a
+//! virtual call to every material type depends on the scene and the processor: the ignored
+//! `benchmark_material_dispatch` test renders the Cornell scenes both ways and prints the times.
+//! On the Cornell box and the Cornell box with glass and metal, both ways render within a few
+//! percent of each other, as most of the time goes to intersecting objects and sampling lights, so
+//! scenes keep trait objects by default.
+
+use crate::hit::HitRecord;
+use crate::material::{
+    Dielectric, DiffuseLight, HenyeyGreenstein, Isotropic, Lambertian, Material, Metal, Principled,
+    RoughDielectric, ScatterRecord,
+};
+use crate::ray::Ray;
+use crate::statistics::SceneStatistics;
+use crate::texture::ShadingContext;
+use crate::vector3::Vector3;
+use std::sync::Arc;
+
+/// How the materials of a scene are dispatched on every bounce.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Dispatch {
+    /// Every material is its own trait object.
+    #[default]
+    TraitObjects,
+    /// Every material is a `MaterialKind`, matched on every call.
+    Enum,
+}
+
+/// Represents a material of one of the built-in types, or of any other through `Custom`.
+#[derive(Debug)]
+pub enum MaterialKind {
+    /// A diffuse material.
+    Lambertian(Lambertian),
+    /// A metal, polished or rough.
+    Metal(Metal),
+    /// A smooth transparent material, such as glass or water.
+    Dielectric(Dielectric),
+    /// A frosted transparent material.
+    RoughDielectric(RoughDielectric),
+    /// A light.
+    DiffuseLight(DiffuseLight),
+    /// The particles of a medium, such as smoke.
+    Isotropic(Isotropic),
+    /// The particles of a medium scattering light mostly forward or back, such as fog.
+    HenyeyGreenstein(HenyeyGreenstein),
+    /// The principled material, blending diffuse, coat, metal, glass and emission.
+    Principled(Principled),
+    /// Any other material, dispatched through its trait object.
+    Custom(Arc<dyn Material>),
+}
+
+/// Calls the same method on the material held by any variant of a `MaterialKind`.
+macro_rules! dispatch {
+    ($kind:expr, $material:ident => $call:expr) => {
+        match $kind {
+            MaterialKind::Lambertian($material) => $call,
+            MaterialKind::Metal($material) => $call,
+            MaterialKind::Dielectric($material) => $call,
+            MaterialKind::RoughDielectric($material) => $call,
+            MaterialKind::DiffuseLight($material) => $call,
+            MaterialKind::Isotropic($material) => $call,
+            MaterialKind::HenyeyGreenstein($material) => $call,
+            MaterialKind::Principled($material) => $call,
+            MaterialKind::Custom($material) => $call,
+        }
+    };
+}
+
+impl MaterialKind {
+    /// Shares the material between objects, dispatched as chosen.
+    ///
+    /// # Arguments
+    ///
+    /// * `dispatch` - Whether the material is shared as a `MaterialKind` or as a trait object of
+    ///   its own type.
+    ///
+    /// # Returns
+    ///
+    /// The shared material, which renders the same either way.
+    pub fn shared(self, dispatch: Dispatch) -> Arc<dyn Material> {
+        if dispatch == Dispatch::Enum {
+            return Arc::new(self);
+        }
+        match self {
+            MaterialKind::Lambertian(material) => Arc::new(material),
+            MaterialKind::Metal(material) => Arc::new(material),
+            MaterialKind::Dielectric(material) => Arc::new(material),
+            MaterialKind::RoughDielectric(material) => Arc::new(material),
+            MaterialKind::DiffuseLight(material) => Arc::new(material),
+            MaterialKind::Isotropic(material) => Arc::new(material),
+            MaterialKind::HenyeyGreenstein(material) => Arc::new(material),
+            MaterialKind::Principled(material) => Arc::new(material),
+            MaterialKind::Custom(material) => material,
+        }
+    }
+}
+
+impl Material for MaterialKind {
+    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<ScatterRecord> {
+        dispatch!(self, material => material.scatter(ray, hit_record))
+    }
+
+    fn scattering_pdf(&self, ray: &Ray, hit_record: &HitRecord, scattered: &Ray) -> f64 {
+        dispatch!(self, material => material.scattering_pdf(ray, hit_record, scattered))
+    }
+
+    fn emitted(&self, context: &ShadingContext) -> Vector3 {
+        dispatch!(self, material => material.emitted(context))
+    }
+
+    fn is_mirror(&self) -> bool {
+        dispatch!(self, material => material.is_mirror())
+    }
+
+    fn is_emissive(&self) -> bool {
+        dispatch!(self, material => material.is_emissive())
+    }
+
+    fn surface_queries(&self) -> bool {
+        dispatch!(self, material => material.surface_queries())
+    }
+
+    fn power(&self) -> Vector3 {
+        dispatch!(self, material => material.power())
+    }
+
+    fn name(&self) -> &'static str {
+        dispatch!(self, material => material.name())
+    }
+
+    fn collect_statistics(&self, statistics: &mut SceneStatistics) {
+        dispatch!(self, material => material.collect_statistics(statistics))
+    }
+}
+
+macro_rules! impl_from {
+    ($($variant:ident),*) => {
+        $(
+            impl From<$variant> for MaterialKind {
+                fn from(material: $variant) -> MaterialKind {
+                    MaterialKind::$variant(material)
+                }
+            }
+        )*
+    };
+}
+
+impl_from!(
+    Lambertian,
+    Metal,
+    Dielectric,
+    RoughDielectric,
+    DiffuseLight,
+    Isotropic,
+    HenyeyGreenstein,
+    Principled
+);
+
+impl From<Arc<dyn Material>> for MaterialKind {
+    fn from(material: Arc<dyn Material>) -> MaterialKind {
+        MaterialKind::Custom(material)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Subsurface;
+
+    #[test]
+    fn test_material_kinds_dispatch_to_their_material() {
+        let light = MaterialKind::from(DiffuseLight::new(Vector3::new(4.0, 4.0, 4.0)));
+        assert!(light.is_emissive());
+        assert_eq!(light.name(), "DiffuseLight");
+        assert_eq!(
+            light.power(),
+            DiffuseLight::new(Vector3::new(4.0, 4.0, 4.0)).power()
+        );
+        let mirror = MaterialKind::from(Metal::new(Vector3::new(0.9, 0.9, 0.9), 0.0));
+        assert!(mirror.is_mirror() && !mirror.is_emissive());
+
+        let custom: Arc<dyn Material> = Arc::new(Subsurface::new(
+            Vector3::new(0.9, 0.8, 0.7),
+            Vector3::new(1.0, 0.5, 0.25),
+        ));
+        let kind = MaterialKind::from(custom.clone());
+        assert_eq!(kind.name(), "Subsurface");
+        assert!(Arc::ptr_eq(&kind.shared(Dispatch::TraitObjects), &custom));
+
+        let lambertian = MaterialKind::from(Lambertian::new(Vector3::new(0.5, 0.5, 0.5)));
+        assert_eq!(lambertian.shared(Dispatch::Enum).name(), "Lambertian");
+    }
+}
@@ -0,0 +1,277 @@
+//! Hit records that own their data. A `HitRecord` borrows the material of the object it hit, so
+//! it cannot outlive the traversal of the scene that found it. A `StoredHitRecord` names the
+//! material by its handle in a `MaterialTable` of the scene instead, so it is `'static` and
+//! `Copy`: hits can be kept in buffers or arrays and shaded in a later pass, as deferred shading
+//! and wavefront designs do, and turned back into a `HitRecord` borrowing from the table when
+//! they are shaded.
+
+use crate::hit::HitRecord;
+use crate::material::Material;
+use crate::vector3::Vector3;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// Identifies a material of a `MaterialTable`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MaterialHandle(u32);
+
+impl MaterialHandle {
+    /// Returns the position of the material in its table.
+    pub fn index(&self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// The materials of a scene, each shared once and named by a `MaterialHandle`.
+#[derive(Debug, Default)]
+pub struct MaterialTable {
+    /// The materials, in the order they were added.
+    materials: Vec<Arc<dyn Material>>,
+    /// The handles of the materials, by the address of the material.
+    handles: HashMap<usize, MaterialHandle>,
+    /// The addresses of the objects shared by instances whose materials were added.
+    instances: HashSet<usize>,
+}
+
+impl MaterialTable {
+    /// Creates a new, empty `MaterialTable`.
+    ///
+    /// # Returns
+    ///
+    /// A new `MaterialTable` instance.
+    pub fn new() -> MaterialTable {
+        MaterialTable::default()
+    }
+
+    /// Adds a material, unless the same material was added before.
+    ///
+    /// # Arguments
+    ///
+    /// * `material` - The material to add.
+    ///
+    /// # Returns
+    ///
+    /// The handle of the material.
+    pub fn add(&mut self, material: &Arc<dyn Material>) -> MaterialHandle {
+        let address = Arc::as_ptr(material) as *const () as usize;
+        *self.handles.entry(address).or_insert_with(|| {
+            self.materials.push(material.clone());
+            MaterialHandle((self.materials.len() - 1) as u32)
+        })
+    }
+
+    /// Records that the materials of an object shared by instances are being added, so they are
+    /// added once however many instances show the object.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - The address of the shared object.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the object was not added before.
+    pub fn add_instance(&mut self, address: usize) -> bool {
+        self.instances.insert(address)
+    }
+
+    /// Finds the handle of a material of the table.
+    ///
+    /// # Arguments
+    ///
+    /// * `material` - The material, such as the one of a `HitRecord`.
+    ///
+    /// # Returns
+    ///
+    /// The handle of the material, or `None` if it is not in the table.
+    pub fn handle(&self, material: &dyn Material) -> Option<MaterialHandle> {
+        let address = material as *const dyn Material as *const () as usize;
+        self.handles.get(&address).copied()
+    }
+
+    /// Returns the material with the given handle.
+    ///
+    /// # Arguments
+    ///
+    /// * `handle` - The handle of the material, given by this table.
+    ///
+    /// # Returns
+    ///
+    /// The material.
+    pub fn get(&self, handle: MaterialHandle) -> &dyn Material {
+        self.materials[handle.index()].as_ref()
+    }
+
+    /// Returns the number of materials in the table.
+    pub fn len(&self) -> usize {
+        self.materials.len()
+    }
+
+    /// Returns `true` if the table holds no material.
+    pub fn is_empty(&self) -> bool {
+        self.materials.is_empty()
+    }
+}
+
+/// A record of a hit point that owns its data, naming its material by its handle in a
+/// `MaterialTable`. The fields are those of `HitRecord`.
+#[derive(Debug, Clone, Copy)]
+pub struct StoredHitRecord {
+    /// The parameter `t` at which the ray intersects the object.
+    pub t: f64,
+    /// The position of the hit point.
+    pub poz: Vector3,
+    /// The position of the hit point in the space of the primitive, before any transformation.
+    pub object_poz: Vector3,
+    /// The normal vector at the hit point.
+    pub normal: Vector3,
+    /// Indicates whether the hit point is on the front face of the object.
+    pub front_face: bool,
+    /// The handle of the material of the object at the hit point.
+    pub material: MaterialHandle,
+    /// The u-coordinate for texture mapping.
+    pub u: f64,
+    /// The v-coordinate for texture mapping.
+    pub v: f64,
+    /// The derivative of the position with respect to the u-coordinate.
+    pub tangent: Vector3,
+    /// The derivative of the position with respect to the v-coordinate.
+    pub bitangent: Vector3,
+    /// The fraction of the hemisphere above the hit point blocked by nearby objects.
+    pub occlusion: f64,
+    /// How sharply the surface bends around the hit point.
+    pub curvature: f64,
+    /// The random value of the instance the hit object belongs to.
+    pub variation: f64,
+    /// The color tint of the instance the hit object belongs to.
+    pub tint: Vector3,
+    /// The offset from the hit point to the point rays leaving the surface start from.
+    pub shading_offset: Vector3,
+}
+
+impl StoredHitRecord {
+    /// Stores a hit record, naming its material by its handle.
+    ///
+    /// # Arguments
+    ///
+    /// * `record` - The record of the hit point.
+    /// * `table` - The materials of the scene the record was found in.
+    ///
+    /// # Returns
+    ///
+    /// The stored record, or `None` if the material of the record is not in the table, as for
+    /// objects that do not report their materials.
+    pub fn new(record: &HitRecord, table: &MaterialTable) -> Option<StoredHitRecord> {
+        Some(StoredHitRecord {
+            t: record.t,
+            poz: record.poz,
+            object_poz: record.object_poz,
+            normal: record.normal,
+            front_face: record.front_face,
+            material: table.handle(record.material)?,
+            u: record.u,
+            v: record.v,
+            tangent: record.tangent,
+            bitangent: record.bitangent,
+            occlusion: record.occlusion,
+            curvature: record.curvature,
+            variation: record.variation,
+            tint: record.tint,
+            shading_offset: record.shading_offset,
+        })
+    }
+
+    /// Turns the stored record back into a `HitRecord`, to be shaded.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table the record was stored with.
+    ///
+    /// # Returns
+    ///
+    /// The record of the hit point, borrowing its material from the table.
+    pub fn record<'a>(&self, table: &'a MaterialTable) -> HitRecord<'a> {
+        HitRecord {
+            t: self.t,
+            poz: self.poz,
+            object_poz: self.object_poz,
+            normal: self.normal,
+            front_face: self.front_face,
+            material: table.get(self.material),
+            u: self.u,
+            v: self.v,
+            tangent: self.tangent,
+            bitangent: self.bitangent,
+            occlusion: self.occlusion,
+            curvature: self.curvature,
+            variation: self.variation,
+            tint: self.tint,
+            shading_offset: self.shading_offset,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::{DiffuseLight, Lambertian};
+    use crate::ray::Ray;
+    use crate::scene::Scene;
+    use crate::shapes::sphere::Sphere;
+    use crate::texture::ShadingContext;
+    use crate::transformation::Instance;
+
+    #[test]
+    fn test_records_are_shaded_in_a_later_pass() {
+        let red: Arc<dyn Material> = Arc::new(Lambertian::new(Vector3::new(0.8, 0.1, 0.1)));
+        let light: Arc<dyn Material> = Arc::new(DiffuseLight::new(Vector3::new(4.0, 4.0, 4.0)));
+        let shared = Arc::new(Sphere::new(Vector3::new(0.0, 0.0, 0.0), 1.0, red.clone()));
+        let scene = Scene::new(vec![
+            Box::new(Instance::new(shared.clone(), Vector3::new(-2.0, 0.0, 0.0))),
+            Box::new(Instance::new(shared, Vector3::new(2.0, 0.0, 0.0))),
+            Box::new(Sphere::new(Vector3::new(0.0, 3.0, 0.0), 1.0, light.clone())),
+        ]);
+        let table = scene.material_table();
+        assert_eq!(table.len(), 2);
+        assert_eq!(table.handle(red.as_ref()), Some(MaterialHandle(0)));
+
+        // The first pass only finds the hits, which outlive the traversal.
+        let origin = Vector3::new(0.0, 0.0, 10.0);
+        let targets = [
+            Vector3::new(-2.0, 0.0, 0.0),
+            Vector3::new(2.0, 0.0, 0.0),
+            Vector3::new(0.0, 3.0, 0.0),
+        ];
+        let stored: Vec<(Ray, StoredHitRecord)> = targets
+            .iter()
+            .map(|&target| {
+                let ray = Ray::new(origin, target - origin);
+                let (_, record) = scene.hit_object(&ray, (0.001, f64::INFINITY)).unwrap();
+                let stored = StoredHitRecord::new(&record, &table).unwrap();
+                (ray, stored)
+            })
+            .collect();
+
+        // The second pass shades them.
+        let names: Vec<&str> = stored
+            .iter()
+            .map(|(_, stored)| stored.record(&table).material.name())
+            .collect();
+        assert_eq!(names, ["Lambertian", "Lambertian", "DiffuseLight"]);
+        let (ray, light_hit) = &stored[2];
+        let record = light_hit.record(&table);
+        assert_eq!(
+            record.material.emitted(&ShadingContext::new(ray, &record)),
+            Vector3::new(4.0, 4.0, 4.0)
+        );
+        assert!(((record.poz - Vector3::new(0.0, 3.0, 0.0)).length() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_unknown_materials_are_not_stored() {
+        let table = MaterialTable::new();
+        let material = Lambertian::new(Vector3::new(0.5, 0.5, 0.5));
+        let record = HitRecord::new(1.0, Vector3::default(), &material, 0.0, 0.0);
+        assert!(StoredHitRecord::new(&record, &table).is_none());
+        assert!(table.is_empty());
+    }
+}
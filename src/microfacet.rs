@@ -0,0 +1,371 @@
+//! Helpers for the GGX (Trowbridge-Reitz) microfacet distribution.
+//!
+//! All directions are expressed in a local shading frame where the surface normal is the z-axis,
+//! and `alpha` is the GGX roughness of the surface.
+
+use crate::utils::{reflect, refract};
+use crate::vector3::Vector3;
+use std::f64::consts::PI;
+
+/// Evaluates the GGX normal distribution function.
+///
+/// # Arguments
+///
+/// * `h` - The microfacet normal.
+/// * `alpha` - The roughness of the surface.
+///
+/// # Returns
+///
+/// The density of microfacets oriented along `h`.
+pub fn ggx_d(h: &Vector3, alpha: f64) -> f64 {
+    if h.z <= 0.0 {
+        return 0.0;
+    }
+    let alpha2 = alpha * alpha;
+    let denominator = h.z * h.z * (alpha2 - 1.0) + 1.0;
+    alpha2 / (PI * denominator * denominator)
+}
+
+/// Evaluates the Smith lambda function of the GGX distribution.
+///
+/// # Arguments
+///
+/// * `w` - The direction, pointing away from the surface.
+/// * `alpha` - The roughness of the surface.
+///
+/// # Returns
+///
+/// The lambda value, used to build the masking-shadowing functions.
+pub fn ggx_lambda(w: &Vector3, alpha: f64) -> f64 {
+    let cos2 = w.z * w.z;
+    if cos2 == 0.0 {
+        return f64::INFINITY;
+    }
+    let tan2 = (1.0 - cos2).max(0.0) / cos2;
+    ((1.0 + alpha * alpha * tan2).sqrt() - 1.0) / 2.0
+}
+
+/// Evaluates the Smith masking function for a single direction.
+///
+/// # Arguments
+///
+/// * `w` - The direction, pointing away from the surface.
+/// * `alpha` - The roughness of the surface.
+///
+/// # Returns
+///
+/// The fraction of microfacets visible from `w`.
+pub fn ggx_g1(w: &Vector3, alpha: f64) -> f64 {
+    if w.z <= 0.0 {
+        return 0.0;
+    }
+    1.0 / (1.0 + ggx_lambda(w, alpha))
+}
+
+/// Evaluates the height-correlated Smith masking-shadowing function.
+///
+/// # Arguments
+///
+/// * `wo` - The outgoing direction, pointing away from the surface.
+/// * `wi` - The incoming direction, pointing away from the surface.
+/// * `alpha` - The roughness of the surface.
+///
+/// # Returns
+///
+/// The fraction of microfacets visible from both directions.
+pub fn ggx_g2(wo: &Vector3, wi: &Vector3, alpha: f64) -> f64 {
+    if wo.z <= 0.0 || wi.z <= 0.0 {
+        return 0.0;
+    }
+    1.0 / (1.0 + ggx_lambda(wo, alpha) + ggx_lambda(wi, alpha))
+}
+
+/// Samples a microfacet normal from the distribution of normals visible from a direction,
+/// following Heitz, "Sampling the GGX Distribution of Visible Normals" (2018).
+///
+/// # Arguments
+///
+/// * `wo` - The outgoing direction, pointing away from the surface.
+/// * `alpha` - The roughness of the surface.
+///
+/// # Returns
+///
+/// The sampled microfacet normal.
+pub fn sample_ggx_vndf(wo: &Vector3, alpha: f64) -> Vector3 {
+    let vh = Vector3::new(alpha * wo.x, alpha * wo.y, wo.z).normalize();
+
+    let length_squared = vh.x * vh.x + vh.y * vh.y;
+    let t1 = if length_squared > 0.0 {
+        Vector3::new(-vh.y, vh.x, 0.0) / length_squared.sqrt()
+    } else {
+        Vector3::new(1.0, 0.0, 0.0)
+    };
+    let t2 = vh.cross(&t1);
+
+    let r = fastrand::f64().sqrt();
+    let phi = 2.0 * PI * fastrand::f64();
+    let p1 = r * phi.cos();
+    let s = 0.5 * (1.0 + vh.z);
+    let p2 = (1.0 - s) * (1.0 - p1 * p1).sqrt() + s * r * phi.sin();
+
+    let nh = p1 * t1 + p2 * t2 + (1.0 - p1 * p1 - p2 * p2).max(0.0).sqrt() * vh;
+    Vector3::new(alpha * nh.x, alpha * nh.y, nh.z.max(0.0)).normalize()
+}
+
+/// Computes the probability density of reflecting `wo` into `wi` when the microfacet normal is
+/// sampled with `sample_ggx_vndf`.
+///
+/// # Arguments
+///
+/// * `wo` - The outgoing direction, pointing away from the surface.
+/// * `wi` - The reflected direction, pointing away from the surface.
+/// * `alpha` - The roughness of the surface.
+///
+/// # Returns
+///
+/// The probability density of `wi` with respect to solid angle.
+pub fn ggx_reflection_pdf(wo: &Vector3, wi: &Vector3, alpha: f64) -> f64 {
+    if wo.z <= 0.0 || wi.z <= 0.0 {
+        return 0.0;
+    }
+    let h = (*wo + *wi).normalize();
+    ggx_g1(wo, alpha) * ggx_d(&h, alpha) / (4.0 * wo.z)
+}
+
+/// The cosine below which directions graze a rough dielectric, which scatters no light into them.
+const GRAZING_COSINE: f64 = 1e-9;
+
+/// Evaluates the Fresnel reflectance of a smooth boundary between two dielectrics for
+/// unpolarized light.
+///
+/// # Arguments
+///
+/// * `cos_i` - The cosine of the angle between the incident direction and the normal, on the
+///   side of the incident light.
+/// * `eta` - The index of refraction of the side the light enters divided by the index of the
+///   side it comes from.
+///
+/// # Returns
+///
+/// The fraction of the light reflected, one under total internal reflection.
+pub fn fresnel_dielectric(cos_i: f64, eta: f64) -> f64 {
+    let cos_i = cos_i.clamp(0.0, 1.0);
+    let sin2_t = (1.0 - cos_i * cos_i) / (eta * eta);
+    if sin2_t >= 1.0 {
+        return 1.0;
+    }
+    let cos_t = (1.0 - sin2_t).sqrt();
+    let parallel = (eta * cos_i - cos_t) / (eta * cos_i + cos_t);
+    let perpendicular = (cos_i - eta * cos_t) / (cos_i + eta * cos_t);
+    (parallel * parallel + perpendicular * perpendicular) / 2.0
+}
+
+/// Finds the microfacet normal scattering `wo` into `wi` off a rough dielectric, by reflection
+/// if both directions are above the surface and by refraction otherwise.
+///
+/// # Arguments
+///
+/// * `wo` - The outgoing direction, above the surface.
+/// * `wi` - The scattered direction.
+/// * `eta` - The index of refraction below the surface divided by the index above it.
+///
+/// # Returns
+///
+/// The microfacet normal, facing up, or `None` if `wi` grazes the surface or no microfacet
+/// facing both directions the right way scatters one into the other.
+fn dielectric_half_vector(wo: &Vector3, wi: &Vector3, eta: f64) -> Option<Vector3> {
+    if wo.z <= 0.0 || wi.z.abs() < GRAZING_COSINE {
+        return None;
+    }
+    let sum = if wi.z > 0.0 {
+        *wo + *wi
+    } else {
+        *wo + *wi * eta
+    };
+    if sum.is_near_zero() {
+        return None;
+    }
+    let h = sum.normalize();
+    let h = if h.z < 0.0 { -h } else { h };
+    if wo.dot(&h) <= 0.0 || (wi.z < 0.0 && wi.dot(&h) >= 0.0) {
+        return None;
+    }
+    Some(h)
+}
+
+/// Evaluates the GGX microfacet BSDF of a rough dielectric times the cosine of the scattered
+/// direction, following Walter et al., "Microfacet Models for Refraction through Rough
+/// Surfaces" (2007). Refracted light is compressed into the smaller solid angle of the denser
+/// side, as radiance is.
+///
+/// # Arguments
+///
+/// * `wo` - The outgoing direction, above the surface.
+/// * `wi` - The scattered direction, above the surface for reflection and below it for
+///   refraction.
+/// * `alpha` - The roughness of the surface.
+/// * `eta` - The index of refraction below the surface divided by the index above it.
+///
+/// # Returns
+///
+/// The BSDF times the absolute cosine of `wi`.
+pub fn ggx_dielectric_bsdf(wo: &Vector3, wi: &Vector3, alpha: f64, eta: f64) -> f64 {
+    let Some(h) = dielectric_half_vector(wo, wi, eta) else {
+        return 0.0;
+    };
+    let fresnel = fresnel_dielectric(wo.dot(&h), eta);
+    let g2 = 1.0 / (1.0 + ggx_lambda(wo, alpha) + ggx_lambda(wi, alpha));
+    if wi.z > 0.0 {
+        ggx_d(&h, alpha) * g2 * fresnel / (4.0 * wo.z)
+    } else {
+        let denominator = (wi.dot(&h) + wo.dot(&h) / eta).powi(2);
+        (1.0 - fresnel) * ggx_d(&h, alpha) * g2 * wi.dot(&h).abs() * wo.dot(&h)
+            / (wo.z * denominator * eta * eta)
+    }
+}
+
+/// Samples a direction scattered off a rough dielectric: a microfacet normal is drawn with
+/// `sample_ggx_vndf`, and light is reflected off it with the Fresnel reflectance and refracted
+/// through it otherwise.
+///
+/// # Arguments
+///
+/// * `wo` - The outgoing direction, above the surface.
+/// * `alpha` - The roughness of the surface.
+/// * `eta` - The index of refraction below the surface divided by the index above it.
+///
+/// # Returns
+///
+/// The scattered direction, or `None` if a steep microfacet sent it to the wrong side of the
+/// surface, where it would be mistaken for the other kind of scattering.
+pub fn sample_ggx_dielectric(wo: &Vector3, alpha: f64, eta: f64) -> Option<Vector3> {
+    let h = sample_ggx_vndf(wo, alpha);
+    if fastrand::f64() < fresnel_dielectric(wo.dot(&h), eta) {
+        Some(reflect(-*wo, h)).filter(|wi| wi.z > 0.0)
+    } else {
+        Some(refract(-*wo, h, 1.0 / eta)).filter(|wi| wi.z < 0.0)
+    }
+}
+
+/// Computes the probability density of scattering `wo` into `wi` off a rough dielectric when
+/// the microfacet normal is sampled with `sample_ggx_vndf`, and light is reflected off it with
+/// the Fresnel reflectance and refracted through it otherwise.
+///
+/// # Arguments
+///
+/// * `wo` - The outgoing direction, above the surface.
+/// * `wi` - The scattered direction.
+/// * `alpha` - The roughness of the surface.
+/// * `eta` - The index of refraction below the surface divided by the index above it.
+///
+/// # Returns
+///
+/// The probability density of `wi` with respect to solid angle.
+pub fn ggx_dielectric_pdf(wo: &Vector3, wi: &Vector3, alpha: f64, eta: f64) -> f64 {
+    let Some(h) = dielectric_half_vector(wo, wi, eta) else {
+        return 0.0;
+    };
+    let fresnel = fresnel_dielectric(wo.dot(&h), eta);
+    let visible = ggx_g1(wo, alpha) * wo.dot(&h) * ggx_d(&h, alpha) / wo.z;
+    if wi.z > 0.0 {
+        visible * fresnel / (4.0 * wo.dot(&h))
+    } else {
+        let denominator = (wi.dot(&h) + wo.dot(&h) / eta).powi(2);
+        visible * (1.0 - fresnel) * wi.dot(&h).abs() / denominator
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distribution_is_normalized() {
+        // The projected area of the microfacets must equal the area of the macro surface.
+        for alpha in [0.1, 0.5, 1.0] {
+            let steps = 4000;
+            let mut integral = 0.0;
+            for i in 0..steps {
+                let theta = (i as f64 + 0.5) / steps as f64 * PI / 2.0;
+                let h = Vector3::new(theta.sin(), 0.0, theta.cos());
+                integral += ggx_d(&h, alpha) * theta.cos() * theta.sin() * 2.0 * PI;
+            }
+            integral *= PI / 2.0 / steps as f64;
+            assert!(
+                (integral - 1.0).abs() < 1e-3,
+                "alpha {}: {}",
+                alpha,
+                integral
+            );
+        }
+    }
+
+    #[test]
+    fn test_vndf_samples_face_the_viewer() {
+        let wo = Vector3::new(0.6, 0.0, 0.8);
+        for _ in 0..1000 {
+            let h = sample_ggx_vndf(&wo, 0.7);
+            assert!(h.z >= 0.0);
+            assert!(h.dot(&wo) >= -1e-12);
+        }
+    }
+
+    #[test]
+    fn test_fresnel_dielectric() {
+        // Light hitting glass head-on reflects ((n - 1) / (n + 1))^2 of its power.
+        assert!((fresnel_dielectric(1.0, 1.5) - 0.04).abs() < 1e-12);
+        assert!((fresnel_dielectric(1.0, 1.0 / 1.5) - 0.04).abs() < 1e-12);
+        assert_eq!(fresnel_dielectric(0.0, 1.5), 1.0);
+        // Past the critical angle inside the glass, all light is reflected.
+        assert_eq!(fresnel_dielectric(0.5, 1.0 / 1.5), 1.0);
+    }
+
+    #[test]
+    fn test_dielectric_pdf_is_normalized() {
+        // The density integrates to one over the whole sphere, on both sides of the surface,
+        // less the samples steep microfacets scatter to the wrong side, which are many when
+        // light leaves the denser side.
+        for (alpha, eta) in [(0.2, 1.5), (0.5, 1.5), (0.5, 1.0 / 1.5)] {
+            let wo = Vector3::new(0.5, 0.0, 0.75f64.sqrt());
+            let (steps, mut integral) = (400, 0.0);
+            for i in 0..steps {
+                let theta = (i as f64 + 0.5) / steps as f64 * PI;
+                for j in 0..2 * steps {
+                    let phi = (j as f64 + 0.5) / steps as f64 * PI;
+                    let wi = Vector3::new(
+                        theta.sin() * phi.cos(),
+                        theta.sin() * phi.sin(),
+                        theta.cos(),
+                    );
+                    integral += ggx_dielectric_pdf(&wo, &wi, alpha, eta) * theta.sin();
+                }
+            }
+            integral *= (PI / steps as f64).powi(2);
+            assert!(
+                integral < 1.005 && integral > 0.8,
+                "alpha {}, eta {}: {}",
+                alpha,
+                eta,
+                integral
+            );
+
+            // Without the compression of refracted light, no energy is created.
+            let weight = |wi: &Vector3| {
+                let scale = if wi.z < 0.0 { eta * eta } else { 1.0 };
+                ggx_dielectric_bsdf(&wo, wi, alpha, eta) * scale
+                    / ggx_dielectric_pdf(&wo, wi, alpha, eta)
+            };
+            let down = Vector3::new(-0.2, 0.1, -0.9).normalize();
+            let up = Vector3::new(-0.4, 0.0, 0.8).normalize();
+            assert!(weight(&up) <= 1.0 + 1e-9 && weight(&down) <= 1.0 + 1e-9);
+
+            // The density matches the sampled directions.
+            let samples = 20000;
+            let kept = (0..samples)
+                .filter(|_| sample_ggx_dielectric(&wo, alpha, eta).is_some())
+                .count();
+            let kept = kept as f64 / samples as f64;
+            assert!((kept - integral).abs() < 0.02, "{} {}", kept, integral);
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use crate::vector3::Vector3;
+
+/// Represents an orthonormal basis built around a normal vector.
+#[derive(Debug, Clone, Copy)]
+pub struct Onb {
+    /// The first tangent vector of the basis.
+    pub u: Vector3,
+    /// The second tangent vector of the basis.
+    pub v: Vector3,
+    /// The normal vector of the basis.
+    pub w: Vector3,
+}
+
+impl Onb {
+    /// Creates a new orthonormal basis whose `w` axis is aligned with the given normal.
+    ///
+    /// # Arguments
+    ///
+    /// * `normal` - The normal vector, which does not need to be normalized.
+    ///
+    /// # Returns
+    ///
+    /// A new `Onb` instance.
+    pub fn new(normal: &Vector3) -> Onb {
+        let w = normal.normalize();
+        let a = if w.x.abs() > 0.9 {
+            Vector3::new(0.0, 1.0, 0.0)
+        } else {
+            Vector3::new(1.0, 0.0, 0.0)
+        };
+        let v = w.cross(&a).normalize();
+        let u = w.cross(&v);
+        Onb { u, v, w }
+    }
+
+    /// Transforms a vector from the local coordinates of the basis to world coordinates.
+    ///
+    /// # Arguments
+    ///
+    /// * `local` - The vector in local coordinates.
+    ///
+    /// # Returns
+    ///
+    /// The vector in world coordinates.
+    pub fn local_to_world(&self, local: &Vector3) -> Vector3 {
+        local.x * self.u + local.y * self.v + local.z * self.w
+    }
+
+    /// Transforms a vector from world coordinates to the local coordinates of the basis.
+    ///
+    /// # Arguments
+    ///
+    /// * `world` - The vector in world coordinates.
+    ///
+    /// # Returns
+    ///
+    /// The vector in local coordinates.
+    pub fn world_to_local(&self, world: &Vector3) -> Vector3 {
+        Vector3::new(world.dot(&self.u), world.dot(&self.v), world.dot(&self.w))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let onb = Onb::new(&Vector3::new(1.0, 2.0, -3.0));
+        let world = Vector3::new(0.3, -0.5, 0.8);
+        let back = onb.local_to_world(&onb.world_to_local(&world));
+
+        assert!((back - world).length() < 1e-12);
+        assert!((onb.world_to_local(&onb.w) - Vector3::new(0.0, 0.0, 1.0)).length() < 1e-12);
+    }
+}
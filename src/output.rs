@@ -0,0 +1,741 @@
+use crate::vector3::Vector3;
+use std::fs;
+use std::path::Path;
+
+/// How linear colors are encoded into the values stored in the output image.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Encoding {
+    /// A pure power curve with the given gamma, such as 2.2.
+    Gamma(f64),
+    /// The exact sRGB transfer function, with its linear segment near black.
+    Srgb,
+}
+
+impl Default for Encoding {
+    fn default() -> Encoding {
+        Encoding::Gamma(2.2)
+    }
+}
+
+impl Encoding {
+    /// Parses an encoding, either `srgb` or a gamma value.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The text to parse.
+    ///
+    /// # Returns
+    ///
+    /// The parsed `Encoding`, or a description of why the text is invalid.
+    pub fn parse(value: &str) -> Result<Encoding, String> {
+        if value.eq_ignore_ascii_case("srgb") {
+            return Ok(Encoding::Srgb);
+        }
+        match value.parse::<f64>() {
+            Ok(gamma) if gamma > 0.0 => Ok(Encoding::Gamma(gamma)),
+            _ => Err(format!(
+                "expected `srgb` or a positive gamma, found `{}`",
+                value
+            )),
+        }
+    }
+
+    /// Encodes a linear color component.
+    ///
+    /// # Arguments
+    ///
+    /// * `linear_component` - The linear color component.
+    ///
+    /// # Returns
+    ///
+    /// The encoded component, between 0 and 1.
+    pub fn encode(self, linear_component: f64) -> f64 {
+        let linear_component = linear_component.clamp(0.0, 1.0);
+        match self {
+            Encoding::Gamma(gamma) => linear_component.powf(1.0 / gamma),
+            Encoding::Srgb => {
+                if linear_component <= 0.0031308 {
+                    12.92 * linear_component
+                } else {
+                    1.055 * linear_component.powf(1.0 / 2.4) - 0.055
+                }
+            }
+        }
+    }
+
+    /// Decodes an encoded color component back into a linear one, the inverse of `encode`.
+    ///
+    /// # Arguments
+    ///
+    /// * `encoded_component` - The encoded color component, between 0 and 1.
+    ///
+    /// # Returns
+    ///
+    /// The linear component, between 0 and 1.
+    pub fn decode(self, encoded_component: f64) -> f64 {
+        let encoded_component = encoded_component.clamp(0.0, 1.0);
+        match self {
+            Encoding::Gamma(gamma) => encoded_component.powf(gamma),
+            Encoding::Srgb => {
+                if encoded_component <= 0.04045 {
+                    encoded_component / 12.92
+                } else {
+                    ((encoded_component + 0.055) / 1.055).powf(2.4)
+                }
+            }
+        }
+    }
+}
+
+/// The number of entries of the tables sampled from the curves of the built-in film stocks.
+const FILM_TABLE_SIZE: usize = 256;
+
+/// Represents a response curve for every color channel, loaded from the 1D LUT of a `.cube`
+/// file and applied to encoded colors for simple look development.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResponseCurves {
+    /// The smallest input value of each channel covered by the curves.
+    domain_min: Vector3,
+    /// The largest input value of each channel covered by the curves.
+    domain_max: Vector3,
+    /// The output values of the curves at evenly spaced inputs, holding all three channels.
+    table: Vec<Vector3>,
+}
+
+impl ResponseCurves {
+    /// Loads response curves from a `.cube` file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the `.cube` file.
+    ///
+    /// # Returns
+    ///
+    /// The loaded `ResponseCurves`, or a description of why the file could not be loaded.
+    pub fn load(path: &Path) -> Result<ResponseCurves, String> {
+        let contents =
+            fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        ResponseCurves::parse(&contents).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// Parses response curves from the contents of a `.cube` file holding a 1D LUT.
+    ///
+    /// # Arguments
+    ///
+    /// * `contents` - The contents of the file. Empty lines, comments starting with `#` and the
+    ///   `TITLE` keyword are ignored.
+    ///
+    /// # Returns
+    ///
+    /// The parsed `ResponseCurves`, or a description of the first invalid line.
+    pub fn parse(contents: &str) -> Result<ResponseCurves, String> {
+        let mut size = None;
+        let mut domain_min = Vector3::new(0.0, 0.0, 0.0);
+        let mut domain_max = Vector3::new(1.0, 1.0, 1.0);
+        let mut table = Vec::new();
+
+        for line in contents.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') || line.starts_with("TITLE") {
+                continue;
+            }
+            let mut words = line.split_whitespace();
+            let keyword = words.next().unwrap_or_default();
+            let values: Vec<&str> = words.collect();
+            let invalid = || format!("invalid line `{}`", line);
+
+            match keyword {
+                "LUT_1D_SIZE" => {
+                    let value: usize = values
+                        .first()
+                        .and_then(|value| value.parse().ok())
+                        .ok_or_else(invalid)?;
+                    if value < 2 {
+                        return Err(invalid());
+                    }
+                    size = Some(value);
+                }
+                "LUT_3D_SIZE" => return Err("3D LUTs are not supported".to_string()),
+                "DOMAIN_MIN" => domain_min = Self::parse_triple(&values).ok_or_else(invalid)?,
+                "DOMAIN_MAX" => domain_max = Self::parse_triple(&values).ok_or_else(invalid)?,
+                _ => {
+                    let mut entry = vec![keyword];
+                    entry.extend(values);
+                    table.push(Self::parse_triple(&entry).ok_or_else(invalid)?);
+                }
+            }
+        }
+
+        let size = size.ok_or("missing `LUT_1D_SIZE`")?;
+        if table.len() != size {
+            return Err(format!(
+                "expected {} table entries, found {}",
+                size,
+                table.len()
+            ));
+        }
+        // The input of every channel is scaled by the width of its domain, which cannot be empty.
+        let domains = [
+            ("red", domain_min.x, domain_max.x),
+            ("green", domain_min.y, domain_max.y),
+            ("blue", domain_min.z, domain_max.z),
+        ];
+        if let Some((channel, min, max)) = domains.iter().find(|(_, min, max)| min >= max) {
+            return Err(format!(
+                "empty domain of the {} channel: `DOMAIN_MIN` {} is not below `DOMAIN_MAX` {}",
+                channel, min, max
+            ));
+        }
+        Ok(ResponseCurves {
+            domain_min,
+            domain_max,
+            table,
+        })
+    }
+
+    /// Samples response curves from a function giving the output of every channel for an input
+    /// value between 0 and 1.
+    ///
+    /// # Arguments
+    ///
+    /// * `size` - The number of table entries, at least 2.
+    /// * `curve` - The function giving the output of every channel.
+    ///
+    /// # Returns
+    ///
+    /// The sampled `ResponseCurves`.
+    pub fn from_fn(size: usize, curve: impl Fn(f64) -> Vector3) -> ResponseCurves {
+        let size = size.max(2);
+        ResponseCurves {
+            domain_min: Vector3::new(0.0, 0.0, 0.0),
+            domain_max: Vector3::new(1.0, 1.0, 1.0),
+            table: (0..size)
+                .map(|index| curve(index as f64 / (size - 1) as f64))
+                .collect(),
+        }
+    }
+
+    /// Finds response curves by name, either a built-in film stock or a `.cube` file.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the film stock, or the path of the `.cube` file.
+    /// * `directory` - The directory relative paths start from.
+    ///
+    /// # Returns
+    ///
+    /// The `ResponseCurves`, or a description of why the name gives none.
+    pub fn find(name: &str, directory: &Path) -> Result<ResponseCurves, String> {
+        if let Ok(stock) = FilmStock::parse(name) {
+            return Ok(stock.curves());
+        }
+        let path = Path::new(name);
+        if !path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("cube"))
+        {
+            return Err(format!(
+                "expected a `.cube` file or one of the film stocks {}, found `{}`",
+                FilmStock::names(),
+                name
+            ));
+        }
+        ResponseCurves::load(&directory.join(path))
+    }
+
+    /// Parses three numbers.
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The text of the numbers.
+    ///
+    /// # Returns
+    ///
+    /// The numbers as a `Vector3`, or `None` if there are not exactly three valid numbers.
+    fn parse_triple(values: &[&str]) -> Option<Vector3> {
+        if values.len() != 3 {
+            return None;
+        }
+        Some(Vector3::new(
+            values[0].parse().ok()?,
+            values[1].parse().ok()?,
+            values[2].parse().ok()?,
+        ))
+    }
+
+    /// Applies the curve of one channel, interpolating linearly between table entries.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The input value of the channel.
+    /// * `min` - The smallest input value covered by the curve.
+    /// * `max` - The largest input value covered by the curve.
+    /// * `channel` - Extracts the channel from a table entry.
+    ///
+    /// # Returns
+    ///
+    /// The output value of the channel.
+    fn apply_channel(&self, value: f64, min: f64, max: f64, channel: fn(&Vector3) -> f64) -> f64 {
+        let position =
+            ((value - min) / (max - min)).clamp(0.0, 1.0) * (self.table.len() - 1) as f64;
+        let index = (position.floor() as usize).min(self.table.len() - 2);
+        let fraction = position - index as f64;
+        (1.0 - fraction) * channel(&self.table[index]) + fraction * channel(&self.table[index + 1])
+    }
+
+    /// Applies the curves to an encoded color.
+    ///
+    /// # Arguments
+    ///
+    /// * `color` - The encoded color.
+    ///
+    /// # Returns
+    ///
+    /// The color with the curve of each channel applied.
+    pub fn apply(&self, color: Vector3) -> Vector3 {
+        Vector3::new(
+            self.apply_channel(color.x, self.domain_min.x, self.domain_max.x, |c| c.x),
+            self.apply_channel(color.y, self.domain_min.y, self.domain_max.y, |c| c.y),
+            self.apply_channel(color.z, self.domain_min.z, self.domain_max.z, |c| c.z),
+        )
+    }
+}
+
+/// The built-in film stocks, response curves giving encoded colors the contrast and color cast of
+/// a kind of photographic film, as a finishing step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilmStock {
+    /// A neutral S-curve, deepening shadows and brightening highlights.
+    Contrast,
+    /// A soft, warm negative film for skin tones, with lifted blacks and gentle highlights.
+    Portrait,
+    /// A punchy slide film, with deep blacks, strong contrast and slightly cool shadows.
+    Slide,
+    /// A motion picture look, with teal shadows, warm highlights and rolled-off whites.
+    Cinema,
+}
+
+/// The S-curve of one channel of a film stock.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct FilmCurve {
+    /// The steepness of the curve at its midpoint.
+    contrast: f64,
+    /// The input value at which the curve is steepest, below one half to brighten the channel.
+    midpoint: f64,
+    /// The output value for black.
+    black: f64,
+    /// The output value for white.
+    white: f64,
+}
+
+impl FilmCurve {
+    /// Applies the curve, a logistic function rescaled to pass through the black and white
+    /// levels at the ends of the input range.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The input value, between 0 and 1.
+    ///
+    /// # Returns
+    ///
+    /// The output value, between the black and white levels.
+    fn apply(&self, value: f64) -> f64 {
+        let logistic = |x: f64| 1.0 / (1.0 + (-self.contrast * (x - self.midpoint)).exp());
+        let (low, high) = (logistic(0.0), logistic(1.0));
+        let shaped = (logistic(value.clamp(0.0, 1.0)) - low) / (high - low);
+        self.black + (self.white - self.black) * shaped
+    }
+}
+
+impl FilmStock {
+    /// Every built-in film stock.
+    pub const ALL: [FilmStock; 4] = [
+        FilmStock::Contrast,
+        FilmStock::Portrait,
+        FilmStock::Slide,
+        FilmStock::Cinema,
+    ];
+
+    /// Returns the name of the film stock, as given on the command line.
+    pub fn name(self) -> &'static str {
+        match self {
+            FilmStock::Contrast => "contrast",
+            FilmStock::Portrait => "portrait",
+            FilmStock::Slide => "slide",
+            FilmStock::Cinema => "cinema",
+        }
+    }
+
+    /// Returns the names of every film stock, separated by commas, for messages.
+    pub fn names() -> String {
+        let names: Vec<&str> = Self::ALL.iter().map(|stock| stock.name()).collect();
+        names.join(", ")
+    }
+
+    /// Parses the name of a film stock, ignoring case.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the film stock.
+    ///
+    /// # Returns
+    ///
+    /// The `FilmStock`, or a description of the error if no film stock has the name.
+    pub fn parse(name: &str) -> Result<FilmStock, String> {
+        Self::ALL
+            .into_iter()
+            .find(|stock| stock.name().eq_ignore_ascii_case(name))
+            .ok_or_else(|| {
+                format!(
+                    "unknown film stock `{}`, expected one of {}",
+                    name,
+                    Self::names()
+                )
+            })
+    }
+
+    /// Returns the curves of the red, green and blue channels of the film stock.
+    fn channels(self) -> [FilmCurve; 3] {
+        let curve = |contrast, midpoint, black, white| FilmCurve {
+            contrast,
+            midpoint,
+            black,
+            white,
+        };
+        match self {
+            FilmStock::Contrast => [curve(6.0, 0.5, 0.0, 1.0); 3],
+            FilmStock::Portrait => [
+                curve(4.0, 0.47, 0.035, 1.0),
+                curve(4.0, 0.5, 0.03, 0.98),
+                curve(4.0, 0.53, 0.025, 0.95),
+            ],
+            FilmStock::Slide => [
+                curve(8.0, 0.5, 0.0, 1.0),
+                curve(8.0, 0.5, 0.0, 1.0),
+                curve(8.0, 0.48, 0.01, 1.0),
+            ],
+            FilmStock::Cinema => [
+                curve(5.0, 0.47, 0.02, 0.98),
+                curve(5.0, 0.5, 0.04, 0.96),
+                curve(5.0, 0.55, 0.08, 0.9),
+            ],
+        }
+    }
+
+    /// Samples the curves of the film stock into a table of response curves.
+    ///
+    /// # Returns
+    ///
+    /// The `ResponseCurves` of the film stock.
+    pub fn curves(self) -> ResponseCurves {
+        let [red, green, blue] = self.channels();
+        ResponseCurves::from_fn(FILM_TABLE_SIZE, |value| {
+            Vector3::new(red.apply(value), green.apply(value), blue.apply(value))
+        })
+    }
+}
+
+/// Represents the pipeline turning the linear colors of a render into the bytes of the output
+/// image: an encoding followed by optional response curves.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OutputTransform {
+    /// The encoding of linear colors.
+    encoding: Encoding,
+    /// The response curves applied to encoded colors, if any.
+    curves: Option<ResponseCurves>,
+}
+
+impl OutputTransform {
+    /// Creates a new `OutputTransform` with the given encoding and no response curves.
+    ///
+    /// # Arguments
+    ///
+    /// * `encoding` - The encoding of linear colors.
+    ///
+    /// # Returns
+    ///
+    /// A new `OutputTransform` instance.
+    pub fn new(encoding: Encoding) -> OutputTransform {
+        OutputTransform {
+            encoding,
+            curves: None,
+        }
+    }
+
+    /// Sets the response curves applied after encoding.
+    ///
+    /// # Arguments
+    ///
+    /// * `curves` - The response curves.
+    ///
+    /// # Returns
+    ///
+    /// The `OutputTransform` with the given response curves.
+    pub fn with_curves(mut self, curves: ResponseCurves) -> OutputTransform {
+        self.curves = Some(curves);
+        self
+    }
+
+    /// Turns a linear color into the bytes of an output pixel.
+    ///
+    /// # Arguments
+    ///
+    /// * `color` - The linear color.
+    ///
+    /// # Returns
+    ///
+    /// The red, green and blue bytes of the pixel.
+    pub fn apply(&self, color: Vector3) -> image::Rgb<u8> {
+        let mut encoded = Vector3::new(
+            self.encoding.encode(color.x),
+            self.encoding.encode(color.y),
+            self.encoding.encode(color.z),
+        );
+        if let Some(curves) = &self.curves {
+            encoded = curves.apply(encoded);
+        }
+        let clamped = Vector3::new(
+            encoded.x.clamp(0.0, 1.0),
+            encoded.y.clamp(0.0, 1.0),
+            encoded.z.clamp(0.0, 1.0),
+        );
+        (255.0 * clamped).to_rgb()
+    }
+}
+
+/// The extensions of the image formats storing linear colors as floating-point numbers.
+const HIGH_DYNAMIC_RANGE_EXTENSIONS: [&str; 2] = ["exr", "hdr"];
+
+/// The extensions of the image formats storing an alpha channel.
+const ALPHA_EXTENSIONS: [&str; 5] = ["png", "tif", "tiff", "webp", "exr"];
+
+/// Checks whether an image file stores linear floating-point colors, which keep highlights
+/// brighter than white for tone mapping in other tools, instead of encoded 8-bit colors.
+///
+/// # Arguments
+///
+/// * `path` - The path of the image file, whose extension gives the format.
+///
+/// # Returns
+///
+/// `true` for OpenEXR (`.exr`) and Radiance HDR (`.hdr`) files.
+pub fn is_high_dynamic_range(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| {
+        HIGH_DYNAMIC_RANGE_EXTENSIONS
+            .iter()
+            .any(|hdr| extension.eq_ignore_ascii_case(hdr))
+    })
+}
+
+/// Checks whether an image file can store an alpha channel.
+///
+/// # Arguments
+///
+/// * `path` - The path of the image file, whose extension gives the format.
+///
+/// # Returns
+///
+/// `true` for PNG, TIFF, WebP and OpenEXR files.
+pub fn has_alpha_channel(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| {
+        ALPHA_EXTENSIONS
+            .iter()
+            .any(|alpha| extension.eq_ignore_ascii_case(alpha))
+    })
+}
+
+/// Turns a linear color into a pixel of a floating-point image, without encoding or clamping
+/// highlights. Negative and invalid components, which no format can store, become zero.
+///
+/// # Arguments
+///
+/// * `color` - The linear color.
+///
+/// # Returns
+///
+/// The red, green and blue components of the pixel.
+pub fn to_float_pixel(color: Vector3) -> image::Rgb<f32> {
+    image::Rgb([color.x, color.y, color.z].map(|component| component.max(0.0) as f32))
+}
+
+/// Saves a buffer of linear colors to an image file, as floats for OpenEXR and Radiance HDR
+/// files and through an output transform for formats storing bytes.
+///
+/// # Arguments
+///
+/// * `pixels` - The colors of the image, stored row by row starting from the top-left pixel.
+/// * `width` - The width of the image in pixels.
+/// * `height` - The height of the image in pixels.
+/// * `transform` - The output transform encoding the colors of byte formats.
+/// * `path` - The path of the image file, whose extension gives the format.
+///
+/// # Returns
+///
+/// The result of writing the file.
+pub fn save_image(
+    pixels: &[Vector3],
+    width: u32,
+    height: u32,
+    transform: &OutputTransform,
+    path: &Path,
+) -> image::ImageResult<()> {
+    let _span = tracing::debug_span!("write_image", path = %path.display()).entered();
+    let pixel = |x: u32, y: u32| pixels[(y * width + x) as usize];
+
+    if is_high_dynamic_range(path) {
+        image::Rgb32FImage::from_fn(width, height, |x, y| to_float_pixel(pixel(x, y))).save(path)
+    } else {
+        image::ImageBuffer::from_fn(width, height, |x, y| transform.apply(pixel(x, y))).save(path)
+    }
+}
+
+/// Turns a value into a pixel of an 8-bit image without encoding, for images holding data such as
+/// normals or IDs rather than colors. Components are clamped to [0, 1].
+///
+/// # Arguments
+///
+/// * `value` - The value, with components in [0, 1].
+///
+/// # Returns
+///
+/// The red, green and blue components of the pixel.
+pub fn to_data_pixel(value: Vector3) -> image::Rgb<u8> {
+    image::Rgb(
+        [value.x, value.y, value.z]
+            .map(|component| (component.clamp(0.0, 1.0) * 255.0).round() as u8),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encodings() {
+        assert_eq!(Encoding::parse("sRGB"), Ok(Encoding::Srgb));
+        assert_eq!(Encoding::parse("2.4"), Ok(Encoding::Gamma(2.4)));
+        assert!(Encoding::parse("-1").is_err());
+
+        let gamma = Encoding::default();
+        assert!((gamma.encode(0.5) - 0.5_f64.powf(1.0 / 2.2)).abs() < 1e-12);
+        assert_eq!(gamma.encode(-1.0), 0.0);
+        assert_eq!(gamma.encode(4.0), 1.0);
+
+        let srgb = Encoding::Srgb;
+        assert_eq!(srgb.encode(0.0), 0.0);
+        assert!((srgb.encode(1.0) - 1.0).abs() < 1e-12);
+        assert!((srgb.encode(0.002) - 12.92 * 0.002).abs() < 1e-12);
+        assert!((srgb.encode(0.18) - 0.4614).abs() < 1e-4);
+        // Both pieces of the curve meet at the threshold.
+        let below = srgb.encode(0.0031308 - 1e-9);
+        let above = srgb.encode(0.0031308 + 1e-9);
+        assert!((below - above).abs() < 1e-6);
+
+        // Decoding undoes the encoding along both curves.
+        for encoding in [gamma, srgb] {
+            for linear in [0.0, 0.001, 0.18, 0.5, 1.0] {
+                assert!((encoding.decode(encoding.encode(linear)) - linear).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn test_response_curves() {
+        let cube = "TITLE \"invert red\"\n# comment\nLUT_1D_SIZE 3\n\n1.0 0.0 0.0\n0.5 0.5 0.25\n0.0 1.0 0.5\n";
+        let curves = ResponseCurves::parse(cube).unwrap();
+        let color = curves.apply(Vector3::new(0.25, 0.75, 1.0));
+        assert!((color.x - 0.75).abs() < 1e-12);
+        assert!((color.y - 0.75).abs() < 1e-12);
+        assert!((color.z - 0.5).abs() < 1e-12);
+
+        assert!(ResponseCurves::parse("LUT_1D_SIZE 3\n0 0 0\n1 1 1\n").is_err());
+        assert!(ResponseCurves::parse("LUT_3D_SIZE 2\n").is_err());
+        assert!(ResponseCurves::parse("LUT_1D_SIZE 2\n0 0\n1 1 1\n").is_err());
+        let domain = |min: &str, max: &str| {
+            ResponseCurves::parse(&format!(
+                "DOMAIN_MIN {}\nDOMAIN_MAX {}\nLUT_1D_SIZE 2\n0 0 0\n1 1 1\n",
+                min, max
+            ))
+        };
+        assert!(domain("0 0 0", "2 2 2").is_ok());
+        assert!(domain("0 0.5 0", "1 0.5 1").is_err());
+        assert!(domain("0 0 1", "1 1 0").is_err());
+
+        let transform = OutputTransform::new(Encoding::Gamma(1.0)).with_curves(curves);
+        assert_eq!(
+            transform.apply(Vector3::new(0.0, 0.0, 0.0)),
+            image::Rgb([255, 0, 0])
+        );
+    }
+
+    #[test]
+    fn test_film_stocks() {
+        assert_eq!(FilmStock::parse("Cinema"), Ok(FilmStock::Cinema));
+        assert!(FilmStock::parse("velvia").is_err());
+        for stock in FilmStock::ALL {
+            assert_eq!(FilmStock::parse(stock.name()), Ok(stock));
+            let curves = stock.curves();
+            // The curves rise from black to white, staying within the output range.
+            let mut previous = Vector3::new(-1.0, -1.0, -1.0);
+            for index in 0..=100 {
+                let color = curves.apply(Vector3::new(1.0, 1.0, 1.0) * (index as f64 / 100.0));
+                assert!(color.x > previous.x && color.y > previous.y && color.z > previous.z);
+                assert!(color.x.min(color.y).min(color.z) >= 0.0 && color.max() <= 1.0);
+                previous = color;
+            }
+        }
+
+        // The S-curve deepens shadows, brightens highlights and keeps the midpoint.
+        let contrast = FilmStock::Contrast.curves();
+        let gray = |value: f64| contrast.apply(Vector3::new(value, value, value)).x;
+        assert!((gray(0.5) - 0.5).abs() < 1e-9);
+        assert!(gray(0.25) < 0.25 && gray(0.75) > 0.75);
+        assert!(gray(0.0).abs() < 1e-12 && (gray(1.0) - 1.0).abs() < 1e-12);
+
+        // The cinema stock turns shadows teal and highlights warm.
+        let cinema = FilmStock::Cinema.curves();
+        let shadow = cinema.apply(Vector3::new(0.1, 0.1, 0.1));
+        let highlight = cinema.apply(Vector3::new(0.9, 0.9, 0.9));
+        assert!(shadow.z > shadow.x && highlight.x > highlight.z);
+
+        let directory = Path::new(".");
+        assert_eq!(
+            ResponseCurves::find("portrait", directory),
+            Ok(FilmStock::Portrait.curves())
+        );
+        assert!(ResponseCurves::find("missing.cube", directory)
+            .unwrap_err()
+            .contains("missing.cube"));
+        assert!(ResponseCurves::find("velvia", directory)
+            .unwrap_err()
+            .contains("contrast, portrait, slide, cinema"));
+    }
+
+    #[test]
+    fn test_high_dynamic_range_output() {
+        assert!(is_high_dynamic_range(Path::new("renders/out.exr")));
+        assert!(is_high_dynamic_range(Path::new("out.HDR")));
+        assert!(!is_high_dynamic_range(Path::new("out.png")));
+        assert!(!is_high_dynamic_range(Path::new("exr")));
+        assert!(has_alpha_channel(Path::new("out.PNG")) && has_alpha_channel(Path::new("a.exr")));
+        assert!(!has_alpha_channel(Path::new("out.hdr")) && !has_alpha_channel(Path::new("a.jpg")));
+
+        assert_eq!(
+            to_float_pixel(Vector3::new(12.5, -1.0, f64::NAN)),
+            image::Rgb([12.5, 0.0, 0.0])
+        );
+        assert_eq!(
+            to_data_pixel(Vector3::new(0.5, -1.0, 2.0)),
+            image::Rgb([128, 0, 255])
+        );
+
+        // Highlights brighter than white survive a round trip through an EXR file.
+        let path = std::env::temp_dir().join("raytracer_output_test.exr");
+        let image = image::Rgb32FImage::from_fn(2, 1, |x, _| {
+            to_float_pixel(Vector3::new(8.0 * x as f64, 0.25, 1.0))
+        });
+        image.save(&path).unwrap();
+        let loaded = image::open(&path).unwrap().into_rgb32f();
+        let _ = fs::remove_file(&path);
+        assert_eq!(loaded.get_pixel(1, 0), &image::Rgb([8.0, 0.25, 1.0]));
+    }
+}
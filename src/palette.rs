@@ -0,0 +1,408 @@
+//! Seeded generators of coherent random colors and materials, so procedural scenes draw from an
+//! art-directed palette instead of uniformly random albedos.
+
+use crate::material::{Dielectric, Lambertian, Material, Metal};
+use crate::vector3::Vector3;
+use std::sync::Arc;
+
+/// The gamma with which palette colors, chosen as they should appear in the image, are decoded
+/// into linear albedos. It matches the default output encoding.
+const DISPLAY_GAMMA: f64 = 2.2;
+
+/// Converts a color from hue, saturation and value into red, green and blue.
+///
+/// # Arguments
+///
+/// * `hue` - The hue in degrees, wrapped into [0, 360).
+/// * `saturation` - The saturation, between 0 and 1.
+/// * `value` - The value, between 0 and 1.
+///
+/// # Returns
+///
+/// The color as a `Vector3` with components between 0 and 1.
+pub fn hsv_to_rgb(hue: f64, saturation: f64, value: f64) -> Vector3 {
+    let hue = hue.rem_euclid(360.0) / 60.0;
+    let chroma = value * saturation;
+    let x = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+    let (r, g, b) = match hue as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let m = value - chroma;
+    Vector3::new(r + m, g + m, b + m)
+}
+
+/// The rule deciding which hues, relative to the base hue, a palette draws from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Harmony {
+    /// Hues close to the base hue.
+    #[default]
+    Analogous,
+    /// The base hue and its opposite on the color wheel.
+    Complementary,
+    /// Three hues evenly spaced around the color wheel.
+    Triadic,
+}
+
+impl Harmony {
+    /// Returns the offsets of the hues of the harmony from the base hue.
+    ///
+    /// # Returns
+    ///
+    /// The offsets in degrees.
+    fn offsets(self) -> &'static [f64] {
+        match self {
+            Harmony::Analogous => &[0.0],
+            Harmony::Complementary => &[0.0, 180.0],
+            Harmony::Triadic => &[0.0, 120.0, 240.0],
+        }
+    }
+}
+
+/// Represents a seeded source of colors following a color harmony, with bounded saturation and
+/// value.
+#[derive(Debug, Clone)]
+pub struct Palette {
+    /// The base hue in degrees.
+    hue: f64,
+    /// The harmony choosing the hues drawn from.
+    harmony: Harmony,
+    /// The largest difference in degrees between a color and the hue it is drawn around.
+    spread: f64,
+    /// The range of the saturation of the colors.
+    saturation: (f64, f64),
+    /// The range of the value of the colors.
+    value: (f64, f64),
+    /// The random generator, seeded so a palette always gives the same colors.
+    rng: fastrand::Rng,
+}
+
+impl Palette {
+    /// Creates a new analogous `Palette` around a base hue derived from the seed.
+    ///
+    /// # Arguments
+    ///
+    /// * `seed` - The seed of the random generator.
+    ///
+    /// # Returns
+    ///
+    /// A new `Palette` instance.
+    pub fn new(seed: u64) -> Palette {
+        let mut rng = fastrand::Rng::with_seed(seed);
+        Palette {
+            hue: rng.f64() * 360.0,
+            harmony: Harmony::default(),
+            spread: 30.0,
+            saturation: (0.4, 0.8),
+            value: (0.5, 0.9),
+            rng,
+        }
+    }
+
+    /// Sets the base hue.
+    ///
+    /// # Arguments
+    ///
+    /// * `hue` - The base hue in degrees.
+    ///
+    /// # Returns
+    ///
+    /// The `Palette` with the given base hue.
+    pub fn with_hue(mut self, hue: f64) -> Palette {
+        self.hue = hue;
+        self
+    }
+
+    /// Sets the harmony choosing the hues drawn from.
+    ///
+    /// # Arguments
+    ///
+    /// * `harmony` - The harmony.
+    ///
+    /// # Returns
+    ///
+    /// The `Palette` with the given harmony.
+    pub fn with_harmony(mut self, harmony: Harmony) -> Palette {
+        self.harmony = harmony;
+        self
+    }
+
+    /// Sets how far colors may stray from the hues of the harmony.
+    ///
+    /// # Arguments
+    ///
+    /// * `spread` - The largest difference in degrees between a color and its hue.
+    ///
+    /// # Returns
+    ///
+    /// The `Palette` with the given spread.
+    pub fn with_spread(mut self, spread: f64) -> Palette {
+        self.spread = spread;
+        self
+    }
+
+    /// Sets the range of the saturation of the colors.
+    ///
+    /// # Arguments
+    ///
+    /// * `min` - The smallest saturation, between 0 and 1.
+    /// * `max` - The largest saturation, between 0 and 1.
+    ///
+    /// # Returns
+    ///
+    /// The `Palette` with the given saturation range.
+    pub fn with_saturation(mut self, min: f64, max: f64) -> Palette {
+        self.saturation = (min, max);
+        self
+    }
+
+    /// Sets the range of the value of the colors.
+    ///
+    /// # Arguments
+    ///
+    /// * `min` - The smallest value, between 0 and 1.
+    /// * `max` - The largest value, between 0 and 1.
+    ///
+    /// # Returns
+    ///
+    /// The `Palette` with the given value range.
+    pub fn with_value(mut self, min: f64, max: f64) -> Palette {
+        self.value = (min, max);
+        self
+    }
+
+    /// Returns a uniform random number from a range.
+    ///
+    /// # Arguments
+    ///
+    /// * `range` - The bounds of the range.
+    ///
+    /// # Returns
+    ///
+    /// The random number.
+    fn uniform(&mut self, range: (f64, f64)) -> f64 {
+        range.0 + self.rng.f64() * (range.1 - range.0)
+    }
+
+    /// Draws the next color of the palette, as it should appear in the image.
+    ///
+    /// # Returns
+    ///
+    /// The display color as a `Vector3` with components between 0 and 1.
+    pub fn next_display_color(&mut self) -> Vector3 {
+        let offsets = self.harmony.offsets();
+        let offset = offsets[self.rng.usize(..offsets.len())];
+        let hue = self.hue + offset + self.uniform((-self.spread, self.spread));
+        let saturation = self.uniform(self.saturation).clamp(0.0, 1.0);
+        let value = self.uniform(self.value).clamp(0.0, 1.0);
+        hsv_to_rgb(hue, saturation, value)
+    }
+
+    /// Draws the next color of the palette as a linear albedo, decoded so that it appears as
+    /// chosen in the image.
+    ///
+    /// # Returns
+    ///
+    /// The linear color as a `Vector3`.
+    pub fn next_color(&mut self) -> Vector3 {
+        let color = self.next_display_color();
+        Vector3::new(
+            color.x.powf(DISPLAY_GAMMA),
+            color.y.powf(DISPLAY_GAMMA),
+            color.z.powf(DISPLAY_GAMMA),
+        )
+    }
+}
+
+/// Represents a seeded source of materials mixing diffuse, metal and dielectric surfaces in fixed
+/// proportions, colored from a palette.
+#[derive(Debug, Clone)]
+pub struct MaterialGenerator {
+    /// The palette coloring the materials, whose generator also picks the kind of each material.
+    palette: Palette,
+    /// The relative weights of diffuse, metal and dielectric materials.
+    weights: [f64; 3],
+    /// The largest fuzziness of metals.
+    max_fuzz: f64,
+    /// The refraction index of dielectrics.
+    refraction_index: f64,
+}
+
+impl MaterialGenerator {
+    /// Creates a new `MaterialGenerator` drawing 80% diffuse, 15% metal and 5% glass materials.
+    ///
+    /// # Arguments
+    ///
+    /// * `palette` - The palette coloring the materials.
+    ///
+    /// # Returns
+    ///
+    /// A new `MaterialGenerator` instance.
+    pub fn new(palette: Palette) -> MaterialGenerator {
+        MaterialGenerator {
+            palette,
+            weights: [0.8, 0.15, 0.05],
+            max_fuzz: 0.5,
+            refraction_index: 1.5,
+        }
+    }
+
+    /// Sets the proportions of the kinds of materials. The weights are relative and do not need to
+    /// sum to one.
+    ///
+    /// # Arguments
+    ///
+    /// * `diffuse` - The weight of diffuse materials.
+    /// * `metal` - The weight of metals.
+    /// * `dielectric` - The weight of dielectrics.
+    ///
+    /// # Returns
+    ///
+    /// The `MaterialGenerator` with the given proportions.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a weight is negative or all weights are zero.
+    pub fn with_ratios(mut self, diffuse: f64, metal: f64, dielectric: f64) -> MaterialGenerator {
+        assert!(
+            diffuse >= 0.0 && metal >= 0.0 && dielectric >= 0.0,
+            "material weights must not be negative"
+        );
+        assert!(
+            diffuse + metal + dielectric > 0.0,
+            "at least one material weight must be positive"
+        );
+        self.weights = [diffuse, metal, dielectric];
+        self
+    }
+
+    /// Sets the largest fuzziness of metals, drawn uniformly up to it.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_fuzz` - The largest fuzziness.
+    ///
+    /// # Returns
+    ///
+    /// The `MaterialGenerator` with the given fuzziness.
+    pub fn with_max_fuzz(mut self, max_fuzz: f64) -> MaterialGenerator {
+        self.max_fuzz = max_fuzz;
+        self
+    }
+
+    /// Sets the refraction index of dielectrics.
+    ///
+    /// # Arguments
+    ///
+    /// * `refraction_index` - The refraction index.
+    ///
+    /// # Returns
+    ///
+    /// The `MaterialGenerator` with the given refraction index.
+    pub fn with_refraction_index(mut self, refraction_index: f64) -> MaterialGenerator {
+        self.refraction_index = refraction_index;
+        self
+    }
+
+    /// Draws the next material. Diffuse materials take a palette color, and metals take a palette
+    /// color brightened halfway to white, as metals reflect most light.
+    ///
+    /// # Returns
+    ///
+    /// The material.
+    pub fn next_material(&mut self) -> Arc<dyn Material> {
+        let total: f64 = self.weights.iter().sum();
+        let choice = self.palette.rng.f64() * total;
+
+        if choice < self.weights[0] {
+            Arc::new(Lambertian::new(self.palette.next_color()))
+        } else if choice < self.weights[0] + self.weights[1] {
+            let albedo = 0.5 * (self.palette.next_color() + Vector3::new(1.0, 1.0, 1.0));
+            let fuzz = self.palette.rng.f64() * self.max_fuzz;
+            Arc::new(Metal::new(albedo, fuzz))
+        } else {
+            Arc::new(Dielectric::new(self.refraction_index))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hsv_to_rgb() {
+        let close = |a: Vector3, b: Vector3| (a - b).length() < 1e-12;
+        assert!(close(
+            hsv_to_rgb(0.0, 1.0, 1.0),
+            Vector3::new(1.0, 0.0, 0.0)
+        ));
+        assert!(close(
+            hsv_to_rgb(120.0, 1.0, 1.0),
+            Vector3::new(0.0, 1.0, 0.0)
+        ));
+        assert!(close(
+            hsv_to_rgb(-120.0, 1.0, 1.0),
+            Vector3::new(0.0, 0.0, 1.0)
+        ));
+        assert!(close(
+            hsv_to_rgb(60.0, 0.5, 0.8),
+            Vector3::new(0.8, 0.8, 0.4)
+        ));
+        assert!(close(
+            hsv_to_rgb(200.0, 0.0, 0.3),
+            Vector3::new(0.3, 0.3, 0.3)
+        ));
+    }
+
+    #[test]
+    fn test_palette_stays_in_range() {
+        let mut palette = Palette::new(5)
+            .with_hue(120.0)
+            .with_spread(10.0)
+            .with_saturation(0.5, 0.6)
+            .with_value(0.7, 0.8);
+        for _ in 0..200 {
+            let color = palette.next_display_color();
+            let max = color.x.max(color.y).max(color.z);
+            let min = color.x.min(color.y).min(color.z);
+            // Green dominates for hues near 120 degrees.
+            assert_eq!(max, color.y);
+            assert!((0.7..=0.8).contains(&max));
+            assert!((0.5..=0.6).contains(&((max - min) / max)));
+        }
+
+        let colors = |seed| {
+            let mut palette = Palette::new(seed).with_harmony(Harmony::Triadic);
+            (0..10).map(|_| palette.next_color()).collect::<Vec<_>>()
+        };
+        assert_eq!(colors(3), colors(3));
+        assert_ne!(colors(3), colors(4));
+    }
+
+    #[test]
+    fn test_material_ratios() {
+        let mut generator = MaterialGenerator::new(Palette::new(1)).with_ratios(2.0, 1.0, 1.0);
+        let mut counts = [0; 3];
+        let samples = 4000;
+        for _ in 0..samples {
+            let index = match generator.next_material().name() {
+                "Lambertian" => 0,
+                "Metal" => 1,
+                "Dielectric" => 2,
+                name => panic!("unexpected material {}", name),
+            };
+            counts[index] += 1;
+        }
+        for (count, expected) in counts.iter().zip([0.5, 0.25, 0.25]) {
+            assert!((*count as f64 / samples as f64 - expected).abs() < 0.03);
+        }
+
+        let mut glass = MaterialGenerator::new(Palette::new(1)).with_ratios(0.0, 0.0, 1.0);
+        assert_eq!(glass.next_material().name(), "Dielectric");
+    }
+}
@@ -0,0 +1,342 @@
+//! Parameter sweeps, rendering the same scene once for every value of a parameter over a range,
+//! such as `--sweep roughness=0..1 --steps 5`, and laying the frames out side by side in a labeled
+//! comparison strip. Every frame is rendered with the same seed, so the frames differ only by the
+//! swept parameter and a sweep renders the same strip on every run.
+//!
+//! The number of samples can be swept for any scene. Material parameters and the intensity of
+//! the light are read by the scenes built for studies, such as `material_study`, through
+//! `RenderSettings::parameter`.
+
+use crate::camera::RenderSettings;
+use crate::contact_sheet::{self, ContactSheet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Instant;
+
+/// The number of frames of a sweep by default.
+pub const DEFAULT_STEPS: u32 = 5;
+
+/// A parameter of a render that can be swept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SweepParameter {
+    /// The roughness of the materials of a study scene.
+    Roughness,
+    /// The index of refraction of the transparent materials of a study scene.
+    RefractionIndex,
+    /// The intensity of the lights of a study scene.
+    LightIntensity,
+    /// The number of samples per pixel, for any scene.
+    Samples,
+}
+
+impl SweepParameter {
+    /// The parameters, by the names given on the command line.
+    const NAMES: [(&'static str, SweepParameter); 4] = [
+        ("roughness", SweepParameter::Roughness),
+        ("ior", SweepParameter::RefractionIndex),
+        ("light", SweepParameter::LightIntensity),
+        ("samples", SweepParameter::Samples),
+    ];
+
+    /// Returns the name of the parameter given on the command line.
+    pub fn name(self) -> &'static str {
+        Self::NAMES
+            .iter()
+            .find(|(_, parameter)| *parameter == self)
+            .map(|(name, _)| *name)
+            .expect("every parameter has a name")
+    }
+}
+
+impl FromStr for SweepParameter {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<SweepParameter, String> {
+        Self::NAMES
+            .iter()
+            .find(|(known, _)| *known == name)
+            .map(|(_, parameter)| *parameter)
+            .ok_or_else(|| {
+                let names: Vec<&str> = Self::NAMES.iter().map(|(name, _)| *name).collect();
+                format!(
+                    "unknown parameter `{}`, expected one of {}",
+                    name,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+impl fmt::Display for SweepParameter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Represents a sweep of a parameter over evenly spaced values of a range.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sweep {
+    /// The swept parameter.
+    pub parameter: SweepParameter,
+    /// The value of the first frame.
+    pub start: f64,
+    /// The value of the last frame.
+    pub end: f64,
+    /// The number of frames.
+    pub steps: u32,
+}
+
+impl Sweep {
+    /// Creates a new sweep of a parameter.
+    ///
+    /// # Arguments
+    ///
+    /// * `parameter` - The swept parameter.
+    /// * `start` - The value of the first frame.
+    /// * `end` - The value of the last frame, which may be lower than the first.
+    ///
+    /// # Returns
+    ///
+    /// A new `Sweep` instance with five frames.
+    pub fn new(parameter: SweepParameter, start: f64, end: f64) -> Sweep {
+        Sweep {
+            parameter,
+            start,
+            end,
+            steps: DEFAULT_STEPS,
+        }
+    }
+
+    /// Sets the number of frames of the sweep.
+    ///
+    /// # Arguments
+    ///
+    /// * `steps` - The number of frames, at least one.
+    ///
+    /// # Returns
+    ///
+    /// The updated `Sweep` instance.
+    pub fn with_steps(mut self, steps: u32) -> Sweep {
+        self.steps = steps.max(1);
+        self
+    }
+
+    /// Parses a sweep written as `parameter=start..end`, such as `roughness=0..1`.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The text of the sweep.
+    ///
+    /// # Returns
+    ///
+    /// The parsed `Sweep` with five frames, or a description of why the text is invalid.
+    pub fn parse(text: &str) -> Result<Sweep, String> {
+        let invalid = || {
+            format!(
+                "expected a parameter and a range such as `roughness=0..1`, found `{}`",
+                text
+            )
+        };
+        let (name, range) = text.split_once('=').ok_or_else(invalid)?;
+        let parameter: SweepParameter = name.trim().parse()?;
+        let (start, end) = range.split_once("..").ok_or_else(invalid)?;
+        let start: f64 = start.trim().parse().map_err(|_| invalid())?;
+        let end: f64 = end.trim().parse().map_err(|_| invalid())?;
+        if !start.is_finite() || !end.is_finite() {
+            return Err(invalid());
+        }
+        if parameter == SweepParameter::Samples && start.min(end) < 1.0 {
+            return Err(format!(
+                "cannot render fewer than one sample, found `{}`",
+                text
+            ));
+        }
+        Ok(Sweep::new(parameter, start, end))
+    }
+
+    /// Returns the value of the parameter in every frame, evenly spaced from the start to the
+    /// end. Numbers of samples are rounded to whole numbers.
+    pub fn values(&self) -> Vec<f64> {
+        (0..self.steps)
+            .map(|step| {
+                let fraction = match self.steps {
+                    1 => 0.0,
+                    steps => step as f64 / (steps - 1) as f64,
+                };
+                let value = self.start + (self.end - self.start) * fraction;
+                match self.parameter {
+                    SweepParameter::Samples => value.round(),
+                    _ => value,
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the settings of a frame of the sweep.
+    ///
+    /// # Arguments
+    ///
+    /// * `settings` - The settings given on the command line, shared by every frame.
+    /// * `index` - The index of the frame.
+    /// * `value` - The value of the parameter in the frame.
+    /// * `output` - The path of the comparison strip, next to which the frame is saved.
+    ///
+    /// # Returns
+    ///
+    /// The settings rendering the frame with the value of the parameter, the seed of the sweep
+    /// and a report, to the image file of the frame.
+    pub fn frame_settings(
+        &self,
+        settings: &RenderSettings,
+        index: usize,
+        value: f64,
+        output: &Path,
+    ) -> RenderSettings {
+        let mut frame = settings.clone();
+        match self.parameter {
+            SweepParameter::Samples => frame.samples = Some(value as u32),
+            parameter => frame.parameter = Some((parameter, value)),
+        }
+        frame.seed = Some(settings.seed.unwrap_or(0));
+        frame.report = true;
+        frame.preview = false;
+
+        let stem = output
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let extension = output
+            .extension()
+            .map(|extension| extension.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "png".to_string());
+        let name = format!("{}_{}_{}.{}", stem, self.parameter, index, extension);
+        frame.output = Some(output.with_file_name(name).to_string_lossy().into_owned());
+        frame
+    }
+
+    /// Renders every frame of the sweep and saves them side by side in a comparison strip, each
+    /// labeled with the value of the parameter, its number of samples and its render time.
+    ///
+    /// # Arguments
+    ///
+    /// * `render` - Renders the scene with the settings of a frame and saves it to their output.
+    /// * `settings` - The settings given on the command line, shared by every frame. The strip is
+    ///   saved to their output, `sweep.png` by default, and the frames next to it, and their width
+    ///   gives the width of the thumbnails.
+    ///
+    /// # Returns
+    ///
+    /// The path of the strip, or a description of why it could not be saved.
+    pub fn run<F>(&self, render: F, settings: &RenderSettings) -> Result<PathBuf, String>
+    where
+        F: Fn(&RenderSettings),
+    {
+        let output = PathBuf::from(settings.output.as_deref().unwrap_or("sweep.png"));
+        let mut frames = Vec::new();
+        for (index, value) in self.values().into_iter().enumerate() {
+            let frame = self.frame_settings(settings, index, value, &output);
+            let path = PathBuf::from(frame.output.clone().unwrap_or_default());
+            println!("Rendering {} = {}", self.parameter, format_value(value));
+            let now = Instant::now();
+            render(&frame);
+            let mut label = vec![format!("{} = {}", self.parameter, format_value(value))];
+            label.push(
+                contact_sheet::report_line(&path)
+                    .unwrap_or_else(|| format!("{} ms", now.elapsed().as_millis())),
+            );
+            frames.push((path, label));
+        }
+
+        let mut layout = ContactSheet::new().with_columns(self.steps);
+        if let Some(width) = settings.width {
+            layout = layout.with_thumbnail_width(width);
+        }
+        let strip = layout.render_labeled(&frames)?;
+        strip
+            .save(&output)
+            .map_err(|e| format!("failed to save {}: {}", output.display(), e))?;
+        Ok(output)
+    }
+}
+
+/// Formats a value of a parameter with at most three decimals, without trailing zeros.
+///
+/// # Arguments
+///
+/// * `value` - The value.
+fn format_value(value: f64) -> String {
+    let text = format!("{:.3}", value);
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    match text {
+        "-0" => "0".to_string(),
+        _ => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    #[test]
+    fn test_parse_sweeps() {
+        let sweep = Sweep::parse("roughness=0..1").unwrap().with_steps(5);
+        assert_eq!(sweep.parameter, SweepParameter::Roughness);
+        assert_eq!(sweep.values(), vec![0.0, 0.25, 0.5, 0.75, 1.0]);
+        let samples = Sweep::parse("samples=64..1").unwrap().with_steps(4);
+        assert_eq!(samples.values(), vec![64.0, 43.0, 22.0, 1.0]);
+        assert_eq!(
+            Sweep::parse("ior=1.3..1.7").unwrap().with_steps(1).values(),
+            vec![1.3]
+        );
+
+        assert!(Sweep::parse("roughness").is_err());
+        assert!(Sweep::parse("roughness=0").is_err());
+        assert!(Sweep::parse("gloss=0..1").is_err());
+        assert!(Sweep::parse("light=0..inf").is_err());
+        assert!(Sweep::parse("samples=0..16").is_err());
+        assert_eq!(format_value(0.30000000000000004), "0.3");
+        assert_eq!(format_value(2.0), "2");
+        assert_eq!(format_value(-0.0001), "0");
+    }
+
+    #[test]
+    fn test_sweep_renders_a_strip() {
+        let directory = std::env::temp_dir().join(format!("sweep_{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let settings = RenderSettings {
+            width: Some(24),
+            output: Some(directory.join("study.png").to_string_lossy().into_owned()),
+            ..RenderSettings::default()
+        };
+
+        // Renders every frame as a gray level of the swept value, as a scene reading it would.
+        let rendered = RefCell::new(Vec::new());
+        let render = |frame: &RenderSettings| {
+            let value = frame.parameter(SweepParameter::LightIntensity, 0.0);
+            let gray = (value * 255.0) as u8;
+            let path = frame.output.clone().unwrap();
+            image::RgbImage::from_pixel(48, 24, image::Rgb([gray; 3]))
+                .save(&path)
+                .unwrap();
+            rendered.borrow_mut().push((frame.seed, frame.report, path));
+        };
+        let sweep = Sweep::parse("light=0..1").unwrap().with_steps(3);
+        let strip = image::open(sweep.run(render, &settings).unwrap())
+            .unwrap()
+            .to_rgb8();
+
+        let rendered = rendered.into_inner();
+        assert_eq!(rendered.len(), 3);
+        assert!(rendered
+            .iter()
+            .all(|(seed, report, _)| *seed == Some(0) && *report));
+        assert!(rendered[2].2.ends_with("study_light_2.png"));
+        // Three thumbnails 24 pixels wide side by side, from dark to bright.
+        assert_eq!(strip.width(), 8 + 3 * (24 + 8));
+        let brightness = |index: u32| strip.get_pixel(8 + index * 32 + 12, 8 + 6)[0];
+        assert!(brightness(0) < brightness(1) && brightness(1) < brightness(2));
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -0,0 +1,339 @@
+use crate::microfacet::{
+    ggx_dielectric_pdf, ggx_reflection_pdf, sample_ggx_dielectric, sample_ggx_vndf,
+};
+use crate::onb::Onb;
+use crate::utils::reflect;
+use crate::vector3::Vector3;
+use std::f64::consts::PI;
+
+/// A trait for probability density functions over directions, used to importance sample rays.
+pub trait Pdf {
+    /// Evaluates the probability density of a direction.
+    ///
+    /// # Arguments
+    ///
+    /// * `direction` - The direction to evaluate.
+    ///
+    /// # Returns
+    ///
+    /// The probability density with respect to solid angle.
+    fn value(&self, direction: &Vector3) -> f64;
+
+    /// Generates a random direction distributed according to the density.
+    ///
+    /// # Returns
+    ///
+    /// The generated direction.
+    fn generate(&self) -> Vector3;
+}
+
+/// A density uniform over all directions of the unit sphere.
+pub struct SpherePdf;
+
+impl Pdf for SpherePdf {
+    fn value(&self, _direction: &Vector3) -> f64 {
+        1.0 / (4.0 * PI)
+    }
+
+    fn generate(&self) -> Vector3 {
+        Vector3::random_unit_vector()
+    }
+}
+
+/// Evaluates the Henyey-Greenstein phase function, the density of the directions light scatters
+/// to off the particles of a medium.
+///
+/// # Arguments
+///
+/// * `cos_theta` - The cosine of the angle between the direction the light travelled in and the
+///   direction it scatters to.
+/// * `g` - The asymmetry of the scattering, the mean cosine of the scattering angle, between -1
+///   for light scattered back towards where it came from and 1 for light scattered forward.
+///
+/// # Returns
+///
+/// The density of the scattered direction with respect to solid angle.
+pub fn henyey_greenstein(cos_theta: f64, g: f64) -> f64 {
+    let denominator = 1.0 + g * g - 2.0 * g * cos_theta;
+    (1.0 - g * g) / (4.0 * PI * denominator * denominator.sqrt())
+}
+
+/// A density of the directions light scatters to off the particles of an anisotropic medium,
+/// following the Henyey-Greenstein phase function.
+pub struct HenyeyGreensteinPdf {
+    /// The basis built around the direction the light travelled in.
+    onb: Onb,
+    /// The asymmetry of the scattering, between -1 and 1.
+    g: f64,
+}
+
+impl HenyeyGreensteinPdf {
+    /// Creates a new `HenyeyGreensteinPdf`.
+    ///
+    /// # Arguments
+    ///
+    /// * `direction` - The direction the light travelled in before scattering.
+    /// * `g` - The asymmetry of the scattering, negative for back-scattering media and positive
+    ///   for forward-scattering ones.
+    ///
+    /// # Returns
+    ///
+    /// A new `HenyeyGreensteinPdf` instance.
+    pub fn new(direction: &Vector3, g: f64) -> HenyeyGreensteinPdf {
+        HenyeyGreensteinPdf {
+            onb: Onb::new(&direction.normalize()),
+            g,
+        }
+    }
+}
+
+impl Pdf for HenyeyGreensteinPdf {
+    fn value(&self, direction: &Vector3) -> f64 {
+        henyey_greenstein(direction.normalize().dot(&self.onb.w), self.g)
+    }
+
+    fn generate(&self) -> Vector3 {
+        // Inverts the cumulative distribution of the cosine of the scattering angle, which is
+        // uniform for an isotropic medium.
+        let (u, v) = (fastrand::f64(), fastrand::f64());
+        let g = self.g;
+        let cos_theta = if g.abs() < 1e-3 {
+            1.0 - 2.0 * u
+        } else {
+            let ratio = (1.0 - g * g) / (1.0 - g + 2.0 * g * u);
+            ((1.0 + g * g - ratio * ratio) / (2.0 * g)).clamp(-1.0, 1.0)
+        };
+        let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+        let phi = 2.0 * PI * v;
+        self.onb.local_to_world(&Vector3::new(
+            sin_theta * phi.cos(),
+            sin_theta * phi.sin(),
+            cos_theta,
+        ))
+    }
+}
+
+/// A density proportional to the cosine of the angle with a normal.
+pub struct CosinePdf {
+    /// The basis built around the normal.
+    onb: Onb,
+}
+
+impl CosinePdf {
+    /// Creates a new `CosinePdf` around the given normal.
+    ///
+    /// # Arguments
+    ///
+    /// * `normal` - The normal of the surface.
+    ///
+    /// # Returns
+    ///
+    /// A new `CosinePdf` instance.
+    pub fn new(normal: &Vector3) -> CosinePdf {
+        CosinePdf {
+            onb: Onb::new(normal),
+        }
+    }
+}
+
+impl Pdf for CosinePdf {
+    fn value(&self, direction: &Vector3) -> f64 {
+        let cosine = direction.normalize().dot(&self.onb.w);
+        (cosine / PI).max(0.0)
+    }
+
+    fn generate(&self) -> Vector3 {
+        self.onb.local_to_world(&Vector3::random_cosine_direction())
+    }
+}
+
+/// A density of directions reflected off GGX microfacets visible from a viewing direction.
+pub struct GgxPdf {
+    /// The basis built around the surface normal.
+    onb: Onb,
+    /// The direction towards the viewer in the local coordinates of the basis.
+    wo: Vector3,
+    /// The `alpha` parameter of the GGX distribution.
+    alpha: f64,
+}
+
+impl GgxPdf {
+    /// Creates a new `GgxPdf`.
+    ///
+    /// # Arguments
+    ///
+    /// * `normal` - The normal of the surface.
+    /// * `wo` - The direction towards the viewer, pointing away from the surface.
+    /// * `alpha` - The `alpha` parameter of the GGX distribution.
+    ///
+    /// # Returns
+    ///
+    /// A new `GgxPdf` instance.
+    pub fn new(normal: &Vector3, wo: &Vector3, alpha: f64) -> GgxPdf {
+        let onb = Onb::new(normal);
+        GgxPdf {
+            wo: onb.world_to_local(&wo.normalize()),
+            onb,
+            alpha,
+        }
+    }
+}
+
+impl Pdf for GgxPdf {
+    fn value(&self, direction: &Vector3) -> f64 {
+        let wi = self.onb.world_to_local(&direction.normalize());
+        ggx_reflection_pdf(&self.wo, &wi, self.alpha)
+    }
+
+    fn generate(&self) -> Vector3 {
+        let h = sample_ggx_vndf(&self.wo, self.alpha);
+        self.onb.local_to_world(&reflect(-self.wo, h))
+    }
+}
+
+/// A density of directions reflected off or refracted through the GGX microfacets of a rough
+/// dielectric visible from a viewing direction.
+pub struct GgxDielectricPdf {
+    /// The basis built around the surface normal, on the side of the viewer.
+    onb: Onb,
+    /// The direction towards the viewer in the local coordinates of the basis.
+    wo: Vector3,
+    /// The `alpha` parameter of the GGX distribution.
+    alpha: f64,
+    /// The index of refraction behind the surface divided by the index on the side of the viewer.
+    eta: f64,
+}
+
+impl GgxDielectricPdf {
+    /// Creates a new `GgxDielectricPdf`.
+    ///
+    /// # Arguments
+    ///
+    /// * `normal` - The normal of the surface, facing the viewer.
+    /// * `wo` - The direction towards the viewer, pointing away from the surface.
+    /// * `alpha` - The `alpha` parameter of the GGX distribution.
+    /// * `eta` - The index of refraction behind the surface divided by the index on the side of
+    ///   the viewer.
+    ///
+    /// # Returns
+    ///
+    /// A new `GgxDielectricPdf` instance.
+    pub fn new(normal: &Vector3, wo: &Vector3, alpha: f64, eta: f64) -> GgxDielectricPdf {
+        let onb = Onb::new(normal);
+        GgxDielectricPdf {
+            wo: onb.world_to_local(&wo.normalize()),
+            onb,
+            alpha,
+            eta,
+        }
+    }
+}
+
+impl Pdf for GgxDielectricPdf {
+    fn value(&self, direction: &Vector3) -> f64 {
+        let wi = self.onb.world_to_local(&direction.normalize());
+        ggx_dielectric_pdf(&self.wo, &wi, self.alpha, self.eta)
+    }
+
+    /// Generates a scattered direction. Samples lost to the wrong side of the surface are sent
+    /// along the surface instead, where the density is zero, so they carry no light.
+    fn generate(&self) -> Vector3 {
+        let wi = sample_ggx_dielectric(&self.wo, self.alpha, self.eta)
+            .unwrap_or(Vector3::new(1.0, 0.0, 0.0));
+        self.onb.local_to_world(&wi)
+    }
+}
+
+/// An equal mixture of two densities, used to combine sampling of the material with sampling of
+/// the lights.
+pub struct MixturePdf<'a> {
+    /// The first density of the mixture.
+    first: &'a dyn Pdf,
+    /// The second density of the mixture.
+    second: &'a dyn Pdf,
+}
+
+impl<'a> MixturePdf<'a> {
+    /// Creates a new `MixturePdf` choosing each density with equal probability.
+    ///
+    /// # Arguments
+    ///
+    /// * `first` - The first density of the mixture.
+    /// * `second` - The second density of the mixture.
+    ///
+    /// # Returns
+    ///
+    /// A new `MixturePdf` instance.
+    pub fn new(first: &'a dyn Pdf, second: &'a dyn Pdf) -> MixturePdf<'a> {
+        MixturePdf { first, second }
+    }
+}
+
+impl Pdf for MixturePdf<'_> {
+    fn value(&self, direction: &Vector3) -> f64 {
+        0.5 * self.first.value(direction) + 0.5 * self.second.value(direction)
+    }
+
+    fn generate(&self) -> Vector3 {
+        if fastrand::bool() {
+            self.first.generate()
+        } else {
+            self.second.generate()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cosine_pdf_generates_upper_hemisphere() {
+        let normal = Vector3::new(0.0, 1.0, 0.0);
+        let pdf = CosinePdf::new(&normal);
+        for _ in 0..1000 {
+            let direction = pdf.generate();
+            assert!(direction.dot(&normal) >= 0.0);
+            assert!((direction.length() - 1.0).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_ggx_pdf_integrates_to_at_most_one() {
+        // Directions reflected below the horizon are discarded, so the density of the remaining
+        // directions integrates to slightly less than one.
+        let pdf = GgxPdf::new(
+            &Vector3::new(0.0, 0.0, 1.0),
+            &Vector3::new(0.5, 0.0, 0.8),
+            0.3,
+        );
+        let samples = 200_000;
+        let integral = (0..samples)
+            .map(|_| pdf.value(&Vector3::random_unit_vector()) * 4.0 * PI)
+            .sum::<f64>()
+            / samples as f64;
+        assert!(integral > 0.9 && integral < 1.02, "{}", integral);
+    }
+
+    #[test]
+    fn test_henyey_greenstein_pdf() {
+        let direction = Vector3::new(0.3, -0.4, 0.8).normalize();
+        let samples = 200_000;
+        for g in [-0.6, 0.0, 0.8] {
+            let pdf = HenyeyGreensteinPdf::new(&direction, g);
+            let integral = (0..samples)
+                .map(|_| pdf.value(&Vector3::random_unit_vector()) * 4.0 * PI)
+                .sum::<f64>()
+                / samples as f64;
+            assert!((integral - 1.0).abs() < 0.03, "g {}: {}", g, integral);
+
+            // The mean cosine of the sampled scattering angles is the asymmetry.
+            let mean_cosine = (0..samples)
+                .map(|_| pdf.generate().dot(&direction))
+                .sum::<f64>()
+                / samples as f64;
+            assert!((mean_cosine - g).abs() < 0.01, "g {}: {}", g, mean_cosine);
+        }
+        assert!((henyey_greenstein(0.3, 0.0) - 1.0 / (4.0 * PI)).abs() < 1e-12);
+    }
+}
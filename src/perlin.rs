@@ -0,0 +1,149 @@
+use crate::vector3::Vector3;
+
+/// The number of lattice points along each axis before the noise repeats.
+const POINT_COUNT: usize = 256;
+
+/// Represents a Perlin gradient noise generator.
+#[derive(Debug)]
+pub struct Perlin {
+    /// The random unit gradients at the lattice points.
+    gradients: Vec<Vector3>,
+    /// The permutation of the lattice indices along the x-axis.
+    perm_x: Vec<usize>,
+    /// The permutation of the lattice indices along the y-axis.
+    perm_y: Vec<usize>,
+    /// The permutation of the lattice indices along the z-axis.
+    perm_z: Vec<usize>,
+}
+
+impl Perlin {
+    /// Creates a new `Perlin` noise generator from a seed, so the same seed always gives the same
+    /// noise.
+    ///
+    /// # Arguments
+    ///
+    /// * `seed` - The seed of the random lattice.
+    ///
+    /// # Returns
+    ///
+    /// A new `Perlin` instance.
+    pub fn new(seed: u64) -> Perlin {
+        let mut rng = fastrand::Rng::with_seed(seed);
+        let gradients = (0..POINT_COUNT)
+            .map(|_| {
+                Vector3::new(
+                    rng.f64() * 2.0 - 1.0,
+                    rng.f64() * 2.0 - 1.0,
+                    rng.f64() * 2.0 - 1.0,
+                )
+                .normalize()
+            })
+            .collect();
+
+        let permutation = |rng: &mut fastrand::Rng| {
+            let mut perm: Vec<usize> = (0..POINT_COUNT).collect();
+            rng.shuffle(&mut perm);
+            perm
+        };
+        let perm_x = permutation(&mut rng);
+        let perm_y = permutation(&mut rng);
+        let perm_z = permutation(&mut rng);
+
+        Perlin {
+            gradients,
+            perm_x,
+            perm_y,
+            perm_z,
+        }
+    }
+
+    /// Evaluates the noise at a point.
+    ///
+    /// # Arguments
+    ///
+    /// * `p` - The point in 3D space.
+    ///
+    /// # Returns
+    ///
+    /// The noise value, between -1 and 1.
+    pub fn noise(&self, p: &Vector3) -> f64 {
+        let u = p.x - p.x.floor();
+        let v = p.y - p.y.floor();
+        let w = p.z - p.z.floor();
+
+        let i = p.x.floor() as i64;
+        let j = p.y.floor() as i64;
+        let k = p.z.floor() as i64;
+
+        let mut sum = 0.0;
+        let uu = u * u * (3.0 - 2.0 * u);
+        let vv = v * v * (3.0 - 2.0 * v);
+        let ww = w * w * (3.0 - 2.0 * w);
+
+        for di in 0..2 {
+            for dj in 0..2 {
+                for dk in 0..2 {
+                    let index = self.perm_x[((i + di) & 255) as usize]
+                        ^ self.perm_y[((j + dj) & 255) as usize]
+                        ^ self.perm_z[((k + dk) & 255) as usize];
+                    let weight = Vector3::new(u - di as f64, v - dj as f64, w - dk as f64);
+
+                    let fi = di as f64;
+                    let fj = dj as f64;
+                    let fk = dk as f64;
+                    sum += (fi * uu + (1.0 - fi) * (1.0 - uu))
+                        * (fj * vv + (1.0 - fj) * (1.0 - vv))
+                        * (fk * ww + (1.0 - fk) * (1.0 - ww))
+                        * self.gradients[index].dot(&weight);
+                }
+            }
+        }
+        sum
+    }
+
+    /// Evaluates turbulence, a sum of noise at increasing frequencies and decreasing amplitudes.
+    ///
+    /// # Arguments
+    ///
+    /// * `p` - The point in 3D space.
+    /// * `depth` - The number of octaves to sum.
+    ///
+    /// # Returns
+    ///
+    /// The non-negative turbulence value.
+    pub fn turbulence(&self, p: &Vector3, depth: u32) -> f64 {
+        let mut sum = 0.0;
+        let mut point = *p;
+        let mut weight = 1.0;
+
+        for _ in 0..depth {
+            sum += weight * self.noise(&point);
+            weight *= 0.5;
+            point = point * 2.0;
+        }
+        sum.abs()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_noise_is_deterministic_and_bounded() {
+        let first = Perlin::new(7);
+        let second = Perlin::new(7);
+        for _ in 0..1000 {
+            let p = Vector3::random(-50.0, 50.0);
+            let value = first.noise(&p);
+            assert_eq!(value, second.noise(&p));
+            assert!((-1.0..=1.0).contains(&value), "{}", value);
+        }
+    }
+
+    #[test]
+    fn test_noise_vanishes_on_lattice() {
+        let perlin = Perlin::new(1);
+        assert!(perlin.noise(&Vector3::new(3.0, -2.0, 5.0)).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,120 @@
+//! A live preview of a render in progress. Tiles are written to a shared buffer as they finish,
+//! first with the colors of the pilot pass and then with every sample, and the buffer is shown in
+//! a window when the crate is built with the `preview` feature.
+#![cfg_attr(not(feature = "preview"), allow(dead_code))]
+
+use std::sync::Mutex;
+
+/// The number of times per second the preview window is redrawn.
+#[cfg(feature = "preview")]
+const FRAMES_PER_SECOND: usize = 15;
+
+/// Represents the image shown by the preview, updated by the render threads.
+pub struct PreviewBuffer {
+    /// The width of the image in pixels.
+    width: usize,
+    /// The height of the image in pixels.
+    height: usize,
+    /// The pixels of the image, row by row, packed as `0RGB`.
+    pixels: Mutex<Vec<u32>>,
+}
+
+impl PreviewBuffer {
+    /// Creates a new black `PreviewBuffer`.
+    ///
+    /// # Arguments
+    ///
+    /// * `width` - The width of the image in pixels.
+    /// * `height` - The height of the image in pixels.
+    ///
+    /// # Returns
+    ///
+    /// A new `PreviewBuffer` instance.
+    pub fn new(width: u32, height: u32) -> PreviewBuffer {
+        PreviewBuffer {
+            width: width as usize,
+            height: height as usize,
+            pixels: Mutex::new(vec![0; width as usize * height as usize]),
+        }
+    }
+
+    /// Writes a rectangular block of pixels, such as a finished tile, to the image.
+    ///
+    /// # Arguments
+    ///
+    /// * `x0` - The left column of the block.
+    /// * `y0` - The top row of the block.
+    /// * `width` - The width of the block in pixels.
+    /// * `colors` - The encoded colors of the block, row by row.
+    pub fn write(&self, x0: u32, y0: u32, width: u32, colors: &[image::Rgb<u8>]) {
+        let mut pixels = self.pixels.lock().unwrap_or_else(|e| e.into_inner());
+        for (offset, color) in colors.iter().enumerate() {
+            let x = x0 as usize + offset % width as usize;
+            let y = y0 as usize + offset / width as usize;
+            let [r, g, b] = color.0;
+            pixels[y * self.width + x] = (r as u32) << 16 | (g as u32) << 8 | b as u32;
+        }
+    }
+
+    /// Returns a copy of the pixels of the image, row by row, packed as `0RGB`.
+    pub fn pixels(&self) -> Vec<u32> {
+        self.pixels
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Shows the image in a window, redrawn as it is updated, until the render is finished or the
+    /// window is closed. Closing the window early does not stop the render. If no window can be
+    /// opened, such as on a machine without a display, the render carries on without a preview.
+    ///
+    /// # Arguments
+    ///
+    /// * `title` - The title of the window.
+    /// * `finished` - Tells whether the render is finished.
+    #[cfg(feature = "preview")]
+    pub fn show(&self, title: &str, finished: impl Fn() -> bool) {
+        use minifb::{Key, Window, WindowOptions};
+
+        let options = WindowOptions {
+            resize: true,
+            ..WindowOptions::default()
+        };
+        let mut window = match Window::new(title, self.width, self.height, options) {
+            Ok(window) => window,
+            Err(e) => {
+                eprintln!("Failed to open the preview window: {}", e);
+                return;
+            }
+        };
+        window.set_target_fps(FRAMES_PER_SECOND);
+
+        while window.is_open() && !window.is_key_down(Key::Escape) && !finished() {
+            if let Err(e) = window.update_with_buffer(&self.pixels(), self.width, self.height) {
+                eprintln!("Failed to update the preview window: {}", e);
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_block() {
+        let buffer = PreviewBuffer::new(4, 3);
+        let red = image::Rgb([255, 0, 0]);
+        let gray = image::Rgb([16, 32, 64]);
+        buffer.write(2, 1, 2, &[red, gray, gray, red]);
+
+        let pixels = buffer.pixels();
+        assert_eq!(pixels.len(), 12);
+        assert_eq!(pixels[6], 0xFF0000);
+        assert_eq!(pixels[7], 0x102040);
+        assert_eq!(pixels[10], 0x102040);
+        assert_eq!(pixels[11], 0xFF0000);
+        assert_eq!(pixels.iter().filter(|&&pixel| pixel != 0).count(), 4);
+    }
+}
@@ -0,0 +1,110 @@
+//! Profiling hooks. The renderer is instrumented with `tracing` spans around rendering the image
+//! and its tiles, building and refitting the hierarchies of meshes, denoising and writing images,
+//! which cost next to nothing while nothing subscribes to them, so performance investigations do
+//! not need timers added by hand.
+//!
+//! Builds with the `tracy` feature stream the spans to the Tracy profiler while they render, and
+//! builds with the `flamegraph` feature write them to the file given with `--flamegraph` as folded
+//! stacks, the input of flame graph tools such as `inferno-flamegraph`. Both features also compile
+//! spans around the traversal of the scene by every ray and the shading of every hit, too fine
+//! for other builds.
+
+use std::path::Path;
+
+/// Enters a span lasting until the end of the enclosing block around a hot path of the tracer,
+/// such as the traversal of the scene by a ray, in builds with a profiling feature. Other builds
+/// compile nothing.
+macro_rules! hot_span {
+    ($name:literal) => {
+        #[cfg(any(feature = "tracy", feature = "flamegraph"))]
+        let _span = tracing::trace_span!($name).entered();
+    };
+}
+pub(crate) use hot_span;
+
+/// Keeps the profilers receiving the spans of the renderer, and writes the flame graph when
+/// dropped.
+pub struct Profiler {
+    /// Flushes the folded stacks of the flame graph to its file when dropped.
+    #[cfg(feature = "flamegraph")]
+    _flame: Option<tracing_flame::FlushGuard<std::io::BufWriter<std::fs::File>>>,
+}
+
+impl Profiler {
+    /// Starts sending the spans of the renderer to the profilers the crate was built with.
+    ///
+    /// # Arguments
+    ///
+    /// * `flamegraph` - The path of the file of folded stacks to write, if any. Builds without
+    ///   the `flamegraph` feature warn that none will be written.
+    ///
+    /// # Returns
+    ///
+    /// The running `Profiler`, or a description of why it could not be started.
+    #[cfg(any(feature = "tracy", feature = "flamegraph"))]
+    pub fn start(flamegraph: Option<&Path>) -> Result<Profiler, String> {
+        use tracing_subscriber::layer::SubscriberExt;
+
+        #[cfg(feature = "tracy")]
+        let tracy = Some(tracing_tracy::TracyLayer::default());
+        #[cfg(not(feature = "tracy"))]
+        let tracy: Option<tracing_subscriber::layer::Identity> = None;
+
+        #[cfg(feature = "flamegraph")]
+        let (flame, guard) = match flamegraph {
+            Some(path) => {
+                // Render threads are merged, so every span is drawn once with the time of all
+                // the threads.
+                let (layer, guard) = tracing_flame::FlameLayer::with_file(path)
+                    .map_err(|e| format!("failed to create {}: {}", path.display(), e))?;
+                let layer = layer.with_threads_collapsed(true).with_file_and_line(false);
+                (Some(layer), Some(guard))
+            }
+            None => (None, None),
+        };
+        #[cfg(not(feature = "flamegraph"))]
+        let flame: Option<tracing_subscriber::layer::Identity> = {
+            warn_without_flamegraph(flamegraph);
+            None
+        };
+
+        let subscriber = tracing_subscriber::registry().with(tracy).with(flame);
+        tracing::subscriber::set_global_default(subscriber).map_err(|e| e.to_string())?;
+        Ok(Profiler {
+            #[cfg(feature = "flamegraph")]
+            _flame: guard,
+        })
+    }
+
+    /// Starts sending the spans of the renderer to the profilers the crate was built with, of
+    /// which there are none in this build.
+    ///
+    /// # Arguments
+    ///
+    /// * `flamegraph` - The path of the file of folded stacks asked for, if any, which cannot be
+    ///   written without the `flamegraph` feature.
+    ///
+    /// # Returns
+    ///
+    /// A `Profiler` doing nothing.
+    #[cfg(not(any(feature = "tracy", feature = "flamegraph")))]
+    pub fn start(flamegraph: Option<&Path>) -> Result<Profiler, String> {
+        warn_without_flamegraph(flamegraph);
+        Ok(Profiler {})
+    }
+}
+
+/// Warns that a flame graph was asked for from a build that cannot write it.
+///
+/// # Arguments
+///
+/// * `flamegraph` - The path of the file of folded stacks asked for, if any.
+#[cfg(not(feature = "flamegraph"))]
+fn warn_without_flamegraph(flamegraph: Option<&Path>) {
+    if let Some(path) = flamegraph {
+        eprintln!(
+            "Built without the `flamegraph` feature, not writing {}",
+            path.display()
+        );
+    }
+}
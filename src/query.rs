@@ -0,0 +1,406 @@
+//! Geometric queries over a built scene that do not render anything, so the intersection code can
+//! serve tools such as lightmap bakers or audio occlusion.
+
+use crate::hit::HitRecord;
+use crate::pdf::{CosinePdf, Pdf};
+use crate::ray::Ray;
+use crate::scene::Scene;
+use crate::vector3::Vector3;
+
+/// The distance kept from the start and the end of query rays, so surfaces touching their end
+/// points are not reported.
+const QUERY_EPSILON: f64 = 1e-6;
+
+/// Describes the closest intersection of a ray with the scene.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RayHit {
+    /// The index of the object, in the order in which objects were added to the scene.
+    pub object: usize,
+    /// The distance from the origin of the ray to the hit point.
+    pub distance: f64,
+    /// The hit point.
+    pub point: Vector3,
+    /// The normal at the hit point, facing the origin of the ray.
+    pub normal: Vector3,
+    /// Whether the ray hit the outside of the object.
+    pub front_face: bool,
+}
+
+/// Describes the point of the scene closest to a query point.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClosestPoint {
+    /// The index of the object, in the order in which objects were added to the scene.
+    pub object: usize,
+    /// The closest point of the surface of the object.
+    pub point: Vector3,
+    /// The distance from the query point to the closest point.
+    pub distance: f64,
+}
+
+impl Scene {
+    /// Finds the closest intersection of a ray with the objects of the scene. Every object is
+    /// considered, whatever its render layer.
+    ///
+    /// # Arguments
+    ///
+    /// * `origin` - The origin of the ray.
+    /// * `direction` - The direction of the ray, which does not need to be normalized.
+    /// * `max_distance` - The largest distance from the origin at which hits are reported.
+    ///
+    /// # Returns
+    ///
+    /// A `RayHit` describing the closest intersection, or `None` if the ray hits nothing.
+    pub fn cast_ray(
+        &self,
+        origin: Vector3,
+        direction: Vector3,
+        max_distance: f64,
+    ) -> Option<RayHit> {
+        let ray = Ray::new(origin, direction);
+        let (object, record) = self.hit_object(&ray, (QUERY_EPSILON, max_distance))?;
+        Some(RayHit {
+            object,
+            distance: record.t,
+            point: record.poz,
+            normal: record.normal,
+            front_face: record.front_face,
+        })
+    }
+
+    /// Checks whether the segment between two points is blocked by an object of the scene.
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - The start of the segment.
+    /// * `to` - The end of the segment.
+    ///
+    /// # Returns
+    ///
+    /// `true` if an object lies between the two points.
+    pub fn occluded(&self, from: Vector3, to: Vector3) -> bool {
+        let offset = to - from;
+        let distance = offset.length();
+        if distance <= 2.0 * QUERY_EPSILON {
+            return false;
+        }
+        self.cast_ray(from, offset, distance - QUERY_EPSILON)
+            .is_some()
+    }
+
+    /// Finds the point of the surfaces of the scene closest to a query point. Objects that do not
+    /// support the query, such as billboards, are skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The query point.
+    ///
+    /// # Returns
+    ///
+    /// A `ClosestPoint` describing the closest surface point, or `None` if no object supports the
+    /// query.
+    pub fn closest_point(&self, point: Vector3) -> Option<ClosestPoint> {
+        self.objects()
+            .enumerate()
+            .filter_map(|(object, hittable)| {
+                hittable.closest_point(&point).map(|closest| ClosestPoint {
+                    object,
+                    point: closest,
+                    distance: (closest - point).length(),
+                })
+            })
+            .min_by(|a, b| a.distance.total_cmp(&b.distance))
+    }
+
+    /// Approximates the signed distance from a point to the surfaces of the scene: the distance to
+    /// the closest surface point, negative when the point lies inside the object owning it.
+    /// Inside and outside are told apart by the face of the surface seen from the point, so the
+    /// sign is only meaningful for closed objects.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The query point.
+    ///
+    /// # Returns
+    ///
+    /// The signed distance, or `None` if no object supports closest point queries.
+    pub fn signed_distance(&self, point: Vector3) -> Option<f64> {
+        let closest = self.closest_point(point)?;
+        if closest.distance <= QUERY_EPSILON {
+            return Some(0.0);
+        }
+
+        let inside = self
+            .cast_ray(
+                point,
+                closest.point - point,
+                closest.distance + QUERY_EPSILON,
+            )
+            .is_some_and(|hit| !hit.front_face);
+        Some(if inside {
+            -closest.distance
+        } else {
+            closest.distance
+        })
+    }
+
+    /// Estimates how much of the hemisphere above a surface point is blocked by nearby objects,
+    /// weighting directions by their cosine with the normal as diffuse lighting does.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The surface point.
+    /// * `normal` - The normal of the surface at the point.
+    /// * `samples` - The number of directions traced.
+    /// * `max_distance` - The distance beyond which objects do not count as blocking.
+    ///
+    /// # Returns
+    ///
+    /// The occluded fraction of the hemisphere, from 0 for an open point to 1 for a fully
+    /// enclosed one.
+    pub fn ambient_occlusion(
+        &self,
+        point: Vector3,
+        normal: Vector3,
+        samples: u32,
+        max_distance: f64,
+    ) -> f64 {
+        if samples == 0 {
+            return 0.0;
+        }
+        let pdf = CosinePdf::new(&normal);
+        let blocked = (0..samples)
+            .filter(|_| self.cast_ray(point, pdf.generate(), max_distance).is_some())
+            .count();
+        blocked as f64 / samples as f64
+    }
+
+    /// Estimates how sharply the surface bends around a point, by comparing how much of the
+    /// hemisphere above it and of the one below it, inside the object, is blocked nearby. Rays
+    /// leaving the inside of an edge soon cross a neighboring face, while rays leaving a crease
+    /// soon hit the facing surface.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The surface point.
+    /// * `normal` - The normal of the surface at the point, on the side it is seen from.
+    /// * `samples` - The number of directions traced on each side.
+    /// * `radius` - The distance within which the surface is considered, the width of the edges
+    ///   and creases found.
+    ///
+    /// # Returns
+    ///
+    /// The curvature, from -1 in a fully closed crease through 0 on a flat surface to 1 on a
+    /// sharp edge. Open surfaces, having no inside, are never found convex.
+    pub fn curvature(&self, point: Vector3, normal: Vector3, samples: u32, radius: f64) -> f64 {
+        self.ambient_occlusion(point, -normal, samples, radius)
+            - self.ambient_occlusion(point, normal, samples, radius)
+    }
+
+    /// Fills in the occlusion and curvature of a hit point, for materials whose textures read
+    /// them, with the radius and number of samples set by `Scene::set_surface_queries`.
+    ///
+    /// # Arguments
+    ///
+    /// * `record` - The record of the hit point.
+    ///
+    /// # Returns
+    ///
+    /// The record with its occlusion and curvature set.
+    pub fn query_surface<'a>(&self, record: &HitRecord<'a>) -> HitRecord<'a> {
+        let (samples, radius) = self.surface_queries();
+        let occlusion = self.ambient_occlusion(record.poz, record.normal, samples, radius);
+        let inside = self.ambient_occlusion(record.poz, -record.normal, samples, radius);
+        HitRecord {
+            occlusion,
+            curvature: inside - occlusion,
+            ..*record
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hit::Hittable;
+    use crate::material::Lambertian;
+    use crate::shapes::box_quad::BoxQuad;
+    use crate::shapes::quad::Quad;
+    use crate::shapes::sphere::Sphere;
+    use crate::transformation::{RotateY, Translate};
+    use std::sync::Arc;
+
+    fn query_scene() -> Scene {
+        let material = Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5)));
+        let objects: Vec<Box<dyn Hittable>> = vec![
+            Box::new(Sphere::new(
+                Vector3::new(0.0, 0.0, -5.0),
+                1.0,
+                material.clone(),
+            )),
+            Box::new(Quad::new(
+                Vector3::new(-1.0, -2.0, -1.0),
+                Vector3::new(2.0, 0.0, 0.0),
+                Vector3::new(0.0, 0.0, 2.0),
+                material.clone(),
+            )),
+            Box::new(Translate::new(
+                Arc::new(RotateY::new(
+                    Arc::new(BoxQuad::new(
+                        Vector3::new(-1.0, -1.0, -1.0),
+                        Vector3::new(1.0, 1.0, 1.0),
+                        material,
+                    )),
+                    45.0,
+                )),
+                Vector3::new(10.0, 0.0, 0.0),
+            )),
+        ];
+        Scene::new(objects)
+    }
+
+    #[test]
+    fn test_cast_ray() {
+        let scene = query_scene();
+        let origin = Vector3::new(0.0, 0.0, 0.0);
+
+        let hit = scene
+            .cast_ray(origin, Vector3::new(0.0, 0.0, -2.0), f64::INFINITY)
+            .unwrap();
+        assert_eq!(hit.object, 0);
+        assert!((hit.distance - 4.0).abs() < 1e-9);
+        assert!(hit.front_face);
+        assert!((hit.normal - Vector3::new(0.0, 0.0, 1.0)).length() < 1e-9);
+
+        assert!(scene
+            .cast_ray(origin, Vector3::new(0.0, 0.0, -1.0), 3.0)
+            .is_none());
+        assert!(scene
+            .cast_ray(origin, Vector3::new(0.0, 1.0, 0.0), f64::INFINITY)
+            .is_none());
+
+        // The faces of the rotated box meet at 45 degrees in its corner facing negative x.
+        let hit = scene
+            .cast_ray(
+                Vector3::new(0.0, 0.0, 0.3),
+                Vector3::new(1.0, 0.0, 0.0),
+                f64::INFINITY,
+            )
+            .unwrap();
+        assert_eq!(hit.object, 2);
+        assert!((hit.distance - (10.3 - 2.0_f64.sqrt())).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_occluded() {
+        let scene = query_scene();
+        let origin = Vector3::new(0.0, 0.0, 0.0);
+
+        assert!(scene.occluded(origin, Vector3::new(0.0, -3.0, 0.0)));
+        assert!(!scene.occluded(origin, Vector3::new(0.0, 0.0, -3.0)));
+        assert!(scene.occluded(origin, Vector3::new(0.0, 0.0, -7.0)));
+        // A segment ending on a surface is not blocked by it.
+        assert!(!scene.occluded(origin, Vector3::new(0.0, 0.0, -4.0)));
+    }
+
+    #[test]
+    fn test_closest_point() {
+        let scene = query_scene();
+
+        let closest = scene.closest_point(Vector3::new(0.0, 3.0, -5.0)).unwrap();
+        assert_eq!(closest.object, 0);
+        assert!((closest.point - Vector3::new(0.0, 1.0, -5.0)).length() < 1e-9);
+        assert!((closest.distance - 2.0).abs() < 1e-9);
+
+        // Beyond the edge of the quad, the closest point lies on the edge.
+        let closest = scene.closest_point(Vector3::new(-3.0, -2.5, 0.0)).unwrap();
+        assert_eq!(closest.object, 1);
+        assert!((closest.point - Vector3::new(-1.0, -2.0, 0.0)).length() < 1e-9);
+
+        // The corner of the rotated box points towards negative x.
+        let closest = scene.closest_point(Vector3::new(7.0, 0.0, 0.0)).unwrap();
+        assert_eq!(closest.object, 2);
+        assert!((closest.distance - (3.0 - 2.0_f64.sqrt())).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_signed_distance() {
+        let scene = query_scene();
+
+        let outside = scene.signed_distance(Vector3::new(0.0, 0.0, -2.5)).unwrap();
+        assert!((outside - 1.5).abs() < 1e-9);
+        let inside = scene.signed_distance(Vector3::new(0.0, 0.5, -5.0)).unwrap();
+        assert!((inside + 0.5).abs() < 1e-9);
+        let on_surface = scene.signed_distance(Vector3::new(0.0, 0.0, -4.0)).unwrap();
+        assert_eq!(on_surface, 0.0);
+        let inside_box = scene.signed_distance(Vector3::new(10.0, 0.2, 0.0)).unwrap();
+        assert!((inside_box + 0.8).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_ambient_occlusion() {
+        let material = Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5)));
+        let floor = Quad::new(
+            Vector3::new(-10.0, 0.0, -10.0),
+            Vector3::new(0.0, 0.0, 20.0),
+            Vector3::new(20.0, 0.0, 0.0),
+            material.clone(),
+        );
+        let ceiling = Quad::new(
+            Vector3::new(-10.0, 1.0, -10.0),
+            Vector3::new(20.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, 20.0),
+            material,
+        );
+        let scene = Scene::new(vec![Box::new(floor), Box::new(ceiling)]);
+        let point = Vector3::new(0.0, 0.0, 0.0);
+        let up = Vector3::new(0.0, 1.0, 0.0);
+
+        // The ceiling blocks nearly every direction, but only when it is close enough to count.
+        assert!(scene.ambient_occlusion(point, up, 500, f64::INFINITY) > 0.95);
+        assert_eq!(scene.ambient_occlusion(point, up, 500, 0.5), 0.0);
+        assert_eq!(scene.ambient_occlusion(point, -up, 500, f64::INFINITY), 0.0);
+    }
+
+    #[test]
+    fn test_curvature() {
+        let material = Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5)));
+        let floor = Quad::new(
+            Vector3::new(-10.0, 0.0, -10.0),
+            Vector3::new(0.0, 0.0, 20.0),
+            Vector3::new(20.0, 0.0, 0.0),
+            material.clone(),
+        );
+        let block = BoxQuad::new(
+            Vector3::new(-1.0, 0.0, -1.0),
+            Vector3::new(1.0, 2.0, 1.0),
+            material.clone(),
+        );
+        let mut scene = Scene::new(vec![Box::new(floor), Box::new(block)]);
+        let up = Vector3::new(0.0, 1.0, 0.0);
+        let curvature = |point: Vector3| scene.curvature(point, up, 500, 0.2);
+
+        // The middle of the top of the block is flat, its corner is an edge, and the floor
+        // beside the block is a crease.
+        assert_eq!(curvature(Vector3::new(0.0, 2.0, 0.0)), 0.0);
+        assert!(curvature(Vector3::new(0.99, 2.0, 0.99)) > 0.3);
+        assert!(curvature(Vector3::new(1.01, 0.0, 0.0)) < -0.2);
+        // The floor, having no inside, is flat far from the block.
+        assert_eq!(curvature(Vector3::new(5.0, 0.0, 0.0)), 0.0);
+
+        // Queried hit points carry the occlusion and curvature found with the settings of the
+        // scene.
+        scene.set_surface_queries(500, 0.2);
+        let mut record = HitRecord::new(
+            1.0,
+            Vector3::new(1.01, 0.0, 0.0),
+            material.as_ref(),
+            0.0,
+            0.0,
+        );
+        record.normal = up;
+        let queried = scene.query_surface(&record);
+        assert!(queried.occlusion > 0.2);
+        assert!((queried.curvature + queried.occlusion).abs() < 1e-12);
+        assert_eq!(queried.t, record.t);
+    }
+}
@@ -8,6 +8,16 @@ pub struct Ray {
     pub direction: Vector3,
     /// The length of the ray.
     pub length: f64,
+    /// The scene time at which the ray is traced.
+    pub time: f64,
+    /// Whether the ray leaves the camera, as opposed to being scattered or cast towards a light.
+    pub primary: bool,
+    /// The width of the footprint of the ray at its origin, estimated from its differentials, the
+    /// rays through the neighbouring pixels, or 0 if the footprint is not tracked.
+    pub footprint: f64,
+    /// How much the footprint of the ray widens per unit of distance, estimated from its
+    /// differentials, or 0 if the footprint is not tracked.
+    pub spread: f64,
 }
 
 impl Ray {
@@ -22,13 +32,58 @@ impl Ray {
     ///
     /// A new `Ray` instance
     pub fn new(origin: Vector3, direction: Vector3) -> Ray {
+        Ray::with_time(origin, direction, 0.0)
+    }
+
+    /// Creates a new `Ray` with the given origin and direction, traced at the given scene time.
+    ///
+    /// # Arguments
+    ///
+    /// * `origin` - The origin point of the ray.
+    /// * `direction` - The direction vector of the ray.
+    /// * `time` - The scene time at which the ray is traced.
+    ///
+    /// # Returns
+    ///
+    /// A new `Ray` instance
+    pub fn with_time(origin: Vector3, direction: Vector3, time: f64) -> Ray {
         Ray {
             origin,
             direction: direction.normalize(),
             length: direction.length(),
+            time,
+            primary: false,
+            footprint: 0.0,
+            spread: 0.0,
         }
     }
 
+    /// Returns the width of the footprint of the ray at a given distance, the size of the patch
+    /// of surface a hit at that distance stands for.
+    ///
+    /// # Arguments
+    ///
+    /// * `t` - The distance along the ray.
+    ///
+    /// # Returns
+    ///
+    /// The width of the footprint, or 0 if the footprint is not tracked.
+    pub fn footprint_at(&self, t: f64) -> f64 {
+        self.footprint + self.spread * t
+    }
+
+    /// Carries over the footprint of the ray this ray was reflected or refracted from, as if the
+    /// surface between them were flat.
+    ///
+    /// # Arguments
+    ///
+    /// * `incoming` - The ray this ray continues.
+    /// * `t` - The distance along the incoming ray at which it was reflected or refracted.
+    pub fn continue_footprint(&mut self, incoming: &Ray, t: f64) {
+        self.footprint = incoming.footprint_at(t);
+        self.spread = incoming.spread;
+    }
+
     /// Computes the point at a given distance `t` along the ray.
     ///
     /// # Arguments
@@ -61,6 +116,15 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_footprint_at() {
+        let mut ray = Ray::new(Vector3::new(0.0, 0.0, 0.0), Vector3::new(0.0, 0.0, -1.0));
+        assert_eq!(ray.footprint_at(10.0), 0.0);
+        ray.footprint = 0.5;
+        ray.spread = 0.01;
+        assert!((ray.footprint_at(10.0) - 0.6).abs() < 1e-12);
+    }
+
     #[test]
     fn test_point_at() {
         let ray = Ray::new(Vector3::new(1.0, 2.0, 3.0), Vector3::new(1.0, 1.0, 1.0));
@@ -0,0 +1,760 @@
+//! Regression harness that renders small versions of the built-in scenes and compares
+//! statistics of the resulting images against stored reference data.
+//!
+//! Renders are noisy, so references are the mean luminance of image regions, measured once at a
+//! high sample count, and are checked with a relative tolerance.
+//!
+//! Small scenes covering the materials and shapes are also rendered with a fixed seed and compared
+//! with reference images stored in the `references` directory, block by block so that rounding
+//! differences between platforms, which send a few paths elsewhere, are tolerated.
+//! After a change meant to alter the renders, run the ignored `update_reference_images` test.
+
+use crate::camera::Camera;
+use crate::hit::Hittable;
+use crate::material::{Dielectric, Lambertian, Material, Metal, RoughDielectric};
+use crate::material_kind::{Dispatch, MaterialKind};
+use crate::output::{Encoding, OutputTransform};
+use crate::scene::{Scene, DEFAULT_LAYER};
+use crate::scenes::{
+    caustics_world, cornell_box_camera, cornell_box_scene, cornell_box_scene_with,
+};
+use crate::shapes::box_quad::BoxQuad;
+use crate::shapes::cylinder::Cylinder;
+use crate::shapes::quad::Quad;
+use crate::shapes::sphere::Sphere;
+use crate::shapes::torus::Torus;
+use crate::texture::CheckerTexture;
+use crate::utils::background_gradient;
+use crate::vector3::Vector3;
+use image::RgbImage;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// The seed with which reference images are rendered.
+const REFERENCE_SEED: u64 = 1;
+
+/// The number of pixels along each side of the blocks whose mean colors are compared.
+const BLOCK_SIZE: u32 = 4;
+
+/// A rectangular region of an image, expressed as fractions of the image size.
+#[derive(Debug, Clone, Copy)]
+pub struct Region {
+    /// The left edge of the region.
+    pub x0: f64,
+    /// The top edge of the region.
+    pub y0: f64,
+    /// The right edge of the region.
+    pub x1: f64,
+    /// The bottom edge of the region.
+    pub y1: f64,
+}
+
+/// The expected mean luminance of a region of a reference render.
+#[derive(Debug, Clone, Copy)]
+pub struct Reference {
+    /// A short description of what the region covers.
+    pub name: &'static str,
+    /// The region of the image.
+    pub region: Region,
+    /// The mean luminance of the region in the reference render.
+    pub luminance: f64,
+    /// The largest accepted absolute difference from the reference luminance.
+    pub tolerance: f64,
+}
+
+/// Reference data for `caustics_world`, measured at a width of 240 pixels and 10000 samples per
+/// pixel. The caustic region lies inside the shadow of the glass sphere and is only lit by light
+/// refracted through it.
+pub const CAUSTICS_REFERENCE: [Reference; 3] = [
+    Reference {
+        name: "caustic",
+        region: Region {
+            x0: 0.52,
+            y0: 0.56,
+            x1: 0.60,
+            y1: 0.63,
+        },
+        luminance: 0.0753,
+        tolerance: 0.035,
+    },
+    Reference {
+        name: "shadow",
+        region: Region {
+            x0: 0.36,
+            y0: 0.50,
+            x1: 0.44,
+            y1: 0.56,
+        },
+        luminance: 0.0036,
+        tolerance: 0.008,
+    },
+    Reference {
+        name: "lit floor",
+        region: Region {
+            x0: 0.05,
+            y0: 0.20,
+            x1: 0.25,
+            y1: 0.40,
+        },
+        luminance: 0.2029,
+        tolerance: 0.15,
+    },
+];
+
+/// A small scene rendered with a fixed seed and compared with a stored reference image.
+#[derive(Debug, Clone, Copy)]
+pub struct ReferenceImage {
+    /// The name of the scene, which is also the name of the reference image file.
+    pub name: &'static str,
+    /// Builds the camera and the scene.
+    pub build: fn() -> (Camera, Scene),
+    /// The largest accepted difference between the mean colors of a block of the render and of
+    /// the reference, between 0 and 1. Renders with other seeds stay within half of it.
+    pub tolerance: f64,
+}
+
+/// The scenes compared with reference images: lights and diffuse walls, glass and its
+/// caustics, and every basic material on a set of shapes.
+pub const REFERENCE_IMAGES: [ReferenceImage; 3] = [
+    ReferenceImage {
+        name: "cornell_box",
+        build: || (cornell_box_camera(96, 64), cornell_box_scene()),
+        tolerance: 0.15,
+    },
+    ReferenceImage {
+        name: "caustics",
+        build: || caustics_world(96, 64),
+        tolerance: 0.15,
+    },
+    ReferenceImage {
+        name: "materials",
+        build: materials_world,
+        tolerance: 0.15,
+    },
+];
+
+/// Builds a small scene with a sphere, box, cylinder and torus of diffuse, metal, rough metal and
+/// glass materials on a checkered floor under the sky.
+///
+/// # Returns
+///
+/// A tuple containing the camera and the scene.
+fn materials_world() -> (Camera, Scene) {
+    let floor = Arc::new(Lambertian::from_texture(Box::new(CheckerTexture::new(
+        0.5,
+        Vector3::new(0.2, 0.3, 0.1),
+        Vector3::new(0.9, 0.9, 0.9),
+    ))));
+    let world: Vec<Box<dyn Hittable>> = vec![
+        Box::new(Quad::new(
+            Vector3::new(-4.0, 0.0, -4.0),
+            Vector3::new(8.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, 8.0),
+            floor,
+        )),
+        Box::new(Sphere::new(
+            Vector3::new(-1.5, 0.5, 0.0),
+            0.5,
+            Arc::new(Lambertian::new(Vector3::new(0.7, 0.2, 0.1))),
+        )),
+        Box::new(BoxQuad::new(
+            Vector3::new(-0.4, 0.0, -0.4),
+            Vector3::new(0.4, 0.8, 0.4),
+            Arc::new(Metal::new(Vector3::new(0.8, 0.8, 0.8), 0.0)),
+        )),
+        Box::new(
+            Cylinder::new(
+                Vector3::new(1.5, 0.0, 0.0),
+                Vector3::new(1.5, 1.0, 0.0),
+                0.4,
+                Arc::new(Metal::new(Vector3::new(0.8, 0.6, 0.2), 0.4)),
+            )
+            .with_caps(true),
+        ),
+        Box::new(Torus::new(
+            Vector3::new(0.0, 0.25, 1.4),
+            Vector3::new(0.0, 1.0, 0.0),
+            0.5,
+            0.2,
+            Arc::new(Dielectric::new(1.5)),
+        )),
+    ];
+    let camera = Camera::new(
+        96,
+        16.0 / 9.0,
+        64,
+        20,
+        background_gradient,
+        40.0,
+        Vector3::new(0.0, 2.5, 5.0),
+        Vector3::new(0.0, 0.4, 0.0),
+        Vector3::new(0.0, 1.0, 0.0),
+        0.0,
+        0.0,
+    );
+    (camera, Scene::new(world))
+}
+
+/// Builds the Cornell scenes on which material dispatch is compared: the Cornell box, and the
+/// Cornell box with a glass ball and a rough metal ball, which mixes more material types.
+///
+/// # Arguments
+///
+/// * `dispatch` - How the materials are dispatched on every bounce.
+///
+/// # Returns
+///
+/// The names of the scenes and the scenes.
+pub fn cornell_dispatch_scenes(dispatch: Dispatch) -> [(&'static str, Scene); 2] {
+    let mut mixed = cornell_box_scene_with(dispatch);
+    let glass = MaterialKind::from(Dielectric::new(1.5)).shared(dispatch);
+    let metal = MaterialKind::from(Metal::new(Vector3::new(0.8, 0.85, 0.88), 0.2)).shared(dispatch);
+    mixed.add(
+        Box::new(Sphere::new(Vector3::new(420.0, 70.0, 120.0), 70.0, glass)),
+        DEFAULT_LAYER,
+    );
+    mixed.add(
+        Box::new(Sphere::new(Vector3::new(210.0, 220.0, 150.0), 55.0, metal)),
+        DEFAULT_LAYER,
+    );
+    [
+        ("cornell_box", cornell_box_scene_with(dispatch)),
+        ("cornell_glass_and_metal", mixed),
+    ]
+}
+
+/// Returns the path of the stored reference image of a scene.
+///
+/// # Arguments
+///
+/// * `name` - The name of the scene.
+///
+/// # Returns
+///
+/// The path of the image in the `references` directory of the crate.
+pub fn reference_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("references")
+        .join(format!("{}.png", name))
+}
+
+/// Renders a reference scene with the reference seed and encodes it as the renderer saves it.
+///
+/// # Arguments
+///
+/// * `reference` - The scene to render.
+///
+/// # Returns
+///
+/// The rendered image.
+pub fn render_reference(reference: &ReferenceImage) -> RgbImage {
+    let (mut camera, scene) = (reference.build)();
+    camera.set_seed(REFERENCE_SEED);
+    let pixels = camera.render_linear(&scene);
+    let output = OutputTransform::new(Encoding::default());
+    let width = camera.image_width();
+    RgbImage::from_fn(width, camera.image_height(), |x, y| {
+        output.apply(pixels[(y * width + x) as usize])
+    })
+}
+
+/// Measures how much two images differ, as the largest difference between the mean colors of
+/// their blocks of `BLOCK_SIZE` pixels, so noise weighs little while a change to any part of the
+/// image, such as the color of one object, stands out.
+///
+/// # Arguments
+///
+/// * `image` - The first image.
+/// * `reference` - The second image.
+///
+/// # Returns
+///
+/// The difference between 0 for equal images and 1 for black against white, or `None` if the
+/// images have different sizes.
+pub fn image_difference(image: &RgbImage, reference: &RgbImage) -> Option<f64> {
+    if image.dimensions() != reference.dimensions() {
+        return None;
+    }
+    let (width, height) = image.dimensions();
+    let block_mean = |image: &RgbImage, x0: u32, y0: u32| {
+        let mut sum = Vector3::default();
+        let mut count = 0;
+        for y in y0..(y0 + BLOCK_SIZE).min(height) {
+            for x in x0..(x0 + BLOCK_SIZE).min(width) {
+                let [r, g, b] = image.get_pixel(x, y).0;
+                sum += Vector3::new(r as f64, g as f64, b as f64) / 255.0;
+                count += 1;
+            }
+        }
+        sum / count as f64
+    };
+
+    let mut largest: f64 = 0.0;
+    for y in (0..height).step_by(BLOCK_SIZE as usize) {
+        for x in (0..width).step_by(BLOCK_SIZE as usize) {
+            let difference = block_mean(image, x, y) - block_mean(reference, x, y);
+            largest =
+                largest.max((difference.x.abs() + difference.y.abs() + difference.z.abs()) / 3.0);
+        }
+    }
+    Some(largest)
+}
+
+/// Renders a reference scene and compares it with its stored reference image. A render that
+/// does not match is saved in the temporary directory for inspection.
+///
+/// # Arguments
+///
+/// * `reference` - The scene to check.
+///
+/// # Returns
+///
+/// The difference from the reference image, or a description of why the render does not match.
+pub fn compare_image(reference: &ReferenceImage) -> Result<f64, String> {
+    let path = reference_path(reference.name);
+    let expected = image::open(&path)
+        .map_err(|error| {
+            format!(
+                "{}: cannot read {}: {}",
+                reference.name,
+                path.display(),
+                error
+            )
+        })?
+        .to_rgb8();
+    let image = render_reference(reference);
+    let difference = image_difference(&image, &expected);
+    if difference.is_some_and(|difference| difference <= reference.tolerance) {
+        return Ok(difference.unwrap_or_default());
+    }
+
+    let actual = std::env::temp_dir().join(format!("{}.actual.png", reference.name));
+    let saved = match image.save(&actual) {
+        Ok(()) => format!("saved the render to {}", actual.display()),
+        Err(error) => format!("cannot save the render: {}", error),
+    };
+    Err(match difference {
+        Some(difference) => format!(
+            "{}: differs from the reference by {:.4}, more than {:.4}, {}",
+            reference.name, difference, reference.tolerance, saved
+        ),
+        None => format!(
+            "{}: rendered {:?} pixels, the reference has {:?}, {}",
+            reference.name,
+            image.dimensions(),
+            expected.dimensions(),
+            saved
+        ),
+    })
+}
+
+/// Computes the mean luminance of a region of a linear image.
+///
+/// # Arguments
+///
+/// * `pixels` - The linear colors of the image, stored row by row.
+/// * `width` - The width of the image in pixels.
+/// * `height` - The height of the image in pixels.
+/// * `region` - The region to average over.
+///
+/// # Returns
+///
+/// The mean luminance of the pixels inside the region.
+pub fn region_luminance(pixels: &[Vector3], width: u32, height: u32, region: Region) -> f64 {
+    let x0 = (region.x0 * width as f64) as u32;
+    let y0 = (region.y0 * height as f64) as u32;
+    let x1 = ((region.x1 * width as f64).ceil() as u32).clamp(x0 + 1, width);
+    let y1 = ((region.y1 * height as f64).ceil() as u32).clamp(y0 + 1, height);
+
+    let mut sum = 0.0;
+    for y in y0..y1 {
+        for x in x0..x1 {
+            sum += pixels[(y * width + x) as usize].luminance();
+        }
+    }
+    sum / ((x1 - x0) * (y1 - y0)) as f64
+}
+
+/// Renders a scene and measures the luminance of every reference region.
+///
+/// # Arguments
+///
+/// * `camera` - The camera used to render the scene.
+/// * `scene` - The scene to render.
+/// * `references` - The regions to measure.
+///
+/// # Returns
+///
+/// The measured mean luminance of each region, in the order of `references`.
+pub fn measure(camera: &Camera, scene: &Scene, references: &[Reference]) -> Vec<f64> {
+    let pixels = camera.render_linear(scene);
+    references
+        .iter()
+        .map(|reference| {
+            region_luminance(
+                &pixels,
+                camera.image_width(),
+                camera.image_height(),
+                reference.region,
+            )
+        })
+        .collect()
+}
+
+/// Renders a scene and checks every reference region against its expected luminance.
+///
+/// # Arguments
+///
+/// * `camera` - The camera used to render the scene.
+/// * `scene` - The scene to render.
+/// * `references` - The reference regions to check.
+///
+/// # Returns
+///
+/// A list of descriptions of the regions that fall outside their tolerance.
+pub fn compare(camera: &Camera, scene: &Scene, references: &[Reference]) -> Vec<String> {
+    references
+        .iter()
+        .zip(measure(camera, scene, references))
+        .filter(|(reference, value)| (value - reference.luminance).abs() > reference.tolerance)
+        .map(|(reference, value)| {
+            format!(
+                "{}: measured {:.4}, expected {:.4} ± {:.4}",
+                reference.name, value, reference.luminance, reference.tolerance
+            )
+        })
+        .collect()
+}
+
+/// Renders a single object inside a uniform white environment, the so-called furnace test.
+///
+/// An object made of a material that neither absorbs nor emits light is invisible in the furnace,
+/// so every pixel of the render should equal the environment. Darker pixels reveal energy loss
+/// and brighter pixels reveal energy gain.
+///
+/// # Arguments
+///
+/// * `object` - The object placed in the furnace, centered at the origin and inside the unit sphere.
+///
+/// # Returns
+///
+/// The linear colors of the rendered image, stored row by row.
+pub fn furnace(object: Box<dyn Hittable>) -> Vec<Vector3> {
+    let camera = Camera::new(
+        32,
+        1.0,
+        16,
+        50,
+        |_| Vector3::new(1.0, 1.0, 1.0),
+        30.0,
+        Vector3::new(0.0, 0.0, 4.0),
+        Vector3::new(0.0, 0.0, 0.0),
+        Vector3::new(0.0, 1.0, 0.0),
+        0.0,
+        0.0,
+    );
+
+    camera.render_linear(&Scene::new(vec![object]))
+}
+
+/// Runs the furnace test on a unit sphere made of the given material.
+///
+/// # Arguments
+///
+/// * `material` - The material of the sphere, which should have a white albedo.
+///
+/// # Returns
+///
+/// The mean luminance of the rendered image and the largest deviation of a pixel from white.
+pub fn furnace_sphere(material: Arc<dyn Material>) -> (f64, f64) {
+    let pixels = furnace(Box::new(Sphere::new(Vector3::default(), 1.0, material)));
+    let mean = pixels.iter().map(|&pixel| pixel.luminance()).sum::<f64>() / pixels.len() as f64;
+    let max_deviation = pixels
+        .iter()
+        .map(|&pixel| (pixel.luminance() - 1.0).abs())
+        .fold(0.0, f64::max);
+    (mean, max_deviation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shapes::volume::ConstantMedium;
+
+    const WHITE: Vector3 = Vector3 {
+        x: 1.0,
+        y: 1.0,
+        z: 1.0,
+    };
+
+    #[test]
+    fn test_furnace_lambertian() {
+        let (mean, max_deviation) = furnace_sphere(Arc::new(Lambertian::new(WHITE)));
+        assert!((mean - 1.0).abs() < 1e-3, "mean {}", mean);
+        assert!(max_deviation < 1e-3, "max deviation {}", max_deviation);
+    }
+
+    #[test]
+    fn test_furnace_metal() {
+        let (mean, max_deviation) = furnace_sphere(Arc::new(Metal::new(WHITE, 0.0)));
+        assert!((mean - 1.0).abs() < 1e-3, "mirror: mean {}", mean);
+        assert!(
+            max_deviation < 1e-3,
+            "mirror: max deviation {}",
+            max_deviation
+        );
+
+        // Single-scattering GGX loses the light bouncing between microfacets, which grows with the
+        // roughness, so rough metals must not gain energy and may only lose a bounded amount.
+        for (fuzz, min_mean) in [(0.3, 0.97), (0.7, 0.75), (1.0, 0.5)] {
+            let pixels = furnace(Box::new(Sphere::new(
+                Vector3::default(),
+                1.0,
+                Arc::new(Metal::new(WHITE, fuzz)),
+            )));
+            let mean =
+                pixels.iter().map(|&pixel| pixel.luminance()).sum::<f64>() / pixels.len() as f64;
+            let max = pixels
+                .iter()
+                .map(|&pixel| pixel.luminance())
+                .fold(0.0, f64::max);
+
+            assert!(max <= 1.0 + 1e-9, "fuzz {}: max {}", fuzz, max);
+            assert!(mean > min_mean, "fuzz {}: mean {}", fuzz, mean);
+        }
+    }
+
+    #[test]
+    fn test_furnace_dielectric() {
+        for refraction_index in [1.0 / 1.33, 1.33, 1.5, 2.4] {
+            let (mean, _) = furnace_sphere(Arc::new(Dielectric::new(refraction_index)));
+
+            // Rays trapped by repeated internal reflections near grazing angles may exceed the
+            // maximum depth, so only the average is checked.
+            assert!(
+                (mean - 1.0).abs() < 1e-2,
+                "ior {}: mean {}",
+                refraction_index,
+                mean
+            );
+        }
+    }
+
+    #[test]
+    fn test_furnace_rough_dielectric() {
+        // Like rough metals, rough glass loses the light bouncing between its microfacets, once
+        // as it enters the sphere and again as it leaves.
+        for (refraction_index, roughness, min_mean) in [
+            (1.5, 0.0, 0.99),
+            (1.5, 0.3, 0.93),
+            (1.5, 0.7, 0.6),
+            (1.0 / 1.33, 0.5, 0.8),
+        ] {
+            let material = RoughDielectric::new(refraction_index, roughness);
+            let (mean, _) = furnace_sphere(Arc::new(material));
+            assert!(
+                mean > min_mean && mean < 1.01,
+                "ior {}, roughness {}: mean {}",
+                refraction_index,
+                roughness,
+                mean
+            );
+        }
+    }
+
+    #[test]
+    fn test_furnace_isotropic_medium() {
+        let boundary = Sphere::new(Vector3::default(), 1.0, Arc::new(Dielectric::new(1.0)));
+        let pixels = furnace(Box::new(ConstantMedium::new(
+            Box::new(boundary),
+            2.0,
+            WHITE,
+        )));
+        let mean = pixels.iter().map(|&pixel| pixel.luminance()).sum::<f64>() / pixels.len() as f64;
+
+        // Long random walks inside the medium may exceed the maximum depth, so a small loss is
+        // accepted on average.
+        assert!((mean - 1.0).abs() < 1e-2, "mean {}", mean);
+    }
+
+    #[test]
+    fn test_furnace_anisotropic_media() {
+        for g in [-0.7, 0.7] {
+            let boundary = Sphere::new(Vector3::default(), 1.0, Arc::new(Dielectric::new(1.0)));
+            let pixels = furnace(Box::new(ConstantMedium::anisotropic(
+                Box::new(boundary),
+                2.0,
+                WHITE,
+                g,
+            )));
+            let mean =
+                pixels.iter().map(|&pixel| pixel.luminance()).sum::<f64>() / pixels.len() as f64;
+            assert!((mean - 1.0).abs() < 2e-2, "g {}: mean {}", g, mean);
+        }
+    }
+
+    #[test]
+    fn test_environment_reflections_match_traced_mirror() {
+        let render = |environment_reflections: bool| {
+            let mut camera = Camera::new(
+                32,
+                1.0,
+                16,
+                50,
+                crate::utils::background_gradient,
+                30.0,
+                Vector3::new(0.0, 0.0, 4.0),
+                Vector3::new(0.0, 0.0, 0.0),
+                Vector3::new(0.0, 1.0, 0.0),
+                0.0,
+                0.0,
+            );
+            camera.set_environment_reflections(environment_reflections);
+            let sphere = Sphere::new(
+                Vector3::default(),
+                1.0,
+                Arc::new(Metal::new(Vector3::new(0.8, 0.6, 0.4), 0.0)),
+            );
+            camera.render_linear(&Scene::new(vec![Box::new(sphere)]))
+        };
+
+        // A lone convex mirror only ever reflects the environment, so both paths agree up to the
+        // noise of the anti-aliasing along the silhouette.
+        let mean = |pixels: Vec<Vector3>| {
+            pixels.iter().map(|&pixel| pixel.luminance()).sum::<f64>() / pixels.len() as f64
+        };
+        let traced = mean(render(false));
+        let looked_up = mean(render(true));
+        assert!(
+            (traced - looked_up).abs() < 1e-2,
+            "traced {}, looked up {}",
+            traced,
+            looked_up
+        );
+    }
+
+    #[test]
+    fn test_caustics_matches_reference() {
+        let (camera, scene) = caustics_world(160, 200);
+        let failures = compare(&camera, &scene, &CAUSTICS_REFERENCE);
+        assert!(failures.is_empty(), "{}", failures.join("\n"));
+    }
+
+    #[test]
+    fn test_seeded_render_is_reproducible() {
+        let (mut camera, scene) = caustics_world(48, 4);
+        camera.set_seed(42);
+        let render = |camera: &Camera, threads: usize| {
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .unwrap()
+                .install(|| camera.render_linear(&scene))
+        };
+
+        let single = render(&camera, 1);
+        assert_eq!(single, render(&camera, 4));
+        assert_eq!(single, render(&camera, 4));
+
+        camera.set_seed(7);
+        assert_ne!(single, render(&camera, 4));
+    }
+
+    #[test]
+    fn test_image_difference() {
+        let gray = RgbImage::from_pixel(8, 8, image::Rgb([128, 128, 128]));
+        assert_eq!(image_difference(&gray, &gray), Some(0.0));
+
+        // A checkerboard averages to gray within every block.
+        let checker = RgbImage::from_fn(8, 8, |x, y| {
+            image::Rgb([if (x + y) % 2 == 0 { 0 } else { 255 }; 3])
+        });
+        assert!(image_difference(&checker, &gray).unwrap() < 0.01);
+
+        let white = RgbImage::from_pixel(8, 8, image::Rgb([255, 255, 255]));
+        let black = RgbImage::new(8, 8);
+        assert_eq!(image_difference(&white, &black), Some(1.0));
+        assert_eq!(image_difference(&white, &RgbImage::new(8, 4)), None);
+    }
+
+    #[test]
+    fn test_scenes_match_reference_images() {
+        let failures: Vec<String> = REFERENCE_IMAGES
+            .iter()
+            .filter_map(|reference| compare_image(reference).err())
+            .collect();
+        assert!(failures.is_empty(), "{}", failures.join("\n"));
+    }
+
+    /// Renders the reference images again, used after a change meant to alter the renders.
+    #[test]
+    #[ignore]
+    fn update_reference_images() {
+        for reference in &REFERENCE_IMAGES {
+            let path = reference_path(reference.name);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            render_reference(reference).save(&path).unwrap();
+            println!("Saved {}", path.display());
+        }
+    }
+
+    #[test]
+    fn test_material_kinds_render_like_trait_objects() {
+        let mut camera = cornell_box_camera(48, 4);
+        camera.set_seed(REFERENCE_SEED);
+        let trait_objects = cornell_dispatch_scenes(Dispatch::TraitObjects);
+        let kinds = cornell_dispatch_scenes(Dispatch::Enum);
+        for ((name, expected), (_, scene)) in trait_objects.iter().zip(&kinds) {
+            assert!(
+                camera.render_linear(scene) == camera.render_linear(expected),
+                "{}",
+                name
+            );
+        }
+    }
+
+    /// Renders the Cornell scenes with their materials dispatched through trait objects and
+    /// through `MaterialKind` on a single thread, alternating between them so both see the same
+    /// load, and prints the fastest of a few render times of each.
+    #[test]
+    #[ignore]
+    fn benchmark_material_dispatch() {
+        let mut camera = cornell_box_camera(160, 16);
+        camera.set_seed(REFERENCE_SEED);
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(1)
+            .build()
+            .unwrap();
+        let scenes = [Dispatch::TraitObjects, Dispatch::Enum].map(cornell_dispatch_scenes);
+        for index in 0..scenes[0].len() {
+            let mut fastest = [std::time::Duration::MAX; 2];
+            for _ in 0..5 {
+                for (fastest, scenes) in fastest.iter_mut().zip(&scenes) {
+                    let start = std::time::Instant::now();
+                    pool.install(|| camera.render_linear(&scenes[index].1));
+                    *fastest = (*fastest).min(start.elapsed());
+                }
+            }
+            println!(
+                "{}: {} ms with trait objects, {} ms with MaterialKind",
+                scenes[0][index].0,
+                fastest[0].as_millis(),
+                fastest[1].as_millis()
+            );
+        }
+    }
+
+    /// Prints the luminance of the caustics reference regions, used to regenerate the reference.
+    #[test]
+    #[ignore]
+    fn measure_caustics_reference() {
+        let (camera, scene) = caustics_world(240, 10000);
+        for (reference, value) in
+            CAUSTICS_REFERENCE
+                .iter()
+                .zip(measure(&camera, &scene, &CAUSTICS_REFERENCE))
+        {
+            println!("{}: {:.4}", reference.name, value);
+        }
+    }
+}
@@ -0,0 +1,291 @@
+//! Scene-construction helpers that place instances over a host surface with Poisson-disk
+//! sampling, so scattered objects keep a minimum spacing and follow a density map instead of
+//! being dropped at fully random positions.
+
+use crate::hit::Hittable;
+use crate::ray::Ray;
+use crate::texture::{ShadingContext, Texture};
+use crate::vector3::Vector3;
+
+/// The number of candidates tried around every active sample before it is retired.
+const CANDIDATES_PER_SAMPLE: usize = 30;
+
+/// The height above the region from which placement rays are cast down onto the host surface.
+const PLACEMENT_HEIGHT: f64 = 1e4;
+
+/// Describes an instance placed on the host surface.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Placement {
+    /// The point of the host surface on which the instance stands.
+    pub point: Vector3,
+    /// The normal of the host surface at the point, facing upwards.
+    pub normal: Vector3,
+}
+
+/// Scatters points over a rectangle of the xz-plane with Poisson-disk sampling and projects them
+/// onto a host surface.
+#[derive(Debug)]
+pub struct SurfaceScatter {
+    /// The corner of the region with the smallest x and z coordinates.
+    min: (f64, f64),
+    /// The corner of the region with the largest x and z coordinates.
+    max: (f64, f64),
+    /// The smallest distance allowed between two points.
+    min_distance: f64,
+    /// The probability of keeping a point, read from the luminance of the texture, if any.
+    density: Option<Box<dyn Texture>>,
+    /// The seed of the random generator, so the same scatter always gives the same points.
+    seed: u64,
+}
+
+impl SurfaceScatter {
+    /// Creates a new `SurfaceScatter` over a region with a uniform density.
+    ///
+    /// # Arguments
+    ///
+    /// * `min` - The x and z coordinates of the corner of the region closest to the origin.
+    /// * `max` - The x and z coordinates of the opposite corner.
+    /// * `min_distance` - The smallest distance allowed between two points.
+    ///
+    /// # Returns
+    ///
+    /// A new `SurfaceScatter` instance.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `min_distance` is not positive.
+    pub fn new(min: (f64, f64), max: (f64, f64), min_distance: f64) -> SurfaceScatter {
+        assert!(min_distance > 0.0, "the minimum distance must be positive");
+        SurfaceScatter {
+            min,
+            max,
+            min_distance,
+            density: None,
+            seed: 0,
+        }
+    }
+
+    /// Sets the density map of the scatter. The texture is evaluated with u along x and v along z
+    /// across the region, and with the point on the xz-plane for world-space textures; the
+    /// luminance of its value is the probability of keeping a point there.
+    ///
+    /// # Arguments
+    ///
+    /// * `density` - The density map.
+    ///
+    /// # Returns
+    ///
+    /// The `SurfaceScatter` with the given density map.
+    pub fn with_density(mut self, density: Box<dyn Texture>) -> SurfaceScatter {
+        self.density = Some(density);
+        self
+    }
+
+    /// Sets the seed of the random generator.
+    ///
+    /// # Arguments
+    ///
+    /// * `seed` - The seed.
+    ///
+    /// # Returns
+    ///
+    /// The `SurfaceScatter` with the given seed.
+    pub fn with_seed(mut self, seed: u64) -> SurfaceScatter {
+        self.seed = seed;
+        self
+    }
+
+    /// Generates the points of the scatter on the xz-plane. A Poisson-disk set covering the
+    /// region is built with Bridson's algorithm, then thinned by the density map.
+    ///
+    /// # Returns
+    ///
+    /// The x and z coordinates of the points.
+    pub fn points(&self) -> Vec<(f64, f64)> {
+        let mut rng = fastrand::Rng::with_seed(self.seed);
+        let width = self.max.0 - self.min.0;
+        let depth = self.max.1 - self.min.1;
+        if width <= 0.0 || depth <= 0.0 {
+            return Vec::new();
+        }
+
+        // Every cell is small enough to hold at most one point.
+        let cell_size = self.min_distance / 2.0_f64.sqrt();
+        let columns = (width / cell_size).ceil() as usize;
+        let rows = (depth / cell_size).ceil() as usize;
+        let cell = |x: f64, z: f64| {
+            (
+                (((x - self.min.0) / cell_size) as usize).min(columns - 1),
+                (((z - self.min.1) / cell_size) as usize).min(rows - 1),
+            )
+        };
+
+        let mut grid: Vec<Option<usize>> = vec![None; columns * rows];
+        let mut points = Vec::new();
+        let mut active = Vec::new();
+
+        let first = (
+            self.min.0 + rng.f64() * width,
+            self.min.1 + rng.f64() * depth,
+        );
+        let (column, row) = cell(first.0, first.1);
+        grid[row * columns + column] = Some(0);
+        points.push(first);
+        active.push(0);
+
+        while !active.is_empty() {
+            let slot = rng.usize(..active.len());
+            let (x, z) = points[active[slot]];
+            let mut found = false;
+
+            for _ in 0..CANDIDATES_PER_SAMPLE {
+                let angle = rng.f64() * 2.0 * std::f64::consts::PI;
+                let radius = self.min_distance * (1.0 + rng.f64());
+                let candidate = (x + radius * angle.cos(), z + radius * angle.sin());
+                if candidate.0 < self.min.0
+                    || candidate.0 >= self.max.0
+                    || candidate.1 < self.min.1
+                    || candidate.1 >= self.max.1
+                {
+                    continue;
+                }
+
+                let (column, row) = cell(candidate.0, candidate.1);
+                let near = (column.saturating_sub(2)..(column + 3).min(columns)).any(|c| {
+                    (row.saturating_sub(2)..(row + 3).min(rows)).any(|r| {
+                        grid[r * columns + c].is_some_and(|index| {
+                            let (px, pz) = points[index];
+                            (px - candidate.0).powi(2) + (pz - candidate.1).powi(2)
+                                < self.min_distance * self.min_distance
+                        })
+                    })
+                });
+                if !near {
+                    grid[row * columns + column] = Some(points.len());
+                    active.push(points.len());
+                    points.push(candidate);
+                    found = true;
+                    break;
+                }
+            }
+
+            if !found {
+                active.swap_remove(slot);
+            }
+        }
+
+        points.retain(|&(x, z)| rng.f64() < self.density_at(x, z));
+        points
+    }
+
+    /// Returns the probability of keeping a point.
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - The x-coordinate of the point.
+    /// * `z` - The z-coordinate of the point.
+    ///
+    /// # Returns
+    ///
+    /// The density at the point, between 0 and 1.
+    fn density_at(&self, x: f64, z: f64) -> f64 {
+        let Some(density) = &self.density else {
+            return 1.0;
+        };
+        let mut context = ShadingContext::from_uv(
+            (x - self.min.0) / (self.max.0 - self.min.0),
+            (z - self.min.1) / (self.max.1 - self.min.1),
+        );
+        context.point = Vector3::new(x, 0.0, z);
+        context.object_point = context.point;
+        density.value(&context).luminance().clamp(0.0, 1.0)
+    }
+
+    /// Places the points of the scatter on a host surface, such as a ground sphere or a mesh, by
+    /// casting a ray straight down onto it from above each point.
+    ///
+    /// # Arguments
+    ///
+    /// * `host` - The host surface.
+    ///
+    /// # Returns
+    ///
+    /// The placements of the points whose ray hits the host.
+    pub fn place_on(&self, host: &dyn Hittable) -> Vec<Placement> {
+        self.points()
+            .into_iter()
+            .filter_map(|(x, z)| {
+                let ray = Ray::new(
+                    Vector3::new(x, PLACEMENT_HEIGHT, z),
+                    Vector3::new(0.0, -1.0, 0.0),
+                );
+                host.hit(&ray, (0.0, f64::INFINITY))
+                    .map(|record| Placement {
+                        point: record.poz,
+                        normal: record.normal,
+                    })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Lambertian;
+    use crate::shapes::sphere::Sphere;
+    use crate::texture::CheckerTexture;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_points_keep_their_distance() {
+        let scatter = SurfaceScatter::new((-5.0, -3.0), (5.0, 3.0), 0.5).with_seed(3);
+        let points = scatter.points();
+        // A maximal Poisson-disk set covers the region densely.
+        assert!(points.len() > 150, "{}", points.len());
+
+        for (i, a) in points.iter().enumerate() {
+            assert!((-5.0..5.0).contains(&a.0) && (-3.0..3.0).contains(&a.1));
+            for b in &points[i + 1..] {
+                let distance = ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt();
+                assert!(distance >= 0.5, "{:?} {:?}", a, b);
+            }
+        }
+
+        assert_eq!(points, scatter.points());
+        let other = SurfaceScatter::new((-5.0, -3.0), (5.0, 3.0), 0.5).with_seed(4);
+        assert_ne!(points, other.points());
+    }
+
+    #[test]
+    fn test_density_map() {
+        // The checker is white where floor(x) is odd and black where it is even.
+        let density = CheckerTexture::new(
+            1.0,
+            Vector3::new(1.0, 1.0, 1.0),
+            Vector3::new(0.0, 0.0, 0.0),
+        );
+        let uniform = SurfaceScatter::new((0.0, 0.0), (4.0, 1.0), 0.1).with_seed(1);
+        let scatter = uniform.with_density(Box::new(density));
+        let points = scatter.points();
+        assert!(!points.is_empty());
+        assert!(points.iter().all(|&(x, _)| (x.floor() as i32) % 2 != 0));
+    }
+
+    #[test]
+    fn test_place_on_ground_sphere() {
+        let material = Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5)));
+        let ground = Sphere::new(Vector3::new(0.0, -10.0, 0.0), 10.0, material);
+        let scatter = SurfaceScatter::new((-12.0, -1.0), (12.0, 1.0), 0.5);
+        let points = scatter.points();
+        let placements = scatter.place_on(&ground);
+
+        // Points beyond the rim of the sphere miss it.
+        assert!(placements.len() < points.len());
+        for placement in placements {
+            let offset = placement.point - Vector3::new(0.0, -10.0, 0.0);
+            assert!((offset.length() - 10.0).abs() < 1e-6);
+            assert!((placement.normal - offset / 10.0).length() < 1e-6);
+        }
+    }
+}
@@ -0,0 +1,1455 @@
+use crate::aabb::Aabb;
+use crate::camera::Camera;
+use crate::decal::Decal;
+use crate::hit::{HitRecord, Hittable};
+use crate::material::Material;
+use crate::material_table::MaterialTable;
+use crate::pdf::Pdf;
+use crate::profiling::hot_span;
+use crate::ray::Ray;
+use crate::shapes::hittable_list::HittableList;
+use crate::shapes::on_hit::{HitCallback, OnHit};
+use crate::statistics::SceneStatistics;
+use crate::transformation::Instance;
+use crate::unit::Unit;
+use crate::vector3::Vector3;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// The render layer of objects added without naming one.
+pub const DEFAULT_LAYER: &str = "default";
+
+/// The distance in meters that scattered rays skip before looking for hits, so rounding errors
+/// do not let them hit the surface they leave again.
+const RAY_EPSILON: f64 = 1e-3;
+
+/// The largest distance that scattered rays skip, as a fraction of the size of the scene, so
+/// scenes modeled in small units do not skip past corners and thin parts of the scene.
+const MAX_RELATIVE_EPSILON: f64 = 1e-6;
+
+/// The number of directions traced on each side of a surface to find its occlusion and
+/// curvature, for materials whose textures read them.
+const SURFACE_QUERY_SAMPLES: u32 = 4;
+
+/// The distance in meters within which surface queries look for nearby surfaces, unless set.
+const SURFACE_QUERY_RADIUS: f64 = 0.1;
+
+/// Maps the names of objects to the materials replacing their own during a render.
+pub type MaterialOverrides = HashMap<String, Arc<dyn Material>>;
+
+/// How a light link treats the objects it lists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkMode {
+    /// Only the listed objects are linked.
+    Include,
+    /// Every object but the listed ones is linked.
+    Exclude,
+}
+
+/// A list of objects linked to a light, or of lights linked to an object, by index.
+#[derive(Debug, Clone, PartialEq)]
+struct LightLink {
+    /// How the listed objects are treated.
+    mode: LinkMode,
+    /// The indices of the listed objects.
+    objects: Vec<usize>,
+}
+
+impl LightLink {
+    /// Returns `true` if the link lets an object through.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the object.
+    fn allows(&self, index: usize) -> bool {
+        self.objects.contains(&index) == (self.mode == LinkMode::Include)
+    }
+}
+
+/// Describes what a pixel of a render shows, as found by `Scene::pick`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PickResult {
+    /// The index of the object, in the order in which objects were added to the scene.
+    pub object: usize,
+    /// The render layer of the object.
+    pub layer: String,
+    /// The distance from the camera to the hit point.
+    pub distance: f64,
+    /// The hit point.
+    pub point: Vector3,
+    /// The normal at the hit point, facing the camera.
+    pub normal: Vector3,
+    /// The name of the material at the hit point.
+    pub material: &'static str,
+}
+
+/// Represents the objects of a scene together with the light sources found among them.
+/// Objects are tagged with render layers, so selected layers can be rendered on their own for
+/// compositing.
+pub struct Scene {
+    /// The list of objects in the scene.
+    objects: Vec<Box<dyn Hittable>>,
+    /// The render layer of each object, in the order of `objects`.
+    object_layers: Vec<String>,
+    /// The name of each object, in the order of `objects`, or `None` for unnamed objects.
+    object_names: Vec<Option<String>>,
+    /// The priority of each object among coplanar surfaces, in the order of `objects`.
+    object_priorities: Vec<i32>,
+    /// The decals projected onto the objects, in the order in which they are blended.
+    decals: Vec<Decal>,
+    /// The materials replacing the ones of objects, by index of the object.
+    material_overrides: HashMap<usize, Arc<dyn Material>>,
+    /// The layers seen by camera rays, or `None` if every layer is seen.
+    visible_layers: Option<Vec<String>>,
+    /// The indices of the emissive objects in `objects`.
+    lights: Vec<usize>,
+    /// The probability of choosing each light when sampling, in the order of `lights`.
+    light_weights: Vec<f64>,
+    /// The objects illuminated by lights, by index of the light, for lights with links.
+    light_links: HashMap<usize, LightLink>,
+    /// The lights illuminating objects, by index of the object, for objects with links.
+    object_links: HashMap<usize, LightLink>,
+    /// The density of the shadows cast by objects from lights, by index of the light and of the
+    /// object, for the pairs whose shadows are lightened.
+    shadow_densities: HashMap<(usize, usize), f64>,
+    /// The unit of the coordinates of the scene.
+    unit: Unit,
+    /// The bounding box of all the objects, visible or not, or `None` if no object has bounds.
+    bounds: Option<Aabb>,
+    /// The factor scaling imported assets on top of the conversion of their unit.
+    import_scale: f64,
+    /// The number of directions traced by surface queries on each side of a surface.
+    query_samples: u32,
+    /// The distance within which surface queries look for nearby surfaces, or `None` for the
+    /// default length.
+    query_radius: Option<f64>,
+}
+
+impl Scene {
+    /// Creates a new `Scene` from a list of objects, all placed in the default layer.
+    /// Emissive objects are discovered automatically and are chosen for light sampling with a
+    /// probability proportional to the luminance of their power.
+    ///
+    /// # Arguments
+    ///
+    /// * `objects` - The list of objects in the scene.
+    ///
+    /// # Returns
+    ///
+    /// A new `Scene` instance.
+    pub fn new(objects: Vec<Box<dyn Hittable>>) -> Scene {
+        let object_layers = vec![DEFAULT_LAYER.to_string(); objects.len()];
+        let object_names = vec![None; objects.len()];
+        let object_priorities = vec![0; objects.len()];
+        let mut scene = Scene {
+            objects,
+            object_layers,
+            object_names,
+            object_priorities,
+            decals: Vec::new(),
+            material_overrides: HashMap::new(),
+            visible_layers: None,
+            lights: Vec::new(),
+            light_weights: Vec::new(),
+            light_links: HashMap::new(),
+            object_links: HashMap::new(),
+            shadow_densities: HashMap::new(),
+            unit: Unit::default(),
+            bounds: None,
+            import_scale: 1.0,
+            query_samples: SURFACE_QUERY_SAMPLES,
+            query_radius: None,
+        };
+        scene.bounds = scene
+            .objects
+            .iter()
+            .filter_map(|object| object.bounding_box())
+            .reduce(|a, b| a.merge(&b));
+        scene.update_lights();
+        scene
+    }
+
+    /// Declares the unit of the coordinates of the scene, which sets how far scattered rays
+    /// skip to leave a surface and the size imported assets are given. Scenes are in meters
+    /// unless declared otherwise.
+    ///
+    /// # Arguments
+    ///
+    /// * `unit` - The unit of the coordinates.
+    pub fn set_unit(&mut self, unit: Unit) {
+        self.unit = unit;
+    }
+
+    /// Returns the unit of the coordinates of the scene.
+    pub fn unit(&self) -> Unit {
+        self.unit
+    }
+
+    /// Sets a factor scaling every asset imported from then on, on top of the conversion of its
+    /// unit, such as to build a miniature of a real-sized model.
+    ///
+    /// # Arguments
+    ///
+    /// * `scale` - The scale factor, greater than zero.
+    pub fn set_import_scale(&mut self, scale: f64) {
+        self.import_scale = scale;
+    }
+
+    /// Returns the factor by which an asset is scaled when imported into the scene.
+    ///
+    /// # Arguments
+    ///
+    /// * `unit` - The unit of the coordinates of the asset.
+    ///
+    /// # Returns
+    ///
+    /// The conversion from the unit of the asset to the unit of the scene, times the import
+    /// scale.
+    pub fn import_scale(&self, unit: Unit) -> f64 {
+        unit.to(self.unit) * self.import_scale
+    }
+
+    /// Adds an asset modeled in its own unit to the scene, tagged with a render layer, scaled
+    /// around its origin to its size in the unit of the scene.
+    ///
+    /// # Arguments
+    ///
+    /// * `object` - The asset.
+    /// * `unit` - The unit of the coordinates of the asset.
+    /// * `layer` - The name of the render layer of the asset.
+    pub fn add_imported(&mut self, object: Arc<dyn Hittable>, unit: Unit, layer: &str) {
+        let scale = self.import_scale(unit);
+        let instance = Instance::new(object, Vector3::default()).with_scale(scale);
+        self.add(Box::new(instance), layer);
+    }
+
+    /// Returns the distance that scattered rays skip before looking for hits, so they do not hit
+    /// the surface they leave again. It is the same length whatever the unit of the scene, so
+    /// scenes modeled in millimeters neither leak light through seams nor show shadow acne. It is
+    /// at most a millionth of the size of the scene, or a thousandth of a unit in smaller scenes,
+    /// so rays leaving a surface near a corner of a small scene do not skip the wall next to it.
+    ///
+    /// # Returns
+    ///
+    /// The distance in the unit of the scene.
+    pub fn epsilon(&self) -> f64 {
+        let epsilon = RAY_EPSILON / self.unit.meters();
+        match self.bounds {
+            Some(bounds) => {
+                let size = (bounds.max - bounds.min).length();
+                epsilon.min(RAY_EPSILON.max(size * MAX_RELATIVE_EPSILON))
+            }
+            None => epsilon,
+        }
+    }
+
+    /// Converts a density given per meter, such as the density of fog, into a density per unit
+    /// of the scene, so light falls off through media at the same rate whatever the unit.
+    ///
+    /// # Arguments
+    ///
+    /// * `per_meter` - The density per meter.
+    ///
+    /// # Returns
+    ///
+    /// The density per unit of the scene.
+    pub fn density(&self, per_meter: f64) -> f64 {
+        per_meter * self.unit.meters()
+    }
+
+    /// Sets how the occlusion and curvature of surfaces are queried for materials whose
+    /// textures read them. Each sample traces a ray on both sides of the surface for every
+    /// shaded point, and the noise of few samples is averaged away by the samples of the pixels.
+    ///
+    /// # Arguments
+    ///
+    /// * `samples` - The number of directions traced on each side of a surface.
+    /// * `radius` - The distance within which nearby surfaces count, in the unit of the scene,
+    ///   which is the width of worn edges and dirty creases.
+    pub fn set_surface_queries(&mut self, samples: u32, radius: f64) {
+        self.query_samples = samples;
+        self.query_radius = Some(radius);
+    }
+
+    /// Returns how the occlusion and curvature of surfaces are queried.
+    ///
+    /// # Returns
+    ///
+    /// The number of directions traced on each side of a surface and the distance within which
+    /// nearby surfaces count, in the unit of the scene.
+    pub fn surface_queries(&self) -> (u32, f64) {
+        let radius = self
+            .query_radius
+            .unwrap_or(SURFACE_QUERY_RADIUS / self.unit.meters());
+        (self.query_samples, radius)
+    }
+
+    /// Adds an object to the scene, tagged with a render layer.
+    ///
+    /// # Arguments
+    ///
+    /// * `object` - The object to add.
+    /// * `layer` - The name of the render layer of the object.
+    pub fn add(&mut self, object: Box<dyn Hittable>, layer: &str) {
+        if let Some(bounds) = object.bounding_box() {
+            self.bounds = Some(self.bounds.map_or(bounds, |all| all.merge(&bounds)));
+        }
+        self.objects.push(object);
+        self.object_layers.push(layer.to_string());
+        self.object_names.push(None);
+        self.object_priorities.push(0);
+        self.update_lights();
+    }
+
+    /// Adds a named object to the scene, tagged with a render layer. The name lets the object be
+    /// found and its material be overridden. Several objects may share a name, in which case
+    /// they are overridden together.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the object.
+    /// * `object` - The object to add.
+    /// * `layer` - The name of the render layer of the object.
+    pub fn add_named(&mut self, name: &str, object: Box<dyn Hittable>, layer: &str) {
+        self.add(object, layer);
+        *self.object_names.last_mut().unwrap() = Some(name.to_string());
+    }
+
+    /// Finds an object by name.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the object.
+    ///
+    /// # Returns
+    ///
+    /// The index of the first object added with the name, or `None` if there is none.
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.object_names
+            .iter()
+            .position(|object_name| object_name.as_deref() == Some(name))
+    }
+
+    /// Returns the name of an object.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the object.
+    ///
+    /// # Returns
+    ///
+    /// The name the object was added with, or `None` if it was added without one.
+    pub fn object_name(&self, index: usize) -> Option<&str> {
+        self.object_names[index].as_deref()
+    }
+
+    /// Adds a decal projected onto the objects within its box, blended over the ones added
+    /// before.
+    ///
+    /// # Arguments
+    ///
+    /// * `decal` - The decal to add.
+    pub fn add_decal(&mut self, decal: Decal) {
+        self.decals.push(decal);
+    }
+
+    /// Blends the decals of the scene over the albedo of a surface point.
+    ///
+    /// # Arguments
+    ///
+    /// * `record` - The record of the hit point.
+    /// * `albedo` - The albedo given by the material of the surface.
+    ///
+    /// # Returns
+    ///
+    /// The albedo with the decals covering the point blended over it.
+    pub fn decal_albedo(&self, record: &HitRecord, albedo: Vector3) -> Vector3 {
+        self.decals.iter().fold(albedo, |albedo, decal| {
+            decal.apply(&record.poz, &record.normal, albedo)
+        })
+    }
+
+    /// Sets the priority of named objects over the surfaces they coincide with, such as decals
+    /// laid flat on a wall. Where surfaces are hit within the distance scattered rays skip of
+    /// each other, the one with the highest priority is seen, instead of whichever rounding
+    /// errors put in front. Objects have a priority of 0 until set.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the objects.
+    /// * `priority` - The priority, higher priorities winning over lower ones.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())`, or a description of the error if no object has the name.
+    pub fn set_priority(&mut self, name: &str, priority: i32) -> Result<(), String> {
+        let mut found = false;
+        for (index, object_name) in self.object_names.iter().enumerate() {
+            if object_name.as_deref() == Some(name) {
+                self.object_priorities[index] = priority;
+                found = true;
+            }
+        }
+        if !found {
+            return Err(format!("no object named `{}`", name));
+        }
+        Ok(())
+    }
+
+    /// Runs a callback on every hit of the objects with the given name, which may change the
+    /// records of the hits or veto them, so the rays go through. Callbacks set before on the
+    /// objects run first.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the objects.
+    /// * `callback` - The callback, returning `false` to veto a hit.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())`, or a description of the error if no object has the name.
+    pub fn set_on_hit(&mut self, name: &str, callback: Arc<HitCallback>) -> Result<(), String> {
+        let mut found = false;
+        for (index, object_name) in self.object_names.iter().enumerate() {
+            if object_name.as_deref() == Some(name) {
+                let object =
+                    std::mem::replace(&mut self.objects[index], Box::new(HittableList::new()));
+                self.objects[index] = Box::new(OnHit::new(object, callback.clone()));
+                found = true;
+            }
+        }
+        if !found {
+            return Err(format!("no object named `{}`", name));
+        }
+        Ok(())
+    }
+
+    /// Returns the priority of an object over the surfaces it coincides with.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the object.
+    pub fn priority(&self, index: usize) -> i32 {
+        self.object_priorities[index]
+    }
+
+    /// Replaces the materials of named objects for the following renders, without rebuilding the
+    /// scene, so variations of a look can be rendered one after the other. Overrides replace
+    /// the ones set before. Lights are still chosen for sampling from the materials the objects
+    /// were built with, so emissive materials are best left alone.
+    ///
+    /// # Arguments
+    ///
+    /// * `overrides` - The replacement material of every overridden object, by name.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())`, or a description of the first name given to no object, in which case no
+    /// override is applied.
+    pub fn set_material_overrides(&mut self, overrides: &MaterialOverrides) -> Result<(), String> {
+        let mut resolved = HashMap::new();
+        for (name, material) in overrides {
+            let mut found = false;
+            for (index, object_name) in self.object_names.iter().enumerate() {
+                if object_name.as_deref() == Some(name.as_str()) {
+                    resolved.insert(index, material.clone());
+                    found = true;
+                }
+            }
+            if !found {
+                return Err(format!("no object named `{}`", name));
+            }
+        }
+        self.material_overrides = resolved;
+        Ok(())
+    }
+
+    /// Gives every object its own material back.
+    pub fn clear_material_overrides(&mut self) {
+        self.material_overrides.clear();
+    }
+
+    /// Links a named light to objects, so it only illuminates the listed objects, or every object
+    /// but them, such as a rim light meant for a single character. The light stays visible to
+    /// the camera and still casts shadows on every object. A new link of the light replaces the
+    /// one set before.
+    ///
+    /// # Arguments
+    ///
+    /// * `light` - The name of the light.
+    /// * `mode` - Whether the light illuminates only the listed objects or all but them.
+    /// * `objects` - The names of the objects.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())`, or a description of the error if a name is given to no object or the light is
+    /// not emissive, in which case no link is set.
+    pub fn set_light_link(
+        &mut self,
+        light: &str,
+        mode: LinkMode,
+        objects: &[&str],
+    ) -> Result<(), String> {
+        let lights = self.find_lights(light)?;
+        let objects = self.find_all(objects)?;
+        for index in lights {
+            let link = LightLink {
+                mode,
+                objects: objects.clone(),
+            };
+            self.light_links.insert(index, link);
+        }
+        Ok(())
+    }
+
+    /// Links a named object to lights, so it is only illuminated by the listed lights, or by every
+    /// light but them, such as a product kept out of the light meant for its backdrop. A new link
+    /// of the object replaces the one set before.
+    ///
+    /// # Arguments
+    ///
+    /// * `object` - The name of the object.
+    /// * `mode` - Whether the object is illuminated only by the listed lights or by all but them.
+    /// * `lights` - The names of the lights.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())`, or a description of the error if a name is given to no object or a listed light
+    /// is not emissive, in which case no link is set.
+    pub fn set_object_link(
+        &mut self,
+        object: &str,
+        mode: LinkMode,
+        lights: &[&str],
+    ) -> Result<(), String> {
+        let objects = self.find_all(&[object])?;
+        let mut linked = Vec::new();
+        for light in lights {
+            linked.extend(self.find_lights(light)?);
+        }
+        for index in objects {
+            let link = LightLink {
+                mode,
+                objects: linked.clone(),
+            };
+            self.object_links.insert(index, link);
+        }
+        Ok(())
+    }
+
+    /// Removes the links of every light and object, so lights illuminate every object again.
+    pub fn clear_light_links(&mut self) {
+        self.light_links.clear();
+        self.object_links.clear();
+    }
+
+    /// Returns `true` if a light illuminates an object, as set by the links of both.
+    ///
+    /// # Arguments
+    ///
+    /// * `light` - The index of the light.
+    /// * `object` - The index of the object.
+    pub fn illuminates(&self, light: usize, object: usize) -> bool {
+        if !self.has_light_links() {
+            return true;
+        }
+        self.light_links
+            .get(&light)
+            .is_none_or(|link| link.allows(object))
+            && self
+                .object_links
+                .get(&object)
+                .is_none_or(|link| link.allows(light))
+    }
+
+    /// Returns `true` if a light or an object has links.
+    fn has_light_links(&self) -> bool {
+        !self.light_links.is_empty() || !self.object_links.is_empty()
+    }
+
+    /// Sets the density of the shadows cast by named objects from a named light, trading
+    /// physical accuracy for art direction, such as to keep a hat from darkening a face. Light
+    /// reaching a surface through an object is scaled by one minus the density, while the object
+    /// itself is still seen and lit as before. Shadows from other lights are left alone.
+    ///
+    /// # Arguments
+    ///
+    /// * `light` - The name of the light.
+    /// * `objects` - The names of the objects casting the shadows.
+    /// * `density` - The density of the shadows, from 0 for no shadow to 1 for full shadows.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())`, or a description of the error if a name is given to no object or the light is
+    /// not emissive, in which case no density is set.
+    pub fn set_shadow_density(
+        &mut self,
+        light: &str,
+        objects: &[&str],
+        density: f64,
+    ) -> Result<(), String> {
+        let lights = self.find_lights(light)?;
+        let objects = self.find_all(objects)?;
+        let density = density.clamp(0.0, 1.0);
+        for &light in &lights {
+            for &object in &objects {
+                if density < 1.0 {
+                    self.shadow_densities.insert((light, object), density);
+                } else {
+                    self.shadow_densities.remove(&(light, object));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Gives every object full shadows from every light again.
+    pub fn clear_shadow_densities(&mut self) {
+        self.shadow_densities.clear();
+    }
+
+    /// Returns the density of the shadow an object casts from a light, 1 unless lightened.
+    ///
+    /// # Arguments
+    ///
+    /// * `light` - The index of the light.
+    /// * `object` - The index of the object.
+    pub fn shadow_density(&self, light: usize, object: usize) -> f64 {
+        self.shadow_densities
+            .get(&(light, object))
+            .copied()
+            .unwrap_or(1.0)
+    }
+
+    /// Returns `true` if an object casts a lightened shadow from at least one light.
+    ///
+    /// # Arguments
+    ///
+    /// * `object` - The index of the object.
+    pub fn lightens_shadows(&self, object: usize) -> bool {
+        self.shadow_densities
+            .keys()
+            .any(|&(_, index)| index == object)
+    }
+
+    /// Returns `true` if some object casts a lightened shadow.
+    pub fn has_shadow_densities(&self) -> bool {
+        !self.shadow_densities.is_empty()
+    }
+
+    /// Finds every object with one of the given names.
+    ///
+    /// # Arguments
+    ///
+    /// * `names` - The names of the objects.
+    ///
+    /// # Returns
+    ///
+    /// The indices of the objects, or a description of the first name given to no object.
+    fn find_all(&self, names: &[&str]) -> Result<Vec<usize>, String> {
+        let mut indices = Vec::new();
+        for name in names {
+            let before = indices.len();
+            indices.extend(
+                self.object_names
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, object_name)| object_name.as_deref() == Some(*name))
+                    .map(|(index, _)| index),
+            );
+            if indices.len() == before {
+                return Err(format!("no object named `{}`", name));
+            }
+        }
+        Ok(indices)
+    }
+
+    /// Finds every light with a name.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the lights.
+    ///
+    /// # Returns
+    ///
+    /// The indices of the lights, or a description of the error if no object has the name or
+    /// one of the objects is not emissive.
+    fn find_lights(&self, name: &str) -> Result<Vec<usize>, String> {
+        let indices = self.find_all(&[name])?;
+        if indices.iter().any(|index| !self.lights.contains(index)) {
+            return Err(format!("`{}` is not a light", name));
+        }
+        Ok(indices)
+    }
+
+    /// Finds the emissive objects of the scene and the probability of choosing each of them.
+    fn update_lights(&mut self) {
+        self.lights = self
+            .objects
+            .iter()
+            .enumerate()
+            .filter(|(_, object)| object.is_emissive())
+            .map(|(index, _)| index)
+            .collect();
+
+        let powers: Vec<f64> = self
+            .lights
+            .iter()
+            .map(|&index| self.objects[index].power().luminance().max(0.0))
+            .collect();
+        let total: f64 = powers.iter().sum();
+
+        self.light_weights = if total > 0.0 {
+            powers.iter().map(|power| power / total).collect()
+        } else {
+            vec![1.0 / self.lights.len() as f64; self.lights.len()]
+        };
+    }
+
+    /// Returns the names of the render layers used by the objects, in order of first use.
+    pub fn layers(&self) -> Vec<&str> {
+        let mut layers: Vec<&str> = Vec::new();
+        for layer in &self.object_layers {
+            if !layers.contains(&layer.as_str()) {
+                layers.push(layer);
+            }
+        }
+        layers
+    }
+
+    /// Selects the render layers seen by camera rays. Objects of the other layers are held out:
+    /// they appear black to the camera, but still cast shadows, block light and show up in
+    /// reflections and refractions of the selected objects.
+    ///
+    /// # Arguments
+    ///
+    /// * `layers` - The names of the selected layers.
+    pub fn set_visible_layers(&mut self, layers: &[&str]) {
+        self.visible_layers = Some(layers.iter().map(|layer| layer.to_string()).collect());
+    }
+
+    /// Makes every render layer visible to camera rays again.
+    pub fn show_all_layers(&mut self) {
+        self.visible_layers = None;
+    }
+
+    /// Returns `true` if the object with the given index is seen by camera rays.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the object.
+    pub fn is_visible(&self, index: usize) -> bool {
+        match &self.visible_layers {
+            Some(layers) => layers.contains(&self.object_layers[index]),
+            None => true,
+        }
+    }
+
+    /// Returns an iterator over the objects of the scene, in the order in which they were added.
+    pub fn objects(&self) -> impl Iterator<Item = &dyn Hittable> {
+        self.objects.iter().map(|object| object.as_ref())
+    }
+
+    /// Follows a point on the surface of an object from one scene time to another.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the object.
+    /// * `point` - The point of the surface at the first time.
+    /// * `from` - The scene time at which the point is given.
+    /// * `to` - The scene time to follow the point to.
+    ///
+    /// # Returns
+    ///
+    /// The position of the same point of the surface at the second time.
+    pub fn point_at_time(&self, index: usize, point: &Vector3, from: f64, to: f64) -> Vector3 {
+        self.objects[index].point_at_time(point, from, to)
+    }
+
+    /// Returns the box enclosing the objects of the scene seen by camera rays, leaving out the
+    /// objects without bounds, such as infinite planes.
+    ///
+    /// # Returns
+    ///
+    /// An `Option` containing the box, or `None` if no visible object has bounds.
+    pub fn bounding_box(&self) -> Option<Aabb> {
+        self.objects
+            .iter()
+            .enumerate()
+            .filter(|&(index, _)| self.is_visible(index))
+            .filter_map(|(_, object)| object.bounding_box())
+            .reduce(|a, b| a.merge(&b))
+    }
+
+    /// Returns an iterator over the emissive objects of the scene.
+    pub fn lights(&self) -> impl Iterator<Item = &dyn Hittable> {
+        self.lights
+            .iter()
+            .map(|&index| self.objects[index].as_ref())
+    }
+
+    /// Returns `true` if the scene contains at least one emissive object.
+    pub fn has_lights(&self) -> bool {
+        !self.lights.is_empty()
+    }
+
+    /// Returns `true` if the object with the given index is emissive.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the object.
+    pub fn is_light(&self, index: usize) -> bool {
+        self.lights.contains(&index)
+    }
+
+    /// Counts the objects, primitives, materials and textures of the scene and the memory they
+    /// take.
+    ///
+    /// # Returns
+    ///
+    /// The statistics of the scene.
+    pub fn statistics(&self) -> SceneStatistics {
+        let mut statistics = SceneStatistics::new();
+        statistics.objects = self.objects.len();
+        statistics.lights = self.lights.len();
+        for object in &self.objects {
+            object.collect_statistics(&mut statistics);
+        }
+        statistics
+    }
+
+    /// Collects the materials of the objects of the scene and their overrides into a table, with
+    /// which records of hits can be stored and shaded later.
+    ///
+    /// # Returns
+    ///
+    /// The materials of the scene.
+    pub fn material_table(&self) -> MaterialTable {
+        let mut table = MaterialTable::new();
+        for object in &self.objects {
+            object.collect_materials(&mut table);
+        }
+        for material in self.material_overrides.values() {
+            table.add(material);
+        }
+        table
+    }
+
+    /// Finds the object seen through the center of a pixel, for interactive tools such as click to
+    /// select or click to focus.
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - The x-coordinate of the pixel.
+    /// * `y` - The y-coordinate of the pixel.
+    /// * `camera` - The camera through which the scene is seen.
+    ///
+    /// # Returns
+    ///
+    /// A `PickResult` describing the closest object, or `None` if the pixel shows the background
+    /// or an object whose layer is hidden from the camera.
+    pub fn pick(&self, x: u32, y: u32, camera: &Camera) -> Option<PickResult> {
+        let ray = camera.pixel_ray(x, y);
+        let (near, far) = camera.clip_interval(&ray);
+        let (index, record) = self.hit_object(&ray, (near.max(self.epsilon()), far))?;
+        if !self.is_visible(index) {
+            return None;
+        }
+        Some(PickResult {
+            object: index,
+            layer: self.object_layers[index].clone(),
+            distance: record.t,
+            point: record.poz,
+            normal: record.normal,
+            material: record.material.name(),
+        })
+    }
+
+    /// Finds the closest object hit by a ray within a given interval, along with its index.
+    ///
+    /// # Arguments
+    ///
+    /// * `ray` - The ray to test for intersection.
+    /// * `interval` - The range of distances to consider for intersections.
+    ///
+    /// # Returns
+    ///
+    /// An `Option` containing the index of the closest object and its `HitRecord`, or `None` if
+    /// no object is hit. Of surfaces hit within the scene epsilon of each other, the one with the
+    /// highest priority is returned. The material of the record is the override of the object,
+    /// if any.
+    pub fn hit_object(&self, ray: &Ray, interval: (f64, f64)) -> Option<(usize, HitRecord<'_>)> {
+        hot_span!("traverse");
+        let tolerance = self.epsilon() / ray.direction.length();
+        let (index, mut record) = self
+            .objects
+            .iter()
+            .enumerate()
+            .filter_map(|(index, object)| object.hit(ray, interval).map(|record| (index, record)))
+            .min_by(|(i1, r1), (i2, r2)| {
+                let (p1, p2) = (self.object_priorities[*i1], self.object_priorities[*i2]);
+                if p1 != p2 && (r1.t - r2.t).abs() <= tolerance {
+                    p2.cmp(&p1)
+                } else {
+                    r1.t.partial_cmp(&r2.t).unwrap_or(Ordering::Equal)
+                }
+            })?;
+        if let Some(material) = self.material_overrides.get(&index) {
+            record.material = material.as_ref();
+        }
+        Some((index, record))
+    }
+}
+
+/// A density of directions pointing from a point towards the lights of a scene.
+pub struct LightPdf<'a> {
+    /// The scene whose lights are sampled.
+    scene: &'a Scene,
+    /// The point from which the lights are sampled.
+    origin: Vector3,
+    /// The index of the object the point lies on, whose light links choose the lights sampled,
+    /// or `None` to sample every light.
+    receiver: Option<usize>,
+    /// The sum of the probabilities of choosing the lights sampled.
+    total_weight: f64,
+}
+
+impl<'a> LightPdf<'a> {
+    /// Creates a new `LightPdf`.
+    ///
+    /// # Arguments
+    ///
+    /// * `scene` - The scene whose lights are sampled, which must contain at least one light.
+    /// * `origin` - The point from which the lights are sampled.
+    ///
+    /// # Returns
+    ///
+    /// A new `LightPdf` instance.
+    pub fn new(scene: &'a Scene, origin: Vector3) -> LightPdf<'a> {
+        LightPdf {
+            scene,
+            origin,
+            receiver: None,
+            total_weight: 1.0,
+        }
+    }
+
+    /// Samples only the lights illuminating the object the point lies on, as set by light links.
+    ///
+    /// # Arguments
+    ///
+    /// * `object` - The index of the object.
+    ///
+    /// # Returns
+    ///
+    /// The updated `LightPdf` instance.
+    pub fn with_receiver(mut self, object: usize) -> LightPdf<'a> {
+        if !self.scene.has_light_links() {
+            return self;
+        }
+        self.receiver = Some(object);
+        self.total_weight = self.sampled().map(|(_, weight)| weight).sum();
+        self
+    }
+
+    /// Returns `true` if at least one light illuminates the point.
+    pub fn has_lights(&self) -> bool {
+        self.sampled().next().is_some()
+    }
+
+    /// Returns the lights sampled and the probability of choosing each of them among all lights.
+    fn sampled(&self) -> impl Iterator<Item = (&'a dyn Hittable, f64)> + '_ {
+        let scene = self.scene;
+        scene
+            .lights
+            .iter()
+            .zip(&scene.light_weights)
+            .filter(move |(&light, _)| {
+                self.receiver
+                    .is_none_or(|object| scene.illuminates(light, object))
+            })
+            .map(move |(&light, &weight)| (scene.objects[light].as_ref(), weight))
+    }
+}
+
+impl Pdf for LightPdf<'_> {
+    fn value(&self, direction: &Vector3) -> f64 {
+        if self.total_weight <= 0.0 {
+            return 0.0;
+        }
+        self.sampled()
+            .map(|(light, weight)| weight * light.pdf_value(&self.origin, direction))
+            .sum::<f64>()
+            / self.total_weight
+    }
+
+    fn generate(&self) -> Vector3 {
+        let mut choice = fastrand::f64() * self.total_weight;
+        for (light, weight) in self.sampled() {
+            if choice < weight {
+                return light.random(&self.origin);
+            }
+            choice -= weight;
+        }
+
+        let (last, _) = self
+            .sampled()
+            .last()
+            .expect("no light illuminates the point");
+        last.random(&self.origin)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::{DiffuseLight, Lambertian};
+    use crate::shapes::quad::Quad;
+    use crate::shapes::sphere::Sphere;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_scene_units() {
+        let mut scene = Scene::new(Vec::new());
+        assert_eq!(scene.unit(), Unit::Meters);
+        assert!((scene.epsilon() - 1e-3).abs() < 1e-15);
+
+        // A scene in millimeters skips the same length in more units, and light falls off
+        // through its media over more units.
+        scene.set_unit(Unit::Millimeters);
+        assert!((scene.epsilon() - 1.0).abs() < 1e-12);
+        // A large scene in millimeters skips the full millimeter.
+        let mut large = Scene::new(vec![Box::new(Sphere::new(
+            Vector3::new(0.0, 0.0, 0.0),
+            1e6,
+            Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5))),
+        ))]);
+        large.set_unit(Unit::Millimeters);
+        assert!((large.epsilon() - 1.0).abs() < 1e-12);
+        assert!((scene.density(10.0) - 0.01).abs() < 1e-15);
+
+        // A ball of 5 centimeters imported into the scene is 50 millimeters wide, then 100 with
+        // an import scale of 2.
+        let ball = || {
+            Arc::new(Sphere::new(
+                Vector3::new(0.0, 0.0, 0.0),
+                2.5,
+                Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5))),
+            ))
+        };
+        scene.add_imported(ball(), Unit::Centimeters, DEFAULT_LAYER);
+        let bounds = scene.bounding_box().unwrap();
+        assert!((bounds.max.x - bounds.min.x - 50.0).abs() < 1e-9);
+        scene.set_import_scale(2.0);
+        assert!((scene.import_scale(Unit::Centimeters) - 20.0).abs() < 1e-12);
+        scene.add_imported(ball(), Unit::Centimeters, DEFAULT_LAYER);
+        let bounds = scene.bounding_box().unwrap();
+        assert!((bounds.max.x - bounds.min.x - 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_discovers_emissive_objects() {
+        let scene = Scene::new(vec![
+            Box::new(Sphere::new(
+                Vector3::new(0.0, 0.0, 0.0),
+                1.0,
+                Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5))),
+            )),
+            Box::new(Quad::new(
+                Vector3::new(-1.0, 3.0, -1.0),
+                Vector3::new(2.0, 0.0, 0.0),
+                Vector3::new(0.0, 0.0, 2.0),
+                Arc::new(DiffuseLight::new(Vector3::new(4.0, 4.0, 4.0))),
+            )),
+            Box::new(Sphere::new(
+                Vector3::new(3.0, 0.0, 0.0),
+                0.5,
+                Arc::new(DiffuseLight::new(Vector3::new(1.0, 1.0, 1.0))),
+            )),
+        ]);
+
+        assert_eq!(scene.lights().count(), 2);
+        assert!((scene.light_weights.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+
+        // The quad emits 4 over an area of 4 and the sphere emits 1 over an area of pi.
+        let quad_power = 16.0;
+        let sphere_power = std::f64::consts::PI;
+        let expected = quad_power / (quad_power + sphere_power);
+        assert!((scene.light_weights[0] - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_light_pdf_integrates_to_one() {
+        let scene = Scene::new(vec![Box::new(Quad::new(
+            Vector3::new(-1.0, 2.0, -1.0),
+            Vector3::new(2.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, 2.0),
+            Arc::new(DiffuseLight::new(Vector3::new(1.0, 1.0, 1.0))),
+        ))]);
+        let pdf = LightPdf::new(&scene, Vector3::new(0.0, 0.0, 0.0));
+
+        let samples = 200_000;
+        let integral = (0..samples)
+            .map(|_| pdf.value(&Vector3::random_unit_vector()) * 4.0 * std::f64::consts::PI)
+            .sum::<f64>()
+            / samples as f64;
+        assert!((integral - 1.0).abs() < 0.02, "{}", integral);
+
+        for _ in 0..1000 {
+            assert!(pdf.value(&pdf.generate()) > 0.0);
+        }
+    }
+
+    #[test]
+    fn test_light_links() {
+        let lamp = |x: f64| -> Box<dyn Hittable> {
+            Box::new(Quad::new(
+                Vector3::new(x - 0.5, 4.0, -0.5),
+                Vector3::new(1.0, 0.0, 0.0),
+                Vector3::new(0.0, 0.0, 1.0),
+                Arc::new(DiffuseLight::new(Vector3::new(1.0, 1.0, 1.0))),
+            ))
+        };
+        let ball = |x: f64| -> Box<dyn Hittable> {
+            Box::new(Sphere::new(
+                Vector3::new(x, 0.0, 0.0),
+                1.0,
+                Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5))),
+            ))
+        };
+        let mut scene = Scene::new(Vec::new());
+        scene.add_named("left", lamp(-3.0), DEFAULT_LAYER);
+        scene.add_named("right", lamp(3.0), DEFAULT_LAYER);
+        scene.add_named("hero", ball(0.0), DEFAULT_LAYER);
+        scene.add_named("extra", ball(5.0), DEFAULT_LAYER);
+        assert!((0..2).all(|light| (2..4).all(|object| scene.illuminates(light, object))));
+
+        // The left lamp only lights the hero, and the extra is kept out of the right lamp.
+        scene
+            .set_light_link("left", LinkMode::Include, &["hero"])
+            .unwrap();
+        scene
+            .set_object_link("extra", LinkMode::Exclude, &["right"])
+            .unwrap();
+        assert!(scene.illuminates(0, 2) && scene.illuminates(1, 2));
+        assert!(!scene.illuminates(0, 3) && !scene.illuminates(1, 3));
+
+        // Only the lights illuminating the receiver are sampled.
+        let origin = Vector3::new(0.0, 1.0, 0.0);
+        let towards_left = Vector3::new(-3.0, 3.0, 0.0);
+        let pdf = LightPdf::new(&scene, origin).with_receiver(2);
+        assert!(pdf.has_lights() && pdf.value(&towards_left) > 0.0);
+        let unlinked = LightPdf::new(&scene, origin).with_receiver(3);
+        assert!(!unlinked.has_lights() && unlinked.value(&towards_left) == 0.0);
+        scene
+            .set_object_link("extra", LinkMode::Include, &["right"])
+            .unwrap();
+        let pdf = LightPdf::new(&scene, origin).with_receiver(3);
+        for _ in 0..100 {
+            assert!(pdf.generate().x > 0.0);
+        }
+        assert_eq!(pdf.value(&towards_left), 0.0);
+
+        assert_eq!(
+            scene.set_light_link("hero", LinkMode::Include, &["extra"]),
+            Err("`hero` is not a light".to_string())
+        );
+        assert_eq!(
+            scene.set_object_link("hero", LinkMode::Exclude, &["lamp"]),
+            Err("no object named `lamp`".to_string())
+        );
+        scene.clear_light_links();
+        assert!(scene.illuminates(0, 3));
+    }
+
+    #[test]
+    fn test_shadow_densities() {
+        let mut scene = Scene::new(Vec::new());
+        scene.add_named(
+            "lamp",
+            Box::new(Quad::new(
+                Vector3::new(-0.5, 4.0, -0.5),
+                Vector3::new(1.0, 0.0, 0.0),
+                Vector3::new(0.0, 0.0, 1.0),
+                Arc::new(DiffuseLight::new(Vector3::new(1.0, 1.0, 1.0))),
+            )),
+            DEFAULT_LAYER,
+        );
+        scene.add_named(
+            "hat",
+            Box::new(Sphere::new(
+                Vector3::new(0.0, 2.0, 0.0),
+                0.5,
+                Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5))),
+            )),
+            DEFAULT_LAYER,
+        );
+        assert!(scene.is_light(0) && !scene.is_light(1));
+        assert!(!scene.has_shadow_densities());
+        assert_eq!(scene.shadow_density(0, 1), 1.0);
+
+        scene.set_shadow_density("lamp", &["hat"], -0.5).unwrap();
+        assert!(scene.has_shadow_densities() && scene.lightens_shadows(1));
+        assert_eq!(scene.shadow_density(0, 1), 0.0);
+        assert!(!scene.lightens_shadows(0));
+
+        // Full shadows need no density.
+        scene.set_shadow_density("lamp", &["hat"], 1.0).unwrap();
+        assert!(!scene.has_shadow_densities());
+
+        assert_eq!(
+            scene.set_shadow_density("hat", &["lamp"], 0.5),
+            Err("`hat` is not a light".to_string())
+        );
+        assert_eq!(
+            scene.set_shadow_density("lamp", &["cap"], 0.5),
+            Err("no object named `cap`".to_string())
+        );
+    }
+
+    #[test]
+    fn test_render_layers() {
+        let material = Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5)));
+        let mut scene = Scene::new(vec![Box::new(Sphere::new(
+            Vector3::new(0.0, 0.0, -5.0),
+            1.0,
+            material.clone(),
+        ))]);
+        scene.add(
+            Box::new(Sphere::new(Vector3::new(0.0, 0.0, -10.0), 1.0, material)),
+            "background",
+        );
+        scene.add(
+            Box::new(Sphere::new(
+                Vector3::new(5.0, 0.0, 0.0),
+                1.0,
+                Arc::new(DiffuseLight::new(Vector3::new(1.0, 1.0, 1.0))),
+            )),
+            "background",
+        );
+
+        assert_eq!(scene.layers(), vec![DEFAULT_LAYER, "background"]);
+        assert_eq!(scene.lights().count(), 1);
+        assert!((0..3).all(|index| scene.is_visible(index)));
+
+        scene.set_visible_layers(&["background"]);
+        assert!(!scene.is_visible(0));
+        assert!(scene.is_visible(1) && scene.is_visible(2));
+
+        // Hidden objects still block rays.
+        let ray = Ray::new(Vector3::new(0.0, 0.0, 0.0), Vector3::new(0.0, 0.0, -1.0));
+        let (index, _) = scene.hit_object(&ray, (0.001, f64::INFINITY)).unwrap();
+        assert_eq!(index, 0);
+
+        scene.show_all_layers();
+        assert!(scene.is_visible(0));
+    }
+
+    #[test]
+    fn test_pick() {
+        let mut scene = Scene::new(vec![Box::new(Sphere::new(
+            Vector3::new(0.0, 0.0, -5.0),
+            1.0,
+            Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5))),
+        ))]);
+        scene.add(
+            Box::new(Quad::new(
+                Vector3::new(-50.0, -50.0, -10.0),
+                Vector3::new(100.0, 0.0, 0.0),
+                Vector3::new(0.0, 100.0, 0.0),
+                Arc::new(DiffuseLight::new(Vector3::new(1.0, 1.0, 1.0))),
+            )),
+            "background",
+        );
+        let camera = Camera::new(
+            21,
+            1.0,
+            1,
+            1,
+            |_| Vector3::new(0.0, 0.0, 0.0),
+            90.0,
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, -1.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            0.0,
+            1.0,
+        );
+
+        let center = scene.pick(10, 10, &camera).unwrap();
+        assert_eq!(center.object, 0);
+        assert_eq!(center.layer, DEFAULT_LAYER);
+        assert_eq!(center.material, "Lambertian");
+        assert!((center.distance - 4.0).abs() < 1e-9);
+        assert!((center.point - Vector3::new(0.0, 0.0, -4.0)).length() < 1e-9);
+        assert!((center.normal - Vector3::new(0.0, 0.0, 1.0)).length() < 1e-9);
+
+        let corner = scene.pick(0, 0, &camera).unwrap();
+        assert_eq!(corner.object, 1);
+        assert_eq!(corner.material, "DiffuseLight");
+
+        scene.set_visible_layers(&[DEFAULT_LAYER]);
+        assert!(scene.pick(0, 0, &camera).is_none());
+    }
+
+    #[test]
+    fn test_on_hit_vetoes_named_objects() {
+        let quad = |z: f64| -> Box<dyn Hittable> {
+            Box::new(Quad::new(
+                Vector3::new(-1.0, -1.0, z),
+                Vector3::new(2.0, 0.0, 0.0),
+                Vector3::new(0.0, 2.0, 0.0),
+                Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5))),
+            ))
+        };
+        let mut scene = Scene::new(Vec::new());
+        scene.add_named("screen", quad(-2.0), DEFAULT_LAYER);
+        scene.add_named("wall", quad(-4.0), DEFAULT_LAYER);
+        // The screen lets rays through its left half.
+        let left_half: Arc<HitCallback> =
+            Arc::new(|_: &Ray, record: &mut HitRecord| record.u > 0.5);
+        assert!(scene.set_on_hit("curtain", left_half.clone()).is_err());
+        scene.set_on_hit("screen", left_half).unwrap();
+
+        let origin = Vector3::new(0.0, 0.0, 0.0);
+        let left = Ray::new(origin, Vector3::new(-0.2, 0.0, -1.0));
+        let right = Ray::new(origin, Vector3::new(0.2, 0.0, -1.0));
+        assert_eq!(
+            scene.hit_object(&left, (0.001, f64::INFINITY)).unwrap().0,
+            1
+        );
+        assert_eq!(
+            scene.hit_object(&right, (0.001, f64::INFINITY)).unwrap().0,
+            0
+        );
+        assert_eq!(scene.find("screen"), Some(0));
+    }
+
+    #[test]
+    fn test_coplanar_priority() {
+        let quad = |z: f64, size: f64| -> Box<dyn Hittable> {
+            Box::new(Quad::new(
+                Vector3::new(-size, -size, z),
+                Vector3::new(2.0 * size, 0.0, 0.0),
+                Vector3::new(0.0, 2.0 * size, 0.0),
+                Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5))),
+            ))
+        };
+        // The decal lies a hair behind the wall, well within the scene epsilon.
+        let mut scene = Scene::new(Vec::new());
+        scene.add_named("decal", quad(-5.0, 1.0), DEFAULT_LAYER);
+        scene.add_named("wall", quad(-4.9999, 10.0), DEFAULT_LAYER);
+        let rays: Vec<Ray> = (0..20)
+            .map(|i| {
+                let x = (i as f64 / 20.0 - 0.5) * 0.3;
+                Ray::new(Vector3::new(0.0, 0.0, 0.0), Vector3::new(x, x / 3.0, -1.0))
+            })
+            .collect();
+        assert!(rays
+            .iter()
+            .all(|ray| scene.hit_object(ray, (0.001, f64::INFINITY)).unwrap().0 == 1));
+
+        assert!(scene.set_priority("poster", 1).is_err());
+        scene.set_priority("decal", 1).unwrap();
+        assert_eq!((scene.priority(0), scene.priority(1)), (1, 0));
+        for ray in &rays {
+            let (index, record) = scene.hit_object(ray, (0.001, f64::INFINITY)).unwrap();
+            assert_eq!(index, 0);
+            assert!((record.poz.z + 5.0).abs() < 1e-9);
+        }
+
+        // Past the decal and past the epsilon, the wall is still seen.
+        let beside = Ray::new(Vector3::new(0.0, 0.0, 0.0), Vector3::new(0.4, 0.0, -1.0));
+        assert_eq!(
+            scene.hit_object(&beside, (0.001, f64::INFINITY)).unwrap().0,
+            1
+        );
+        let mut scene = Scene::new(Vec::new());
+        scene.add_named("decal", quad(-5.0, 1.0), DEFAULT_LAYER);
+        scene.add_named("wall", quad(-4.99, 10.0), DEFAULT_LAYER);
+        scene.set_priority("decal", 1).unwrap();
+        assert!(rays
+            .iter()
+            .all(|ray| scene.hit_object(ray, (0.001, f64::INFINITY)).unwrap().0 == 1));
+    }
+
+    #[test]
+    fn test_material_overrides() {
+        let mut scene = Scene::new(vec![Box::new(Sphere::new(
+            Vector3::new(0.0, 0.0, -10.0),
+            1.0,
+            Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5))),
+        ))]);
+        scene.add_named(
+            "subject",
+            Box::new(Sphere::new(
+                Vector3::new(0.0, 0.0, -5.0),
+                1.0,
+                Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5))),
+            )),
+            DEFAULT_LAYER,
+        );
+        assert_eq!(scene.find("subject"), Some(1));
+        assert_eq!(scene.find("backdrop"), None);
+
+        let ray = Ray::new(Vector3::new(0.0, 0.0, 0.0), Vector3::new(0.0, 0.0, -1.0));
+        let material = |scene: &Scene| {
+            let (_, record) = scene.hit_object(&ray, (0.001, f64::INFINITY)).unwrap();
+            record.material.name()
+        };
+
+        let mut overrides = MaterialOverrides::new();
+        overrides.insert(
+            "subject".to_string(),
+            Arc::new(DiffuseLight::new(Vector3::new(1.0, 1.0, 1.0))),
+        );
+        scene.set_material_overrides(&overrides).unwrap();
+        assert_eq!(material(&scene), "DiffuseLight");
+
+        overrides.insert(
+            "backdrop".to_string(),
+            Arc::new(Lambertian::new(Vector3::new(0.1, 0.1, 0.1))),
+        );
+        assert_eq!(
+            scene.set_material_overrides(&overrides),
+            Err("no object named `backdrop`".to_string())
+        );
+        assert_eq!(material(&scene), "DiffuseLight");
+
+        scene.clear_material_overrides();
+        assert_eq!(material(&scene), "Lambertian");
+    }
+
+    #[test]
+    fn test_rays_near_corners_hit_the_walls() {
+        // The Cornell box is modeled in millimeters and is 555 millimeters wide, so a ray leaving
+        // the floor half a millimeter from the green wall hits that wall.
+        let scene = crate::scenes::cornell_box_scene();
+        assert_eq!(scene.unit(), Unit::Millimeters);
+        assert!(scene.epsilon() < 0.01);
+        for (origin, direction, wall) in [
+            (
+                Vector3::new(554.5, 0.0, 300.0),
+                Vector3::new(1.0, 1.0, 0.0),
+                Vector3::new(1.0, 0.0, 0.0),
+            ),
+            (
+                Vector3::new(0.5, 0.0, 300.0),
+                Vector3::new(-1.0, 1.0, 0.0),
+                Vector3::new(1.0, 0.0, 0.0),
+            ),
+            (
+                Vector3::new(300.0, 554.5, 554.5),
+                Vector3::new(0.0, 1.0, 1.0),
+                Vector3::new(0.0, 1.0, 0.0),
+            ),
+        ] {
+            let ray = Ray::new(origin, direction.normalize());
+            let (_, record) = scene
+                .hit_object(&ray, (scene.epsilon(), f64::INFINITY))
+                .unwrap();
+            assert!(record.t < 1.0, "{}", record.t);
+            assert!(record.normal.dot(&wall).abs() > 0.999);
+        }
+    }
+}
@@ -0,0 +1,701 @@
+//! A lightweight scene graph of named nodes, each placing its object and its children relative to
+//! its parent, so hierarchies such as rigs and the nodes of glTF files can be built and rearranged
+//! before being flattened into the `Instance` wrappers a `Scene` renders.
+
+use crate::hit::Hittable;
+use crate::material::Material;
+use crate::scene::{Scene, DEFAULT_LAYER};
+use crate::shapes::cleanup::{MeshData, WELD_TOLERANCE};
+use crate::transformation::{Instance, Placement};
+use crate::vector3::Vector3;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Identifies a node of a `SceneGraph`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NodeId(usize);
+
+/// Represents the placement of a node relative to its parent: a rotation around any axis and a
+/// uniform scale, followed by a translation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NodeTransform {
+    /// The rotation, scale and translation of the node.
+    placement: Placement,
+}
+
+impl Default for NodeTransform {
+    fn default() -> NodeTransform {
+        NodeTransform::new(Vector3::new(0.0, 0.0, 0.0))
+    }
+}
+
+impl NodeTransform {
+    /// Creates a new `NodeTransform` moving the node by an offset, without rotation or scale.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - The translation of the node.
+    ///
+    /// # Returns
+    ///
+    /// A new `NodeTransform` instance.
+    pub fn new(offset: Vector3) -> NodeTransform {
+        NodeTransform {
+            placement: Placement::new(offset),
+        }
+    }
+
+    /// Rotates the node around an axis through its origin, after any rotation set before.
+    ///
+    /// # Arguments
+    ///
+    /// * `axis` - The axis of the rotation, which does not have to be normalized.
+    /// * `angle` - The rotation angle in degrees.
+    ///
+    /// # Returns
+    ///
+    /// The updated `NodeTransform` instance.
+    pub fn with_rotation(mut self, axis: Vector3, angle: f64) -> NodeTransform {
+        self.placement = self.placement.rotated(axis, angle);
+        self
+    }
+
+    /// Scales the node uniformly around its origin.
+    ///
+    /// # Arguments
+    ///
+    /// * `scale` - The scale factor, greater than zero.
+    ///
+    /// # Returns
+    ///
+    /// The updated `NodeTransform` instance.
+    pub fn with_scale(mut self, scale: f64) -> NodeTransform {
+        self.placement = self.placement.scaled(scale);
+        self
+    }
+
+    /// Returns the translation of the node, where its origin lands in the space of its parent.
+    pub fn offset(&self) -> Vector3 {
+        self.placement.offset()
+    }
+
+    /// Applies the transform to a point.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The point in the space of the node.
+    ///
+    /// # Returns
+    ///
+    /// The point in the space of the parent.
+    pub fn apply(&self, point: &Vector3) -> Vector3 {
+        self.placement.point_to_world(point)
+    }
+
+    /// Combines the transform with the transform of a child node.
+    ///
+    /// # Arguments
+    ///
+    /// * `child` - The transform of the child relative to this node.
+    ///
+    /// # Returns
+    ///
+    /// The transform of the child relative to the parent of this node.
+    pub fn then(&self, child: &NodeTransform) -> NodeTransform {
+        NodeTransform {
+            placement: self.placement.then(&child.placement),
+        }
+    }
+}
+
+/// Represents a node of a `SceneGraph`.
+struct Node {
+    /// The name of the node.
+    name: String,
+    /// The placement of the node relative to its parent.
+    transform: NodeTransform,
+    /// The object placed by the node, if any.
+    object: Option<Arc<dyn Hittable>>,
+    /// The parent of the node, or `None` for a root node.
+    parent: Option<NodeId>,
+    /// The children of the node, in the order in which they were attached.
+    children: Vec<NodeId>,
+}
+
+/// Represents a hierarchy of named nodes placing objects relative to each other.
+#[derive(Default)]
+pub struct SceneGraph {
+    /// Every node ever added, indexed by its `NodeId`.
+    nodes: Vec<Node>,
+}
+
+impl SceneGraph {
+    /// Creates a new empty `SceneGraph`.
+    ///
+    /// # Returns
+    ///
+    /// A new `SceneGraph` instance.
+    pub fn new() -> SceneGraph {
+        SceneGraph::default()
+    }
+
+    /// Loads the hierarchy of nodes of the default scene of a glTF file, or of its first scene.
+    /// Every node keeps its name, or is named after its index, and its translation, rotation and
+    /// scale relative to its parent. Nodes holding a mesh place it, with all its triangle
+    /// primitives merged and drawn in one material. Cameras, lights, skins and animations are
+    /// left out.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the `.gltf` or `.glb` file.
+    /// * `material` - The material of every mesh.
+    ///
+    /// # Returns
+    ///
+    /// The graph, or an error message if the file cannot be read, has no scene, or scales a node
+    /// unevenly, which node transforms cannot hold.
+    pub fn load(path: &Path, material: Arc<dyn Material>) -> Result<SceneGraph, String> {
+        let (document, buffers, _) = gltf::import(path)
+            .map_err(|e| format!("Failed to load glTF {}: {}", path.display(), e))?;
+        let scene = document
+            .default_scene()
+            .or_else(|| document.scenes().next())
+            .ok_or_else(|| format!("{}: no scene", path.display()))?;
+
+        // Nodes are added before their children, in the order of the file, and meshes placed by
+        // several nodes are built once.
+        let mut graph = SceneGraph::new();
+        let mut meshes: Vec<Option<Arc<dyn Hittable>>> = vec![None; document.meshes().len()];
+        let mut stack: Vec<(gltf::Node, Option<NodeId>)> =
+            scene.nodes().map(|node| (node, None)).collect();
+        stack.reverse();
+        while let Some((node, parent)) = stack.pop() {
+            let name = node
+                .name()
+                .map_or_else(|| format!("node {}", node.index()), str::to_string);
+            let transform = Self::gltf_transform(&node)
+                .ok_or_else(|| format!("{}: node `{}` is scaled unevenly", path.display(), name))?;
+            let object = node.mesh().and_then(|mesh| {
+                let index = mesh.index();
+                if meshes[index].is_none() {
+                    meshes[index] = Self::gltf_mesh(&mesh, &buffers, material.clone());
+                }
+                meshes[index].clone()
+            });
+            let id = graph.add(&name, parent, transform, object);
+            let mut children: Vec<_> = node.children().map(|child| (child, Some(id))).collect();
+            children.reverse();
+            stack.extend(children);
+        }
+        Ok(graph)
+    }
+
+    /// Reads the placement of a glTF node relative to its parent.
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The node.
+    ///
+    /// # Returns
+    ///
+    /// The transform of the node, or `None` if its scale differs between the axes.
+    fn gltf_transform(node: &gltf::Node) -> Option<NodeTransform> {
+        let (translation, rotation, scale) = node.transform().decomposed();
+        let [x, y, z, w] = rotation.map(|value| value as f64);
+        let scale = scale.map(|value| value as f64);
+        if scale
+            .iter()
+            .any(|s| (s - scale[0]).abs() > 1e-6 * scale[0].abs())
+        {
+            return None;
+        }
+
+        let translation = translation.map(|value| value as f64);
+        let transform =
+            NodeTransform::new(Vector3::new(translation[0], translation[1], translation[2]))
+                .with_scale(scale[0]);
+        // A unit quaternion holds the sine of half the angle along the axis and its cosine.
+        let sine = (x * x + y * y + z * z).sqrt();
+        if sine < 1e-12 {
+            return Some(transform);
+        }
+        let angle = 2.0 * sine.atan2(w);
+        Some(transform.with_rotation(Vector3::new(x, y, z), angle.to_degrees()))
+    }
+
+    /// Builds the triangle primitives of a glTF mesh into one mesh.
+    ///
+    /// # Arguments
+    ///
+    /// * `mesh` - The glTF mesh.
+    /// * `buffers` - The buffers of the file.
+    /// * `material` - The material of the mesh.
+    ///
+    /// # Returns
+    ///
+    /// The mesh, or `None` if it has no triangles.
+    fn gltf_mesh(
+        mesh: &gltf::Mesh,
+        buffers: &[gltf::buffer::Data],
+        material: Arc<dyn Material>,
+    ) -> Option<Arc<dyn Hittable>> {
+        let buffer = |buffer: gltf::Buffer| buffers.get(buffer.index()).map(|data| &data[..]);
+        let vector = |v: [f32; 3]| Vector3::new(v[0] as f64, v[1] as f64, v[2] as f64);
+        let mut data = MeshData::default();
+        let mut has_normals = true;
+        for primitive in mesh.primitives() {
+            if primitive.mode() != gltf::mesh::Mode::Triangles {
+                continue;
+            }
+            let reader = primitive.reader(buffer);
+            let Some(positions) = reader.read_positions() else {
+                continue;
+            };
+            let offset = data.positions.len();
+            data.positions.extend(positions.map(vector));
+            let count = data.positions.len() - offset;
+            match reader.read_normals() {
+                Some(normals) => data.normals.extend(normals.map(vector)),
+                None => has_normals = false,
+            }
+            match reader.read_tex_coords(0) {
+                Some(uvs) => data
+                    .uvs
+                    .extend(uvs.into_f32().map(|uv| (uv[0] as f64, 1.0 - uv[1] as f64))),
+                None => data.uvs.extend(std::iter::repeat_n((0.0, 0.0), count)),
+            }
+            let indices: Vec<usize> = match reader.read_indices() {
+                Some(indices) => indices.into_u32().map(|i| offset + i as usize).collect(),
+                None => (offset..offset + count).collect(),
+            };
+            data.triangles.extend(
+                indices
+                    .chunks_exact(3)
+                    .filter(|triangle| triangle.iter().all(|&i| i < offset + count))
+                    .map(|triangle| [triangle[0], triangle[1], triangle[2]]),
+            );
+        }
+        if !has_normals {
+            data.normals.clear();
+        }
+        // The cleanup may remove every triangle, as slivers, which a mesh cannot be built from.
+        data.clean(WELD_TOLERANCE);
+        if data.triangles.is_empty() {
+            return None;
+        }
+        Some(Arc::new(data.build(material).0))
+    }
+
+    /// Adds a node to the graph.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the node.
+    /// * `parent` - The parent of the node, or `None` to add a root node.
+    /// * `transform` - The placement of the node relative to its parent.
+    /// * `object` - The object placed by the node, or `None` for a group.
+    ///
+    /// # Returns
+    ///
+    /// The `NodeId` of the new node.
+    pub fn add(
+        &mut self,
+        name: &str,
+        parent: Option<NodeId>,
+        transform: NodeTransform,
+        object: Option<Arc<dyn Hittable>>,
+    ) -> NodeId {
+        let id = NodeId(self.nodes.len());
+        self.nodes.push(Node {
+            name: name.to_string(),
+            transform,
+            object,
+            parent,
+            children: Vec::new(),
+        });
+        if let Some(parent) = parent {
+            self.nodes[parent.0].children.push(id);
+        }
+        id
+    }
+
+    /// Finds a node by name.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the node.
+    ///
+    /// # Returns
+    ///
+    /// The `NodeId` of the first node added with the name, or `None` if there is none.
+    pub fn find(&self, name: &str) -> Option<NodeId> {
+        self.nodes
+            .iter()
+            .position(|node| node.name == name)
+            .map(NodeId)
+    }
+
+    /// Finds a node by the names of the nodes leading to it from a root, separated by `/`, such as
+    /// `rig/arm/hand`. Unlike `find`, this tells apart nodes sharing a name under different parents.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the node.
+    ///
+    /// # Returns
+    ///
+    /// The `NodeId` of the node, or `None` if no node has the path.
+    pub fn find_path(&self, path: &str) -> Option<NodeId> {
+        let mut names = path.split('/');
+        let root = names.next()?;
+        let mut current = self.roots().find(|&id| self.nodes[id.0].name == root)?;
+        for name in names {
+            current = *self.nodes[current.0]
+                .children
+                .iter()
+                .find(|child| self.nodes[child.0].name == name)?;
+        }
+        Some(current)
+    }
+
+    /// Returns an iterator over the root nodes of the graph.
+    pub fn roots(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, node)| node.parent.is_none())
+            .map(|(index, _)| NodeId(index))
+    }
+
+    /// Returns the name of a node.
+    pub fn name(&self, id: NodeId) -> &str {
+        &self.nodes[id.0].name
+    }
+
+    /// Returns the parent of a node, or `None` for a root node.
+    pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+        self.nodes[id.0].parent
+    }
+
+    /// Returns the children of a node.
+    pub fn children(&self, id: NodeId) -> &[NodeId] {
+        &self.nodes[id.0].children
+    }
+
+    /// Returns the placement of a node relative to its parent.
+    pub fn transform(&self, id: NodeId) -> NodeTransform {
+        self.nodes[id.0].transform
+    }
+
+    /// Changes the placement of a node relative to its parent, moving its children with it.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The node.
+    /// * `transform` - The new placement of the node.
+    pub fn set_transform(&mut self, id: NodeId, transform: NodeTransform) {
+        self.nodes[id.0].transform = transform;
+    }
+
+    /// Returns the placement of a node in world space, combining the transforms of its ancestors.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The node.
+    ///
+    /// # Returns
+    ///
+    /// The world transform of the node.
+    pub fn world_transform(&self, id: NodeId) -> NodeTransform {
+        let node = &self.nodes[id.0];
+        match node.parent {
+            Some(parent) => self.world_transform(parent).then(&node.transform),
+            None => node.transform,
+        }
+    }
+
+    /// Moves a node and its children under a new parent. The transform of the node is kept, so it
+    /// becomes relative to the new parent.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The node to move.
+    /// * `parent` - The new parent, or `None` to make the node a root node.
+    ///
+    /// # Returns
+    ///
+    /// `Ok` if the node was moved, or a description of why the move would create a cycle.
+    pub fn reparent(&mut self, id: NodeId, parent: Option<NodeId>) -> Result<(), String> {
+        let mut ancestor = parent;
+        while let Some(current) = ancestor {
+            if current == id {
+                return Err(format!(
+                    "cannot move `{}` under itself or one of its descendants",
+                    self.nodes[id.0].name
+                ));
+            }
+            ancestor = self.nodes[current.0].parent;
+        }
+
+        if let Some(old_parent) = self.nodes[id.0].parent {
+            self.nodes[old_parent.0]
+                .children
+                .retain(|&child| child != id);
+        }
+        if let Some(parent) = parent {
+            self.nodes[parent.0].children.push(id);
+        }
+        self.nodes[id.0].parent = parent;
+        Ok(())
+    }
+
+    /// Flattens the graph into world-space objects, placing every object by its world transform.
+    ///
+    /// # Returns
+    ///
+    /// The objects of the graph, in the order in which their nodes were added.
+    pub fn objects(&self) -> Vec<Box<dyn Hittable>> {
+        self.placed_objects()
+            .into_iter()
+            .map(|(_, object)| object)
+            .collect()
+    }
+
+    /// Flattens the graph into world-space objects, along with the nodes placing them.
+    ///
+    /// # Returns
+    ///
+    /// The node and the object of every node holding one, in the order in which they were added.
+    fn placed_objects(&self) -> Vec<(NodeId, Box<dyn Hittable>)> {
+        self.nodes
+            .iter()
+            .enumerate()
+            .filter_map(|(index, node)| {
+                let object = node.object.clone()?;
+                let world = self.world_transform(NodeId(index));
+                let placed: Box<dyn Hittable> = Box::new(Instance::placed(object, world.placement));
+                Some((NodeId(index), placed))
+            })
+            .collect()
+    }
+
+    /// Returns the path of a node: the names of the nodes leading to it from a root, separated by
+    /// `/`, as accepted by `find_path`.
+    pub fn path(&self, id: NodeId) -> String {
+        match self.nodes[id.0].parent {
+            Some(parent) => format!("{}/{}", self.path(parent), self.nodes[id.0].name),
+            None => self.nodes[id.0].name.clone(),
+        }
+    }
+
+    /// Builds a scene holding the objects of the graph, each named by the path of its node so
+    /// its material can be overridden.
+    ///
+    /// # Returns
+    ///
+    /// A new `Scene` instance.
+    pub fn to_scene(&self) -> Scene {
+        let mut scene = Scene::new(Vec::new());
+        for (id, object) in self.placed_objects() {
+            scene.add_named(&self.path(id), object, DEFAULT_LAYER);
+        }
+        scene
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Lambertian;
+    use crate::shapes::sphere::Sphere;
+
+    fn ball() -> Option<Arc<dyn Hittable>> {
+        let material = Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5)));
+        Some(Arc::new(Sphere::new(
+            Vector3::new(0.0, 0.0, 0.0),
+            0.5,
+            material,
+        )))
+    }
+
+    /// Returns the center of a flattened unit ball, found as the closest point to a far point
+    /// straight above it, moved down by the radius.
+    fn center(object: &dyn Hittable, near: Vector3) -> Vector3 {
+        let above = near + Vector3::new(0.0, 100.0, 0.0);
+        object.closest_point(&above).unwrap() - Vector3::new(0.0, 0.5, 0.0)
+    }
+
+    #[test]
+    fn test_world_transforms() {
+        let mut graph = SceneGraph::new();
+        let rig = graph.add(
+            "rig",
+            None,
+            NodeTransform::new(Vector3::new(10.0, 0.0, 0.0))
+                .with_rotation(Vector3::new(0.0, 1.0, 0.0), 90.0),
+            None,
+        );
+        let arm = graph.add(
+            "arm",
+            Some(rig),
+            NodeTransform::new(Vector3::new(2.0, 1.0, 0.0)),
+            ball(),
+        );
+        graph.add(
+            "hand",
+            Some(arm),
+            NodeTransform::new(Vector3::new(1.0, 0.0, 0.0)),
+            ball(),
+        );
+
+        // A rotation of 90 degrees turns +x into -z.
+        let expected = Vector3::new(10.0, 1.0, -3.0);
+        let hand = graph.world_transform(graph.find_path("rig/arm/hand").unwrap());
+        assert!((hand.offset() - expected).length() < 1e-9);
+        let forward = hand.apply(&Vector3::new(1.0, 0.0, 0.0)) - expected;
+        assert!((forward - Vector3::new(0.0, 0.0, -1.0)).length() < 1e-9);
+
+        let objects = graph.objects();
+        assert_eq!(objects.len(), 2);
+        assert!(
+            (center(objects[0].as_ref(), Vector3::new(10.0, 1.0, -2.0))
+                - Vector3::new(10.0, 1.0, -2.0))
+            .length()
+                < 1e-9
+        );
+        assert!((center(objects[1].as_ref(), expected) - expected).length() < 1e-9);
+        assert_eq!(graph.path(graph.find("hand").unwrap()), "rig/arm/hand");
+        let scene = graph.to_scene();
+        assert_eq!(scene.objects().count(), 2);
+        assert_eq!(scene.find("rig/arm/hand"), Some(1));
+    }
+
+    #[test]
+    fn test_find_and_reparent() {
+        let mut graph = SceneGraph::new();
+        let left = graph.add("left", None, NodeTransform::default(), None);
+        let right = graph.add(
+            "right",
+            None,
+            NodeTransform::new(Vector3::new(5.0, 0.0, 0.0)),
+            None,
+        );
+        let hand = graph.add(
+            "hand",
+            Some(left),
+            NodeTransform::new(Vector3::new(0.0, 1.0, 0.0)),
+            ball(),
+        );
+
+        assert_eq!(graph.find("hand"), Some(hand));
+        assert_eq!(graph.find("foot"), None);
+        assert_eq!(graph.find_path("right/hand"), None);
+        assert_eq!(graph.roots().collect::<Vec<_>>(), vec![left, right]);
+
+        graph.reparent(hand, Some(right)).unwrap();
+        assert_eq!(graph.parent(hand), Some(right));
+        assert!(graph.children(left).is_empty());
+        assert_eq!(graph.children(right), &[hand]);
+        assert_eq!(graph.find_path("right/hand"), Some(hand));
+        assert!(
+            (graph.world_transform(hand).offset() - Vector3::new(5.0, 1.0, 0.0)).length() < 1e-9
+        );
+
+        assert!(graph.reparent(right, Some(hand)).is_err());
+        assert!(graph.reparent(hand, Some(hand)).is_err());
+        graph.reparent(hand, None).unwrap();
+        assert_eq!(graph.roots().count(), 3);
+        assert_eq!(graph.name(hand), "hand");
+    }
+
+    #[test]
+    fn test_tilted_and_scaled_transforms() {
+        let mut graph = SceneGraph::new();
+        let base = graph.add(
+            "base",
+            None,
+            NodeTransform::new(Vector3::new(1.0, 0.0, 0.0))
+                .with_rotation(Vector3::new(0.0, 0.0, 1.0), 90.0)
+                .with_scale(2.0),
+            None,
+        );
+        let arm = graph.add(
+            "arm",
+            Some(base),
+            NodeTransform::new(Vector3::new(1.0, 0.0, 0.0))
+                .with_rotation(Vector3::new(0.0, 0.0, 1.0), -90.0),
+            ball(),
+        );
+
+        // The base turns +x into +y and doubles it, and the arm turns back, keeping the scale.
+        let world = graph.world_transform(arm);
+        assert!((world.offset() - Vector3::new(1.0, 2.0, 0.0)).length() < 1e-9);
+        let tip = world.apply(&Vector3::new(1.0, 0.0, 0.0));
+        assert!((tip - Vector3::new(3.0, 2.0, 0.0)).length() < 1e-9);
+
+        let bounds = graph.objects()[0].bounding_box().unwrap();
+        assert!((bounds.min - Vector3::new(0.0, 1.0, -1.0)).length() < 1e-6);
+        assert!((bounds.max - Vector3::new(2.0, 3.0, 1.0)).length() < 1e-6);
+    }
+
+    /// Writes a glTF file with a lamp node, turned by 90 degrees around the z-axis and scaled by
+    /// the given factors, holding a shade node with a triangle.
+    fn write_gltf(directory: &Path, scale: &str) -> std::path::PathBuf {
+        let mut bin: Vec<u8> = Vec::new();
+        let floats: [f32; 9] = [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0];
+        floats
+            .iter()
+            .for_each(|value| bin.extend(value.to_le_bytes()));
+        std::fs::write(directory.join("lamp.bin"), &bin).unwrap();
+
+        let json = r#"{
+            "asset": {"version": "2.0"},
+            "scene": 0,
+            "scenes": [{"nodes": [0]}],
+            "nodes": [
+                {
+                    "name": "lamp",
+                    "translation": [1, 0, 0],
+                    "rotation": [0, 0, 0.70710678, 0.70710678],
+                    "scale": SCALE,
+                    "children": [1]
+                },
+                {"name": "shade", "translation": [1, 0, 0], "mesh": 0}
+            ],
+            "meshes": [{"primitives": [{"attributes": {"POSITION": 0}}]}],
+            "buffers": [{"uri": "lamp.bin", "byteLength": 36}],
+            "bufferViews": [{"buffer": 0, "byteOffset": 0, "byteLength": 36}],
+            "accessors": [
+                {"bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3", "min": [0, 0, 0], "max": [1, 1, 0]}
+            ]
+        }"#
+        .replace("SCALE", scale);
+        let path = directory.join("lamp.gltf");
+        std::fs::write(&path, json).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_load_gltf() {
+        let directory =
+            std::env::temp_dir().join(format!("scene_graph_test_{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let material: Arc<dyn Material> = Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5)));
+        let loaded = SceneGraph::load(&write_gltf(&directory, "[2, 2, 2]"), material.clone());
+        let stretched = SceneGraph::load(&write_gltf(&directory, "[1, 2, 1]"), material.clone());
+        std::fs::remove_dir_all(&directory).unwrap();
+        let graph = loaded.unwrap();
+
+        let shade = graph.find_path("lamp/shade").unwrap();
+        let offset = graph.world_transform(shade).offset();
+        assert!((offset - Vector3::new(1.0, 2.0, 0.0)).length() < 1e-6);
+        let objects = graph.objects();
+        assert_eq!(objects.len(), 1);
+        let bounds = objects[0].bounding_box().unwrap();
+        assert!((bounds.min - Vector3::new(-1.0, 2.0, 0.0)).length() < 1e-3);
+        assert!((bounds.max - Vector3::new(1.0, 4.0, 0.0)).length() < 1e-3);
+
+        assert!(stretched.is_err_and(|e| e.contains("lamp")));
+        assert!(SceneGraph::load(&directory.join("missing.gltf"), material).is_err());
+    }
+}
@@ -0,0 +1,454 @@
+//! The scenes that can be rendered by name. Every scene is registered once with the functions
+//! building it and its camera, and the command line, the render daemon and headless job files all
+//! look scenes up here, so any scene that can be rendered from the command line can also be
+//! rendered by a job.
+
+use crate::camera::{Camera, RenderSettings};
+use crate::scene::Scene;
+use crate::scenes::{
+    animated_textures_camera, animated_textures_scene, billboards_camera, billboards_scene,
+    blobs_camera, blobs_scene, bokeh_camera, bokeh_scene, caustics_camera, caustics_scene,
+    checkered_spheres_camera, checkered_spheres_scene, closed_cornell_box_scene,
+    colored_glass_camera, colored_glass_scene, colored_simple_lights_camera,
+    colored_simple_lights_scene, cornell_box_camera, cornell_box_scene, cornell_cutaway_camera,
+    cornell_cutaway_scene, curved_panels_camera, curved_panels_scene, cylinders_camera,
+    cylinders_scene, decals_camera, decals_scene, deforming_mesh_camera, earth_camera, earth_scene,
+    final_scene, final_scene_camera, fisheye_camera, fractals_camera, fractals_scene,
+    framed_model_camera, framed_model_scene, hdri_camera, hdri_scene, hierarchy_camera,
+    hierarchy_scene, hit_callbacks_camera, hit_callbacks_scene, image_lights_camera,
+    image_lights_scene, layered_cornell_box_camera, layered_cornell_box_scene, light_links_camera,
+    light_links_scene, little_planet_camera, material_study_camera, material_study_scene,
+    material_variants_scene, morph_animation, morph_animation_scene, motion_paths_camera,
+    motion_paths_scene, normal_maps_camera, normal_maps_scene, orchard_camera, orchard_scene,
+    orthographic_camera, planar_shapes_camera, planar_shapes_scene, polygons_camera,
+    polygons_scene, principled_camera, principled_scene, quads_camera, quads_scene, render_frames,
+    render_material_variants, rough_glass_camera, rough_glass_scene, screens_camera, screens_scene,
+    shadow_densities_camera, shadow_densities_scene, simple_lights_camera, simple_lights_scene,
+    skinned_animation, skinned_animation_scene, smoky_cornell_box_scene, spheres_camera,
+    spheres_scene, subsurface_camera, subsurface_scene, sweeps_camera, sweeps_scene,
+    textured_metal_and_glass_camera, textured_metal_and_glass_scene, title_camera, title_scene,
+    tori_camera, tori_scene, triangle_soup_camera, triangle_soup_scene,
+    uv_checkered_spheres_camera, uv_checkered_spheres_scene, vase_row_camera, vase_row_scene,
+    weathered_camera, weathered_scene,
+};
+
+/// Builds a scene, reading the scene parameters of the settings if it has any.
+pub type BuildScene = fn(&RenderSettings) -> Result<Scene, String>;
+
+/// Builds the camera of a scene, before the settings given on the command line or in a job are
+/// applied to it.
+pub type BuildCamera = fn() -> Camera;
+
+/// Renders the images of a scene with its camera and saves them.
+pub type RenderImages = fn(Camera, Scene);
+
+/// Represents a scene that can be rendered by name.
+pub struct SceneEntry {
+    /// The name of the scene, as given on the command line and in job files.
+    pub name: &'static str,
+    /// Builds the scene.
+    scene: BuildScene,
+    /// Builds the camera of the scene.
+    camera: BuildCamera,
+    /// The margin left around the scene by a camera framing it, or `None` if the camera is
+    /// placed by hand.
+    framing: Option<f64>,
+    /// Renders the images of the scene from the command line.
+    render: RenderImages,
+}
+
+impl SceneEntry {
+    /// Creates a new `SceneEntry` rendering a single image of the scene.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the scene.
+    /// * `scene` - Builds the scene.
+    /// * `camera` - Builds the camera of the scene.
+    ///
+    /// # Returns
+    ///
+    /// A new `SceneEntry` instance.
+    const fn new(name: &'static str, scene: BuildScene, camera: BuildCamera) -> SceneEntry {
+        SceneEntry {
+            name,
+            scene,
+            camera,
+            framing: None,
+            render: render_image,
+        }
+    }
+
+    /// Makes the camera frame the whole scene before the settings are applied to it.
+    ///
+    /// # Arguments
+    ///
+    /// * `margin` - The fraction of the image left empty around the scene.
+    ///
+    /// # Returns
+    ///
+    /// The `SceneEntry` instance with the framing set.
+    const fn with_framing(self, margin: f64) -> SceneEntry {
+        SceneEntry {
+            framing: Some(margin),
+            ..self
+        }
+    }
+
+    /// Renders the scene from the command line with the given function instead of as a single
+    /// image, such as for animations and variants.
+    ///
+    /// # Arguments
+    ///
+    /// * `render` - Renders the images of the scene and saves them.
+    ///
+    /// # Returns
+    ///
+    /// The `SceneEntry` instance with the render function set.
+    const fn with_render(self, render: RenderImages) -> SceneEntry {
+        SceneEntry { render, ..self }
+    }
+
+    /// Builds the scene.
+    ///
+    /// # Arguments
+    ///
+    /// * `settings` - The settings holding the scene parameters, if the scene has any.
+    ///
+    /// # Returns
+    ///
+    /// The scene, or a description of why it could not be built.
+    pub fn scene(&self, settings: &RenderSettings) -> Result<Scene, String> {
+        (self.scene)(settings)
+    }
+
+    /// Builds the camera of the scene, framing the scene if the camera is not placed by hand, and
+    /// applies the settings to it.
+    ///
+    /// # Arguments
+    ///
+    /// * `scene` - The scene built by this entry.
+    /// * `settings` - The settings overriding the ones of the camera.
+    ///
+    /// # Returns
+    ///
+    /// The camera.
+    pub fn camera(&self, scene: &Scene, settings: &RenderSettings) -> Camera {
+        let mut camera = (self.camera)();
+        if let Some(margin) = self.framing {
+            camera.frame_scene(scene, margin);
+        }
+        camera.apply_settings(settings);
+        camera
+    }
+
+    /// Builds the scene and its camera and renders the images of the scene, as the command line
+    /// does.
+    ///
+    /// # Arguments
+    ///
+    /// * `settings` - The settings given on the command line.
+    ///
+    /// # Returns
+    ///
+    /// `Ok` once the images are rendered, or a description of why the scene could not be built.
+    pub fn render(&self, settings: &RenderSettings) -> Result<(), String> {
+        let scene = self.scene(settings)?;
+        let camera = self.camera(&scene, settings);
+        (self.render)(camera, scene);
+        Ok(())
+    }
+}
+
+/// Renders a single image of a scene and saves it.
+///
+/// # Arguments
+///
+/// * `camera` - The camera rendering the image.
+/// * `scene` - The scene to render.
+fn render_image(camera: Camera, scene: Scene) {
+    camera.render(scene);
+}
+
+/// The scenes that can be rendered, in the order they are listed.
+pub static SCENES: [SceneEntry; 55] = [
+    SceneEntry::new("spheres", |_| Ok(spheres_scene()), spheres_camera),
+    SceneEntry::new(
+        "checkered_spheres",
+        |_| Ok(checkered_spheres_scene()),
+        checkered_spheres_camera,
+    ),
+    SceneEntry::new(
+        "uv_checkered_spheres",
+        |_| Ok(uv_checkered_spheres_scene()),
+        uv_checkered_spheres_camera,
+    ),
+    SceneEntry::new("earth", |_| Ok(earth_scene()), earth_camera),
+    SceneEntry::new("quads", |_| Ok(quads_scene()), quads_camera),
+    SceneEntry::new("decals", |_| Ok(decals_scene()), decals_camera),
+    SceneEntry::new("weathered", |_| Ok(weathered_scene()), weathered_camera),
+    SceneEntry::new(
+        "planar_shapes",
+        |_| Ok(planar_shapes_scene()),
+        planar_shapes_camera,
+    ),
+    SceneEntry::new(
+        "curved_panels",
+        |_| Ok(curved_panels_scene()),
+        curved_panels_camera,
+    ),
+    SceneEntry::new("cylinders", |_| Ok(cylinders_scene()), cylinders_camera),
+    SceneEntry::new("tori", |_| Ok(tori_scene()), tori_camera),
+    SceneEntry::new("polygons", |_| Ok(polygons_scene()), polygons_camera),
+    SceneEntry::new("sweeps", |_| Ok(sweeps_scene()), sweeps_camera),
+    SceneEntry::new("vase_row", |_| Ok(vase_row_scene()), vase_row_camera),
+    SceneEntry::new(
+        "framed_model",
+        |_| Ok(framed_model_scene()),
+        framed_model_camera,
+    )
+    .with_framing(0.05),
+    SceneEntry::new(
+        "triangle_soup",
+        |_| Ok(triangle_soup_scene()),
+        triangle_soup_camera,
+    ),
+    SceneEntry::new("title", |_| title_scene(), title_camera),
+    SceneEntry::new(
+        "simple_lights",
+        |_| Ok(simple_lights_scene()),
+        simple_lights_camera,
+    ),
+    SceneEntry::new(
+        "colored_simple_lights",
+        |_| Ok(colored_simple_lights_scene()),
+        colored_simple_lights_camera,
+    ),
+    SceneEntry::new(
+        "light_links",
+        |_| Ok(light_links_scene()),
+        light_links_camera,
+    ),
+    SceneEntry::new(
+        "hit_callbacks",
+        |_| Ok(hit_callbacks_scene()),
+        hit_callbacks_camera,
+    ),
+    SceneEntry::new(
+        "shadow_densities",
+        |_| Ok(shadow_densities_scene()),
+        shadow_densities_camera,
+    ),
+    SceneEntry::new("subsurface", |_| Ok(subsurface_scene()), subsurface_camera),
+    SceneEntry::new(
+        "rough_glass",
+        |_| Ok(rough_glass_scene()),
+        rough_glass_camera,
+    ),
+    SceneEntry::new(
+        "colored_glass",
+        |_| Ok(colored_glass_scene()),
+        colored_glass_camera,
+    ),
+    SceneEntry::new("principled", |_| Ok(principled_scene()), principled_camera),
+    SceneEntry::new(
+        "textured_metal_and_glass",
+        |_| Ok(textured_metal_and_glass_scene()),
+        textured_metal_and_glass_camera,
+    ),
+    SceneEntry::new(
+        "material_study",
+        |settings| Ok(material_study_scene(settings)),
+        material_study_camera,
+    ),
+    SceneEntry::new("bokeh", |_| Ok(bokeh_scene()), bokeh_camera),
+    SceneEntry::new(
+        "cornell_box",
+        |_| Ok(cornell_box_scene()),
+        || cornell_box_camera(1920, 10000),
+    ),
+    SceneEntry::new(
+        "cornell_smoke",
+        |_| Ok(smoky_cornell_box_scene(0.0)),
+        || cornell_box_camera(1920, 10000),
+    ),
+    SceneEntry::new(
+        "cornell_smoke_forward",
+        |_| Ok(smoky_cornell_box_scene(0.8)),
+        || cornell_box_camera(1920, 10000),
+    ),
+    SceneEntry::new(
+        "final_scene",
+        |_| Ok(final_scene(true)),
+        || final_scene_camera(1920, 10000, 5),
+    ),
+    SceneEntry::new(
+        "final_scene_preview",
+        |_| Ok(final_scene(true)),
+        || final_scene_camera(400, 250, 10),
+    ),
+    SceneEntry::new(
+        "caustics",
+        |_| Ok(caustics_scene()),
+        || caustics_camera(800, 2000),
+    ),
+    SceneEntry::new(
+        "animated_textures",
+        |_| Ok(animated_textures_scene()),
+        || animated_textures_camera(24),
+    )
+    .with_render(|camera, scene| render_frames(camera, &scene, 24)),
+    SceneEntry::new(
+        "skinned_animation",
+        |_| skinned_animation_scene(),
+        deforming_mesh_camera,
+    )
+    .with_render(|camera, _| skinned_animation(camera, 24)),
+    SceneEntry::new(
+        "morph_animation",
+        |_| morph_animation_scene(),
+        deforming_mesh_camera,
+    )
+    .with_render(|camera, _| morph_animation(camera, 24)),
+    SceneEntry::new(
+        "motion_paths",
+        |_| Ok(motion_paths_scene()),
+        motion_paths_camera,
+    ),
+    SceneEntry::new("hierarchy", |_| hierarchy_scene(), hierarchy_camera),
+    SceneEntry::new(
+        "layered_cornell_box",
+        |_| Ok(layered_cornell_box_scene()),
+        layered_cornell_box_camera,
+    )
+    .with_render(|camera, mut scene| camera.render_layers(&mut scene)),
+    SceneEntry::new("billboards", |_| Ok(billboards_scene()), billboards_camera),
+    SceneEntry::new("orchard", |_| Ok(orchard_scene()), orchard_camera),
+    SceneEntry::new(
+        "closed_cornell_box",
+        |_| Ok(closed_cornell_box_scene()),
+        || cornell_box_camera(600, 500),
+    ),
+    SceneEntry::new(
+        "cornell_cutaway",
+        |_| Ok(cornell_cutaway_scene()),
+        cornell_cutaway_camera,
+    ),
+    SceneEntry::new(
+        "material_variants",
+        |_| Ok(material_variants_scene()),
+        || cornell_box_camera(600, 200),
+    )
+    .with_render(render_material_variants),
+    SceneEntry::new("screens", |_| Ok(screens_scene()), screens_camera),
+    SceneEntry::new(
+        "image_lights",
+        |_| Ok(image_lights_scene()),
+        image_lights_camera,
+    ),
+    SceneEntry::new("hdri", |_| Ok(hdri_scene()), hdri_camera),
+    SceneEntry::new(
+        "normal_maps",
+        |_| Ok(normal_maps_scene()),
+        normal_maps_camera,
+    ),
+    SceneEntry::new(
+        "little_planet",
+        |_| Ok(spheres_scene()),
+        little_planet_camera,
+    ),
+    SceneEntry::new("fisheye", |_| Ok(spheres_scene()), fisheye_camera),
+    SceneEntry::new("orthographic", |_| Ok(spheres_scene()), orthographic_camera),
+    SceneEntry::new("fractals", |_| Ok(fractals_scene()), fractals_camera),
+    SceneEntry::new("blobs", |_| Ok(blobs_scene()), blobs_camera),
+];
+
+/// Finds a scene by name.
+///
+/// # Arguments
+///
+/// * `name` - The name of the scene.
+///
+/// # Returns
+///
+/// The scene, or `None` if no scene has the given name.
+pub fn find(name: &str) -> Option<&'static SceneEntry> {
+    SCENES.iter().find(|entry| entry.name == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::Arguments;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_scene_names_are_unique() {
+        let names: HashSet<&str> = SCENES.iter().map(|entry| entry.name).collect();
+        assert_eq!(names.len(), SCENES.len());
+        assert_eq!(find("quads").map(|entry| entry.name), Some("quads"));
+        assert!(find("missing").is_none());
+    }
+
+    #[test]
+    fn test_settings_override_the_camera() {
+        let entry = find("framed_model").unwrap();
+        let settings = RenderSettings {
+            width: Some(32),
+            samples: Some(3),
+            ..RenderSettings::default()
+        };
+        let scene = entry.scene(&settings).unwrap();
+        let camera = entry.camera(&scene, &settings);
+        assert_eq!(camera.image_width(), 32);
+        assert_eq!(camera.samples_per_pixel(), 3);
+    }
+
+    #[test]
+    fn test_seeded_renders_are_identical() {
+        // Renders a scene the way the command line does, from the arguments to the colors of the
+        // image, on a pool of the given number of threads.
+        let render = |arguments: &[&str], threads: usize| {
+            let arguments = Arguments::parse(arguments.iter().map(|a| a.to_string())).unwrap();
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .unwrap()
+                .install(|| {
+                    let entry = find(&arguments.scene).unwrap();
+                    if let Some(seed) = arguments.settings.seed {
+                        fastrand::seed(seed);
+                    }
+                    let scene = entry.scene(&arguments.settings).unwrap();
+                    let camera = entry.camera(&scene, &arguments.settings);
+                    camera.render_linear(&scene)
+                })
+        };
+        // Both renders run at the same time, so their threads draw random numbers side by side.
+        let spheres = |seed| ["spheres", "--seed", seed, "-w", "24", "-n", "2"];
+        let (first, second) = std::thread::scope(|scope| {
+            let first = scope.spawn(|| render(&spheres("9"), 4));
+            let second = scope.spawn(|| render(&spheres("9"), 1));
+            (first.join().unwrap(), second.join().unwrap())
+        });
+        assert_eq!(first, second);
+        assert_ne!(first, render(&spheres("10"), 4));
+
+        // The denoiser follows the glass, which reflects or refracts at random. Only a few pixels
+        // depend on which thread finds their features, so the render is repeated.
+        let denoised = [
+            "colored_glass",
+            "--seed",
+            "9",
+            "-w",
+            "64",
+            "-n",
+            "1",
+            "--denoise",
+        ];
+        let single = render(&denoised, 1);
+        for _ in 0..4 {
+            assert_eq!(render(&denoised, 4), single);
+        }
+    }
+}
@@ -1,32 +1,65 @@
-use crate::camera::Camera;
-use crate::hit::Hittable;
-use crate::material::{Dielectric, DiffuseLight, Lambertian, Material, Metal};
+use crate::aabb::Aabb;
+use crate::aperture::Aperture;
+use crate::camera::{Camera, Projection, RenderSettings};
+use crate::decal::Decal;
+use crate::environment::HdrEnvironmentMap;
+use crate::hit::{HitRecord, Hittable};
+use crate::material::{
+    Dielectric, DiffuseLight, Lambertian, Material, Metal, NormalMap, Principled, RoughDielectric,
+    Subsurface,
+};
+use crate::material_kind::{Dispatch, MaterialKind};
+use crate::palette::{Harmony, MaterialGenerator, Palette};
+use crate::parameter_sweep::SweepParameter;
+use crate::ray::Ray;
+use crate::scatter::SurfaceScatter;
+use crate::scene::{LinkMode, MaterialOverrides, Scene, DEFAULT_LAYER};
+use crate::scene_graph::{NodeTransform, SceneGraph};
+use crate::shapes::bilinear_patch::BilinearPatch;
+use crate::shapes::billboard::Billboard;
 use crate::shapes::box_quad::BoxQuad;
+use crate::shapes::cleanup::MeshData;
+use crate::shapes::cone::Cone;
+use crate::shapes::cylinder::Cylinder;
+use crate::shapes::fractal::{Mandelbulb, MengerSponge};
+use crate::shapes::hittable_list::HittableList;
+use crate::shapes::lod::Lod;
+use crate::shapes::mesh::TriangleMesh;
+use crate::shapes::morph::{MorphMesh, MorphTarget, WeightAnimation};
+use crate::shapes::polygon::Polygon;
 use crate::shapes::quad::Quad;
+use crate::shapes::sdf::{smooth_intersection, smooth_subtraction, smooth_union, SdfShape};
+use crate::shapes::section::Section;
+use crate::shapes::skin::{
+    quaternion, Affine, Animation, Channel, Interpolation, Joint, JointPose, Property, Skeleton,
+    SkinnedMesh,
+};
 use crate::shapes::sphere::Sphere;
+use crate::shapes::sweep::{extrude, lathe};
+use crate::shapes::text::{Font, Text};
+use crate::shapes::torus::Torus;
 use crate::shapes::volume::ConstantMedium;
-use crate::texture::{CheckerTexture, ImageTexture};
-use crate::transformation::{RotateY, Translate};
+use crate::shutter::Shutter;
+use crate::texture::{
+    CheckerTexture, ImageTexture, NoiseTexture, PulseTexture, ScrollingTexture, SolidTexture,
+    SurfaceMaskTexture, SurfaceQuery, Texture, TextureSpace, TintedTexture, TransformedTexture,
+    UvTransform,
+};
+use crate::transformation::{Instance, Motion, MotionKey, Rotate, RotateY, Translate};
+use crate::unit::Unit;
 use crate::utils::background_gradient;
 use crate::vector3::Vector3;
 use fastrand::f64;
+use image::{DynamicImage, Rgba, RgbaImage};
+use std::path::Path;
 use std::sync::Arc;
 
-/// Creates a scene with multiple spheres of different materials and renders it using the camera.
-pub fn spheres() {
-    let camera = Camera::new(
-        1920,
-        16.0 / 9.0,
-        20,
-        10,
-        background_gradient,
-        20.0,
-        Vector3::new(13.0, 2.0, 3.0),
-        Vector3::new(0.0, 0.0, 0.0),
-        Vector3::new(0.0, 1.0, 0.0),
-        0.2,
-        10.0,
-    );
+/// Creates the scene of many small spheres of different materials around three large spheres.
+///
+/// # Returns
+///
+/// The scene.
+pub fn spheres_scene() -> Scene {
     let mut world: Vec<Box<dyn Hittable>> = Vec::new();
 
     let checker = Box::new(CheckerTexture::new(
@@ -36,40 +69,27 @@ pub fn spheres() {
     ));
 
     let material_ground = Arc::new(Lambertian::from_texture(checker));
-    world.push(Box::new(Sphere::new(
-        Vector3::new(0.0, -1000.0, 0.0),
-        1000.0,
-        material_ground,
-    )));
-
-    for a in -11..11 {
-        for b in -11..11 {
-            let choose_mat = f64();
-            let center = Vector3::new(a as f64 + 0.9 * f64(), 0.2, b as f64 + 0.9 * f64());
-
-            if (center - Vector3::new(4.0, 0.2, 0.0)).length() > 0.9 {
-                let material: Arc<dyn Material>;
-                match choose_mat {
-                    0.0..0.8 => {
-                        // diffuse
-                        let albdeo = Vector3::random(0.0, 1.0) * Vector3::random(0.0, 1.0);
-                        material = Arc::new(Lambertian::new(albdeo));
-                        world.push(Box::new(Sphere::new(center, 0.2, material)));
-                    }
-                    0.8..0.95 => {
-                        // metal
-                        let albedo = Vector3::random(0.5, 1.0);
-                        let fuzz = f64() * 0.5;
-                        material = Arc::new(Metal::new(albedo, fuzz));
-                        world.push(Box::new(Sphere::new(center, 0.2, material)));
-                    }
-                    _ => {
-                        // glass
-                        material = Arc::new(Dielectric::new(1.5));
-                        world.push(Box::new(Sphere::new(center, 0.2, material)));
-                    }
-                }
-            }
+    let ground = Sphere::new(Vector3::new(0.0, -1000.0, 0.0), 1000.0, material_ground);
+
+    let scatter = SurfaceScatter::new((-11.0, -11.0), (11.0, 11.0), 0.9).with_seed(42);
+    let placements = scatter.place_on(&ground);
+    world.push(Box::new(ground));
+
+    // Warm earth tones with a few complementary blues, keeping the classic mix of materials.
+    let palette = Palette::new(42)
+        .with_hue(25.0)
+        .with_harmony(Harmony::Complementary)
+        .with_spread(20.0);
+    let mut materials = MaterialGenerator::new(palette);
+    for placement in placements {
+        let center = placement.point + 0.2 * placement.normal;
+
+        if (center - Vector3::new(4.0, 0.2, 0.0)).length() > 0.9 {
+            world.push(Box::new(Sphere::new(
+                center,
+                0.2,
+                materials.next_material(),
+            )));
         }
     }
     let material_1 = Arc::new(Dielectric::new(1.5));
@@ -93,11 +113,110 @@ pub fn spheres() {
         material_3,
     )));
 
-    camera.render(world);
+    Scene::new(world)
+}
+
+/// Builds the camera looking over the scene of many spheres towards its three large spheres,
+/// focused on them with a slight defocus blur.
+///
+/// # Returns
+///
+/// The camera.
+pub fn spheres_camera() -> Camera {
+    Camera::new(
+        1920,
+        16.0 / 9.0,
+        20,
+        10,
+        background_gradient,
+        20.0,
+        Vector3::new(13.0, 2.0, 3.0),
+        Vector3::new(0.0, 0.0, 0.0),
+        Vector3::new(0.0, 1.0, 0.0),
+        0.2,
+        10.0,
+    )
+}
+
+/// Builds a camera showing the scene of many spheres as a "little planet": a stereographic
+/// projection from just above the ground, looking straight down with a very wide field of view, so
+/// the ground curls into a small globe surrounded by the sky.
+///
+/// # Returns
+///
+/// The camera.
+pub fn little_planet_camera() -> Camera {
+    let mut camera = Camera::new(
+        800,
+        1.0,
+        100,
+        10,
+        background_gradient,
+        90.0,
+        Vector3::new(0.0, 2.5, 0.0),
+        Vector3::new(0.0, 0.0, 0.0),
+        Vector3::new(0.0, 0.0, -1.0),
+        0.0,
+        0.0,
+    );
+    camera.set_projection(Projection::Stereographic { fov: 240.0 });
+    camera
+}
+
+/// Builds a camera showing the scene of many spheres through a fisheye lens from among the spheres,
+/// the whole half of the world in front of the camera bending into the image.
+///
+/// # Returns
+///
+/// The camera.
+pub fn fisheye_camera() -> Camera {
+    let mut camera = Camera::new(
+        800,
+        16.0 / 9.0,
+        100,
+        10,
+        background_gradient,
+        90.0,
+        Vector3::new(0.0, 1.2, 6.0),
+        Vector3::new(0.0, 1.0, 0.0),
+        Vector3::new(0.0, 1.0, 0.0),
+        0.0,
+        0.0,
+    );
+    camera.set_projection(Projection::Fisheye { fov: 200.0 });
+    camera
+}
+
+/// Builds a camera showing the scene of many spheres with an orthographic projection from high
+/// above at the angle of an isometric drawing, so the far spheres look as large as the near ones.
+///
+/// # Returns
+///
+/// The camera.
+pub fn orthographic_camera() -> Camera {
+    let mut camera = Camera::new(
+        800,
+        16.0 / 9.0,
+        100,
+        10,
+        background_gradient,
+        90.0,
+        Vector3::new(30.0, 30.0, 30.0),
+        Vector3::new(0.0, 0.0, 0.0),
+        Vector3::new(0.0, 1.0, 0.0),
+        0.0,
+        0.0,
+    );
+    camera.set_projection(Projection::Orthographic { height: 10.0 });
+    camera
 }
 
-/// Creates a scene with two checkered spheres and renders it using the camera.
-pub fn checkered_spheres() {
+/// Creates a scene with two checkered spheres.
+///
+/// # Returns
+///
+/// The scene.
+pub fn checkered_spheres_scene() -> Scene {
     let mut world: Vec<Box<dyn Hittable>> = Vec::new();
     let checker_1 = Box::new(CheckerTexture::new(
         3.0,
@@ -122,8 +241,16 @@ pub fn checkered_spheres() {
         10.0,
         Arc::new(Lambertian::from_texture(checker_2)),
     )));
+    Scene::new(world)
+}
 
-    let camera = Camera::new(
+/// Builds the camera of the `checkered_spheres` scene.
+///
+/// # Returns
+///
+/// The camera of the `checkered_spheres` scene.
+pub fn checkered_spheres_camera() -> Camera {
+    Camera::new(
         400,
         16.0 / 9.0,
         100,
@@ -135,15 +262,85 @@ pub fn checkered_spheres() {
         Vector3::new(0.0, 1.0, 0.0),
         0.0,
         0.0,
-    );
+    )
+}
+
+/// Creates the scene of `checkered_spheres` with the checkers laid out in texture coordinates, so
+/// the squares follow the spheres instead of breaking into stripes where the spheres cross the
+/// planes of the 3D pattern. The upper checker is rotated and shifted by a texture transform.
+///
+/// # Returns
+///
+/// The scene.
+pub fn uv_checkered_spheres_scene() -> Scene {
+    let mut world: Vec<Box<dyn Hittable>> = Vec::new();
+    // The u-coordinate goes around a sphere, twice as far as the v-coordinate from pole to pole,
+    // so it is scaled twice as much to keep the squares square.
+    let checker = |transform: UvTransform| {
+        Box::new(TransformedTexture::new(
+            Box::new(
+                CheckerTexture::new(
+                    1.0,
+                    Vector3::new(0.2, 0.3, 0.1),
+                    Vector3::new(0.9, 0.9, 0.9),
+                )
+                .with_space(TextureSpace::Uv),
+            ),
+            transform,
+        ))
+    };
+
+    world.push(Box::new(Sphere::new(
+        Vector3::new(0.0, -10.0, 0.0),
+        10.0,
+        Arc::new(Lambertian::from_texture(checker(
+            UvTransform::new().with_scale(48.0, 24.0),
+        ))),
+    )));
+
+    world.push(Box::new(Sphere::new(
+        Vector3::new(0.0, 10.0, 0.0),
+        10.0,
+        Arc::new(Lambertian::from_texture(checker(
+            UvTransform::new()
+                .with_scale(48.0, 24.0)
+                .with_rotation(45.0)
+                .with_offset(0.5, 0.0),
+        ))),
+    )));
+    Scene::new(world)
+}
 
-    camera.render(world);
+/// Builds the camera of the `uv_checkered_spheres` scene.
+///
+/// # Returns
+///
+/// The camera of the `uv_checkered_spheres` scene.
+pub fn uv_checkered_spheres_camera() -> Camera {
+    Camera::new(
+        400,
+        16.0 / 9.0,
+        100,
+        50,
+        background_gradient,
+        20.0,
+        Vector3::new(13.0, 2.0, 3.0),
+        Vector3::new(0.0, 0.0, 0.0),
+        Vector3::new(0.0, 1.0, 0.0),
+        0.0,
+        0.0,
+    )
 }
 
-/// Creates a scene with a sphere textured with an image of the Earth and renders it using the camera.
-pub fn earth() {
+/// Creates a scene with a sphere textured with an image of the Earth. The image is much finer than
+/// the pixels the sphere covers, so it is sampled through mipmaps.
+///
+/// # Returns
+///
+/// The scene.
+pub fn earth_scene() -> Scene {
     let mut world: Vec<Box<dyn Hittable>> = Vec::new();
-    let earth_texture = Box::new(ImageTexture::new("earthmap.jpg"));
+    let earth_texture = Box::new(ImageTexture::new("earthmap.jpg").with_mipmaps());
     let earth_surface = Arc::new(Lambertian::from_texture(earth_texture));
 
     world.push(Box::new(Sphere::new(
@@ -151,8 +348,16 @@ pub fn earth() {
         2.0,
         earth_surface,
     )));
+    Scene::new(world)
+}
 
-    let camera = Camera::new(
+/// Builds the camera of the `earth` scene.
+///
+/// # Returns
+///
+/// The camera of the `earth` scene.
+pub fn earth_camera() -> Camera {
+    Camera::new(
         400,
         16.0 / 9.0,
         100,
@@ -164,13 +369,15 @@ pub fn earth() {
         Vector3::new(0.0, 1.0, 0.0),
         0.0,
         0.0,
-    );
-
-    camera.render(world);
+    )
 }
 
-/// Create a scene with 4 quads and renders it using the camera.
-pub fn quads() {
+/// Creates a scene with 4 quads.
+///
+/// # Returns
+///
+/// The scene.
+pub fn quads_scene() -> Scene {
     let mut world: Vec<Box<dyn Hittable>> = Vec::new();
 
     // Materials
@@ -215,9 +422,16 @@ pub fn quads() {
         Vector3::new(0.0, 0.0, -4.0),
         lower_teal,
     )));
+    Scene::new(world)
+}
 
-    // Camera
-    let camera = Camera::new(
+/// Builds the camera of the `quads` scene.
+///
+/// # Returns
+///
+/// The camera of the `quads` scene.
+pub fn quads_camera() -> Camera {
+    Camera::new(
         400,
         1.0,
         100,
@@ -229,414 +443,3991 @@ pub fn quads() {
         Vector3::new(0.0, 1.0, 0.0),
         0.0,
         1.0,
-    );
-    camera.render(world);
+    )
 }
 
-/// Creates a scene with a sphere and a quad with light material and renders it using the camera.
-pub fn simple_lights() {
-    let mut world: Vec<Box<dyn Hittable>> = Vec::new();
-    let material = Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5)));
-    world.push(Box::new(Sphere::new(
-        Vector3::new(0.0, -1000.0, 0.0),
-        1000.0,
-        material.clone(),
-    )));
+/// Creates a scene with a wall and a floor carrying decals: quads lying in their planes which are
+/// given priority over them so they show without speckles, a stain projected across the corner
+/// where they meet and a target projected onto a ball.
+///
+/// # Returns
+///
+/// The scene.
+pub fn decals_scene() -> Scene {
+    let mut scene = Scene::new(Vec::new());
+    let gray = Arc::new(Lambertian::new(Vector3::new(0.6, 0.6, 0.6)));
 
-    world.push(Box::new(Sphere::new(
-        Vector3::new(0.0, 2.0, 0.0),
-        2.0,
-        material,
-    )));
+    scene.add_named(
+        "wall",
+        Box::new(Quad::new(
+            Vector3::new(-4.0, 0.0, -2.0),
+            Vector3::new(8.0, 0.0, 0.0),
+            Vector3::new(0.0, 5.0, 0.0),
+            gray.clone(),
+        )),
+        DEFAULT_LAYER,
+    );
+    scene.add_named(
+        "floor",
+        Box::new(Quad::new(
+            Vector3::new(-4.0, 0.0, -2.0),
+            Vector3::new(0.0, 0.0, 6.0),
+            Vector3::new(8.0, 0.0, 0.0),
+            gray,
+        )),
+        DEFAULT_LAYER,
+    );
 
-    let diff_light = Arc::new(DiffuseLight::new(Vector3::new(4.0, 4.0, 4.0)));
-    world.push(Box::new(Sphere::new(
-        Vector3::new(0.0, 7.0, 0.0),
-        2.0,
-        diff_light.clone(),
-    )));
+    // Posters on the wall and a rug on the floor, in the very planes of the surfaces below them.
+    let posters = [
+        (Vector3::new(-3.0, 1.5, -2.0), Vector3::new(0.9, 0.2, 0.1)),
+        (Vector3::new(-0.75, 2.0, -2.0), Vector3::new(0.1, 0.5, 0.8)),
+        (Vector3::new(1.5, 1.5, -2.0), Vector3::new(0.9, 0.7, 0.1)),
+    ];
+    for (corner, color) in posters {
+        scene.add_named(
+            "decal",
+            Box::new(Quad::new(
+                corner,
+                Vector3::new(1.5, 0.0, 0.0),
+                Vector3::new(0.0, 2.0, 0.0),
+                Arc::new(Lambertian::new(color)),
+            )),
+            DEFAULT_LAYER,
+        );
+    }
+    scene.add_named(
+        "decal",
+        Box::new(Quad::new(
+            Vector3::new(-2.0, 0.0, -0.5),
+            Vector3::new(0.0, 0.0, 2.5),
+            Vector3::new(4.0, 0.0, 0.0),
+            Arc::new(Lambertian::new(Vector3::new(0.2, 0.6, 0.3))),
+        )),
+        DEFAULT_LAYER,
+    );
+    scene
+        .set_priority("decal", 1)
+        .expect("the decals are in the scene");
 
-    world.push(Box::new(Quad::new(
-        Vector3::new(3.0, 1.0, -2.0),
-        Vector3::new(2.0, 0.0, 0.0),
-        Vector3::new(0.0, 2.0, 0.0),
-        diff_light,
-    )));
+    scene.add(
+        Box::new(Sphere::new(
+            Vector3::new(0.0, 1.0, 1.0),
+            1.0,
+            Arc::new(Metal::new(Vector3::new(0.8, 0.8, 0.8), 0.05)),
+        )),
+        DEFAULT_LAYER,
+    );
 
-    let camera = Camera::new(
-        1920,
+    // Projected decals need no texture coordinates, so they wrap around the corner and the ball.
+    scene.add_decal(
+        Decal::new(
+            Vector3::new(2.6, 0.3, -1.7),
+            Vector3::new(-0.3, -1.0, -1.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            Vector3::new(3.0, 3.0, 2.0),
+            decal_image(|distance| (Vector3::new(0.1, 0.07, 0.03), 1.0 - distance * distance)),
+        )
+        .with_opacity(0.9),
+    );
+    scene.add_decal(Decal::new(
+        Vector3::new(0.0, 1.0, 2.0),
+        Vector3::new(0.0, 0.0, -1.0),
+        Vector3::new(0.0, 1.0, 0.0),
+        Vector3::new(1.0, 1.0, 1.0),
+        decal_image(|distance| {
+            let ring = ((distance * 3.0) as u32).is_multiple_of(2);
+            let color = if ring {
+                Vector3::new(0.8, 0.1, 0.1)
+            } else {
+                Vector3::new(0.9, 0.9, 0.9)
+            };
+            (color, 1.0)
+        }),
+    ));
+    scene
+}
+
+/// Builds the camera of the `decals` scene.
+///
+/// # Returns
+///
+/// The camera of the `decals` scene.
+pub fn decals_camera() -> Camera {
+    Camera::new(
+        400,
         16.0 / 9.0,
-        10000,
+        100,
         50,
-        |_| Vector3::new(0.0, 0.0, 0.0),
-        20.0,
-        Vector3::new(26.0, 3.0, 6.0),
-        Vector3::new(0.0, 2.0, 0.0),
+        background_gradient,
+        50.0,
+        Vector3::new(0.0, 2.5, 7.0),
+        Vector3::new(0.0, 1.5, -1.0),
         Vector3::new(0.0, 1.0, 0.0),
         0.0,
-        0.0,
-    );
-
-    camera.render(world);
+        1.0,
+    )
 }
 
-/// Creates a scene with a sphere and a quad with colored light material and renders it using the camera.
-pub fn colored_simple_lights() {
-    let mut world: Vec<Box<dyn Hittable>> = Vec::new();
-    let material = Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5)));
-    world.push(Box::new(Sphere::new(
-        Vector3::new(0.0, -1000.0, 0.0),
-        1000.0,
-        material.clone(),
-    )));
+/// Creates a scene of weathered crates and a rock, whose wear is made by textures reading the
+/// surface: paint worn off the edges, dust settled on top surfaces, dirt gathered in the creases
+/// and moss grown on the north faces, taken to face negative x.
+///
+/// # Returns
+///
+/// The scene.
+pub fn weathered_scene() -> Scene {
+    let mut scene = Scene::new(Vec::new());
+    scene.set_surface_queries(4, 0.25);
 
-    world.push(Box::new(Sphere::new(
-        Vector3::new(0.0, 2.0, 0.0),
-        2.0,
-        material,
-    )));
+    let solid = |r: f64, g: f64, b: f64| -> Box<dyn Texture> {
+        Box::new(SolidTexture::new(Vector3::new(r, g, b)))
+    };
+    let worn = |paint: Box<dyn Texture>| -> Box<dyn Texture> {
+        let edges = SurfaceMaskTexture::new(
+            SurfaceQuery::Curvature,
+            paint,
+            solid(0.55, 0.55, 0.6),
+            (0.1, 0.4),
+        );
+        let dust = SurfaceMaskTexture::new(
+            SurfaceQuery::Facing(Vector3::new(0.0, 1.0, 0.0)),
+            Box::new(edges),
+            solid(0.75, 0.65, 0.45),
+            (0.8, 1.0),
+        );
+        Box::new(SurfaceMaskTexture::new(
+            SurfaceQuery::Occlusion,
+            Box::new(dust),
+            solid(0.08, 0.06, 0.04),
+            (0.1, 0.5),
+        ))
+    };
 
-    let diff_light_1 = Arc::new(DiffuseLight::new(Vector3::new(8.0, 2.0, 2.0)));
-    let diff_light_2 = Arc::new(DiffuseLight::new(Vector3::new(2.0, 2.0, 8.0)));
-    world.push(Box::new(Sphere::new(
-        Vector3::new(0.0, 7.0, 0.0),
-        2.0,
-        diff_light_1,
-    )));
+    scene.add(
+        Box::new(Quad::new(
+            Vector3::new(-6.0, 0.0, -6.0),
+            Vector3::new(0.0, 0.0, 12.0),
+            Vector3::new(12.0, 0.0, 0.0),
+            Arc::new(Lambertian::from_texture(Box::new(SurfaceMaskTexture::new(
+                SurfaceQuery::Occlusion,
+                solid(0.4, 0.38, 0.35),
+                solid(0.08, 0.06, 0.04),
+                (0.05, 0.4),
+            )))),
+        )),
+        DEFAULT_LAYER,
+    );
 
-    world.push(Box::new(Quad::new(
-        Vector3::new(3.0, 1.0, -2.0),
-        Vector3::new(2.0, 0.0, 0.0),
-        Vector3::new(0.0, 2.0, 0.0),
-        diff_light_2,
-    )));
+    let crate_paint = Arc::new(Lambertian::from_texture(worn(solid(0.1, 0.35, 0.4))));
+    let crates = [
+        (Vector3::new(-1.5, 0.0, -1.0), Vector3::new(0.5, 2.0, 1.0)),
+        (Vector3::new(0.5, 0.0, -1.0), Vector3::new(2.0, 1.5, 0.5)),
+        (Vector3::new(-1.2, 2.0, -0.8), Vector3::new(0.2, 2.8, 0.8)),
+    ];
+    for (a, b) in crates {
+        scene.add(
+            Box::new(BoxQuad::new(a, b, crate_paint.clone())),
+            DEFAULT_LAYER,
+        );
+    }
 
-    let camera = Camera::new(
-        1920,
+    let moss = SurfaceMaskTexture::new(
+        SurfaceQuery::Facing(Vector3::new(-1.0, 0.0, 0.0)),
+        worn(solid(0.45, 0.45, 0.45)),
+        solid(0.15, 0.35, 0.08),
+        (0.2, 0.7),
+    );
+    scene.add(
+        Box::new(Sphere::new(
+            Vector3::new(-3.0, 0.6, 1.0),
+            1.0,
+            Arc::new(Lambertian::from_texture(Box::new(moss))),
+        )),
+        DEFAULT_LAYER,
+    );
+    scene
+}
+
+/// Builds the camera of the `weathered` scene.
+///
+/// # Returns
+///
+/// The camera of the `weathered` scene.
+pub fn weathered_camera() -> Camera {
+    Camera::new(
+        400,
         16.0 / 9.0,
-        10000,
+        100,
         50,
-        |_| Vector3::new(0.0, 0.0, 0.0),
-        20.0,
-        Vector3::new(26.0, 3.0, 6.0),
-        Vector3::new(0.0, 2.0, 0.0),
+        background_gradient,
+        40.0,
+        Vector3::new(-3.0, 6.0, 8.0),
+        Vector3::new(-0.5, 1.0, 0.0),
         Vector3::new(0.0, 1.0, 0.0),
         0.0,
-        0.0,
-    );
+        1.0,
+    )
+}
 
-    camera.render(world);
+/// Draws a round decal image, transparent outside its circle.
+///
+/// # Arguments
+///
+/// * `paint` - Gives the color and opacity at a distance from the center, from 0 at the center
+///   to 1 at the edge of the circle.
+///
+/// # Returns
+///
+/// The image as an image texture.
+fn decal_image(paint: impl Fn(f64) -> (Vector3, f64)) -> ImageTexture {
+    let size = 64;
+    let mut image = RgbaImage::new(size, size);
+    for (x, y, pixel) in image.enumerate_pixels_mut() {
+        let dx = (x as f64 + 0.5) / size as f64 - 0.5;
+        let dy = (y as f64 + 0.5) / size as f64 - 0.5;
+        let distance = 2.0 * (dx * dx + dy * dy).sqrt();
+        let (color, alpha) = if distance < 1.0 {
+            paint(distance)
+        } else {
+            (Vector3::new(0.0, 0.0, 0.0), 0.0)
+        };
+        let byte = |value: f64| (value.clamp(0.0, 1.0) * 255.0) as u8;
+        *pixel = Rgba([byte(color.x), byte(color.y), byte(color.z), byte(alpha)]);
+    }
+    // The colors are linear, so they are read without gamma decoding.
+    ImageTexture::from_data_image(&DynamicImage::ImageRgba8(image))
 }
 
-/// Creates a Cornell box scene and renders it using the camera.
-pub fn cornell_box() {
+/// Creates a scene with the flat shapes sharing the intersection of quads: a triangle, an ellipse
+/// and a ring standing on a floor, lit by a disk light.
+///
+/// # Returns
+///
+/// The scene.
+pub fn planar_shapes_scene() -> Scene {
     let mut world: Vec<Box<dyn Hittable>> = Vec::new();
 
-    let red = Arc::new(Lambertian::new(Vector3::new(0.65, 0.05, 0.05)));
-    let white = Arc::new(Lambertian::new(Vector3::new(0.73, 0.73, 0.73)));
-    let green = Arc::new(Lambertian::new(Vector3::new(0.12, 0.45, 0.15)));
-    let light = Arc::new(DiffuseLight::new(Vector3::new(15.0, 15.0, 15.0)));
-
     world.push(Box::new(Quad::new(
-        Vector3::new(555.0, 0.0, 0.0),
-        Vector3::new(0.0, 555.0, 0.0),
-        Vector3::new(0.0, 0.0, 555.0),
-        green,
+        Vector3::new(-6.0, 0.0, -4.0),
+        Vector3::new(12.0, 0.0, 0.0),
+        Vector3::new(0.0, 0.0, 8.0),
+        Arc::new(Lambertian::new(Vector3::new(0.73, 0.73, 0.73))),
     )));
 
-    world.push(Box::new(Quad::new(
-        Vector3::new(0.0, 0.0, 0.0),
-        Vector3::new(0.0, 555.0, 0.0),
-        Vector3::new(0.0, 0.0, 555.0),
-        red,
+    let checker = |odd: Vector3| {
+        Arc::new(Lambertian::from_texture(Box::new(
+            CheckerTexture::new(6.0, Vector3::new(0.9, 0.9, 0.9), odd).with_space(TextureSpace::Uv),
+        )))
+    };
+    world.push(Box::new(Quad::triangle(
+        Vector3::new(-4.5, 0.0, 0.0),
+        Vector3::new(2.5, 0.0, 0.0),
+        Vector3::new(1.25, 2.5, 0.0),
+        checker(Vector3::new(0.8, 0.2, 0.2)),
+    )));
+    world.push(Box::new(Quad::ellipse(
+        Vector3::new(0.0, 1.5, 0.0),
+        Vector3::new(1.0, 0.0, 0.0),
+        Vector3::new(0.0, 1.5, 0.0),
+        checker(Vector3::new(0.2, 0.6, 0.2)),
+    )));
+    world.push(Box::new(Quad::annulus(
+        Vector3::new(3.25, 1.25, 0.0),
+        Vector3::new(0.0, 0.0, 1.0),
+        0.6,
+        1.25,
+        checker(Vector3::new(0.2, 0.2, 0.8)),
     )));
 
-    world.push(Box::new(Quad::new(
-        Vector3::new(343.0, 554.0, 332.0),
-        Vector3::new(-130.0, 0.0, 0.0),
-        Vector3::new(0.0, 0.0, -105.0),
-        light,
+    world.push(Box::new(Quad::disk(
+        Vector3::new(0.0, 6.0, 1.0),
+        Vector3::new(0.0, -1.0, 0.0),
+        1.5,
+        Arc::new(DiffuseLight::new(Vector3::new(6.0, 6.0, 6.0))),
     )));
+    Scene::new(world)
+}
+
+/// Builds the camera of the `planar_shapes` scene.
+///
+/// # Returns
+///
+/// The camera of the `planar_shapes` scene.
+pub fn planar_shapes_camera() -> Camera {
+    Camera::new(
+        800,
+        16.0 / 9.0,
+        100,
+        50,
+        |_| Vector3::new(0.05, 0.05, 0.08),
+        45.0,
+        Vector3::new(0.0, 2.5, 10.0),
+        Vector3::new(0.0, 1.3, 0.0),
+        Vector3::new(0.0, 1.0, 0.0),
+        0.0,
+        1.0,
+    )
+}
+
+/// Creates a scene with cylinders and cones standing on a floor, a checkered capped cylinder, an
+/// open metal tube lying on its side and two cones.
+///
+/// # Returns
+///
+/// The scene.
+pub fn cylinders_scene() -> Scene {
+    let mut world: Vec<Box<dyn Hittable>> = Vec::new();
 
     world.push(Box::new(Quad::new(
-        Vector3::new(0.0, 0.0, 0.0),
-        Vector3::new(555.0, 0.0, 0.0),
-        Vector3::new(0.0, 0.0, 555.0),
-        white.clone(),
+        Vector3::new(-6.0, 0.0, -4.0),
+        Vector3::new(12.0, 0.0, 0.0),
+        Vector3::new(0.0, 0.0, 8.0),
+        Arc::new(Lambertian::new(Vector3::new(0.73, 0.73, 0.73))),
+    )));
+
+    let checker = |odd: Vector3| {
+        Arc::new(Lambertian::from_texture(Box::new(
+            CheckerTexture::new(8.0, Vector3::new(0.9, 0.9, 0.9), odd).with_space(TextureSpace::Uv),
+        )))
+    };
+    world.push(Box::new(Cylinder::new(
+        Vector3::new(-3.0, 0.0, 0.0),
+        Vector3::new(-3.0, 2.5, 0.0),
+        0.9,
+        checker(Vector3::new(0.8, 0.2, 0.2)),
+    )));
+    world.push(Box::new(
+        Cylinder::new(
+            Vector3::new(-0.8, 0.6, 1.2),
+            Vector3::new(1.6, 0.6, 0.4),
+            0.6,
+            Arc::new(Metal::new(Vector3::new(0.8, 0.8, 0.85), 0.1)),
+        )
+        .with_caps(false),
+    ));
+    // The base of the cone rests on the floor, so it is left open.
+    world.push(Box::new(
+        Cone::new(
+            Vector3::new(0.5, 0.0, -1.5),
+            Vector3::new(0.5, 3.0, -1.5),
+            1.0,
+            checker(Vector3::new(0.2, 0.6, 0.2)),
+        )
+        .with_cap(false),
+    ));
+    world.push(Box::new(Cone::new(
+        Vector3::new(3.2, 1.6, 0.0),
+        Vector3::new(3.2, 0.0, 0.0),
+        0.9,
+        Arc::new(Dielectric::new(1.5)),
+    )));
+
+    world.push(Box::new(Quad::disk(
+        Vector3::new(0.0, 6.0, 1.0),
+        Vector3::new(0.0, -1.0, 0.0),
+        1.5,
+        Arc::new(DiffuseLight::new(Vector3::new(6.0, 6.0, 6.0))),
+    )));
+    Scene::new(world)
+}
+
+/// Builds the camera of the `cylinders` scene.
+///
+/// # Returns
+///
+/// The camera of the `cylinders` scene.
+pub fn cylinders_camera() -> Camera {
+    Camera::new(
+        800,
+        16.0 / 9.0,
+        100,
+        50,
+        |_| Vector3::new(0.05, 0.05, 0.08),
+        45.0,
+        Vector3::new(0.0, 3.0, 10.0),
+        Vector3::new(0.0, 1.2, 0.0),
+        Vector3::new(0.0, 1.0, 0.0),
+        0.0,
+        1.0,
+    )
+}
+
+/// Creates a scene with 3 tori, a checkered one lying on the floor, a mirror standing behind it and
+/// a small glass one standing in front.
+///
+/// # Returns
+///
+/// The scene.
+pub fn tori_scene() -> Scene {
+    let world: Vec<Box<dyn Hittable>> = vec![
+        Box::new(Quad::new(
+            Vector3::new(-8.0, 0.0, -6.0),
+            Vector3::new(16.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, 12.0),
+            Arc::new(Lambertian::new(Vector3::new(0.73, 0.73, 0.73))),
+        )),
+        Box::new(Torus::new(
+            Vector3::new(-1.6, 0.35, 0.3),
+            Vector3::new(0.0, 1.0, 0.0),
+            1.0,
+            0.35,
+            Arc::new(Lambertian::from_texture(Box::new(
+                CheckerTexture::new(
+                    12.0,
+                    Vector3::new(0.9, 0.9, 0.9),
+                    Vector3::new(0.8, 0.2, 0.2),
+                )
+                .with_space(TextureSpace::Uv),
+            ))),
+        )),
+        Box::new(Torus::new(
+            Vector3::new(1.2, 1.3, -1.0),
+            Vector3::new(0.3, 0.0, 1.0),
+            1.0,
+            0.3,
+            Arc::new(Metal::new(Vector3::new(0.9, 0.9, 0.9), 0.0)),
+        )),
+        Box::new(Torus::new(
+            Vector3::new(0.2, 0.65, 1.6),
+            Vector3::new(1.0, 0.0, 0.5),
+            0.5,
+            0.15,
+            Arc::new(Dielectric::new(1.5)),
+        )),
+        Box::new(Quad::disk(
+            Vector3::new(0.0, 6.0, 1.0),
+            Vector3::new(0.0, -1.0, 0.0),
+            1.5,
+            Arc::new(DiffuseLight::new(Vector3::new(6.0, 6.0, 6.0))),
+        )),
+    ];
+    Scene::new(world)
+}
+
+/// Builds the camera of the `tori` scene.
+///
+/// # Returns
+///
+/// The camera of the `tori` scene.
+pub fn tori_camera() -> Camera {
+    Camera::new(
+        800,
+        16.0 / 9.0,
+        100,
+        50,
+        |_| Vector3::new(0.05, 0.05, 0.08),
+        45.0,
+        Vector3::new(0.0, 3.0, 7.0),
+        Vector3::new(0.0, 1.0, 0.0),
+        Vector3::new(0.0, 1.0, 0.0),
+        0.0,
+        1.0,
+    )
+}
+
+/// Creates a scene with 3 curved panels made of bilinear patches, a saddle, a twisted ribbon and a
+/// bent sheet, checkered in their texture coordinates.
+///
+/// # Returns
+///
+/// The scene.
+pub fn curved_panels_scene() -> Scene {
+    let mut world: Vec<Box<dyn Hittable>> = Vec::new();
+
+    let checker = |odd: Vector3| {
+        Arc::new(Lambertian::from_texture(Box::new(
+            CheckerTexture::new(8.0, Vector3::new(0.9, 0.9, 0.9), odd).with_space(TextureSpace::Uv),
+        )))
+    };
+
+    // A saddle, with two opposite corners raised.
+    world.push(Box::new(BilinearPatch::new(
+        Vector3::new(-5.0, -1.0, 0.0),
+        Vector3::new(-2.0, 1.0, 0.0),
+        Vector3::new(-2.0, -1.0, -3.0),
+        Vector3::new(-5.0, 1.0, -3.0),
+        checker(Vector3::new(0.8, 0.2, 0.2)),
+    )));
+
+    // A ribbon twisted by 60 degrees from its bottom edge to its top edge.
+    world.push(Box::new(BilinearPatch::new(
+        Vector3::new(-1.0, -2.0, 0.0),
+        Vector3::new(1.0, -2.0, 0.0),
+        Vector3::new(0.5, 2.0, 0.87),
+        Vector3::new(-0.5, 2.0, -0.87),
+        checker(Vector3::new(0.2, 0.6, 0.2)),
+    )));
+
+    // A sheet bent towards the camera along one of its edges.
+    world.push(Box::new(BilinearPatch::new(
+        Vector3::new(2.0, -2.0, -2.0),
+        Vector3::new(5.0, -2.0, 1.0),
+        Vector3::new(5.0, 2.0, -2.0),
+        Vector3::new(2.0, 2.0, -2.0),
+        checker(Vector3::new(0.2, 0.2, 0.8)),
+    )));
+    Scene::new(world)
+}
+
+/// Builds the camera of the `curved_panels` scene.
+///
+/// # Returns
+///
+/// The camera of the `curved_panels` scene.
+pub fn curved_panels_camera() -> Camera {
+    Camera::new(
+        800,
+        16.0 / 9.0,
+        100,
+        50,
+        background_gradient,
+        60.0,
+        Vector3::new(0.0, 1.0, 7.0),
+        Vector3::new(0.0, 0.0, -1.0),
+        Vector3::new(0.0, 1.0, 0.0),
+        0.0,
+        1.0,
+    )
+}
+
+/// Creates a scene with an L-shaped floor plan lit by a star-shaped light, both polygons
+/// triangulated by ear clipping.
+///
+/// # Returns
+///
+/// The scene.
+pub fn polygons_scene() -> Scene {
+    let mut world: Vec<Box<dyn Hittable>> = Vec::new();
+
+    let floor_plan = [
+        (-4.0, -3.0),
+        (-4.0, 3.0),
+        (-1.0, 3.0),
+        (-1.0, 0.0),
+        (4.0, 0.0),
+        (4.0, -3.0),
+    ];
+    world.push(Box::new(Polygon::new(
+        floor_plan
+            .iter()
+            .map(|&(x, z)| Vector3::new(x, 0.0, z))
+            .collect(),
+        Arc::new(Lambertian::from_texture(Box::new(
+            CheckerTexture::new(
+                12.0,
+                Vector3::new(0.9, 0.9, 0.9),
+                Vector3::new(0.2, 0.3, 0.1),
+            )
+            .with_space(TextureSpace::Uv),
+        ))),
+    )));
+
+    // A five-pointed star facing down, its vertices alternating between the tips and the inner
+    // corners.
+    let star = (0..10)
+        .map(|i| {
+            let angle = i as f64 * std::f64::consts::PI / 5.0;
+            let radius = if i % 2 == 0 { 1.5 } else { 0.6 };
+            Vector3::new(radius * angle.cos(), 3.0, radius * angle.sin() - 1.0)
+        })
+        .collect();
+    world.push(Box::new(Polygon::new(
+        star,
+        Arc::new(DiffuseLight::new(Vector3::new(6.0, 5.0, 3.0))),
+    )));
+
+    world.push(Box::new(Sphere::new(
+        Vector3::new(-2.5, 0.7, 1.5),
+        0.7,
+        Arc::new(Metal::new(Vector3::new(0.8, 0.8, 0.9), 0.1)),
     )));
+    Scene::new(world)
+}
+
+/// Builds the camera of the `polygons` scene.
+///
+/// # Returns
+///
+/// The camera of the `polygons` scene.
+pub fn polygons_camera() -> Camera {
+    Camera::new(
+        800,
+        16.0 / 9.0,
+        100,
+        50,
+        background_gradient,
+        50.0,
+        Vector3::new(2.0, 6.0, 9.0),
+        Vector3::new(0.0, 0.5, 0.0),
+        Vector3::new(0.0, 1.0, 0.0),
+        0.0,
+        1.0,
+    )
+}
+
+/// Creates a scene with meshes swept from profiles: a turned vase and a ring revolved around an
+/// axis, and a length of molding extruded along one.
+///
+/// # Returns
+///
+/// The scene.
+pub fn sweeps_scene() -> Scene {
+    let mut world: Vec<Box<dyn Hittable>> = Vec::new();
 
     world.push(Box::new(Quad::new(
-        Vector3::new(555.0, 555.0, 555.0),
-        Vector3::new(-555.0, 0.0, 0.0),
-        Vector3::new(0.0, 0.0, -555.0),
-        white.clone(),
+        Vector3::new(-10.0, 0.0, -10.0),
+        Vector3::new(20.0, 0.0, 0.0),
+        Vector3::new(0.0, 0.0, 20.0),
+        Arc::new(Lambertian::new(Vector3::new(0.48, 0.42, 0.36))),
+    )));
+
+    let glaze = Arc::new(Metal::new(Vector3::new(0.15, 0.35, 0.6), 0.15));
+    world.push(Box::new(Translate::new(
+        Arc::new(lathe(&vase_profile(24), false, 64, glaze)),
+        Vector3::new(-1.2, 0.0, 0.0),
+    )));
+
+    // A ring lying on the floor, a circle revolved around an axis beside it.
+    let circle: Vec<(f64, f64)> = (0..24)
+        .map(|i| {
+            let angle = 2.0 * std::f64::consts::PI * i as f64 / 24.0;
+            (0.7 + 0.2 * angle.cos(), 0.2 + 0.2 * angle.sin())
+        })
+        .collect();
+    let gold = Arc::new(Metal::new(Vector3::new(0.9, 0.7, 0.3), 0.05));
+    world.push(Box::new(Translate::new(
+        Arc::new(lathe(&circle, true, 64, gold)),
+        Vector3::new(1.6, 0.0, 0.8),
+    )));
+
+    // A molding at the back, a cove between two fillets facing the camera, running along the
+    // X-axis.
+    let mut molding = vec![(0.0, 0.0), (0.0, 0.1), (0.12, 0.1)];
+    molding.extend((0..=12).map(|i| {
+        let angle = std::f64::consts::FRAC_PI_2 * i as f64 / 12.0;
+        (0.12 + 0.4 * angle.sin(), 0.1 + 0.4 * (1.0 - angle.cos()))
+    }));
+    molding.extend([(0.52, 0.6), (0.6, 0.6), (0.6, 0.7), (0.0, 0.7)]);
+    let molding = extrude(
+        &molding,
+        true,
+        8.0,
+        Arc::new(Lambertian::new(Vector3::new(0.85, 0.82, 0.75))),
+    );
+    world.push(Box::new(Translate::new(
+        Arc::new(RotateY::new(Arc::new(molding), -90.0)),
+        Vector3::new(4.0, 0.0, -2.0),
     )));
+    Scene::new(world)
+}
+
+/// Builds the camera of the `sweeps` scene.
+///
+/// # Returns
+///
+/// The camera of the `sweeps` scene.
+pub fn sweeps_camera() -> Camera {
+    Camera::new(
+        800,
+        16.0 / 9.0,
+        100,
+        50,
+        background_gradient,
+        40.0,
+        Vector3::new(0.0, 3.0, 8.0),
+        Vector3::new(0.0, 0.9, 0.0),
+        Vector3::new(0.0, 1.0, 0.0),
+        0.0,
+        1.0,
+    )
+}
+
+/// Returns the profile of a vase 2.4 units tall, going up its outside from the middle of its foot,
+/// over the lip and down its inside.
+///
+/// # Arguments
+///
+/// * `steps` - The number of steps up the outside of the vase.
+///
+/// # Returns
+///
+/// The profile, as (distance from the axis, height) points to revolve with `lathe`.
+fn vase_profile(steps: usize) -> Vec<(f64, f64)> {
+    let outside: Vec<(f64, f64)> = (0..=steps)
+        .map(|i| {
+            let height = 2.4 * i as f64 / steps as f64;
+            let radius = 0.45 + 0.3 * (2.0 * height + 0.3).sin() + 0.25 * (height / 2.4).powi(6);
+            (radius, height)
+        })
+        .collect();
+    let mut profile = vec![(0.0, 0.0)];
+    profile.extend(outside.iter().copied());
+    profile.extend(
+        outside
+            .iter()
+            .rev()
+            .map(|&(radius, height)| (radius - 0.06, height))
+            .filter(|&(_, height)| height > 0.1),
+    );
+    profile.push((0.0, 0.1));
+    profile
+}
+
+/// Creates a scene with a long row of vases receding into the distance, all instances of one finely
+/// tessellated vase whose simplified copies stand in for it further away.
+///
+/// # Returns
+///
+/// The scene.
+pub fn vase_row_scene() -> Scene {
+    let mut world: Vec<Box<dyn Hittable>> = Vec::new();
 
     world.push(Box::new(Quad::new(
-        Vector3::new(0.0, 0.0, 555.0),
-        Vector3::new(555.0, 0.0, 0.0),
-        Vector3::new(0.0, 555.0, 0.0),
-        white.clone(),
+        Vector3::new(-20.0, 0.0, -100.0),
+        Vector3::new(40.0, 0.0, 0.0),
+        Vector3::new(0.0, 0.0, 110.0),
+        Arc::new(Lambertian::new(Vector3::new(0.48, 0.42, 0.36))),
     )));
 
-    let mut box_1: Arc<dyn Hittable> = Arc::new(BoxQuad::new(
-        Vector3::new(0.0, 0.0, 0.0),
-        Vector3::new(165.0, 330.0, 165.0),
-        white.clone(),
+    let glaze = Arc::new(Metal::new(Vector3::new(0.15, 0.35, 0.6), 0.15));
+    let vase = lathe(&vase_profile(96), false, 256, glaze);
+    let vase: Arc<dyn Hittable> = Arc::new(Lod::from_mesh(vase, &[(8.0, 4000), (25.0, 600)]));
+    for i in 0..24 {
+        for x in [-1.5, 1.5] {
+            world.push(Box::new(Instance::new(
+                vase.clone(),
+                Vector3::new(x, 0.0, -4.0 * i as f64),
+            )));
+        }
+    }
+    Scene::new(world)
+}
+
+/// Builds the camera of the `vase_row` scene.
+///
+/// # Returns
+///
+/// The camera of the `vase_row` scene.
+pub fn vase_row_camera() -> Camera {
+    Camera::new(
+        800,
+        16.0 / 9.0,
+        100,
+        50,
+        background_gradient,
+        40.0,
+        Vector3::new(0.0, 2.5, 6.0),
+        Vector3::new(0.0, 1.0, -10.0),
+        Vector3::new(0.0, 1.0, 0.0),
+        0.0,
+        1.0,
+    )
+}
+
+/// Creates a scene in meters holding a vase and a ring modeled in centimeters far from the
+/// origin, the way models often come out of modeling programs, imported at their real size.
+///
+/// # Returns
+///
+/// The scene.
+pub fn framed_model_scene() -> Scene {
+    let glaze = Arc::new(Metal::new(Vector3::new(0.6, 0.25, 0.15), 0.2));
+    let vase: Arc<dyn Hittable> = Arc::new(lathe(&vase_profile(48), false, 128, glaze));
+    let ring: Vec<(f64, f64)> = (0..24)
+        .map(|i| {
+            let angle = 2.0 * std::f64::consts::PI * i as f64 / 24.0;
+            (0.9 + 0.15 * angle.cos(), 0.15 + 0.15 * angle.sin())
+        })
+        .collect();
+    let gold = Arc::new(Metal::new(Vector3::new(0.9, 0.7, 0.3), 0.05));
+    let ring: Arc<dyn Hittable> = Arc::new(lathe(&ring, true, 64, gold));
+
+    // The model, a vase 96 centimeters tall.
+    let origin = Vector3::new(350.0, -120.0, -640.0);
+    let mut model = HittableList::new();
+    model.add(Box::new(Instance::new(vase, origin).with_scale(40.0)));
+    model.add(Box::new(
+        Instance::new(ring, origin + Vector3::new(70.0, 0.0, 20.0))
+            .with_rotation(Vector3::new(1.0, 0.0, 0.0), 60.0)
+            .with_scale(40.0),
     ));
+    let mut scene = Scene::new(Vec::new());
+    scene.add_imported(Arc::new(model), Unit::Centimeters, DEFAULT_LAYER);
+    scene
+}
 
-    box_1 = Arc::new(RotateY::new(box_1, 15.0));
+/// Builds the camera of the `framed_model` scene. Only the direction it looks in is chosen, and
+/// framing the scene finds where to put it.
+///
+/// # Returns
+///
+/// The camera of the `framed_model` scene, before it frames the scene.
+pub fn framed_model_camera() -> Camera {
+    Camera::new(
+        800,
+        16.0 / 9.0,
+        100,
+        50,
+        background_gradient,
+        35.0,
+        Vector3::new(0.0, 0.0, 0.0),
+        Vector3::new(-1.0, -0.4, -2.0),
+        Vector3::new(0.0, 1.0, 0.0),
+        0.0,
+        1.0,
+    )
+}
+
+/// Creates a scene with a bumpy ball built from a triangle soup, the way STL files store meshes,
+/// cleaned up into a smooth mesh before rendering.
+///
+/// # Returns
+///
+/// The scene.
+pub fn triangle_soup_scene() -> Scene {
+    let mut world: Vec<Box<dyn Hittable>> = Vec::new();
+
+    world.push(Box::new(Quad::new(
+        Vector3::new(-10.0, 0.0, -10.0),
+        Vector3::new(20.0, 0.0, 0.0),
+        Vector3::new(0.0, 0.0, 20.0),
+        Arc::new(Lambertian::new(Vector3::new(0.48, 0.42, 0.36))),
+    )));
+
+    // An octahedron subdivided 4 times and pushed out onto a bumpy sphere.
+    let bumpy = |p: Vector3| {
+        let d = p.normalize();
+        d * (1.0 + 0.08 * (5.0 * d.x).sin() * (5.0 * d.y).sin() * (5.0 * d.z).sin())
+    };
+    let axes = [
+        Vector3::new(1.0, 0.0, 0.0),
+        Vector3::new(0.0, 1.0, 0.0),
+        Vector3::new(0.0, 0.0, 1.0),
+    ];
+    let mut faces: Vec<[Vector3; 3]> = Vec::new();
+    for signs in 0..8 {
+        let [x, y, z] = std::array::from_fn(|i| {
+            if signs & (1 << i) == 0 {
+                axes[i]
+            } else {
+                -axes[i]
+            }
+        });
+        faces.push([x, y, z]);
+    }
+    for _ in 0..4 {
+        faces = faces
+            .iter()
+            .flat_map(|&[a, b, c]| {
+                let (ab, bc, ca) = (
+                    (a + b).normalize(),
+                    (b + c).normalize(),
+                    (c + a).normalize(),
+                );
+                [[a, ab, ca], [ab, b, bc], [ca, bc, c], [ab, bc, ca]]
+            })
+            .collect();
+    }
 
+    // Every triangle has its own corners, half of them are wound the wrong way and there are no
+    // normals, which the cleanup mends.
+    let positions: Vec<Vector3> = faces
+        .iter()
+        .flat_map(|&[a, b, c]| {
+            let corners = if f64() < 0.5 { [a, b, c] } else { [a, c, b] };
+            corners.map(bumpy)
+        })
+        .collect();
+    let triangles = (0..faces.len())
+        .map(|i| [3 * i, 3 * i + 1, 3 * i + 2])
+        .collect();
+    let soup = MeshData::new(positions, Vec::new(), Vec::new(), triangles);
+    let (ball, report) = soup.build(Arc::new(Lambertian::new(Vector3::new(0.7, 0.2, 0.15))));
+    report.print();
     world.push(Box::new(Translate::new(
-        box_1,
-        Vector3::new(265.0, 0.0, 295.0),
+        Arc::new(ball),
+        Vector3::new(0.0, 1.0, 0.0),
     )));
+    Scene::new(world)
+}
 
-    let mut box_2: Arc<dyn Hittable> = Arc::new(BoxQuad::new(
-        Vector3::new(0.0, 0.0, 0.0),
-        Vector3::new(165.0, 165.0, 165.0),
-        white.clone(),
-    ));
+/// Builds the camera of the `triangle_soup` scene.
+///
+/// # Returns
+///
+/// The camera of the `triangle_soup` scene.
+pub fn triangle_soup_camera() -> Camera {
+    Camera::new(
+        800,
+        16.0 / 9.0,
+        100,
+        50,
+        background_gradient,
+        40.0,
+        Vector3::new(0.0, 2.5, 6.0),
+        Vector3::new(0.0, 1.0, 0.0),
+        Vector3::new(0.0, 1.0, 0.0),
+        0.0,
+        1.0,
+    )
+}
 
-    box_2 = Arc::new(RotateY::new(box_2, -18.0));
+/// The font of the title scene, one of the DejaVu fonts installed on most Linux systems.
+/// The font is read at run time, so the title scene fails to build where it is missing.
+const TITLE_FONT: &str = "/usr/share/fonts/truetype/dejavu/DejaVuSans-Bold.ttf";
+
+/// Creates a scene with a title of beveled gold letters standing on a floor.
+///
+/// # Returns
+///
+/// The scene, or a description of why the font could not be loaded.
+pub fn title_scene() -> Result<Scene, String> {
+    let font = Font::load(Path::new(TITLE_FONT))?;
+    let gold = Arc::new(Metal::new(Vector3::new(0.9, 0.7, 0.3), 0.1));
+    let text = Text::new("RayTracer", 1.0)
+        .with_depth(0.25)
+        .with_bevel(0.03)
+        .into_mesh(&font, gold)
+        .ok_or_else(|| format!("{}: no visible glyphs in the title", TITLE_FONT))?;
+
+    let mut world: Vec<Box<dyn Hittable>> = Vec::new();
+    let bounds = text.bounding_box().expect("meshes are bounded");
+    let center = (bounds.min.x + bounds.max.x) / 2.0;
     world.push(Box::new(Translate::new(
-        box_2,
-        Vector3::new(130.0, 0.0, 65.0),
+        Arc::new(text),
+        Vector3::new(-center, 0.0, -0.125),
     )));
+    world.push(Box::new(Quad::new(
+        Vector3::new(-20.0, 0.0, -10.0),
+        Vector3::new(40.0, 0.0, 0.0),
+        Vector3::new(0.0, 0.0, 20.0),
+        Arc::new(Lambertian::new(Vector3::new(0.2, 0.2, 0.25))),
+    )));
+    world.push(Box::new(Quad::disk(
+        Vector3::new(-2.0, 5.0, 4.0),
+        Vector3::new(0.3, -1.0, -0.6),
+        1.5,
+        Arc::new(DiffuseLight::new(Vector3::new(8.0, 8.0, 8.0))),
+    )));
+    Ok(Scene::new(world))
+}
 
-    let camera = Camera::new(
-        1920,
+/// Builds the camera of the `title` scene.
+///
+/// # Returns
+///
+/// The camera of the `title` scene.
+pub fn title_camera() -> Camera {
+    Camera::new(
+        800,
+        16.0 / 9.0,
+        100,
+        50,
+        |_| Vector3::new(0.02, 0.02, 0.04),
+        35.0,
+        Vector3::new(1.5, 2.0, 9.0),
+        Vector3::new(0.0, 0.5, 0.0),
+        Vector3::new(0.0, 1.0, 0.0),
+        0.0,
+        1.0,
+    )
+}
+
+/// Creates a scene with a sphere and a quad with light material.
+///
+/// # Returns
+///
+/// The scene.
+pub fn simple_lights_scene() -> Scene {
+    let mut world: Vec<Box<dyn Hittable>> = Vec::new();
+    let material = Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5)));
+    world.push(Box::new(Sphere::new(
+        Vector3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        material.clone(),
+    )));
+
+    world.push(Box::new(Sphere::new(
+        Vector3::new(0.0, 2.0, 0.0),
+        2.0,
+        material,
+    )));
+
+    let diff_light = Arc::new(DiffuseLight::new(Vector3::new(4.0, 4.0, 4.0)));
+    world.push(Box::new(Sphere::new(
+        Vector3::new(0.0, 7.0, 0.0),
+        2.0,
+        diff_light.clone(),
+    )));
+
+    world.push(Box::new(Quad::new(
+        Vector3::new(3.0, 1.0, -2.0),
+        Vector3::new(2.0, 0.0, 0.0),
+        Vector3::new(0.0, 2.0, 0.0),
+        diff_light,
+    )));
+    Scene::new(world)
+}
+
+/// Builds the camera of the `simple_lights` scene.
+///
+/// # Returns
+///
+/// The camera of the `simple_lights` scene.
+pub fn simple_lights_camera() -> Camera {
+    Camera::new(
+        1920,
+        16.0 / 9.0,
+        10000,
+        50,
+        |_| Vector3::new(0.0, 0.0, 0.0),
+        20.0,
+        Vector3::new(26.0, 3.0, 6.0),
+        Vector3::new(0.0, 2.0, 0.0),
+        Vector3::new(0.0, 1.0, 0.0),
+        0.0,
+        0.0,
+    )
+}
+
+/// Creates a scene with a sphere and a quad with colored light material.
+///
+/// # Returns
+///
+/// The scene.
+pub fn colored_simple_lights_scene() -> Scene {
+    let mut world: Vec<Box<dyn Hittable>> = Vec::new();
+    let material = Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5)));
+    world.push(Box::new(Sphere::new(
+        Vector3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        material.clone(),
+    )));
+
+    world.push(Box::new(Sphere::new(
+        Vector3::new(0.0, 2.0, 0.0),
+        2.0,
+        material,
+    )));
+
+    let diff_light_1 = Arc::new(DiffuseLight::new(Vector3::new(8.0, 2.0, 2.0)));
+    let diff_light_2 = Arc::new(DiffuseLight::new(Vector3::new(2.0, 2.0, 8.0)));
+    world.push(Box::new(Sphere::new(
+        Vector3::new(0.0, 7.0, 0.0),
+        2.0,
+        diff_light_1,
+    )));
+
+    world.push(Box::new(Quad::new(
+        Vector3::new(3.0, 1.0, -2.0),
+        Vector3::new(2.0, 0.0, 0.0),
+        Vector3::new(0.0, 2.0, 0.0),
+        diff_light_2,
+    )));
+    Scene::new(world)
+}
+
+/// Builds the camera of the `colored_simple_lights` scene.
+///
+/// # Returns
+///
+/// The camera of the `colored_simple_lights` scene.
+pub fn colored_simple_lights_camera() -> Camera {
+    Camera::new(
+        1920,
+        16.0 / 9.0,
+        10000,
+        50,
+        |_| Vector3::new(0.0, 0.0, 0.0),
+        20.0,
+        Vector3::new(26.0, 3.0, 6.0),
+        Vector3::new(0.0, 2.0, 0.0),
+        Vector3::new(0.0, 1.0, 0.0),
+        0.0,
+        0.0,
+    )
+}
+
+/// Creates a scene with three spheres under a warm key light and a blue spotlight, linked so the
+/// key light leaves the middle sphere to the spotlight alone and the spotlight lights nothing else.
+///
+/// # Returns
+///
+/// The scene.
+pub fn light_links_scene() -> Scene {
+    let mut scene = Scene::new(Vec::new());
+    let material = Arc::new(Lambertian::new(Vector3::new(0.6, 0.6, 0.6)));
+    scene.add_named(
+        "ground",
+        Box::new(Sphere::new(
+            Vector3::new(0.0, -1000.0, 0.0),
+            1000.0,
+            material.clone(),
+        )),
+        DEFAULT_LAYER,
+    );
+    for (name, x) in [("left", -2.5), ("hero", 0.0), ("right", 2.5)] {
+        scene.add_named(
+            name,
+            Box::new(Sphere::new(
+                Vector3::new(x, 1.0, 0.0),
+                1.0,
+                material.clone(),
+            )),
+            DEFAULT_LAYER,
+        );
+    }
+
+    scene.add_named(
+        "key",
+        Box::new(Quad::new(
+            Vector3::new(-3.0, 6.0, -1.0),
+            Vector3::new(6.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, 3.0),
+            Arc::new(DiffuseLight::new(Vector3::new(4.0, 3.2, 2.4))),
+        )),
+        DEFAULT_LAYER,
+    );
+    scene.add_named(
+        "spot",
+        Box::new(Quad::new(
+            Vector3::new(-0.75, 4.0, 1.0),
+            Vector3::new(1.5, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, 1.5),
+            Arc::new(DiffuseLight::new(Vector3::new(2.0, 5.0, 16.0))),
+        )),
+        DEFAULT_LAYER,
+    );
+    scene
+        .set_light_link("spot", LinkMode::Include, &["hero"])
+        .expect("the spotlight and the hero are named");
+    scene
+        .set_object_link("hero", LinkMode::Exclude, &["key"])
+        .expect("the hero and the key light are named");
+    scene
+}
+
+/// Builds the camera of the `light_links` scene.
+///
+/// # Returns
+///
+/// The camera of the `light_links` scene.
+pub fn light_links_camera() -> Camera {
+    Camera::new(
+        400,
+        16.0 / 9.0,
+        200,
+        50,
+        |_| Vector3::new(0.0, 0.0, 0.0),
+        35.0,
+        Vector3::new(0.0, 3.0, 10.0),
+        Vector3::new(0.0, 1.0, 0.0),
+        Vector3::new(0.0, 1.0, 0.0),
+        0.0,
+        0.0,
+    )
+}
+
+/// Creates a scene with two balls whose hits go through callbacks: one clipped to a cage by vetoing
+/// the hits between the bars, and one mapped with texture coordinates projected along the view
+/// instead of wrapped around it.
+///
+/// # Returns
+///
+/// The scene.
+pub fn hit_callbacks_scene() -> Scene {
+    let mut scene = Scene::new(Vec::new());
+    scene.add_named(
+        "ground",
+        Box::new(Sphere::new(
+            Vector3::new(0.0, -1000.0, 0.0),
+            1000.0,
+            Arc::new(Lambertian::new(Vector3::new(0.6, 0.6, 0.6))),
+        )),
+        DEFAULT_LAYER,
+    );
+    scene.add_named(
+        "cage",
+        Box::new(Sphere::new(
+            Vector3::new(-1.5, 1.0, 0.0),
+            1.0,
+            Arc::new(Metal::new(Vector3::new(0.8, 0.6, 0.3), 0.2)),
+        )),
+        DEFAULT_LAYER,
+    );
+    let checker = CheckerTexture::new(
+        8.0,
+        Vector3::new(0.2, 0.3, 0.7),
+        Vector3::new(0.9, 0.9, 0.9),
+    )
+    .with_space(TextureSpace::Uv);
+    scene.add_named(
+        "projected",
+        Box::new(Sphere::new(
+            Vector3::new(1.5, 1.0, 0.0),
+            1.0,
+            Arc::new(Lambertian::from_texture(Box::new(checker))),
+        )),
+        DEFAULT_LAYER,
+    );
+
+    // Only thin bars along the texture coordinates are kept, so the inside shows through.
+    let bar = |coordinate: f64, count: f64| (coordinate * count).fract() < 0.15;
+    scene
+        .set_on_hit(
+            "cage",
+            Arc::new(move |_: &Ray, record: &mut HitRecord| {
+                bar(record.u, 16.0) || bar(record.v, 8.0)
+            }),
+        )
+        .expect("the cage is named");
+    // The squares are laid out on the plane facing the camera, as by a slide projector.
+    scene
+        .set_on_hit(
+            "projected",
+            Arc::new(|_: &Ray, record: &mut HitRecord| {
+                let offset = record.poz - Vector3::new(1.5, 1.0, 0.0);
+                record.u = 0.5 + 0.5 * offset.x;
+                record.v = 0.5 + 0.5 * offset.y;
+                true
+            }),
+        )
+        .expect("the projected ball is named");
+    scene
+}
+
+/// Builds the camera of the `hit_callbacks` scene.
+///
+/// # Returns
+///
+/// The camera of the `hit_callbacks` scene.
+pub fn hit_callbacks_camera() -> Camera {
+    Camera::new(
+        400,
+        16.0 / 9.0,
+        100,
+        50,
+        background_gradient,
+        35.0,
+        Vector3::new(0.0, 2.0, 8.0),
+        Vector3::new(0.0, 1.0, 0.0),
+        Vector3::new(0.0, 1.0, 0.0),
+        0.0,
+        0.0,
+    )
+}
+
+/// Creates a scene with three balls hanging over the ground below a lamp, casting full, half and no
+/// shadows from it.
+///
+/// # Returns
+///
+/// The scene.
+pub fn shadow_densities_scene() -> Scene {
+    let mut scene = Scene::new(Vec::new());
+    let material = Arc::new(Lambertian::new(Vector3::new(0.6, 0.6, 0.6)));
+    scene.add_named(
+        "ground",
+        Box::new(Sphere::new(
+            Vector3::new(0.0, -1000.0, 0.0),
+            1000.0,
+            material.clone(),
+        )),
+        DEFAULT_LAYER,
+    );
+    for (name, x) in [("full", -2.5), ("half", 0.0), ("none", 2.5)] {
+        scene.add_named(
+            name,
+            Box::new(Sphere::new(
+                Vector3::new(x, 1.5, 0.0),
+                0.8,
+                material.clone(),
+            )),
+            DEFAULT_LAYER,
+        );
+    }
+    scene.add_named(
+        "lamp",
+        Box::new(Quad::new(
+            Vector3::new(-4.0, 6.0, -1.0),
+            Vector3::new(8.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, 2.0),
+            Arc::new(DiffuseLight::new(Vector3::new(4.0, 3.6, 3.0))),
+        )),
+        DEFAULT_LAYER,
+    );
+    scene
+        .set_shadow_density("lamp", &["half"], 0.5)
+        .expect("the lamp and the ball are named");
+    scene
+        .set_shadow_density("lamp", &["none"], 0.0)
+        .expect("the lamp and the ball are named");
+    scene
+}
+
+/// Builds the camera of the `shadow_densities` scene.
+///
+/// # Returns
+///
+/// The camera of the `shadow_densities` scene.
+pub fn shadow_densities_camera() -> Camera {
+    Camera::new(
+        400,
+        16.0 / 9.0,
+        200,
+        50,
+        |_| Vector3::new(0.0, 0.0, 0.0),
+        35.0,
+        Vector3::new(0.0, 4.0, 10.0),
+        Vector3::new(0.0, 0.8, 0.0),
+        Vector3::new(0.0, 1.0, 0.0),
+        0.0,
+        0.0,
+    )
+}
+
+/// Creates a scene with a row of glass balls growing rougher from left to right, from clear to
+/// frosted glass, before a checkered wall blurred through them.
+///
+/// # Returns
+///
+/// The scene.
+pub fn rough_glass_scene() -> Scene {
+    let mut world: Vec<Box<dyn Hittable>> = Vec::new();
+
+    let checker = Box::new(CheckerTexture::new(
+        0.5,
+        Vector3::new(0.1, 0.1, 0.1),
+        Vector3::new(0.8, 0.8, 0.8),
+    ));
+    world.push(Box::new(Quad::new(
+        Vector3::new(-8.0, 0.0, -3.0),
+        Vector3::new(16.0, 0.0, 0.0),
+        Vector3::new(0.0, 8.0, 0.0),
+        Arc::new(Lambertian::from_texture(checker)),
+    )));
+    world.push(Box::new(Sphere::new(
+        Vector3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        Arc::new(Lambertian::new(Vector3::new(0.5, 0.45, 0.4))),
+    )));
+    for (x, roughness) in [(-3.3, 0.0), (-1.1, 0.15), (1.1, 0.35), (3.3, 0.6)] {
+        world.push(Box::new(Sphere::new(
+            Vector3::new(x, 1.0, 0.0),
+            1.0,
+            Arc::new(RoughDielectric::new(1.5, roughness)),
+        )));
+    }
+    world.push(Box::new(Quad::new(
+        Vector3::new(-3.0, 7.0, 1.0),
+        Vector3::new(6.0, 0.0, 0.0),
+        Vector3::new(0.0, 0.0, 3.0),
+        Arc::new(DiffuseLight::new(Vector3::new(5.0, 5.0, 5.0))),
+    )));
+    Scene::new(world)
+}
+
+/// Builds the camera of the `rough_glass` scene.
+///
+/// # Returns
+///
+/// The camera of the `rough_glass` scene.
+pub fn rough_glass_camera() -> Camera {
+    Camera::new(
+        400,
+        16.0 / 9.0,
+        500,
+        50,
+        |_| Vector3::new(0.0, 0.0, 0.0),
+        40.0,
+        Vector3::new(0.0, 2.0, 9.0),
+        Vector3::new(0.0, 1.2, 0.0),
+        Vector3::new(0.0, 1.0, 0.0),
+        0.0,
+        0.0,
+    )
+}
+
+/// Creates a scene with a row of balls made of the principled material: glossy plastic, matte
+/// rubber, brushed gold, green frosted glass and a glowing ceramic.
+///
+/// # Returns
+///
+/// The scene.
+pub fn principled_scene() -> Scene {
+    let mut world: Vec<Box<dyn Hittable>> = Vec::new();
+
+    world.push(Box::new(Sphere::new(
+        Vector3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        Arc::new(Principled::new(Vector3::new(0.5, 0.5, 0.5)).with_roughness(0.3)),
+    )));
+    let plastic = Principled::new(Vector3::new(0.7, 0.05, 0.05)).with_roughness(0.1);
+    let rubber = Principled::new(Vector3::new(0.1, 0.1, 0.12))
+        .with_roughness(0.8)
+        .with_specular(0.2);
+    let gold = Principled::new(Vector3::new(1.0, 0.78, 0.34))
+        .with_metallic(1.0)
+        .with_roughness(0.3);
+    let glass = Principled::new(Vector3::new(0.6, 0.9, 0.7))
+        .with_transmission(1.0)
+        .with_roughness(0.15)
+        .with_refraction_index(1.5);
+    let ceramic = Principled::new(Vector3::new(0.9, 0.9, 0.85))
+        .with_roughness(0.2)
+        .with_emission(Vector3::new(1.0, 0.6, 0.2), 2.0);
+    for (x, material) in [
+        (-4.4, plastic),
+        (-2.2, rubber),
+        (0.0, gold),
+        (2.2, glass),
+        (4.4, ceramic),
+    ] {
+        world.push(Box::new(Sphere::new(
+            Vector3::new(x, 1.0, 0.0),
+            1.0,
+            Arc::new(material),
+        )));
+    }
+    world.push(Box::new(Quad::new(
+        Vector3::new(-3.0, 7.0, 1.0),
+        Vector3::new(6.0, 0.0, 0.0),
+        Vector3::new(0.0, 0.0, 3.0),
+        Arc::new(DiffuseLight::new(Vector3::new(5.0, 5.0, 5.0))),
+    )));
+    Scene::new(world)
+}
+
+/// Builds the camera of the `principled` scene.
+///
+/// # Returns
+///
+/// The camera of the `principled` scene.
+pub fn principled_camera() -> Camera {
+    Camera::new(
+        400,
+        16.0 / 9.0,
+        500,
+        50,
+        |_| Vector3::new(0.05, 0.05, 0.08),
+        40.0,
+        Vector3::new(0.0, 3.0, 11.0),
+        Vector3::new(0.0, 1.0, 0.0),
+        Vector3::new(0.0, 1.0, 0.0),
+        0.0,
+        0.0,
+    )
+}
+
+/// Creates a study scene for parameter sweeps: a ball of metal and a ball of glass on a gray floor
+/// under an area light. The roughness of both balls, the index of refraction of the glass and the
+/// intensity of the light are read from the sweep parameters, and default to 0.3, 1.5 and 4.
+///
+/// # Arguments
+///
+/// * `settings` - The settings given on the command line, holding the swept parameter if any.
+///
+/// # Returns
+///
+/// The scene.
+pub fn material_study_scene(settings: &RenderSettings) -> Scene {
+    let roughness = settings.parameter(SweepParameter::Roughness, 0.3);
+    let refraction_index = settings.parameter(SweepParameter::RefractionIndex, 1.5);
+    let intensity = settings.parameter(SweepParameter::LightIntensity, 4.0);
+    let mut world: Vec<Box<dyn Hittable>> = Vec::new();
+
+    world.push(Box::new(Sphere::new(
+        Vector3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        Arc::new(Lambertian::from_texture(Box::new(CheckerTexture::new(
+            0.5,
+            Vector3::new(0.3, 0.3, 0.3),
+            Vector3::new(0.6, 0.6, 0.6),
+        )))),
+    )));
+    let metal = Principled::new(Vector3::new(0.9, 0.6, 0.3))
+        .with_metallic(1.0)
+        .with_roughness(roughness);
+    let glass = Principled::new(Vector3::new(0.95, 0.95, 0.95))
+        .with_transmission(1.0)
+        .with_roughness(roughness)
+        .with_refraction_index(refraction_index);
+    for (x, material) in [(-1.2, metal), (1.2, glass)] {
+        world.push(Box::new(Sphere::new(
+            Vector3::new(x, 1.0, 0.0),
+            1.0,
+            Arc::new(material),
+        )));
+    }
+    world.push(Box::new(Quad::new(
+        Vector3::new(-2.0, 5.0, -1.0),
+        Vector3::new(4.0, 0.0, 0.0),
+        Vector3::new(0.0, 0.0, 2.0),
+        Arc::new(DiffuseLight::new(Vector3::new(1.0, 1.0, 1.0) * intensity)),
+    )));
+    Scene::new(world)
+}
+
+/// Builds the camera of the `material_study` scene.
+///
+/// # Returns
+///
+/// The camera of the `material_study` scene.
+pub fn material_study_camera() -> Camera {
+    Camera::new(
+        400,
+        4.0 / 3.0,
+        256,
+        50,
+        |_| Vector3::new(0.05, 0.05, 0.08),
+        35.0,
+        Vector3::new(0.0, 2.5, 7.5),
+        Vector3::new(0.0, 0.9, 0.0),
+        Vector3::new(0.0, 1.0, 0.0),
+        0.0,
+        0.0,
+    )
+}
+
+/// Creates a scene with textured metals and glass: a checkered chrome and gold floor, a metal globe
+/// mapped with the earth, a ball of stained glass and a ball of glass frosted in a checkered
+/// pattern.
+///
+/// # Returns
+///
+/// The scene.
+pub fn textured_metal_and_glass_scene() -> Scene {
+    let mut world: Vec<Box<dyn Hittable>> = Vec::new();
+
+    world.push(Box::new(Sphere::new(
+        Vector3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        Arc::new(Metal::from_texture(
+            Box::new(CheckerTexture::new(
+                0.5,
+                Vector3::new(0.8, 0.8, 0.85),
+                Vector3::new(0.85, 0.65, 0.3),
+            )),
+            0.2,
+        )),
+    )));
+    world.push(Box::new(Sphere::new(
+        Vector3::new(-2.4, 1.0, 0.0),
+        1.0,
+        Arc::new(Metal::from_texture(
+            Box::new(ImageTexture::new("earthmap.jpg")),
+            0.1,
+        )),
+    )));
+    let stained = Dielectric::new(1.5).with_tint(Box::new(CheckerTexture::new(
+        3.0,
+        Vector3::new(0.9, 0.3, 0.3),
+        Vector3::new(0.3, 0.5, 0.9),
+    )));
+    world.push(Box::new(Sphere::new(
+        Vector3::new(0.0, 1.0, 0.0),
+        1.0,
+        Arc::new(stained),
+    )));
+    let frosted =
+        RoughDielectric::new(1.5, 0.0).with_roughness_texture(Box::new(CheckerTexture::new(
+            3.0,
+            Vector3::new(0.5, 0.5, 0.5),
+            Vector3::new(0.0, 0.0, 0.0),
+        )));
+    world.push(Box::new(Sphere::new(
+        Vector3::new(2.4, 1.0, 0.0),
+        1.0,
+        Arc::new(frosted),
+    )));
+    world.push(Box::new(Quad::new(
+        Vector3::new(-3.0, 7.0, 1.0),
+        Vector3::new(6.0, 0.0, 0.0),
+        Vector3::new(0.0, 0.0, 3.0),
+        Arc::new(DiffuseLight::new(Vector3::new(5.0, 5.0, 5.0))),
+    )));
+    Scene::new(world)
+}
+
+/// Builds the camera of the `textured_metal_and_glass` scene.
+///
+/// # Returns
+///
+/// The camera of the `textured_metal_and_glass` scene.
+pub fn textured_metal_and_glass_camera() -> Camera {
+    Camera::new(
+        400,
+        16.0 / 9.0,
+        500,
+        50,
+        |_| Vector3::new(0.3, 0.4, 0.5),
+        40.0,
+        Vector3::new(0.0, 3.0, 9.0),
+        Vector3::new(0.0, 1.0, 0.0),
+        Vector3::new(0.0, 1.0, 0.0),
+        0.0,
+        0.0,
+    )
+}
+
+/// Creates a scene with a row of balls of the same green glass growing larger from left to right,
+/// absorbing more light the farther it travels through them.
+///
+/// # Returns
+///
+/// The scene.
+pub fn colored_glass_scene() -> Scene {
+    let mut world: Vec<Box<dyn Hittable>> = Vec::new();
+
+    world.push(Box::new(Sphere::new(
+        Vector3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        Arc::new(Lambertian::new(Vector3::new(0.8, 0.8, 0.8))),
+    )));
+    let green = Arc::new(Dielectric::new(1.5).with_absorption(Vector3::new(0.3, 0.8, 0.4), 1.0));
+    let mut x = -4.0;
+    for radius in [0.25, 0.5, 0.9, 1.4] {
+        x += radius;
+        world.push(Box::new(Sphere::new(
+            Vector3::new(x, radius, 0.0),
+            radius,
+            green.clone(),
+        )));
+        x += radius + 0.4;
+    }
+    world.push(Box::new(Quad::new(
+        Vector3::new(-3.0, 7.0, -1.0),
+        Vector3::new(6.0, 0.0, 0.0),
+        Vector3::new(0.0, 0.0, 3.0),
+        Arc::new(DiffuseLight::new(Vector3::new(5.0, 5.0, 5.0))),
+    )));
+    Scene::new(world)
+}
+
+/// Builds the camera of the `colored_glass` scene.
+///
+/// # Returns
+///
+/// The camera of the `colored_glass` scene.
+pub fn colored_glass_camera() -> Camera {
+    Camera::new(
+        400,
+        16.0 / 9.0,
+        500,
+        50,
+        |_| Vector3::new(0.6, 0.7, 0.9),
+        40.0,
+        Vector3::new(0.0, 2.5, 9.0),
+        Vector3::new(0.0, 0.8, 0.0),
+        Vector3::new(0.0, 1.0, 0.0),
+        0.0,
+        0.0,
+    )
+}
+
+/// Creates a scene with three translucent balls of skin, wax and marble, lit from behind so the
+/// light glows through their edges.
+///
+/// # Returns
+///
+/// The scene.
+pub fn subsurface_scene() -> Scene {
+    let mut world: Vec<Box<dyn Hittable>> = Vec::new();
+
+    world.push(Box::new(Sphere::new(
+        Vector3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        Arc::new(Lambertian::new(Vector3::new(0.4, 0.4, 0.45))),
+    )));
+    let skin = Subsurface::new(Vector3::new(0.85, 0.55, 0.45), Vector3::new(0.4, 0.25, 0.2));
+    let wax = Subsurface::new(Vector3::new(0.95, 0.9, 0.7), Vector3::new(0.3, 0.3, 0.25));
+    let marble = Subsurface::from_texture(
+        Box::new(NoiseTexture::new(3.0, Vector3::new(0.0, 0.0, 0.0))),
+        Vector3::new(0.15, 0.15, 0.15),
+    )
+    .with_refraction_index(1.5);
+    for (x, material) in [(-2.2, skin), (0.0, wax), (2.2, marble)] {
+        world.push(Box::new(Sphere::new(
+            Vector3::new(x, 1.0, 0.0),
+            1.0,
+            Arc::new(material),
+        )));
+    }
+    world.push(Box::new(Quad::new(
+        Vector3::new(-4.0, 4.6, -4.0),
+        Vector3::new(8.0, 0.0, 0.0),
+        Vector3::new(0.0, 2.0, 0.0),
+        Arc::new(DiffuseLight::new(Vector3::new(6.0, 5.5, 5.0))),
+    )));
+    world.push(Box::new(Quad::new(
+        Vector3::new(-3.0, 6.0, 3.0),
+        Vector3::new(6.0, 0.0, 0.0),
+        Vector3::new(0.0, 0.0, 2.0),
+        Arc::new(DiffuseLight::new(Vector3::new(1.0, 1.0, 1.0))),
+    )));
+    Scene::new(world)
+}
+
+/// Builds the camera of the `subsurface` scene.
+///
+/// # Returns
+///
+/// The camera of the `subsurface` scene.
+pub fn subsurface_camera() -> Camera {
+    Camera::new(
+        400,
+        16.0 / 9.0,
+        500,
+        128,
+        |_| Vector3::new(0.0, 0.0, 0.0),
+        35.0,
+        Vector3::new(0.0, 2.5, 9.0),
+        Vector3::new(0.0, 1.0, 0.0),
+        Vector3::new(0.0, 1.0, 0.0),
+        0.0,
+        0.0,
+    )
+}
+
+/// Creates a scene with a chrome ball in focus before a field of small colored lights far behind
+/// it, blurred into the hexagons of a six-bladed aperture.
+///
+/// # Returns
+///
+/// The scene.
+pub fn bokeh_scene() -> Scene {
+    let mut world: Vec<Box<dyn Hittable>> = Vec::new();
+
+    world.push(Box::new(Quad::new(
+        Vector3::new(-40.0, 0.0, -40.0),
+        Vector3::new(80.0, 0.0, 0.0),
+        Vector3::new(0.0, 0.0, 50.0),
+        Arc::new(Lambertian::new(Vector3::new(0.15, 0.13, 0.12))),
+    )));
+    world.push(Box::new(Sphere::new(
+        Vector3::new(0.0, 0.6, 0.0),
+        0.6,
+        Arc::new(Metal::new(Vector3::new(0.9, 0.9, 0.9), 0.02)),
+    )));
+    world.push(Box::new(Quad::disk(
+        Vector3::new(3.0, 6.0, 3.0),
+        Vector3::new(-0.5, -1.0, -0.5),
+        1.5,
+        Arc::new(DiffuseLight::new(Vector3::new(6.0, 6.0, 6.0))),
+    )));
+
+    let mut palette = Palette::new(3).with_harmony(Harmony::Triadic);
+    for _ in 0..40 {
+        let color = palette.next_color();
+        world.push(Box::new(Sphere::new(
+            Vector3::new(f64() * 40.0 - 20.0, f64() * 8.0 + 1.0, -15.0 - f64() * 20.0),
+            0.12,
+            Arc::new(DiffuseLight::new(color).with_intensity(80.0)),
+        )));
+    }
+    Scene::new(world)
+}
+
+/// Builds the camera of the `bokeh` scene.
+///
+/// # Returns
+///
+/// The camera of the `bokeh` scene.
+pub fn bokeh_camera() -> Camera {
+    let mut camera = Camera::new(
+        800,
+        16.0 / 9.0,
+        100,
+        50,
+        |_| Vector3::new(0.01, 0.01, 0.02),
+        30.0,
+        Vector3::new(0.0, 1.0, 5.0),
+        Vector3::new(0.0, 0.6, 0.0),
+        Vector3::new(0.0, 1.0, 0.0),
+        4.0,
+        5.0,
+    );
+    camera.set_aperture(Aperture::polygon(6, 90.0));
+    camera
+}
+
+/// Builds the Cornell box scene: a closed room with a red and a green wall, lit by a square light
+/// in the ceiling, holding two rotated white boxes.
+///
+/// # Returns
+///
+/// The Cornell box scene.
+pub fn cornell_box_scene() -> Scene {
+    cornell_box_scene_with(Dispatch::TraitObjects)
+}
+
+/// Builds the Cornell box scene of `cornell_box_scene` with its materials dispatched as chosen.
+///
+/// # Arguments
+///
+/// * `dispatch` - How the materials are dispatched on every bounce.
+///
+/// # Returns
+///
+/// The Cornell box scene.
+pub fn cornell_box_scene_with(dispatch: Dispatch) -> Scene {
+    let mut world: Vec<Box<dyn Hittable>> = Vec::new();
+
+    let lambertian = |albedo: Vector3| MaterialKind::from(Lambertian::new(albedo)).shared(dispatch);
+    let red = lambertian(Vector3::new(0.65, 0.05, 0.05));
+    let white = lambertian(Vector3::new(0.73, 0.73, 0.73));
+    let green = lambertian(Vector3::new(0.12, 0.45, 0.15));
+    let light =
+        MaterialKind::from(DiffuseLight::new(Vector3::new(15.0, 15.0, 15.0))).shared(dispatch);
+
+    world.push(Box::new(Quad::new(
+        Vector3::new(555.0, 0.0, 0.0),
+        Vector3::new(0.0, 555.0, 0.0),
+        Vector3::new(0.0, 0.0, 555.0),
+        green,
+    )));
+
+    world.push(Box::new(Quad::new(
+        Vector3::new(0.0, 0.0, 0.0),
+        Vector3::new(0.0, 555.0, 0.0),
+        Vector3::new(0.0, 0.0, 555.0),
+        red,
+    )));
+
+    world.push(Box::new(Quad::new(
+        Vector3::new(343.0, 554.0, 332.0),
+        Vector3::new(-130.0, 0.0, 0.0),
+        Vector3::new(0.0, 0.0, -105.0),
+        light,
+    )));
+
+    world.push(Box::new(Quad::new(
+        Vector3::new(0.0, 0.0, 0.0),
+        Vector3::new(555.0, 0.0, 0.0),
+        Vector3::new(0.0, 0.0, 555.0),
+        white.clone(),
+    )));
+
+    world.push(Box::new(Quad::new(
+        Vector3::new(555.0, 555.0, 555.0),
+        Vector3::new(-555.0, 0.0, 0.0),
+        Vector3::new(0.0, 0.0, -555.0),
+        white.clone(),
+    )));
+
+    world.push(Box::new(Quad::new(
+        Vector3::new(0.0, 0.0, 555.0),
+        Vector3::new(555.0, 0.0, 0.0),
+        Vector3::new(0.0, 555.0, 0.0),
+        white.clone(),
+    )));
+
+    let mut box_1: Arc<dyn Hittable> = Arc::new(BoxQuad::new(
+        Vector3::new(0.0, 0.0, 0.0),
+        Vector3::new(165.0, 330.0, 165.0),
+        white.clone(),
+    ));
+
+    box_1 = Arc::new(RotateY::new(box_1, 15.0));
+
+    world.push(Box::new(Translate::new(
+        box_1,
+        Vector3::new(265.0, 0.0, 295.0),
+    )));
+
+    let mut box_2: Arc<dyn Hittable> = Arc::new(BoxQuad::new(
+        Vector3::new(0.0, 0.0, 0.0),
+        Vector3::new(165.0, 165.0, 165.0),
+        white.clone(),
+    ));
+
+    box_2 = Arc::new(RotateY::new(box_2, -18.0));
+    world.push(Box::new(Translate::new(
+        box_2,
+        Vector3::new(130.0, 0.0, 65.0),
+    )));
+
+    // The box is 555 millimeters wide, like the real one.
+    let mut scene = Scene::new(world);
+    scene.set_unit(Unit::Millimeters);
+    scene
+}
+
+/// Builds the camera looking into the Cornell box.
+///
+/// # Arguments
+///
+/// * `image_width` - The width of the image in pixels.
+/// * `samples` - The number of samples per pixel.
+///
+/// # Returns
+///
+/// The camera of the Cornell box scene.
+pub fn cornell_box_camera(image_width: u32, samples: u32) -> Camera {
+    Camera::new(
+        image_width,
+        16.0 / 9.0,
+        samples,
+        5,
+        |_| Vector3::new(0.0, 0.0, 0.0),
+        40.0,
+        Vector3::new(278.0, 278.0, -800.0),
+        Vector3::new(278.0, 278.0, 0.0),
+        Vector3::new(0.0, 1.0, 0.0),
+        0.0,
+        0.0,
+    )
+}
+
+/// Creates a Cornell box closed by a front wall, to be rendered from outside. The wall faces into
+/// the room, so camera rays pass through its back while light keeps bouncing off its front.
+///
+/// # Returns
+///
+/// The scene.
+pub fn closed_cornell_box_scene() -> Scene {
+    let mut scene = cornell_box_scene();
+    let white = Arc::new(Lambertian::new(Vector3::new(0.73, 0.73, 0.73)));
+    scene.add(
+        Box::new(
+            Quad::new(
+                Vector3::new(0.0, 0.0, 0.0),
+                Vector3::new(555.0, 0.0, 0.0),
+                Vector3::new(0.0, 555.0, 0.0),
+                white,
+            )
+            .with_backface_culling(),
+        ),
+        DEFAULT_LAYER,
+    );
+    scene
+}
+
+/// Creates a Cornell box closed by a front wall, holding a sphere cut in half with a red cap.
+///
+/// # Returns
+///
+/// The scene.
+pub fn cornell_cutaway_scene() -> Scene {
+    let mut scene = cornell_box_scene();
+    let white = Arc::new(Lambertian::new(Vector3::new(0.73, 0.73, 0.73)));
+    scene.add(
+        Box::new(Quad::new(
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(555.0, 0.0, 0.0),
+            Vector3::new(0.0, 555.0, 0.0),
+            white.clone(),
+        )),
+        DEFAULT_LAYER,
+    );
+
+    let center = Vector3::new(212.0, 235.0, 147.0);
+    scene.add(
+        Box::new(
+            Section::new(
+                Box::new(Sphere::new(center, 70.0, white)),
+                center,
+                Vector3::new(-0.5, 0.3, -1.0),
+            )
+            .with_cap(Arc::new(Lambertian::new(Vector3::new(0.65, 0.05, 0.05)))),
+        ),
+        DEFAULT_LAYER,
+    );
+    scene
+}
+
+/// Builds the camera of the `cornell_cutaway` scene, rendering a cutaway of the box. Its near
+/// clipping plane removes the front wall, so the room keeps the light it reflects.
+///
+/// # Returns
+///
+/// The camera of the `cornell_cutaway` scene.
+pub fn cornell_cutaway_camera() -> Camera {
+    let mut camera = cornell_box_camera(600, 500);
+    // The front wall is 800 units in front of the camera.
+    camera.set_clipping(810.0, f64::INFINITY);
+    camera
+}
+
+/// Creates a Cornell box scene with 2 boxes made out of smoke. Smoke scattering light mostly
+/// forward, like fog, lets the light from the ceiling pass down through it instead of scattering
+/// towards the camera, which leaves the boxes darker and softer than isotropic smoke, with the
+/// floor beneath them lit through them.
+///
+/// # Arguments
+///
+/// * `anisotropy` - The asymmetry of the scattering of the smoke, zero for isotropic smoke.
+///
+/// # Returns
+///
+/// The scene.
+pub fn smoky_cornell_box_scene(anisotropy: f64) -> Scene {
+    let mut world: Vec<Box<dyn Hittable>> = Vec::new();
+
+    let red = Arc::new(Lambertian::new(Vector3::new(0.65, 0.05, 0.05)));
+    let white = Arc::new(Lambertian::new(Vector3::new(0.73, 0.73, 0.73)));
+    let green = Arc::new(Lambertian::new(Vector3::new(0.12, 0.45, 0.15)));
+    let light = Arc::new(DiffuseLight::new(Vector3::new(7.0, 7.0, 7.0)));
+
+    world.push(Box::new(Quad::new(
+        Vector3::new(555.0, 0.0, 0.0),
+        Vector3::new(0.0, 555.0, 0.0),
+        Vector3::new(0.0, 0.0, 555.0),
+        green,
+    )));
+
+    world.push(Box::new(Quad::new(
+        Vector3::new(0.0, 0.0, 0.0),
+        Vector3::new(0.0, 555.0, 0.0),
+        Vector3::new(0.0, 0.0, 555.0),
+        red,
+    )));
+
+    world.push(Box::new(Quad::new(
+        Vector3::new(113.0, 554.0, 127.0),
+        Vector3::new(330.0, 0.0, 0.0),
+        Vector3::new(0.0, 0.0, 305.0),
+        light,
+    )));
+
+    world.push(Box::new(Quad::new(
+        Vector3::new(0.0, 0.0, 0.0),
+        Vector3::new(555.0, 0.0, 0.0),
+        Vector3::new(0.0, 0.0, 555.0),
+        white.clone(),
+    )));
+
+    world.push(Box::new(Quad::new(
+        Vector3::new(555.0, 555.0, 555.0),
+        Vector3::new(-555.0, 0.0, 0.0),
+        Vector3::new(0.0, 0.0, -555.0),
+        white.clone(),
+    )));
+
+    world.push(Box::new(Quad::new(
+        Vector3::new(0.0, 0.0, 555.0),
+        Vector3::new(555.0, 0.0, 0.0),
+        Vector3::new(0.0, 555.0, 0.0),
+        white.clone(),
+    )));
+
+    let mut scene = Scene::new(world);
+    scene.set_unit(Unit::Millimeters);
+    // Smoke and fog thin enough to see 10 centimeters into.
+    let density = scene.density(10.0);
+
+    let mut box_1: Arc<dyn Hittable> = Arc::new(BoxQuad::new(
+        Vector3::new(0.0, 0.0, 0.0),
+        Vector3::new(165.0, 330.0, 165.0),
+        white.clone(),
+    ));
+
+    box_1 = Arc::new(RotateY::new(box_1, 15.0));
+    let box_1 = Translate::new(box_1, Vector3::new(265.0, 0.0, 295.0));
+    let fog_1 = ConstantMedium::anisotropic(
+        Box::new(box_1),
+        density,
+        Vector3::new(0.0, 0.0, 0.0),
+        anisotropy,
+    );
+    scene.add(Box::new(fog_1), DEFAULT_LAYER);
+
+    let mut box_2: Arc<dyn Hittable> = Arc::new(BoxQuad::new(
+        Vector3::new(0.0, 0.0, 0.0),
+        Vector3::new(165.0, 165.0, 165.0),
+        white.clone(),
+    ));
+
+    box_2 = Arc::new(RotateY::new(box_2, -18.0));
+    let box_2 = Translate::new(box_2, Vector3::new(130.0, 0.0, 65.0));
+    let fog_2 = ConstantMedium::anisotropic(
+        Box::new(box_2),
+        density,
+        Vector3::new(1.0, 1.0, 1.0),
+        anisotropy,
+    );
+    scene.add(Box::new(fog_2), DEFAULT_LAYER);
+    scene
+}
+
+/// Creates the final scene with various objects and materials.
+///
+/// # Arguments
+///
+/// * `reduced` - A boolean flag to reduce the number of objects in the scene for faster rendering.
+///
+/// # Returns
+///
+/// The scene.
+pub fn final_scene(reduced: bool) -> Scene {
+    let mut world: Vec<Box<dyn Hittable>> = Vec::new();
+
+    let ground = Arc::new(Lambertian::new(Vector3::new(0.48, 0.83, 0.53)));
+
+    let boxes_per_side = if reduced { 5 } else { 20 };
+
+    for i in 0..boxes_per_side {
+        for j in 0..boxes_per_side {
+            let w = 100.0 * (20.0 / boxes_per_side as f64);
+            let x0 = -1000.0 + (i as f64) * w;
+            let z0 = -1000.0 + (j as f64) * w;
+            let y0 = 0.0;
+            let x1 = x0 + w;
+            let y1 = f64() * 100.0 + 1.0;
+            let z1 = z0 + w;
+
+            world.push(Box::new(BoxQuad::new(
+                Vector3::new(x0, y0, z0),
+                Vector3::new(x1, y1, z1),
+                ground.clone(),
+            )));
+        }
+    }
+
+    let light = Arc::new(DiffuseLight::new(Vector3::new(7.0, 7.0, 7.0)));
+    world.push(Box::new(Quad::new(
+        Vector3::new(123.0, 554.0, 147.0),
+        Vector3::new(300.0, 0.0, 0.0),
+        Vector3::new(0.0, 0.0, 265.0),
+        light,
+    )));
+
+    let center = Vector3::new(400.0, 400.0, 200.0);
+    let sphere_material = Arc::new(Lambertian::new(Vector3::new(0.7, 0.3, 0.1)));
+
+    world.push(Box::new(Sphere::new(center, 50.0, sphere_material)));
+    world.push(Box::new(Sphere::new(
+        Vector3::new(260.0, 150.0, 45.0),
+        50.0,
+        Arc::new(Dielectric::new(1.5)),
+    )));
+
+    world.push(Box::new(Sphere::new(
+        Vector3::new(0.0, 150.0, 145.0),
+        50.0,
+        Arc::new(Metal::new(Vector3::new(0.8, 0.8, 0.9), 1.0)),
+    )));
+
+    let mut boundary = Box::new(Sphere::new(
+        Vector3::new(360.0, 150.0, 145.0),
+        70.0,
+        Arc::new(Dielectric::new(1.5)),
+    ));
+
+    world.push(boundary);
+
+    world.push(Box::new(ConstantMedium::new(
+        Box::new(Sphere::new(
+            Vector3::new(360.0, 150.0, 145.0),
+            70.0,
+            Arc::new(Dielectric::new(1.5)),
+        )),
+        0.02,
+        Vector3::new(0.2, 0.4, 0.9),
+    )));
+
+    boundary = Box::new(Sphere::new(
+        Vector3::new(0.0, 0.0, 0.0),
+        5000.0,
+        Arc::new(Dielectric::new(1.5)),
+    ));
+    world.push(Box::new(ConstantMedium::new(
+        boundary,
+        0.0001,
+        Vector3::new(1.0, 1.0, 1.0),
+    )));
+
+    let emat = Arc::new(Lambertian::from_texture(Box::new(ImageTexture::new(
+        "earthmap.jpg",
+    ))));
+
+    world.push(Box::new(Sphere::new(
+        Vector3::new(400.0, 200.0, 400.0),
+        100.0,
+        emat,
+    )));
+
+    let mirror = Arc::new(Metal::new(Vector3::new(0.8, 0.8, 0.8), 0.0));
+    world.push(Box::new(Sphere::new(
+        Vector3::new(220.0, 280.0, 300.0),
+        80.0,
+        mirror,
+    )));
+
+    if !reduced {
+        let white = Arc::new(Lambertian::new(Vector3::new(0.73, 0.73, 0.73)));
+        let ns = 1000;
+
+        // Every small sphere is an instance of one shared sphere.
+        let sphere: Arc<dyn Hittable> =
+            Arc::new(Sphere::new(Vector3::new(0.0, 0.0, 0.0), 10.0, white));
+        let mut spheres = HittableList::new();
+        for _ in 0..ns {
+            spheres.add(Box::new(Instance::new(
+                sphere.clone(),
+                Vector3::random(0.0, 165.0),
+            )));
+        }
+        let rotate = RotateY::new(Arc::new(spheres), 15.0);
+        let translate = Translate::new(Arc::new(rotate), Vector3::new(-100.0, 270.0, 395.0));
+        world.push(Box::new(translate));
+    }
+    Scene::new(world)
+}
+
+/// Builds the camera of the final scene.
+///
+/// # Arguments
+///
+/// * `image_width` - The width of the image in pixels.
+/// * `samples` - The number of samples per pixel.
+/// * `max_depth` - The maximum depth for ray tracing.
+///
+/// # Returns
+///
+/// The camera of the final scene.
+pub fn final_scene_camera(image_width: u32, samples: u32, max_depth: u32) -> Camera {
+    Camera::new(
+        image_width,
+        16.0 / 9.0,
+        samples,
+        max_depth,
+        |_| Vector3::new(0.0, 0.0, 0.0),
+        40.0,
+        Vector3::new(478.0, 278.0, -600.0),
+        Vector3::new(278.0, 278.0, 0.0),
+        Vector3::new(0.0, 1.0, 0.0),
+        0.0,
+        0.0,
+    )
+}
+
+/// Builds the refractive caustics scene: a glass sphere floating above a checkered floor and lit
+/// from the side by a small, bright light, so that the sphere focuses the light into a caustic
+/// spot inside its own shadow.
+///
+/// # Returns
+///
+/// The refractive caustics scene.
+pub fn caustics_scene() -> Scene {
+    let mut world: Vec<Box<dyn Hittable>> = Vec::new();
+
+    let checker = Box::new(CheckerTexture::new(
+        1.0,
+        Vector3::new(0.3, 0.3, 0.3),
+        Vector3::new(0.8, 0.8, 0.8),
+    ));
+    world.push(Box::new(Quad::new(
+        Vector3::new(-10.0, 0.0, 10.0),
+        Vector3::new(20.0, 0.0, 0.0),
+        Vector3::new(0.0, 0.0, -20.0),
+        Arc::new(Lambertian::from_texture(checker)),
+    )));
+
+    world.push(Box::new(Sphere::new(
+        Vector3::new(0.0, 1.5, 0.0),
+        1.0,
+        Arc::new(Dielectric::new(1.5)),
+    )));
+
+    let light = Arc::new(DiffuseLight::new(Vector3::new(25.0, 25.0, 25.0)));
+    world.push(Box::new(Quad::new(
+        Vector3::new(-4.75, 5.75, -0.75),
+        Vector3::new(0.0, 0.0, 1.5),
+        Vector3::new(1.5, -1.5, 0.0) * (0.5_f64).sqrt(),
+        light,
+    )));
+
+    Scene::new(world)
+}
+
+/// Builds the camera of the refractive caustics scene.
+///
+/// # Arguments
+///
+/// * `image_width` - The width of the image in pixels.
+/// * `samples` - The number of samples per pixel.
+///
+/// # Returns
+///
+/// The camera of the refractive caustics scene.
+pub fn caustics_camera(image_width: u32, samples: u32) -> Camera {
+    Camera::new(
+        image_width,
+        4.0 / 3.0,
+        samples,
+        10,
+        |_| Vector3::new(0.0, 0.0, 0.0),
+        30.0,
+        Vector3::new(7.0, 5.0, 5.0),
+        Vector3::new(1.0, 0.5, 0.0),
+        Vector3::new(0.0, 1.0, 0.0),
+        0.0,
+        0.0,
+    )
+}
+
+/// Builds the refractive caustics scene together with its camera.
+///
+/// # Arguments
+///
+/// * `image_width` - The width of the image in pixels.
+/// * `samples` - The number of samples per pixel.
+///
+/// # Returns
+///
+/// A tuple containing the camera and the scene.
+pub fn caustics_world(image_width: u32, samples: u32) -> (Camera, Scene) {
+    (caustics_camera(image_width, samples), caustics_scene())
+}
+
+/// Creates a scene with time-varying textures, to be rendered as a sequence of frames: a globe
+/// whose texture scrolls around it, a drifting marble floor and a flickering screen.
+///
+/// # Returns
+///
+/// The scene.
+pub fn animated_textures_scene() -> Scene {
+    let mut world: Vec<Box<dyn Hittable>> = Vec::new();
+
+    let floor_texture = Box::new(NoiseTexture::new(4.0, Vector3::new(0.5, 0.0, 0.25)));
+    world.push(Box::new(Sphere::new(
+        Vector3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        Arc::new(Lambertian::from_texture(floor_texture)),
+    )));
+
+    let earth_texture = Box::new(ScrollingTexture::new(
+        Box::new(ImageTexture::new("earthmap.jpg")),
+        1.0,
+        0.0,
+    ));
+    world.push(Box::new(Sphere::new(
+        Vector3::new(0.0, 2.0, 0.0),
+        2.0,
+        Arc::new(Lambertian::from_texture(earth_texture)),
+    )));
+
+    let screen_texture = Box::new(PulseTexture::new(
+        Box::new(SolidTexture::new(Vector3::new(2.0, 3.0, 4.0))),
+        3.0,
+        0.2,
+    ));
+    world.push(Box::new(Quad::new(
+        Vector3::new(3.0, 1.0, -2.0),
+        Vector3::new(2.0, 0.0, 0.0),
+        Vector3::new(0.0, 2.0, 0.0),
+        Arc::new(DiffuseLight::from_texture(screen_texture)),
+    )));
+
+    Scene::new(world)
+}
+
+/// Builds the camera of the `animated_textures` scene. The shutter stays open for half of every
+/// frame and opens and closes gradually, so the motion blurs.
+///
+/// # Arguments
+///
+/// * `frames` - The number of frames rendered over one unit of scene time.
+///
+/// # Returns
+///
+/// The camera of the `animated_textures` scene.
+pub fn animated_textures_camera(frames: u32) -> Camera {
+    let mut camera = Camera::new(
+        400,
+        16.0 / 9.0,
+        100,
+        50,
+        background_gradient,
+        20.0,
+        Vector3::new(26.0, 3.0, 6.0),
+        Vector3::new(0.0, 2.0, 0.0),
+        Vector3::new(0.0, 1.0, 0.0),
+        0.0,
+        0.0,
+    );
+    camera.set_output_path("frame.png");
+    camera.set_shutter(Shutter::new(0.5 / frames as f64).with_curve(vec![1.0, 2.0, 2.0, 1.0]));
+    camera
+}
+
+/// Renders a sequence of frames spread evenly over one unit of scene time, saving every frame
+/// with its number added to the output path.
+///
+/// # Arguments
+///
+/// * `camera` - The camera rendering the frames.
+/// * `scene` - The scene to render.
+/// * `frames` - The number of frames to render, one unit of scene time apart divided by `frames`.
+pub fn render_frames(mut camera: Camera, scene: &Scene, frames: u32) {
+    for frame in 0..frames {
+        camera.set_time(frame as f64 / frames as f64);
+        camera.render_to_file(
+            scene,
+            &camera.output_path_with_suffix(&format!("{:03}", frame)),
+        );
+    }
+}
+
+/// Creates a Cornell box scene with a sphere named `subject` resting on the short box, whose
+/// material `render_material_variants` overrides.
+///
+/// # Returns
+///
+/// The scene.
+pub fn material_variants_scene() -> Scene {
+    let mut scene = cornell_box_scene();
+    scene.add_named(
+        "subject",
+        Box::new(Sphere::new(
+            Vector3::new(212.0, 235.0, 147.0),
+            70.0,
+            Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5))),
+        )),
+        DEFAULT_LAYER,
+    );
+    scene
+}
+
+/// Renders variations of the look of the scene of `material_variants_scene`: the material of the
+/// sphere is overridden by name for every render, without rebuilding the scene, and every image
+/// is saved with the name of its variant added.
+///
+/// # Arguments
+///
+/// * `camera` - The camera rendering the variants.
+/// * `scene` - The scene holding the sphere named `subject`.
+pub fn render_material_variants(camera: Camera, mut scene: Scene) {
+    let variants: [(&str, Arc<dyn Material>); 3] = [
+        (
+            "clay",
+            Arc::new(Lambertian::new(Vector3::new(0.8, 0.6, 0.5))),
+        ),
+        (
+            "chrome",
+            Arc::new(Metal::new(Vector3::new(0.9, 0.9, 0.9), 0.0)),
+        ),
+        ("glass", Arc::new(Dielectric::new(1.5))),
+    ];
+
+    for (name, material) in variants {
+        let overrides = MaterialOverrides::from([("subject".to_string(), material)]);
+        scene
+            .set_material_overrides(&overrides)
+            .expect("the subject is part of the scene");
+        camera.render_to_file(&scene, &camera.output_path_with_suffix(name));
+    }
+}
+
+/// Creates a Cornell box scene split into render layers, to render every layer on its own: the
+/// room in the background layer, the boxes in the foreground layer and a glass sphere in the
+/// effects layer.
+///
+/// # Returns
+///
+/// The scene.
+pub fn layered_cornell_box_scene() -> Scene {
+    let mut scene = Scene::new(Vec::new());
+
+    let red = Arc::new(Lambertian::new(Vector3::new(0.65, 0.05, 0.05)));
+    let white = Arc::new(Lambertian::new(Vector3::new(0.73, 0.73, 0.73)));
+    let green = Arc::new(Lambertian::new(Vector3::new(0.12, 0.45, 0.15)));
+    let light = Arc::new(DiffuseLight::new(Vector3::new(15.0, 15.0, 15.0)));
+
+    let room: [(Vector3, Vector3, Vector3, Arc<dyn Material>); 6] = [
+        (
+            Vector3::new(555.0, 0.0, 0.0),
+            Vector3::new(0.0, 555.0, 0.0),
+            Vector3::new(0.0, 0.0, 555.0),
+            green,
+        ),
+        (
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(0.0, 555.0, 0.0),
+            Vector3::new(0.0, 0.0, 555.0),
+            red,
+        ),
+        (
+            Vector3::new(343.0, 554.0, 332.0),
+            Vector3::new(-130.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, -105.0),
+            light,
+        ),
+        (
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(555.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, 555.0),
+            white.clone(),
+        ),
+        (
+            Vector3::new(555.0, 555.0, 555.0),
+            Vector3::new(-555.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, -555.0),
+            white.clone(),
+        ),
+        (
+            Vector3::new(0.0, 0.0, 555.0),
+            Vector3::new(555.0, 0.0, 0.0),
+            Vector3::new(0.0, 555.0, 0.0),
+            white.clone(),
+        ),
+    ];
+    for (corner, u, v, material) in room {
+        scene.add(Box::new(Quad::new(corner, u, v, material)), "background");
+    }
+
+    let box_1: Arc<dyn Hittable> = Arc::new(RotateY::new(
+        Arc::new(BoxQuad::new(
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(165.0, 330.0, 165.0),
+            white.clone(),
+        )),
+        15.0,
+    ));
+    scene.add(
+        Box::new(Translate::new(box_1, Vector3::new(265.0, 0.0, 295.0))),
+        "foreground",
+    );
+
+    let box_2: Arc<dyn Hittable> = Arc::new(RotateY::new(
+        Arc::new(BoxQuad::new(
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(165.0, 165.0, 165.0),
+            white,
+        )),
+        -18.0,
+    ));
+    scene.add(
+        Box::new(Translate::new(box_2, Vector3::new(130.0, 0.0, 65.0))),
+        "foreground",
+    );
+
+    scene.add(
+        Box::new(Sphere::new(
+            Vector3::new(212.5, 255.0, 147.5),
+            90.0,
+            Arc::new(Dielectric::new(1.5)),
+        )),
+        "fx",
+    );
+    scene
+}
+
+/// Builds the camera of the `layered_cornell_box` scene.
+///
+/// # Returns
+///
+/// The camera of the `layered_cornell_box` scene.
+pub fn layered_cornell_box_camera() -> Camera {
+    Camera::new(
+        600,
+        1.0,
+        200,
+        10,
+        |_| Vector3::new(0.0, 0.0, 0.0),
+        40.0,
+        Vector3::new(278.0, 278.0, -800.0),
+        Vector3::new(278.0, 278.0, 0.0),
+        Vector3::new(0.0, 1.0, 0.0),
+        0.0,
+        0.0,
+    )
+}
+
+/// Draws a simple tree sprite: a green triangular canopy on a brown trunk, on a transparent
+/// background.
+///
+/// # Returns
+///
+/// The sprite as an image texture.
+fn tree_sprite() -> ImageTexture {
+    let size = 64;
+    let mut image = RgbaImage::new(size, size);
+    for y in 0..size {
+        for x in 0..size {
+            let dx = (x as f64 + 0.5) / size as f64 - 0.5;
+            let height = 1.0 - (y as f64 + 0.5) / size as f64;
+
+            let pixel = if height > 0.2 && dx.abs() < 0.45 * (1.0 - height) / 0.8 {
+                Rgba([40, 120, 40, 255])
+            } else if height <= 0.2 && dx.abs() < 0.06 {
+                Rgba([100, 70, 40, 255])
+            } else {
+                Rgba([0, 0, 0, 0])
+            };
+            image.put_pixel(x, y, pixel);
+        }
+    }
+    ImageTexture::from_image(&DynamicImage::ImageRgba8(image))
+}
+
+/// Creates a scene with a forest of trees around a glass sphere. Trees close to the ray origin are
+/// round bushes, and distant trees fall back to billboards.
+///
+/// # Returns
+///
+/// The scene.
+pub fn billboards_scene() -> Scene {
+    let mut world: Vec<Box<dyn Hittable>> = Vec::new();
+
+    let ground = Sphere::new(
+        Vector3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        Arc::new(Lambertian::new(Vector3::new(0.4, 0.6, 0.3))),
+    );
+
+    // The trees gather in groves following the bands of a marble noise.
+    let scatter = SurfaceScatter::new((-40.0, -80.0), (40.0, 0.0), 2.5)
+        .with_density(Box::new(NoiseTexture::new(
+            0.2,
+            Vector3::new(0.0, 0.0, 0.0),
+        )))
+        .with_seed(7);
+    let placements = scatter.place_on(&ground);
+    world.push(Box::new(ground));
+
+    let sprite = Arc::new(tree_sprite());
+    let foliage = Arc::new(Lambertian::new(Vector3::new(0.16, 0.47, 0.16)));
+    for placement in placements {
+        let base = placement.point;
+        let height = 2.0 + f64() * 3.0;
+        let center = base + Vector3::new(0.0, height * 0.5, 0.0);
+
+        let bush = Sphere::new(center, height * 0.4, foliage.clone());
+        let billboard = Billboard::from_image(base, height * 0.8, height, sprite.clone());
+        world.push(Box::new(Lod::new(
+            center,
+            vec![
+                (20.0, Box::new(bush) as Box<dyn Hittable>),
+                (f64::INFINITY, Box::new(billboard)),
+            ],
+        )));
+    }
+
+    world.push(Box::new(Sphere::new(
+        Vector3::new(0.0, 1.5, 6.0),
+        1.5,
+        Arc::new(Dielectric::new(1.5)),
+    )));
+    Scene::new(world)
+}
+
+/// Builds the camera of the `billboards` scene.
+///
+/// # Returns
+///
+/// The camera of the `billboards` scene.
+pub fn billboards_camera() -> Camera {
+    Camera::new(
+        400,
+        16.0 / 9.0,
+        100,
+        50,
+        background_gradient,
+        40.0,
+        Vector3::new(0.0, 3.0, 16.0),
+        Vector3::new(0.0, 2.0, 0.0),
+        Vector3::new(0.0, 1.0, 0.0),
+        0.0,
+        0.0,
+    )
+}
+
+/// Creates a scene with an orchard of one shared tree, every instance with its own size, shade of
+/// green and, for a few of them, autumn leaves.
+///
+/// # Returns
+///
+/// The scene.
+pub fn orchard_scene() -> Scene {
+    let mut world: Vec<Box<dyn Hittable>> = Vec::new();
+
+    let ground = Sphere::new(
+        Vector3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        Arc::new(Lambertian::new(Vector3::new(0.45, 0.55, 0.3))),
+    );
+    let scatter = SurfaceScatter::new((-12.0, -24.0), (12.0, 2.0), 2.2).with_seed(11);
+    let placements = scatter.place_on(&ground);
+    world.push(Box::new(ground));
+
+    // The leaves of every tree are tinted by its instance, and one tree in six turns red.
+    let leaves = Box::new(TintedTexture::new(Box::new(SolidTexture::new(
+        Vector3::new(0.2, 0.5, 0.15),
+    ))));
+    let autumn = Box::new(SolidTexture::new(Vector3::new(0.75, 0.25, 0.05)));
+    let foliage = Arc::new(Lambertian::from_texture(Box::new(SurfaceMaskTexture::new(
+        SurfaceQuery::Variation,
+        leaves,
+        autumn,
+        (0.83, 0.83),
+    ))));
+    let bark = Arc::new(Lambertian::new(Vector3::new(0.35, 0.22, 0.12)));
+    let mut tree = HittableList::new();
+    tree.add(Box::new(
+        Cylinder::new(
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            0.12,
+            bark,
+        )
+        .with_caps(true),
+    ));
+    tree.add(Box::new(Sphere::new(
+        Vector3::new(0.0, 1.6, 0.0),
+        0.8,
+        foliage,
+    )));
+    let tree: Arc<dyn Hittable> = Arc::new(tree);
+
+    let mut rng = fastrand::Rng::with_seed(11);
+    for (i, placement) in placements.into_iter().enumerate() {
+        let shade = 0.7 + 0.6 * rng.f64();
+        let tint = Vector3::new(shade * (0.8 + 0.4 * rng.f64()), shade, shade * 0.9);
+        world.push(Box::new(
+            Instance::new(tree.clone(), placement.point)
+                .with_rotation(Vector3::new(0.0, 1.0, 0.0), 360.0 * rng.f64())
+                .with_jitter(i as u64, 0.35)
+                .with_tint(tint),
+        ));
+    }
+    Scene::new(world)
+}
+
+/// Builds the camera of the `orchard` scene.
+///
+/// # Returns
+///
+/// The camera of the `orchard` scene.
+pub fn orchard_camera() -> Camera {
+    Camera::new(
+        400,
+        16.0 / 9.0,
+        100,
+        50,
+        background_gradient,
+        40.0,
+        Vector3::new(0.0, 3.5, 9.0),
+        Vector3::new(0.0, 1.0, -6.0),
+        Vector3::new(0.0, 1.0, 0.0),
+        0.0,
+        0.0,
+    )
+}
+
+/// Creates a scene with two screens showing an image from their front face and staying dark at the
+/// back, one facing the camera and one turned away from it.
+///
+/// # Returns
+///
+/// The scene.
+pub fn screens_scene() -> Scene {
+    let mut world: Vec<Box<dyn Hittable>> = Vec::new();
+
+    world.push(Box::new(Sphere::new(
+        Vector3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5))),
+    )));
+
+    let screen = Arc::new(DiffuseLight::two_sided(
+        Box::new(ImageTexture::new("earthmap.jpg")),
+        Box::new(SolidTexture::new(Vector3::new(0.0, 0.0, 0.0))),
+    ));
+
+    // The front face is on the side of u x v, towards the camera for the left screen only.
+    world.push(Box::new(Quad::new(
+        Vector3::new(-3.5, 0.5, 0.0),
+        Vector3::new(3.0, 0.0, 0.0),
+        Vector3::new(0.0, 2.0, 0.0),
+        screen.clone(),
+    )));
+    world.push(Box::new(Quad::new(
+        Vector3::new(3.5, 0.5, 0.0),
+        Vector3::new(-3.0, 0.0, 0.0),
+        Vector3::new(0.0, 2.0, 0.0),
+        screen,
+    )));
+    Scene::new(world)
+}
+
+/// Builds the camera of the `screens` scene.
+///
+/// # Returns
+///
+/// The camera of the `screens` scene.
+pub fn screens_camera() -> Camera {
+    Camera::new(
+        800,
+        16.0 / 9.0,
+        500,
+        50,
+        |_| Vector3::new(0.05, 0.05, 0.05),
+        40.0,
+        Vector3::new(0.0, 3.0, 12.0),
+        Vector3::new(0.0, 1.5, 0.0),
+        Vector3::new(0.0, 1.0, 0.0),
+        0.0,
+        0.0,
+    )
+}
+
+/// Creates a scene with a golden Mandelbulb and a Menger sponge balanced on a corner, standing on a
+/// checkered ground, both ray-marched from their distance estimators.
+///
+/// # Returns
+///
+/// The scene.
+pub fn fractals_scene() -> Scene {
+    let mut world: Vec<Box<dyn Hittable>> = Vec::new();
+
+    let checker = Box::new(CheckerTexture::new(
+        0.5,
+        Vector3::new(0.2, 0.3, 0.1),
+        Vector3::new(0.9, 0.9, 0.9),
+    ));
+    world.push(Box::new(Sphere::new(
+        Vector3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        Arc::new(Lambertian::from_texture(checker)),
+    )));
+
+    let gold = Arc::new(Metal::new(Vector3::new(0.85, 0.65, 0.3), 0.1));
+    world.push(Box::new(Mandelbulb::new(8.0, 12, 2.0).into_shape(
+        Vector3::new(-1.4, 1.2, 0.0),
+        1.0,
+        gold,
+    )));
+
+    // The tunnels of the sponge take more steps to march through than the default allows. The
+    // sponge is turned to stand on one of its corners, with its opposite corner straight above.
+    let stone = Arc::new(Lambertian::new(Vector3::new(0.7, 0.7, 0.75)));
+    let mut sponge: Arc<dyn Hittable> = Arc::new(
+        MengerSponge::new(4)
+            .into_shape(Vector3::new(0.0, 0.0, 0.0), 1.0, stone)
+            .with_precision(1e-4, 512),
+    );
+    sponge = Arc::new(Rotate::around_z(sponge, 45.0));
+    sponge = Arc::new(Rotate::around_x(
+        sponge,
+        -(0.5f64.sqrt().atan().to_degrees()),
+    ));
+    world.push(Box::new(Translate::new(
+        sponge,
+        Vector3::new(1.6, 3.0f64.sqrt(), 0.0),
+    )));
+    Scene::new(world)
+}
+
+/// Builds the camera of the `fractals` scene.
+///
+/// # Returns
+///
+/// The camera of the `fractals` scene.
+pub fn fractals_camera() -> Camera {
+    Camera::new(
+        800,
+        16.0 / 9.0,
+        64,
+        10,
+        background_gradient,
+        35.0,
+        Vector3::new(1.0, 3.5, 9.0),
+        Vector3::new(0.2, 1.5, 0.0),
+        Vector3::new(0.0, 1.0, 0.0),
+        0.0,
+        0.0,
+    )
+}
+
+/// Returns the signed distance from a point to an axis-aligned box.
+///
+/// # Arguments
+///
+/// * `point` - The point to measure from.
+/// * `center` - The center of the box.
+/// * `half` - Half the size of the box along each axis.
+///
+/// # Returns
+///
+/// The distance to the box, negative inside it.
+fn box_distance(point: Vector3, center: Vector3, half: Vector3) -> f64 {
+    let q = point - center;
+    let q = Vector3::new(q.x.abs() - half.x, q.y.abs() - half.y, q.z.abs() - half.z);
+    let outside = Vector3::new(q.x.max(0.0), q.y.max(0.0), q.z.max(0.0)).length();
+    outside + q.max().min(0.0)
+}
+
+/// Creates a scene with shapes built by smoothly blending distance functions, a cluster of spheres
+/// melted into one blob, a box with a sphere scooped out of it and a lens rounded from a sphere and
+/// a slab.
+///
+/// # Returns
+///
+/// The scene.
+pub fn blobs_scene() -> Scene {
+    let sphere =
+        |center: Vector3, radius: f64| move |point: Vector3| (point - center).length() - radius;
+
+    let checker = Box::new(CheckerTexture::new(
+        0.5,
+        Vector3::new(0.2, 0.3, 0.1),
+        Vector3::new(0.9, 0.9, 0.9),
+    ));
+    let mut world: Vec<Box<dyn Hittable>> = vec![Box::new(Sphere::new(
+        Vector3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        Arc::new(Lambertian::from_texture(checker)),
+    ))];
+
+    let balls = [
+        sphere(Vector3::new(-2.6, 0.7, 0.0), 0.7),
+        sphere(Vector3::new(-1.8, 0.9, -0.3), 0.6),
+        sphere(Vector3::new(-2.3, 1.6, -0.2), 0.5),
+        sphere(Vector3::new(-2.0, 0.45, 0.7), 0.45),
+    ];
+    world.push(Box::new(SdfShape::new(
+        move |point| {
+            balls
+                .iter()
+                .map(|ball| ball(point))
+                .reduce(|a, b| smooth_union(a, b, 0.4))
+                .unwrap()
+        },
+        Aabb::new(Vector3::new(-3.5, 0.0, -1.2), Vector3::new(-1.0, 2.3, 1.4)),
+        Arc::new(Lambertian::new(Vector3::new(0.8, 0.3, 0.25))),
+    )));
+
+    let center = Vector3::new(0.0, 0.8, 0.0);
+    let scoop = sphere(Vector3::new(0.0, 1.6, 0.3), 0.8);
+    world.push(Box::new(SdfShape::new(
+        move |point| {
+            smooth_subtraction(
+                box_distance(point, center, Vector3::new(0.8, 0.8, 0.8)),
+                scoop(point),
+                0.15,
+            )
+        },
+        Aabb::new(Vector3::new(-0.9, -0.1, -0.9), Vector3::new(0.9, 1.7, 0.9)),
+        Arc::new(Metal::new(Vector3::new(0.8, 0.8, 0.85), 0.05)),
+    )));
+
+    let lens = sphere(Vector3::new(2.3, 1.0, 0.0), 1.0);
+    let slab_center = Vector3::new(2.3, 1.0, 0.0);
+    world.push(Box::new(SdfShape::new(
+        move |point| {
+            smooth_intersection(
+                lens(point),
+                box_distance(point, slab_center, Vector3::new(1.0, 1.0, 0.35)),
+                0.2,
+            )
+        },
+        Aabb::new(Vector3::new(1.2, -0.1, -0.5), Vector3::new(3.4, 2.1, 0.5)),
+        Arc::new(Dielectric::new(1.5)),
+    )));
+    Scene::new(world)
+}
+
+/// Builds the camera of the `blobs` scene.
+///
+/// # Returns
+///
+/// The camera of the `blobs` scene.
+pub fn blobs_camera() -> Camera {
+    Camera::new(
+        800,
+        16.0 / 9.0,
+        100,
+        50,
+        background_gradient,
+        35.0,
+        Vector3::new(0.0, 3.0, 9.0),
+        Vector3::new(0.0, 0.9, 0.0),
+        Vector3::new(0.0, 1.0, 0.0),
+        0.0,
+        0.0,
+    )
+}
+
+/// The glTF file rendered by the `skinned_animation` scene, when it exists.
+const SKINNED_MODEL: &str = "character.glb";
+
+/// Builds an open tube standing on the origin, bound to a chain of three joints stacked along it
+/// and swaying side to side over two seconds.
+fn swaying_tube() -> SkinnedMesh {
+    let (rings, segments, radius) = (31, 24, 0.3);
+    let mut mesh = MeshData::default();
+    let mut joints = Vec::new();
+    let mut weights = Vec::new();
+    for ring in 0..rings {
+        let height = 3.0 * ring as f64 / (rings - 1) as f64;
+        // Every joint leads the third of the tube above it, blending into its neighbours.
+        let influence = |joint: usize| (1.0 - (height - (joint as f64 + 0.5)).abs()).max(0.0);
+        for segment in 0..=segments {
+            let angle = 2.0 * std::f64::consts::PI * segment as f64 / segments as f64;
+            let normal = Vector3::new(angle.cos(), 0.0, -angle.sin());
+            mesh.positions
+                .push(radius * normal + Vector3::new(0.0, height, 0.0));
+            mesh.normals.push(normal);
+            mesh.uvs
+                .push((segment as f64 / segments as f64, height / 3.0));
+            joints.push([0, 1, 2, 0]);
+            weights.push([influence(0), influence(1), influence(2), 0.0]);
+        }
+    }
+    let row = segments + 1;
+    for ring in 0..rings - 1 {
+        for segment in 0..segments {
+            let (a, b) = (ring * row + segment, ring * row + segment + 1);
+            mesh.triangles.push([a, b, b + row]);
+            mesh.triangles.push([a, b + row, a + row]);
+        }
+    }
+
+    let joint = |name: &str, parent: Option<usize>, height: f64| Joint {
+        name: name.to_string(),
+        parent,
+        base: Affine::IDENTITY,
+        rest: JointPose {
+            translation: Vector3::new(0.0, if parent.is_some() { 1.0 } else { 0.0 }, 0.0),
+            ..JointPose::default()
+        },
+        inverse_bind: Affine::from_pose(&JointPose {
+            translation: Vector3::new(0.0, -height, 0.0),
+            ..JointPose::default()
+        }),
+    };
+    let skeleton = Skeleton::new(vec![
+        joint("root", None, 0.0),
+        joint("middle", Some(0), 1.0),
+        joint("tip", Some(1), 2.0),
+    ]);
+
+    let sway = |joint: usize, angle: f64| {
+        Channel::new(
+            joint,
+            Property::Rotation,
+            vec![0.0, 0.5, 1.0, 1.5, 2.0],
+            [0.0, angle, 0.0, -angle, 0.0]
+                .map(|angle| quaternion(Vector3::new(0.0, 0.0, 1.0), angle))
+                .to_vec(),
+            Interpolation::Linear,
+        )
+    };
+    SkinnedMesh::new(mesh, joints, weights, skeleton)
+        .with_animation(Animation::new("sway", vec![sway(1, 25.0), sway(2, 35.0)]))
+}
+
+/// Loads the skinned mesh of the `skinned_animation` scene: the one in `character.glb` if the file
+/// exists, and a swaying tube otherwise.
+///
+/// # Returns
+///
+/// The skinned mesh, which has at least one animation, or a description of why it could not be
+/// loaded.
+fn skinned_model() -> Result<SkinnedMesh, String> {
+    if !Path::new(SKINNED_MODEL).exists() {
+        return Ok(swaying_tube());
+    }
+    let skinned = SkinnedMesh::load(Path::new(SKINNED_MODEL))?;
+    if skinned.animations().is_empty() {
+        return Err(format!("{}: no animation", SKINNED_MODEL));
+    }
+    Ok(skinned)
+}
+
+/// The material of the skinned mesh of the `skinned_animation` scene.
+fn skinned_material() -> Arc<dyn Material> {
+    Arc::new(Lambertian::new(Vector3::new(0.8, 0.5, 0.2)))
+}
+
+/// Creates the scene of the first frame of a skeletal animation: the skinned mesh in
+/// `character.glb` if the file exists, and a swaying tube otherwise, in the pose of the start of
+/// its first animation. The mesh is scaled to stand three units tall on a checkered ground.
+///
+/// # Returns
+///
+/// The scene, or a description of why the skinned mesh could not be loaded.
+pub fn skinned_animation_scene() -> Result<Scene, String> {
+    let skinned = skinned_model()?;
+    let mesh = skinned.frame(&skinned.animations()[0], 0.0, skinned_material());
+    let bounds = mesh.bounding_box().expect("meshes are bounded");
+    Ok(deforming_mesh_scene(Arc::new(mesh), &bounds))
+}
+
+/// Renders the frames of a skeletal animation, deforming the skinned mesh of
+/// `skinned_animation_scene` into the pose of every frame. The mesh is loaded again, as the frames
+/// are not rendered from a single scene.
+///
+/// # Arguments
+///
+/// * `camera` - The camera rendering the frames.
+/// * `frames` - The number of frames to render, spread evenly over the animation.
+pub fn skinned_animation(camera: Camera, frames: u32) {
+    let skinned = match skinned_model() {
+        Ok(skinned) => skinned,
+        Err(e) => {
+            eprintln!("{}", e);
+            return;
+        }
+    };
+    let animation = &skinned.animations()[0];
+    let names: Vec<&str> = skinned
+        .skeleton()
+        .joints()
+        .iter()
+        .map(|joint| joint.name.as_str())
+        .collect();
+    println!(
+        "Animation {} moving the joints {} over {} seconds",
+        animation.name,
+        names.join(", "),
+        animation.duration()
+    );
+
+    render_deforming_mesh(
+        camera,
+        frames,
+        animation.duration(),
+        skinned.frame(animation, 0.0, skinned_material()),
+        |time, mesh| skinned.refit_frame(animation, time, mesh),
+    );
+}
+
+/// Creates the scene of a frame of a deforming mesh standing on a checkered ground. The mesh is
+/// scaled to stand three units tall in the first frame of its animation.
+///
+/// # Arguments
+///
+/// * `mesh` - The mesh in the pose of the frame.
+/// * `first` - The bounding box of the mesh in the first frame.
+///
+/// # Returns
+///
+/// The scene.
+fn deforming_mesh_scene(mesh: Arc<TriangleMesh>, first: &Aabb) -> Scene {
+    let scale = 3.0 / (first.max.y - first.min.y);
+    let offset = -scale
+        * Vector3::new(
+            (first.min.x + first.max.x) / 2.0,
+            first.min.y,
+            (first.min.z + first.max.z) / 2.0,
+        );
+    let checker = Box::new(CheckerTexture::new(
+        0.5,
+        Vector3::new(0.2, 0.3, 0.1),
+        Vector3::new(0.9, 0.9, 0.9),
+    ));
+    Scene::new(vec![
+        Box::new(Sphere::new(
+            Vector3::new(0.0, -1000.0, 0.0),
+            1000.0,
+            Arc::new(Lambertian::from_texture(checker)),
+        )),
+        Box::new(Instance::new(mesh, offset).with_scale(scale)),
+    ])
+}
+
+/// Builds the camera of the scenes of deforming meshes, saving their frames to `frame.png` with
+/// the number of the frame added.
+///
+/// # Returns
+///
+/// The camera of the scenes of deforming meshes.
+pub fn deforming_mesh_camera() -> Camera {
+    let mut camera = Camera::new(
+        400,
+        16.0 / 9.0,
+        100,
+        50,
+        background_gradient,
+        35.0,
+        Vector3::new(0.0, 2.5, 9.0),
+        Vector3::new(0.0, 1.5, 0.0),
+        Vector3::new(0.0, 1.0, 0.0),
+        0.0,
+        0.0,
+    );
+    camera.set_output_path("frame.png");
+    camera
+}
+
+/// Renders the frames of a deforming mesh standing on a checkered ground, spread evenly over an
+/// animation, as in `deforming_mesh_scene`. Every frame is saved with its number added to the
+/// output path.
+///
+/// The mesh is built once and its vertices are moved for every later frame, refitting its
+/// hierarchy, and the number of frames for which it had to be rebuilt is printed at the end.
+///
+/// # Arguments
+///
+/// * `camera` - The camera rendering the frames.
+/// * `frames` - The number of frames to render.
+/// * `duration` - The length of the animation in seconds.
+/// * `mesh` - The mesh at the start of the animation.
+/// * `refit_at` - Moves the mesh to a time of the animation, returning whether its hierarchy was
+///   rebuilt.
+fn render_deforming_mesh(
+    camera: Camera,
+    frames: u32,
+    duration: f64,
+    mesh: TriangleMesh,
+    refit_at: impl Fn(f64, &mut TriangleMesh) -> bool,
+) {
+    let first = mesh.bounding_box().expect("meshes are bounded");
+    let mut mesh = Arc::new(mesh);
+    let mut rebuilds = 0;
+    for frame in 0..frames {
+        if frame > 0 {
+            // The scene of the previous frame is gone, so the mesh is no longer shared.
+            let mesh = Arc::get_mut(&mut mesh).expect("the mesh is only held by this loop");
+            if refit_at(duration * frame as f64 / frames as f64, mesh) {
+                rebuilds += 1;
+            }
+        }
+        camera.render_to_file(
+            &deforming_mesh_scene(mesh.clone(), &first),
+            &camera.output_path_with_suffix(&format!("{:03}", frame)),
+        );
+    }
+    println!(
+        "Refitted the mesh for {} frames, rebuilt it for {}",
+        frames.saturating_sub(1) - rebuilds,
+        rebuilds
+    );
+}
+
+/// The glTF file rendered by the `morph_animation` scene, when it exists.
+const MORPH_MODEL: &str = "morph.glb";
+
+/// Builds a ball with two morph targets, one squashing it onto its bottom and one raising bumps
+/// over it, and an animation blending between them over two seconds.
+fn morphing_ball() -> MorphMesh {
+    let (rings, segments) = (48, 96);
+    let point = |ring: usize, segment: usize| {
+        let theta = std::f64::consts::PI * ring as f64 / rings as f64;
+        let phi = 2.0 * std::f64::consts::PI * segment as f64 / segments as f64;
+        (theta, phi)
+    };
+    // The vertices of every ring wrap around without a seam, and each pole is a single vertex,
+    // so the smooth normals of the morphed shapes are computed without cracks.
+    let mut base = MeshData::default();
+    base.positions.push(Vector3::new(0.0, 1.0, 0.0));
+    for ring in 1..rings {
+        for segment in 0..segments {
+            let (theta, phi) = point(ring, segment);
+            base.positions.push(Vector3::new(
+                theta.sin() * phi.cos(),
+                theta.cos(),
+                -theta.sin() * phi.sin(),
+            ));
+        }
+    }
+    base.positions.push(Vector3::new(0.0, -1.0, 0.0));
+    let vertex = |ring: usize, segment: usize| match ring {
+        0 => 0,
+        r if r == rings => 1 + (rings - 1) * segments,
+        r => 1 + (r - 1) * segments + segment % segments,
+    };
+    for ring in 0..rings {
+        for segment in 0..segments {
+            let (a, b) = (vertex(ring, segment), vertex(ring, segment + 1));
+            let (c, d) = (vertex(ring + 1, segment), vertex(ring + 1, segment + 1));
+            if ring > 0 {
+                base.triangles.push([a, c, b]);
+            }
+            if ring < rings - 1 {
+                base.triangles.push([b, c, d]);
+            }
+        }
+    }
+    base.orient_normals();
+
+    let squash: Vec<Vector3> = base
+        .positions
+        .iter()
+        .map(|p| Vector3::new(1.4 * p.x, 0.55 * (p.y + 1.0) - 1.0, 1.4 * p.z))
+        .collect();
+    let bumps: Vec<Vector3> = base
+        .positions
+        .iter()
+        .map(|&p| {
+            let bump = (5.0 * p.x).sin() * (5.0 * p.y).sin() * (5.0 * p.z).sin();
+            (1.0 + 0.35 * bump) * p
+        })
+        .collect();
+    let targets = vec![
+        MorphTarget::from_shape("squash", &base, squash),
+        MorphTarget::from_shape("bumps", &base, bumps),
+    ];
+    MorphMesh::new(base, targets).with_animation(WeightAnimation::new(
+        "breathe",
+        vec![0.0, 0.5, 1.0, 1.5, 2.0],
+        vec![
+            vec![0.0, 0.0],
+            vec![1.0, 0.0],
+            vec![0.0, 0.0],
+            vec![0.0, 1.0],
+            vec![0.0, 0.0],
+        ],
+        Interpolation::Linear,
+    ))
+}
+
+/// Loads the mesh of the `morph_animation` scene: the one in `morph.glb` if the file exists, and
+/// a ball squashing and growing bumps otherwise.
+///
+/// # Returns
+///
+/// The mesh with its morph targets, or a description of why it could not be loaded.
+fn morph_model() -> Result<MorphMesh, String> {
+    if Path::new(MORPH_MODEL).exists() {
+        MorphMesh::load(Path::new(MORPH_MODEL))
+    } else {
+        Ok(morphing_ball())
+    }
+}
+
+/// The material of the mesh of the `morph_animation` scene.
+fn morph_material() -> Arc<dyn Material> {
+    Arc::new(Metal::new(Vector3::new(0.7, 0.75, 0.8), 0.2))
+}
+
+/// Creates the scene of the first frame of a morph target animation: the mesh in `morph.glb` if
+/// the file exists, and a ball squashing and growing bumps otherwise, with the weights of the
+/// start of its first weight animation, or its default weights if it has none. The mesh is scaled
+/// to stand three units tall on a checkered ground.
+///
+/// # Returns
+///
+/// The scene, or a description of why the mesh could not be loaded.
+pub fn morph_animation_scene() -> Result<Scene, String> {
+    let morph = morph_model()?;
+    let mesh = match morph.animations().first() {
+        Some(animation) => morph.frame(animation, 0.0, morph_material()),
+        None => morph.deform(morph.weights(), morph_material()),
+    };
+    let bounds = mesh.bounding_box().expect("meshes are bounded");
+    Ok(deforming_mesh_scene(Arc::new(mesh), &bounds))
+}
+
+/// Renders the frames of a morph target animation, blending the targets of the mesh of
+/// `morph_animation_scene` with the weights of every frame, or a still of its default weights if
+/// it has no weight animation. The mesh is loaded again, as the frames are not rendered from a
+/// single scene.
+///
+/// # Arguments
+///
+/// * `camera` - The camera rendering the frames.
+/// * `frames` - The number of frames to render, spread evenly over the animation.
+pub fn morph_animation(camera: Camera, frames: u32) {
+    let morph = match morph_model() {
+        Ok(morph) => morph,
+        Err(e) => {
+            eprintln!("{}", e);
+            return;
+        }
+    };
+    let Some(animation) = morph.animations().first() else {
+        // Without animations the mesh is shown with its default weights.
+        let still = morph.deform(morph.weights(), morph_material());
+        render_deforming_mesh(camera, 1, 0.0, still, |_, _| false);
+        return;
+    };
+    let names: Vec<&str> = morph
+        .targets()
+        .iter()
+        .map(|target| target.name.as_str())
+        .collect();
+    println!(
+        "Animation {} blending the targets {} over {} seconds",
+        animation.name,
+        names.join(", "),
+        animation.duration()
+    );
+
+    render_deforming_mesh(
+        camera,
+        frames,
+        animation.duration(),
+        morph.frame(animation, 0.0, morph_material()),
+        |time, mesh| morph.refit_frame(animation, time, mesh),
+    );
+}
+
+/// Draws the normal map of a grid of square tiles with bevelled edges, in tangent space.
+///
+/// # Arguments
+///
+/// * `columns` - The number of tiles along the u-coordinate.
+/// * `rows` - The number of tiles along the v-coordinate.
+/// * `size` - The number of texels per tile along each side.
+///
+/// # Returns
+///
+/// The normal map as an image texture read without gamma decoding.
+fn tile_normal_map(columns: u32, rows: u32, size: u32) -> ImageTexture {
+    let bevel = 0.15;
+    let mut image = RgbaImage::new(columns * size, rows * size);
+    for (x, y, pixel) in image.enumerate_pixels_mut() {
+        // The position within the tile, with y going up like the v-coordinate.
+        let tx = ((x % size) as f64 + 0.5) / size as f64;
+        let ty = 1.0 - ((y % size) as f64 + 0.5) / size as f64;
+
+        // The bevel rises from the closest edge at 45 degrees, and the top of the tile is flat.
+        let edges = [
+            (tx, -1.0, 0.0),
+            (1.0 - tx, 1.0, 0.0),
+            (ty, 0.0, -1.0),
+            (1.0 - ty, 0.0, 1.0),
+        ];
+        let (distance, sx, sy) =
+            edges
+                .into_iter()
+                .fold((f64::INFINITY, 0.0, 0.0), |closest, edge| {
+                    if edge.0 < closest.0 {
+                        edge
+                    } else {
+                        closest
+                    }
+                });
+        let normal = if distance < bevel {
+            Vector3::new(sx, sy, 1.0).normalize()
+        } else {
+            Vector3::new(0.0, 0.0, 1.0)
+        };
+
+        let encode = |c: f64| ((c * 0.5 + 0.5) * 255.0).round() as u8;
+        *pixel = Rgba([encode(normal.x), encode(normal.y), encode(normal.z), 255]);
+    }
+    ImageTexture::from_data_image(&DynamicImage::ImageRgba8(image))
+}
+
+/// Creates a scene with a tiled floor and wall and a tiled metal sphere whose tiles exist only in
+/// their normal maps, lit by a low light grazing the surfaces. A plain sphere beside the tiled one
+/// shows the surface without the map.
+///
+/// # Returns
+///
+/// The scene.
+pub fn normal_maps_scene() -> Scene {
+    let mut world: Vec<Box<dyn Hittable>> = Vec::new();
+
+    let stone: Arc<dyn Material> = Arc::new(Lambertian::new(Vector3::new(0.7, 0.65, 0.6)));
+    let floor = NormalMap::new(stone.clone(), Box::new(tile_normal_map(10, 10, 32)));
+    world.push(Box::new(Quad::new(
+        Vector3::new(-5.0, 0.0, 5.0),
+        Vector3::new(10.0, 0.0, 0.0),
+        Vector3::new(0.0, 0.0, -10.0),
+        Arc::new(floor),
+    )));
+    let wall = NormalMap::new(stone, Box::new(tile_normal_map(10, 5, 32))).with_strength(0.6);
+    world.push(Box::new(Quad::new(
+        Vector3::new(-5.0, 0.0, -5.0),
+        Vector3::new(10.0, 0.0, 0.0),
+        Vector3::new(0.0, 5.0, 0.0),
+        Arc::new(wall),
+    )));
+
+    let steel: Arc<dyn Material> = Arc::new(Metal::new(Vector3::new(0.8, 0.7, 0.5), 0.3));
+    let tiled_steel = NormalMap::new(steel.clone(), Box::new(tile_normal_map(16, 8, 16)));
+    world.push(Box::new(Sphere::new(
+        Vector3::new(-1.3, 1.0, 0.0),
+        1.0,
+        Arc::new(tiled_steel),
+    )));
+    world.push(Box::new(Sphere::new(
+        Vector3::new(1.3, 1.0, 0.0),
+        1.0,
+        steel,
+    )));
+
+    // A long light low at the side makes the bevels facing it bright and the others dark.
+    world.push(Box::new(Quad::new(
+        Vector3::new(6.0, 0.3, 4.0),
+        Vector3::new(0.0, 0.0, -8.0),
+        Vector3::new(0.0, 1.5, 0.0),
+        Arc::new(DiffuseLight::new(Vector3::new(6.0, 5.5, 5.0))),
+    )));
+    Scene::new(world)
+}
+
+/// Builds the camera of the `normal_maps` scene.
+///
+/// # Returns
+///
+/// The camera of the `normal_maps` scene.
+pub fn normal_maps_camera() -> Camera {
+    Camera::new(
+        400,
         16.0 / 9.0,
-        10000,
-        5,
-        |_| Vector3::new(0.0, 0.0, 0.0),
+        100,
+        50,
+        |_| Vector3::new(0.02, 0.02, 0.03),
         40.0,
-        Vector3::new(278.0, 278.0, -800.0),
-        Vector3::new(278.0, 278.0, 0.0),
+        Vector3::new(0.0, 3.5, 9.0),
+        Vector3::new(0.0, 1.0, 0.0),
         Vector3::new(0.0, 1.0, 0.0),
         0.0,
         0.0,
-    );
-    camera.render(world);
+    )
 }
 
-/// Creates a Cornell box scene with 2 boxes made out of smoke and renders it using the camera.
-pub fn cornell_smoke() {
+/// Creates a scene with a spinning propeller and a ball thrown in an arc, both moving along
+/// keyframed curves while the shutter is open. The propeller blurs into a fan around its hub and
+/// the ball into a curved streak.
+///
+/// # Returns
+///
+/// The scene.
+pub fn motion_paths_scene() -> Scene {
     let mut world: Vec<Box<dyn Hittable>> = Vec::new();
 
-    let red = Arc::new(Lambertian::new(Vector3::new(0.65, 0.05, 0.05)));
-    let white = Arc::new(Lambertian::new(Vector3::new(0.73, 0.73, 0.73)));
-    let green = Arc::new(Lambertian::new(Vector3::new(0.12, 0.45, 0.15)));
-    let light = Arc::new(DiffuseLight::new(Vector3::new(7.0, 7.0, 7.0)));
-
-    world.push(Box::new(Quad::new(
-        Vector3::new(555.0, 0.0, 0.0),
-        Vector3::new(0.0, 555.0, 0.0),
-        Vector3::new(0.0, 0.0, 555.0),
-        green,
+    world.push(Box::new(Sphere::new(
+        Vector3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        Arc::new(Lambertian::from_texture(Box::new(CheckerTexture::new(
+            1.0,
+            Vector3::new(0.2, 0.3, 0.1),
+            Vector3::new(0.9, 0.9, 0.9),
+        )))),
     )));
 
-    world.push(Box::new(Quad::new(
-        Vector3::new(0.0, 0.0, 0.0),
-        Vector3::new(0.0, 555.0, 0.0),
-        Vector3::new(0.0, 0.0, 555.0),
-        red,
+    // Two blades crossing at the hub, turning a third of a turn around the shaft.
+    let paint: Arc<dyn Material> = Arc::new(Lambertian::new(Vector3::new(0.8, 0.15, 0.1)));
+    let mut propeller = HittableList::new();
+    propeller.add(Box::new(BoxQuad::new(
+        Vector3::new(-2.0, -0.15, -0.05),
+        Vector3::new(2.0, 0.15, 0.05),
+        paint.clone(),
     )));
-
-    world.push(Box::new(Quad::new(
-        Vector3::new(113.0, 554.0, 127.0),
-        Vector3::new(330.0, 0.0, 0.0),
-        Vector3::new(0.0, 0.0, 305.0),
-        light,
+    propeller.add(Box::new(BoxQuad::new(
+        Vector3::new(-0.15, -2.0, -0.05),
+        Vector3::new(0.15, 2.0, 0.05),
+        paint,
     )));
-
-    world.push(Box::new(Quad::new(
-        Vector3::new(0.0, 0.0, 0.0),
-        Vector3::new(555.0, 0.0, 0.0),
-        Vector3::new(0.0, 0.0, 555.0),
-        white.clone(),
+    let shaft = Vector3::new(0.0, 0.0, 1.0);
+    let hub = Vector3::new(2.5, 3.0, 0.0);
+    world.push(Box::new(Motion::new(
+        Arc::new(propeller),
+        vec![
+            MotionKey::new(0.0, hub),
+            MotionKey::new(1.0, hub).with_rotation(shaft, 120.0),
+        ],
     )));
-
-    world.push(Box::new(Quad::new(
-        Vector3::new(555.0, 555.0, 555.0),
-        Vector3::new(-555.0, 0.0, 0.0),
-        Vector3::new(0.0, 0.0, -555.0),
-        white.clone(),
+    world.push(Box::new(Cylinder::new(
+        hub - Vector3::new(0.0, 0.0, 1.0),
+        hub + Vector3::new(0.0, 0.0, 0.2),
+        0.3,
+        Arc::new(Metal::new(Vector3::new(0.7, 0.7, 0.7), 0.1)),
     )));
-
-    world.push(Box::new(Quad::new(
-        Vector3::new(0.0, 0.0, 555.0),
-        Vector3::new(555.0, 0.0, 0.0),
-        Vector3::new(0.0, 555.0, 0.0),
-        white.clone(),
+    world.push(Box::new(Cylinder::new(
+        Vector3::new(2.5, 0.0, -1.0),
+        Vector3::new(2.5, 3.0, -1.0),
+        0.15,
+        Arc::new(Lambertian::new(Vector3::new(0.3, 0.3, 0.3))),
     )));
 
-    let mut box_1: Arc<dyn Hittable> = Arc::new(BoxQuad::new(
-        Vector3::new(0.0, 0.0, 0.0),
-        Vector3::new(165.0, 330.0, 165.0),
-        white.clone(),
-    ));
-
-    box_1 = Arc::new(RotateY::new(box_1, 15.0));
-    let box_1 = Translate::new(box_1, Vector3::new(265.0, 0.0, 295.0));
-    let fog_1 = ConstantMedium::new(Box::new(box_1), 0.01, Vector3::new(0.0, 0.0, 0.0));
-    world.push(Box::new(fog_1));
-
-    let mut box_2: Arc<dyn Hittable> = Arc::new(BoxQuad::new(
+    // The ball rises and falls through keyframes along a parabola, spinning as it flies.
+    let ball = Arc::new(Sphere::new(
         Vector3::new(0.0, 0.0, 0.0),
-        Vector3::new(165.0, 165.0, 165.0),
-        white.clone(),
+        0.6,
+        Arc::new(Lambertian::from_texture(Box::new(
+            CheckerTexture::new(
+                0.25,
+                Vector3::new(0.1, 0.2, 0.8),
+                Vector3::new(0.9, 0.9, 0.2),
+            )
+            .with_space(TextureSpace::Object),
+        ))),
     ));
+    let keys = (0..=4)
+        .map(|i| {
+            let s = i as f64 / 4.0;
+            let offset = Vector3::new(-4.0 + 2.5 * s, 0.6 + 6.0 * s * (1.0 - s), 1.0);
+            MotionKey::new(s, offset).with_rotation(shaft, -360.0 * s)
+        })
+        .collect();
+    world.push(Box::new(Motion::new(ball, keys)));
+    Scene::new(world)
+}
 
-    box_2 = Arc::new(RotateY::new(box_2, -18.0));
-    let box_2 = Translate::new(box_2, Vector3::new(130.0, 0.0, 65.0));
-    let fog_2 = ConstantMedium::new(Box::new(box_2), 0.01, Vector3::new(1.0, 1.0, 1.0));
-    world.push(Box::new(fog_2));
-
-    let camera = Camera::new(
-        1920,
+/// Builds the camera of the `motion_paths` scene.
+///
+/// # Returns
+///
+/// The camera of the `motion_paths` scene.
+pub fn motion_paths_camera() -> Camera {
+    let mut camera = Camera::new(
+        400,
         16.0 / 9.0,
-        10000,
-        5,
-        |_| Vector3::new(0.0, 0.0, 0.0),
+        100,
+        50,
+        background_gradient,
         40.0,
-        Vector3::new(278.0, 278.0, -800.0),
-        Vector3::new(278.0, 278.0, 0.0),
+        Vector3::new(0.0, 3.0, 12.0),
+        Vector3::new(0.0, 2.0, 0.0),
         Vector3::new(0.0, 1.0, 0.0),
         0.0,
         0.0,
     );
-    camera.render(world);
+    camera.set_shutter(Shutter::new(1.0));
+    camera
 }
 
-/// Creates the final scene with various objects and materials, and renders it using the camera.
+/// The glTF file rendered by the `hierarchy` scene, when it exists.
+const HIERARCHY_MODEL: &str = "hierarchy.glb";
+
+/// Builds a desk lamp as a hierarchy of nodes: a base holding two arms, each tilted around the
+/// z-axis relative to the one below it, then the shade and its bulb. The base is turned toward
+/// the camera, which turns every node above it.
 ///
-/// # Arguments
+/// # Returns
 ///
-/// * `image_width` - The width of the image in pixels.
-/// * `samples` - The number of samples per pixel.
-/// * `max_depth` - The maximum depth for ray tracing.
-/// * `reduced` - A boolean flag to reduce the number of objects in the scene for faster rendering.
-pub fn final_scene(image_width: u32, samples: u32, max_depth: u32, reduced: bool) {
-    let mut world: Vec<Box<dyn Hittable>> = Vec::new();
+/// The graph of the lamp.
+fn desk_lamp() -> SceneGraph {
+    let z_axis = Vector3::new(0.0, 0.0, 1.0);
+    let steel: Arc<dyn Material> = Arc::new(Metal::new(Vector3::new(0.7, 0.7, 0.75), 0.2));
+    let mut graph = SceneGraph::new();
+    let base = graph.add(
+        "lamp",
+        None,
+        NodeTransform::default().with_rotation(Vector3::new(0.0, 1.0, 0.0), -30.0),
+        Some(Arc::new(Cylinder::new(
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.15, 0.0),
+            0.8,
+            steel.clone(),
+        ))),
+    );
+    let lower_arm = graph.add(
+        "lower_arm",
+        Some(base),
+        NodeTransform::new(Vector3::new(0.0, 0.15, 0.0)).with_rotation(z_axis, 25.0),
+        Some(Arc::new(Cylinder::new(
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(0.0, 1.6, 0.0),
+            0.07,
+            steel.clone(),
+        ))),
+    );
+    let upper_arm = graph.add(
+        "upper_arm",
+        Some(lower_arm),
+        NodeTransform::new(Vector3::new(0.0, 1.6, 0.0)).with_rotation(z_axis, -80.0),
+        Some(Arc::new(Cylinder::new(
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(0.0, 1.4, 0.0),
+            0.06,
+            steel,
+        ))),
+    );
+    // The shade opens downward along its own y-axis, and is turned back to face the ground.
+    let shade = graph.add(
+        "shade",
+        Some(upper_arm),
+        NodeTransform::new(Vector3::new(0.0, 1.4, 0.0)).with_rotation(z_axis, 70.0),
+        Some(Arc::new(
+            Cone::new(
+                Vector3::new(0.0, -0.5, 0.0),
+                Vector3::new(0.0, 0.1, 0.0),
+                0.4,
+                Arc::new(Lambertian::new(Vector3::new(0.8, 0.15, 0.1))),
+            )
+            .with_cap(false),
+        )),
+    );
+    graph.add(
+        "bulb",
+        Some(shade),
+        NodeTransform::new(Vector3::new(0.0, -0.3, 0.0)),
+        Some(Arc::new(Sphere::new(
+            Vector3::new(0.0, 0.0, 0.0),
+            0.15,
+            Arc::new(DiffuseLight::new(Vector3::new(8.0, 7.0, 5.0))),
+        ))),
+    );
+    graph
+}
 
-    let ground = Arc::new(Lambertian::new(Vector3::new(0.48, 0.83, 0.53)));
+/// Creates a scene from a hierarchy of nodes: the one in `hierarchy.glb` if the file exists,
+/// drawn in a gray material, and a desk lamp otherwise. The hierarchy is put under a `model`
+/// node scaling it to stand three units tall on a checkered ground, and every object is named
+/// after the path of its node.
+///
+/// # Returns
+///
+/// The scene, or a description of why the file could not be loaded.
+pub fn hierarchy_scene() -> Result<Scene, String> {
+    let mut graph = if Path::new(HIERARCHY_MODEL).exists() {
+        SceneGraph::load(
+            Path::new(HIERARCHY_MODEL),
+            Arc::new(Lambertian::new(Vector3::new(0.7, 0.7, 0.7))),
+        )?
+    } else {
+        desk_lamp()
+    };
+    let bounds = graph
+        .objects()
+        .iter()
+        .filter_map(|object| object.bounding_box())
+        .reduce(|a, b| a.merge(&b))
+        .ok_or_else(|| format!("{}: no mesh", HIERARCHY_MODEL))?;
 
-    let boxes_per_side = if reduced { 5 } else { 20 };
+    let scale = 3.0 / (bounds.max.y - bounds.min.y);
+    let offset = -scale
+        * Vector3::new(
+            (bounds.min.x + bounds.max.x) / 2.0,
+            bounds.min.y,
+            (bounds.min.z + bounds.max.z) / 2.0,
+        );
+    let roots: Vec<_> = graph.roots().collect();
+    let model = graph.add(
+        "model",
+        None,
+        NodeTransform::new(offset).with_scale(scale),
+        None,
+    );
+    for root in roots {
+        graph.reparent(root, Some(model))?;
+    }
 
-    for i in 0..boxes_per_side {
-        for j in 0..boxes_per_side {
-            let w = 100.0 * (20.0 / boxes_per_side as f64);
-            let x0 = -1000.0 + (i as f64) * w;
-            let z0 = -1000.0 + (j as f64) * w;
-            let y0 = 0.0;
-            let x1 = x0 + w;
-            let y1 = f64() * 100.0 + 1.0;
-            let z1 = z0 + w;
+    let mut scene = graph.to_scene();
+    let checker = Box::new(CheckerTexture::new(
+        0.5,
+        Vector3::new(0.2, 0.3, 0.1),
+        Vector3::new(0.9, 0.9, 0.9),
+    ));
+    scene.add_named(
+        "ground",
+        Box::new(Sphere::new(
+            Vector3::new(0.0, -1000.0, 0.0),
+            1000.0,
+            Arc::new(Lambertian::from_texture(checker)),
+        )),
+        DEFAULT_LAYER,
+    );
+    Ok(scene)
+}
 
-            world.push(Box::new(BoxQuad::new(
-                Vector3::new(x0, y0, z0),
-                Vector3::new(x1, y1, z1),
-                ground.clone(),
-            )));
-        }
+/// Builds the camera of the `hierarchy` scene.
+///
+/// # Returns
+///
+/// The camera of the `hierarchy` scene.
+pub fn hierarchy_camera() -> Camera {
+    Camera::new(
+        400,
+        16.0 / 9.0,
+        100,
+        50,
+        background_gradient,
+        35.0,
+        Vector3::new(0.0, 2.5, 9.0),
+        Vector3::new(0.0, 1.5, 0.0),
+        Vector3::new(0.0, 1.0, 0.0),
+        0.0,
+        0.0,
+    )
+}
+
+/// Draws a stained glass window as a high dynamic range image: colored panes of glass between
+/// dark lead cames, with a bright sun shining through the top panes.
+///
+/// # Returns
+///
+/// The window as an image texture.
+fn stained_glass() -> ImageTexture {
+    let panes = [
+        Vector3::new(0.9, 0.15, 0.1),
+        Vector3::new(0.1, 0.3, 0.9),
+        Vector3::new(0.95, 0.75, 0.1),
+        Vector3::new(0.15, 0.7, 0.25),
+    ];
+    let (columns, rows, size) = (4, 6, 24);
+    let mut image = image::Rgb32FImage::new(columns * size, rows * size);
+    for (x, y, pixel) in image.enumerate_pixels_mut() {
+        let (column, row) = (x / size, y / size);
+        let lead = x % size < 2 || y % size < 2;
+        let color = if lead {
+            Vector3::new(0.01, 0.01, 0.01)
+        } else {
+            // The top rows catch the sun and are several times brighter than white.
+            let sun = if row < 2 { 4.0 } else { 1.0 };
+            panes[((column + 3 * row) % 4) as usize] * sun
+        };
+        *pixel = image::Rgb([color.x as f32, color.y as f32, color.z as f32]);
     }
+    ImageTexture::from_image(&DynamicImage::ImageRgb32F(image))
+}
 
-    let light = Arc::new(DiffuseLight::new(Vector3::new(7.0, 7.0, 7.0)));
+/// Creates a dark chapel lit only by a stained glass window, an image-mapped area light whose
+/// intensity is scaled up. The light falling on the floor and the spheres takes the colors of the
+/// panes.
+///
+/// # Returns
+///
+/// The scene.
+pub fn image_lights_scene() -> Scene {
+    let mut world: Vec<Box<dyn Hittable>> = Vec::new();
+
+    let white: Arc<dyn Material> = Arc::new(Lambertian::new(Vector3::new(0.75, 0.75, 0.75)));
     world.push(Box::new(Quad::new(
-        Vector3::new(123.0, 554.0, 147.0),
-        Vector3::new(300.0, 0.0, 0.0),
-        Vector3::new(0.0, 0.0, 265.0),
-        light,
+        Vector3::new(-6.0, 0.0, 6.0),
+        Vector3::new(12.0, 0.0, 0.0),
+        Vector3::new(0.0, 0.0, -12.0),
+        white.clone(),
+    )));
+    world.push(Box::new(Quad::new(
+        Vector3::new(-6.0, 0.0, -6.0),
+        Vector3::new(12.0, 0.0, 0.0),
+        Vector3::new(0.0, 8.0, 0.0),
+        white.clone(),
     )));
 
-    let center = Vector3::new(400.0, 400.0, 200.0);
-    let sphere_material = Arc::new(Lambertian::new(Vector3::new(0.7, 0.3, 0.1)));
+    // The window leans out of the back wall, so it faces both the camera and the floor.
+    world.push(Box::new(Quad::new(
+        Vector3::new(-1.0, 2.5, -5.5),
+        Vector3::new(2.0, 0.0, 0.0),
+        Vector3::new(0.0, 3.0, 0.8),
+        Arc::new(DiffuseLight::from_texture(Box::new(stained_glass())).with_intensity(3.0)),
+    )));
 
-    world.push(Box::new(Sphere::new(center, 50.0, sphere_material)));
     world.push(Box::new(Sphere::new(
-        Vector3::new(260.0, 150.0, 45.0),
-        50.0,
-        Arc::new(Dielectric::new(1.5)),
+        Vector3::new(-1.8, 0.8, -1.5),
+        0.8,
+        white,
     )));
-
     world.push(Box::new(Sphere::new(
-        Vector3::new(0.0, 150.0, 145.0),
-        50.0,
-        Arc::new(Metal::new(Vector3::new(0.8, 0.8, 0.9), 1.0)),
+        Vector3::new(1.6, 0.8, -2.0),
+        0.8,
+        Arc::new(Metal::new(Vector3::new(0.9, 0.9, 0.9), 0.05)),
     )));
+    Scene::new(world)
+}
 
-    let mut boundary = Box::new(Sphere::new(
-        Vector3::new(360.0, 150.0, 145.0),
-        70.0,
-        Arc::new(Dielectric::new(1.5)),
-    ));
+/// Builds the camera of the `image_lights` scene.
+///
+/// # Returns
+///
+/// The camera of the `image_lights` scene.
+pub fn image_lights_camera() -> Camera {
+    Camera::new(
+        400,
+        16.0 / 9.0,
+        100,
+        50,
+        |_| Vector3::new(0.0, 0.0, 0.0),
+        45.0,
+        Vector3::new(0.0, 2.5, 6.0),
+        Vector3::new(0.0, 2.0, -3.0),
+        Vector3::new(0.0, 1.0, 0.0),
+        0.0,
+        0.0,
+    )
+}
 
-    world.push(boundary);
+/// The equirectangular HDR image lighting the `hdri` scene, when it exists.
+const ENVIRONMENT_MAP: &str = "environment.hdr";
 
-    world.push(Box::new(ConstantMedium::new(
+/// Draws an equirectangular high dynamic range sky: a blue gradient above a brown ground, with a
+/// small sun far brighter than the rest of the sky, standing low in the middle of the image.
+///
+/// # Returns
+///
+/// The sky as an image.
+fn procedural_sky() -> DynamicImage {
+    let (width, height) = (512, 256);
+    let sun = Vector3::new(0.0, 0.6, -1.0).normalize();
+    let mut image = image::Rgb32FImage::new(width, height);
+    for (x, y, pixel) in image.enumerate_pixels_mut() {
+        // The inverse of the mapping of `HdrEnvironmentMap`.
+        let longitude = ((x as f64 + 0.5) / width as f64 - 0.5) * 2.0 * std::f64::consts::PI;
+        let latitude = (0.5 - (y as f64 + 0.5) / height as f64) * std::f64::consts::PI;
+        let direction = Vector3::new(
+            latitude.cos() * longitude.sin(),
+            latitude.sin(),
+            -latitude.cos() * longitude.cos(),
+        );
+
+        let color = if direction.y < 0.0 {
+            Vector3::new(0.35, 0.25, 0.15)
+        } else if direction.dot(&sun) > 0.99 {
+            Vector3::new(12.0, 11.0, 9.0)
+        } else {
+            let a = direction.y.sqrt();
+            (1.0 - a) * Vector3::new(0.8, 0.75, 0.7) + a * Vector3::new(0.2, 0.35, 0.8)
+        };
+        *pixel = image::Rgb([color.x as f32, color.y as f32, color.z as f32]);
+    }
+    DynamicImage::ImageRgb32F(image)
+}
+
+/// Creates a scene with diffuse, metal and glass spheres, lit only by the environment map of the
+/// camera of `hdri_camera`.
+///
+/// # Returns
+///
+/// The scene.
+pub fn hdri_scene() -> Scene {
+    let world: Vec<Box<dyn Hittable>> = vec![
+        Box::new(Quad::disk(
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            6.0,
+            Arc::new(Lambertian::new(Vector3::new(0.6, 0.6, 0.6))),
+        )),
         Box::new(Sphere::new(
-            Vector3::new(360.0, 150.0, 145.0),
-            70.0,
+            Vector3::new(-2.2, 1.0, 0.0),
+            1.0,
+            Arc::new(Lambertian::new(Vector3::new(0.8, 0.3, 0.2))),
+        )),
+        Box::new(Sphere::new(
+            Vector3::new(0.0, 1.0, 0.0),
+            1.0,
+            Arc::new(Metal::new(Vector3::new(0.9, 0.9, 0.9), 0.0)),
+        )),
+        Box::new(Sphere::new(
+            Vector3::new(2.2, 1.0, 0.0),
+            1.0,
             Arc::new(Dielectric::new(1.5)),
         )),
-        0.02,
-        Vector3::new(0.2, 0.4, 0.9),
-    )));
-
-    boundary = Box::new(Sphere::new(
-        Vector3::new(0.0, 0.0, 0.0),
-        5000.0,
-        Arc::new(Dielectric::new(1.5)),
-    ));
-    world.push(Box::new(ConstantMedium::new(
-        boundary,
-        0.0001,
-        Vector3::new(1.0, 1.0, 1.0),
-    )));
-
-    let emat = Arc::new(Lambertian::from_texture(Box::new(ImageTexture::new(
-        "earthmap.jpg",
-    ))));
-
-    world.push(Box::new(Sphere::new(
-        Vector3::new(400.0, 200.0, 400.0),
-        100.0,
-        emat,
-    )));
-
-    let mirror = Arc::new(Metal::new(Vector3::new(0.8, 0.8, 0.8), 0.0));
-    world.push(Box::new(Sphere::new(
-        Vector3::new(220.0, 280.0, 300.0),
-        80.0,
-        mirror,
-    )));
-
-    if !reduced {
-        let white = Arc::new(Lambertian::new(Vector3::new(0.73, 0.73, 0.73)));
-        let ns = 1000;
+    ];
+    Scene::new(world)
+}
 
-        for _ in 0..ns {
-            let sphere = Sphere::new(Vector3::random(0.0, 165.0), 10.0, white.clone());
-            let rotate = RotateY::new(Arc::new(sphere), 15.0);
-            let translate = Translate::new(Arc::new(rotate), Vector3::new(-100.0, 270.0, 395.0));
-            world.push(Box::new(translate));
-        }
-    }
+/// Builds the camera of the `hdri` scene, surrounded by an environment map. The map is read from
+/// `environment.hdr` when it exists, so any real-world HDRI can be used, and a procedural sky is
+/// drawn otherwise.
+///
+/// # Returns
+///
+/// The camera of the `hdri` scene.
+pub fn hdri_camera() -> Camera {
+    let environment = if Path::new(ENVIRONMENT_MAP).exists() {
+        HdrEnvironmentMap::new(ENVIRONMENT_MAP)
+    } else {
+        // The sun is turned from behind the spheres to their right, and the sky is brightened.
+        HdrEnvironmentMap::from_image(&procedural_sky())
+            .with_rotation(-120.0)
+            .with_intensity(1.2)
+    };
 
-    let camera = Camera::new(
-        image_width,
+    let mut camera = Camera::new(
+        400,
         16.0 / 9.0,
-        samples,
-        max_depth,
-        |_| Vector3::new(0.0, 0.0, 0.0),
+        100,
+        50,
+        background_gradient,
         40.0,
-        Vector3::new(478.0, 278.0, -600.0),
-        Vector3::new(278.0, 278.0, 0.0),
+        Vector3::new(0.0, 2.0, 8.0),
+        Vector3::new(0.0, 1.0, 0.0),
         Vector3::new(0.0, 1.0, 0.0),
         0.0,
         0.0,
     );
-
-    camera.render(world);
+    camera.set_environment(Arc::new(environment));
+    camera
 }
@@ -0,0 +1,251 @@
+use crate::aabb::Aabb;
+use crate::hit::{HitRecord, Hittable};
+use crate::material::Material;
+use crate::material_table::MaterialTable;
+use crate::ray::Ray;
+use crate::statistics::SceneStatistics;
+use crate::vector3::Vector3;
+use std::sync::Arc;
+
+/// Represents a bilinear patch: the surface interpolating four corners that do not have to lie in
+/// a plane, such as a gently curved or twisted panel. With planar corners forming a parallelogram
+/// it is the same surface as a `Quad`.
+///
+/// Patches are not sampled as lights, so emissive patches only light the scene when scattered
+/// rays happen to hit them.
+pub struct BilinearPatch {
+    /// The corner at texture coordinates (0, 0).
+    q00: Vector3,
+    /// The corner at texture coordinates (1, 0).
+    q10: Vector3,
+    /// The corner at texture coordinates (1, 1).
+    q11: Vector3,
+    /// The corner at texture coordinates (0, 1).
+    q01: Vector3,
+    /// The material of the patch.
+    material: Arc<dyn Material>,
+}
+
+impl BilinearPatch {
+    /// Creates a new `BilinearPatch` from its four corners, given in order around the patch. The
+    /// front face is on the side of `(q10 - q00) x (q01 - q00)`, like the normal of a `Quad`.
+    ///
+    /// # Arguments
+    ///
+    /// * `q00` - The corner at texture coordinates (0, 0).
+    /// * `q10` - The corner at texture coordinates (1, 0).
+    /// * `q11` - The corner at texture coordinates (1, 1).
+    /// * `q01` - The corner at texture coordinates (0, 1).
+    /// * `material` - The material of the patch.
+    ///
+    /// # Returns
+    ///
+    /// A new `BilinearPatch` instance.
+    pub fn new(
+        q00: Vector3,
+        q10: Vector3,
+        q11: Vector3,
+        q01: Vector3,
+        material: Arc<dyn Material>,
+    ) -> BilinearPatch {
+        BilinearPatch {
+            q00,
+            q10,
+            q11,
+            q01,
+            material,
+        }
+    }
+
+    /// Returns the point of the patch at given texture coordinates.
+    ///
+    /// # Arguments
+    ///
+    /// * `u` - The coordinate along the edge from `q00` to `q10`.
+    /// * `v` - The coordinate along the edge from `q00` to `q01`.
+    ///
+    /// # Returns
+    ///
+    /// The point of the patch.
+    pub fn point_at(&self, u: f64, v: f64) -> Vector3 {
+        (1.0 - v) * ((1.0 - u) * self.q00 + u * self.q10)
+            + v * ((1.0 - u) * self.q01 + u * self.q11)
+    }
+
+    /// Returns the unit normal of the patch at given texture coordinates, from the cross product
+    /// of its partial derivatives.
+    ///
+    /// # Arguments
+    ///
+    /// * `u` - The coordinate along the edge from `q00` to `q10`.
+    /// * `v` - The coordinate along the edge from `q00` to `q01`.
+    ///
+    /// # Returns
+    ///
+    /// The unit normal, on the side of the front face.
+    fn normal(&self, u: f64, v: f64) -> Vector3 {
+        let du = (1.0 - v) * (self.q10 - self.q00) + v * (self.q11 - self.q01);
+        let dv = (1.0 - u) * (self.q01 - self.q00) + u * (self.q11 - self.q10);
+        du.cross(&dv).normalize()
+    }
+}
+
+impl Hittable for BilinearPatch {
+    /// Checks if a ray hits the patch within a given interval. The ray meets the patch where the
+    /// segment between the points at `u` of two opposite edges crosses it, which gives a quadratic
+    /// equation in `u`, as described by Reshetov in "Cool Patches" (Ray Tracing Gems II).
+    ///
+    /// # Arguments
+    ///
+    /// * `ray` - The ray to test for intersection.
+    /// * `interval` - The range of distances to consider for intersections.
+    ///
+    /// # Returns
+    ///
+    /// An `Option` containing the closest `HitRecord` if an intersection is found, or `None` if no intersection is found.
+    fn hit(&self, ray: &Ray, interval: (f64, f64)) -> Option<HitRecord<'_>> {
+        let d = ray.direction;
+        let q00 = self.q00 - ray.origin;
+        let q10 = self.q10 - ray.origin;
+        let e10 = self.q10 - self.q00;
+        let e11 = self.q11 - self.q10;
+        let e00 = self.q01 - self.q00;
+        let qn = e10.cross(&(self.q01 - self.q11));
+
+        // The coefficients of a + b u + c u^2 = 0.
+        let a = q00.cross(&d).dot(&e00);
+        let c = qn.dot(&d);
+        let b = q10.cross(&d).dot(&e11) - a - c;
+        let discriminant = b * b - 4.0 * a * c;
+        if discriminant < 0.0 {
+            return None;
+        }
+        let root = discriminant.sqrt();
+        let (u1, u2) = if c == 0.0 {
+            // The two edges are parallel along the ray, leaving a single root.
+            (-a / b, -1.0)
+        } else {
+            let q = (-b - root.copysign(b)) / 2.0;
+            (q / c, a / q)
+        };
+
+        let mut closest: Option<(f64, f64, f64)> = None;
+        for u in [u1, u2] {
+            if !(0.0..=1.0).contains(&u) {
+                continue;
+            }
+            // The segment between the points at u of the edges from q00 to q10 and q01 to q11.
+            let pa = (1.0 - u) * q00 + u * q10;
+            let pb = (1.0 - u) * e00 + u * e11;
+            let n = d.cross(&pb);
+            let length_squared = n.dot(&n);
+            if length_squared == 0.0 {
+                continue;
+            }
+            let n = n.cross(&pa);
+            let t = n.dot(&pb) / length_squared;
+            let v = n.dot(&d) / length_squared;
+            if (0.0..=1.0).contains(&v)
+                && t >= interval.0
+                && t <= interval.1
+                && closest.is_none_or(|(closest_t, _, _)| t < closest_t)
+            {
+                closest = Some((t, u, v));
+            }
+        }
+
+        let (t, u, v) = closest?;
+        let mut record = HitRecord::new(t, ray.point_at(t), self.material.as_ref(), u, v);
+        record.set_face_normal(ray, &self.normal(u, v));
+        record.set_tangents(
+            (1.0 - v) * (self.q10 - self.q00) + v * (self.q11 - self.q01),
+            (1.0 - u) * (self.q01 - self.q00) + u * (self.q11 - self.q10),
+        );
+        Some(record)
+    }
+
+    fn collect_materials(&self, table: &mut MaterialTable) {
+        table.add(&self.material);
+    }
+
+    fn collect_statistics(&self, statistics: &mut SceneStatistics) {
+        statistics.add_primitive("BilinearPatch", std::mem::size_of_val(self));
+        self.material.collect_statistics(statistics);
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        // The patch lies in the convex hull of its corners.
+        Some(Aabb::from_points(&[self.q00, self.q10, self.q11, self.q01]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Lambertian;
+    use crate::shapes::quad::Quad;
+
+    fn material() -> Arc<dyn Material> {
+        Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5)))
+    }
+
+    #[test]
+    fn test_planar_patch_matches_quad() {
+        let patch = BilinearPatch::new(
+            Vector3::new(-1.0, -1.0, 0.0),
+            Vector3::new(1.0, -1.0, 0.0),
+            Vector3::new(1.0, 1.0, 0.0),
+            Vector3::new(-1.0, 1.0, 0.0),
+            material(),
+        );
+        let quad = Quad::new(
+            Vector3::new(-1.0, -1.0, 0.0),
+            Vector3::new(2.0, 0.0, 0.0),
+            Vector3::new(0.0, 2.0, 0.0),
+            material(),
+        );
+        for (x, y) in [(0.0, 0.0), (0.3, -0.25), (-0.9, 0.8)] {
+            let ray = Ray::new(Vector3::new(x, y, 5.0), Vector3::new(0.1, -0.05, -1.0));
+            let expected = quad.hit(&ray, (0.001, f64::INFINITY)).unwrap();
+            let record = patch.hit(&ray, (0.001, f64::INFINITY)).unwrap();
+            assert!((record.t - expected.t).abs() < 1e-9);
+            assert!((record.u - expected.u).abs() < 1e-9);
+            assert!((record.v - expected.v).abs() < 1e-9);
+            assert!((record.normal - expected.normal).length() < 1e-9);
+            assert_eq!(record.front_face, expected.front_face);
+        }
+        let miss = Ray::new(Vector3::new(1.5, 0.0, 5.0), Vector3::new(0.0, 0.0, -1.0));
+        assert!(patch.hit(&miss, (0.001, f64::INFINITY)).is_none());
+    }
+
+    #[test]
+    fn test_saddle() {
+        // A hyperbolic paraboloid, z = x y, over the square from -1 to 1.
+        let patch = BilinearPatch::new(
+            Vector3::new(-1.0, -1.0, 1.0),
+            Vector3::new(1.0, -1.0, -1.0),
+            Vector3::new(1.0, 1.0, 1.0),
+            Vector3::new(-1.0, 1.0, -1.0),
+            material(),
+        );
+        for (x, y) in [(0.0, 0.0), (0.5, 0.5), (-0.7, 0.3), (0.9, -0.95)] {
+            let ray = Ray::new(Vector3::new(x, y, 5.0), Vector3::new(0.0, 0.0, -1.0));
+            let record = patch.hit(&ray, (0.001, f64::INFINITY)).unwrap();
+            assert!((record.poz.z - x * y).abs() < 1e-9, "{:?}", record.poz);
+            assert!((patch.point_at(record.u, record.v) - record.poz).length() < 1e-9);
+
+            // The normal of z = x y is along (-y, -x, 1).
+            let expected = Vector3::new(-y, -x, 1.0).normalize();
+            assert!((record.normal - expected).length() < 1e-9);
+            assert!(record.front_face);
+        }
+
+        // Along the diagonal the surface curves up as z = x^2, so a level ray crosses it twice and
+        // the closest crossing is kept.
+        let ray = Ray::new(Vector3::new(-2.0, -2.0, 0.25), Vector3::new(1.0, 1.0, 0.0));
+        let record = patch.hit(&ray, (0.001, f64::INFINITY)).unwrap();
+        assert!((record.poz.x + 0.5).abs() < 1e-9, "{:?}", record.poz);
+        let far = patch.hit(&ray, (record.t + 0.001, f64::INFINITY)).unwrap();
+        assert!((far.poz.x - 0.5).abs() < 1e-9, "{:?}", far.poz);
+    }
+}
@@ -0,0 +1,225 @@
+use crate::aabb::Aabb;
+use crate::hit::{HitRecord, Hittable};
+use crate::material::{Lambertian, Material};
+use crate::material_table::MaterialTable;
+use crate::ray::Ray;
+use crate::statistics::SceneStatistics;
+use crate::texture::{ImageTexture, Texture};
+use crate::vector3::Vector3;
+use std::sync::Arc;
+
+/// Represents an upright rectangular sprite that turns around its vertical axis to face the origin
+/// of every ray, used to populate large scenes with cheap stand-ins for distant objects.
+/// The opacity of the sprite image cuts out its silhouette.
+pub struct Billboard {
+    /// The center of the bottom edge of the sprite.
+    base: Vector3,
+    /// The width of the sprite.
+    width: f64,
+    /// The height of the sprite.
+    height: f64,
+    /// The image whose opacity cuts out the sprite.
+    sprite: Arc<ImageTexture>,
+    /// The material of the sprite.
+    material: Arc<dyn Material>,
+}
+
+impl Billboard {
+    /// Creates a new `Billboard`.
+    ///
+    /// # Arguments
+    ///
+    /// * `base` - The center of the bottom edge of the sprite.
+    /// * `width` - The width of the sprite.
+    /// * `height` - The height of the sprite.
+    /// * `sprite` - The image whose opacity cuts out the sprite.
+    /// * `material` - The material of the sprite.
+    ///
+    /// # Returns
+    ///
+    /// A new `Billboard` instance.
+    pub fn new(
+        base: Vector3,
+        width: f64,
+        height: f64,
+        sprite: Arc<ImageTexture>,
+        material: Arc<dyn Material>,
+    ) -> Billboard {
+        Billboard {
+            base,
+            width,
+            height,
+            sprite,
+            material,
+        }
+    }
+
+    /// Creates a new `Billboard` with a diffuse material colored by the sprite image.
+    ///
+    /// # Arguments
+    ///
+    /// * `base` - The center of the bottom edge of the sprite.
+    /// * `width` - The width of the sprite.
+    /// * `height` - The height of the sprite.
+    /// * `sprite` - The image of the sprite, whose opacity cuts out its silhouette.
+    ///
+    /// # Returns
+    ///
+    /// A new `Billboard` instance.
+    pub fn from_image(
+        base: Vector3,
+        width: f64,
+        height: f64,
+        sprite: Arc<ImageTexture>,
+    ) -> Billboard {
+        let material = Arc::new(Lambertian::from_texture(Box::new(sprite.clone())));
+        Billboard::new(base, width, height, sprite, material)
+    }
+}
+
+impl Hittable for Billboard {
+    /// Checks if a ray hits the billboard within a given interval.
+    /// The billboard is turned to face the origin of the ray, and transparent parts of the sprite
+    /// are hit with a probability equal to their opacity.
+    ///
+    /// # Arguments
+    ///
+    /// * `ray` - The ray to test for intersection.
+    /// * `interval` - The range of distances to consider for intersections.
+    ///
+    /// # Returns
+    ///
+    /// An `Option` containing the `HitRecord` if an intersection is found, or `None` if no intersection is found.
+    fn hit(&self, ray: &Ray, interval: (f64, f64)) -> Option<HitRecord<'_>> {
+        let up = Vector3::new(0.0, 1.0, 0.0);
+        let mut normal = ray.origin - self.base;
+        normal.y = 0.0;
+        if normal.is_near_zero() {
+            // Seen from straight above or below, face against the horizontal part of the ray.
+            normal = Vector3::new(-ray.direction.x, 0.0, -ray.direction.z);
+            if normal.is_near_zero() {
+                return None;
+            }
+        }
+        let normal = normal.normalize();
+
+        let denominator = ray.direction.dot(&normal);
+        if denominator.abs() < 1e-8 {
+            return None;
+        }
+
+        let t = (self.base - ray.origin).dot(&normal) / denominator;
+        if t < interval.0 || t > interval.1 {
+            return None;
+        }
+
+        let intersection = ray.point_at(t);
+        let offset = intersection - self.base;
+        let right = up.cross(&normal);
+        let u = offset.dot(&right) / self.width + 0.5;
+        let v = offset.y / self.height;
+        if !(0.0..=1.0).contains(&u) || !(0.0..=1.0).contains(&v) {
+            return None;
+        }
+
+        let alpha = self.sprite.alpha(u, v);
+        if alpha < 1.0 && fastrand::f64() >= alpha {
+            return None;
+        }
+
+        let mut record = HitRecord::new(t, intersection, &*self.material, u, v);
+        record.set_face_normal(ray, &normal);
+        record.set_tangents(self.width * right, self.height * up);
+        Some(record)
+    }
+
+    fn collect_materials(&self, table: &mut MaterialTable) {
+        table.add(&self.material);
+    }
+
+    fn collect_statistics(&self, statistics: &mut SceneStatistics) {
+        statistics.add_primitive("Billboard", std::mem::size_of_val(self));
+        self.sprite.collect_statistics(statistics);
+        self.material.collect_statistics(statistics);
+    }
+
+    /// Returns the box enclosing the billboard whichever way it turns around its vertical axis.
+    ///
+    /// # Returns
+    ///
+    /// The bounding box of the billboard.
+    fn bounding_box(&self) -> Option<Aabb> {
+        let half_width = self.width / 2.0;
+        Some(Aabb::new(
+            self.base - Vector3::new(half_width, 0.0, half_width),
+            self.base + Vector3::new(half_width, self.height, half_width),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::texture::WrapMode;
+    use image::{DynamicImage, Rgba, RgbaImage};
+
+    /// Builds a 2 by 2 sprite whose left column is opaque and whose right column is transparent.
+    fn half_transparent_sprite() -> Arc<ImageTexture> {
+        let mut image = RgbaImage::new(2, 2);
+        for y in 0..2 {
+            image.put_pixel(0, y, Rgba([255, 255, 255, 255]));
+            image.put_pixel(1, y, Rgba([255, 255, 255, 0]));
+        }
+        Arc::new(
+            ImageTexture::from_image(&DynamicImage::ImageRgba8(image))
+                .with_wrap(WrapMode::Clamp, WrapMode::Clamp),
+        )
+    }
+
+    #[test]
+    fn test_billboard_faces_every_ray() {
+        let billboard = Billboard::from_image(
+            Vector3::new(0.0, 0.0, 0.0),
+            2.0,
+            2.0,
+            Arc::new(ImageTexture::from_image(&DynamicImage::new_rgb8(1, 1))),
+        );
+
+        for angle in [0.0_f64, 45.0, 90.0, 180.0, 270.0] {
+            let (sin, cos) = angle.to_radians().sin_cos();
+            let origin = Vector3::new(10.0 * sin, 1.0, 10.0 * cos);
+            let ray = Ray::new(origin, Vector3::new(0.0, 1.0, 0.0) - origin);
+            let record = billboard
+                .hit(&ray, (0.001, f64::INFINITY))
+                .expect("the billboard should face the ray");
+
+            assert!(record.front_face);
+            assert!((record.t - 10.0).abs() < 1e-9);
+            assert!((record.u - 0.5).abs() < 1e-9 && (record.v - 0.5).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_billboard_alpha_cutout() {
+        let billboard = Billboard::from_image(
+            Vector3::new(0.0, 0.0, 0.0),
+            2.0,
+            2.0,
+            half_transparent_sprite(),
+        );
+        let origin = Vector3::new(0.0, 1.0, 5.0);
+
+        // From this side, the left of the sprite lies towards negative x.
+        let opaque = Ray::new(origin, Vector3::new(-0.9, 0.0, -5.0));
+        let transparent = Ray::new(origin, Vector3::new(0.9, 0.0, -5.0));
+        for _ in 0..100 {
+            assert!(billboard.hit(&opaque, (0.001, f64::INFINITY)).is_some());
+            assert!(billboard
+                .hit(&transparent, (0.001, f64::INFINITY))
+                .is_none());
+        }
+
+        let above = Ray::new(origin, Vector3::new(0.0, 2.0, -5.0));
+        assert!(billboard.hit(&above, (0.001, f64::INFINITY)).is_none());
+    }
+}
@@ -1,15 +1,18 @@
+use crate::aabb::Aabb;
 use crate::hit::{HitRecord, Hittable};
 use crate::material::Material;
+use crate::material_table::MaterialTable;
 use crate::ray::Ray;
+use crate::shapes::hittable_list::HittableList;
 use crate::shapes::quad::Quad;
+use crate::statistics::SceneStatistics;
 use crate::vector3::Vector3;
-use std::cmp::Ordering;
 use std::sync::Arc;
 
 /// Represents a box composed of six quads
 pub struct BoxQuad {
     /// The six sides of the box, each represented as a `Hittable` quad.
-    sides: Vec<Box<dyn Hittable>>,
+    sides: HittableList,
 }
 
 impl BoxQuad {
@@ -25,7 +28,7 @@ impl BoxQuad {
     ///
     /// A new `BoxQuad` instance.
     pub fn new(a: Vector3, b: Vector3, material: Arc<dyn Material>) -> Self {
-        let mut sides: Vec<Box<dyn Hittable>> = Vec::new();
+        let mut sides = HittableList::new();
 
         let min = Vector3::new(a.x.min(b.x), a.y.min(b.y), a.z.min(b.z));
         let max = Vector3::new(a.x.max(b.x), a.y.max(b.y), a.z.max(b.z));
@@ -34,42 +37,42 @@ impl BoxQuad {
         let dy = Vector3::new(0.0, max.y - min.y, 0.0);
         let dz = Vector3::new(0.0, 0.0, max.z - min.z);
 
-        sides.push(Box::new(Quad::new(
+        sides.add(Box::new(Quad::new(
             Vector3::new(min.x, min.y, max.z),
             dx,
             dy,
             material.clone(),
         ))); // front
 
-        sides.push(Box::new(Quad::new(
+        sides.add(Box::new(Quad::new(
             Vector3::new(max.x, min.y, max.z),
             -dz,
             dy,
             material.clone(),
         ))); // right
 
-        sides.push(Box::new(Quad::new(
+        sides.add(Box::new(Quad::new(
             Vector3::new(max.x, min.y, min.z),
             -dx,
             dy,
             material.clone(),
         ))); // back
 
-        sides.push(Box::new(Quad::new(
+        sides.add(Box::new(Quad::new(
             Vector3::new(min.x, min.y, min.z),
             dz,
             dy,
             material.clone(),
         ))); // left
 
-        sides.push(Box::new(Quad::new(
+        sides.add(Box::new(Quad::new(
             Vector3::new(min.x, max.y, max.z),
             dx,
             -dz,
             material.clone(),
         ))); // top
 
-        sides.push(Box::new(Quad::new(
+        sides.add(Box::new(Quad::new(
             Vector3::new(min.x, min.y, min.z),
             dx,
             dz,
@@ -90,10 +93,59 @@ impl Hittable for BoxQuad {
     /// # Returns
     ///
     /// An `Option` containing the closest `HitRecord` if an intersection is found, or `None` if no intersection is found.
-    fn hit(&self, ray: &Ray, interval: (f64, f64)) -> Option<HitRecord> {
-        self.sides
-            .iter()
-            .filter_map(|s| s.hit(ray, interval))
-            .min_by(|r1, r2| r1.t.partial_cmp(&r2.t).unwrap_or(Ordering::Equal))
+    fn hit(&self, ray: &Ray, interval: (f64, f64)) -> Option<HitRecord<'_>> {
+        self.sides.hit(ray, interval)
+    }
+
+    fn is_emissive(&self) -> bool {
+        self.sides.is_emissive()
+    }
+
+    fn power(&self) -> Vector3 {
+        self.sides.power()
+    }
+
+    /// Returns the probability density of sampling a direction towards a uniformly chosen side of the box.
+    ///
+    /// # Arguments
+    ///
+    /// * `origin` - The point from which the box is sampled.
+    /// * `direction` - The direction to evaluate.
+    ///
+    /// # Returns
+    ///
+    /// The probability density with respect to solid angle.
+    fn pdf_value(&self, origin: &Vector3, direction: &Vector3) -> f64 {
+        self.sides.pdf_value(origin, direction)
+    }
+
+    /// Generates a direction towards a uniformly chosen side of the box.
+    ///
+    /// # Arguments
+    ///
+    /// * `origin` - The point from which the box is sampled.
+    ///
+    /// # Returns
+    ///
+    /// The generated direction.
+    fn random(&self, origin: &Vector3) -> Vector3 {
+        self.sides.random(origin)
+    }
+
+    fn collect_materials(&self, table: &mut MaterialTable) {
+        self.sides.collect_materials(table);
+    }
+
+    fn collect_statistics(&self, statistics: &mut SceneStatistics) {
+        statistics.add_geometry(std::mem::size_of_val(self));
+        self.sides.collect_statistics(statistics);
+    }
+
+    fn closest_point(&self, point: &Vector3) -> Option<Vector3> {
+        self.sides.closest_point(point)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.sides.bounding_box()
     }
 }
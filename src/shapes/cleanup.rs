@@ -0,0 +1,567 @@
+use crate::material::Material;
+use crate::shapes::mesh::{shared_positions, TriangleMesh};
+use crate::vector3::Vector3;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// The largest distance between vertices welded by `MeshData::build`, small enough to only close
+/// the cracks left by rounding in mesh files.
+pub const WELD_TOLERANCE: f64 = 1e-6;
+
+/// The largest difference between the normals or texture coordinates of vertices that are welded
+/// into one. Vertices closer than the tolerance but differing more keep a seam, sharing a
+/// position without being merged.
+const ATTRIBUTE_TOLERANCE: f64 = 1e-6;
+
+/// Represents the raw arrays of a triangle mesh, as read from a mesh file or generated, before
+/// they are cleaned up and built into a `TriangleMesh`.
+///
+/// Files such as STL or carelessly exported OBJ are often dirty: every triangle with its own
+/// vertices, triangles wound either way, zero-area slivers and repeated triangles. `clean` fixes
+/// what it can and reports the rest.
+#[derive(Debug, Clone, Default)]
+pub struct MeshData {
+    /// The positions of the vertices.
+    pub positions: Vec<Vector3>,
+    /// The normals of the vertices, or empty if the mesh has none, in which case smooth normals
+    /// are computed from the triangles.
+    pub normals: Vec<Vector3>,
+    /// The texture coordinates of the vertices, or empty if the mesh has none.
+    pub uvs: Vec<(f64, f64)>,
+    /// The triangles, as indices into the vertices.
+    pub triangles: Vec<[usize; 3]>,
+}
+
+/// Represents the changes made by `MeshData::clean`, and the flaws it found but left in place.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CleanupReport {
+    /// The number of vertices merged into another one.
+    pub welded_vertices: usize,
+    /// The number of triangles removed for having no area.
+    pub degenerate_triangles: usize,
+    /// The number of triangles removed for repeating another one.
+    pub duplicate_triangles: usize,
+    /// The number of triangles whose winding was reversed to match their neighbours.
+    pub flipped_triangles: usize,
+    /// The number of vertex normals reversed to match the triangles around them.
+    pub flipped_normals: usize,
+    /// The number of edges shared by more than two triangles, across which winding is not
+    /// propagated.
+    pub non_manifold_edges: usize,
+    /// The number of boundary loops, the holes of a mesh meant to be closed or the outlines of an
+    /// open one.
+    pub holes: usize,
+}
+
+impl CleanupReport {
+    /// Prints the report to the console.
+    pub fn print(&self) {
+        println!(
+            "Mesh cleanup: {} vertices welded, {} degenerate and {} duplicate triangles removed",
+            self.welded_vertices, self.degenerate_triangles, self.duplicate_triangles
+        );
+        println!(
+            "  {} triangles and {} normals flipped, {} non-manifold edges, {} holes",
+            self.flipped_triangles, self.flipped_normals, self.non_manifold_edges, self.holes
+        );
+    }
+}
+
+impl MeshData {
+    /// Creates a new `MeshData` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `positions` - The positions of the vertices.
+    /// * `normals` - The normals of the vertices, or empty if the mesh has none.
+    /// * `uvs` - The texture coordinates of the vertices, or empty if the mesh has none.
+    /// * `triangles` - The triangles, as indices into the vertices.
+    ///
+    /// # Returns
+    ///
+    /// A new `MeshData` instance.
+    pub fn new(
+        positions: Vec<Vector3>,
+        normals: Vec<Vector3>,
+        uvs: Vec<(f64, f64)>,
+        triangles: Vec<[usize; 3]>,
+    ) -> MeshData {
+        MeshData {
+            positions,
+            normals,
+            uvs,
+            triangles,
+        }
+    }
+
+    /// Runs every step of the cleanup in order: welding, removing degenerate and duplicate
+    /// triangles, dropping unused vertices, making the winding consistent, orienting the normals
+    /// and counting the holes.
+    ///
+    /// # Arguments
+    ///
+    /// * `tolerance` - The largest distance between welded vertices.
+    ///
+    /// # Returns
+    ///
+    /// The report of the changes and of the remaining flaws.
+    pub fn clean(&mut self, tolerance: f64) -> CleanupReport {
+        let welded_vertices = self.weld(tolerance);
+        let (degenerate_triangles, duplicate_triangles) = self.remove_degenerates();
+        self.remove_unused_vertices();
+        let (flipped_triangles, non_manifold_edges) = self.orient();
+        let flipped_normals = self.orient_normals();
+        CleanupReport {
+            welded_vertices,
+            degenerate_triangles,
+            duplicate_triangles,
+            flipped_triangles,
+            flipped_normals,
+            non_manifold_edges,
+            holes: self.holes(),
+        }
+    }
+
+    /// Cleans the mesh with the default tolerance and builds it, with its bounding volume
+    /// hierarchy, into a `TriangleMesh`.
+    ///
+    /// # Arguments
+    ///
+    /// * `material` - The material of the mesh.
+    ///
+    /// # Returns
+    ///
+    /// The built mesh, and the report of its cleanup.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no triangle is left after the cleanup.
+    pub fn build(mut self, material: Arc<dyn Material>) -> (TriangleMesh, CleanupReport) {
+        let report = self.clean(WELD_TOLERANCE);
+        if self.uvs.len() != self.positions.len() {
+            self.uvs = vec![(0.0, 0.0); self.positions.len()];
+        }
+        let mesh = TriangleMesh::new(
+            self.positions,
+            self.normals,
+            self.uvs,
+            self.triangles,
+            material,
+        );
+        (mesh, report)
+    }
+
+    /// Merges the vertices closer than a tolerance, hashing them into a grid of cells as large as
+    /// the tolerance so only the neighbouring cells are searched. Nearby vertices whose normals or
+    /// texture coordinates differ are moved onto the same position but not merged.
+    ///
+    /// # Arguments
+    ///
+    /// * `tolerance` - The largest distance between welded vertices.
+    ///
+    /// # Returns
+    ///
+    /// The number of vertices merged into another one.
+    pub fn weld(&mut self, tolerance: f64) -> usize {
+        let has_normals = self.normals.len() == self.positions.len();
+        let has_uvs = self.uvs.len() == self.positions.len();
+        let same_attributes = |a: usize, b: usize, mesh: &MeshData| {
+            let normals =
+                !has_normals || (mesh.normals[a] - mesh.normals[b]).length() <= ATTRIBUTE_TOLERANCE;
+            let uvs = !has_uvs || {
+                let ((ua, va), (ub, vb)) = (mesh.uvs[a], mesh.uvs[b]);
+                (ua - ub).abs() <= ATTRIBUTE_TOLERANCE && (va - vb).abs() <= ATTRIBUTE_TOLERANCE
+            };
+            normals && uvs
+        };
+
+        let cell = tolerance.max(f64::MIN_POSITIVE);
+        let key =
+            |p: &Vector3| [p.x, p.y, p.z].map(|coordinate| (coordinate / cell).floor() as i64);
+        let mut grid: HashMap<[i64; 3], Vec<usize>> = HashMap::new();
+        let mut kept: Vec<usize> = Vec::new();
+        let mut remap = Vec::with_capacity(self.positions.len());
+        for vertex in 0..self.positions.len() {
+            let [x, y, z] = key(&self.positions[vertex]);
+            let nearby: Vec<usize> = (-1..=1)
+                .flat_map(|dx| (-1..=1).flat_map(move |dy| (-1..=1).map(move |dz| (dx, dy, dz))))
+                .filter_map(|(dx, dy, dz)| grid.get(&[x + dx, y + dy, z + dz]))
+                .flatten()
+                .copied()
+                .filter(|&other| {
+                    (self.positions[kept[other]] - self.positions[vertex]).length() <= tolerance
+                })
+                .collect();
+
+            if let Some(&other) = nearby
+                .iter()
+                .find(|&&other| same_attributes(kept[other], vertex, self))
+            {
+                remap.push(other);
+                continue;
+            }
+            if let Some(&other) = nearby.first() {
+                self.positions[vertex] = self.positions[kept[other]];
+            }
+            remap.push(kept.len());
+            grid.entry([x, y, z]).or_default().push(kept.len());
+            kept.push(vertex);
+        }
+
+        let welded = self.positions.len() - kept.len();
+        self.positions = kept.iter().map(|&vertex| self.positions[vertex]).collect();
+        if has_normals {
+            self.normals = kept.iter().map(|&vertex| self.normals[vertex]).collect();
+        }
+        if has_uvs {
+            self.uvs = kept.iter().map(|&vertex| self.uvs[vertex]).collect();
+        }
+        for triangle in &mut self.triangles {
+            *triangle = triangle.map(|vertex| remap[vertex]);
+        }
+        welded
+    }
+
+    /// Removes the triangles with no area, and those repeating the corners of an earlier triangle
+    /// in any order.
+    ///
+    /// # Returns
+    ///
+    /// The numbers of degenerate and of duplicate triangles removed.
+    pub fn remove_degenerates(&mut self) -> (usize, usize) {
+        let (points, indices) = shared_positions(&self.positions);
+        let mut seen = HashSet::new();
+        let (mut degenerate, mut duplicate) = (0, 0);
+        self.triangles.retain(|triangle| {
+            let [a, b, c] = triangle.map(|vertex| indices[vertex]);
+            if (points[b] - points[a])
+                .cross(&(points[c] - points[a]))
+                .is_near_zero()
+            {
+                degenerate += 1;
+                return false;
+            }
+            let mut corners = [a, b, c];
+            corners.sort_unstable();
+            if !seen.insert(corners) {
+                duplicate += 1;
+                return false;
+            }
+            true
+        });
+        (degenerate, duplicate)
+    }
+
+    /// Drops the vertices no triangle refers to.
+    pub fn remove_unused_vertices(&mut self) {
+        let mut indices = vec![usize::MAX; self.positions.len()];
+        let mut kept = Vec::new();
+        for triangle in &mut self.triangles {
+            for vertex in triangle.iter_mut() {
+                if indices[*vertex] == usize::MAX {
+                    indices[*vertex] = kept.len();
+                    kept.push(*vertex);
+                }
+                *vertex = indices[*vertex];
+            }
+        }
+        self.positions = kept.iter().map(|&vertex| self.positions[vertex]).collect();
+        if !self.normals.is_empty() {
+            self.normals = kept.iter().map(|&vertex| self.normals[vertex]).collect();
+        }
+        if !self.uvs.is_empty() {
+            self.uvs = kept.iter().map(|&vertex| self.uvs[vertex]).collect();
+        }
+    }
+
+    /// Returns the triangles meeting at every edge, an edge being a pair of points.
+    ///
+    /// # Arguments
+    ///
+    /// * `indices` - The index of the point of every vertex.
+    fn edges(&self, indices: &[usize]) -> HashMap<(usize, usize), Vec<usize>> {
+        let mut edges: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+        for (index, triangle) in self.triangles.iter().enumerate() {
+            let [a, b, c] = triangle.map(|vertex| indices[vertex]);
+            for (p, q) in [(a, b), (b, c), (c, a)] {
+                edges.entry((p.min(q), p.max(q))).or_default().push(index);
+            }
+        }
+        edges
+    }
+
+    /// Makes the winding of the triangles consistent, walking from every triangle to its
+    /// neighbours across the edges they share and reversing those going along a shared edge the
+    /// same way. Every closed piece is then wound so its triangles face outwards, and every open
+    /// piece so its triangles face the side most of its vertex normals point to.
+    ///
+    /// # Returns
+    ///
+    /// The number of triangles reversed, and the number of edges shared by more than two
+    /// triangles.
+    pub fn orient(&mut self) -> (usize, usize) {
+        let (points, indices) = shared_positions(&self.positions);
+        let edges = self.edges(&indices);
+        let non_manifold = edges.values().filter(|faces| faces.len() > 2).count();
+        let corners = |triangle: &[usize; 3]| triangle.map(|vertex| indices[vertex]);
+        let goes = |corners: [usize; 3], p: usize, q: usize| {
+            (0..3).any(|i| corners[i] == p && corners[(i + 1) % 3] == q)
+        };
+
+        let mut flipped = vec![false; self.triangles.len()];
+        let mut visited = vec![false; self.triangles.len()];
+        for start in 0..self.triangles.len() {
+            if visited[start] {
+                continue;
+            }
+            visited[start] = true;
+            let mut piece = vec![start];
+            let mut closed = true;
+            let mut next = 0;
+            while next < piece.len() {
+                let index = piece[next];
+                next += 1;
+                let [a, b, c] = corners(&self.triangles[index]);
+                for (p, q) in [(a, b), (b, c), (c, a)] {
+                    let faces = &edges[&(p.min(q), p.max(q))];
+                    closed &= faces.len() == 2;
+                    if faces.len() != 2 {
+                        continue;
+                    }
+                    let other = if faces[0] == index {
+                        faces[1]
+                    } else {
+                        faces[0]
+                    };
+                    if visited[other] {
+                        continue;
+                    }
+                    visited[other] = true;
+                    if goes(corners(&self.triangles[other]), p, q) {
+                        self.triangles[other].swap(1, 2);
+                        flipped[other] = !flipped[other];
+                    }
+                    piece.push(other);
+                }
+            }
+
+            // The signed volume enclosed by a closed piece is positive when it faces outwards.
+            let facing: f64 = piece
+                .iter()
+                .map(|&index| {
+                    let [a, b, c] = corners(&self.triangles[index]).map(|point| points[point]);
+                    if closed {
+                        a.dot(&b.cross(&c))
+                    } else if self.normals.len() == self.positions.len() {
+                        let [na, nb, nc] = self.triangles[index].map(|vertex| self.normals[vertex]);
+                        (b - a).cross(&(c - a)).dot(&(na + nb + nc))
+                    } else {
+                        0.0
+                    }
+                })
+                .sum();
+            if facing < 0.0 {
+                for &index in &piece {
+                    self.triangles[index].swap(1, 2);
+                    flipped[index] = !flipped[index];
+                }
+            }
+        }
+        (
+            flipped.iter().filter(|&&flipped| flipped).count(),
+            non_manifold,
+        )
+    }
+
+    /// Turns the vertex normals to the side the triangles around them face, so the front faces of
+    /// the built mesh follow its winding. Meshes without normals get smooth normals, averaged from
+    /// the triangles around every vertex weighted by their areas.
+    ///
+    /// # Returns
+    ///
+    /// The number of vertex normals reversed.
+    pub fn orient_normals(&mut self) -> usize {
+        let mut sums = vec![Vector3::new(0.0, 0.0, 0.0); self.positions.len()];
+        for triangle in &self.triangles {
+            let [a, b, c] = triangle.map(|vertex| self.positions[vertex]);
+            let normal = (b - a).cross(&(c - a));
+            for &vertex in triangle {
+                sums[vertex] += normal;
+            }
+        }
+
+        if self.normals.len() != self.positions.len() {
+            self.normals = sums
+                .iter()
+                .map(|sum| {
+                    if sum.is_near_zero() {
+                        Vector3::new(0.0, 1.0, 0.0)
+                    } else {
+                        sum.normalize()
+                    }
+                })
+                .collect();
+            return 0;
+        }
+        let mut flipped = 0;
+        for (normal, sum) in self.normals.iter_mut().zip(&sums) {
+            if normal.dot(sum) < 0.0 {
+                *normal = -*normal;
+                flipped += 1;
+            }
+        }
+        flipped
+    }
+
+    /// Counts the boundary loops of the mesh, made of the edges belonging to a single triangle.
+    ///
+    /// # Returns
+    ///
+    /// The number of loops, joined wherever they touch.
+    pub fn holes(&self) -> usize {
+        let (points, indices) = shared_positions(&self.positions);
+        let mut parents: Vec<usize> = (0..points.len()).collect();
+        fn root(parents: &mut [usize], mut point: usize) -> usize {
+            while parents[point] != point {
+                parents[point] = parents[parents[point]];
+                point = parents[point];
+            }
+            point
+        }
+
+        let mut boundary = HashSet::new();
+        for (&(p, q), faces) in &self.edges(&indices) {
+            if faces.len() == 1 {
+                let (root_p, root_q) = (root(&mut parents, p), root(&mut parents, q));
+                parents[root_p] = root_q;
+                boundary.insert(p);
+                boundary.insert(q);
+            }
+        }
+        let roots: HashSet<usize> = boundary
+            .iter()
+            .map(|&point| root(&mut parents, point))
+            .collect();
+        roots.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hit::Hittable;
+    use crate::material::Lambertian;
+    use crate::ray::Ray;
+
+    /// The 12 triangles of a cube from -1 to 1 facing outwards, every triangle with its own
+    /// vertices like in an STL file.
+    fn cube_soup() -> MeshData {
+        let corner = |i: usize| {
+            Vector3::new(
+                if i & 1 == 0 { -1.0 } else { 1.0 },
+                if i & 2 == 0 { -1.0 } else { 1.0 },
+                if i & 4 == 0 { -1.0 } else { 1.0 },
+            )
+        };
+        let faces = [
+            [0, 2, 3, 1],
+            [4, 5, 7, 6],
+            [0, 1, 5, 4],
+            [2, 6, 7, 3],
+            [0, 4, 6, 2],
+            [1, 3, 7, 5],
+        ];
+        let mut data = MeshData::default();
+        for [a, b, c, d] in faces {
+            for triangle in [[a, b, c], [a, c, d]] {
+                data.triangles
+                    .push(std::array::from_fn(|i| data.positions.len() + i));
+                data.positions.extend(triangle.map(corner));
+            }
+        }
+        data
+    }
+
+    #[test]
+    fn test_clean_cube_soup() {
+        let mut data = cube_soup();
+        // Rounding noise, a reversed triangle, a repeated one and a sliver.
+        data.positions[5] += Vector3::new(1e-9, -1e-9, 0.0);
+        data.triangles[3].swap(0, 1);
+        data.triangles.push(data.triangles[7]);
+        data.triangles.push([0, 1, 1]);
+
+        let report = data.clean(WELD_TOLERANCE);
+        assert_eq!(report.welded_vertices, 36 - 8);
+        assert_eq!(report.degenerate_triangles, 1);
+        assert_eq!(report.duplicate_triangles, 1);
+        assert_eq!(report.flipped_triangles, 1);
+        assert_eq!(report.non_manifold_edges, 0);
+        assert_eq!(report.holes, 0);
+        assert_eq!(data.positions.len(), 8);
+        assert_eq!(data.triangles.len(), 12);
+
+        // Every normal points away from the center.
+        for (position, normal) in data.positions.iter().zip(&data.normals) {
+            assert!(normal.dot(position) > 0.0);
+        }
+    }
+
+    #[test]
+    fn test_orient_inside_out() {
+        let mut data = cube_soup();
+        for triangle in &mut data.triangles {
+            triangle.swap(1, 2);
+        }
+        data.triangles[0].swap(1, 2);
+        let report = data.clean(WELD_TOLERANCE);
+        assert_eq!(report.flipped_triangles, 11);
+
+        let (mesh, _) = data.build(Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5))));
+        let ray = Ray::new(Vector3::new(0.3, 0.2, 5.0), Vector3::new(0.0, 0.0, -1.0));
+        let record = mesh.hit(&ray, (0.001, f64::INFINITY)).unwrap();
+        assert!(record.front_face);
+        assert!((record.t - 4.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_seams_and_holes() {
+        // Two squares of a strip sharing an edge whose texture coordinates differ on each side,
+        // given with normals facing down.
+        let positions = vec![
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(1.0, 1.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(2.0, 0.0, 0.0),
+            Vector3::new(2.0, 1.0, 0.0),
+            Vector3::new(1.0, 1.0, 0.0),
+        ];
+        let uvs = vec![
+            (0.0, 0.0),
+            (1.0, 0.0),
+            (1.0, 1.0),
+            (0.0, 1.0),
+            (0.0, 0.0),
+            (1.0, 0.0),
+            (1.0, 1.0),
+            (0.0, 1.0),
+        ];
+        let normals = vec![Vector3::new(0.0, 0.0, -1.0); 8];
+        let triangles = vec![[0, 1, 2], [0, 2, 3], [4, 6, 5], [4, 7, 6]];
+        let mut data = MeshData::new(positions, normals, uvs, triangles);
+        let report = data.clean(WELD_TOLERANCE);
+
+        // The seam is kept, yet the strip is one piece facing its normals with a single outline.
+        assert_eq!(report.welded_vertices, 0);
+        assert_eq!(data.positions.len(), 8);
+        assert_eq!(report.holes, 1);
+        assert_eq!(report.flipped_normals, 0);
+        assert_eq!(report.flipped_triangles, 2);
+        for triangle in &data.triangles {
+            let [a, b, c] = triangle.map(|vertex| data.positions[vertex]);
+            assert!((b - a).cross(&(c - a)).z < 0.0);
+        }
+    }
+}
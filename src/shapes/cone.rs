@@ -0,0 +1,276 @@
+use crate::aabb::Aabb;
+use crate::hit::{HitRecord, Hittable};
+use crate::material::Material;
+use crate::material_table::MaterialTable;
+use crate::onb::Onb;
+use crate::ray::Ray;
+use crate::statistics::SceneStatistics;
+use crate::vector3::Vector3;
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+/// Represents a finite cone narrowing from a circular base to an apex, with its base optionally
+/// closed by a flat cap.
+///
+/// Cones are not sampled as lights, so emissive cones only light the scene when scattered rays
+/// happen to hit them.
+pub struct Cone {
+    /// The center of the base.
+    base: Vector3,
+    /// A right-handed basis whose `w` axis points from the base to the apex.
+    frame: Onb,
+    /// The distance between the base and the apex.
+    height: f64,
+    /// The radius of the base.
+    radius: f64,
+    /// Whether the base is closed by a cap.
+    capped: bool,
+    /// The material of the cone.
+    material: Arc<dyn Material>,
+}
+
+impl Cone {
+    /// Creates a new `Cone` with a capped base.
+    ///
+    /// # Arguments
+    ///
+    /// * `base` - The center of the base.
+    /// * `apex` - The tip of the cone.
+    /// * `radius` - The radius of the base.
+    /// * `material` - The material of the cone.
+    ///
+    /// # Returns
+    ///
+    /// A new `Cone` instance.
+    pub fn new(base: Vector3, apex: Vector3, radius: f64, material: Arc<dyn Material>) -> Cone {
+        let axis = apex - base;
+        let onb = Onb::new(&axis);
+        Cone {
+            base,
+            // The basis of `Onb` is left-handed, swapping its tangents keeps the texture
+            // coordinates from being mirrored.
+            frame: Onb {
+                u: onb.v,
+                v: onb.u,
+                w: onb.w,
+            },
+            height: axis.length(),
+            radius,
+            capped: true,
+            material,
+        }
+    }
+
+    /// Sets whether the base of the cone is closed by a cap.
+    ///
+    /// # Arguments
+    ///
+    /// * `capped` - Whether the base is closed.
+    ///
+    /// # Returns
+    ///
+    /// The updated `Cone` instance.
+    pub fn with_cap(mut self, capped: bool) -> Cone {
+        self.capped = capped;
+        self
+    }
+}
+
+impl Hittable for Cone {
+    /// Checks if a ray hits the cone within a given interval, testing the slanted side and the
+    /// cap in the local frame of the cone, where the side is the set of points whose distance to
+    /// the axis is `radius / height` times their distance below the apex.
+    ///
+    /// The side is textured with `u` going once around the axis and `v` from the base to the apex,
+    /// while the cap maps the square enclosing it to the unit square.
+    ///
+    /// # Arguments
+    ///
+    /// * `ray` - The ray to test for intersection.
+    /// * `interval` - The range of distances to consider for intersections.
+    ///
+    /// # Returns
+    ///
+    /// An `Option` containing the closest `HitRecord` if an intersection is found, or `None` if no intersection is found.
+    fn hit(&self, ray: &Ray, interval: (f64, f64)) -> Option<HitRecord<'_>> {
+        let origin = self.frame.world_to_local(&(ray.origin - self.base));
+        let direction = self.frame.world_to_local(&ray.direction);
+
+        // The closest crossing as its distance, local outward normal, texture coordinates and
+        // local directions in which they increase.
+        let mut closest: Option<(f64, Vector3, f64, f64, [Vector3; 2])> = None;
+        let mut consider = |t: f64, normal: Vector3, u: f64, v: f64, tangents: [Vector3; 2]| {
+            if t >= interval.0
+                && t <= interval.1
+                && closest.is_none_or(|(closest_t, ..)| t < closest_t)
+            {
+                closest = Some((t, normal, u, v, tangents));
+            }
+        };
+
+        // x^2 + y^2 = k^2 (h - z)^2, solved as a t^2 + b t + c = 0 like the sphere.
+        let k = self.radius / self.height;
+        let below = self.height - origin.z;
+        let a = direction.x * direction.x + direction.y * direction.y
+            - k * k * direction.z * direction.z;
+        let b =
+            2.0 * (origin.x * direction.x + origin.y * direction.y + k * k * below * direction.z);
+        let c = origin.x * origin.x + origin.y * origin.y - k * k * below * below;
+        let discriminant = b * b - 4.0 * a * c;
+        if a != 0.0 && discriminant >= 0.0 {
+            let root = discriminant.sqrt();
+            for t in [(-b - root) / (2.0 * a), (-b + root) / (2.0 * a)] {
+                let p = origin + t * direction;
+                // The equation also holds on the mirrored cone above the apex.
+                if (0.0..=self.height).contains(&p.z) {
+                    let distance = (p.x * p.x + p.y * p.y).sqrt();
+                    let normal = if distance > 0.0 {
+                        Vector3::new(
+                            p.x / distance * self.height,
+                            p.y / distance * self.height,
+                            self.radius,
+                        )
+                        .normalize()
+                    } else {
+                        Vector3::new(0.0, 0.0, 1.0)
+                    };
+                    let u = (p.y.atan2(p.x) / (2.0 * PI)).rem_euclid(1.0);
+                    // Going up the side moves towards the axis.
+                    let up = if distance > 0.0 {
+                        Vector3::new(-k * p.x / distance, -k * p.y / distance, 1.0)
+                    } else {
+                        Vector3::new(0.0, 0.0, 1.0)
+                    };
+                    let tangents = [2.0 * PI * Vector3::new(-p.y, p.x, 0.0), self.height * up];
+                    consider(t, normal, u, p.z / self.height, tangents);
+                }
+            }
+        }
+
+        if self.capped && direction.z != 0.0 {
+            let t = -origin.z / direction.z;
+            let p = origin + t * direction;
+            if p.x * p.x + p.y * p.y <= self.radius * self.radius {
+                let u = 0.5 + 0.5 * p.x / self.radius;
+                let v = 0.5 - 0.5 * p.y / self.radius;
+                let tangents = [
+                    Vector3::new(2.0 * self.radius, 0.0, 0.0),
+                    Vector3::new(0.0, -2.0 * self.radius, 0.0),
+                ];
+                consider(t, Vector3::new(0.0, 0.0, -1.0), u, v, tangents);
+            }
+        }
+
+        let (t, normal, u, v, [tangent, bitangent]) = closest?;
+        let mut record = HitRecord::new(t, ray.point_at(t), self.material.as_ref(), u, v);
+        record.set_face_normal(ray, &self.frame.local_to_world(&normal));
+        record.set_tangents(
+            self.frame.local_to_world(&tangent),
+            self.frame.local_to_world(&bitangent),
+        );
+        Some(record)
+    }
+
+    fn collect_materials(&self, table: &mut MaterialTable) {
+        table.add(&self.material);
+    }
+
+    fn collect_statistics(&self, statistics: &mut SceneStatistics) {
+        statistics.add_primitive("Cone", std::mem::size_of_val(self));
+        self.material.collect_statistics(statistics);
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        // The base circle reaches r sqrt(1 - w_i^2) along axis i, and the apex is a single point.
+        let w = self.frame.w;
+        let extent = self.radius
+            * Vector3::new(
+                (1.0 - w.x * w.x).max(0.0).sqrt(),
+                (1.0 - w.y * w.y).max(0.0).sqrt(),
+                (1.0 - w.z * w.z).max(0.0).sqrt(),
+            );
+        Some(Aabb::from_points(&[
+            self.base - extent,
+            self.base + extent,
+            self.base + self.height * w,
+        ]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Lambertian;
+
+    fn cone() -> Cone {
+        Cone::new(
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(0.0, 2.0, 0.0),
+            1.0,
+            Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5))),
+        )
+    }
+
+    #[test]
+    fn test_cone_side() {
+        let cone = cone();
+        // Halfway up the radius is halved.
+        let ray = Ray::new(Vector3::new(0.0, 1.0, 5.0), Vector3::new(0.0, 0.0, -1.0));
+        let record = cone.hit(&ray, (0.001, f64::INFINITY)).unwrap();
+        assert!((record.t - 4.5).abs() < 1e-9);
+        assert!((record.v - 0.5).abs() < 1e-9);
+        let expected = Vector3::new(0.0, 1.0, 2.0).normalize();
+        assert!((record.normal - expected).length() < 1e-9);
+        assert!(record.front_face);
+
+        // The mirrored cone above the apex is not part of the surface.
+        let above = Ray::new(Vector3::new(0.0, 3.0, 5.0), Vector3::new(0.0, 0.0, -1.0));
+        assert!(cone.hit(&above, (0.001, f64::INFINITY)).is_none());
+        let down = Ray::new(Vector3::new(0.0, 3.0, 0.0), Vector3::new(0.0, -1.0, 0.0));
+        let record = cone.hit(&down, (0.001, f64::INFINITY)).unwrap();
+        assert!((record.t - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_cone_cap() {
+        let up = Ray::new(Vector3::new(0.2, -1.0, 0.3), Vector3::new(0.0, 1.0, 0.0));
+        let cone = cone();
+        let record = cone.hit(&up, (0.001, f64::INFINITY)).unwrap();
+        assert!((record.t - 1.0).abs() < 1e-9);
+        assert!((record.normal - Vector3::new(0.0, -1.0, 0.0)).length() < 1e-9);
+        assert!(record.front_face);
+
+        // Without the cap the ray enters the hollow cone and hits the inside of the side.
+        let open = cone.with_cap(false);
+        let record = open.hit(&up, (0.001, f64::INFINITY)).unwrap();
+        assert!(!record.front_face);
+        let distance = (0.2f64 * 0.2 + 0.3 * 0.3).sqrt();
+        assert!((record.poz.y - 2.0 * (1.0 - distance)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_cone_bounding_box() {
+        let cone = Cone::new(
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(1.0, 2.0, -1.0),
+            0.7,
+            Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5))),
+        );
+        let bounds = cone.bounding_box().unwrap();
+        for _ in 0..1000 {
+            let origin = Vector3::new(
+                fastrand::f64() * 4.0 - 2.0,
+                fastrand::f64() * 4.0 - 1.0,
+                3.0,
+            );
+            let ray = Ray::new(origin, Vector3::new(0.0, 0.0, -1.0));
+            if let Some(record) = cone.hit(&ray, (0.001, f64::INFINITY)) {
+                assert!(bounds.hit(&ray, (0.001, f64::INFINITY)));
+                let p = record.poz;
+                assert!(p.x >= bounds.min.x && p.x <= bounds.max.x);
+                assert!(p.y >= bounds.min.y && p.y <= bounds.max.y);
+                assert!(p.z >= bounds.min.z && p.z <= bounds.max.z);
+            }
+        }
+    }
+}
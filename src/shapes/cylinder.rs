@@ -0,0 +1,272 @@
+use crate::aabb::Aabb;
+use crate::hit::{HitRecord, Hittable};
+use crate::material::Material;
+use crate::material_table::MaterialTable;
+use crate::onb::Onb;
+use crate::ray::Ray;
+use crate::statistics::SceneStatistics;
+use crate::vector3::Vector3;
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+/// Represents a finite cylinder between two points, with its ends optionally closed by flat caps.
+///
+/// Cylinders are not sampled as lights, so emissive cylinders only light the scene when scattered
+/// rays happen to hit them.
+pub struct Cylinder {
+    /// The center of the bottom end.
+    base: Vector3,
+    /// A right-handed basis whose `w` axis points from the bottom end to the top end.
+    frame: Onb,
+    /// The distance between the two ends.
+    height: f64,
+    /// The radius of the cylinder.
+    radius: f64,
+    /// Whether the ends are closed by caps.
+    capped: bool,
+    /// The material of the cylinder.
+    material: Arc<dyn Material>,
+}
+
+impl Cylinder {
+    /// Creates a new capped `Cylinder` between two points.
+    ///
+    /// # Arguments
+    ///
+    /// * `base` - The center of the bottom end.
+    /// * `top` - The center of the top end.
+    /// * `radius` - The radius of the cylinder.
+    /// * `material` - The material of the cylinder.
+    ///
+    /// # Returns
+    ///
+    /// A new `Cylinder` instance.
+    pub fn new(base: Vector3, top: Vector3, radius: f64, material: Arc<dyn Material>) -> Cylinder {
+        let axis = top - base;
+        let onb = Onb::new(&axis);
+        Cylinder {
+            base,
+            // The basis of `Onb` is left-handed, swapping its tangents keeps the texture
+            // coordinates from being mirrored.
+            frame: Onb {
+                u: onb.v,
+                v: onb.u,
+                w: onb.w,
+            },
+            height: axis.length(),
+            radius,
+            capped: true,
+            material,
+        }
+    }
+
+    /// Sets whether the ends of the cylinder are closed by caps. An open cylinder is a tube
+    /// showing its inside.
+    ///
+    /// # Arguments
+    ///
+    /// * `capped` - Whether the ends are closed.
+    ///
+    /// # Returns
+    ///
+    /// The updated `Cylinder` instance.
+    pub fn with_caps(mut self, capped: bool) -> Cylinder {
+        self.capped = capped;
+        self
+    }
+}
+
+impl Hittable for Cylinder {
+    /// Checks if a ray hits the cylinder within a given interval, testing the curved side and the
+    /// caps in the local frame of the cylinder.
+    ///
+    /// The side is textured with `u` going once around the axis and `v` from the bottom to the
+    /// top, while each cap maps the square enclosing it to the unit square.
+    ///
+    /// # Arguments
+    ///
+    /// * `ray` - The ray to test for intersection.
+    /// * `interval` - The range of distances to consider for intersections.
+    ///
+    /// # Returns
+    ///
+    /// An `Option` containing the closest `HitRecord` if an intersection is found, or `None` if no intersection is found.
+    fn hit(&self, ray: &Ray, interval: (f64, f64)) -> Option<HitRecord<'_>> {
+        let origin = self.frame.world_to_local(&(ray.origin - self.base));
+        let direction = self.frame.world_to_local(&ray.direction);
+
+        // The closest crossing as its distance, local outward normal, texture coordinates and
+        // local directions in which they increase.
+        let mut closest: Option<(f64, Vector3, f64, f64, [Vector3; 2])> = None;
+        let mut consider = |t: f64, normal: Vector3, u: f64, v: f64, tangents: [Vector3; 2]| {
+            if t >= interval.0
+                && t <= interval.1
+                && closest.is_none_or(|(closest_t, ..)| t < closest_t)
+            {
+                closest = Some((t, normal, u, v, tangents));
+            }
+        };
+
+        let a = direction.x * direction.x + direction.y * direction.y;
+        let b = 2.0 * (origin.x * direction.x + origin.y * direction.y);
+        let c = origin.x * origin.x + origin.y * origin.y - self.radius * self.radius;
+        let discriminant = b * b - 4.0 * a * c;
+        if a > 0.0 && discriminant >= 0.0 {
+            let root = discriminant.sqrt();
+            for t in [(-b - root) / (2.0 * a), (-b + root) / (2.0 * a)] {
+                let p = origin + t * direction;
+                if (0.0..=self.height).contains(&p.z) {
+                    let u = (p.y.atan2(p.x) / (2.0 * PI)).rem_euclid(1.0);
+                    let normal = Vector3::new(p.x, p.y, 0.0) / self.radius;
+                    let tangents = [
+                        2.0 * PI * Vector3::new(-p.y, p.x, 0.0),
+                        Vector3::new(0.0, 0.0, self.height),
+                    ];
+                    consider(t, normal, u, p.z / self.height, tangents);
+                }
+            }
+        }
+
+        if self.capped && direction.z != 0.0 {
+            for (z, normal) in [(0.0, -1.0), (self.height, 1.0)] {
+                let t = (z - origin.z) / direction.z;
+                let p = origin + t * direction;
+                if p.x * p.x + p.y * p.y <= self.radius * self.radius {
+                    let u = 0.5 + 0.5 * p.x / self.radius;
+                    let v = 0.5 + 0.5 * normal * p.y / self.radius;
+                    let tangents = [
+                        Vector3::new(2.0 * self.radius, 0.0, 0.0),
+                        Vector3::new(0.0, 2.0 * normal * self.radius, 0.0),
+                    ];
+                    consider(t, Vector3::new(0.0, 0.0, normal), u, v, tangents);
+                }
+            }
+        }
+
+        let (t, normal, u, v, [tangent, bitangent]) = closest?;
+        let mut record = HitRecord::new(t, ray.point_at(t), self.material.as_ref(), u, v);
+        record.set_face_normal(ray, &self.frame.local_to_world(&normal));
+        record.set_tangents(
+            self.frame.local_to_world(&tangent),
+            self.frame.local_to_world(&bitangent),
+        );
+        Some(record)
+    }
+
+    fn collect_materials(&self, table: &mut MaterialTable) {
+        table.add(&self.material);
+    }
+
+    fn collect_statistics(&self, statistics: &mut SceneStatistics) {
+        statistics.add_primitive("Cylinder", std::mem::size_of_val(self));
+        self.material.collect_statistics(statistics);
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        // A circle of radius r around the unit axis w reaches r sqrt(1 - w_i^2) along axis i.
+        let w = self.frame.w;
+        let extent = self.radius
+            * Vector3::new(
+                (1.0 - w.x * w.x).max(0.0).sqrt(),
+                (1.0 - w.y * w.y).max(0.0).sqrt(),
+                (1.0 - w.z * w.z).max(0.0).sqrt(),
+            );
+        let top = self.base + self.height * w;
+        Some(Aabb::from_points(&[
+            self.base - extent,
+            self.base + extent,
+            top - extent,
+            top + extent,
+        ]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Lambertian;
+
+    fn cylinder() -> Cylinder {
+        Cylinder::new(
+            Vector3::new(0.0, -1.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            0.5,
+            Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5))),
+        )
+    }
+
+    #[test]
+    fn test_cylinder_side() {
+        let cylinder = cylinder();
+        let ray = Ray::new(Vector3::new(0.0, 0.5, 5.0), Vector3::new(0.0, 0.0, -1.0));
+        let record = cylinder.hit(&ray, (0.001, f64::INFINITY)).unwrap();
+        assert!((record.t - 4.5).abs() < 1e-9);
+        assert!((record.normal - Vector3::new(0.0, 0.0, 1.0)).length() < 1e-9);
+        assert!(record.front_face);
+        assert!((record.v - 0.75).abs() < 1e-9);
+
+        // Going once around the axis covers u once, increasing to the right seen from outside.
+        let right = Ray::new(Vector3::new(0.1, 0.5, 5.0), Vector3::new(0.0, 0.0, -1.0));
+        let right = cylinder.hit(&right, (0.001, f64::INFINITY)).unwrap();
+        assert!((right.u - record.u + 1.0).rem_euclid(1.0) < 0.5);
+
+        // Rays above the top or through the open ends miss the side.
+        let above = Ray::new(Vector3::new(0.0, 1.5, 5.0), Vector3::new(0.0, 0.0, -1.0));
+        assert!(cylinder.hit(&above, (0.001, f64::INFINITY)).is_none());
+        let down = Ray::new(Vector3::new(0.1, 5.0, 0.0), Vector3::new(0.0, -1.0, 0.0));
+        let open = cylinder.with_caps(false);
+        let inside = open.hit(&down, (0.001, f64::INFINITY));
+        assert!(inside.is_none());
+    }
+
+    #[test]
+    fn test_cylinder_caps() {
+        let cylinder = cylinder();
+        let down = Ray::new(Vector3::new(0.1, 5.0, 0.2), Vector3::new(0.0, -1.0, 0.0));
+        let record = cylinder.hit(&down, (0.001, f64::INFINITY)).unwrap();
+        assert!((record.t - 4.0).abs() < 1e-9);
+        assert!((record.normal - Vector3::new(0.0, 1.0, 0.0)).length() < 1e-9);
+        assert!((0.0..=1.0).contains(&record.u) && (0.0..=1.0).contains(&record.v));
+
+        // From inside the cylinder the far cap is hit from its back.
+        let inside = Ray::new(Vector3::new(0.0, 0.0, 0.0), Vector3::new(0.0, -1.0, 0.0));
+        let record = cylinder.hit(&inside, (0.001, f64::INFINITY)).unwrap();
+        assert!((record.t - 1.0).abs() < 1e-9);
+        assert!(!record.front_face);
+
+        // A slanted ray entering through the side leaves through the bottom cap.
+        let slanted = Ray::new(Vector3::new(-1.0, 0.0, 0.0), Vector3::new(1.0, -1.0, 0.0));
+        let entry = cylinder.hit(&slanted, (0.001, f64::INFINITY)).unwrap();
+        assert!((entry.poz.x + 0.5).abs() < 1e-9);
+        let exit = cylinder
+            .hit(&slanted, (entry.t + 0.001, f64::INFINITY))
+            .unwrap();
+        assert!((exit.poz.y + 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_cylinder_bounding_box() {
+        let cylinder = Cylinder::new(
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(1.0, 1.0, 0.0),
+            0.5,
+            Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5))),
+        );
+        let bounds = cylinder.bounding_box().unwrap();
+        for _ in 0..1000 {
+            let origin = Vector3::new(
+                fastrand::f64() * 4.0 - 2.0,
+                fastrand::f64() * 4.0 - 2.0,
+                3.0,
+            );
+            let ray = Ray::new(origin, Vector3::new(0.0, 0.0, -1.0));
+            if let Some(record) = cylinder.hit(&ray, (0.001, f64::INFINITY)) {
+                assert!(bounds.hit(&ray, (0.001, f64::INFINITY)));
+                let p = record.poz;
+                assert!(p.x >= bounds.min.x && p.x <= bounds.max.x);
+                assert!(p.y >= bounds.min.y && p.y <= bounds.max.y);
+                assert!(p.z >= bounds.min.z && p.z <= bounds.max.z);
+            }
+        }
+    }
+}
@@ -0,0 +1,236 @@
+//! Distance estimators of fractals, turned into ray-marched shapes: the Mandelbulb, a 3D analogue
+//! of the Mandelbrot set, and the Menger sponge, a cube with crosses carved out recursively.
+
+use crate::aabb::Aabb;
+use crate::material::Material;
+use crate::shapes::sdf::SdfShape;
+use crate::vector3::Vector3;
+use std::sync::Arc;
+
+/// The margin added around the extent of a fractal for its bounding box.
+const BOUNDS_MARGIN: f64 = 1.01;
+
+/// Represents the Mandelbulb fractal, the set of points whose orbit under `z -> z^power + c` in
+/// spherical coordinates stays bounded, with its pole along the y-axis.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mandelbulb {
+    /// The exponent of the iteration, 8 for the classic bulb.
+    power: f64,
+    /// The largest number of iterations of a point. More iterations show finer details.
+    iterations: u32,
+    /// The radius beyond which the orbit of a point is considered to escape.
+    bailout: f64,
+}
+
+impl Mandelbulb {
+    /// Creates a new `Mandelbulb`.
+    ///
+    /// # Arguments
+    ///
+    /// * `power` - The exponent of the iteration, 8 for the classic bulb.
+    /// * `iterations` - The largest number of iterations of a point.
+    /// * `bailout` - The radius beyond which the orbit of a point escapes, at least 2.
+    ///
+    /// # Returns
+    ///
+    /// A new `Mandelbulb` instance.
+    pub fn new(power: f64, iterations: u32, bailout: f64) -> Mandelbulb {
+        Mandelbulb {
+            power: power.max(2.0),
+            iterations,
+            bailout: bailout.max(2.0),
+        }
+    }
+
+    /// Estimates the distance from a point to the Mandelbulb, from the rate at which its orbit
+    /// escapes.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The point, in the frame of the fractal.
+    ///
+    /// # Returns
+    ///
+    /// The estimated distance, zero or negative inside the set.
+    pub fn distance(&self, point: Vector3) -> f64 {
+        let mut z = point;
+        let mut derivative = 1.0;
+        let mut r = 0.0;
+        for _ in 0..self.iterations {
+            r = z.length();
+            if r > self.bailout {
+                break;
+            }
+            let cos_theta = if r > 0.0 {
+                (z.y / r).clamp(-1.0, 1.0)
+            } else {
+                1.0
+            };
+            let theta = cos_theta.acos() * self.power;
+            let phi = z.z.atan2(z.x) * self.power;
+            derivative = r.powf(self.power - 1.0) * self.power * derivative + 1.0;
+            z = r.powf(self.power)
+                * Vector3::new(
+                    theta.sin() * phi.cos(),
+                    theta.cos(),
+                    theta.sin() * phi.sin(),
+                )
+                + point;
+        }
+        if r > 0.0 {
+            0.5 * r.ln() * r / derivative
+        } else {
+            0.0
+        }
+    }
+
+    /// Returns the radius of a sphere around the origin enclosing the Mandelbulb: points further
+    /// than `r`, with `r^power = r + 1`, escape.
+    fn radius(&self) -> f64 {
+        (0..32).fold(2.0, |r: f64, _| (r + 1.0).powf(1.0 / self.power))
+    }
+
+    /// Turns the Mandelbulb into a shape placed in the scene.
+    ///
+    /// # Arguments
+    ///
+    /// * `center` - The center of the fractal in the scene.
+    /// * `scale` - The size of one unit of the fractal in the scene. The classic bulb is about
+    ///   1.1 units across from its center.
+    /// * `material` - The material of the fractal.
+    ///
+    /// # Returns
+    ///
+    /// The ray-marched shape of the fractal.
+    pub fn into_shape(self, center: Vector3, scale: f64, material: Arc<dyn Material>) -> SdfShape {
+        let extent = Vector3::new(1.0, 1.0, 1.0) * (self.radius() * BOUNDS_MARGIN * scale);
+        SdfShape::new(
+            move |point| scale * self.distance((point - center) / scale),
+            Aabb::new(center - extent, center + extent),
+            material,
+        )
+    }
+}
+
+/// Represents the Menger sponge, a cube from -1 to 1 along every axis from which the middle cross
+/// of every face is carved out, recursively in each of the 20 remaining subcubes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MengerSponge {
+    /// The number of times the subcubes are carved out.
+    iterations: u32,
+}
+
+impl MengerSponge {
+    /// Creates a new `MengerSponge`.
+    ///
+    /// # Arguments
+    ///
+    /// * `iterations` - The number of times the subcubes are carved out.
+    ///
+    /// # Returns
+    ///
+    /// A new `MengerSponge` instance.
+    pub fn new(iterations: u32) -> MengerSponge {
+        MengerSponge { iterations }
+    }
+
+    /// Computes the signed distance from a point to the Menger sponge.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The point, in the frame of the fractal.
+    ///
+    /// # Returns
+    ///
+    /// The signed distance, negative inside the sponge.
+    pub fn distance(&self, point: Vector3) -> f64 {
+        // The distance to the whole cube.
+        let q =
+            Vector3::new(point.x.abs(), point.y.abs(), point.z.abs()) - Vector3::new(1.0, 1.0, 1.0);
+        let outside = Vector3::new(q.x.max(0.0), q.y.max(0.0), q.z.max(0.0)).length();
+        let mut distance = outside + q.x.max(q.y).max(q.z).min(0.0);
+
+        // Every iteration carves out the crosses of the subcubes three times smaller.
+        let mut scale = 1.0;
+        for _ in 0..self.iterations {
+            let fold = |c: f64| {
+                let a = (c * scale).rem_euclid(2.0) - 1.0;
+                (1.0 - 3.0 * a.abs()).abs()
+            };
+            let (x, y, z) = (fold(point.x), fold(point.y), fold(point.z));
+            scale *= 3.0;
+            let cross = (x.max(y).min(y.max(z)).min(z.max(x)) - 1.0) / scale;
+            distance = distance.max(cross);
+        }
+        distance
+    }
+
+    /// Turns the Menger sponge into a shape placed in the scene.
+    ///
+    /// # Arguments
+    ///
+    /// * `center` - The center of the sponge in the scene.
+    /// * `scale` - Half the side of the sponge in the scene.
+    /// * `material` - The material of the sponge.
+    ///
+    /// # Returns
+    ///
+    /// The ray-marched shape of the fractal.
+    pub fn into_shape(self, center: Vector3, scale: f64, material: Arc<dyn Material>) -> SdfShape {
+        let extent = Vector3::new(1.0, 1.0, 1.0) * (BOUNDS_MARGIN * scale);
+        SdfShape::new(
+            move |point| scale * self.distance((point - center) / scale),
+            Aabb::new(center - extent, center + extent),
+            material,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hit::Hittable;
+    use crate::material::Lambertian;
+    use crate::ray::Ray;
+
+    #[test]
+    fn test_mandelbulb() {
+        let bulb = Mandelbulb::new(8.0, 12, 2.0);
+        assert!(bulb.distance(Vector3::new(0.0, 0.0, 0.0)) <= 0.0);
+        let far = bulb.distance(Vector3::new(3.0, 0.0, 0.0));
+        assert!(far > 0.0 && far < 3.0, "{}", far);
+        assert!((bulb.radius().powf(8.0) - bulb.radius() - 1.0).abs() < 1e-9);
+
+        let shape = bulb.into_shape(
+            Vector3::new(0.0, 0.0, 0.0),
+            1.0,
+            Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5))),
+        );
+        let ray = Ray::new(Vector3::new(0.0, 0.0, -5.0), Vector3::new(0.0, 0.0, 1.0));
+        let record = shape.hit(&ray, (0.001, f64::INFINITY)).unwrap();
+        assert!(record.t > 3.8 && record.t < 5.0, "{}", record.t);
+        assert!(record.normal.z < 0.0);
+    }
+
+    #[test]
+    fn test_menger_sponge() {
+        let sponge = MengerSponge::new(3);
+        assert!((sponge.distance(Vector3::new(3.0, 0.0, 0.0)) - 2.0).abs() < 1e-9);
+        // The middle of the cube and of its faces are carved out, its corners are not.
+        assert!(sponge.distance(Vector3::new(0.0, 0.0, 0.0)) > 0.0);
+        assert!(sponge.distance(Vector3::new(1.0, 0.0, 0.0)) > 0.0);
+        assert!(sponge.distance(Vector3::new(0.95, 0.95, 0.95)) < 0.0);
+
+        let shape = sponge.into_shape(
+            Vector3::new(0.0, 0.0, 10.0),
+            2.0,
+            Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5))),
+        );
+        let corner = Ray::new(Vector3::new(1.9, 1.9, 0.0), Vector3::new(0.0, 0.0, 1.0));
+        let record = shape.hit(&corner, (0.001, f64::INFINITY)).unwrap();
+        assert!((record.t - 8.0).abs() < 1e-3, "{}", record.t);
+        // Through the middle of the cube, the ray passes through the tunnel carved out.
+        let tunnel = Ray::new(Vector3::new(0.0, 0.0, 0.0), Vector3::new(0.0, 0.0, 1.0));
+        assert!(shape.hit(&tunnel, (0.001, f64::INFINITY)).is_none());
+    }
+}
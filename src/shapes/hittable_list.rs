@@ -0,0 +1,268 @@
+use crate::aabb::Aabb;
+use crate::hit::{HitRecord, Hittable};
+use crate::material_table::MaterialTable;
+use crate::ray::Ray;
+use crate::statistics::SceneStatistics;
+use crate::vector3::Vector3;
+use std::cmp::Ordering;
+
+/// Represents a group of objects that behaves as a single object, so it can be transformed,
+/// instanced or sampled as a light as a whole.
+#[derive(Default)]
+pub struct HittableList {
+    /// The objects of the group.
+    objects: Vec<Box<dyn Hittable>>,
+    /// The box enclosing every object, or `None` if the group is empty or some object is
+    /// unbounded.
+    bounds: Option<Aabb>,
+    /// Whether some object of the group is unbounded, in which case rays are never skipped.
+    unbounded: bool,
+    /// The indices of the emissive objects, found once as they are added since the group is
+    /// sampled as a light on every bounce.
+    emissive: Vec<usize>,
+}
+
+impl HittableList {
+    /// Creates a new, empty `HittableList`.
+    ///
+    /// # Returns
+    ///
+    /// A new `HittableList` instance.
+    pub fn new() -> HittableList {
+        HittableList::default()
+    }
+
+    /// Adds an object to the group and grows its bounding box to enclose it.
+    ///
+    /// # Arguments
+    ///
+    /// * `object` - The object to add.
+    pub fn add(&mut self, object: Box<dyn Hittable>) {
+        match object.bounding_box() {
+            Some(bounds) if !self.unbounded => {
+                self.bounds = Some(self.bounds.map_or(bounds, |b| b.merge(&bounds)));
+            }
+            _ => {
+                self.unbounded = true;
+                self.bounds = None;
+            }
+        }
+        if object.is_emissive() {
+            self.emissive.push(self.objects.len());
+        }
+        self.objects.push(object);
+    }
+
+    /// Returns the number of objects in the group.
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    /// Returns `true` if the group has no objects.
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    /// Returns the number of objects sampled as lights: the emissive ones, or every object if
+    /// none emits.
+    fn sampled_len(&self) -> usize {
+        if self.emissive.is_empty() {
+            self.objects.len()
+        } else {
+            self.emissive.len()
+        }
+    }
+
+    /// Returns an object sampled as a light.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The position of the object among the sampled objects, below `sampled_len`.
+    ///
+    /// # Returns
+    ///
+    /// The object.
+    fn sampled_object(&self, index: usize) -> &dyn Hittable {
+        if self.emissive.is_empty() {
+            self.objects[index].as_ref()
+        } else {
+            self.objects[self.emissive[index]].as_ref()
+        }
+    }
+}
+
+impl Hittable for HittableList {
+    /// Checks if a ray hits any object of the group within a given interval. Rays missing the
+    /// bounding box of the group are rejected without testing its objects.
+    ///
+    /// # Arguments
+    ///
+    /// * `ray` - The ray to test for intersection.
+    /// * `interval` - The range of distances to consider for intersections.
+    ///
+    /// # Returns
+    ///
+    /// An `Option` containing the closest `HitRecord` if an intersection is found, or `None` if no intersection is found.
+    fn hit(&self, ray: &Ray, interval: (f64, f64)) -> Option<HitRecord<'_>> {
+        if let Some(bounds) = &self.bounds {
+            if !bounds.hit(ray, interval) {
+                return None;
+            }
+        }
+        self.objects
+            .iter()
+            .filter_map(|object| object.hit(ray, interval))
+            .min_by(|r1, r2| r1.t.partial_cmp(&r2.t).unwrap_or(Ordering::Equal))
+    }
+
+    fn is_emissive(&self) -> bool {
+        !self.emissive.is_empty()
+    }
+
+    fn power(&self) -> Vector3 {
+        self.objects
+            .iter()
+            .fold(Vector3::new(0.0, 0.0, 0.0), |sum, object| {
+                sum + object.power()
+            })
+    }
+
+    /// Returns the probability density of sampling a direction towards a uniformly chosen
+    /// emissive object of the group.
+    ///
+    /// # Arguments
+    ///
+    /// * `origin` - The point from which the group is sampled.
+    /// * `direction` - The direction to evaluate.
+    ///
+    /// # Returns
+    ///
+    /// The probability density with respect to solid angle.
+    fn pdf_value(&self, origin: &Vector3, direction: &Vector3) -> f64 {
+        let count = self.sampled_len();
+        let weight = 1.0 / count.max(1) as f64;
+        (0..count)
+            .map(|index| weight * self.sampled_object(index).pdf_value(origin, direction))
+            .sum()
+    }
+
+    /// Generates a direction towards a uniformly chosen emissive object of the group.
+    ///
+    /// # Arguments
+    ///
+    /// * `origin` - The point from which the group is sampled.
+    ///
+    /// # Returns
+    ///
+    /// The generated direction.
+    fn random(&self, origin: &Vector3) -> Vector3 {
+        let count = self.sampled_len();
+        if count == 0 {
+            return Vector3::new(1.0, 0.0, 0.0);
+        }
+        self.sampled_object(fastrand::usize(..count)).random(origin)
+    }
+
+    fn collect_materials(&self, table: &mut MaterialTable) {
+        for object in &self.objects {
+            object.collect_materials(table);
+        }
+    }
+
+    fn collect_statistics(&self, statistics: &mut SceneStatistics) {
+        statistics.add_geometry(std::mem::size_of_val(self));
+        for object in &self.objects {
+            object.collect_statistics(statistics);
+        }
+    }
+
+    fn closest_point(&self, point: &Vector3) -> Option<Vector3> {
+        self.objects
+            .iter()
+            .filter_map(|object| object.closest_point(point))
+            .min_by(|a, b| (*a - *point).length().total_cmp(&(*b - *point).length()))
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.bounds
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::{DiffuseLight, Lambertian};
+    use crate::shapes::quad::Quad;
+    use crate::shapes::sphere::Sphere;
+    use crate::shapes::volume::ConstantMedium;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_hit_and_bounds() {
+        let material = Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5)));
+        let mut list = HittableList::new();
+        assert!(list.is_empty() && list.bounding_box().is_none());
+        list.add(Box::new(Sphere::new(
+            Vector3::new(0.0, 0.0, -5.0),
+            1.0,
+            material.clone(),
+        )));
+        list.add(Box::new(Sphere::new(
+            Vector3::new(0.0, 0.0, -10.0),
+            2.0,
+            material.clone(),
+        )));
+        assert_eq!(list.len(), 2);
+
+        let bounds = list.bounding_box().unwrap();
+        assert_eq!(bounds.min, Vector3::new(-2.0, -2.0, -12.0));
+        assert_eq!(bounds.max, Vector3::new(2.0, 2.0, -4.0));
+
+        let ray = Ray::new(Vector3::new(0.0, 0.0, 0.0), Vector3::new(0.0, 0.0, -1.0));
+        let record = list.hit(&ray, (0.001, f64::INFINITY)).unwrap();
+        assert!((record.t - 4.0).abs() < 1e-9);
+        let far = list.hit(&ray, (7.0, f64::INFINITY)).unwrap();
+        assert!((far.t - 8.0).abs() < 1e-9);
+        let miss = Ray::new(Vector3::new(5.0, 0.0, 0.0), Vector3::new(0.0, 0.0, -1.0));
+        assert!(list.hit(&miss, (0.001, f64::INFINITY)).is_none());
+
+        // A medium in an empty boundary reports no extent, which makes the whole group boundless.
+        list.add(Box::new(ConstantMedium::new(
+            Box::new(HittableList::new()),
+            0.1,
+            Vector3::new(1.0, 1.0, 1.0),
+        )));
+        assert!(list.bounding_box().is_none());
+        list.add(Box::new(Sphere::new(
+            Vector3::new(0.0, 0.0, 0.0),
+            1.0,
+            material,
+        )));
+        assert!(list.bounding_box().is_none());
+    }
+
+    #[test]
+    fn test_samples_emissive_objects() {
+        let mut list = HittableList::new();
+        list.add(Box::new(Sphere::new(
+            Vector3::new(0.0, 0.0, -3.0),
+            1.0,
+            Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5))),
+        )));
+        list.add(Box::new(Quad::new(
+            Vector3::new(-1.0, 2.0, -1.0),
+            Vector3::new(2.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, 2.0),
+            Arc::new(DiffuseLight::new(Vector3::new(1.0, 1.0, 1.0))),
+        )));
+        assert!(list.is_emissive());
+
+        let origin = Vector3::new(0.0, 0.0, 0.0);
+        for _ in 0..100 {
+            let direction = list.random(&origin);
+            assert!(direction.y > 0.0);
+            assert!(list.pdf_value(&origin, &direction) > 0.0);
+        }
+        assert_eq!(list.pdf_value(&origin, &Vector3::new(0.0, 0.0, -1.0)), 0.0);
+    }
+}
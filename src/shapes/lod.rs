@@ -0,0 +1,269 @@
+use crate::aabb::Aabb;
+use crate::hit::{HitRecord, Hittable};
+use crate::material_table::MaterialTable;
+use crate::ray::Ray;
+use crate::shapes::mesh::TriangleMesh;
+use crate::statistics::SceneStatistics;
+use crate::vector3::Vector3;
+
+/// Represents an object with several levels of detail, choosing a representation for every ray
+/// from the distance between the origin of the ray and the object, so distant objects are traced
+/// through cheap proxy geometry.
+pub struct Lod {
+    /// The point from which distances to the object are measured.
+    center: Vector3,
+    /// The representations of the object, ordered from the most to the least detailed, each with
+    /// the largest distance at which it is used.
+    levels: Vec<(f64, Box<dyn Hittable>)>,
+    /// The distance from their origin within which rays leaving the object ignore it. A ray
+    /// scattered from one representation is traced against the most detailed one, and would
+    /// otherwise hit it right where it starts wherever the two do not line up.
+    tolerance: f64,
+}
+
+impl Lod {
+    /// Creates a new `Lod` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `center` - The point from which distances to the object are measured.
+    /// * `levels` - The representations of the object, each with the largest distance at which it
+    ///   is used. The least detailed representation is also used beyond its distance.
+    ///
+    /// # Returns
+    ///
+    /// A new `Lod` instance.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `levels` is empty.
+    pub fn new(center: Vector3, mut levels: Vec<(f64, Box<dyn Hittable>)>) -> Lod {
+        assert!(!levels.is_empty(), "a Lod needs at least one level");
+        levels.sort_by(|(d1, _), (d2, _)| d1.total_cmp(d2));
+        Lod {
+            center,
+            levels,
+            tolerance: 0.0,
+        }
+    }
+
+    /// Creates a new `Lod` from a mesh and coarser copies of it made by simplifying it, measuring
+    /// distances from the center of its bounding box.
+    ///
+    /// # Arguments
+    ///
+    /// * `mesh` - The most detailed representation of the object.
+    /// * `levels` - The coarser representations, each as the distance beyond which it is used and
+    ///   the number of triangles the mesh is reduced to.
+    ///
+    /// # Returns
+    ///
+    /// A new `Lod` instance, using the mesh itself up to the smallest distance of `levels`. Rays
+    /// leaving the object ignore it for as far as the simplified copies stray from the mesh.
+    pub fn from_mesh(mesh: TriangleMesh, levels: &[(f64, usize)]) -> Lod {
+        let bounds = mesh.bounding_box().expect("a mesh is bounded");
+        let center = 0.5 * (bounds.min + bounds.max);
+
+        let mut levels = levels.to_vec();
+        levels.sort_by(|(d1, _), (d2, _)| d1.total_cmp(d2));
+        let mut representations: Vec<(f64, Box<dyn Hittable>)> = Vec::new();
+        let mut max_distance = levels
+            .first()
+            .map_or(f64::INFINITY, |&(distance, _)| distance);
+        let coarser: Vec<TriangleMesh> = levels
+            .iter()
+            .map(|&(_, triangles)| mesh.simplify(triangles))
+            .collect();
+        let tolerance = coarser
+            .iter()
+            .map(|simplified| mesh.deviation(simplified))
+            .fold(0.0, f64::max);
+        representations.push((max_distance, Box::new(mesh)));
+        for (index, simplified) in coarser.into_iter().enumerate() {
+            max_distance = levels
+                .get(index + 1)
+                .map_or(f64::INFINITY, |&(distance, _)| distance);
+            representations.push((max_distance, Box::new(simplified)));
+        }
+        Lod {
+            tolerance,
+            ..Lod::new(center, representations)
+        }
+    }
+
+    /// Selects the representation used for rays leaving the given point.
+    ///
+    /// # Arguments
+    ///
+    /// * `origin` - The origin of the ray.
+    ///
+    /// # Returns
+    ///
+    /// The selected representation of the object.
+    fn level(&self, origin: &Vector3) -> &dyn Hittable {
+        let distance = (*origin - self.center).length();
+        let (_, object) = self
+            .levels
+            .iter()
+            .find(|(max_distance, _)| distance <= *max_distance)
+            .unwrap_or_else(|| self.levels.last().unwrap());
+        object.as_ref()
+    }
+}
+
+impl Hittable for Lod {
+    /// Checks if a ray hits the representation of the object selected for the ray. Rays scattered
+    /// from inside the bounds of the object skip it within the tolerance of its representations.
+    ///
+    /// # Arguments
+    ///
+    /// * `ray` - The ray to test for intersection.
+    /// * `interval` - The range of distances to consider for intersections.
+    ///
+    /// # Returns
+    ///
+    /// An `Option` containing the `HitRecord` if an intersection is found, or `None` if no intersection is found.
+    fn hit(&self, ray: &Ray, interval: (f64, f64)) -> Option<HitRecord<'_>> {
+        let leaving = !ray.primary
+            && self.tolerance > 0.0
+            && self.bounding_box().is_some_and(|bounds| {
+                let (min, max) = (bounds.min, bounds.max);
+                let p = ray.origin;
+                (min.x..=max.x).contains(&p.x)
+                    && (min.y..=max.y).contains(&p.y)
+                    && (min.z..=max.z).contains(&p.z)
+            });
+        let interval = if leaving {
+            (interval.0.max(self.tolerance), interval.1)
+        } else {
+            interval
+        };
+        self.level(&ray.origin).hit(ray, interval)
+    }
+
+    fn is_emissive(&self) -> bool {
+        self.levels[0].1.is_emissive()
+    }
+
+    fn power(&self) -> Vector3 {
+        self.levels[0].1.power()
+    }
+
+    fn pdf_value(&self, origin: &Vector3, direction: &Vector3) -> f64 {
+        self.level(origin).pdf_value(origin, direction)
+    }
+
+    fn random(&self, origin: &Vector3) -> Vector3 {
+        self.level(origin).random(origin)
+    }
+
+    fn collect_materials(&self, table: &mut MaterialTable) {
+        for (_, level) in &self.levels {
+            level.collect_materials(table);
+        }
+    }
+
+    /// Counts the primitives of every level of detail, since all of them are kept in memory.
+    ///
+    /// # Arguments
+    ///
+    /// * `statistics` - The statistics to add the object to.
+    fn collect_statistics(&self, statistics: &mut SceneStatistics) {
+        statistics.add_geometry(std::mem::size_of_val(self));
+        for (_, level) in &self.levels {
+            level.collect_statistics(statistics);
+        }
+    }
+
+    fn closest_point(&self, point: &Vector3) -> Option<Vector3> {
+        self.levels[0].1.closest_point(point)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let mut levels = self.levels.iter().map(|(_, level)| level.bounding_box());
+        let first = levels.next()??;
+        levels.try_fold(first, |bounds, level| Some(bounds.merge(&level?)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Lambertian;
+    use crate::shapes::sphere::Sphere;
+    use crate::shapes::sweep::lathe;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_lod_selects_level_by_distance() {
+        let material = Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5)));
+        let center = Vector3::new(0.0, 0.0, 0.0);
+        let lod = Lod::new(
+            center,
+            vec![
+                (
+                    f64::INFINITY,
+                    Box::new(Sphere::new(center, 0.5, material.clone())),
+                ),
+                (10.0, Box::new(Sphere::new(center, 1.0, material))),
+            ],
+        );
+
+        let near = Ray::new(Vector3::new(0.0, 0.0, 5.0), Vector3::new(0.0, 0.0, -1.0));
+        let far = Ray::new(Vector3::new(0.0, 0.0, 50.0), Vector3::new(0.0, 0.0, -1.0));
+        let near_hit = lod.hit(&near, (0.001, f64::INFINITY)).unwrap();
+        let far_hit = lod.hit(&far, (0.001, f64::INFINITY)).unwrap();
+
+        assert!((near_hit.t - 4.0).abs() < 1e-9);
+        assert!((far_hit.t - 49.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_lod_from_mesh() {
+        // A lathed sphere of radius 1 around its center at height 1.
+        let profile: Vec<(f64, f64)> = (0..=32)
+            .map(|i| {
+                let angle = std::f64::consts::PI * i as f64 / 32.0;
+                (angle.sin(), 1.0 - angle.cos())
+            })
+            .collect();
+        let material = Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5)));
+        let mesh = lathe(&profile, false, 64, material);
+        let full = mesh.triangle_count();
+        let lod = Lod::from_mesh(mesh, &[(40.0, 200), (10.0, 1000)]);
+
+        let counts: Vec<usize> = lod
+            .levels
+            .iter()
+            .map(|(_, level)| {
+                let mut statistics = SceneStatistics::default();
+                level.collect_statistics(&mut statistics);
+                statistics.geometry_bytes
+            })
+            .collect();
+        assert_eq!(lod.levels.len(), 3);
+        assert_eq!(lod.levels[0].0, 10.0);
+        assert_eq!(lod.levels[1].0, 40.0);
+        assert_eq!(lod.levels[2].0, f64::INFINITY);
+        assert!(
+            counts[0] > counts[1] && counts[1] > counts[2],
+            "{:?}",
+            counts
+        );
+        assert!(full > 1000);
+
+        // Every level still closes around the center, seen from near and far.
+        for origin in [Vector3::new(0.0, 1.0, 5.0), Vector3::new(0.0, 1.0, 100.0)] {
+            let ray = Ray::new(origin, Vector3::new(0.0, 0.0, -1.0));
+            let record = lod.hit(&ray, (0.001, f64::INFINITY)).unwrap();
+            assert!((record.poz.z - 1.0).abs() < 0.1, "{:?}", record.poz);
+        }
+
+        // A ray scattered outwards from the coarsest level starts inside the most detailed one,
+        // which it leaves without hitting.
+        let far = Ray::new(Vector3::new(0.0, 1.0, 100.0), Vector3::new(0.0, 0.0, -1.0));
+        let record = lod.hit(&far, (0.001, f64::INFINITY)).unwrap();
+        let scattered = Ray::new(record.poz, record.normal);
+        assert!(lod.hit(&scattered, (0.001, f64::INFINITY)).is_none());
+    }
+}
@@ -0,0 +1,1079 @@
+use crate::aabb::Aabb;
+use crate::hit::{HitRecord, Hittable};
+use crate::material::Material;
+use crate::material_table::MaterialTable;
+use crate::ray::Ray;
+use crate::statistics::SceneStatistics;
+use crate::vector3::Vector3;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BinaryHeap, HashMap};
+use std::sync::Arc;
+
+/// The largest number of triangles in a leaf of the hierarchy of a mesh.
+const LEAF_SIZE: usize = 4;
+
+/// The largest depth of the hierarchy of a mesh, bounding the stack used to traverse it.
+const MAX_DEPTH: usize = 64;
+
+/// The factor by which the cost of a refitted hierarchy may grow over the cost it had when it was
+/// built, before it is rebuilt around the moved triangles.
+const REBUILD_COST_RATIO: f64 = 1.5;
+
+/// Represents a node of the bounding volume hierarchy of a mesh.
+struct MeshNode {
+    /// The box enclosing the triangles of the node.
+    bounds: Aabb,
+    /// The index of the first triangle of a leaf, or of the second child of an inner node, whose
+    /// first child follows it.
+    index: usize,
+    /// The number of triangles of a leaf, or zero for an inner node.
+    count: usize,
+}
+
+/// The weight of the planes holding the boundary of an open mesh in place while it is simplified,
+/// relative to the planes of its triangles.
+const BOUNDARY_WEIGHT: f64 = 100.0;
+
+/// Represents the quadric error of a vertex, the weighted sum of the squared distances from a
+/// point to a set of planes, stored as the upper triangle of a symmetric 4 by 4 matrix.
+#[derive(Clone, Copy, Default)]
+struct Quadric([f64; 10]);
+
+impl Quadric {
+    /// Creates the quadric of a single plane.
+    ///
+    /// # Arguments
+    ///
+    /// * `normal` - The unit normal of the plane.
+    /// * `point` - A point of the plane.
+    /// * `weight` - The factor scaling the squared distance to the plane.
+    ///
+    /// # Returns
+    ///
+    /// A new `Quadric` instance.
+    fn plane(normal: Vector3, point: Vector3, weight: f64) -> Quadric {
+        let (a, b, c) = (normal.x, normal.y, normal.z);
+        let d = -normal.dot(&point);
+        Quadric(
+            [
+                a * a,
+                a * b,
+                a * c,
+                a * d,
+                b * b,
+                b * c,
+                b * d,
+                c * c,
+                c * d,
+                d * d,
+            ]
+            .map(|x| x * weight),
+        )
+    }
+
+    /// Returns the sum of two quadrics, measuring the distances to the planes of both.
+    fn merge(&self, other: &Quadric) -> Quadric {
+        Quadric(std::array::from_fn(|i| self.0[i] + other.0[i]))
+    }
+
+    /// Returns the weighted sum of the squared distances from a point to the planes.
+    fn error(&self, point: Vector3) -> f64 {
+        let [aa, ab, ac, ad, bb, bc, bd, cc, cd, dd] = self.0;
+        let (x, y, z) = (point.x, point.y, point.z);
+        aa * x * x
+            + bb * y * y
+            + cc * z * z
+            + 2.0 * (ab * x * y + ac * x * z + bc * y * z)
+            + 2.0 * (ad * x + bd * y + cd * z)
+            + dd
+    }
+}
+
+/// Represents a candidate step of the simplification of a mesh, moving a vertex onto a neighbour.
+/// Candidates are ordered so the cheapest one comes first out of a `BinaryHeap`.
+struct Collapse {
+    /// The error added by the step.
+    cost: f64,
+    /// The vertex removed by the step.
+    from: usize,
+    /// The vertex kept by the step.
+    to: usize,
+    /// The versions of the two vertices when the cost was computed, which no longer match once
+    /// either of them has moved.
+    versions: (usize, usize),
+}
+
+impl PartialEq for Collapse {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Collapse {}
+
+impl PartialOrd for Collapse {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Collapse {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.cost.total_cmp(&self.cost)
+    }
+}
+
+/// Represents a mesh of triangles sharing a material, with normals and texture coordinates
+/// interpolated across every triangle. Its triangles are held in a bounding volume hierarchy, so
+/// rays only test the few triangles near them.
+///
+/// Meshes are not sampled as lights, so emissive meshes only light the scene when scattered rays
+/// happen to hit them.
+pub struct TriangleMesh {
+    /// The positions of the vertices.
+    positions: Vec<Vector3>,
+    /// The unit normals of the vertices, on the side of the front faces.
+    normals: Vec<Vector3>,
+    /// The texture coordinates of the vertices.
+    uvs: Vec<(f64, f64)>,
+    /// The triangles, as indices into the vertices, ordered by the leaves of the hierarchy.
+    triangles: Vec<[usize; 3]>,
+    /// The nodes of the hierarchy, the root first.
+    nodes: Vec<MeshNode>,
+    /// The cost of the hierarchy when it was last built.
+    built_cost: f64,
+    /// The material of the mesh.
+    material: Arc<dyn Material>,
+}
+
+impl TriangleMesh {
+    /// Creates a new `TriangleMesh`.
+    ///
+    /// # Arguments
+    ///
+    /// * `positions` - The positions of the vertices.
+    /// * `normals` - The normals of the vertices. The front face of a triangle is the side its
+    ///   normals point to.
+    /// * `uvs` - The texture coordinates of the vertices.
+    /// * `triangles` - The triangles, as indices into the vertices, at least one.
+    /// * `material` - The material of the mesh.
+    ///
+    /// # Returns
+    ///
+    /// A new `TriangleMesh` instance.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are no triangles, if the vertices do not all have a normal and texture
+    /// coordinates, or if a triangle refers to a missing vertex.
+    pub fn new(
+        positions: Vec<Vector3>,
+        normals: Vec<Vector3>,
+        uvs: Vec<(f64, f64)>,
+        triangles: Vec<[usize; 3]>,
+        material: Arc<dyn Material>,
+    ) -> TriangleMesh {
+        assert!(!triangles.is_empty(), "a mesh needs at least one triangle");
+        assert!(
+            normals.len() == positions.len() && uvs.len() == positions.len(),
+            "every vertex of a mesh needs a normal and texture coordinates"
+        );
+        assert!(
+            triangles.iter().flatten().all(|&i| i < positions.len()),
+            "a triangle refers to a missing vertex"
+        );
+
+        let mut mesh = TriangleMesh {
+            normals: normals.iter().map(|normal| normal.normalize()).collect(),
+            positions,
+            uvs,
+            triangles,
+            nodes: Vec::new(),
+            built_cost: 0.0,
+            material,
+        };
+        mesh.rebuild();
+        mesh
+    }
+
+    /// Moves the vertices of the mesh, such as into the next frame of an animation, keeping its
+    /// triangles and texture coordinates.
+    ///
+    /// The hierarchy is refitted by growing and shrinking the boxes of its nodes around the moved
+    /// triangles, which is much cheaper than building it again. Once the triangles have moved so
+    /// far that the boxes overlap too much, tracked by the cost of the hierarchy, it is rebuilt.
+    ///
+    /// # Arguments
+    ///
+    /// * `positions` - The new positions of the vertices.
+    /// * `normals` - The new normals of the vertices.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the hierarchy was rebuilt, or `false` if refitting it was enough.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of positions or normals differs from the number of vertices.
+    pub fn refit(&mut self, positions: Vec<Vector3>, normals: Vec<Vector3>) -> bool {
+        assert!(
+            positions.len() == self.positions.len() && normals.len() == self.positions.len(),
+            "a refitted mesh needs a position and a normal for every vertex"
+        );
+        let _span = tracing::debug_span!("refit_bvh", triangles = self.triangles.len()).entered();
+        self.positions = positions;
+        self.normals = normals.iter().map(|normal| normal.normalize()).collect();
+
+        // Children follow their parent, so walking the nodes backwards updates them first.
+        for index in (0..self.nodes.len()).rev() {
+            let node = &self.nodes[index];
+            let bounds = if node.count > 0 {
+                self.triangles[node.index..node.index + node.count]
+                    .iter()
+                    .map(|triangle| self.triangle_bounds(triangle))
+                    .reduce(|a, b| a.merge(&b))
+                    .expect("a leaf holds at least one triangle")
+            } else {
+                self.nodes[index + 1]
+                    .bounds
+                    .merge(&self.nodes[node.index].bounds)
+            };
+            self.nodes[index].bounds = bounds;
+        }
+
+        if self.cost() > REBUILD_COST_RATIO * self.built_cost {
+            self.rebuild();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Builds the hierarchy of the mesh from scratch and records its cost.
+    fn rebuild(&mut self) {
+        let count = self.triangles.len();
+        let _span = tracing::debug_span!("build_bvh", triangles = count).entered();
+        self.nodes.clear();
+        self.build(0, count, 0);
+        self.built_cost = self.cost();
+    }
+
+    /// Estimates the cost of tracing a ray through the hierarchy with the surface area
+    /// heuristic: the number of boxes and triangles a random ray hitting the mesh is expected to
+    /// test, each counted with the chance that the ray crosses the box holding it.
+    ///
+    /// # Returns
+    ///
+    /// The expected number of tests, which does not change when the whole mesh is scaled.
+    fn cost(&self) -> f64 {
+        let root = self.nodes[0].bounds.surface_area();
+        self.nodes
+            .iter()
+            .map(|node| node.bounds.surface_area() * node.count.max(1) as f64)
+            .sum::<f64>()
+            / root
+    }
+
+    /// Returns the box enclosing a triangle.
+    ///
+    /// # Arguments
+    ///
+    /// * `triangle` - The indices of the vertices of the triangle.
+    fn triangle_bounds(&self, triangle: &[usize; 3]) -> Aabb {
+        Aabb::from_points(&triangle.map(|i| self.positions[i]))
+    }
+
+    /// Builds the node of the hierarchy holding a range of triangles, splitting it in half along
+    /// the longest axis of the box of their centers until few triangles are left.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The index of the first triangle of the range.
+    /// * `end` - The index past the last triangle of the range.
+    /// * `depth` - The depth of the node in the hierarchy.
+    fn build(&mut self, start: usize, end: usize, depth: usize) {
+        let bounds = self.triangles[start..end]
+            .iter()
+            .map(|triangle| self.triangle_bounds(triangle))
+            .reduce(|a, b| a.merge(&b))
+            .expect("a node holds at least one triangle");
+        let node = self.nodes.len();
+        self.nodes.push(MeshNode {
+            bounds,
+            index: start,
+            count: end - start,
+        });
+        if end - start <= LEAF_SIZE || depth + 1 >= MAX_DEPTH {
+            return;
+        }
+
+        let center = |positions: &[Vector3], triangle: &[usize; 3]| {
+            positions[triangle[0]] + positions[triangle[1]] + positions[triangle[2]]
+        };
+        let centers: Vec<Vector3> = self.triangles[start..end]
+            .iter()
+            .map(|triangle| center(&self.positions, triangle))
+            .collect();
+        let extent = Aabb::from_points(&centers);
+        let size = extent.max - extent.min;
+        let axis = |point: Vector3| {
+            if size.x >= size.y && size.x >= size.z {
+                point.x
+            } else if size.y >= size.z {
+                point.y
+            } else {
+                point.z
+            }
+        };
+        let positions = &self.positions;
+        self.triangles[start..end]
+            .sort_by(|a, b| axis(center(positions, a)).total_cmp(&axis(center(positions, b))));
+
+        let middle = (start + end) / 2;
+        self.nodes[node].count = 0;
+        self.build(start, middle, depth + 1);
+        self.nodes[node].index = self.nodes.len();
+        self.build(middle, end, depth + 1);
+    }
+
+    /// Checks if a ray hits a triangle within a given interval, with the Möller-Trumbore
+    /// algorithm.
+    ///
+    /// # Arguments
+    ///
+    /// * `triangle` - The indices of the vertices of the triangle.
+    /// * `ray` - The ray to test for intersection.
+    /// * `interval` - The range of distances to consider for intersections.
+    ///
+    /// # Returns
+    ///
+    /// The distance along the ray and the barycentric coordinates of the second and third
+    /// vertices at the hit, or `None` if the ray misses the triangle.
+    fn hit_triangle(
+        &self,
+        triangle: &[usize; 3],
+        ray: &Ray,
+        interval: (f64, f64),
+    ) -> Option<(f64, f64, f64)> {
+        let [a, b, c] = triangle.map(|i| self.positions[i]);
+        let (edge1, edge2) = (b - a, c - a);
+        let p = ray.direction.cross(&edge2);
+        let determinant = edge1.dot(&p);
+        if determinant.abs() < 1e-12 {
+            return None;
+        }
+        let inverse = 1.0 / determinant;
+        let s = ray.origin - a;
+        let beta = s.dot(&p) * inverse;
+        if !(0.0..=1.0).contains(&beta) {
+            return None;
+        }
+        let q = s.cross(&edge1);
+        let gamma = ray.direction.dot(&q) * inverse;
+        if gamma < 0.0 || beta + gamma > 1.0 {
+            return None;
+        }
+        let t = edge2.dot(&q) * inverse;
+        if t < interval.0 || t > interval.1 {
+            return None;
+        }
+        Some((t, beta, gamma))
+    }
+
+    /// Returns the number of triangles of the mesh.
+    pub fn triangle_count(&self) -> usize {
+        self.triangles.len()
+    }
+
+    /// Creates a coarser copy of the mesh with at most a given number of triangles, for previews
+    /// and distant levels of detail.
+    ///
+    /// Edges are collapsed one at a time, the cheapest first, measuring the cost of moving a
+    /// vertex with the quadric error metric of Garland and Heckbert: the squared distances to the
+    /// planes of the triangles that met at the removed vertices. Vertices only move onto
+    /// neighbours, so the kept vertices keep their normals and texture coordinates. Vertices
+    /// sharing a position are moved together, so seams of normals or texture coordinates do not
+    /// open, and the boundary of an open mesh is held in place by planes along its edges.
+    /// Collapses that would fold a triangle over or pinch the surface are skipped, so the
+    /// simplification may stop above the target.
+    ///
+    /// # Arguments
+    ///
+    /// * `target_triangles` - The number of triangles to reduce the mesh to.
+    ///
+    /// # Returns
+    ///
+    /// The simplified mesh, sharing the material of the mesh.
+    pub fn simplify(&self, target_triangles: usize) -> TriangleMesh {
+        // The vertices sharing a position are welded into a point, and the vertices of every
+        // point are kept to choose from when a triangle corner moves there.
+        let (points, welded) = shared_positions(&self.positions);
+        let mut siblings = vec![Vec::new(); points.len()];
+        for (vertex, &point) in welded.iter().enumerate() {
+            siblings[point].push(vertex);
+        }
+
+        let mut triangles = self.triangles.clone();
+        let mut alive = vec![true; triangles.len()];
+        let mut faces = vec![Vec::new(); points.len()];
+        let mut quadrics = vec![Quadric::default(); points.len()];
+        // The edges are kept in order, so the quadrics are summed and the collapses queued the
+        // same way on every run, and so is the simplified mesh.
+        let mut edges: BTreeMap<(usize, usize), Vec<Vector3>> = BTreeMap::new();
+        for (index, triangle) in triangles.iter().enumerate() {
+            let [a, b, c] = triangle.map(|vertex| welded[vertex]);
+            let normal = (points[b] - points[a]).cross(&(points[c] - points[a]));
+            if a == b || b == c || c == a || normal.is_near_zero() {
+                alive[index] = false;
+                continue;
+            }
+            // Larger triangles weigh more, so the error does not depend on the tessellation.
+            let plane = Quadric::plane(normal.normalize(), points[a], normal.length() / 2.0);
+            for (p, q) in [(a, b), (b, c), (c, a)] {
+                quadrics[p] = quadrics[p].merge(&plane);
+                faces[p].push(index);
+                edges
+                    .entry((p.min(q), p.max(q)))
+                    .or_default()
+                    .push(normal.normalize());
+            }
+        }
+        for (&(p, q), normals) in &edges {
+            if let [normal] = normals[..] {
+                // A plane through the boundary edge, perpendicular to its triangle.
+                let edge = points[q] - points[p];
+                let plane = Quadric::plane(
+                    edge.cross(&normal).normalize(),
+                    points[p],
+                    BOUNDARY_WEIGHT * edge.dot(&edge),
+                );
+                quadrics[p] = quadrics[p].merge(&plane);
+                quadrics[q] = quadrics[q].merge(&plane);
+            }
+        }
+
+        let mut versions = vec![0; points.len()];
+        let mut heap = BinaryHeap::new();
+        let candidate = |quadrics: &[Quadric], versions: &[usize], p: usize, q: usize| {
+            let quadric = quadrics[p].merge(&quadrics[q]);
+            let (cost_pq, cost_qp) = (quadric.error(points[q]), quadric.error(points[p]));
+            let (from, to, cost) = if cost_pq <= cost_qp {
+                (p, q, cost_pq)
+            } else {
+                (q, p, cost_qp)
+            };
+            Collapse {
+                cost,
+                from,
+                to,
+                versions: (versions[from], versions[to]),
+            }
+        };
+        for &(p, q) in edges.keys() {
+            heap.push(candidate(&quadrics, &versions, p, q));
+        }
+
+        let neighbours =
+            |faces: &[usize], triangles: &[[usize; 3]], alive: &[bool], point: usize| {
+                let mut neighbours: Vec<usize> = faces
+                    .iter()
+                    .filter(|&&index| alive[index])
+                    .flat_map(|&index| triangles[index].map(|vertex| welded[vertex]))
+                    .filter(|&other| other != point)
+                    .collect();
+                neighbours.sort_unstable();
+                neighbours.dedup();
+                neighbours
+            };
+
+        let mut remaining = alive.iter().filter(|&&alive| alive).count();
+        while remaining > target_triangles {
+            let Some(Collapse {
+                from,
+                to,
+                versions: (from_version, to_version),
+                ..
+            }) = heap.pop()
+            else {
+                break;
+            };
+            if versions[from] != from_version || versions[to] != to_version {
+                continue;
+            }
+
+            let (shared, moved): (Vec<usize>, Vec<usize>) = faces[from]
+                .iter()
+                .copied()
+                .filter(|&index| alive[index])
+                .partition(|&index| triangles[index].iter().any(|&vertex| welded[vertex] == to));
+            if shared.is_empty() || shared.len() >= remaining {
+                continue;
+            }
+
+            // Both ends of the edge may only share the neighbours across its triangles, or the
+            // collapse would pinch the surface into a non-manifold edge.
+            let from_neighbours = neighbours(&faces[from], &triangles, &alive, from);
+            let to_neighbours = neighbours(&faces[to], &triangles, &alive, to);
+            let common = from_neighbours
+                .iter()
+                .filter(|point| to_neighbours.binary_search(point).is_ok())
+                .count();
+            if common != shared.len() {
+                continue;
+            }
+
+            let normal = |corners: [usize; 3]| {
+                let [a, b, c] = corners.map(|point| points[point]);
+                (b - a).cross(&(c - a))
+            };
+            let folds = moved.iter().any(|&index| {
+                let corners = triangles[index].map(|vertex| welded[vertex]);
+                let old = normal(corners);
+                let new = normal(corners.map(|point| if point == from { to } else { point }));
+                new.is_near_zero() || new.dot(&old) <= 0.0
+            });
+            if folds {
+                continue;
+            }
+
+            for &index in &shared {
+                alive[index] = false;
+            }
+            remaining -= shared.len();
+            for &index in &moved {
+                for corner in triangles[index].iter_mut() {
+                    if welded[*corner] != from {
+                        continue;
+                    }
+                    // The vertex of the kept point closest in normal and texture coordinates
+                    // stands in for the moved one, keeping each side of a seam on its side.
+                    let (normal, uv) = (self.normals[*corner], self.uvs[*corner]);
+                    let distance = |vertex: usize| {
+                        let (u, v) = self.uvs[vertex];
+                        1.0 - normal.dot(&self.normals[vertex])
+                            + (u - uv.0).powi(2)
+                            + (v - uv.1).powi(2)
+                    };
+                    *corner = *siblings[to]
+                        .iter()
+                        .min_by(|&&a, &&b| distance(a).total_cmp(&distance(b)))
+                        .expect("every point has a vertex");
+                }
+            }
+            let kept: Vec<usize> = faces[to]
+                .iter()
+                .chain(&moved)
+                .copied()
+                .filter(|&index| alive[index])
+                .collect();
+            faces[to] = kept;
+            faces[from].clear();
+            quadrics[to] = quadrics[to].merge(&quadrics[from]);
+            versions[from] += 1;
+            versions[to] += 1;
+            for other in neighbours(&faces[to], &triangles, &alive, to) {
+                heap.push(candidate(&quadrics, &versions, to, other));
+            }
+        }
+
+        // The vertices still in use are gathered in their original order.
+        let mut indices = vec![usize::MAX; self.positions.len()];
+        let mut positions = Vec::new();
+        let mut normals = Vec::new();
+        let mut uvs = Vec::new();
+        let triangles: Vec<[usize; 3]> = triangles
+            .iter()
+            .zip(&alive)
+            .filter(|(_, &alive)| alive)
+            .map(|(triangle, _)| {
+                triangle.map(|vertex| {
+                    if indices[vertex] == usize::MAX {
+                        indices[vertex] = positions.len();
+                        positions.push(self.positions[vertex]);
+                        normals.push(self.normals[vertex]);
+                        uvs.push(self.uvs[vertex]);
+                    }
+                    indices[vertex]
+                })
+            })
+            .collect();
+        TriangleMesh::new(positions, normals, uvs, triangles, self.material.clone())
+    }
+
+    /// Returns how far the mesh strays from another mesh approximating it, such as a simplified
+    /// copy, as the largest distance from a vertex of the mesh to the other mesh.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The approximating mesh.
+    ///
+    /// # Returns
+    ///
+    /// The largest distance.
+    pub fn deviation(&self, other: &TriangleMesh) -> f64 {
+        self.positions
+            .iter()
+            .filter_map(|position| {
+                other
+                    .closest_point(position)
+                    .map(|closest| (closest - *position).length())
+            })
+            .fold(0.0, f64::max)
+    }
+}
+
+/// Groups the vertices of a mesh sharing the exact same position into points, so vertices split
+/// along seams of normals or texture coordinates are known to be the same point of the surface.
+///
+/// # Arguments
+///
+/// * `positions` - The positions of the vertices.
+///
+/// # Returns
+///
+/// The positions of the points, and the index of the point of every vertex.
+pub fn shared_positions(positions: &[Vector3]) -> (Vec<Vector3>, Vec<usize>) {
+    let mut points: Vec<Vector3> = Vec::new();
+    let mut ids = HashMap::new();
+    let indices = positions
+        .iter()
+        .map(|p| {
+            *ids.entry([p.x.to_bits(), p.y.to_bits(), p.z.to_bits()])
+                .or_insert_with(|| {
+                    points.push(*p);
+                    points.len() - 1
+                })
+        })
+        .collect();
+    (points, indices)
+}
+
+/// Finds the point of a triangle closest to a given point, from the region of the triangle the
+/// point projects to, as described by Ericson in "Real-Time Collision Detection".
+///
+/// # Arguments
+///
+/// * `point` - The query point.
+/// * `corners` - The vertices of the triangle.
+///
+/// # Returns
+///
+/// The closest point of the triangle.
+fn closest_point_on_triangle(point: &Vector3, [a, b, c]: [Vector3; 3]) -> Vector3 {
+    let (ab, ac, ap) = (b - a, c - a, *point - a);
+    let (d1, d2) = (ab.dot(&ap), ac.dot(&ap));
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return a;
+    }
+    let bp = *point - b;
+    let (d3, d4) = (ab.dot(&bp), ac.dot(&bp));
+    if d3 >= 0.0 && d4 <= d3 {
+        return b;
+    }
+    let cp = *point - c;
+    let (d5, d6) = (ab.dot(&cp), ac.dot(&cp));
+    if d6 >= 0.0 && d5 <= d6 {
+        return c;
+    }
+
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        return a + d1 / (d1 - d3) * ab;
+    }
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        return a + d2 / (d2 - d6) * ac;
+    }
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && d4 - d3 >= 0.0 && d5 - d6 >= 0.0 {
+        return b + (d4 - d3) / ((d4 - d3) + (d5 - d6)) * (c - b);
+    }
+
+    let denominator = 1.0 / (va + vb + vc);
+    a + vb * denominator * ab + vc * denominator * ac
+}
+
+impl Hittable for TriangleMesh {
+    /// Checks if a ray hits the mesh within a given interval, by walking down the nodes of the
+    /// hierarchy whose boxes the ray passes through.
+    ///
+    /// # Arguments
+    ///
+    /// * `ray` - The ray to test for intersection.
+    /// * `interval` - The range of distances to consider for intersections.
+    ///
+    /// # Returns
+    ///
+    /// An `Option` containing the closest `HitRecord` if an intersection is found, or `None` if no intersection is found.
+    fn hit(&self, ray: &Ray, interval: (f64, f64)) -> Option<HitRecord<'_>> {
+        let mut closest: Option<(usize, f64, f64, f64)> = None;
+        let mut far = interval.1;
+        let mut stack = [0; MAX_DEPTH + 1];
+        let mut size = 1;
+        while size > 0 {
+            size -= 1;
+            let index = stack[size];
+            let node = &self.nodes[index];
+            if !node.bounds.hit(ray, (interval.0, far)) {
+                continue;
+            }
+            if node.count == 0 {
+                // The first child follows its parent and is visited first.
+                stack[size] = node.index;
+                stack[size + 1] = index + 1;
+                size += 2;
+                continue;
+            }
+            for index in node.index..node.index + node.count {
+                if let Some((t, beta, gamma)) =
+                    self.hit_triangle(&self.triangles[index], ray, (interval.0, far))
+                {
+                    far = t;
+                    closest = Some((index, t, beta, gamma));
+                }
+            }
+        }
+
+        let (index, t, beta, gamma) = closest?;
+        let [a, b, c] = self.triangles[index];
+        let alpha = 1.0 - beta - gamma;
+        let u = alpha * self.uvs[a].0 + beta * self.uvs[b].0 + gamma * self.uvs[c].0;
+        let v = alpha * self.uvs[a].1 + beta * self.uvs[b].1 + gamma * self.uvs[c].1;
+        let mut record = HitRecord::new(t, ray.point_at(t), self.material.as_ref(), u, v);
+
+        // The side of the triangle is found from its plane, on the side of its vertex normals, and
+        // the interpolated normal is turned to that side.
+        let shading = (alpha * self.normals[a] + beta * self.normals[b] + gamma * self.normals[c])
+            .normalize();
+        let mut geometric =
+            (self.positions[b] - self.positions[a]).cross(&(self.positions[c] - self.positions[a]));
+        if geometric.dot(&shading) < 0.0 {
+            geometric = -geometric;
+        }
+        record.set_face_normal(ray, &geometric);
+        record.normal = if record.front_face { shading } else { -shading };
+
+        // The flat triangles cut under the smooth surface their normals describe, and would shadow
+        // the rays leaving it near the terminator. Following Hanika, the hit point is lifted onto
+        // the planes through each vertex across its normal, where it lies below them, and the
+        // lifted points are interpolated into the point the leaving rays start from.
+        if record.front_face {
+            let lift = |vertex: usize| {
+                let normal = self.normals[vertex];
+                let below = (record.poz - self.positions[vertex]).dot(&normal).min(0.0);
+                record.poz - below * normal
+            };
+            let lifted = alpha * lift(a) + beta * lift(b) + gamma * lift(c);
+            record.shading_offset = lifted - record.poz;
+        }
+
+        // The edges of the triangle written in texture coordinates give the directions in which
+        // the coordinates increase, unless the triangle has no area in texture space.
+        let (edge1, edge2) = (
+            self.positions[b] - self.positions[a],
+            self.positions[c] - self.positions[a],
+        );
+        let (du1, dv1) = (self.uvs[b].0 - self.uvs[a].0, self.uvs[b].1 - self.uvs[a].1);
+        let (du2, dv2) = (self.uvs[c].0 - self.uvs[a].0, self.uvs[c].1 - self.uvs[a].1);
+        let determinant = du1 * dv2 - du2 * dv1;
+        if determinant.abs() > 1e-12 {
+            record.set_tangents(
+                (dv2 * edge1 - dv1 * edge2) / determinant,
+                (du1 * edge2 - du2 * edge1) / determinant,
+            );
+        }
+        Some(record)
+    }
+
+    fn collect_materials(&self, table: &mut MaterialTable) {
+        table.add(&self.material);
+    }
+
+    fn collect_statistics(&self, statistics: &mut SceneStatistics) {
+        let bytes = std::mem::size_of_val(self)
+            + self.positions.len() * 2 * std::mem::size_of::<Vector3>()
+            + self.uvs.len() * std::mem::size_of::<(f64, f64)>()
+            + self.triangles.len() * std::mem::size_of::<[usize; 3]>()
+            + self.nodes.len() * std::mem::size_of::<MeshNode>();
+        statistics.add_primitive("TriangleMesh", bytes);
+        statistics.bvh_nodes += self.nodes.len();
+        self.material.collect_statistics(statistics);
+    }
+
+    /// Finds the point of the mesh closest to a given point, skipping the nodes of the hierarchy
+    /// whose boxes are further away than the closest point found so far.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The query point.
+    ///
+    /// # Returns
+    ///
+    /// The closest point of the mesh.
+    fn closest_point(&self, point: &Vector3) -> Option<Vector3> {
+        let box_distance_squared = |bounds: &Aabb| {
+            let outside = |value: f64, min: f64, max: f64| (min - value).max(value - max).max(0.0);
+            let offset = Vector3::new(
+                outside(point.x, bounds.min.x, bounds.max.x),
+                outside(point.y, bounds.min.y, bounds.max.y),
+                outside(point.z, bounds.min.z, bounds.max.z),
+            );
+            offset.dot(&offset)
+        };
+
+        let mut closest = None;
+        let mut best = f64::INFINITY;
+        let mut stack = [0; MAX_DEPTH + 1];
+        let mut size = 1;
+        while size > 0 {
+            size -= 1;
+            let index = stack[size];
+            let node = &self.nodes[index];
+            if box_distance_squared(&node.bounds) >= best {
+                continue;
+            }
+            if node.count == 0 {
+                stack[size] = node.index;
+                stack[size + 1] = index + 1;
+                size += 2;
+                continue;
+            }
+            for triangle in &self.triangles[node.index..node.index + node.count] {
+                let candidate =
+                    closest_point_on_triangle(point, triangle.map(|i| self.positions[i]));
+                let offset = candidate - *point;
+                let distance_squared = offset.dot(&offset);
+                if distance_squared < best {
+                    best = distance_squared;
+                    closest = Some(candidate);
+                }
+            }
+        }
+        closest
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.nodes[0].bounds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Lambertian;
+    use crate::shapes::sweep::lathe;
+
+    fn material() -> Arc<dyn Material> {
+        Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5)))
+    }
+
+    /// A grid of `n` by `n` unit squares in the plane z = 0, each split into two triangles.
+    fn grid(n: usize) -> TriangleMesh {
+        let mut positions = Vec::new();
+        let mut uvs = Vec::new();
+        for j in 0..=n {
+            for i in 0..=n {
+                positions.push(Vector3::new(i as f64, j as f64, 0.0));
+                uvs.push((i as f64 / n as f64, j as f64 / n as f64));
+            }
+        }
+        let mut triangles = Vec::new();
+        for j in 0..n {
+            for i in 0..n {
+                let corner = j * (n + 1) + i;
+                triangles.push([corner, corner + 1, corner + n + 2]);
+                triangles.push([corner, corner + n + 2, corner + n + 1]);
+            }
+        }
+        let normals = vec![Vector3::new(0.0, 0.0, 1.0); positions.len()];
+        TriangleMesh::new(positions, normals, uvs, triangles, material())
+    }
+
+    #[test]
+    fn test_hit() {
+        let mesh = grid(16);
+        assert!(mesh.nodes.len() > 1);
+        let bounds = mesh.bounding_box().unwrap();
+        assert!((bounds.max - Vector3::new(16.0, 16.0, bounds.max.z)).length() < 1e-9);
+
+        for (x, y) in [(0.25, 0.75), (8.5, 3.2), (15.9, 15.1)] {
+            let ray = Ray::new(Vector3::new(x, y, 5.0), Vector3::new(0.0, 0.0, -1.0));
+            let record = mesh.hit(&ray, (0.001, f64::INFINITY)).unwrap();
+            assert!((record.t - 5.0).abs() < 1e-9);
+            assert!((record.u - x / 16.0).abs() < 1e-9);
+            assert!((record.v - y / 16.0).abs() < 1e-9);
+            assert!(record.front_face);
+            assert!((record.normal - Vector3::new(0.0, 0.0, 1.0)).length() < 1e-9);
+            assert!((record.tangent - Vector3::new(16.0, 0.0, 0.0)).length() < 1e-9);
+            assert!((record.bitangent - Vector3::new(0.0, 16.0, 0.0)).length() < 1e-9);
+
+            let below = Ray::new(Vector3::new(x, y, -5.0), Vector3::new(0.0, 0.0, 1.0));
+            let record = mesh.hit(&below, (0.001, f64::INFINITY)).unwrap();
+            assert!(!record.front_face);
+            assert!((record.normal - Vector3::new(0.0, 0.0, -1.0)).length() < 1e-9);
+        }
+        let miss = Ray::new(Vector3::new(16.5, 8.0, 5.0), Vector3::new(0.0, 0.0, -1.0));
+        assert!(mesh.hit(&miss, (0.001, f64::INFINITY)).is_none());
+    }
+
+    #[test]
+    fn test_refit() {
+        let mut mesh = grid(16);
+        let original = grid(16);
+        let (positions, normals) = (mesh.positions.clone(), mesh.normals.clone());
+
+        // Tilting the grid only refits the hierarchy.
+        let height = |p: &Vector3| 0.3 * p.x + 0.1 * p.y;
+        let tilted: Vec<Vector3> = positions
+            .iter()
+            .map(|p| *p + Vector3::new(0.0, 0.0, height(p)))
+            .collect();
+        assert!(!mesh.refit(tilted, normals.clone()));
+        for (x, y) in [(0.25, 0.75), (8.5, 3.2), (15.9, 15.1)] {
+            let ray = Ray::new(Vector3::new(x, y, 20.0), Vector3::new(0.0, 0.0, -1.0));
+            let record = mesh.hit(&ray, (0.001, f64::INFINITY)).unwrap();
+            assert!((record.t - (20.0 - height(&Vector3::new(x, y, 0.0)))).abs() < 1e-9);
+        }
+        let bounds = mesh.bounding_box().unwrap();
+        assert!((bounds.max.z - 6.4).abs() < 1e-9);
+
+        // Scattering the vertices makes the boxes overlap, so the hierarchy is rebuilt.
+        let scattered: Vec<Vector3> = (0..positions.len())
+            .map(|i| positions[(i * 97) % positions.len()])
+            .collect();
+        assert!(mesh.refit(scattered, normals.clone()));
+        assert!(mesh.cost() <= REBUILD_COST_RATIO * mesh.built_cost);
+
+        // Moving the vertices back gives the same surface as the original mesh.
+        mesh.refit(positions, normals);
+        for (x, y) in [(2.5, 9.75), (12.1, 0.4)] {
+            let ray = Ray::new(Vector3::new(x, y, 5.0), Vector3::new(0.0, 0.0, -1.0));
+            let record = mesh.hit(&ray, (0.001, f64::INFINITY)).unwrap();
+            let expected = original.hit(&ray, (0.001, f64::INFINITY)).unwrap();
+            assert!((record.t - expected.t).abs() < 1e-9);
+            assert!((record.u - expected.u).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_simplify() {
+        // A flat grid keeps its outline and stays in its plane.
+        let mesh = grid(16).simplify(32);
+        assert!(mesh.triangle_count() <= 32);
+        let bounds = mesh.bounding_box().unwrap();
+        assert!((bounds.min - Vector3::new(0.0, 0.0, bounds.min.z)).length() < 1e-9);
+        assert!((bounds.max - Vector3::new(16.0, 16.0, bounds.max.z)).length() < 1e-9);
+        for _ in 0..200 {
+            let (x, y) = (
+                0.01 + 15.98 * fastrand::f64(),
+                0.01 + 15.98 * fastrand::f64(),
+            );
+            let ray = Ray::new(Vector3::new(x, y, 5.0), Vector3::new(0.0, 0.0, -1.0));
+            let record = mesh.hit(&ray, (0.001, f64::INFINITY)).unwrap();
+            assert!((record.t - 5.0).abs() < 1e-9);
+            assert!((record.u - x / 16.0).abs() < 1e-9 && (record.v - y / 16.0).abs() < 1e-9);
+        }
+
+        // A lathed sphere, with a seam of texture coordinates and poles of many vertices, stays
+        // closed and close to round.
+        let profile: Vec<(f64, f64)> = (0..=32)
+            .map(|i| {
+                let angle = std::f64::consts::PI * i as f64 / 32.0;
+                (angle.sin(), -angle.cos())
+            })
+            .collect();
+        let sphere = lathe(&profile, false, 64, material());
+        let simplified = sphere.simplify(sphere.triangle_count() / 10);
+        assert!(simplified.triangle_count() <= sphere.triangle_count() / 10);
+        for _ in 0..1000 {
+            let direction = Vector3::random_unit_vector();
+            let ray = Ray::new(Vector3::new(0.0, 0.0, 0.0), direction);
+            let record = simplified.hit(&ray, (0.0, f64::INFINITY)).unwrap();
+            assert!(record.t > 0.85 && record.t <= 1.0 + 1e-9, "{}", record.t);
+            assert!(!record.front_face);
+        }
+        assert!(sphere.deviation(&simplified) < 0.15);
+        assert!(sphere.deviation(&sphere) < 1e-9);
+
+        // The same mesh is simplified the same way every time.
+        let again = sphere.simplify(sphere.triangle_count() / 10);
+        assert_eq!(again.triangles, simplified.triangles);
+        assert_eq!(again.positions, simplified.positions);
+    }
+
+    #[test]
+    fn test_smooth_terminator() {
+        // A low-poly sphere lit from the side. Its flat triangles cut under the smooth surface, so
+        // near the terminator, rays towards the light leaving from the hit point enter the mesh
+        // again and leave bands of shadow along the triangles on the lit side.
+        let profile: Vec<(f64, f64)> = (0..=6)
+            .map(|i| {
+                let angle = std::f64::consts::PI * i as f64 / 6.0;
+                (angle.sin(), -angle.cos())
+            })
+            .collect();
+        let sphere = lathe(&profile, false, 8, material());
+        let light = Vector3::new(1.0, 0.3, 0.2).normalize();
+
+        let (mut lit, mut banded, mut shadowed) = (0, 0, 0);
+        for _ in 0..4000 {
+            let origin = 5.0 * Vector3::random_unit_vector();
+            let ray = Ray::new(origin, -origin);
+            let Some(record) = sphere.hit(&ray, (0.001, f64::INFINITY)) else {
+                continue;
+            };
+            if record.normal.dot(&light) < 0.1 {
+                continue;
+            }
+            lit += 1;
+            let from_hit = Ray::new(record.poz, light);
+            if sphere.hit(&from_hit, (1e-6, f64::INFINITY)).is_some() {
+                banded += 1;
+            }
+            let from_shading = Ray::new(record.ray_origin(&light), light);
+            if sphere.hit(&from_shading, (1e-6, f64::INFINITY)).is_some() {
+                shadowed += 1;
+            }
+        }
+        assert!(lit > 1000);
+        assert!(banded > 0);
+        assert_eq!(shadowed, 0);
+    }
+
+    #[test]
+    fn test_closest_point() {
+        let mesh = grid(16);
+        let closest = mesh.closest_point(&Vector3::new(3.3, 7.6, 2.0)).unwrap();
+        assert!((closest - Vector3::new(3.3, 7.6, 0.0)).length() < 1e-9);
+        let closest = mesh.closest_point(&Vector3::new(-1.0, 20.0, -1.0)).unwrap();
+        assert!((closest - Vector3::new(0.0, 16.0, 0.0)).length() < 1e-9);
+        let closest = mesh.closest_point(&Vector3::new(18.0, 5.5, 1.0)).unwrap();
+        assert!((closest - Vector3::new(16.0, 5.5, 0.0)).length() < 1e-9);
+    }
+
+    #[test]
+    fn test_closest_triangle() {
+        // Two triangles facing each other, the ray crossing both.
+        let positions = vec![
+            Vector3::new(-1.0, -1.0, 0.0),
+            Vector3::new(1.0, -1.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            Vector3::new(-1.0, -1.0, 2.0),
+            Vector3::new(1.0, -1.0, 2.0),
+            Vector3::new(0.0, 1.0, 2.0),
+        ];
+        let normals = vec![Vector3::new(0.0, 0.0, 1.0); 6];
+        let mesh = TriangleMesh::new(
+            positions,
+            normals,
+            vec![(0.0, 0.0); 6],
+            vec![[0, 1, 2], [3, 4, 5]],
+            material(),
+        );
+        let ray = Ray::new(Vector3::new(0.0, 0.0, 5.0), Vector3::new(0.0, 0.0, -1.0));
+        let record = mesh.hit(&ray, (0.001, f64::INFINITY)).unwrap();
+        assert!((record.t - 3.0).abs() < 1e-9);
+        let record = mesh.hit(&ray, (3.5, f64::INFINITY)).unwrap();
+        assert!((record.t - 5.0).abs() < 1e-9);
+    }
+}
@@ -1,4 +1,22 @@
+pub mod bilinear_patch;
+pub mod billboard;
 pub mod box_quad;
+pub mod cleanup;
+pub mod cone;
+pub mod cylinder;
+pub mod fractal;
+pub mod hittable_list;
+pub mod lod;
+pub mod mesh;
+pub mod morph;
+pub mod on_hit;
+pub mod polygon;
 pub mod quad;
+pub mod sdf;
+pub mod section;
+pub mod skin;
 pub mod sphere;
+pub mod sweep;
+pub mod text;
+pub mod torus;
 pub mod volume;
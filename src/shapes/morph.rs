@@ -0,0 +1,664 @@
+use crate::material::Material;
+use crate::shapes::cleanup::MeshData;
+use crate::shapes::mesh::TriangleMesh;
+use crate::shapes::skin::{hermite, keyframes_around, Interpolation};
+use crate::vector3::Vector3;
+use gltf::animation::util::ReadOutputs;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Represents a shape a mesh can blend towards, as displacements of its vertices.
+#[derive(Debug, Clone)]
+pub struct MorphTarget {
+    /// The name of the target.
+    pub name: String,
+    /// The displacement of every vertex at full weight.
+    pub positions: Vec<Vector3>,
+    /// The change of the normal of every vertex at full weight, or empty if the normals do not
+    /// change.
+    pub normals: Vec<Vector3>,
+}
+
+impl MorphTarget {
+    /// Creates a `MorphTarget` blending a mesh towards another shape with the same triangles,
+    /// the changes of the normals being those of the smooth normals of the shape.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the target.
+    /// * `base` - The mesh with all weights at zero, with normals.
+    /// * `positions` - The positions of the vertices in the shape of the target.
+    ///
+    /// # Returns
+    ///
+    /// A new `MorphTarget` instance.
+    pub fn from_shape(name: &str, base: &MeshData, positions: Vec<Vector3>) -> MorphTarget {
+        let mut shape = MeshData::new(positions, Vec::new(), Vec::new(), base.triangles.clone());
+        shape.orient_normals();
+        MorphTarget {
+            name: name.to_string(),
+            positions: shape
+                .positions
+                .iter()
+                .zip(&base.positions)
+                .map(|(&target, &position)| target - position)
+                .collect(),
+            normals: shape
+                .normals
+                .iter()
+                .zip(&base.normals)
+                .map(|(&target, &normal)| target - normal)
+                .collect(),
+        }
+    }
+}
+
+/// Represents a named animation of the weights of the morph targets of a mesh.
+#[derive(Debug, Clone)]
+pub struct WeightAnimation {
+    /// The name of the animation.
+    pub name: String,
+    /// The times of the keyframes in seconds, increasing.
+    times: Vec<f64>,
+    /// The weights of every target at every keyframe, three sets per keyframe for cubic splines.
+    weights: Vec<Vec<f64>>,
+    /// How weights change between keyframes.
+    interpolation: Interpolation,
+}
+
+impl WeightAnimation {
+    /// Creates a new `WeightAnimation`.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the animation.
+    /// * `times` - The times of the keyframes in seconds, increasing, at least one.
+    /// * `weights` - The weights of every target at every keyframe, with
+    ///   `[in tangents, weights, out tangents]` for every keyframe of a cubic spline.
+    /// * `interpolation` - How weights change between keyframes.
+    ///
+    /// # Returns
+    ///
+    /// A new `WeightAnimation` instance.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are no keyframes or the number of weight sets does not match them.
+    pub fn new(
+        name: &str,
+        times: Vec<f64>,
+        weights: Vec<Vec<f64>>,
+        interpolation: Interpolation,
+    ) -> WeightAnimation {
+        assert!(
+            !times.is_empty(),
+            "an animation needs at least one keyframe"
+        );
+        assert_eq!(
+            weights.len(),
+            times.len() * interpolation.values_per_keyframe(),
+            "every keyframe of an animation needs weights"
+        );
+        WeightAnimation {
+            name: name.to_string(),
+            times,
+            weights,
+            interpolation,
+        }
+    }
+
+    /// Returns the time of the last keyframe of the animation in seconds.
+    pub fn duration(&self) -> f64 {
+        self.times.last().copied().unwrap_or(0.0)
+    }
+
+    /// Returns the weights of the keyframe with an index, skipping the tangents of cubic splines.
+    fn keyframe(&self, keyframe: usize) -> &[f64] {
+        match self.interpolation {
+            Interpolation::CubicSpline => &self.weights[3 * keyframe + 1],
+            _ => &self.weights[keyframe],
+        }
+    }
+
+    /// Evaluates the weights at a time, holding the first and last weights outside of the
+    /// keyframes.
+    ///
+    /// # Arguments
+    ///
+    /// * `time` - The time in seconds.
+    ///
+    /// # Returns
+    ///
+    /// The weight of every target.
+    pub fn sample(&self, time: f64) -> Vec<f64> {
+        let (previous, next, s) = keyframes_around(&self.times, time);
+        let (a, b) = (self.keyframe(previous), self.keyframe(next));
+        if previous == next || self.interpolation == Interpolation::Step {
+            return a.to_vec();
+        }
+        let span = self.times[next] - self.times[previous];
+        (0..a.len().min(b.len()))
+            .map(|i| match self.interpolation {
+                Interpolation::CubicSpline => hermite(
+                    a[i],
+                    self.weights[3 * previous + 2][i],
+                    b[i],
+                    self.weights[3 * next][i],
+                    span,
+                    s,
+                ),
+                _ => a[i] + (b[i] - a[i]) * s,
+            })
+            .collect()
+    }
+}
+
+/// Represents a triangle mesh with morph targets, also called blend shapes: every target moves
+/// the vertices by its displacements scaled by its weight, so shapes such as facial expressions
+/// are animated by keyframing weights, without a skeleton. Every set of weights is turned into a
+/// new `TriangleMesh`, so animations are rendered one frame at a time.
+pub struct MorphMesh {
+    /// The mesh with all weights at zero.
+    mesh: MeshData,
+    /// The morph targets.
+    targets: Vec<MorphTarget>,
+    /// The weights used when no animation sets them.
+    weights: Vec<f64>,
+    /// The animations of the weights.
+    animations: Vec<WeightAnimation>,
+}
+
+impl MorphMesh {
+    /// Creates a new `MorphMesh` with all weights at zero. Missing normals are computed from the
+    /// triangles and missing texture coordinates are set to zero.
+    ///
+    /// # Arguments
+    ///
+    /// * `mesh` - The mesh with all weights at zero.
+    /// * `targets` - The morph targets.
+    ///
+    /// # Returns
+    ///
+    /// A new `MorphMesh` instance.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a target does not displace every vertex, or has normals for some vertices only.
+    pub fn new(mut mesh: MeshData, targets: Vec<MorphTarget>) -> MorphMesh {
+        let count = mesh.positions.len();
+        assert!(
+            targets.iter().all(|target| target.positions.len() == count
+                && (target.normals.is_empty() || target.normals.len() == count)),
+            "every vertex of a morph target needs a displacement"
+        );
+        if mesh.normals.len() != count {
+            mesh.normals.clear();
+            mesh.orient_normals();
+        }
+        if mesh.uvs.len() != count {
+            mesh.uvs = vec![(0.0, 0.0); count];
+        }
+        MorphMesh {
+            mesh,
+            weights: vec![0.0; targets.len()],
+            targets,
+            animations: Vec::new(),
+        }
+    }
+
+    /// Sets the weights used when no animation sets them.
+    ///
+    /// # Arguments
+    ///
+    /// * `weights` - The weight of every target, missing ones being zero.
+    ///
+    /// # Returns
+    ///
+    /// The updated `MorphMesh` instance.
+    pub fn with_weights(mut self, weights: &[f64]) -> MorphMesh {
+        for (weight, &value) in self.weights.iter_mut().zip(weights) {
+            *weight = value;
+        }
+        self
+    }
+
+    /// Adds an animation of the weights.
+    ///
+    /// # Arguments
+    ///
+    /// * `animation` - The animation.
+    ///
+    /// # Returns
+    ///
+    /// The updated `MorphMesh` instance.
+    pub fn with_animation(mut self, animation: WeightAnimation) -> MorphMesh {
+        self.animations.push(animation);
+        self
+    }
+
+    /// Loads the first mesh with morph targets of a glTF file, with its default weights and every
+    /// animation of the weights of its node. All triangle primitives of the mesh are merged, and
+    /// the mesh is kept in its own space, without the transforms of its node. The names of the
+    /// targets are not stored by glTF itself, so they are numbered.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the `.gltf` or `.glb` file.
+    ///
+    /// # Returns
+    ///
+    /// The mesh, or an error message if the file cannot be read or has no mesh with morph
+    /// targets.
+    pub fn load(path: &Path) -> Result<MorphMesh, String> {
+        let (document, buffers, _) = gltf::import(path)
+            .map_err(|e| format!("Failed to load glTF {}: {}", path.display(), e))?;
+        let buffer = |buffer: gltf::Buffer| buffers.get(buffer.index()).map(|data| &data[..]);
+        let (node, mesh) = document
+            .nodes()
+            .find_map(|node| {
+                let mesh = node.mesh()?;
+                mesh.primitives()
+                    .any(|primitive| primitive.morph_targets().len() > 0)
+                    .then_some((node, mesh))
+            })
+            .ok_or_else(|| format!("{}: no mesh with morph targets", path.display()))?;
+        let target_count = mesh
+            .primitives()
+            .map(|primitive| primitive.morph_targets().len())
+            .max()
+            .unwrap_or(0);
+
+        let vector = |v: [f32; 3]| Vector3::new(v[0] as f64, v[1] as f64, v[2] as f64);
+        let mut data = MeshData::default();
+        let mut targets: Vec<MorphTarget> = (0..target_count)
+            .map(|i| MorphTarget {
+                name: format!("target {}", i),
+                positions: Vec::new(),
+                normals: Vec::new(),
+            })
+            .collect();
+        let mut has_normals = true;
+        let mut has_target_normals = vec![true; target_count];
+        for primitive in mesh.primitives() {
+            if primitive.mode() != gltf::mesh::Mode::Triangles {
+                continue;
+            }
+            let reader = primitive.reader(buffer);
+            let Some(positions) = reader.read_positions() else {
+                continue;
+            };
+            let offset = data.positions.len();
+            data.positions.extend(positions.map(vector));
+            let count = data.positions.len() - offset;
+            match reader.read_normals() {
+                Some(normals) => data.normals.extend(normals.map(vector)),
+                None => has_normals = false,
+            }
+            match reader.read_tex_coords(0) {
+                Some(uvs) => data
+                    .uvs
+                    .extend(uvs.into_f32().map(|uv| (uv[0] as f64, 1.0 - uv[1] as f64))),
+                None => data.uvs.extend(std::iter::repeat_n((0.0, 0.0), count)),
+            }
+            let mut morphs = reader.read_morph_targets();
+            for (i, target) in targets.iter_mut().enumerate() {
+                let (positions, normals) = match morphs.next() {
+                    Some((positions, normals, _)) => (positions, normals),
+                    None => (None, None),
+                };
+                match positions {
+                    Some(positions) => target.positions.extend(positions.map(vector)),
+                    None => target
+                        .positions
+                        .extend(std::iter::repeat_n(Vector3::default(), count)),
+                }
+                match normals {
+                    Some(normals) => target.normals.extend(normals.map(vector)),
+                    None => has_target_normals[i] = false,
+                }
+            }
+            let indices: Vec<usize> = match reader.read_indices() {
+                Some(indices) => indices.into_u32().map(|i| offset + i as usize).collect(),
+                None => (offset..offset + count).collect(),
+            };
+            data.triangles.extend(
+                indices
+                    .chunks_exact(3)
+                    .filter(|triangle| triangle.iter().all(|&i| i < offset + count))
+                    .map(|triangle| [triangle[0], triangle[1], triangle[2]]),
+            );
+        }
+        if data.triangles.is_empty() {
+            return Err(format!(
+                "{}: the morphed mesh has no triangles",
+                path.display()
+            ));
+        }
+        if !has_normals {
+            data.normals.clear();
+        }
+        for (target, has_normals) in targets.iter_mut().zip(has_target_normals) {
+            if !has_normals {
+                target.normals.clear();
+            }
+        }
+
+        let animations = document
+            .animations()
+            .filter_map(|animation| {
+                let channel = animation.channels().find(|channel| {
+                    channel.target().node().index() == node.index()
+                        && channel.target().property()
+                            == gltf::animation::Property::MorphTargetWeights
+                })?;
+                let reader = channel.reader(buffer);
+                let times: Vec<f64> = reader.read_inputs()?.map(|t| t as f64).collect();
+                let ReadOutputs::MorphTargetWeights(weights) = reader.read_outputs()? else {
+                    return None;
+                };
+                let weights: Vec<f64> = weights.into_f32().map(|w| w as f64).collect();
+                let weights: Vec<Vec<f64>> = weights
+                    .chunks_exact(target_count.max(1))
+                    .map(<[f64]>::to_vec)
+                    .collect();
+                let interpolation = Interpolation::from(channel.sampler().interpolation());
+                (!times.is_empty()
+                    && weights.len() == times.len() * interpolation.values_per_keyframe())
+                .then(|| {
+                    WeightAnimation::new(
+                        animation.name().unwrap_or_default(),
+                        times,
+                        weights,
+                        interpolation,
+                    )
+                })
+            })
+            .collect();
+
+        let default_weights: Vec<f64> = mesh
+            .weights()
+            .map(|weights| weights.iter().map(|&w| w as f64).collect())
+            .unwrap_or_default();
+        let mut morph = MorphMesh::new(data, targets).with_weights(&default_weights);
+        morph.animations = animations;
+        Ok(morph)
+    }
+
+    /// Returns the morph targets.
+    pub fn targets(&self) -> &[MorphTarget] {
+        &self.targets
+    }
+
+    /// Returns the weights used when no animation sets them.
+    pub fn weights(&self) -> &[f64] {
+        &self.weights
+    }
+
+    /// Returns the animations of the weights.
+    pub fn animations(&self) -> &[WeightAnimation] {
+        &self.animations
+    }
+
+    /// Blends the morph targets into the mesh, adding the displacements of every target scaled
+    /// by its weight.
+    ///
+    /// # Arguments
+    ///
+    /// * `weights` - The weight of every target, missing ones being zero.
+    /// * `material` - The material of the mesh.
+    ///
+    /// # Returns
+    ///
+    /// The morphed mesh, with its hierarchy built around the moved triangles.
+    pub fn deform(&self, weights: &[f64], material: Arc<dyn Material>) -> TriangleMesh {
+        let (positions, normals) = self.morphed_vertices(weights);
+        TriangleMesh::new(
+            positions,
+            normals,
+            self.mesh.uvs.clone(),
+            self.mesh.triangles.clone(),
+            material,
+        )
+    }
+
+    /// Moves the vertices of a mesh morphed from this one to the weights at a time of an
+    /// animation, refitting its hierarchy rather than building a new mesh, which keeps the
+    /// preparation of every frame of an animation cheap.
+    ///
+    /// # Arguments
+    ///
+    /// * `animation` - The animation.
+    /// * `time` - The time in seconds.
+    /// * `mesh` - A mesh built by `deform` or `frame` from this morph mesh.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the hierarchy of the mesh had to be rebuilt.
+    pub fn refit_frame(
+        &self,
+        animation: &WeightAnimation,
+        time: f64,
+        mesh: &mut TriangleMesh,
+    ) -> bool {
+        let (positions, normals) = self.morphed_vertices(&animation.sample(time));
+        mesh.refit(positions, normals)
+    }
+
+    /// Computes the positions and normals of the vertices with the targets blended in.
+    ///
+    /// # Arguments
+    ///
+    /// * `weights` - The weight of every target, missing ones being zero.
+    ///
+    /// # Returns
+    ///
+    /// The moved positions and normals.
+    fn morphed_vertices(&self, weights: &[f64]) -> (Vec<Vector3>, Vec<Vector3>) {
+        let mut positions = self.mesh.positions.clone();
+        let mut normals = self.mesh.normals.clone();
+        for (target, &weight) in self.targets.iter().zip(weights) {
+            if weight == 0.0 {
+                continue;
+            }
+            for (position, displacement) in positions.iter_mut().zip(&target.positions) {
+                *position += weight * *displacement;
+            }
+            for (normal, change) in normals.iter_mut().zip(&target.normals) {
+                *normal += weight * *change;
+            }
+        }
+        (positions, normals)
+    }
+
+    /// Blends the morph targets into the mesh with the weights at a time of an animation.
+    ///
+    /// # Arguments
+    ///
+    /// * `animation` - The animation.
+    /// * `time` - The time in seconds.
+    /// * `material` - The material of the mesh.
+    ///
+    /// # Returns
+    ///
+    /// The morphed mesh.
+    pub fn frame(
+        &self,
+        animation: &WeightAnimation,
+        time: f64,
+        material: Arc<dyn Material>,
+    ) -> TriangleMesh {
+        self.deform(&animation.sample(time), material)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hit::Hittable;
+    use crate::material::Lambertian;
+
+    fn material() -> Arc<dyn Material> {
+        Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5)))
+    }
+
+    fn triangle() -> MeshData {
+        MeshData::new(
+            vec![
+                Vector3::new(0.0, 0.0, 0.0),
+                Vector3::new(1.0, 0.0, 0.0),
+                Vector3::new(0.0, 2.0, 0.0),
+            ],
+            Vec::new(),
+            Vec::new(),
+            vec![[0, 1, 2]],
+        )
+    }
+
+    #[test]
+    fn test_weight_animation() {
+        let weights = vec![vec![0.0, 1.0], vec![1.0, 0.0]];
+        let linear = WeightAnimation::new(
+            "linear",
+            vec![1.0, 3.0],
+            weights.clone(),
+            Interpolation::Linear,
+        );
+        assert_eq!(linear.sample(2.0), vec![0.5, 0.5]);
+        assert_eq!(linear.sample(0.0), weights[0]);
+        assert_eq!(linear.sample(9.0), weights[1]);
+        assert_eq!(linear.duration(), 3.0);
+        let step = WeightAnimation::new("step", vec![1.0, 3.0], weights, Interpolation::Step);
+        assert_eq!(step.sample(2.9), vec![0.0, 1.0]);
+
+        // Flat tangents ease in and out, passing halfway at the middle.
+        let spline = WeightAnimation::new(
+            "spline",
+            vec![0.0, 1.0],
+            vec![
+                vec![0.0],
+                vec![0.0],
+                vec![0.0],
+                vec![0.0],
+                vec![1.0],
+                vec![0.0],
+            ],
+            Interpolation::CubicSpline,
+        );
+        assert!((spline.sample(0.5)[0] - 0.5).abs() < 1e-12);
+        assert!(spline.sample(0.1)[0] < 0.1);
+    }
+
+    #[test]
+    fn test_deform() {
+        let base = triangle();
+        let lifted = MorphTarget {
+            name: "lift".to_string(),
+            positions: vec![
+                Vector3::default(),
+                Vector3::default(),
+                Vector3::new(0.0, 1.0, 0.0),
+            ],
+            normals: Vec::new(),
+        };
+        let tilted = MorphTarget::from_shape(
+            "tilt",
+            &MeshData::new(
+                base.positions.clone(),
+                vec![Vector3::new(0.0, 0.0, 1.0); 3],
+                Vec::new(),
+                base.triangles.clone(),
+            ),
+            vec![
+                Vector3::new(0.0, 0.0, 0.0),
+                Vector3::new(1.0, 0.0, 0.0),
+                Vector3::new(0.0, 2.0, 2.0),
+            ],
+        );
+        // The tilted triangle faces away from the Y-axis as well as towards Z.
+        let expected = Vector3::new(0.0, -1.0, 1.0).normalize() - Vector3::new(0.0, 0.0, 1.0);
+        assert!((tilted.normals[0] - expected).length() < 1e-9);
+
+        let morph = MorphMesh::new(base, vec![lifted, tilted]).with_weights(&[0.5]);
+        assert_eq!(morph.weights(), &[0.5, 0.0]);
+        let mesh = morph.deform(morph.weights(), material());
+        let bounds = mesh.bounding_box().unwrap();
+        assert!((bounds.max.y - 2.5).abs() < 1e-9);
+
+        let both = morph.deform(&[1.0, 1.0], material());
+        let bounds = both.bounding_box().unwrap();
+        assert!((bounds.max.y - 3.0).abs() < 1e-9 && (bounds.max.z - 2.0).abs() < 1e-3);
+    }
+
+    /// Writes a glTF file with the triangle of `triangle`, a target lifting its top vertex by one
+    /// unit at a default weight of a quarter, and an animation raising the weight to one in a
+    /// second.
+    fn write_gltf(directory: &Path) -> std::path::PathBuf {
+        let mut bin: Vec<u8> = Vec::new();
+        let floats: [f32; 22] = [
+            0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 2.0, 0.0, // positions
+            0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, // displacements
+            0.0, 1.0, // times
+            0.0, 1.0, // weights
+        ];
+        floats
+            .iter()
+            .for_each(|value| bin.extend(value.to_le_bytes()));
+        std::fs::write(directory.join("lift.bin"), &bin).unwrap();
+
+        let json = r#"{
+            "asset": {"version": "2.0"},
+            "scene": 0,
+            "scenes": [{"nodes": [0]}],
+            "nodes": [{"name": "face", "mesh": 0}],
+            "meshes": [{
+                "primitives": [{"attributes": {"POSITION": 0}, "targets": [{"POSITION": 1}]}],
+                "weights": [0.25]
+            }],
+            "animations": [{
+                "name": "lift",
+                "channels": [{"sampler": 0, "target": {"node": 0, "path": "weights"}}],
+                "samplers": [{"input": 2, "output": 3}]
+            }],
+            "buffers": [{"uri": "lift.bin", "byteLength": 88}],
+            "bufferViews": [
+                {"buffer": 0, "byteOffset": 0, "byteLength": 36},
+                {"buffer": 0, "byteOffset": 36, "byteLength": 36},
+                {"buffer": 0, "byteOffset": 72, "byteLength": 8},
+                {"buffer": 0, "byteOffset": 80, "byteLength": 8}
+            ],
+            "accessors": [
+                {"bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3", "min": [0, 0, 0], "max": [1, 2, 0]},
+                {"bufferView": 1, "componentType": 5126, "count": 3, "type": "VEC3", "min": [0, 0, 0], "max": [0, 1, 0]},
+                {"bufferView": 2, "componentType": 5126, "count": 2, "type": "SCALAR", "min": [0], "max": [1]},
+                {"bufferView": 3, "componentType": 5126, "count": 2, "type": "SCALAR"}
+            ]
+        }"#;
+        let path = directory.join("lift.gltf");
+        std::fs::write(&path, json).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_load_gltf() {
+        let directory = std::env::temp_dir().join(format!("morph_test_{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let loaded = MorphMesh::load(&write_gltf(&directory));
+        std::fs::remove_dir_all(&directory).unwrap();
+        let morph = loaded.unwrap();
+
+        assert_eq!(morph.targets().len(), 1);
+        assert_eq!(morph.weights(), &[0.25]);
+        let animation = &morph.animations()[0];
+        assert_eq!(
+            (animation.name.as_str(), animation.duration()),
+            ("lift", 1.0)
+        );
+
+        let rest = morph.deform(morph.weights(), material());
+        assert!((rest.bounding_box().unwrap().max.y - 2.25).abs() < 1e-6);
+        let halfway = morph.frame(animation, 0.5, material());
+        assert!((halfway.bounding_box().unwrap().max.y - 2.5).abs() < 1e-6);
+
+        assert!(MorphMesh::load(&directory.join("missing.gltf")).is_err());
+    }
+}
@@ -0,0 +1,148 @@
+use crate::aabb::Aabb;
+use crate::hit::{HitRecord, Hittable};
+use crate::material_table::MaterialTable;
+use crate::ray::Ray;
+use crate::statistics::SceneStatistics;
+use crate::vector3::Vector3;
+use std::sync::Arc;
+
+/// The largest number of vetoed hits skipped along a ray.
+const MAX_VETOED_HITS: usize = 32;
+
+/// A callback run on every hit of an object before it is shaded. It may change the record of the
+/// hit, such as its texture coordinates, normal or tint, and returns `false` to veto the hit so
+/// the ray goes on through the surface.
+pub type HitCallback = dyn Fn(&Ray, &mut HitRecord) -> bool + Send + Sync;
+
+/// Represents an object whose hits go through a callback, an extension point for procedural
+/// clipping, custom texture coordinates and other changes to hits without changing the code
+/// intersecting the object.
+pub struct OnHit {
+    /// The object whose hits go through the callback.
+    object: Box<dyn Hittable>,
+    /// The callback run on every hit of the object.
+    callback: Arc<HitCallback>,
+}
+
+impl OnHit {
+    /// Creates a new `OnHit`.
+    ///
+    /// # Arguments
+    ///
+    /// * `object` - The object whose hits go through the callback.
+    /// * `callback` - The callback run on every hit of the object, which may be shared by several
+    ///   objects.
+    ///
+    /// # Returns
+    ///
+    /// A new `OnHit` instance.
+    pub fn new(object: Box<dyn Hittable>, callback: Arc<HitCallback>) -> OnHit {
+        OnHit { object, callback }
+    }
+}
+
+impl Hittable for OnHit {
+    /// Checks if a ray hits the object within a given interval, at a hit the callback keeps.
+    /// Hits the callback vetoes are skipped, and the object is searched again beyond them.
+    ///
+    /// # Arguments
+    ///
+    /// * `ray` - The ray to test for intersection.
+    /// * `interval` - The range of distances to consider for intersections.
+    ///
+    /// # Returns
+    ///
+    /// An `Option` containing the closest `HitRecord` kept by the callback, as changed by it, or
+    /// `None` if no intersection is found or kept.
+    fn hit(&self, ray: &Ray, interval: (f64, f64)) -> Option<HitRecord<'_>> {
+        let mut start = interval.0;
+        for _ in 0..MAX_VETOED_HITS {
+            let mut record = self.object.hit(ray, (start, interval.1))?;
+            if (self.callback)(ray, &mut record) {
+                return Some(record);
+            }
+            start = record.t + 1e-6;
+        }
+        None
+    }
+
+    fn is_emissive(&self) -> bool {
+        self.object.is_emissive()
+    }
+
+    fn power(&self) -> Vector3 {
+        self.object.power()
+    }
+
+    /// Returns the probability density of the object, as if no hit were vetoed.
+    fn pdf_value(&self, origin: &Vector3, direction: &Vector3) -> f64 {
+        self.object.pdf_value(origin, direction)
+    }
+
+    fn random(&self, origin: &Vector3) -> Vector3 {
+        self.object.random(origin)
+    }
+
+    fn closest_point(&self, point: &Vector3) -> Option<Vector3> {
+        self.object.closest_point(point)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.object.bounding_box()
+    }
+
+    fn collect_materials(&self, table: &mut MaterialTable) {
+        self.object.collect_materials(table);
+    }
+
+    fn collect_statistics(&self, statistics: &mut SceneStatistics) {
+        statistics.add_geometry(std::mem::size_of_val(self));
+        self.object.collect_statistics(statistics);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Lambertian;
+    use crate::shapes::sphere::Sphere;
+
+    fn unit_sphere() -> Box<dyn Hittable> {
+        Box::new(Sphere::new(
+            Vector3::new(0.0, 0.0, 0.0),
+            1.0,
+            Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5))),
+        ))
+    }
+
+    #[test]
+    fn test_vetoed_hits_are_skipped() {
+        // The half of the sphere facing the viewer is clipped away.
+        let clipped = OnHit::new(
+            unit_sphere(),
+            Arc::new(|_: &Ray, record: &mut HitRecord| record.poz.z <= 0.0),
+        );
+        let ray = Ray::new(Vector3::new(0.0, 0.0, 5.0), Vector3::new(0.0, 0.0, -1.0));
+        let record = clipped.hit(&ray, (0.001, f64::INFINITY)).unwrap();
+        assert!((record.t - 6.0).abs() < 1e-9);
+        assert!(!record.front_face);
+
+        let hidden = OnHit::new(unit_sphere(), Arc::new(|_: &Ray, _: &mut HitRecord| false));
+        assert!(hidden.hit(&ray, (0.001, f64::INFINITY)).is_none());
+    }
+
+    #[test]
+    fn test_callback_changes_the_record() {
+        let planar = OnHit::new(
+            unit_sphere(),
+            Arc::new(|_: &Ray, record: &mut HitRecord| {
+                record.u = 0.5 + 0.5 * record.object_poz.x;
+                record.v = 0.5 + 0.5 * record.object_poz.y;
+                true
+            }),
+        );
+        let ray = Ray::new(Vector3::new(0.5, 0.0, 5.0), Vector3::new(0.0, 0.0, -1.0));
+        let record = planar.hit(&ray, (0.001, f64::INFINITY)).unwrap();
+        assert!((record.u - 0.75).abs() < 1e-9 && (record.v - 0.5).abs() < 1e-9);
+    }
+}
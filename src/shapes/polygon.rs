@@ -0,0 +1,547 @@
+use crate::aabb::Aabb;
+use crate::hit::{HitRecord, Hittable};
+use crate::material::Material;
+use crate::material_table::MaterialTable;
+use crate::ray::Ray;
+use crate::statistics::SceneStatistics;
+use crate::vector3::Vector3;
+use std::sync::Arc;
+
+/// Represents a flat polygon with any number of vertices, convex or not, such as a floor plan or a
+/// sign. It is triangulated when it is built, and rays are tested against its triangles.
+pub struct Polygon {
+    /// The vertices of the polygon, in order around it.
+    vertices: Vec<Vector3>,
+    /// The vertices in the plane of the polygon, as coordinates along `x_axis` and `y_axis`.
+    points: Vec<(f64, f64)>,
+    /// The triangles covering the polygon, as indices into its vertices.
+    triangles: Vec<[usize; 3]>,
+    /// The running sum of the areas of the triangles, used to sample them by area.
+    cumulative_areas: Vec<f64>,
+    /// The unit normal of the polygon.
+    normal: Vector3,
+    /// The unit vector along the first edge, the u direction of the texture coordinates.
+    x_axis: Vector3,
+    /// The unit vector perpendicular to the first edge in the plane, the v direction of the
+    /// texture coordinates.
+    y_axis: Vector3,
+    /// The smallest coordinates of the points, mapped to the texture coordinates (0, 0).
+    min: (f64, f64),
+    /// The largest coordinates of the points, mapped to the texture coordinates (1, 1).
+    max: (f64, f64),
+    /// The material of the polygon.
+    material: Arc<dyn Material>,
+}
+
+impl Polygon {
+    /// Creates a new `Polygon` from its vertices, triangulated by ear clipping. The front face is
+    /// the side from which the vertices go around counterclockwise. Texture coordinates span the
+    /// rectangle enclosing the polygon, with u along its first edge.
+    ///
+    /// # Arguments
+    ///
+    /// * `vertices` - The vertices, in order around the polygon and all in one plane. The edges
+    ///   must not cross each other.
+    /// * `material` - The material of the polygon.
+    ///
+    /// # Returns
+    ///
+    /// A new `Polygon` instance.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are fewer than 3 vertices or they all lie on a line.
+    pub fn new(vertices: Vec<Vector3>, material: Arc<dyn Material>) -> Polygon {
+        assert!(vertices.len() >= 3, "a polygon needs at least 3 vertices");
+
+        // Newell's method gives the normal of the best fitting plane, oriented by the winding.
+        let mut normal = Vector3::new(0.0, 0.0, 0.0);
+        for (i, current) in vertices.iter().enumerate() {
+            let next = vertices[(i + 1) % vertices.len()];
+            normal.x += (current.y - next.y) * (current.z + next.z);
+            normal.y += (current.z - next.z) * (current.x + next.x);
+            normal.z += (current.x - next.x) * (current.y + next.y);
+        }
+        assert!(
+            !normal.is_near_zero(),
+            "the vertices of a polygon lie on a line"
+        );
+        let normal = normal.normalize();
+
+        let first_edge = vertices[1] - vertices[0];
+        let x_axis = (first_edge - first_edge.dot(&normal) * normal).normalize();
+        let y_axis = normal.cross(&x_axis);
+        let points: Vec<(f64, f64)> = vertices
+            .iter()
+            .map(|vertex| {
+                let offset = *vertex - vertices[0];
+                (offset.dot(&x_axis), offset.dot(&y_axis))
+            })
+            .collect();
+
+        let triangles = Self::triangulate(&points);
+        let mut total = 0.0;
+        let cumulative_areas = triangles
+            .iter()
+            .map(|&[a, b, c]| {
+                total += 0.5 * Self::signed_area(points[a], points[b], points[c]).abs();
+                total
+            })
+            .collect();
+
+        let min = points.iter().fold((f64::INFINITY, f64::INFINITY), |m, p| {
+            (m.0.min(p.0), m.1.min(p.1))
+        });
+        let max = points
+            .iter()
+            .fold((f64::NEG_INFINITY, f64::NEG_INFINITY), |m, p| {
+                (m.0.max(p.0), m.1.max(p.1))
+            });
+
+        Polygon {
+            vertices,
+            points,
+            triangles,
+            cumulative_areas,
+            normal,
+            x_axis,
+            y_axis,
+            min,
+            max,
+            material,
+        }
+    }
+
+    /// Returns twice the signed area of a triangle, positive if its vertices go around it
+    /// counterclockwise.
+    ///
+    /// # Arguments
+    ///
+    /// * `a` - The first vertex.
+    /// * `b` - The second vertex.
+    /// * `c` - The third vertex.
+    pub fn signed_area(a: (f64, f64), b: (f64, f64), c: (f64, f64)) -> f64 {
+        (b.0 - a.0) * (c.1 - a.1) - (b.1 - a.1) * (c.0 - a.0)
+    }
+
+    /// Checks if a point lies inside a counterclockwise triangle or on its edges.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The point to test.
+    /// * `a` - The first vertex of the triangle.
+    /// * `b` - The second vertex of the triangle.
+    /// * `c` - The third vertex of the triangle.
+    fn in_triangle(point: (f64, f64), a: (f64, f64), b: (f64, f64), c: (f64, f64)) -> bool {
+        Self::signed_area(a, b, point) >= 0.0
+            && Self::signed_area(b, c, point) >= 0.0
+            && Self::signed_area(c, a, point) >= 0.0
+    }
+
+    /// Splits a simple counterclockwise polygon into triangles by ear clipping: a convex corner
+    /// whose triangle holds no other vertex is cut off, until a single triangle is left.
+    ///
+    /// # Arguments
+    ///
+    /// * `points` - The vertices of the polygon in its plane.
+    ///
+    /// # Returns
+    ///
+    /// The triangles, as counterclockwise indices into the vertices.
+    pub fn triangulate(points: &[(f64, f64)]) -> Vec<[usize; 3]> {
+        let mut remaining: Vec<usize> = (0..points.len()).collect();
+        let mut triangles = Vec::with_capacity(points.len() - 2);
+
+        while remaining.len() > 3 {
+            let n = remaining.len();
+            let corner = |i: usize| {
+                (
+                    remaining[(i + n - 1) % n],
+                    remaining[i],
+                    remaining[(i + 1) % n],
+                )
+            };
+            let ear = (0..n).find(|&i| {
+                let (a, b, c) = corner(i);
+                Self::signed_area(points[a], points[b], points[c]) > 0.0
+                    && remaining.iter().all(|&other| {
+                        // Vertices repeated by the bridges to holes do not block the ear.
+                        [a, b, c]
+                            .iter()
+                            .any(|&corner| points[corner] == points[other])
+                            || !Self::in_triangle(points[other], points[a], points[b], points[c])
+                    })
+            });
+
+            match ear {
+                Some(i) => {
+                    let (a, b, c) = corner(i);
+                    triangles.push([a, b, c]);
+                    remaining.remove(i);
+                }
+                None => {
+                    // Only degenerate corners are left, such as vertices in the middle of an edge,
+                    // or the polygon is not simple. Flat corners are dropped, anything else is
+                    // covered by a fan.
+                    let flat = (0..n).find(|&i| {
+                        let (a, b, c) = corner(i);
+                        Self::signed_area(points[a], points[b], points[c]).abs() < 1e-12
+                    });
+                    match flat {
+                        Some(i) => {
+                            remaining.remove(i);
+                        }
+                        None => {
+                            for i in 1..n - 1 {
+                                triangles.push([remaining[0], remaining[i], remaining[i + 1]]);
+                            }
+                            return triangles;
+                        }
+                    }
+                }
+            }
+        }
+
+        triangles.push([remaining[0], remaining[1], remaining[2]]);
+        triangles
+    }
+
+    /// Joins holes to the outline of a polygon, so the polygon with holes can be triangulated as a
+    /// single polygon: every hole is cut open along a bridge from its rightmost vertex to a
+    /// vertex of the outline that it can see, the bridge being walked once in each direction.
+    ///
+    /// # Arguments
+    ///
+    /// * `outline` - The outline of the polygon, counterclockwise.
+    /// * `holes` - The holes of the polygon, clockwise, inside the outline and not touching it or
+    ///   each other.
+    ///
+    /// # Returns
+    ///
+    /// The vertices of the joined polygon, counterclockwise, as pairs of the index of their
+    /// contour, 0 for the outline and `i + 1` for the hole `i`, and their index in that contour.
+    pub fn bridge_holes(outline: &[(f64, f64)], holes: &[Vec<(f64, f64)>]) -> Vec<(usize, usize)> {
+        let contours: Vec<&[(f64, f64)]> = std::iter::once(outline)
+            .chain(holes.iter().map(|hole| hole.as_slice()))
+            .collect();
+        let point = |(contour, index): (usize, usize)| contours[contour][index];
+        let mut joined: Vec<(usize, usize)> = (0..outline.len()).map(|i| (0, i)).collect();
+
+        // The holes are joined from right to left, so each bridge runs to the outline or to a
+        // hole joined before it.
+        let mut order: Vec<(usize, usize)> = holes
+            .iter()
+            .enumerate()
+            .filter(|(_, hole)| !hole.is_empty())
+            .map(|(i, hole)| {
+                let rightmost = (0..hole.len())
+                    .max_by(|&a, &b| hole[a].0.total_cmp(&hole[b].0))
+                    .unwrap_or(0);
+                (i + 1, rightmost)
+            })
+            .collect();
+        order.sort_by(|&a, &b| point(b).0.total_cmp(&point(a).0));
+
+        for (contour, rightmost) in order {
+            let m = point((contour, rightmost));
+
+            // The closest edge crossed by a ray from the vertex towards positive x.
+            let mut closest: Option<(f64, usize)> = None;
+            for i in 0..joined.len() {
+                let (a, b) = (point(joined[i]), point(joined[(i + 1) % joined.len()]));
+                if (a.1 > m.1) == (b.1 > m.1) {
+                    continue;
+                }
+                let x = a.0 + (m.1 - a.1) * (b.0 - a.0) / (b.1 - a.1);
+                if x >= m.0 && closest.is_none_or(|(closest_x, _)| x < closest_x) {
+                    // The end of the edge furthest along the ray is the candidate.
+                    let end = if a.0 > b.0 { i } else { (i + 1) % joined.len() };
+                    closest = Some((x, end));
+                }
+            }
+            let Some((x, mut bridge)) = closest else {
+                continue;
+            };
+
+            // A reflex vertex inside the triangle between the vertex, the crossing and the
+            // candidate would hide the candidate, and the one closest in angle to the ray is taken.
+            let crossing = (x, m.1);
+            let candidate = point(joined[bridge]);
+            let mut best_angle = f64::INFINITY;
+            for i in 0..joined.len() {
+                let p = point(joined[i]);
+                let previous = point(joined[(i + joined.len() - 1) % joined.len()]);
+                let next = point(joined[(i + 1) % joined.len()]);
+                let reflex = Self::signed_area(previous, p, next) <= 0.0;
+                let (first, second) = if candidate.1 < m.1 {
+                    (candidate, crossing)
+                } else {
+                    (crossing, candidate)
+                };
+                if i != bridge && reflex && p != m && Self::in_triangle(p, m, first, second) {
+                    let angle = (p.1 - m.1).abs().atan2(p.0 - m.0);
+                    if angle < best_angle {
+                        best_angle = angle;
+                        bridge = i;
+                    }
+                }
+            }
+
+            let hole_len = contours[contour].len();
+            let mut spliced = Vec::with_capacity(joined.len() + hole_len + 2);
+            spliced.extend_from_slice(&joined[..=bridge]);
+            spliced.extend((0..=hole_len).map(|k| (contour, (rightmost + k) % hole_len)));
+            spliced.extend_from_slice(&joined[bridge..]);
+            joined = spliced;
+        }
+        joined
+    }
+
+    /// Returns the area of the polygon.
+    fn area(&self) -> f64 {
+        self.cumulative_areas.last().copied().unwrap_or(0.0)
+    }
+
+    /// Returns the coordinates of a point in the plane of the polygon.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - A point in the plane.
+    fn plane_point(&self, point: &Vector3) -> (f64, f64) {
+        let offset = *point - self.vertices[0];
+        (offset.dot(&self.x_axis), offset.dot(&self.y_axis))
+    }
+}
+
+impl Hittable for Polygon {
+    /// Checks if a ray hits the polygon within a given interval: the ray meets the plane of the
+    /// polygon inside one of its triangles.
+    ///
+    /// # Arguments
+    ///
+    /// * `ray` - The ray to test for intersection.
+    /// * `interval` - The range of distances to consider for intersections.
+    ///
+    /// # Returns
+    ///
+    /// An `Option` containing the `HitRecord` if an intersection is found, or `None` if no intersection is found.
+    fn hit(&self, ray: &Ray, interval: (f64, f64)) -> Option<HitRecord<'_>> {
+        let denom = self.normal.dot(&ray.direction);
+        if denom.abs() < 1e-8 {
+            return None;
+        }
+        let t = self.normal.dot(&(self.vertices[0] - ray.origin)) / denom;
+        if !(t >= interval.0 && t <= interval.1) {
+            return None;
+        }
+
+        let intersection = ray.point_at(t);
+        let point = self.plane_point(&intersection);
+        let inside = self.triangles.iter().any(|&[a, b, c]| {
+            Self::in_triangle(point, self.points[a], self.points[b], self.points[c])
+        });
+        if !inside {
+            return None;
+        }
+
+        let u = (point.0 - self.min.0) / (self.max.0 - self.min.0);
+        let v = (point.1 - self.min.1) / (self.max.1 - self.min.1);
+        let mut record = HitRecord::new(t, intersection, self.material.as_ref(), u, v);
+        record.set_face_normal(ray, &self.normal);
+        record.set_tangents(
+            (self.max.0 - self.min.0) * self.x_axis,
+            (self.max.1 - self.min.1) * self.y_axis,
+        );
+        Some(record)
+    }
+
+    fn is_emissive(&self) -> bool {
+        self.material.is_emissive()
+    }
+
+    fn power(&self) -> Vector3 {
+        self.material.power() * self.area()
+    }
+
+    /// Returns the probability density of sampling a direction towards a point of the polygon
+    /// chosen uniformly.
+    ///
+    /// # Arguments
+    ///
+    /// * `origin` - The point from which the polygon is sampled.
+    /// * `direction` - The direction to evaluate.
+    ///
+    /// # Returns
+    ///
+    /// The probability density with respect to solid angle, or zero if the direction misses the
+    /// polygon.
+    fn pdf_value(&self, origin: &Vector3, direction: &Vector3) -> f64 {
+        let ray = Ray::new(*origin, *direction);
+        if let Some(record) = self.hit(&ray, (0.001, f64::INFINITY)) {
+            let distance_squared = record.t * record.t;
+            let cosine = ray.direction.dot(&self.normal).abs();
+            distance_squared / (cosine * self.area())
+        } else {
+            0.0
+        }
+    }
+
+    /// Generates a direction towards a point of the polygon chosen uniformly, by picking one of
+    /// its triangles in proportion to its area and a point of that triangle.
+    ///
+    /// # Arguments
+    ///
+    /// * `origin` - The point from which the polygon is sampled.
+    ///
+    /// # Returns
+    ///
+    /// The generated direction.
+    fn random(&self, origin: &Vector3) -> Vector3 {
+        let target = fastrand::f64() * self.area();
+        let index = self
+            .cumulative_areas
+            .partition_point(|&area| area < target)
+            .min(self.triangles.len() - 1);
+        let [a, b, c] = self.triangles[index].map(|i| self.vertices[i]);
+
+        let (r1, r2) = (fastrand::f64().sqrt(), fastrand::f64());
+        let point = (1.0 - r1) * a + r1 * (1.0 - r2) * b + r1 * r2 * c;
+        point - *origin
+    }
+
+    fn collect_materials(&self, table: &mut MaterialTable) {
+        table.add(&self.material);
+    }
+
+    fn collect_statistics(&self, statistics: &mut SceneStatistics) {
+        let bytes = std::mem::size_of_val(self)
+            + self.vertices.len() * std::mem::size_of::<Vector3>()
+            + self.points.len() * std::mem::size_of::<(f64, f64)>()
+            + self.triangles.len()
+                * (std::mem::size_of::<[usize; 3]>() + std::mem::size_of::<f64>());
+        statistics.add_primitive("Polygon", bytes);
+        self.material.collect_statistics(statistics);
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(Aabb::from_points(&self.vertices))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::{DiffuseLight, Lambertian};
+
+    fn material() -> Arc<dyn Material> {
+        Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5)))
+    }
+
+    /// An L-shaped floor plan in the plane y = 0, made of three unit squares.
+    fn floor_plan() -> Vec<Vector3> {
+        vec![
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, 2.0),
+            Vector3::new(1.0, 0.0, 2.0),
+            Vector3::new(1.0, 0.0, 1.0),
+            Vector3::new(2.0, 0.0, 1.0),
+            Vector3::new(2.0, 0.0, 0.0),
+        ]
+    }
+
+    #[test]
+    fn test_concave_polygon() {
+        let polygon = Polygon::new(floor_plan(), material());
+        assert_eq!(polygon.triangles.len(), 4);
+        assert!((polygon.area() - 3.0).abs() < 1e-9);
+        assert!((polygon.normal - Vector3::new(0.0, 1.0, 0.0)).length() < 1e-9);
+
+        let down = Vector3::new(0.0, -1.0, 0.0);
+        for (x, z) in [(0.5, 0.5), (0.5, 1.5), (1.5, 0.5), (0.99, 1.01)] {
+            let ray = Ray::new(Vector3::new(x, 3.0, z), down);
+            let record = polygon.hit(&ray, (0.001, f64::INFINITY)).unwrap();
+            assert!((record.t - 3.0).abs() < 1e-9);
+            assert!(record.front_face);
+            assert!((0.0..=1.0).contains(&record.u) && (0.0..=1.0).contains(&record.v));
+        }
+        // The missing corner of the L is not part of the polygon.
+        let notch = Ray::new(Vector3::new(1.5, 3.0, 1.5), down);
+        assert!(polygon.hit(&notch, (0.001, f64::INFINITY)).is_none());
+    }
+
+    #[test]
+    fn test_degenerate_vertices() {
+        // A square with an extra vertex in the middle of one of its edges.
+        let polygon = Polygon::new(
+            vec![
+                Vector3::new(0.0, 0.0, 0.0),
+                Vector3::new(1.0, 0.0, 0.0),
+                Vector3::new(2.0, 0.0, 0.0),
+                Vector3::new(2.0, 2.0, 0.0),
+                Vector3::new(0.0, 2.0, 0.0),
+            ],
+            material(),
+        );
+        assert!((polygon.area() - 4.0).abs() < 1e-9);
+        assert!((polygon.normal - Vector3::new(0.0, 0.0, 1.0)).length() < 1e-9);
+    }
+
+    #[test]
+    fn test_light_sampling() {
+        let light = Polygon::new(
+            floor_plan(),
+            Arc::new(DiffuseLight::new(Vector3::new(1.0, 1.0, 1.0))),
+        );
+        assert!(light.is_emissive());
+
+        // The light faces up, so it is sampled from above.
+        let origin = Vector3::new(1.0, 2.0, 1.0);
+        let mut notch_samples = 0;
+        for _ in 0..2000 {
+            let direction = light.random(&origin);
+            let point = origin + direction;
+            assert!(point.y.abs() < 1e-9);
+            if point.x > 1.0 && point.z > 1.0 {
+                notch_samples += 1;
+            }
+            assert!(light.pdf_value(&origin, &direction) > 0.0);
+        }
+        assert_eq!(notch_samples, 0);
+    }
+
+    #[test]
+    fn test_bridge_holes() {
+        // A square with a square hole, like a picture frame.
+        let outline = vec![(0.0, 0.0), (4.0, 0.0), (4.0, 4.0), (0.0, 4.0)];
+        let hole = vec![(1.0, 1.0), (1.0, 3.0), (3.0, 3.0), (3.0, 1.0)];
+        let joined = Polygon::bridge_holes(&outline, std::slice::from_ref(&hole));
+        assert_eq!(joined.len(), 10);
+
+        let points: Vec<(f64, f64)> = joined
+            .iter()
+            .map(|&(contour, index)| {
+                if contour == 0 {
+                    outline[index]
+                } else {
+                    hole[index]
+                }
+            })
+            .collect();
+        let triangles = Polygon::triangulate(&points);
+        let area: f64 = triangles
+            .iter()
+            .map(|&[a, b, c]| 0.5 * Polygon::signed_area(points[a], points[b], points[c]))
+            .sum();
+        assert!((area - 12.0).abs() < 1e-9, "{}", area);
+        for &[a, b, c] in &triangles {
+            assert!(Polygon::signed_area(points[a], points[b], points[c]) >= 0.0);
+            // No triangle covers the middle of the hole.
+            assert!(!Polygon::in_triangle(
+                (2.0, 2.0),
+                points[a],
+                points[b],
+                points[c]
+            ));
+        }
+    }
+}
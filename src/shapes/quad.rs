@@ -1,12 +1,128 @@
+use crate::aabb::Aabb;
+use crate::distribution::Distribution2D;
 use crate::hit::{HitRecord, Hittable};
 use crate::material::Material;
+use crate::material_table::MaterialTable;
+use crate::onb::Onb;
 use crate::ray::Ray;
+use crate::statistics::SceneStatistics;
+use crate::texture::ShadingContext;
 use crate::vector3::Vector3;
+use std::f64::consts::PI;
 use std::sync::Arc;
 
-/// Represents a quadrilateral shape in 3D space.
+/// The number of cells along each edge of the grid on which the emission of a textured light is
+/// measured for light sampling.
+const EMISSION_MAP_RESOLUTION: usize = 64;
+
+/// The outline of a flat shape within the plane spanned by its two edge vectors, in the
+/// coordinates `alpha` and `beta` along them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Outline {
+    /// The parallelogram with `alpha` and `beta` from 0 to 1, such as a rectangle.
+    Parallelogram,
+    /// The triangle with corners at the start, at the end of the first edge and at the end of the
+    /// second edge.
+    Triangle,
+    /// The ellipse centered on the start, with the edge vectors as its semi-axes.
+    Ellipse,
+    /// The ring between the ellipse with the edge vectors as its semi-axes and a smaller one
+    /// scaled by the inner radius, from 0 to 1.
+    Annulus(f64),
+}
+
+impl Outline {
+    /// Checks if a point of the plane lies inside the outline and gives its texture coordinates:
+    /// `alpha` and `beta` themselves for parallelograms and triangles, and the coordinates of the
+    /// point in the square enclosing an ellipse or annulus.
+    ///
+    /// # Arguments
+    ///
+    /// * `alpha` - The coordinate of the point along the first edge vector.
+    /// * `beta` - The coordinate of the point along the second edge vector.
+    ///
+    /// # Returns
+    ///
+    /// The texture coordinates of the point, or `None` if it lies outside the outline.
+    pub fn contains(&self, alpha: f64, beta: f64) -> Option<(f64, f64)> {
+        let inside = match *self {
+            Outline::Parallelogram => (0.0..=1.0).contains(&alpha) && (0.0..=1.0).contains(&beta),
+            Outline::Triangle => alpha >= 0.0 && beta >= 0.0 && alpha + beta <= 1.0,
+            Outline::Ellipse => alpha * alpha + beta * beta <= 1.0,
+            Outline::Annulus(inner) => {
+                (inner * inner..=1.0).contains(&(alpha * alpha + beta * beta))
+            }
+        };
+        match (inside, self) {
+            (false, _) => None,
+            (true, Outline::Parallelogram | Outline::Triangle) => Some((alpha, beta)),
+            (true, _) => Some(((alpha + 1.0) / 2.0, (beta + 1.0) / 2.0)),
+        }
+    }
+
+    /// Returns the area of the outline relative to the parallelogram spanned by the edge vectors.
+    pub fn area_fraction(&self) -> f64 {
+        match *self {
+            Outline::Parallelogram => 1.0,
+            Outline::Triangle => 0.5,
+            Outline::Ellipse => PI,
+            Outline::Annulus(inner) => PI * (1.0 - inner * inner),
+        }
+    }
+
+    /// Maps two uniform random numbers to a point of the outline chosen uniformly by area.
+    ///
+    /// # Arguments
+    ///
+    /// * `r1` - A random number between 0 and 1.
+    /// * `r2` - Another random number between 0 and 1.
+    ///
+    /// # Returns
+    ///
+    /// The coordinates `alpha` and `beta` of the point.
+    pub fn sample(&self, r1: f64, r2: f64) -> (f64, f64) {
+        let polar = |radius: f64| {
+            let (sin, cos) = (2.0 * PI * r2).sin_cos();
+            (radius * cos, radius * sin)
+        };
+        match *self {
+            Outline::Parallelogram => (r1, r2),
+            Outline::Triangle => {
+                let s = r1.sqrt();
+                (s * (1.0 - r2), s * r2)
+            }
+            Outline::Ellipse => polar(r1.sqrt()),
+            Outline::Annulus(inner) => polar((inner * inner + r1 * (1.0 - inner * inner)).sqrt()),
+        }
+    }
+
+    /// Returns the corners of a polygon enclosing the outline, in coordinates `alpha` and `beta`.
+    fn enclosing_corners(&self) -> Vec<(f64, f64)> {
+        match *self {
+            Outline::Parallelogram => vec![(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)],
+            Outline::Triangle => vec![(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)],
+            Outline::Ellipse | Outline::Annulus(_) => {
+                vec![(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)]
+            }
+        }
+    }
+
+    /// Returns the name of the outline, used to count primitives.
+    fn name(&self) -> &'static str {
+        match self {
+            Outline::Parallelogram => "Quad",
+            Outline::Triangle => "Triangle",
+            Outline::Ellipse => "Ellipse",
+            Outline::Annulus(_) => "Annulus",
+        }
+    }
+}
+
+/// Represents a flat shape in 3D space spanned by two edge vectors from a starting point: a
+/// quadrilateral, or a triangle, ellipse, disk or annulus sharing the same intersection with the
+/// plane and differing only in their outline.
 pub struct Quad {
-    /// The starting corner of the quad.
+    /// The starting corner of the quad, or the center of an ellipse or annulus.
     starting_corner: Vector3,
     /// The vector representing one edge of the quad.
     u: Vector3,
@@ -20,6 +136,15 @@ pub struct Quad {
     d: f64,
     /// The vector used for intersection calculations.
     w: Vector3,
+    /// The area of the quad.
+    area: f64,
+    /// The outline of the shape within the plane of the quad.
+    outline: Outline,
+    /// Whether camera rays pass through the back of the quad, the side opposite to its normal.
+    cull_backfaces: bool,
+    /// The density over the texture coordinates following the luminance of the emission, or
+    /// `None` if the quad emits uniformly and is sampled uniformly.
+    emission: Option<Distribution2D>,
 }
 
 impl Quad {
@@ -40,13 +165,38 @@ impl Quad {
         u: Vector3,
         v: Vector3,
         material: Arc<dyn Material>,
+    ) -> Quad {
+        Quad::with_outline(starting_corner, u, v, Outline::Parallelogram, material)
+    }
+
+    /// Creates a new flat shape from a starting point, two edge vectors, an outline and a
+    /// material.
+    ///
+    /// # Arguments
+    ///
+    /// * `starting_corner` - The starting point of the edge vectors.
+    /// * `u` - The first edge vector.
+    /// * `v` - The second edge vector.
+    /// * `outline` - The outline of the shape, in coordinates along the edge vectors.
+    /// * `material` - The material of the shape.
+    ///
+    /// # Returns
+    ///
+    /// A new `Quad` instance with the given outline.
+    pub fn with_outline(
+        starting_corner: Vector3,
+        u: Vector3,
+        v: Vector3,
+        outline: Outline,
+        material: Arc<dyn Material>,
     ) -> Quad {
         let n = u.cross(&v);
         let normal = n.normalize();
         let d = normal.dot(&starting_corner);
         let w = n / n.dot(&n);
+        let area = n.length() * outline.area_fraction();
 
-        Quad {
+        let mut quad = Quad {
             starting_corner,
             u,
             v,
@@ -54,8 +204,170 @@ impl Quad {
             normal,
             d,
             w,
+            area,
+            outline,
+            cull_backfaces: false,
+            emission: None,
+        };
+        quad.emission = quad.emission_distribution();
+        quad
+    }
+
+    /// Creates a new triangle from a corner and the two edges leaving it.
+    ///
+    /// # Arguments
+    ///
+    /// * `corner` - A corner of the triangle.
+    /// * `u` - The edge from the corner to the second corner.
+    /// * `v` - The edge from the corner to the third corner.
+    /// * `material` - The material of the triangle.
+    ///
+    /// # Returns
+    ///
+    /// A new `Quad` instance with a triangular outline.
+    pub fn triangle(corner: Vector3, u: Vector3, v: Vector3, material: Arc<dyn Material>) -> Quad {
+        Quad::with_outline(corner, u, v, Outline::Triangle, material)
+    }
+
+    /// Creates a new ellipse from its center and two semi-axes.
+    ///
+    /// # Arguments
+    ///
+    /// * `center` - The center of the ellipse.
+    /// * `u` - The first semi-axis.
+    /// * `v` - The second semi-axis, perpendicular to the first for a true ellipse.
+    /// * `material` - The material of the ellipse.
+    ///
+    /// # Returns
+    ///
+    /// A new `Quad` instance with an elliptical outline.
+    pub fn ellipse(center: Vector3, u: Vector3, v: Vector3, material: Arc<dyn Material>) -> Quad {
+        Quad::with_outline(center, u, v, Outline::Ellipse, material)
+    }
+
+    /// Creates a new disk facing a given direction.
+    ///
+    /// # Arguments
+    ///
+    /// * `center` - The center of the disk.
+    /// * `normal` - The direction the front of the disk faces.
+    /// * `radius` - The radius of the disk.
+    /// * `material` - The material of the disk.
+    ///
+    /// # Returns
+    ///
+    /// A new `Quad` instance with a circular outline.
+    pub fn disk(
+        center: Vector3,
+        normal: Vector3,
+        radius: f64,
+        material: Arc<dyn Material>,
+    ) -> Quad {
+        Quad::annulus(center, normal, 0.0, radius, material)
+    }
+
+    /// Creates a new annulus, a flat ring, facing a given direction.
+    ///
+    /// # Arguments
+    ///
+    /// * `center` - The center of the ring.
+    /// * `normal` - The direction the front of the ring faces.
+    /// * `inner` - The radius of the hole of the ring, zero for a disk.
+    /// * `outer` - The outer radius of the ring.
+    /// * `material` - The material of the ring.
+    ///
+    /// # Returns
+    ///
+    /// A new `Quad` instance with a ring outline.
+    pub fn annulus(
+        center: Vector3,
+        normal: Vector3,
+        inner: f64,
+        outer: f64,
+        material: Arc<dyn Material>,
+    ) -> Quad {
+        // The tangents of the basis turn clockwise around its normal, so they are swapped to
+        // make the front face the normal.
+        let onb = Onb::new(&normal);
+        let outline = if inner > 0.0 {
+            Outline::Annulus((inner / outer).min(1.0))
+        } else {
+            Outline::Ellipse
+        };
+        Quad::with_outline(center, outer * onb.v, outer * onb.u, outline, material)
+    }
+
+    /// Measures the luminance emitted by both faces of the quad on a grid of texture coordinates,
+    /// so textured lights such as a window photo are sampled where they are bright.
+    ///
+    /// # Returns
+    ///
+    /// The density following the emitted luminance, or `None` if the quad does not emit light or
+    /// emits it uniformly.
+    fn emission_distribution(&self) -> Option<Distribution2D> {
+        // The map covers the texture coordinates of parallelograms only, other outlines are
+        // sampled uniformly.
+        if !self.material.is_emissive() || self.outline != Outline::Parallelogram {
+            return None;
         }
+
+        let mut luminance = Vec::with_capacity(EMISSION_MAP_RESOLUTION * EMISSION_MAP_RESOLUTION);
+        for j in 0..EMISSION_MAP_RESOLUTION {
+            for i in 0..EMISSION_MAP_RESOLUTION {
+                let alpha = (i as f64 + 0.5) / EMISSION_MAP_RESOLUTION as f64;
+                let beta = (j as f64 + 0.5) / EMISSION_MAP_RESOLUTION as f64;
+                let point = self.starting_corner + alpha * self.u + beta * self.v;
+                let mut context = ShadingContext {
+                    u: alpha,
+                    v: beta,
+                    point,
+                    object_point: point,
+                    normal: self.normal,
+                    front_face: true,
+                    ..ShadingContext::default()
+                };
+                let mut emitted = self.material.emitted(&context).luminance();
+                context.front_face = false;
+                emitted += self.material.emitted(&context).luminance();
+                luminance.push(emitted.max(0.0));
+            }
+        }
+
+        let first = luminance[0];
+        if luminance.iter().all(|&value| value == first) {
+            return None;
+        }
+        Some(Distribution2D::new(&luminance, EMISSION_MAP_RESOLUTION))
     }
+
+    /// Makes camera rays pass through the back of the quad, so a closed room can be photographed
+    /// from outside through a wall facing away from the camera. Scattered rays and shadow rays
+    /// still hit both sides, so the wall keeps bouncing light inside the room.
+    ///
+    /// # Returns
+    ///
+    /// The `Quad` with backface culling enabled.
+    pub fn with_backface_culling(mut self) -> Quad {
+        self.cull_backfaces = true;
+        self
+    }
+}
+
+/// Finds the point of a line segment closest to a given point.
+///
+/// # Arguments
+///
+/// * `point` - The query point.
+/// * `start` - The start of the segment.
+/// * `end` - The end of the segment.
+///
+/// # Returns
+///
+/// The closest point of the segment.
+fn closest_point_on_segment(point: &Vector3, start: &Vector3, end: &Vector3) -> Vector3 {
+    let edge = *end - *start;
+    let t = ((*point - *start).dot(&edge) / edge.dot(&edge)).clamp(0.0, 1.0);
+    *start + t * edge
 }
 
 impl Hittable for Quad {
@@ -69,10 +381,10 @@ impl Hittable for Quad {
     /// # Returns
     ///
     /// An `Option` containing the `HitRecord` if an intersection is found, or `None` if no intersection is found.
-    fn hit(&self, ray: &Ray, interval: (f64, f64)) -> Option<HitRecord> {
+    fn hit(&self, ray: &Ray, interval: (f64, f64)) -> Option<HitRecord<'_>> {
         let denom = self.normal.dot(&ray.direction);
 
-        if denom.abs() < 1e-8 {
+        if denom.abs() < 1e-8 || (self.cull_backfaces && ray.primary && denom > 0.0) {
             return None;
         }
 
@@ -88,12 +400,311 @@ impl Hittable for Quad {
         let alpha = self.w.dot(&planar_hit_point_intersection.cross(&self.v));
         let beta = self.w.dot(&self.u.cross(&planar_hit_point_intersection));
 
-        if alpha > 1.0 || beta > 1.0 || alpha < 0.0 || beta < 0.0 {
-            return None;
-        }
+        let (u, v) = self.outline.contains(alpha, beta)?;
 
-        let mut record = HitRecord::new(t, intersection, &*self.material, alpha, beta);
+        let mut record = HitRecord::new(t, intersection, &*self.material, u, v);
         record.set_face_normal(ray, &self.normal);
+        record.set_tangents(self.u, self.v);
         Some(record)
     }
+
+    fn is_emissive(&self) -> bool {
+        self.material.is_emissive()
+    }
+
+    fn power(&self) -> Vector3 {
+        self.material.power() * self.area
+    }
+
+    /// Returns the probability density of sampling a direction towards a point of the quad chosen
+    /// uniformly, or following the emitted luminance for textured lights.
+    ///
+    /// # Arguments
+    ///
+    /// * `origin` - The point from which the quad is sampled.
+    /// * `direction` - The direction to evaluate.
+    ///
+    /// # Returns
+    ///
+    /// The probability density with respect to solid angle, or zero if the direction misses the quad.
+    fn pdf_value(&self, origin: &Vector3, direction: &Vector3) -> f64 {
+        let ray = Ray::new(*origin, *direction);
+        if let Some(record) = self.hit(&ray, (0.001, f64::INFINITY)) {
+            let distance_squared = record.t * record.t;
+            let cosine = ray.direction.dot(&self.normal).abs();
+            let density = self
+                .emission
+                .as_ref()
+                .map_or(1.0, |emission| emission.pdf(record.u, record.v));
+            distance_squared * density / (cosine * self.area)
+        } else {
+            0.0
+        }
+    }
+
+    /// Generates a direction towards a point of the quad chosen uniformly, or following the emitted
+    /// luminance for textured lights.
+    ///
+    /// # Arguments
+    ///
+    /// * `origin` - The point from which the quad is sampled.
+    ///
+    /// # Returns
+    ///
+    /// The generated direction.
+    fn random(&self, origin: &Vector3) -> Vector3 {
+        let (alpha, beta) = match &self.emission {
+            Some(emission) => {
+                let (alpha, beta, _) = emission.sample(fastrand::f64(), fastrand::f64());
+                (alpha, beta)
+            }
+            None => self.outline.sample(fastrand::f64(), fastrand::f64()),
+        };
+        let point = self.starting_corner + alpha * self.u + beta * self.v;
+        point - *origin
+    }
+
+    fn collect_materials(&self, table: &mut MaterialTable) {
+        table.add(&self.material);
+    }
+
+    fn collect_statistics(&self, statistics: &mut SceneStatistics) {
+        statistics.add_primitive(self.outline.name(), std::mem::size_of_val(self));
+        self.material.collect_statistics(statistics);
+    }
+
+    /// Finds the point of the shape closest to a given point: the projection of the point onto
+    /// the plane of the shape if it falls inside, or else the closest point of its edges. For
+    /// ellipses and annuli the projection is pulled straight towards or away from the center onto
+    /// the outline, which is exact for disks and rings.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The query point.
+    ///
+    /// # Returns
+    ///
+    /// The closest point of the shape.
+    fn closest_point(&self, point: &Vector3) -> Option<Vector3> {
+        let projected = *point - (self.normal.dot(point) - self.d) * self.normal;
+        let planar = projected - self.starting_corner;
+        let alpha = self.w.dot(&planar.cross(&self.v));
+        let beta = self.w.dot(&self.u.cross(&planar));
+        if self.outline.contains(alpha, beta).is_some() {
+            return Some(projected);
+        }
+
+        let at = |alpha: f64, beta: f64| self.starting_corner + alpha * self.u + beta * self.v;
+        match self.outline {
+            Outline::Parallelogram | Outline::Triangle => {
+                let corners: Vec<Vector3> = self
+                    .outline
+                    .enclosing_corners()
+                    .into_iter()
+                    .map(|(alpha, beta)| at(alpha, beta))
+                    .collect();
+                (0..corners.len())
+                    .map(|i| {
+                        closest_point_on_segment(
+                            point,
+                            &corners[i],
+                            &corners[(i + 1) % corners.len()],
+                        )
+                    })
+                    .min_by(|a, b| (*a - *point).length().total_cmp(&(*b - *point).length()))
+            }
+            Outline::Ellipse | Outline::Annulus(_) => {
+                let radius = alpha.hypot(beta);
+                let inner = match self.outline {
+                    Outline::Annulus(inner) => inner,
+                    _ => 0.0,
+                };
+                if radius == 0.0 {
+                    // The center of a ring is equally close to every point of its hole.
+                    return Some(at(inner, 0.0));
+                }
+                let clamped = radius.clamp(inner, 1.0) / radius;
+                Some(at(alpha * clamped, beta * clamped))
+            }
+        }
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let corners: Vec<Vector3> = self
+            .outline
+            .enclosing_corners()
+            .into_iter()
+            .map(|(alpha, beta)| self.starting_corner + alpha * self.u + beta * self.v)
+            .collect();
+        Some(Aabb::from_points(&corners))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::{DiffuseLight, Lambertian};
+    use crate::texture::{CheckerTexture, TextureSpace};
+
+    #[test]
+    fn test_backface_culling_of_camera_rays() {
+        let material = Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5)));
+        let quad = Quad::new(
+            Vector3::new(-1.0, -1.0, 0.0),
+            Vector3::new(2.0, 0.0, 0.0),
+            Vector3::new(0.0, 2.0, 0.0),
+            material,
+        )
+        .with_backface_culling();
+
+        // The normal points towards positive z, so this ray sees the back of the quad.
+        let mut back = Ray::new(Vector3::new(0.0, 0.0, -5.0), Vector3::new(0.0, 0.0, 1.0));
+        let mut front = Ray::new(Vector3::new(0.0, 0.0, 5.0), Vector3::new(0.0, 0.0, -1.0));
+        assert!(quad.hit(&back, (0.001, f64::INFINITY)).is_some());
+
+        back.primary = true;
+        front.primary = true;
+        assert!(quad.hit(&back, (0.001, f64::INFINITY)).is_none());
+        assert!(quad.hit(&front, (0.001, f64::INFINITY)).is_some());
+    }
+
+    #[test]
+    fn test_textured_light_sampling() {
+        // Two of the four quadrants of the light are dark.
+        let texture = CheckerTexture::new(
+            2.0,
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(4.0, 4.0, 4.0),
+        )
+        .with_space(TextureSpace::Uv);
+        let light = Quad::new(
+            Vector3::new(-1.0, -1.0, 0.0),
+            Vector3::new(2.0, 0.0, 0.0),
+            Vector3::new(0.0, 2.0, 0.0),
+            Arc::new(DiffuseLight::from_texture(Box::new(texture))),
+        );
+        assert!(light.emission.is_some());
+
+        fastrand::seed(7);
+        let origin = Vector3::new(0.3, -0.2, 3.0);
+        let samples = 20000;
+        let mut solid_angle = 0.0;
+        for _ in 0..samples {
+            let direction = light.random(&origin);
+            let record = light
+                .hit(&Ray::new(origin, direction), (0.001, f64::INFINITY))
+                .unwrap();
+            // Only the bright quadrants, where u and v fall in the same half, are sampled.
+            assert_eq!(record.u < 0.5, record.v < 0.5);
+            solid_angle += 1.0 / light.pdf_value(&origin, &direction);
+        }
+        solid_angle /= samples as f64;
+
+        // Half of the quad is sampled, so the estimate covers the solid angle of the bright half.
+        let steps = 200;
+        let cell_area = 4.0 / (steps * steps) as f64;
+        let mut expected = 0.0;
+        for i in 0..steps {
+            for j in 0..steps {
+                let alpha = (i as f64 + 0.5) / steps as f64;
+                let beta = (j as f64 + 0.5) / steps as f64;
+                if (alpha < 0.5) == (beta < 0.5) {
+                    let offset = Vector3::new(2.0 * alpha - 1.0, 2.0 * beta - 1.0, 0.0) - origin;
+                    let distance = offset.length();
+                    expected += cell_area * (offset.z.abs() / distance) / (distance * distance);
+                }
+            }
+        }
+
+        assert!(
+            (solid_angle - expected).abs() < 0.02 * expected,
+            "{} {}",
+            solid_angle,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_outlines() {
+        let material: Arc<dyn Material> = Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5)));
+        let down = Vector3::new(0.0, -1.0, 0.0);
+        let hits = |shape: &Quad, x: f64, z: f64| {
+            shape
+                .hit(
+                    &Ray::new(Vector3::new(x, 2.0, z), down),
+                    (0.001, f64::INFINITY),
+                )
+                .map(|record| (record.u, record.v, record.front_face))
+        };
+
+        let triangle = Quad::triangle(
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, 2.0),
+            Vector3::new(2.0, 0.0, 0.0),
+            material.clone(),
+        );
+        assert!((triangle.area - 2.0).abs() < 1e-9);
+        let (u, v, front_face) = hits(&triangle, 0.5, 1.0).unwrap();
+        assert!((u - 0.5).abs() < 1e-9 && (v - 0.25).abs() < 1e-9 && front_face);
+        assert!(hits(&triangle, 1.5, 1.5).is_none());
+
+        let disk = Quad::disk(
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            1.0,
+            material.clone(),
+        );
+        assert!((disk.area - PI).abs() < 1e-9);
+        let (u, v, front_face) = hits(&disk, 0.0, 0.0).unwrap();
+        assert!((u - 0.5).abs() < 1e-9 && (v - 0.5).abs() < 1e-9 && front_face);
+        assert!(hits(&disk, 0.6, 0.6).is_some());
+        assert!(hits(&disk, 0.75, 0.75).is_none());
+        let closest = disk.closest_point(&Vector3::new(3.0, 1.0, 0.0)).unwrap();
+        assert!((closest - Vector3::new(1.0, 0.0, 0.0)).length() < 1e-9);
+
+        let ring = Quad::annulus(
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            0.5,
+            1.0,
+            material.clone(),
+        );
+        assert!((ring.area - 0.75 * PI).abs() < 1e-9);
+        assert!(hits(&ring, 0.0, 0.0).is_none());
+        assert!(hits(&ring, 0.0, 0.75).is_some());
+        let closest = ring.closest_point(&Vector3::new(0.0, 1.0, 0.2)).unwrap();
+        assert!((closest - Vector3::new(0.0, 0.0, 0.5)).length() < 1e-9);
+
+        let ellipse = Quad::ellipse(
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, 2.0),
+            Vector3::new(1.0, 0.0, 0.0),
+            material,
+        );
+        assert!(hits(&ellipse, 0.0, 1.9).is_some());
+        assert!(hits(&ellipse, 0.9, 0.9).is_none());
+        let bounds = ellipse.bounding_box().unwrap();
+        assert!((bounds.max.z - 2.0).abs() < 1e-9 && (bounds.max.x - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_outline_light_sampling() {
+        let light = Quad::annulus(
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, 1.0),
+            0.5,
+            1.0,
+            Arc::new(DiffuseLight::new(Vector3::new(1.0, 1.0, 1.0))),
+        );
+        let origin = Vector3::new(0.2, 0.1, 2.0);
+        for _ in 0..1000 {
+            let direction = light.random(&origin);
+            let record = light
+                .hit(&Ray::new(origin, direction), (0.001, f64::INFINITY))
+                .unwrap();
+            let radius = record.poz.x.hypot(record.poz.y);
+            assert!((0.5 - 1e-9..=1.0 + 1e-9).contains(&radius));
+            assert!(light.pdf_value(&origin, &direction) > 0.0);
+        }
+    }
 }
@@ -0,0 +1,285 @@
+use crate::aabb::Aabb;
+use crate::hit::{HitRecord, Hittable};
+use crate::material::Material;
+use crate::material_table::MaterialTable;
+use crate::ray::Ray;
+use crate::statistics::SceneStatistics;
+use crate::vector3::Vector3;
+use std::sync::Arc;
+
+/// The largest number of steps taken along a ray by default.
+const DEFAULT_MAX_STEPS: usize = 256;
+
+/// The distance below which a ray is on the surface by default, relative to the diagonal of the
+/// bounding box of the shape.
+const DEFAULT_RELATIVE_EPSILON: f64 = 1e-5;
+
+/// A function giving a lower bound of the distance from a point to a surface, negative inside it.
+pub type DistanceFunction = dyn Fn(Vector3) -> f64 + Send + Sync;
+
+/// Blends the distances to two surfaces into the distance to their union, rounding the crease
+/// where they meet, with the polynomial smooth minimum of Inigo Quilez. The result never exceeds
+/// the distance to the sharp union, so it is safe to march.
+///
+/// # Arguments
+///
+/// * `a` - The distance to the first surface.
+/// * `b` - The distance to the second surface.
+/// * `k` - The width of the blend. Surfaces further apart than `k` are not blended.
+///
+/// # Returns
+///
+/// The distance to the blended union.
+pub fn smooth_union(a: f64, b: f64, k: f64) -> f64 {
+    let h = (0.5 + 0.5 * (b - a) / k).clamp(0.0, 1.0);
+    b + (a - b) * h - k * h * (1.0 - h)
+}
+
+/// Blends the distances to two surfaces into the distance to their intersection, rounding the
+/// edge where they meet. The result may exceed the true distance by up to `k / 4` near the edge,
+/// which a small precision hides.
+///
+/// # Arguments
+///
+/// * `a` - The distance to the first surface.
+/// * `b` - The distance to the second surface.
+/// * `k` - The width of the blend.
+///
+/// # Returns
+///
+/// The distance to the blended intersection.
+pub fn smooth_intersection(a: f64, b: f64, k: f64) -> f64 {
+    -smooth_union(-a, -b, k)
+}
+
+/// Blends the distances to two surfaces into the distance to the first one with the second one
+/// carved out of it, rounding the rim of the cut.
+///
+/// # Arguments
+///
+/// * `a` - The distance to the surface carved out of.
+/// * `b` - The distance to the surface carved out.
+/// * `k` - The width of the blend.
+///
+/// # Returns
+///
+/// The distance to the blended difference.
+pub fn smooth_subtraction(a: f64, b: f64, k: f64) -> f64 {
+    smooth_intersection(a, -b, k)
+}
+
+/// Represents a surface given by a signed distance function, intersected by sphere tracing: rays
+/// step forward by the distance to the surface until they come close enough to it. This renders
+/// shapes without an analytic intersection, such as fractals and smoothly blended primitives.
+pub struct SdfShape {
+    /// The distance function of the surface.
+    distance: Box<DistanceFunction>,
+    /// The box enclosing the surface, outside of which rays are not marched.
+    bounds: Aabb,
+    /// The material of the surface.
+    material: Arc<dyn Material>,
+    /// The distance below which a ray is on the surface.
+    epsilon: f64,
+    /// The largest number of steps taken along a ray.
+    max_steps: usize,
+}
+
+impl SdfShape {
+    /// Creates a new `SdfShape`.
+    ///
+    /// # Arguments
+    ///
+    /// * `distance` - The distance function of the surface. It must never overestimate the
+    ///   distance to the surface, or rays may step through it.
+    /// * `bounds` - A box enclosing the surface.
+    /// * `material` - The material of the surface.
+    ///
+    /// # Returns
+    ///
+    /// A new `SdfShape` instance.
+    pub fn new(
+        distance: impl Fn(Vector3) -> f64 + Send + Sync + 'static,
+        bounds: Aabb,
+        material: Arc<dyn Material>,
+    ) -> SdfShape {
+        SdfShape {
+            distance: Box::new(distance),
+            bounds,
+            material,
+            epsilon: DEFAULT_RELATIVE_EPSILON * (bounds.max - bounds.min).length(),
+            max_steps: DEFAULT_MAX_STEPS,
+        }
+    }
+
+    /// Sets the precision of the ray marching. Smaller distances show finer details but take more
+    /// steps, and rays running out of steps miss the surface.
+    ///
+    /// # Arguments
+    ///
+    /// * `epsilon` - The distance below which a ray is on the surface.
+    /// * `max_steps` - The largest number of steps taken along a ray.
+    ///
+    /// # Returns
+    ///
+    /// The `SdfShape` with the given precision.
+    pub fn with_precision(mut self, epsilon: f64, max_steps: usize) -> SdfShape {
+        self.epsilon = epsilon;
+        self.max_steps = max_steps;
+        self
+    }
+
+    /// Estimates the normal of the surface at a point from the gradient of the distance function,
+    /// with central differences.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - A point on or near the surface.
+    ///
+    /// # Returns
+    ///
+    /// The outward unit normal.
+    fn normal(&self, point: Vector3) -> Vector3 {
+        let h = self.epsilon;
+        let difference =
+            |offset: Vector3| (self.distance)(point + offset) - (self.distance)(point - offset);
+        Vector3::new(
+            difference(Vector3::new(h, 0.0, 0.0)),
+            difference(Vector3::new(0.0, h, 0.0)),
+            difference(Vector3::new(0.0, 0.0, h)),
+        )
+        .normalize()
+    }
+}
+
+impl Hittable for SdfShape {
+    /// Checks if a ray hits the surface within a given interval, by marching along the part of the
+    /// ray inside the bounding box.
+    ///
+    /// # Arguments
+    ///
+    /// * `ray` - The ray to test for intersection.
+    /// * `interval` - The range of distances to consider for intersections.
+    ///
+    /// # Returns
+    ///
+    /// An `Option` containing the `HitRecord` if an intersection is found, or `None` if no intersection is found.
+    fn hit(&self, ray: &Ray, interval: (f64, f64)) -> Option<HitRecord<'_>> {
+        let (start, end) = self.bounds.clip(ray, interval)?;
+
+        // Rays starting inside the box, such as rays scattered by the surface, first step off the
+        // surface they start on so they do not hit it again right away. Rays inside the surface
+        // march by the distance to it from inside, up to where they leave it.
+        let mut leaving = start == interval.0;
+        let mut t = start;
+        for _ in 0..self.max_steps {
+            if t > end {
+                return None;
+            }
+            let point = ray.point_at(t);
+            let distance = (self.distance)(point).abs();
+            if distance < self.epsilon {
+                if !leaving {
+                    let mut record = HitRecord::new(t, point, self.material.as_ref(), 0.0, 0.0);
+                    record.set_face_normal(ray, &self.normal(point));
+                    return Some(record);
+                }
+                t += self.epsilon;
+            } else {
+                leaving = false;
+                t += distance;
+            }
+        }
+        None
+    }
+
+    fn collect_materials(&self, table: &mut MaterialTable) {
+        table.add(&self.material);
+    }
+
+    fn collect_statistics(&self, statistics: &mut SceneStatistics) {
+        statistics.add_primitive("SdfShape", std::mem::size_of_val(self));
+        self.material.collect_statistics(statistics);
+    }
+
+    fn closest_point(&self, point: &Vector3) -> Option<Vector3> {
+        Some(*point - (self.distance)(*point) * self.normal(*point))
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.bounds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Lambertian;
+
+    fn unit_sphere() -> SdfShape {
+        SdfShape::new(
+            |point: Vector3| point.length() - 1.0,
+            Aabb::new(Vector3::new(-1.0, -1.0, -1.0), Vector3::new(1.0, 1.0, 1.0)),
+            Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5))),
+        )
+    }
+
+    #[test]
+    fn test_hit() {
+        let sphere = unit_sphere();
+        let ray = Ray::new(Vector3::new(0.0, 0.0, -5.0), Vector3::new(0.0, 0.0, 1.0));
+        let record = sphere.hit(&ray, (0.001, f64::INFINITY)).unwrap();
+        assert!((record.t - 4.0).abs() < 1e-3);
+        assert!(record.front_face);
+        assert!((record.normal - Vector3::new(0.0, 0.0, -1.0)).length() < 1e-3);
+
+        assert!(sphere.hit(&ray, (0.001, 3.9)).is_none());
+        let miss = Ray::new(Vector3::new(1.5, 0.0, -5.0), Vector3::new(0.0, 0.0, 1.0));
+        assert!(sphere.hit(&miss, (0.001, f64::INFINITY)).is_none());
+    }
+
+    #[test]
+    fn test_rays_leave_the_surface() {
+        let sphere = unit_sphere();
+        let ray = Ray::new(Vector3::new(0.0, 0.0, -5.0), Vector3::new(0.0, 0.0, 1.0));
+        let record = sphere.hit(&ray, (0.001, f64::INFINITY)).unwrap();
+
+        // A ray scattered away from the surface does not hit it again.
+        let scattered = Ray::new(record.poz, Vector3::new(0.3, 0.2, -1.0));
+        assert!(sphere.hit(&scattered, (0.001, f64::INFINITY)).is_none());
+
+        // A ray refracted into the surface hits it again where it leaves it, from the inside.
+        let refracted = Ray::new(record.poz, Vector3::new(0.0, 0.0, 1.0));
+        let exit = sphere.hit(&refracted, (0.001, f64::INFINITY)).unwrap();
+        assert!((exit.t - 2.0).abs() < 1e-3);
+        assert!(!exit.front_face);
+
+        let closest = sphere.closest_point(&Vector3::new(0.0, 3.0, 0.0)).unwrap();
+        assert!((closest - Vector3::new(0.0, 1.0, 0.0)).length() < 1e-6);
+    }
+
+    #[test]
+    fn test_smooth_blends() {
+        // Far apart the blends are the sharp operations, and close they round them off.
+        assert_eq!(smooth_union(1.0, 3.0, 0.5), 1.0);
+        assert_eq!(smooth_intersection(1.0, 3.0, 0.5), 3.0);
+        assert_eq!(smooth_subtraction(1.0, -3.0, 0.5), 3.0);
+        assert!((smooth_union(1.0, 1.0, 0.5) - 0.875).abs() < 1e-12);
+        assert!((smooth_intersection(1.0, 1.0, 0.5) - 1.125).abs() < 1e-12);
+
+        // Two spheres blended into one shape, joined by a neck between them.
+        let sphere = |center: Vector3| move |point: Vector3| (point - center).length() - 1.0;
+        let (left, right) = (
+            sphere(Vector3::new(-1.2, 0.0, 0.0)),
+            sphere(Vector3::new(1.2, 0.0, 0.0)),
+        );
+        let blob = SdfShape::new(
+            move |point| smooth_union(left(point), right(point), 0.8),
+            Aabb::new(Vector3::new(-2.5, -1.5, -1.5), Vector3::new(2.5, 1.5, 1.5)),
+            Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5))),
+        );
+        let down = Ray::new(Vector3::new(0.0, 5.0, 0.0), Vector3::new(0.0, -1.0, 0.0));
+        let record = blob.hit(&down, (0.001, f64::INFINITY)).unwrap();
+        assert!(record.poz.y > 0.0 && record.poz.y < 1.0, "{:?}", record.poz);
+        assert!((record.normal - Vector3::new(0.0, 1.0, 0.0)).length() < 1e-3);
+    }
+}
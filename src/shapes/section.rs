@@ -0,0 +1,225 @@
+use crate::aabb::Aabb;
+use crate::hit::{HitRecord, Hittable};
+use crate::material::Material;
+use crate::material_table::MaterialTable;
+use crate::ray::Ray;
+use crate::statistics::SceneStatistics;
+use crate::vector3::Vector3;
+use std::sync::Arc;
+
+/// The largest number of hits on the removed side of the plane skipped along a ray.
+const MAX_SKIPPED_HITS: usize = 32;
+
+/// Represents an object cut by a section plane, with the part of the object on the side the
+/// normal of the plane points to removed, for cutaway renders that leave the geometry untouched.
+pub struct Section {
+    /// The object being cut.
+    object: Box<dyn Hittable>,
+    /// A point of the section plane.
+    point: Vector3,
+    /// The unit normal of the section plane, pointing into the removed part.
+    normal: Vector3,
+    /// The material closing the cut where the plane passes through the inside of the object, or
+    /// `None` to leave the cut open.
+    cap: Option<Arc<dyn Material>>,
+}
+
+impl Section {
+    /// Creates a new open `Section`.
+    ///
+    /// # Arguments
+    ///
+    /// * `object` - The object to cut.
+    /// * `point` - A point of the section plane.
+    /// * `normal` - The normal of the section plane, pointing into the part that is removed.
+    ///
+    /// # Returns
+    ///
+    /// A new `Section` instance.
+    pub fn new(object: Box<dyn Hittable>, point: Vector3, normal: Vector3) -> Section {
+        Section {
+            object,
+            point,
+            normal: normal.normalize(),
+            cap: None,
+        }
+    }
+
+    /// Closes the cut with a cap where the section plane passes through the inside of the object.
+    /// The inside is told apart by the next surface of the object along the ray being a back
+    /// face, so caps only suit closed objects such as spheres and boxes.
+    ///
+    /// # Arguments
+    ///
+    /// * `material` - The material of the cap.
+    ///
+    /// # Returns
+    ///
+    /// The `Section` with the given cap.
+    pub fn with_cap(mut self, material: Arc<dyn Material>) -> Section {
+        self.cap = Some(material);
+        self
+    }
+
+    /// Returns `true` if a point lies in the removed part of the object.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The point to test.
+    fn is_removed(&self, point: &Vector3) -> bool {
+        self.normal.dot(&(*point - self.point)) > 0.0
+    }
+
+    /// Finds where a ray crosses the cap of the section.
+    ///
+    /// # Arguments
+    ///
+    /// * `ray` - The ray to test for intersection.
+    /// * `interval` - The range of distances to consider for intersections.
+    ///
+    /// # Returns
+    ///
+    /// An `Option` containing the `HitRecord` on the cap, or `None` if the ray does not cross the
+    /// plane inside the object or the section has no cap.
+    fn hit_cap(&self, ray: &Ray, interval: (f64, f64)) -> Option<HitRecord<'_>> {
+        let cap = self.cap.as_ref()?;
+        let denominator = self.normal.dot(&ray.direction);
+        if denominator.abs() < 1e-12 {
+            return None;
+        }
+        let t = self.normal.dot(&(self.point - ray.origin)) / denominator;
+        if t < interval.0 || t > interval.1 {
+            return None;
+        }
+
+        // The plane passes through the inside where the surface met next is seen from behind.
+        let next = self.object.hit(ray, (t, f64::INFINITY))?;
+        if next.front_face {
+            return None;
+        }
+
+        let mut record = HitRecord::new(t, ray.point_at(t), cap.as_ref(), 0.0, 0.0);
+        record.set_face_normal(ray, &self.normal);
+        Some(record)
+    }
+}
+
+impl Hittable for Section {
+    /// Checks if a ray hits the part of the object that is kept, or its cap, within a given
+    /// interval.
+    ///
+    /// # Arguments
+    ///
+    /// * `ray` - The ray to test for intersection.
+    /// * `interval` - The range of distances to consider for intersections.
+    ///
+    /// # Returns
+    ///
+    /// An `Option` containing the closest `HitRecord` if an intersection is found, or `None` if no intersection is found.
+    fn hit(&self, ray: &Ray, interval: (f64, f64)) -> Option<HitRecord<'_>> {
+        let mut start = interval.0;
+        let mut surface = None;
+        for _ in 0..MAX_SKIPPED_HITS {
+            let Some(record) = self.object.hit(ray, (start, interval.1)) else {
+                break;
+            };
+            if !self.is_removed(&record.poz) {
+                surface = Some(record);
+                break;
+            }
+            start = record.t + 1e-6;
+        }
+
+        let end = surface.as_ref().map_or(interval.1, |record| record.t);
+        self.hit_cap(ray, (interval.0, end)).or(surface)
+    }
+
+    fn is_emissive(&self) -> bool {
+        self.object.is_emissive()
+    }
+
+    fn power(&self) -> Vector3 {
+        self.object.power()
+    }
+
+    fn pdf_value(&self, origin: &Vector3, direction: &Vector3) -> f64 {
+        self.object.pdf_value(origin, direction)
+    }
+
+    fn random(&self, origin: &Vector3) -> Vector3 {
+        self.object.random(origin)
+    }
+
+    fn collect_materials(&self, table: &mut MaterialTable) {
+        self.object.collect_materials(table);
+        if let Some(cap) = &self.cap {
+            table.add(cap);
+        }
+    }
+
+    fn collect_statistics(&self, statistics: &mut SceneStatistics) {
+        statistics.add_geometry(std::mem::size_of_val(self));
+        self.object.collect_statistics(statistics);
+        if let Some(cap) = &self.cap {
+            cap.collect_statistics(statistics);
+        }
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.object.bounding_box()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Lambertian;
+    use crate::shapes::sphere::Sphere;
+
+    fn unit_sphere() -> Box<dyn Hittable> {
+        Box::new(Sphere::new(
+            Vector3::new(0.0, 0.0, 0.0),
+            1.0,
+            Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5))),
+        ))
+    }
+
+    #[test]
+    fn test_open_section() {
+        // The half of the sphere facing the viewer is removed.
+        let section = Section::new(
+            unit_sphere(),
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, 1.0),
+        );
+        let ray = Ray::new(Vector3::new(0.0, 0.0, 5.0), Vector3::new(0.0, 0.0, -1.0));
+        let record = section.hit(&ray, (0.001, f64::INFINITY)).unwrap();
+        // The inside of the far half is seen through the cut.
+        assert!((record.t - 6.0).abs() < 1e-9);
+        assert!(!record.front_face);
+
+        let kept_side = Ray::new(Vector3::new(0.0, 0.0, -5.0), Vector3::new(0.0, 0.0, 1.0));
+        let record = section.hit(&kept_side, (0.001, f64::INFINITY)).unwrap();
+        assert!((record.t - 4.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_capped_section() {
+        let section = Section::new(
+            unit_sphere(),
+            Vector3::new(0.0, 0.0, 0.5),
+            Vector3::new(0.0, 0.0, 1.0),
+        )
+        .with_cap(Arc::new(Lambertian::new(Vector3::new(0.8, 0.1, 0.1))));
+
+        let ray = Ray::new(Vector3::new(0.0, 0.0, 5.0), Vector3::new(0.0, 0.0, -1.0));
+        let record = section.hit(&ray, (0.001, f64::INFINITY)).unwrap();
+        assert!((record.t - 4.5).abs() < 1e-9);
+        assert!(record.front_face);
+        assert!((record.normal - Vector3::new(0.0, 0.0, 1.0)).length() < 1e-9);
+
+        // Beside the sphere the plane is crossed outside of it, so there is no cap.
+        let beside = Ray::new(Vector3::new(2.0, 0.0, 5.0), Vector3::new(0.0, 0.0, -1.0));
+        assert!(section.hit(&beside, (0.001, f64::INFINITY)).is_none());
+    }
+}
@@ -0,0 +1,1261 @@
+use crate::material::Material;
+use crate::shapes::cleanup::MeshData;
+use crate::shapes::mesh::TriangleMesh;
+use crate::vector3::Vector3;
+use gltf::animation::util::ReadOutputs;
+use std::path::Path;
+use std::sync::Arc;
+
+/// The number of joints that can move a vertex, as in glTF files.
+pub const JOINTS_PER_VERTEX: usize = 4;
+
+/// Represents an affine transform, as the top three rows of a 4x4 matrix.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Affine {
+    /// The rows of the matrix, the last column being the translation.
+    rows: [[f64; 4]; 3],
+}
+
+impl Affine {
+    /// The transform leaving every point in place.
+    pub const IDENTITY: Affine = Affine {
+        rows: [
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+        ],
+    };
+
+    /// Creates an `Affine` from a 4x4 matrix stored column by column, as in glTF files. The
+    /// bottom row is assumed to be `0 0 0 1`.
+    ///
+    /// # Arguments
+    ///
+    /// * `columns` - The columns of the matrix.
+    ///
+    /// # Returns
+    ///
+    /// A new `Affine` instance.
+    pub fn from_columns(columns: [[f32; 4]; 4]) -> Affine {
+        let mut rows = [[0.0; 4]; 3];
+        for (i, row) in rows.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = columns[j][i] as f64;
+            }
+        }
+        Affine { rows }
+    }
+
+    /// Creates an `Affine` scaling, then rotating, then translating points, as a joint is placed
+    /// relative to its parent.
+    ///
+    /// # Arguments
+    ///
+    /// * `pose` - The translation, rotation and scale.
+    ///
+    /// # Returns
+    ///
+    /// A new `Affine` instance.
+    pub fn from_pose(pose: &JointPose) -> Affine {
+        let [x, y, z, w] = pose.rotation;
+        let rotation = [
+            [
+                1.0 - 2.0 * (y * y + z * z),
+                2.0 * (x * y - z * w),
+                2.0 * (x * z + y * w),
+            ],
+            [
+                2.0 * (x * y + z * w),
+                1.0 - 2.0 * (x * x + z * z),
+                2.0 * (y * z - x * w),
+            ],
+            [
+                2.0 * (x * z - y * w),
+                2.0 * (y * z + x * w),
+                1.0 - 2.0 * (x * x + y * y),
+            ],
+        ];
+        let scale = [pose.scale.x, pose.scale.y, pose.scale.z];
+        let translation = [pose.translation.x, pose.translation.y, pose.translation.z];
+        let mut rows = [[0.0; 4]; 3];
+        for i in 0..3 {
+            for j in 0..3 {
+                rows[i][j] = rotation[i][j] * scale[j];
+            }
+            rows[i][3] = translation[i];
+        }
+        Affine { rows }
+    }
+
+    /// Combines the transform with the transform of a child.
+    ///
+    /// # Arguments
+    ///
+    /// * `child` - The transform of the child relative to this one.
+    ///
+    /// # Returns
+    ///
+    /// The transform applying `child` first and then this transform.
+    pub fn then(&self, child: &Affine) -> Affine {
+        let mut rows = [[0.0; 4]; 3];
+        for (i, row) in rows.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = (0..3)
+                    .map(|k| self.rows[i][k] * child.rows[k][j])
+                    .sum::<f64>();
+            }
+            row[3] += self.rows[i][3];
+        }
+        Affine { rows }
+    }
+
+    /// Blends transforms by their weights, as linear blend skinning does for the joints moving a
+    /// vertex.
+    ///
+    /// # Arguments
+    ///
+    /// * `weighted` - The transforms and their weights, which should sum to one.
+    ///
+    /// # Returns
+    ///
+    /// The weighted sum of the matrices.
+    pub fn blend(weighted: impl IntoIterator<Item = (Affine, f64)>) -> Affine {
+        let mut rows = [[0.0; 4]; 3];
+        for (affine, weight) in weighted {
+            for (row, other) in rows.iter_mut().zip(&affine.rows) {
+                for (value, other) in row.iter_mut().zip(other) {
+                    *value += weight * other;
+                }
+            }
+        }
+        Affine { rows }
+    }
+
+    /// Transforms a point.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The point to transform.
+    ///
+    /// # Returns
+    ///
+    /// The transformed point.
+    pub fn apply(&self, point: &Vector3) -> Vector3 {
+        let row = |r: &[f64; 4]| r[0] * point.x + r[1] * point.y + r[2] * point.z + r[3];
+        Vector3::new(row(&self.rows[0]), row(&self.rows[1]), row(&self.rows[2]))
+    }
+
+    /// Transforms a normal so it stays perpendicular to the transformed surface, with the inverse
+    /// transpose of the matrix, which also holds for non-uniform scales.
+    ///
+    /// # Arguments
+    ///
+    /// * `normal` - The normal to transform.
+    ///
+    /// # Returns
+    ///
+    /// The transformed unit normal.
+    pub fn apply_normal(&self, normal: &Vector3) -> Vector3 {
+        let [r0, r1, r2] = self.rows.map(|row| Vector3::new(row[0], row[1], row[2]));
+        // The rows of the inverse transpose are the cofactors below divided by the determinant,
+        // whose sign keeps the normal on the same side of a mirrored surface.
+        let cofactors = [r1.cross(&r2), r2.cross(&r0), r0.cross(&r1)];
+        let sign = r0.dot(&cofactors[0]).signum();
+        let transformed = sign
+            * Vector3::new(
+                cofactors[0].dot(normal),
+                cofactors[1].dot(normal),
+                cofactors[2].dot(normal),
+            );
+        if transformed.is_near_zero() {
+            *normal
+        } else {
+            transformed.normalize()
+        }
+    }
+}
+
+/// Returns the rotation by an angle around an axis, as a unit quaternion.
+///
+/// # Arguments
+///
+/// * `axis` - The axis of the rotation.
+/// * `angle` - The angle of the rotation in degrees, counterclockwise looking down the axis.
+///
+/// # Returns
+///
+/// The quaternion as `[x, y, z, w]`, the order of glTF files.
+pub fn quaternion(axis: Vector3, angle: f64) -> [f64; 4] {
+    let axis = axis.normalize();
+    let (sin, cos) = (angle.to_radians() / 2.0).sin_cos();
+    [axis.x * sin, axis.y * sin, axis.z * sin, cos]
+}
+
+/// Interpolates between two rotations along the shortest arc, at constant angular speed.
+///
+/// # Arguments
+///
+/// * `a` - The rotation at `s = 0`, as a unit quaternion.
+/// * `b` - The rotation at `s = 1`, as a unit quaternion.
+/// * `s` - The fraction of the way from `a` to `b`.
+///
+/// # Returns
+///
+/// The interpolated unit quaternion.
+fn slerp(a: [f64; 4], b: [f64; 4], s: f64) -> [f64; 4] {
+    let mut cos = (0..4).map(|i| a[i] * b[i]).sum::<f64>();
+    // A quaternion and its opposite are the same rotation, the closer one takes the short way.
+    let b = if cos < 0.0 {
+        cos = -cos;
+        b.map(|value| -value)
+    } else {
+        b
+    };
+    let (wa, wb) = if cos > 0.9995 {
+        (1.0 - s, s)
+    } else {
+        let angle = cos.acos();
+        let sin = angle.sin();
+        (((1.0 - s) * angle).sin() / sin, (s * angle).sin() / sin)
+    };
+    normalize_quaternion([0, 1, 2, 3].map(|i| wa * a[i] + wb * b[i]))
+}
+
+/// Scales a quaternion to unit length, or returns the identity rotation for a zero quaternion.
+fn normalize_quaternion(q: [f64; 4]) -> [f64; 4] {
+    let length = q.iter().map(|value| value * value).sum::<f64>().sqrt();
+    if length > 0.0 {
+        q.map(|value| value / length)
+    } else {
+        [0.0, 0.0, 0.0, 1.0]
+    }
+}
+
+/// Represents the placement of a joint relative to its parent: a scale, then a rotation, then a
+/// translation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct JointPose {
+    /// The translation.
+    pub translation: Vector3,
+    /// The rotation, as a unit quaternion `[x, y, z, w]`.
+    pub rotation: [f64; 4],
+    /// The scale along each axis.
+    pub scale: Vector3,
+}
+
+impl Default for JointPose {
+    fn default() -> Self {
+        JointPose {
+            translation: Vector3::new(0.0, 0.0, 0.0),
+            rotation: [0.0, 0.0, 0.0, 1.0],
+            scale: Vector3::new(1.0, 1.0, 1.0),
+        }
+    }
+}
+
+/// Represents a bone of a skeleton.
+#[derive(Debug, Clone)]
+pub struct Joint {
+    /// The name of the joint.
+    pub name: String,
+    /// The index of the parent joint, or `None` for a root. Parents come before their children.
+    pub parent: Option<usize>,
+    /// The fixed transform between the parent joint, or the origin for a root, and this joint,
+    /// left by nodes of the file that are not joints.
+    pub base: Affine,
+    /// The placement of the joint when no animation moves it.
+    pub rest: JointPose,
+    /// The transform from the space of the mesh to the space of the joint in the pose the mesh
+    /// was modeled in.
+    pub inverse_bind: Affine,
+}
+
+/// Represents a hierarchy of joints deforming a mesh.
+#[derive(Debug, Clone)]
+pub struct Skeleton {
+    /// The joints, every parent before its children.
+    joints: Vec<Joint>,
+}
+
+impl Skeleton {
+    /// Creates a new `Skeleton`.
+    ///
+    /// # Arguments
+    ///
+    /// * `joints` - The joints, every parent before its children.
+    ///
+    /// # Returns
+    ///
+    /// A new `Skeleton` instance.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a joint comes before its parent.
+    pub fn new(joints: Vec<Joint>) -> Skeleton {
+        assert!(
+            joints
+                .iter()
+                .enumerate()
+                .all(|(i, joint)| joint.parent.is_none_or(|parent| parent < i)),
+            "a joint comes before its parent"
+        );
+        Skeleton { joints }
+    }
+
+    /// Returns the joints, every parent before its children.
+    pub fn joints(&self) -> &[Joint] {
+        &self.joints
+    }
+
+    /// Returns the placements of the joints when no animation moves them.
+    pub fn rest_pose(&self) -> Vec<JointPose> {
+        self.joints.iter().map(|joint| joint.rest).collect()
+    }
+
+    /// Computes the transforms moving the vertices of the mesh with each joint, from the pose
+    /// the mesh was modeled in to a given pose.
+    ///
+    /// # Arguments
+    ///
+    /// * `pose` - The placement of every joint relative to its parent.
+    ///
+    /// # Returns
+    ///
+    /// The skinning transform of every joint.
+    pub fn skinning_transforms(&self, pose: &[JointPose]) -> Vec<Affine> {
+        let mut globals: Vec<Affine> = Vec::with_capacity(self.joints.len());
+        for (joint, placement) in self.joints.iter().zip(pose) {
+            let parent = joint
+                .parent
+                .map_or(Affine::IDENTITY, |parent| globals[parent]);
+            globals.push(parent.then(&joint.base).then(&Affine::from_pose(placement)));
+        }
+        globals
+            .iter()
+            .zip(&self.joints)
+            .map(|(global, joint)| global.then(&joint.inverse_bind))
+            .collect()
+    }
+}
+
+/// Represents how the values of an animation channel change between keyframes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interpolation {
+    /// Every value holds until the next keyframe.
+    Step,
+    /// Values change linearly, and rotations at constant angular speed.
+    Linear,
+    /// Values follow cubic Hermite curves, every keyframe storing its incoming tangent, its value
+    /// and its outgoing tangent.
+    CubicSpline,
+}
+
+impl Interpolation {
+    /// Returns the number of values stored for every keyframe.
+    pub fn values_per_keyframe(self) -> usize {
+        match self {
+            Interpolation::CubicSpline => 3,
+            _ => 1,
+        }
+    }
+}
+
+impl From<gltf::animation::Interpolation> for Interpolation {
+    fn from(interpolation: gltf::animation::Interpolation) -> Self {
+        match interpolation {
+            gltf::animation::Interpolation::Step => Interpolation::Step,
+            gltf::animation::Interpolation::Linear => Interpolation::Linear,
+            gltf::animation::Interpolation::CubicSpline => Interpolation::CubicSpline,
+        }
+    }
+}
+
+/// Finds the keyframes around a time.
+///
+/// # Arguments
+///
+/// * `times` - The times of the keyframes, increasing, at least one.
+/// * `time` - The time to find.
+///
+/// # Returns
+///
+/// The index of the keyframe before the time, the index of the keyframe after it and the fraction
+/// of the way between them. Outside of the keyframes both indices are the closest keyframe.
+pub fn keyframes_around(times: &[f64], time: f64) -> (usize, usize, f64) {
+    let next = times.partition_point(|&t| t <= time);
+    if next == 0 {
+        return (0, 0, 0.0);
+    }
+    if next == times.len() {
+        return (next - 1, next - 1, 0.0);
+    }
+    let previous = next - 1;
+    let s = (time - times[previous]) / (times[next] - times[previous]);
+    (previous, next, s)
+}
+
+/// Evaluates a cubic Hermite curve between two keyframes, as glTF cubic splines do.
+///
+/// # Arguments
+///
+/// * `a` - The value at the first keyframe.
+/// * `out_tangent` - The outgoing tangent of the first keyframe, per second.
+/// * `b` - The value at the second keyframe.
+/// * `in_tangent` - The incoming tangent of the second keyframe, per second.
+/// * `span` - The time between the keyframes in seconds.
+/// * `s` - The fraction of the way between the keyframes.
+///
+/// # Returns
+///
+/// The value of the curve.
+pub fn hermite(a: f64, out_tangent: f64, b: f64, in_tangent: f64, span: f64, s: f64) -> f64 {
+    let (s2, s3) = (s * s, s * s * s);
+    (2.0 * s3 - 3.0 * s2 + 1.0) * a
+        + (s3 - 2.0 * s2 + s) * span * out_tangent
+        + (-2.0 * s3 + 3.0 * s2) * b
+        + (s3 - s2) * span * in_tangent
+}
+
+/// Represents the part of a joint pose animated by a channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Property {
+    /// The translation, with values `[x, y, z, 0]`.
+    Translation,
+    /// The rotation, with quaternion values `[x, y, z, w]`.
+    Rotation,
+    /// The scale, with values `[x, y, z, 0]`.
+    Scale,
+}
+
+/// Represents the keyframes animating one property of one joint.
+#[derive(Debug, Clone)]
+pub struct Channel {
+    /// The index of the animated joint.
+    joint: usize,
+    /// The animated property.
+    property: Property,
+    /// The times of the keyframes in seconds, increasing.
+    times: Vec<f64>,
+    /// The values of the keyframes, three per keyframe for cubic splines.
+    values: Vec<[f64; 4]>,
+    /// How values change between keyframes.
+    interpolation: Interpolation,
+}
+
+impl Channel {
+    /// Creates a new `Channel`.
+    ///
+    /// # Arguments
+    ///
+    /// * `joint` - The index of the animated joint.
+    /// * `property` - The animated property.
+    /// * `times` - The times of the keyframes in seconds, increasing, at least one.
+    /// * `values` - The values of the keyframes, with `[in tangent, value, out tangent]` for
+    ///   every keyframe of a cubic spline.
+    /// * `interpolation` - How values change between keyframes.
+    ///
+    /// # Returns
+    ///
+    /// A new `Channel` instance.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are no keyframes or the number of values does not match them.
+    pub fn new(
+        joint: usize,
+        property: Property,
+        times: Vec<f64>,
+        values: Vec<[f64; 4]>,
+        interpolation: Interpolation,
+    ) -> Channel {
+        assert!(!times.is_empty(), "a channel needs at least one keyframe");
+        assert_eq!(
+            values.len(),
+            times.len() * interpolation.values_per_keyframe(),
+            "every keyframe of a channel needs a value"
+        );
+        Channel {
+            joint,
+            property,
+            times,
+            values,
+            interpolation,
+        }
+    }
+
+    /// Returns the value of the keyframe with an index, skipping the tangents of cubic splines.
+    fn value(&self, keyframe: usize) -> [f64; 4] {
+        match self.interpolation {
+            Interpolation::CubicSpline => self.values[3 * keyframe + 1],
+            _ => self.values[keyframe],
+        }
+    }
+
+    /// Evaluates the channel at a time, holding the first and last values outside of the
+    /// keyframes.
+    ///
+    /// # Arguments
+    ///
+    /// * `time` - The time in seconds.
+    ///
+    /// # Returns
+    ///
+    /// The value of the animated property.
+    pub fn sample(&self, time: f64) -> [f64; 4] {
+        let (previous, next, s) = keyframes_around(&self.times, time);
+        if previous == next {
+            return self.value(previous);
+        }
+        let span = self.times[next] - self.times[previous];
+        let (a, b) = (self.value(previous), self.value(next));
+        let value = match (self.interpolation, self.property) {
+            (Interpolation::Step, _) => a,
+            (Interpolation::Linear, Property::Rotation) => return slerp(a, b, s),
+            (Interpolation::Linear, _) => [0, 1, 2, 3].map(|i| a[i] + (b[i] - a[i]) * s),
+            (Interpolation::CubicSpline, _) => {
+                let out_tangent = self.values[3 * previous + 2];
+                let in_tangent = self.values[3 * next];
+                [0, 1, 2, 3].map(|i| hermite(a[i], out_tangent[i], b[i], in_tangent[i], span, s))
+            }
+        };
+        match self.property {
+            Property::Rotation => normalize_quaternion(value),
+            _ => value,
+        }
+    }
+}
+
+/// Represents a named animation of a skeleton, made of channels moving its joints.
+#[derive(Debug, Clone)]
+pub struct Animation {
+    /// The name of the animation.
+    pub name: String,
+    /// The channels of the animation.
+    channels: Vec<Channel>,
+}
+
+impl Animation {
+    /// Creates a new `Animation`.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the animation.
+    /// * `channels` - The channels of the animation.
+    ///
+    /// # Returns
+    ///
+    /// A new `Animation` instance.
+    pub fn new(name: &str, channels: Vec<Channel>) -> Animation {
+        Animation {
+            name: name.to_string(),
+            channels,
+        }
+    }
+
+    /// Returns the time of the last keyframe of the animation in seconds.
+    pub fn duration(&self) -> f64 {
+        self.channels
+            .iter()
+            .filter_map(|channel| channel.times.last())
+            .fold(0.0, |a, &b| a.max(b))
+    }
+
+    /// Evaluates the pose of a skeleton at a time of the animation. Joints and properties without
+    /// a channel keep their rest placement.
+    ///
+    /// # Arguments
+    ///
+    /// * `skeleton` - The animated skeleton.
+    /// * `time` - The time in seconds.
+    ///
+    /// # Returns
+    ///
+    /// The placement of every joint relative to its parent.
+    pub fn sample(&self, skeleton: &Skeleton, time: f64) -> Vec<JointPose> {
+        let mut pose = skeleton.rest_pose();
+        for channel in &self.channels {
+            let Some(joint) = pose.get_mut(channel.joint) else {
+                continue;
+            };
+            let [x, y, z, w] = channel.sample(time);
+            match channel.property {
+                Property::Translation => joint.translation = Vector3::new(x, y, z),
+                Property::Rotation => joint.rotation = [x, y, z, w],
+                Property::Scale => joint.scale = Vector3::new(x, y, z),
+            }
+        }
+        pose
+    }
+}
+
+/// Represents a triangle mesh bound to a skeleton, each vertex following a weighted blend of up
+/// to four joints. Every pose is turned into a new `TriangleMesh`, whose hierarchy is rebuilt
+/// around the moved triangles, so animations are rendered one frame at a time.
+pub struct SkinnedMesh {
+    /// The positions of the vertices in the pose the mesh was modeled in.
+    positions: Vec<Vector3>,
+    /// The normals of the vertices in the pose the mesh was modeled in.
+    normals: Vec<Vector3>,
+    /// The texture coordinates of the vertices.
+    uvs: Vec<(f64, f64)>,
+    /// The triangles, as indices into the vertices.
+    triangles: Vec<[usize; 3]>,
+    /// The joints moving every vertex.
+    joints: Vec<[usize; JOINTS_PER_VERTEX]>,
+    /// The weights of the joints moving every vertex, summing to one.
+    weights: Vec<[f64; JOINTS_PER_VERTEX]>,
+    /// The skeleton the mesh is bound to.
+    skeleton: Skeleton,
+    /// The animations of the skeleton.
+    animations: Vec<Animation>,
+}
+
+impl SkinnedMesh {
+    /// Creates a new `SkinnedMesh`. Missing normals are computed from the triangles, missing
+    /// texture coordinates are set to zero and the weights of every vertex are scaled to sum to
+    /// one, vertices without any weight staying in place.
+    ///
+    /// # Arguments
+    ///
+    /// * `mesh` - The mesh in the pose it was modeled in.
+    /// * `joints` - The joints moving every vertex.
+    /// * `weights` - The weights of the joints moving every vertex.
+    /// * `skeleton` - The skeleton the mesh is bound to.
+    ///
+    /// # Returns
+    ///
+    /// A new `SkinnedMesh` instance.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a vertex has no joints or weights, or refers to a missing joint.
+    pub fn new(
+        mut mesh: MeshData,
+        joints: Vec<[usize; JOINTS_PER_VERTEX]>,
+        weights: Vec<[f64; JOINTS_PER_VERTEX]>,
+        skeleton: Skeleton,
+    ) -> SkinnedMesh {
+        assert!(
+            joints.len() == mesh.positions.len() && weights.len() == mesh.positions.len(),
+            "every vertex of a skinned mesh needs joints and weights"
+        );
+        assert!(
+            joints.iter().flatten().all(|&j| j < skeleton.joints.len()),
+            "a vertex refers to a missing joint"
+        );
+        if mesh.normals.len() != mesh.positions.len() {
+            mesh.normals.clear();
+            mesh.orient_normals();
+        }
+        if mesh.uvs.len() != mesh.positions.len() {
+            mesh.uvs = vec![(0.0, 0.0); mesh.positions.len()];
+        }
+        let weights = weights
+            .into_iter()
+            .map(|weights| {
+                let total: f64 = weights.iter().sum();
+                if total > 0.0 {
+                    weights.map(|weight| weight / total)
+                } else {
+                    weights
+                }
+            })
+            .collect();
+        SkinnedMesh {
+            positions: mesh.positions,
+            normals: mesh.normals,
+            uvs: mesh.uvs,
+            triangles: mesh.triangles,
+            joints,
+            weights,
+            skeleton,
+            animations: Vec::new(),
+        }
+    }
+
+    /// Adds an animation of the skeleton.
+    ///
+    /// # Arguments
+    ///
+    /// * `animation` - The animation.
+    ///
+    /// # Returns
+    ///
+    /// The updated `SkinnedMesh` instance.
+    pub fn with_animation(mut self, animation: Animation) -> SkinnedMesh {
+        self.animations.push(animation);
+        self
+    }
+
+    /// Loads the first skinned mesh of a glTF file, with its skeleton and every animation moving
+    /// its joints. All triangle primitives of the mesh are merged, and the transform of the node
+    /// holding the mesh is ignored, as glTF requires for skinned meshes.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the `.gltf` or `.glb` file.
+    ///
+    /// # Returns
+    ///
+    /// The skinned mesh, or an error message if the file cannot be read or has no skinned mesh.
+    pub fn load(path: &Path) -> Result<SkinnedMesh, String> {
+        let (document, buffers, _) = gltf::import(path)
+            .map_err(|e| format!("Failed to load glTF {}: {}", path.display(), e))?;
+        let buffer = |buffer: gltf::Buffer| buffers.get(buffer.index()).map(|data| &data[..]);
+        let node = document
+            .nodes()
+            .find(|node| node.mesh().is_some() && node.skin().is_some())
+            .ok_or_else(|| format!("{}: no skinned mesh", path.display()))?;
+        let (mesh, skin) = (node.mesh().unwrap(), node.skin().unwrap());
+
+        let mut parents = vec![None; document.nodes().len()];
+        for parent in document.nodes() {
+            for child in parent.children() {
+                parents[child.index()] = Some(parent.index());
+            }
+        }
+        let locals: Vec<JointPose> = document
+            .nodes()
+            .map(|node| {
+                let (translation, rotation, scale) = node.transform().decomposed();
+                let vector = |v: [f32; 3]| Vector3::new(v[0] as f64, v[1] as f64, v[2] as f64);
+                JointPose {
+                    translation: vector(translation),
+                    rotation: normalize_quaternion(rotation.map(|value| value as f64)),
+                    scale: vector(scale),
+                }
+            })
+            .collect();
+
+        // Joints are sorted by depth so parents come first, and vertices refer to them by their
+        // position in the skin, hence the mapping between the two orders.
+        let skin_nodes: Vec<usize> = skin.joints().map(|joint| joint.index()).collect();
+        let depth = |mut node: usize| {
+            let mut depth = 0;
+            while let Some(parent) = parents[node] {
+                node = parent;
+                depth += 1;
+            }
+            depth
+        };
+        let mut order: Vec<usize> = (0..skin_nodes.len()).collect();
+        order.sort_by_key(|&i| depth(skin_nodes[i]));
+        let mut sorted_index = vec![0; skin_nodes.len()];
+        for (sorted, &i) in order.iter().enumerate() {
+            sorted_index[i] = sorted;
+        }
+        let joint_of_node = |node: usize| skin_nodes.iter().position(|&n| n == node);
+
+        let inverse_binds: Vec<Affine> = skin
+            .reader(buffer)
+            .read_inverse_bind_matrices()
+            .map(|matrices| matrices.map(Affine::from_columns).collect())
+            .unwrap_or_else(|| vec![Affine::IDENTITY; skin_nodes.len()]);
+        let joints = order
+            .iter()
+            .map(|&i| {
+                // Nodes between a joint and its parent joint only add their fixed transform.
+                let mut base = Affine::IDENTITY;
+                let mut ancestor = parents[skin_nodes[i]];
+                let parent = loop {
+                    match ancestor {
+                        None => break None,
+                        Some(node) => match joint_of_node(node) {
+                            Some(joint) => break Some(sorted_index[joint]),
+                            None => {
+                                base = Affine::from_pose(&locals[node]).then(&base);
+                                ancestor = parents[node];
+                            }
+                        },
+                    }
+                };
+                Joint {
+                    name: document
+                        .nodes()
+                        .nth(skin_nodes[i])
+                        .and_then(|node| node.name().map(str::to_string))
+                        .unwrap_or_default(),
+                    parent,
+                    base,
+                    rest: locals[skin_nodes[i]],
+                    inverse_bind: inverse_binds.get(i).copied().unwrap_or(Affine::IDENTITY),
+                }
+            })
+            .collect();
+
+        let mut data = MeshData::default();
+        let mut vertex_joints = Vec::new();
+        let mut vertex_weights = Vec::new();
+        let mut has_normals = true;
+        for primitive in mesh.primitives() {
+            if primitive.mode() != gltf::mesh::Mode::Triangles {
+                continue;
+            }
+            let reader = primitive.reader(buffer);
+            let Some(positions) = reader.read_positions() else {
+                continue;
+            };
+            let offset = data.positions.len();
+            data.positions
+                .extend(positions.map(|p| Vector3::new(p[0] as f64, p[1] as f64, p[2] as f64)));
+            let count = data.positions.len() - offset;
+            match reader.read_normals() {
+                Some(normals) => data
+                    .normals
+                    .extend(normals.map(|n| Vector3::new(n[0] as f64, n[1] as f64, n[2] as f64))),
+                None => has_normals = false,
+            }
+            match reader.read_tex_coords(0) {
+                Some(uvs) => data
+                    .uvs
+                    .extend(uvs.into_f32().map(|uv| (uv[0] as f64, 1.0 - uv[1] as f64))),
+                None => data.uvs.extend(std::iter::repeat_n((0.0, 0.0), count)),
+            }
+            match reader.read_joints(0) {
+                Some(joints) => vertex_joints.extend(joints.into_u16().map(|joints| {
+                    joints.map(|j| sorted_index.get(j as usize).copied().unwrap_or(0))
+                })),
+                None => vertex_joints.extend(std::iter::repeat_n([0; 4], count)),
+            }
+            match reader.read_weights(0) {
+                Some(weights) => vertex_weights
+                    .extend(weights.into_f32().map(|weights| weights.map(|w| w as f64))),
+                None => vertex_weights.extend(std::iter::repeat_n([0.0; 4], count)),
+            }
+            let indices: Vec<usize> = match reader.read_indices() {
+                Some(indices) => indices.into_u32().map(|i| offset + i as usize).collect(),
+                None => (offset..offset + count).collect(),
+            };
+            data.triangles.extend(
+                indices
+                    .chunks_exact(3)
+                    .filter(|triangle| triangle.iter().all(|&i| i < offset + count))
+                    .map(|triangle| [triangle[0], triangle[1], triangle[2]]),
+            );
+        }
+        if data.triangles.is_empty() {
+            return Err(format!(
+                "{}: the skinned mesh has no triangles",
+                path.display()
+            ));
+        }
+        if !has_normals {
+            data.normals.clear();
+        }
+
+        let animations = document
+            .animations()
+            .map(|animation| {
+                let channels = animation
+                    .channels()
+                    .filter_map(|channel| {
+                        let joint = joint_of_node(channel.target().node().index())?;
+                        let reader = channel.reader(buffer);
+                        let times: Vec<f64> = reader.read_inputs()?.map(|t| t as f64).collect();
+                        let (property, values): (Property, Vec<[f64; 4]>) =
+                            match reader.read_outputs()? {
+                                ReadOutputs::Translations(values) => (
+                                    Property::Translation,
+                                    values
+                                        .map(|v| [v[0] as f64, v[1] as f64, v[2] as f64, 0.0])
+                                        .collect(),
+                                ),
+                                ReadOutputs::Rotations(values) => (
+                                    Property::Rotation,
+                                    values.into_f32().map(|q| q.map(|v| v as f64)).collect(),
+                                ),
+                                ReadOutputs::Scales(values) => (
+                                    Property::Scale,
+                                    values
+                                        .map(|v| [v[0] as f64, v[1] as f64, v[2] as f64, 0.0])
+                                        .collect(),
+                                ),
+                                ReadOutputs::MorphTargetWeights(_) => return None,
+                            };
+                        let interpolation = Interpolation::from(channel.sampler().interpolation());
+                        let per_keyframe = interpolation.values_per_keyframe();
+                        (!times.is_empty() && values.len() == times.len() * per_keyframe).then(
+                            || {
+                                Channel::new(
+                                    sorted_index[joint],
+                                    property,
+                                    times,
+                                    values,
+                                    interpolation,
+                                )
+                            },
+                        )
+                    })
+                    .collect();
+                Animation::new(animation.name().unwrap_or_default(), channels)
+            })
+            .collect();
+
+        let mut skinned =
+            SkinnedMesh::new(data, vertex_joints, vertex_weights, Skeleton::new(joints));
+        skinned.animations = animations;
+        Ok(skinned)
+    }
+
+    /// Returns the skeleton the mesh is bound to.
+    pub fn skeleton(&self) -> &Skeleton {
+        &self.skeleton
+    }
+
+    /// Returns the animations of the skeleton.
+    pub fn animations(&self) -> &[Animation] {
+        &self.animations
+    }
+
+    /// Deforms the mesh into a pose of its skeleton with linear blend skinning, every vertex
+    /// moved by the weighted blend of the transforms of its joints.
+    ///
+    /// # Arguments
+    ///
+    /// * `pose` - The placement of every joint relative to its parent.
+    /// * `material` - The material of the mesh.
+    ///
+    /// # Returns
+    ///
+    /// The deformed mesh, with its hierarchy built around the moved triangles.
+    pub fn deform(&self, pose: &[JointPose], material: Arc<dyn Material>) -> TriangleMesh {
+        let (positions, normals) = self.deformed_vertices(pose);
+        TriangleMesh::new(
+            positions,
+            normals,
+            self.uvs.clone(),
+            self.triangles.clone(),
+            material,
+        )
+    }
+
+    /// Moves the vertices of a mesh deformed from this one into the pose of the skeleton at a
+    /// time of an animation, refitting its hierarchy rather than building a new mesh, which
+    /// keeps the preparation of every frame of an animation cheap.
+    ///
+    /// # Arguments
+    ///
+    /// * `animation` - The animation.
+    /// * `time` - The time in seconds.
+    /// * `mesh` - A mesh built by `deform` or `frame` from this skinned mesh.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the hierarchy of the mesh had to be rebuilt.
+    pub fn refit_frame(&self, animation: &Animation, time: f64, mesh: &mut TriangleMesh) -> bool {
+        let (positions, normals) = self.deformed_vertices(&animation.sample(&self.skeleton, time));
+        mesh.refit(positions, normals)
+    }
+
+    /// Computes the positions and normals of the vertices in a pose of the skeleton.
+    ///
+    /// # Arguments
+    ///
+    /// * `pose` - The placement of every joint relative to its parent.
+    ///
+    /// # Returns
+    ///
+    /// The moved positions and normals.
+    fn deformed_vertices(&self, pose: &[JointPose]) -> (Vec<Vector3>, Vec<Vector3>) {
+        let transforms = self.skeleton.skinning_transforms(pose);
+        (0..self.positions.len())
+            .map(|vertex| {
+                let weights = self.weights[vertex];
+                let transform = if weights.iter().sum::<f64>() > 0.0 {
+                    Affine::blend(
+                        self.joints[vertex]
+                            .iter()
+                            .zip(weights)
+                            .map(|(&joint, weight)| (transforms[joint], weight)),
+                    )
+                } else {
+                    Affine::IDENTITY
+                };
+                (
+                    transform.apply(&self.positions[vertex]),
+                    transform.apply_normal(&self.normals[vertex]),
+                )
+            })
+            .unzip()
+    }
+
+    /// Deforms the mesh into the pose of its skeleton at a time of an animation.
+    ///
+    /// # Arguments
+    ///
+    /// * `animation` - The animation.
+    /// * `time` - The time in seconds.
+    /// * `material` - The material of the mesh.
+    ///
+    /// # Returns
+    ///
+    /// The deformed mesh.
+    pub fn frame(
+        &self,
+        animation: &Animation,
+        time: f64,
+        material: Arc<dyn Material>,
+    ) -> TriangleMesh {
+        self.deform(&animation.sample(&self.skeleton, time), material)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hit::Hittable;
+    use crate::material::Lambertian;
+    use crate::ray::Ray;
+
+    fn material() -> Arc<dyn Material> {
+        Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5)))
+    }
+
+    /// Compares points, allowing for the padding of the boxes of flat meshes.
+    fn close(a: Vector3, b: Vector3) -> bool {
+        (a - b).length() < 1e-4
+    }
+
+    #[test]
+    fn test_affine() {
+        let pose = JointPose {
+            translation: Vector3::new(1.0, 2.0, 3.0),
+            rotation: quaternion(Vector3::new(0.0, 0.0, 1.0), 90.0),
+            scale: Vector3::new(2.0, 1.0, 1.0),
+        };
+        let affine = Affine::from_pose(&pose);
+        // Scaled, then turned from the X-axis to the Y-axis, then moved.
+        let point = affine.apply(&Vector3::new(1.0, 0.0, 0.0));
+        assert!(close(point, Vector3::new(1.0, 4.0, 3.0)));
+        let back = Affine::from_pose(&JointPose {
+            translation: Vector3::new(-1.0, -2.0, -3.0),
+            ..JointPose::default()
+        });
+        assert!(close(
+            back.then(&affine).apply(&Vector3::new(1.0, 0.0, 0.0)),
+            Vector3::new(0.0, 2.0, 0.0)
+        ));
+
+        // A surface stretched along X tilts its normals towards the other axes.
+        let stretch = Affine::from_pose(&JointPose {
+            scale: Vector3::new(2.0, 1.0, 1.0),
+            ..JointPose::default()
+        });
+        let normal = Vector3::new(1.0, 1.0, 0.0).normalize();
+        let tangent = Vector3::new(1.0, -1.0, 0.0);
+        let moved = stretch.apply_normal(&normal);
+        let moved_tangent = stretch.apply(&tangent) - stretch.apply(&Vector3::default());
+        assert!(moved.dot(&moved_tangent).abs() < 1e-9);
+        assert!((moved.length() - 1.0).abs() < 1e-9);
+
+        let halfway = slerp(
+            [0.0, 0.0, 0.0, 1.0],
+            quaternion(Vector3::new(0.0, 1.0, 0.0), 120.0),
+            0.5,
+        );
+        let expected = quaternion(Vector3::new(0.0, 1.0, 0.0), 60.0);
+        assert!((0..4).all(|i| (halfway[i] - expected[i]).abs() < 1e-9));
+    }
+
+    #[test]
+    fn test_channel_interpolation() {
+        let values = vec![[0.0, 0.0, 0.0, 0.0], [2.0, 4.0, 0.0, 0.0]];
+        let channel = |interpolation| {
+            Channel::new(
+                0,
+                Property::Translation,
+                vec![1.0, 2.0],
+                values.clone(),
+                interpolation,
+            )
+        };
+        assert_eq!(
+            channel(Interpolation::Linear).sample(1.5),
+            [1.0, 2.0, 0.0, 0.0]
+        );
+        assert_eq!(channel(Interpolation::Step).sample(1.5), [0.0; 4]);
+        // Outside of the keyframes the first and last values hold.
+        assert_eq!(channel(Interpolation::Linear).sample(0.0), [0.0; 4]);
+        assert_eq!(channel(Interpolation::Linear).sample(5.0), values[1]);
+
+        // With tangents matching the slope the spline is the straight line.
+        let slope = [2.0, 4.0, 0.0, 0.0];
+        let spline = Channel::new(
+            0,
+            Property::Translation,
+            vec![1.0, 2.0],
+            vec![slope, values[0], slope, slope, values[1], slope],
+            Interpolation::CubicSpline,
+        );
+        let sample = spline.sample(1.25);
+        assert!((sample[0] - 0.5).abs() < 1e-9 && (sample[1] - 1.0).abs() < 1e-9);
+    }
+
+    /// Builds a triangle bound to a root joint at the origin and an arm joint one unit above it,
+    /// its top vertex following the arm.
+    fn arm() -> SkinnedMesh {
+        let mesh = MeshData::new(
+            vec![
+                Vector3::new(0.0, 0.0, 0.0),
+                Vector3::new(1.0, 0.0, 0.0),
+                Vector3::new(0.0, 2.0, 0.0),
+            ],
+            Vec::new(),
+            Vec::new(),
+            vec![[0, 1, 2]],
+        );
+        let skeleton = Skeleton::new(vec![
+            Joint {
+                name: "root".to_string(),
+                parent: None,
+                base: Affine::IDENTITY,
+                rest: JointPose::default(),
+                inverse_bind: Affine::IDENTITY,
+            },
+            Joint {
+                name: "arm".to_string(),
+                parent: Some(0),
+                base: Affine::IDENTITY,
+                rest: JointPose {
+                    translation: Vector3::new(0.0, 1.0, 0.0),
+                    ..JointPose::default()
+                },
+                inverse_bind: Affine::from_pose(&JointPose {
+                    translation: Vector3::new(0.0, -1.0, 0.0),
+                    ..JointPose::default()
+                }),
+            },
+        ]);
+        SkinnedMesh::new(
+            mesh,
+            vec![[0, 0, 0, 0], [0, 1, 0, 0], [1, 0, 0, 0]],
+            vec![
+                [1.0, 0.0, 0.0, 0.0],
+                [1.0, 1.0, 0.0, 0.0],
+                [2.0, 0.0, 0.0, 0.0],
+            ],
+            skeleton,
+        )
+    }
+
+    #[test]
+    fn test_deform() {
+        let arm = arm();
+        let mut pose = arm.skeleton().rest_pose();
+        pose[1].rotation = quaternion(Vector3::new(0.0, 0.0, 1.0), 90.0);
+        let mesh = arm.deform(&pose, material());
+
+        // The top vertex swings around the arm joint, the vertex split between both joints goes
+        // halfway, and the root vertex stays.
+        let hit = |origin: Vector3| {
+            let ray = Ray::new(origin, Vector3::new(0.0, 0.0, -1.0));
+            mesh.hit(&ray, (0.001, f64::INFINITY))
+                .map(|record| record.poz)
+        };
+        let bounds = mesh.bounding_box().unwrap();
+        assert!(close(bounds.min, Vector3::new(-1.0, 0.0, 0.0)));
+        assert!(close(bounds.max, Vector3::new(1.0, 1.0, 0.0)));
+        assert!(hit(Vector3::new(-0.9, 0.95, 1.0)).is_some());
+        assert!(hit(Vector3::new(0.0, 1.5, 1.0)).is_none());
+
+        // In the rest pose the mesh is unchanged.
+        let rest = arm.deform(&arm.skeleton().rest_pose(), material());
+        let bounds = rest.bounding_box().unwrap();
+        assert!(close(bounds.max, Vector3::new(1.0, 2.0, 0.0)));
+    }
+
+    /// Writes a glTF file with the triangle of `arm`, listing the arm joint before its parent,
+    /// and an animation turning the arm a quarter turn in a second.
+    fn write_gltf(directory: &Path) -> std::path::PathBuf {
+        let mut bin: Vec<u8> = Vec::new();
+        let floats = |bin: &mut Vec<u8>, values: &[f32]| {
+            values
+                .iter()
+                .for_each(|value| bin.extend(value.to_le_bytes()))
+        };
+        floats(&mut bin, &[0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 2.0, 0.0]);
+        bin.extend([1, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0]);
+        floats(&mut bin, &[1.0, 0.0, 0.0, 0.0].repeat(3));
+        let mut matrices = [
+            1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0,
+        ]
+        .repeat(2);
+        matrices[13] = -1.0;
+        floats(&mut bin, &matrices);
+        floats(&mut bin, &[0.0, 1.0]);
+        let quarter = quaternion(Vector3::new(0.0, 0.0, 1.0), 90.0).map(|value| value as f32);
+        floats(&mut bin, &[0.0, 0.0, 0.0, 1.0]);
+        floats(&mut bin, &quarter);
+        assert_eq!(bin.len(), 264);
+        std::fs::write(directory.join("arm.bin"), &bin).unwrap();
+
+        let json = r#"{
+            "asset": {"version": "2.0"},
+            "scene": 0,
+            "scenes": [{"nodes": [0, 1]}],
+            "nodes": [
+                {"name": "mesh", "mesh": 0, "skin": 0},
+                {"name": "root", "children": [2]},
+                {"name": "arm", "translation": [0, 1, 0]}
+            ],
+            "meshes": [{"primitives": [{"attributes": {"POSITION": 0, "JOINTS_0": 1, "WEIGHTS_0": 2}}]}],
+            "skins": [{"joints": [2, 1], "inverseBindMatrices": 3}],
+            "animations": [{
+                "name": "raise",
+                "channels": [{"sampler": 0, "target": {"node": 2, "path": "rotation"}}],
+                "samplers": [{"input": 4, "output": 5, "interpolation": "LINEAR"}]
+            }],
+            "buffers": [{"uri": "arm.bin", "byteLength": 264}],
+            "bufferViews": [
+                {"buffer": 0, "byteOffset": 0, "byteLength": 36},
+                {"buffer": 0, "byteOffset": 36, "byteLength": 12},
+                {"buffer": 0, "byteOffset": 48, "byteLength": 48},
+                {"buffer": 0, "byteOffset": 96, "byteLength": 128},
+                {"buffer": 0, "byteOffset": 224, "byteLength": 8},
+                {"buffer": 0, "byteOffset": 232, "byteLength": 32}
+            ],
+            "accessors": [
+                {"bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3", "min": [0, 0, 0], "max": [1, 2, 0]},
+                {"bufferView": 1, "componentType": 5121, "count": 3, "type": "VEC4"},
+                {"bufferView": 2, "componentType": 5126, "count": 3, "type": "VEC4"},
+                {"bufferView": 3, "componentType": 5126, "count": 2, "type": "MAT4"},
+                {"bufferView": 4, "componentType": 5126, "count": 2, "type": "SCALAR", "min": [0], "max": [1]},
+                {"bufferView": 5, "componentType": 5126, "count": 2, "type": "VEC4"}
+            ]
+        }"#;
+        let path = directory.join("arm.gltf");
+        std::fs::write(&path, json).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_load_gltf() {
+        let directory = std::env::temp_dir().join(format!("skin_test_{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let loaded = SkinnedMesh::load(&write_gltf(&directory));
+        std::fs::remove_dir_all(&directory).unwrap();
+        let skinned = loaded.unwrap();
+
+        let joints = skinned.skeleton().joints();
+        assert_eq!(joints[0].name, "root");
+        assert_eq!(
+            (joints[1].name.as_str(), joints[1].parent),
+            ("arm", Some(0))
+        );
+        let animation = &skinned.animations()[0];
+        assert_eq!(
+            (animation.name.as_str(), animation.duration()),
+            ("raise", 1.0)
+        );
+
+        // Halfway through, the top vertex has turned an eighth of a turn around the arm joint.
+        let mesh = skinned.frame(animation, 0.5, material());
+        let bounds = mesh.bounding_box().unwrap();
+        let (sin, cos) = std::f64::consts::FRAC_PI_4.sin_cos();
+        assert!(close(bounds.min, Vector3::new(-sin, 0.0, 0.0)));
+        assert!(close(bounds.max, Vector3::new(1.0, 1.0 + cos, 0.0)));
+
+        let missing = SkinnedMesh::load(&directory.join("missing.gltf"));
+        assert!(missing.is_err());
+    }
+}
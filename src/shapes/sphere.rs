@@ -1,7 +1,12 @@
+use crate::aabb::Aabb;
 use crate::hit::{HitRecord, Hittable};
 use crate::material::Material;
+use crate::material_table::MaterialTable;
+use crate::onb::Onb;
 use crate::ray::Ray;
+use crate::statistics::SceneStatistics;
 use crate::vector3::Vector3;
+use std::f64::consts::PI;
 use std::sync::Arc;
 
 /// Represents a sphere in 3D space.
@@ -42,19 +47,42 @@ impl Sphere {
     ///
     /// # Returns
     ///
-    /// A tuple containing the spherical coordinates (u, v).
+    /// A tuple containing the spherical coordinates (u, v), with u in [0, 1) and v in [0, 1].
     fn get_sphere_uv(p: Vector3) -> (f64, f64) {
         let phi = (-p.z).atan2(p.x) + std::f64::consts::PI;
-        let theta = (-p.y).acos();
+        // Rounding may push the normal slightly past the poles.
+        let theta = (-p.y).clamp(-1.0, 1.0).acos();
 
-        let u = phi / (2.0 * std::f64::consts::PI);
+        let u = (phi / (2.0 * std::f64::consts::PI)).rem_euclid(1.0);
         let v = theta / std::f64::consts::PI;
         (u, v)
     }
+
+    /// Generates a random direction inside the cone subtended by a sphere, around the z-axis.
+    ///
+    /// # Arguments
+    ///
+    /// * `radius` - The radius of the sphere.
+    /// * `distance_squared` - The squared distance from the origin to the center of the sphere.
+    ///
+    /// # Returns
+    ///
+    /// The generated direction, uniformly distributed over the solid angle of the cone.
+    fn random_to_sphere(radius: f64, distance_squared: f64) -> Vector3 {
+        let r1 = fastrand::f64();
+        let r2 = fastrand::f64();
+        let z = 1.0 + r2 * ((1.0 - radius * radius / distance_squared).sqrt() - 1.0);
+
+        let phi = 2.0 * PI * r1;
+        let x = phi.cos() * (1.0 - z * z).sqrt();
+        let y = phi.sin() * (1.0 - z * z).sqrt();
+        Vector3::new(x, y, z)
+    }
 }
 
 impl Hittable for Sphere {
     /// Checks if a ray hits the sphere within a given interval.
+    /// Intersections behind the origin of the ray are never reported.
     ///
     /// # Arguments
     ///
@@ -64,7 +92,7 @@ impl Hittable for Sphere {
     /// # Returns
     ///
     /// An `Option` containing the `HitRecord` if an intersection is found, or `None` if no intersection is found.
-    fn hit(&self, ray: &Ray, interval: (f64, f64)) -> Option<HitRecord> {
+    fn hit(&self, ray: &Ray, interval: (f64, f64)) -> Option<HitRecord<'_>> {
         let oc = ray.origin - self.center;
         let a = ray.direction.dot(&ray.direction);
         let b = 2.0 * ray.direction.dot(&oc);
@@ -79,9 +107,10 @@ impl Hittable for Sphere {
         let first_root = (-b - sqrt_d) / (2.0 * a);
         let second_root = (-b + sqrt_d) / (2.0 * a);
 
-        let solution = if first_root > interval.0 {
+        let t_min = interval.0.max(0.0);
+        let solution = if first_root > t_min {
             first_root
-        } else if second_root > interval.0 {
+        } else if second_root > t_min {
             second_root
         } else {
             return None;
@@ -95,9 +124,104 @@ impl Hittable for Sphere {
         let (u, v) = Sphere::get_sphere_uv(outward_normal);
         let mut hit = HitRecord::new(solution, ray.point_at(solution), &*self.material, u, v);
         hit.set_face_normal(ray, &outward_normal);
+        // u follows the longitude eastwards, once around the sphere, and v the latitude
+        // northwards, from pole to pole.
+        let n = outward_normal;
+        let radius = self.radius.abs();
+        let north = Vector3::new(0.0, 1.0, 0.0) - n.y * n;
+        let north_length = north.length();
+        hit.set_tangents(
+            2.0 * std::f64::consts::PI * radius * Vector3::new(n.z, 0.0, -n.x),
+            if north_length > 0.0 {
+                std::f64::consts::PI * radius / north_length * north
+            } else {
+                north
+            },
+        );
 
         Some(hit)
     }
+
+    fn is_emissive(&self) -> bool {
+        self.material.is_emissive()
+    }
+
+    fn power(&self) -> Vector3 {
+        self.material.power() * (4.0 * PI * self.radius * self.radius)
+    }
+
+    /// Returns the probability density of sampling a direction inside the cone subtended by the sphere.
+    /// Points inside the sphere sample all directions uniformly.
+    ///
+    /// # Arguments
+    ///
+    /// * `origin` - The point from which the sphere is sampled.
+    /// * `direction` - The direction to evaluate.
+    ///
+    /// # Returns
+    ///
+    /// The probability density with respect to solid angle, or zero if the direction misses the sphere.
+    fn pdf_value(&self, origin: &Vector3, direction: &Vector3) -> f64 {
+        let distance_squared = (self.center - *origin).dot(&(self.center - *origin));
+        if distance_squared <= self.radius * self.radius {
+            return 1.0 / (4.0 * PI);
+        }
+
+        if self
+            .hit(&Ray::new(*origin, *direction), (0.001, f64::INFINITY))
+            .is_none()
+        {
+            return 0.0;
+        }
+
+        let cos_theta_max = (1.0 - self.radius * self.radius / distance_squared).sqrt();
+        let solid_angle = 2.0 * PI * (1.0 - cos_theta_max);
+        1.0 / solid_angle
+    }
+
+    /// Generates a direction inside the cone subtended by the sphere.
+    ///
+    /// # Arguments
+    ///
+    /// * `origin` - The point from which the sphere is sampled.
+    ///
+    /// # Returns
+    ///
+    /// The generated direction.
+    fn random(&self, origin: &Vector3) -> Vector3 {
+        let direction = self.center - *origin;
+        let distance_squared = direction.dot(&direction);
+        if distance_squared <= self.radius * self.radius {
+            return Vector3::random_unit_vector();
+        }
+
+        let onb = Onb::new(&direction);
+        onb.local_to_world(&Sphere::random_to_sphere(self.radius, distance_squared))
+    }
+
+    fn collect_materials(&self, table: &mut MaterialTable) {
+        table.add(&self.material);
+    }
+
+    fn collect_statistics(&self, statistics: &mut SceneStatistics) {
+        statistics.add_primitive("Sphere", std::mem::size_of_val(self));
+        self.material.collect_statistics(statistics);
+    }
+
+    fn closest_point(&self, point: &Vector3) -> Option<Vector3> {
+        let offset = *point - self.center;
+        let direction = if offset.is_near_zero() {
+            Vector3::new(0.0, 1.0, 0.0)
+        } else {
+            offset.normalize()
+        };
+        Some(self.center + self.radius * direction)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let extent = Vector3::new(self.radius, self.radius, self.radius);
+        Some(Aabb::new(self.center - extent, self.center + extent))
+    }
 }
 
 #[cfg(test)]
@@ -157,4 +281,39 @@ mod tests {
         assert_eq!(hit_record.poz, Vector3::new(0.0, 0.0, -10.0));
         assert_eq!(hit_record.normal, Vector3::new(-0.0, -0.0, 1.0));
     }
+
+    #[test]
+    fn test_sphere_uv_range() {
+        let poles = [
+            Vector3::new(0.0, 1.0 + 1e-15, 0.0),
+            Vector3::new(0.0, -1.0 - 1e-15, 0.0),
+        ];
+        for p in poles {
+            let (u, v) = Sphere::get_sphere_uv(p);
+            assert!(!u.is_nan() && !v.is_nan());
+        }
+
+        // The seam lies along the negative x-axis, where phi reaches 2 pi.
+        let (u, _) = Sphere::get_sphere_uv(Vector3::new(-1.0, 0.0, -0.0));
+        assert!((0.0..1.0).contains(&u), "{}", u);
+    }
+
+    #[test]
+    fn test_sphere_tangents_follow_uv() {
+        let material = Arc::new(Lambertian::new(Vector3::new(1.0, 1.0, 1.0)));
+        let sphere = Sphere::new(Vector3::new(0.0, 0.0, 0.0), 1.0, material);
+        let ray = Ray::new(Vector3::new(2.0, 1.5, 3.0), Vector3::new(-2.0, -1.0, -3.0));
+        let record = sphere.hit(&ray, (0.001, f64::INFINITY)).unwrap();
+        let frame = record.tangent_frame();
+
+        // Stepping along the tangent frame moves the texture coordinates along their axes.
+        let step = 1e-4;
+        let (u, v) = Sphere::get_sphere_uv(record.poz);
+        let (along_u, same_v) = Sphere::get_sphere_uv((record.poz + step * frame.u).normalize());
+        let (same_u, along_v) = Sphere::get_sphere_uv((record.poz + step * frame.v).normalize());
+        assert!(along_u > u && (same_v - v).abs() < 1e-7);
+        assert!(along_v > v && (same_u - u).abs() < 1e-7);
+        assert!(frame.u.dot(&record.normal).abs() < 1e-12);
+        assert!(frame.v.dot(&record.normal).abs() < 1e-12);
+    }
 }
@@ -0,0 +1,700 @@
+//! Generators of triangle meshes sweeping a 2D profile through space: extrusions along a straight
+//! axis, such as moldings, beams and rods, and lathes revolving a profile around an axis, such as
+//! vases, bottles and rings. A profile is a short list of points, so a whole shape takes a few
+//! lines to describe.
+
+use crate::material::Material;
+use crate::shapes::mesh::TriangleMesh;
+use crate::shapes::polygon::Polygon;
+use crate::vector3::Vector3;
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+/// The largest angle in degrees between two edges of a profile for the surface to be smooth across
+/// the point between them. Sharper corners keep a crease.
+const SMOOTH_ANGLE: f64 = 30.0;
+
+/// Represents an edge of a profile, with the normals of the surface swept from it.
+struct ProfileEdge {
+    /// The point at the start of the edge.
+    start: (f64, f64),
+    /// The point at the end of the edge.
+    end: (f64, f64),
+    /// The unit normal of the surface at the start of the edge.
+    start_normal: (f64, f64),
+    /// The unit normal of the surface at the end of the edge.
+    end_normal: (f64, f64),
+    /// The length of the profile up to the start of the edge, divided by its total length.
+    start_length: f64,
+    /// The length of the profile up to the end of the edge, divided by its total length.
+    end_length: f64,
+}
+
+/// Splits a profile into its edges, with normals on the right of the direction of travel, smoothed
+/// across the points where the profile bends gently.
+///
+/// # Arguments
+///
+/// * `profile` - The points of the profile, repeated points being skipped.
+/// * `closed` - Whether the last point is joined back to the first one.
+///
+/// # Returns
+///
+/// The edges of the profile.
+fn profile_edges(profile: &[(f64, f64)], closed: bool) -> Vec<ProfileEdge> {
+    let mut points: Vec<(f64, f64)> = Vec::with_capacity(profile.len());
+    for &point in profile {
+        if points.last() != Some(&point) {
+            points.push(point);
+        }
+    }
+    if closed && points.len() > 1 && points.first() == points.last() {
+        points.pop();
+    }
+    let count = if closed {
+        points.len()
+    } else {
+        points.len().saturating_sub(1)
+    };
+
+    let edges: Vec<_> = (0..count)
+        .map(|i| {
+            let (start, end) = (points[i], points[(i + 1) % points.len()]);
+            let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+            let length = dx.hypot(dy);
+            (start, end, (dy / length, -dx / length), length)
+        })
+        .collect();
+    let total: f64 = edges.iter().map(|edge| edge.3).sum();
+
+    // The normal of the surface at the point between two edges, shared by both if the profile
+    // bends gently there.
+    let joint = |own: (f64, f64), other: Option<(f64, f64)>| match other {
+        Some(other) if own.0 * other.0 + own.1 * other.1 >= SMOOTH_ANGLE.to_radians().cos() => {
+            let (x, y) = (own.0 + other.0, own.1 + other.1);
+            let length = x.hypot(y);
+            (x / length, y / length)
+        }
+        _ => own,
+    };
+
+    let mut travelled = 0.0;
+    (0..edges.len())
+        .map(|i| {
+            let (start, end, normal, length) = edges[i];
+            let previous = if i > 0 || closed {
+                Some(edges[(i + edges.len() - 1) % edges.len()].2)
+            } else {
+                None
+            };
+            let next = if i + 1 < edges.len() || closed {
+                Some(edges[(i + 1) % edges.len()].2)
+            } else {
+                None
+            };
+            let edge = ProfileEdge {
+                start,
+                end,
+                start_normal: joint(normal, previous),
+                end_normal: joint(normal, next),
+                start_length: travelled / total,
+                end_length: (travelled + length) / total,
+            };
+            travelled += length;
+            edge
+        })
+        .collect()
+}
+
+/// Turns a closed profile counterclockwise, so the normals of its edges point out of it.
+///
+/// # Arguments
+///
+/// * `profile` - The points of the profile.
+///
+/// # Returns
+///
+/// The points of the profile in counterclockwise order.
+fn counterclockwise(profile: &[(f64, f64)]) -> Vec<(f64, f64)> {
+    let area: f64 = (0..profile.len())
+        .map(|i| {
+            let (a, b) = (profile[i], profile[(i + 1) % profile.len()]);
+            a.0 * b.1 - b.0 * a.1
+        })
+        .sum();
+    let mut points = profile.to_vec();
+    if area < 0.0 {
+        points.reverse();
+    }
+    points
+}
+
+/// Collects the vertices and triangles of a mesh being generated.
+#[derive(Default)]
+struct MeshBuilder {
+    /// The positions of the vertices.
+    positions: Vec<Vector3>,
+    /// The normals of the vertices.
+    normals: Vec<Vector3>,
+    /// The texture coordinates of the vertices.
+    uvs: Vec<(f64, f64)>,
+    /// The triangles, as indices into the vertices.
+    triangles: Vec<[usize; 3]>,
+}
+
+impl MeshBuilder {
+    /// Adds a vertex.
+    ///
+    /// # Arguments
+    ///
+    /// * `position` - The position of the vertex.
+    /// * `normal` - The normal of the vertex.
+    /// * `uv` - The texture coordinates of the vertex.
+    ///
+    /// # Returns
+    ///
+    /// The index of the vertex.
+    fn vertex(&mut self, position: Vector3, normal: Vector3, uv: (f64, f64)) -> usize {
+        self.positions.push(position);
+        self.normals.push(normal);
+        self.uvs.push(uv);
+        self.positions.len() - 1
+    }
+
+    /// Adds a triangle, wound so its plane faces the side of its vertex normals. Triangles without
+    /// area, such as the ones meeting at the axis of a lathe, are skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `triangle` - The indices of the vertices of the triangle.
+    fn triangle(&mut self, [a, b, c]: [usize; 3]) {
+        let p = &self.positions;
+        let plane = (p[b] - p[a]).cross(&(p[c] - p[a]));
+        if plane.is_near_zero() {
+            return;
+        }
+        let normal = self.normals[a] + self.normals[b] + self.normals[c];
+        self.triangles.push(if plane.dot(&normal) < 0.0 {
+            [a, c, b]
+        } else {
+            [a, b, c]
+        });
+    }
+
+    /// Adds a quadrilateral as two triangles.
+    ///
+    /// # Arguments
+    ///
+    /// * `corners` - The indices of the vertices of the quadrilateral, in order around it.
+    fn quad(&mut self, [a, b, c, d]: [usize; 4]) {
+        self.triangle([a, b, c]);
+        self.triangle([a, c, d]);
+    }
+
+    /// Builds the mesh from the vertices and triangles added.
+    ///
+    /// # Arguments
+    ///
+    /// * `material` - The material of the mesh.
+    fn build(self, material: Arc<dyn Material>) -> TriangleMesh {
+        TriangleMesh::new(
+            self.positions,
+            self.normals,
+            self.uvs,
+            self.triangles,
+            material,
+        )
+    }
+}
+
+/// Generates the mesh of a profile in the XY plane extruded along the Z-axis. Closed profiles are
+/// capped at both ends. The u texture coordinate runs along the profile and v along the axis.
+///
+/// # Arguments
+///
+/// * `profile` - The points of the profile, at least 2, or 3 for a closed profile. The front face
+///   of an open profile is on the right of its direction of travel, as seen from the positive
+///   Z-axis; closed profiles face outwards.
+/// * `closed` - Whether the last point is joined back to the first one. Closed profiles must not
+///   cross themselves.
+/// * `length` - The length of the extrusion, from z = 0 to z = `length`.
+/// * `material` - The material of the mesh.
+///
+/// # Returns
+///
+/// The generated mesh.
+pub fn extrude(
+    profile: &[(f64, f64)],
+    closed: bool,
+    length: f64,
+    material: Arc<dyn Material>,
+) -> TriangleMesh {
+    let profile = if closed {
+        counterclockwise(profile)
+    } else {
+        profile.to_vec()
+    };
+    let mut builder = MeshBuilder::default();
+    let point = |(x, y): (f64, f64), z: f64| Vector3::new(x, y, z);
+    let normal = |(x, y): (f64, f64)| Vector3::new(x, y, 0.0);
+
+    for edge in profile_edges(&profile, closed) {
+        let corners = [
+            builder.vertex(
+                point(edge.start, 0.0),
+                normal(edge.start_normal),
+                (edge.start_length, 0.0),
+            ),
+            builder.vertex(
+                point(edge.end, 0.0),
+                normal(edge.end_normal),
+                (edge.end_length, 0.0),
+            ),
+            builder.vertex(
+                point(edge.end, length),
+                normal(edge.end_normal),
+                (edge.end_length, 1.0),
+            ),
+            builder.vertex(
+                point(edge.start, length),
+                normal(edge.start_normal),
+                (edge.start_length, 1.0),
+            ),
+        ];
+        builder.quad(corners);
+    }
+
+    if closed {
+        let (min, max) = profile.iter().fold(
+            (
+                (f64::INFINITY, f64::INFINITY),
+                (f64::NEG_INFINITY, f64::NEG_INFINITY),
+            ),
+            |(min, max), p| {
+                (
+                    (min.0.min(p.0), min.1.min(p.1)),
+                    (max.0.max(p.0), max.1.max(p.1)),
+                )
+            },
+        );
+        let triangles = Polygon::triangulate(&profile);
+        for (z, facing) in [(0.0, -1.0), (length, 1.0)] {
+            let first = builder.positions.len();
+            for &p in &profile {
+                let uv = (
+                    (p.0 - min.0) / (max.0 - min.0),
+                    (p.1 - min.1) / (max.1 - min.1),
+                );
+                builder.vertex(point(p, z), Vector3::new(0.0, 0.0, facing), uv);
+            }
+            for triangle in &triangles {
+                builder.triangle(triangle.map(|i| first + i));
+            }
+        }
+    }
+
+    builder.build(material)
+}
+
+/// Generates the mesh of a profile revolved around the Y-axis, a surface of revolution such as a
+/// turned vase. The u texture coordinate runs around the axis and v along the profile.
+///
+/// # Arguments
+///
+/// * `profile` - The points of the profile as (distance from the axis, height), at least 2, or 3
+///   for a closed profile. Points on the axis close the surface there. The front face of an open
+///   profile is on the right of its direction of travel, so a profile going up the outside of a
+///   vase faces outwards; closed profiles face outwards.
+/// * `closed` - Whether the last point is joined back to the first one, sweeping a solid ring.
+/// * `segments` - The number of steps around the axis, at least 3.
+/// * `material` - The material of the mesh.
+///
+/// # Returns
+///
+/// The generated mesh.
+pub fn lathe(
+    profile: &[(f64, f64)],
+    closed: bool,
+    segments: usize,
+    material: Arc<dyn Material>,
+) -> TriangleMesh {
+    let profile = if closed {
+        counterclockwise(profile)
+    } else {
+        profile.to_vec()
+    };
+    let segments = segments.max(3);
+    let mut builder = MeshBuilder::default();
+
+    let edges = profile_edges(&profile, closed);
+    for step in 0..segments {
+        let angles = [step, step + 1].map(|i| 2.0 * PI * i as f64 / segments as f64);
+        let revolve =
+            |(r, y): (f64, f64), angle: f64| Vector3::new(r * angle.cos(), y, -r * angle.sin());
+        for edge in &edges {
+            let mut corners = [0; 4];
+            let ends = [
+                (edge.start, edge.start_normal, edge.start_length),
+                (edge.end, edge.end_normal, edge.end_length),
+            ];
+            for (corner, (angle, (point, normal, v))) in corners.iter_mut().zip([
+                (angles[0], ends[0]),
+                (angles[0], ends[1]),
+                (angles[1], ends[1]),
+                (angles[1], ends[0]),
+            ]) {
+                let u = angle / (2.0 * PI);
+                *corner = builder.vertex(revolve(point, angle), revolve(normal, angle), (u, v));
+            }
+            builder.quad(corners);
+        }
+    }
+
+    builder.build(material)
+}
+
+/// Represents a flat region to extrude: an outline with holes, such as the shape of a letter.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Region {
+    /// The outline of the region.
+    pub outline: Vec<(f64, f64)>,
+    /// The holes of the region, inside its outline.
+    pub holes: Vec<Vec<(f64, f64)>>,
+}
+
+/// Moves the points of a closed contour inwards, away from the side its edge normals face.
+///
+/// # Arguments
+///
+/// * `edges` - The edges of the contour.
+/// * `distance` - The distance between the edges of the contour and the moved edges.
+///
+/// # Returns
+///
+/// The moved start point of every edge.
+fn inset(edges: &[ProfileEdge], distance: f64) -> Vec<(f64, f64)> {
+    (0..edges.len())
+        .map(|i| {
+            let previous = &edges[(i + edges.len() - 1) % edges.len()];
+            let edge = &edges[i];
+            let normal = |(dx, dy): (f64, f64)| {
+                let length = dx.hypot(dy);
+                (dy / length, -dx / length)
+            };
+            let a = normal((
+                previous.end.0 - previous.start.0,
+                previous.end.1 - previous.start.1,
+            ));
+            let b = normal((edge.end.0 - edge.start.0, edge.end.1 - edge.start.1));
+            let (x, y) = (a.0 + b.0, a.1 + b.1);
+            let length = x.hypot(y);
+            if length < 1e-9 {
+                return edge.start;
+            }
+            let miter = (x / length, y / length);
+            // The corner moves further than the edges, limited at sharp corners.
+            let scale = distance / (miter.0 * b.0 + miter.1 * b.1).max(0.5);
+            (
+                edge.start.0 - scale * miter.0,
+                edge.start.1 - scale * miter.1,
+            )
+        })
+        .collect()
+}
+
+/// Generates the mesh of flat regions in the XY plane extruded along the Z-axis, from z = 0 to
+/// z = `depth`, capped at both ends. The front edges can be cut at 45 degrees by a bevel. The
+/// caps are textured across the box enclosing each region, and the sides as with `extrude`.
+///
+/// # Arguments
+///
+/// * `regions` - The regions, with outlines and holes that do not cross themselves or each other.
+/// * `depth` - The length of the extrusion.
+/// * `bevel` - The width of the bevel around the front cap, zero for sharp edges. It must be
+///   smaller than the depth and than half the thinnest part of the regions.
+/// * `material` - The material of the mesh.
+///
+/// # Returns
+///
+/// The generated mesh, or `None` if no region has an outline.
+pub fn extrude_regions(
+    regions: &[Region],
+    depth: f64,
+    bevel: f64,
+    material: Arc<dyn Material>,
+) -> Option<TriangleMesh> {
+    let bevel = bevel.clamp(0.0, depth);
+    let mut builder = MeshBuilder::default();
+    let point = |(x, y): (f64, f64), z: f64| Vector3::new(x, y, z);
+
+    for region in regions.iter().filter(|region| region.outline.len() >= 3) {
+        // The outline goes counterclockwise and the holes clockwise, so every edge normal points
+        // out of the region.
+        let outline = counterclockwise(&region.outline);
+        let holes: Vec<Vec<(f64, f64)>> = region
+            .holes
+            .iter()
+            .filter(|hole| hole.len() >= 3)
+            .map(|hole| {
+                let mut hole = counterclockwise(hole);
+                hole.reverse();
+                hole
+            })
+            .collect();
+
+        let mut front: Vec<Vec<(f64, f64)>> = Vec::new();
+        for contour in std::iter::once(&outline).chain(&holes) {
+            let edges = profile_edges(contour, true);
+            let inner = if bevel > 0.0 {
+                inset(&edges, bevel)
+            } else {
+                edges.iter().map(|edge| edge.start).collect()
+            };
+
+            for (i, edge) in edges.iter().enumerate() {
+                let normal = |(x, y): (f64, f64)| Vector3::new(x, y, 0.0);
+                let side = [
+                    (edge.start, 0.0, edge.start_normal, edge.start_length),
+                    (edge.end, 0.0, edge.end_normal, edge.end_length),
+                    (edge.end, depth - bevel, edge.end_normal, edge.end_length),
+                    (
+                        edge.start,
+                        depth - bevel,
+                        edge.start_normal,
+                        edge.start_length,
+                    ),
+                ]
+                .map(|(p, z, n, u)| builder.vertex(point(p, z), normal(n), (u, z / depth)));
+                builder.quad(side);
+
+                if bevel > 0.0 {
+                    let slope = |(x, y): (f64, f64)| Vector3::new(x, y, 1.0);
+                    let next = (i + 1) % edges.len();
+                    let band = [
+                        (
+                            edge.start,
+                            depth - bevel,
+                            edge.start_normal,
+                            edge.start_length,
+                        ),
+                        (edge.end, depth - bevel, edge.end_normal, edge.end_length),
+                        (inner[next], depth, edge.end_normal, edge.end_length),
+                        (inner[i], depth, edge.start_normal, edge.start_length),
+                    ]
+                    .map(|(p, z, n, u)| builder.vertex(point(p, z), slope(n), (u, z / depth)));
+                    builder.quad(band);
+                }
+            }
+            // The edges skip repeated points, so the caps use the points of the edges too.
+            front.push(inner);
+        }
+
+        let back: Vec<Vec<(f64, f64)>> = std::iter::once(&outline)
+            .chain(&holes)
+            .map(|contour| {
+                profile_edges(contour, true)
+                    .iter()
+                    .map(|edge| edge.start)
+                    .collect()
+            })
+            .collect();
+        let joined = Polygon::bridge_holes(&back[0], &back[1..]);
+        let joined_points: Vec<(f64, f64)> = joined
+            .iter()
+            .map(|&(contour, index)| back[contour][index])
+            .collect();
+        let triangles = Polygon::triangulate(&joined_points);
+
+        let (min, max) = back[0].iter().fold(
+            (
+                (f64::INFINITY, f64::INFINITY),
+                (f64::NEG_INFINITY, f64::NEG_INFINITY),
+            ),
+            |(min, max), p| {
+                (
+                    (min.0.min(p.0), min.1.min(p.1)),
+                    (max.0.max(p.0), max.1.max(p.1)),
+                )
+            },
+        );
+        let uv = |p: (f64, f64)| {
+            (
+                (p.0 - min.0) / (max.0 - min.0),
+                (p.1 - min.1) / (max.1 - min.1),
+            )
+        };
+        for (contours, z, facing) in [(&back, 0.0, -1.0), (&front, depth, 1.0)] {
+            let first = builder.positions.len();
+            for &(contour, index) in &joined {
+                let p = contours[contour][index];
+                builder.vertex(point(p, z), Vector3::new(0.0, 0.0, facing), uv(p));
+            }
+            for triangle in &triangles {
+                builder.triangle(triangle.map(|i| first + i));
+            }
+        }
+    }
+
+    if builder.triangles.is_empty() {
+        return None;
+    }
+    Some(builder.build(material))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hit::Hittable;
+    use crate::material::Lambertian;
+    use crate::ray::Ray;
+
+    fn material() -> Arc<dyn Material> {
+        Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5)))
+    }
+
+    /// The points of a circle, counterclockwise.
+    fn circle(center: (f64, f64), radius: f64, points: usize) -> Vec<(f64, f64)> {
+        (0..points)
+            .map(|i| {
+                let angle = 2.0 * PI * i as f64 / points as f64;
+                (
+                    center.0 + radius * angle.cos(),
+                    center.1 + radius * angle.sin(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_extrude_square() {
+        // Clockwise, so it is turned around to face outwards.
+        let square = [(0.0, 0.0), (0.0, 1.0), (1.0, 1.0), (1.0, 0.0)];
+        let mesh = extrude(&square, true, 2.0, material());
+
+        let cap = Ray::new(Vector3::new(0.5, 0.25, 5.0), Vector3::new(0.0, 0.0, -1.0));
+        let record = mesh.hit(&cap, (0.001, f64::INFINITY)).unwrap();
+        assert!((record.t - 3.0).abs() < 1e-9);
+        assert!(record.front_face);
+        assert!((record.normal - Vector3::new(0.0, 0.0, 1.0)).length() < 1e-9);
+        assert!((record.u - 0.5).abs() < 1e-9 && (record.v - 0.25).abs() < 1e-9);
+
+        // The corners are sharp, so the sides keep flat normals.
+        let side = Ray::new(Vector3::new(5.0, 0.9, 0.5), Vector3::new(-1.0, 0.0, 0.0));
+        let record = mesh.hit(&side, (0.001, f64::INFINITY)).unwrap();
+        assert!((record.t - 4.0).abs() < 1e-9);
+        assert!(record.front_face);
+        assert!((record.normal - Vector3::new(1.0, 0.0, 0.0)).length() < 1e-9);
+        assert!((record.v - 0.25).abs() < 1e-9);
+
+        let inside = Ray::new(Vector3::new(0.5, 0.5, 1.0), Vector3::new(0.0, -1.0, 0.0));
+        let record = mesh.hit(&inside, (0.001, f64::INFINITY)).unwrap();
+        assert!(!record.front_face);
+    }
+
+    #[test]
+    fn test_extrude_smooth_rod() {
+        let mesh = extrude(&circle((0.0, 0.0), 1.0, 32), true, 4.0, material());
+        let ray = Ray::new(Vector3::new(5.0, 0.3, 2.0), Vector3::new(-1.0, 0.0, 0.0));
+        let record = mesh.hit(&ray, (0.001, f64::INFINITY)).unwrap();
+
+        // The normal follows the circle rather than the flat side the ray hits.
+        let radial = Vector3::new(record.poz.x, record.poz.y, 0.0).normalize();
+        assert!(
+            (record.normal - radial).length() < 0.01,
+            "{:?}",
+            record.normal
+        );
+        assert!(record.front_face);
+    }
+
+    #[test]
+    fn test_lathe() {
+        // An open cylinder of radius 1 and height 2, going up so it faces outwards.
+        let mesh = lathe(&[(1.0, 0.0), (1.0, 2.0)], false, 64, material());
+        let outside = Ray::new(Vector3::new(5.0, 1.0, 0.0), Vector3::new(-1.0, 0.0, 0.0));
+        let record = mesh.hit(&outside, (0.001, f64::INFINITY)).unwrap();
+        assert!((record.t - 4.0).abs() < 1e-9);
+        assert!(record.front_face);
+        assert!((record.normal - Vector3::new(1.0, 0.0, 0.0)).length() < 1e-9);
+        assert!((record.v - 0.5).abs() < 1e-9);
+
+        let inside = Ray::new(Vector3::new(0.0, 1.0, 0.0), Vector3::new(0.0, 0.0, 1.0));
+        let record = mesh.hit(&inside, (0.001, f64::INFINITY)).unwrap();
+        assert!(!record.front_face);
+        assert!((record.normal - Vector3::new(0.0, 0.0, -1.0)).length() < 1e-9);
+        assert!((record.u - 0.75).abs() < 1e-9);
+
+        // A closed circle revolved into a torus around the axis.
+        let torus = lathe(&circle((2.0, 0.0), 0.5, 24), true, 48, material());
+        let hole = Ray::new(Vector3::new(0.0, 5.0, 0.0), Vector3::new(0.0, -1.0, 0.0));
+        assert!(torus.hit(&hole, (0.001, f64::INFINITY)).is_none());
+        let ring = Ray::new(Vector3::new(2.0, 5.0, 0.0), Vector3::new(0.0, -1.0, 0.0));
+        let record = torus.hit(&ring, (0.001, f64::INFINITY)).unwrap();
+        assert!((record.t - 4.5).abs() < 0.01, "{}", record.t);
+        assert!(record.front_face);
+        assert!(record.normal.y > 0.99);
+    }
+
+    #[test]
+    fn test_extrude_regions() {
+        // A square frame, beveled at the front.
+        let region = Region {
+            outline: vec![(0.0, 0.0), (4.0, 0.0), (4.0, 4.0), (0.0, 4.0)],
+            holes: vec![vec![(1.0, 1.0), (3.0, 1.0), (3.0, 3.0), (1.0, 3.0)]],
+        };
+        let mesh = extrude_regions(&[region], 1.0, 0.2, material()).unwrap();
+        let down = Vector3::new(0.0, 0.0, -1.0);
+
+        // The front cap is hit on the frame and the ray passes through the hole.
+        let record = mesh
+            .hit(
+                &Ray::new(Vector3::new(0.5, 2.0, 5.0), down),
+                (0.001, f64::INFINITY),
+            )
+            .unwrap();
+        assert!((record.t - 4.0).abs() < 1e-9);
+        assert!((record.normal - Vector3::new(0.0, 0.0, 1.0)).length() < 1e-9);
+        assert!(mesh
+            .hit(
+                &Ray::new(Vector3::new(2.0, 2.0, 5.0), down),
+                (0.001, f64::INFINITY)
+            )
+            .is_none());
+
+        // Near the outer edge the ray hits the bevel, sloping at 45 degrees.
+        let record = mesh
+            .hit(
+                &Ray::new(Vector3::new(0.1, 2.0, 5.0), down),
+                (0.001, f64::INFINITY),
+            )
+            .unwrap();
+        assert!((record.t - 4.1).abs() < 1e-9, "{}", record.t);
+        let slope = Vector3::new(-1.0, 0.0, 1.0).normalize();
+        assert!(
+            (record.normal - slope).length() < 1e-9,
+            "{:?}",
+            record.normal
+        );
+
+        // The walls of the hole face into it, and the back cap faces away from the front.
+        let record = mesh
+            .hit(
+                &Ray::new(Vector3::new(2.0, 2.0, 0.5), Vector3::new(-1.0, 0.0, 0.0)),
+                (0.001, f64::INFINITY),
+            )
+            .unwrap();
+        assert!((record.t - 1.0).abs() < 1e-9);
+        assert!(record.front_face);
+        assert!((record.normal - Vector3::new(1.0, 0.0, 0.0)).length() < 1e-9);
+        let record = mesh
+            .hit(
+                &Ray::new(Vector3::new(0.5, 0.5, -5.0), -down),
+                (0.001, f64::INFINITY),
+            )
+            .unwrap();
+        assert!(record.front_face);
+        assert!((record.t - 5.0).abs() < 1e-9);
+
+        assert!(extrude_regions(&[Region::default()], 1.0, 0.0, material()).is_none());
+    }
+}
@@ -0,0 +1,404 @@
+//! Text turned into geometry: the outlines of the glyphs of a TrueType or OpenType font are
+//! flattened into polygons and extruded into a mesh, so titles and labels can stand in a scene.
+
+use crate::material::Material;
+use crate::shapes::mesh::TriangleMesh;
+use crate::shapes::sweep::{extrude_regions, Region};
+use std::path::Path;
+use std::sync::Arc;
+use ttf_parser::{Face, GlyphId, OutlineBuilder};
+
+/// The number of straight segments each curve of a glyph outline is flattened into.
+const CURVE_SEGMENTS: usize = 8;
+
+/// The depth of the extrusion by default, relative to the size of the text.
+const DEFAULT_RELATIVE_DEPTH: f64 = 0.2;
+
+/// Represents a font loaded from a TrueType or OpenType file.
+pub struct Font {
+    /// The contents of the font file.
+    data: Vec<u8>,
+}
+
+impl Font {
+    /// Loads a font from a file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the font file.
+    ///
+    /// # Returns
+    ///
+    /// The font, or an error message if the file cannot be read or is not a font.
+    pub fn load(path: &Path) -> Result<Font, String> {
+        let data = std::fs::read(path)
+            .map_err(|e| format!("Failed to read font {}: {}", path.display(), e))?;
+        Font::from_bytes(data).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// Creates a font from the contents of a font file.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The contents of the font file.
+    ///
+    /// # Returns
+    ///
+    /// The font, or an error message if the data is not a font.
+    pub fn from_bytes(data: Vec<u8>) -> Result<Font, String> {
+        Face::parse(&data, 0).map_err(|e| format!("Failed to parse font: {}", e))?;
+        Ok(Font { data })
+    }
+
+    /// Returns the parsed font face.
+    fn face(&self) -> Face<'_> {
+        Face::parse(&self.data, 0).expect("the font was parsed when it was created")
+    }
+
+    /// Lays out a string and returns the regions covered by its glyphs, on a baseline along the
+    /// X-axis starting at the origin. Every line break moves the next line down.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The string to lay out.
+    /// * `size` - The height of the em square of the font in scene units.
+    ///
+    /// # Returns
+    ///
+    /// The regions covered by the glyphs, outlines with holes.
+    pub fn regions(&self, text: &str, size: f64) -> Vec<Region> {
+        let face = self.face();
+        let scale = size / face.units_per_em() as f64;
+        let line_height =
+            (face.ascender() as f64 - face.descender() as f64 + face.line_gap() as f64) * scale;
+
+        let mut regions = Vec::new();
+        let (mut x, mut y) = (0.0, 0.0);
+        for character in text.chars() {
+            if character == '\n' {
+                x = 0.0;
+                y -= line_height;
+                continue;
+            }
+            let glyph = face.glyph_index(character).unwrap_or(GlyphId(0));
+            let mut outline = GlyphOutline {
+                origin: (x, y),
+                scale,
+                contours: Vec::new(),
+            };
+            face.outline_glyph(glyph, &mut outline);
+            regions.extend(group_contours(outline.contours));
+            x += face.glyph_hor_advance(glyph).unwrap_or(0) as f64 * scale;
+        }
+        regions
+    }
+}
+
+/// Collects the contours of a glyph outline, flattening its curves into straight segments.
+struct GlyphOutline {
+    /// The position of the origin of the glyph in scene units.
+    origin: (f64, f64),
+    /// The size of a font unit in scene units.
+    scale: f64,
+    /// The contours collected so far, the last one being drawn.
+    contours: Vec<Vec<(f64, f64)>>,
+}
+
+impl GlyphOutline {
+    /// Returns the point of the scene at a position of the glyph.
+    fn point(&self, x: f32, y: f32) -> (f64, f64) {
+        (
+            self.origin.0 + x as f64 * self.scale,
+            self.origin.1 + y as f64 * self.scale,
+        )
+    }
+
+    /// Returns the last point of the contour being drawn.
+    fn last(&self) -> (f64, f64) {
+        self.contours
+            .last()
+            .and_then(|contour| contour.last())
+            .copied()
+            .unwrap_or(self.origin)
+    }
+
+    /// Adds a point to the contour being drawn.
+    fn push(&mut self, point: (f64, f64)) {
+        if let Some(contour) = self.contours.last_mut() {
+            contour.push(point);
+        }
+    }
+}
+
+impl OutlineBuilder for GlyphOutline {
+    fn move_to(&mut self, x: f32, y: f32) {
+        let point = self.point(x, y);
+        self.contours.push(vec![point]);
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        let point = self.point(x, y);
+        self.push(point);
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        let (p0, p1, p2) = (self.last(), self.point(x1, y1), self.point(x, y));
+        for i in 1..=CURVE_SEGMENTS {
+            let t = i as f64 / CURVE_SEGMENTS as f64;
+            let s = 1.0 - t;
+            let blend = |a: f64, b: f64, c: f64| s * s * a + 2.0 * s * t * b + t * t * c;
+            self.push((blend(p0.0, p1.0, p2.0), blend(p0.1, p1.1, p2.1)));
+        }
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        let p0 = self.last();
+        let (p1, p2, p3) = (self.point(x1, y1), self.point(x2, y2), self.point(x, y));
+        for i in 1..=CURVE_SEGMENTS {
+            let t = i as f64 / CURVE_SEGMENTS as f64;
+            let s = 1.0 - t;
+            let blend = |a: f64, b: f64, c: f64, d: f64| {
+                s * s * s * a + 3.0 * s * s * t * b + 3.0 * s * t * t * c + t * t * t * d
+            };
+            self.push((blend(p0.0, p1.0, p2.0, p3.0), blend(p0.1, p1.1, p2.1, p3.1)));
+        }
+    }
+
+    fn close(&mut self) {
+        if let Some(contour) = self.contours.last_mut() {
+            if contour.len() > 1 && contour.first() == contour.last() {
+                contour.pop();
+            }
+        }
+    }
+}
+
+/// Checks if a point lies inside a closed contour, with the even-odd rule.
+///
+/// # Arguments
+///
+/// * `point` - The point to test.
+/// * `contour` - The points of the contour.
+fn contains(point: (f64, f64), contour: &[(f64, f64)]) -> bool {
+    let mut inside = false;
+    for i in 0..contour.len() {
+        let (a, b) = (contour[i], contour[(i + 1) % contour.len()]);
+        if (a.1 > point.1) != (b.1 > point.1)
+            && point.0 < a.0 + (point.1 - a.1) * (b.0 - a.0) / (b.1 - a.1)
+        {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+/// Groups the contours of a glyph into regions: a contour inside an even number of others is an
+/// outline, and one inside an odd number is a hole of the smallest outline around it, whatever
+/// the direction in which the font draws them.
+///
+/// # Arguments
+///
+/// * `contours` - The contours of the glyph.
+///
+/// # Returns
+///
+/// The regions of the glyph.
+fn group_contours(contours: Vec<Vec<(f64, f64)>>) -> Vec<Region> {
+    let contours: Vec<Vec<(f64, f64)>> = contours
+        .into_iter()
+        .filter(|contour| contour.len() >= 3)
+        .collect();
+    let area = |contour: &[(f64, f64)]| {
+        (0..contour.len())
+            .map(|i| {
+                let (a, b) = (contour[i], contour[(i + 1) % contour.len()]);
+                a.0 * b.1 - b.0 * a.1
+            })
+            .sum::<f64>()
+            .abs()
+            / 2.0
+    };
+    let parents: Vec<Vec<usize>> = contours
+        .iter()
+        .enumerate()
+        .map(|(i, contour)| {
+            (0..contours.len())
+                .filter(|&j| j != i && contains(contour[0], &contours[j]))
+                .collect()
+        })
+        .collect();
+
+    let is_outline: Vec<bool> = parents
+        .iter()
+        .map(|parents| parents.len().is_multiple_of(2))
+        .collect();
+
+    let mut regions: Vec<(usize, Region)> = Vec::new();
+    for (i, contour) in contours.iter().enumerate() {
+        if is_outline[i] {
+            regions.push((
+                i,
+                Region {
+                    outline: contour.clone(),
+                    holes: Vec::new(),
+                },
+            ));
+        }
+    }
+    for (i, contour) in contours.iter().enumerate() {
+        if !is_outline[i] {
+            let parent = parents[i]
+                .iter()
+                .filter(|&&j| is_outline[j])
+                .min_by(|&&a, &&b| area(&contours[a]).total_cmp(&area(&contours[b])));
+            if let Some(region) =
+                parent.and_then(|&j| regions.iter_mut().find(|(index, _)| *index == j))
+            {
+                region.1.holes.push(contour.clone());
+            }
+        }
+    }
+    regions.into_iter().map(|(_, region)| region).collect()
+}
+
+/// Represents a string of text to turn into a mesh.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Text {
+    /// The string, with line breaks between lines.
+    content: String,
+    /// The height of the em square of the font in scene units.
+    size: f64,
+    /// The depth of the extrusion.
+    depth: f64,
+    /// The width of the bevel around the front faces of the glyphs.
+    bevel: f64,
+}
+
+impl Text {
+    /// Creates a new `Text`, extruded by a fifth of its size without a bevel.
+    ///
+    /// # Arguments
+    ///
+    /// * `content` - The string, with line breaks between lines.
+    /// * `size` - The height of the em square of the font in scene units.
+    ///
+    /// # Returns
+    ///
+    /// A new `Text` instance.
+    pub fn new(content: &str, size: f64) -> Text {
+        Text {
+            content: content.to_string(),
+            size,
+            depth: DEFAULT_RELATIVE_DEPTH * size,
+            bevel: 0.0,
+        }
+    }
+
+    /// Sets the depth of the extrusion.
+    ///
+    /// # Arguments
+    ///
+    /// * `depth` - The depth of the extrusion in scene units.
+    ///
+    /// # Returns
+    ///
+    /// The `Text` with the given depth.
+    pub fn with_depth(mut self, depth: f64) -> Text {
+        self.depth = depth;
+        self
+    }
+
+    /// Cuts the front edges of the glyphs at 45 degrees, so they catch the light.
+    ///
+    /// # Arguments
+    ///
+    /// * `bevel` - The width of the bevel in scene units, smaller than the depth and than half
+    ///   the thinnest stroke of the glyphs.
+    ///
+    /// # Returns
+    ///
+    /// The `Text` with the given bevel.
+    pub fn with_bevel(mut self, bevel: f64) -> Text {
+        self.bevel = bevel;
+        self
+    }
+
+    /// Turns the text into a mesh, its baseline along the X-axis from the origin and its glyphs
+    /// extruded from z = 0 towards positive z, where their front faces are.
+    ///
+    /// # Arguments
+    ///
+    /// * `font` - The font of the text.
+    /// * `material` - The material of the mesh.
+    ///
+    /// # Returns
+    ///
+    /// The mesh of the text, or `None` if it has no visible glyphs.
+    pub fn into_mesh(self, font: &Font, material: Arc<dyn Material>) -> Option<TriangleMesh> {
+        extrude_regions(
+            &font.regions(&self.content, self.size),
+            self.depth,
+            self.bevel,
+            material,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hit::Hittable;
+    use crate::material::Lambertian;
+    use crate::ray::Ray;
+    use crate::vector3::Vector3;
+
+    #[test]
+    fn test_group_contours() {
+        let square = |min: f64, max: f64| vec![(min, min), (max, min), (max, max), (min, max)];
+        // A frame holding an island, next to a separate square.
+        let regions = group_contours(vec![
+            square(0.0, 10.0),
+            square(2.0, 8.0),
+            square(4.0, 6.0),
+            vec![(20.0, 0.0), (21.0, 0.0), (21.0, 1.0), (20.0, 1.0)],
+        ]);
+        assert_eq!(regions.len(), 3);
+        assert_eq!(regions[0].holes, vec![square(2.0, 8.0)]);
+        assert!(regions[1].holes.is_empty() && regions[2].holes.is_empty());
+    }
+
+    #[test]
+    #[ignore = "needs the DejaVu Sans font installed in /usr/share/fonts/truetype/dejavu"]
+    fn test_text_mesh() {
+        let font =
+            Font::load(Path::new("/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf")).unwrap();
+        assert!(Font::from_bytes(vec![0; 16]).is_err());
+
+        // The letter O has a hole, and a space has no glyph outline.
+        let regions = font.regions("O ", 1.0);
+        assert_eq!(regions.len(), 1);
+        assert_eq!(regions[0].holes.len(), 1);
+
+        let material = Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5)));
+        assert!(Text::new(" ", 1.0)
+            .into_mesh(&font, material.clone())
+            .is_none());
+        let mesh = Text::new("O", 1.0)
+            .with_depth(0.2)
+            .with_bevel(0.02)
+            .into_mesh(&font, material)
+            .unwrap();
+        let bounds = mesh.bounding_box().unwrap();
+        let center = (bounds.min + bounds.max) / 2.0;
+        assert!((bounds.max.z - 0.2).abs() < 1e-3, "{:?}", bounds);
+        assert!(bounds.max.y > 0.6 && bounds.max.y < 0.8, "{:?}", bounds);
+
+        // Rays pass through the middle of the O and hit its ring.
+        let down = Vector3::new(0.0, 0.0, -1.0);
+        let middle = Ray::new(Vector3::new(center.x, center.y, 5.0), down);
+        assert!(mesh.hit(&middle, (0.001, f64::INFINITY)).is_none());
+        let ring = Ray::new(Vector3::new(bounds.min.x + 0.03, center.y, 5.0), down);
+        let record = mesh.hit(&ring, (0.001, f64::INFINITY)).unwrap();
+        assert!(record.t > 4.79 && record.t < 5.0, "{}", record.t);
+    }
+}
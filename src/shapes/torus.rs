@@ -0,0 +1,344 @@
+use crate::aabb::Aabb;
+use crate::hit::{HitRecord, Hittable};
+use crate::material::Material;
+use crate::material_table::MaterialTable;
+use crate::onb::Onb;
+use crate::ray::Ray;
+use crate::statistics::SceneStatistics;
+use crate::vector3::Vector3;
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+/// Finds the real roots of a polynomial within an interval. The roots of its derivative split the
+/// interval into pieces where the polynomial only rises or only falls, so every piece holds at
+/// most one root, found by bisection.
+///
+/// # Arguments
+///
+/// * `coefficients` - The coefficients of the polynomial, from the constant term up, at least 2.
+/// * `interval` - The range of values to search.
+///
+/// # Returns
+///
+/// The roots in increasing order. Roots where the polynomial touches zero without changing sign
+/// are not found.
+fn polynomial_roots(coefficients: &[f64], interval: (f64, f64)) -> Vec<f64> {
+    if let [constant, slope] = coefficients {
+        let root = -constant / slope;
+        return if *slope != 0.0 && root >= interval.0 && root <= interval.1 {
+            vec![root]
+        } else {
+            Vec::new()
+        };
+    }
+
+    let value = |x: f64| {
+        coefficients
+            .iter()
+            .rev()
+            .fold(0.0, |value, coefficient| value * x + coefficient)
+    };
+    let derivative: Vec<f64> = coefficients[1..]
+        .iter()
+        .enumerate()
+        .map(|(power, coefficient)| (power + 1) as f64 * coefficient)
+        .collect();
+    let mut bounds = vec![interval.0];
+    bounds.extend(polynomial_roots(&derivative, interval));
+    bounds.push(interval.1);
+
+    bounds
+        .windows(2)
+        .filter_map(|piece| {
+            let (mut low, mut high) = (piece[0], piece[1]);
+            let rising = value(low) < 0.0;
+            if rising == (value(high) < 0.0) {
+                return None;
+            }
+            while high - low > 1e-10 * low.abs().max(1.0) {
+                let middle = 0.5 * (low + high);
+                if (value(middle) < 0.0) == rising {
+                    low = middle;
+                } else {
+                    high = middle;
+                }
+            }
+            Some(0.5 * (low + high))
+        })
+        .collect()
+}
+
+/// Represents a torus, the surface swept by a circle, the tube, going around an axis, like a ring
+/// or a doughnut.
+///
+/// Tori are not sampled as lights, so emissive tori only light the scene when scattered rays
+/// happen to hit them.
+pub struct Torus {
+    /// The center of the hole.
+    center: Vector3,
+    /// A right-handed basis whose `w` axis is the axis of the torus.
+    frame: Onb,
+    /// The distance from the axis to the center of the tube.
+    major_radius: f64,
+    /// The radius of the tube.
+    minor_radius: f64,
+    /// The material of the torus.
+    material: Arc<dyn Material>,
+}
+
+impl Torus {
+    /// Creates a new `Torus`.
+    ///
+    /// # Arguments
+    ///
+    /// * `center` - The center of the hole.
+    /// * `axis` - The direction of the axis the tube goes around, which does not need to be
+    ///   normalized.
+    /// * `major_radius` - The distance from the axis to the center of the tube.
+    /// * `minor_radius` - The radius of the tube.
+    /// * `material` - The material of the torus.
+    ///
+    /// # Returns
+    ///
+    /// A new `Torus` instance.
+    pub fn new(
+        center: Vector3,
+        axis: Vector3,
+        major_radius: f64,
+        minor_radius: f64,
+        material: Arc<dyn Material>,
+    ) -> Torus {
+        let onb = Onb::new(&axis);
+        Torus {
+            center,
+            // The basis of `Onb` is left-handed, swapping its tangents keeps the texture
+            // coordinates from being mirrored.
+            frame: Onb {
+                u: onb.v,
+                v: onb.u,
+                w: onb.w,
+            },
+            major_radius,
+            minor_radius,
+            material,
+        }
+    }
+
+    /// Returns the point of the circle at the center of the tube closest to a given point.
+    ///
+    /// # Arguments
+    ///
+    /// * `local` - The point in the local frame of the torus.
+    ///
+    /// # Returns
+    ///
+    /// The closest point of the circle in the local frame, or any point of it for points on the
+    /// axis.
+    fn core_point(&self, local: &Vector3) -> Vector3 {
+        let distance = (local.x * local.x + local.y * local.y).sqrt();
+        if distance == 0.0 {
+            return Vector3::new(self.major_radius, 0.0, 0.0);
+        }
+        Vector3::new(local.x, local.y, 0.0) * (self.major_radius / distance)
+    }
+}
+
+impl Hittable for Torus {
+    /// Checks if a ray hits the torus within a given interval, by solving the quartic equation
+    /// `(|p|^2 + R^2 - r^2)^2 = 4 R^2 (x^2 + y^2)` of the torus along the ray in its local frame.
+    /// The ray is first clipped to the sphere enclosing the torus and the distances measured from
+    /// where it enters, keeping the coefficients small.
+    ///
+    /// The u texture coordinate goes once around the axis and v once around the tube, starting
+    /// from its outer rim.
+    ///
+    /// # Arguments
+    ///
+    /// * `ray` - The ray to test for intersection.
+    /// * `interval` - The range of distances to consider for intersections.
+    ///
+    /// # Returns
+    ///
+    /// An `Option` containing the closest `HitRecord` if an intersection is found, or `None` if no intersection is found.
+    fn hit(&self, ray: &Ray, interval: (f64, f64)) -> Option<HitRecord<'_>> {
+        let origin = self.frame.world_to_local(&(ray.origin - self.center));
+        let direction = self.frame.world_to_local(&ray.direction).normalize();
+        let scale = ray.direction.length();
+
+        // The distances along the local direction where the ray crosses the enclosing sphere,
+        // made a little larger so it does not touch the outer rim where the ray enters.
+        let bound = 1.01 * (self.major_radius + self.minor_radius);
+        let half_b = origin.dot(&direction);
+        let discriminant = half_b * half_b - (origin.dot(&origin) - bound * bound);
+        if discriminant < 0.0 {
+            return None;
+        }
+        let root = discriminant.sqrt();
+        let start = (-half_b - root).max(interval.0 * scale);
+        let end = (-half_b + root).min(interval.1 * scale);
+        if start > end {
+            return None;
+        }
+
+        let o = origin + start * direction;
+        let d = direction;
+        let (r2, m2) = (
+            self.major_radius * self.major_radius,
+            self.minor_radius * self.minor_radius,
+        );
+        let k = o.dot(&o) + r2 - m2;
+        let od = o.dot(&d);
+        let coefficients = [
+            k * k - 4.0 * r2 * (o.x * o.x + o.y * o.y),
+            4.0 * od * k - 8.0 * r2 * (o.x * d.x + o.y * d.y),
+            4.0 * od * od + 2.0 * k - 4.0 * r2 * (d.x * d.x + d.y * d.y),
+            4.0 * od,
+            1.0,
+        ];
+        let s = *polynomial_roots(&coefficients, (0.0, end - start)).first()?;
+
+        let local = o + s * d;
+        let core = self.core_point(&local);
+        let normal = (local - core) / self.minor_radius;
+        let u = (local.y.atan2(local.x) / (2.0 * PI)).rem_euclid(1.0);
+        let outward = core / self.major_radius;
+        let v = (normal.z.atan2(normal.dot(&outward)) / (2.0 * PI)).rem_euclid(1.0);
+
+        let t = (start + s) / scale;
+        let mut record = HitRecord::new(t, ray.point_at(t), self.material.as_ref(), u, v);
+        record.set_face_normal(ray, &self.frame.local_to_world(&normal));
+        // u goes around the axis, and v turns the normal from the outer rim towards the axis.
+        let around_tube = Vector3::new(0.0, 0.0, normal.dot(&outward)) - normal.z * outward;
+        record.set_tangents(
+            self.frame
+                .local_to_world(&(2.0 * PI * Vector3::new(-local.y, local.x, 0.0))),
+            self.frame
+                .local_to_world(&(2.0 * PI * self.minor_radius * around_tube)),
+        );
+        Some(record)
+    }
+
+    fn collect_materials(&self, table: &mut MaterialTable) {
+        table.add(&self.material);
+    }
+
+    fn collect_statistics(&self, statistics: &mut SceneStatistics) {
+        statistics.add_primitive("Torus", std::mem::size_of_val(self));
+        self.material.collect_statistics(statistics);
+    }
+
+    fn closest_point(&self, point: &Vector3) -> Option<Vector3> {
+        let local = self.frame.world_to_local(&(*point - self.center));
+        let core = self.core_point(&local);
+        let offset = local - core;
+        let direction = if offset.is_near_zero() {
+            Vector3::new(0.0, 0.0, 1.0)
+        } else {
+            offset.normalize()
+        };
+        Some(
+            self.center
+                + self
+                    .frame
+                    .local_to_world(&(core + self.minor_radius * direction)),
+        )
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        // The core circle reaches R sqrt(1 - w_i^2) along axis i, and the tube r further.
+        let w = self.frame.w;
+        let reach =
+            |w_i: f64| self.major_radius * (1.0 - w_i * w_i).max(0.0).sqrt() + self.minor_radius;
+        let extent = Vector3::new(reach(w.x), reach(w.y), reach(w.z));
+        Some(Aabb::new(self.center - extent, self.center + extent))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Lambertian;
+
+    fn torus() -> Torus {
+        Torus::new(
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            2.0,
+            0.5,
+            Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5))),
+        )
+    }
+
+    #[test]
+    fn test_polynomial_roots() {
+        // (x - 1)(x - 2)(x + 3)(x - 5) = x^4 - 5x^3 - 7x^2 + 41x - 30.
+        let roots = polynomial_roots(&[-30.0, 41.0, -7.0, -5.0, 1.0], (-10.0, 10.0));
+        assert_eq!(roots.len(), 4);
+        for (root, expected) in roots.iter().zip([-3.0, 1.0, 2.0, 5.0]) {
+            assert!((root - expected).abs() < 1e-8, "{:?}", roots);
+        }
+        let roots = polynomial_roots(&[-30.0, 41.0, -7.0, -5.0, 1.0], (1.5, 4.0));
+        assert_eq!(roots.len(), 1);
+        assert!(polynomial_roots(&[1.0, 0.0, 1.0], (-5.0, 5.0)).is_empty());
+    }
+
+    #[test]
+    fn test_torus_hits() {
+        let torus = torus();
+        // A ray along the X-axis crosses the tube four times.
+        let ray = Ray::new(Vector3::new(5.0, 0.0, 0.0), Vector3::new(-1.0, 0.0, 0.0));
+        let mut t = 0.001;
+        let mut crossings = Vec::new();
+        while let Some(record) = torus.hit(&ray, (t, f64::INFINITY)) {
+            crossings.push((record.poz.x, record.front_face));
+            t = record.t + 1e-6;
+        }
+        let expected = [(2.5, true), (1.5, false), (-1.5, true), (-2.5, false)];
+        assert_eq!(crossings.len(), expected.len());
+        for ((x, front), (expected_x, expected_front)) in crossings.iter().zip(expected) {
+            assert!((x - expected_x).abs() < 1e-8, "{:?}", crossings);
+            assert_eq!(*front, expected_front);
+        }
+
+        // Down the axis through the hole, and beside the tube, rays miss.
+        let down = Ray::new(Vector3::new(0.0, 5.0, 0.0), Vector3::new(0.0, -1.0, 0.0));
+        assert!(torus.hit(&down, (0.001, f64::INFINITY)).is_none());
+        let beside = Ray::new(Vector3::new(2.6, 5.0, 0.0), Vector3::new(0.0, -1.0, 0.0));
+        assert!(torus.hit(&beside, (0.001, f64::INFINITY)).is_none());
+
+        // From above the tube the top of the tube is hit, halfway around it from the outer rim.
+        let top = Ray::new(Vector3::new(0.0, 5.0, 2.0), Vector3::new(0.0, -1.0, 0.0));
+        let record = torus.hit(&top, (0.001, f64::INFINITY)).unwrap();
+        assert!((record.t - 4.5).abs() < 1e-8);
+        assert!((record.normal - Vector3::new(0.0, 1.0, 0.0)).length() < 1e-8);
+        assert!((record.v - 0.25).abs() < 1e-8);
+    }
+
+    #[test]
+    fn test_torus_normals_and_bounds() {
+        let torus = Torus::new(
+            Vector3::new(1.0, -1.0, 0.5),
+            Vector3::new(1.0, 2.0, -0.5),
+            1.5,
+            0.4,
+            Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5))),
+        );
+        let bounds = torus.bounding_box().unwrap();
+        let mut hits = 0;
+        for _ in 0..2000 {
+            let origin = Vector3::new(1.0, -1.0, 0.5) + 6.0 * Vector3::random_unit_vector();
+            let target = Vector3::new(1.0, -1.0, 0.5) + Vector3::random(-2.0, 2.0);
+            let ray = Ray::new(origin, target - origin);
+            let Some(record) = torus.hit(&ray, (0.001, f64::INFINITY)) else {
+                continue;
+            };
+            hits += 1;
+            assert!(bounds.hit(&ray, (0.001, f64::INFINITY)));
+            let closest = torus.closest_point(&record.poz).unwrap();
+            assert!((closest - record.poz).length() < 1e-6);
+            assert!((record.normal.length() - 1.0).abs() < 1e-9);
+            assert!(record.front_face);
+        }
+        assert!(hits > 100);
+    }
+}
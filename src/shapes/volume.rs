@@ -1,7 +1,9 @@
-#![allow(dead_code)]
+use crate::aabb::Aabb;
 use crate::hit::{HitRecord, Hittable};
-use crate::material::{Isotropic, Material};
+use crate::material::{HenyeyGreenstein, Isotropic, Material};
+use crate::material_table::MaterialTable;
 use crate::ray::Ray;
+use crate::statistics::SceneStatistics;
 use crate::texture::Texture;
 use crate::vector3::Vector3;
 use fastrand::f64;
@@ -33,42 +35,73 @@ impl ConstantMedium {
             material: Arc::new(Isotropic::from_texture(texture)),
         }
     }
+
+    /// Creates a medium scattering light mostly forward or mostly back, following the
+    /// Henyey-Greenstein phase function with the asymmetry `g`, between -1 and 1.
+    pub fn anisotropic(boundary: Box<dyn Hittable>, density: f64, color: Vector3, g: f64) -> Self {
+        Self {
+            boundary,
+            neg_inv_density: -1.0 / density,
+            material: Arc::new(HenyeyGreenstein::new(color, g)),
+        }
+    }
 }
 
 impl Hittable for ConstantMedium {
-    fn hit(&self, ray: &Ray, interval: (f64, f64)) -> Option<HitRecord> {
-        if let Some(mut hit1) = self.boundary.hit(ray, (f64::NEG_INFINITY, f64::INFINITY)) {
-            return if let Some(mut hit2) = self.boundary.hit(ray, (hit1.t + 0.0001, f64::INFINITY))
-            {
-                if hit1.t < interval.0 {
-                    hit1.t = interval.0;
-                }
-
-                if hit2.t > interval.1 {
-                    hit2.t = interval.1;
-                }
-
-                if hit1.t >= hit2.t {
-                    return None;
-                }
-
-                if hit1.t < 0.0 {
-                    hit1.t = 0.0;
-                }
-
-                let distance_inside_boundary = (hit2.t - hit1.t) * ray.length;
-                let hit_distance = self.neg_inv_density * f64().ln();
-
-                if hit_distance > distance_inside_boundary {
-                    return None;
-                }
-                let t = hit1.t + hit_distance / ray.length;
-                let hit_record = HitRecord::new(t, ray.point_at(t), &*self.material, 0.0, 0.0);
-                Some(hit_record)
-            } else {
-                None
+    fn hit(&self, ray: &Ray, interval: (f64, f64)) -> Option<HitRecord<'_>> {
+        let hit1 = self.boundary.hit(ray, (f64::NEG_INFINITY, f64::INFINITY))?;
+
+        // Boundaries that only report hits ahead of the ray return the exit point first
+        // when the ray starts inside the medium, in which case the medium begins at the origin.
+        let (mut entry_t, mut exit_t) =
+            match self.boundary.hit(ray, (hit1.t + 0.0001, f64::INFINITY)) {
+                Some(hit2) => (hit1.t, hit2.t),
+                None => (0.0, hit1.t),
             };
+
+        if entry_t < interval.0 {
+            entry_t = interval.0;
+        }
+
+        if exit_t > interval.1 {
+            exit_t = interval.1;
         }
-        None
+
+        if entry_t >= exit_t {
+            return None;
+        }
+
+        if entry_t < 0.0 {
+            entry_t = 0.0;
+        }
+
+        let distance_inside_boundary = (exit_t - entry_t) * ray.length;
+        let hit_distance = self.neg_inv_density * f64().ln();
+
+        if hit_distance > distance_inside_boundary {
+            return None;
+        }
+        let t = entry_t + hit_distance / ray.length;
+        let hit_record = HitRecord::new(t, ray.point_at(t), &*self.material, 0.0, 0.0);
+        Some(hit_record)
+    }
+
+    /// Adds the phase function of the medium, the material of every hit inside it.
+    fn collect_materials(&self, table: &mut MaterialTable) {
+        table.add(&self.material);
+    }
+
+    fn collect_statistics(&self, statistics: &mut SceneStatistics) {
+        statistics.add_primitive("ConstantMedium", std::mem::size_of_val(self));
+        self.boundary.collect_statistics(statistics);
+        self.material.collect_statistics(statistics);
+    }
+
+    fn closest_point(&self, point: &Vector3) -> Option<Vector3> {
+        self.boundary.closest_point(point)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.boundary.bounding_box()
     }
 }
@@ -0,0 +1,147 @@
+//! The shutter of the camera, spreading the rays of a pixel over an interval of scene time so
+//! time-varying scenes blur as they would on film, with an optional weighting curve and a rolling
+//! readout that exposes every row of the image later than the one above it.
+
+use crate::distribution::Distribution1D;
+
+/// Represents the exposure of a camera over an interval of scene time.
+#[derive(Debug, Clone)]
+pub struct Shutter {
+    /// The scene time during which every row of the image is exposed.
+    duration: f64,
+    /// The weight of every part of the exposure, from the opening to the closing of the shutter.
+    curve: Distribution1D,
+    /// The scene time between the opening of the shutter for the top row and for the bottom row.
+    readout: f64,
+}
+
+impl Shutter {
+    /// Creates a new global `Shutter`, exposing the whole image at once with the same weight over
+    /// the interval.
+    ///
+    /// # Arguments
+    ///
+    /// * `duration` - The scene time during which the shutter stays open.
+    ///
+    /// # Returns
+    ///
+    /// A new `Shutter` instance.
+    pub fn new(duration: f64) -> Shutter {
+        Shutter {
+            duration: duration.max(0.0),
+            curve: Distribution1D::new(vec![1.0]),
+            readout: 0.0,
+        }
+    }
+
+    /// Sets the weighting curve of the shutter, such as a ramp for a shutter that opens and
+    /// closes gradually. The samples of a pixel are spread over the interval in proportion to it.
+    ///
+    /// # Arguments
+    ///
+    /// * `weights` - The non-negative weights of equal parts of the interval, from the opening to
+    ///   the closing of the shutter.
+    ///
+    /// # Returns
+    ///
+    /// The `Shutter` with the given curve.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `weights` is empty.
+    pub fn with_curve(mut self, weights: Vec<f64>) -> Shutter {
+        self.curve = Distribution1D::new(weights);
+        self
+    }
+
+    /// Turns the shutter into a rolling shutter, which reads the image out row by row from the
+    /// top, so objects changing quickly appear skewed or banded.
+    ///
+    /// # Arguments
+    ///
+    /// * `readout` - The scene time between the opening of the shutter for the top row and for
+    ///   the bottom row.
+    ///
+    /// # Returns
+    ///
+    /// The `Shutter` with the given readout time.
+    pub fn with_rolling(mut self, readout: f64) -> Shutter {
+        self.readout = readout.max(0.0);
+        self
+    }
+
+    /// Picks the time of a sample after the opening of the shutter for the top row.
+    ///
+    /// # Arguments
+    ///
+    /// * `row` - The position of the row of the sample, from 0 at the top of the image to 1 at
+    ///   the bottom.
+    /// * `random` - A uniform random number between 0 and 1.
+    ///
+    /// # Returns
+    ///
+    /// The time of the sample relative to the opening of the shutter.
+    pub fn sample_time(&self, row: f64, random: f64) -> f64 {
+        let (position, _, _) = self.curve.sample(random);
+        self.interval(row).0 + self.duration * position
+    }
+
+    /// Returns the interval during which a row of the image is exposed.
+    ///
+    /// # Arguments
+    ///
+    /// * `row` - The position of the row, from 0 at the top of the image to 1 at the bottom.
+    ///
+    /// # Returns
+    ///
+    /// The times at which the shutter opens and closes for the row, relative to its opening for
+    /// the top row.
+    pub fn interval(&self, row: f64) -> (f64, f64) {
+        let open = self.readout * row.clamp(0.0, 1.0);
+        (open, open + self.duration)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_global_shutter() {
+        let shutter = Shutter::new(0.5);
+        let times: Vec<f64> = (0..100)
+            .map(|i| shutter.sample_time(0.7, (i as f64 + 0.5) / 100.0))
+            .collect();
+        assert!(times.iter().all(|&t| (0.0..=0.5).contains(&t)));
+        let mean = times.iter().sum::<f64>() / times.len() as f64;
+        assert!((mean - 0.25).abs() < 1e-6, "{}", mean);
+        assert_eq!(Shutter::new(0.0).sample_time(0.5, 0.3), 0.0);
+    }
+
+    #[test]
+    fn test_weighting_curve() {
+        // All the weight is in the second half of the interval.
+        let shutter = Shutter::new(1.0).with_curve(vec![0.0, 1.0]);
+        for i in 0..20 {
+            let time = shutter.sample_time(0.0, i as f64 / 20.0);
+            assert!((0.5..=1.0).contains(&time), "{}", time);
+        }
+
+        // A ramp puts more samples late in the interval.
+        let ramp = Shutter::new(1.0).with_curve(vec![1.0, 2.0, 3.0, 4.0]);
+        let late = (0..1000)
+            .filter(|&i| ramp.sample_time(0.0, (i as f64 + 0.5) / 1000.0) >= 0.5)
+            .count();
+        assert!((late as f64 / 1000.0 - 0.7).abs() < 0.01, "{}", late);
+    }
+
+    #[test]
+    fn test_rolling_shutter() {
+        let shutter = Shutter::new(0.1).with_rolling(1.0);
+        assert!((shutter.sample_time(0.0, 0.5) - 0.05).abs() < 1e-9);
+        assert!((shutter.sample_time(0.5, 0.5) - 0.55).abs() < 1e-9);
+        assert!((shutter.sample_time(1.0, 0.5) - 1.05).abs() < 1e-9);
+        let (open, close) = shutter.interval(0.5);
+        assert!((open - 0.5).abs() < 1e-9 && (close - 0.6).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,347 @@
+use std::collections::{BTreeMap, HashSet};
+use std::mem::size_of;
+
+/// The number of bytes in a mebibyte, used when printing memory sizes.
+const MEBIBYTE: f64 = 1024.0 * 1024.0;
+
+/// Summarizes what a scene is made of and how much memory it takes, so slow or memory hungry
+/// scenes can be understood before they are rendered.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SceneStatistics {
+    /// The number of objects tested by every ray, at the top level of the scene.
+    pub objects: usize,
+    /// The number of emissive objects sampled as lights.
+    pub lights: usize,
+    /// The number of primitives, by type.
+    pub primitives: BTreeMap<String, usize>,
+    /// The number of nodes of bounding volume hierarchies in the scene.
+    pub bvh_nodes: usize,
+    /// The number of instances placing shared objects in the scene.
+    pub instances: usize,
+    /// The memory taken by the objects of the scene, in bytes.
+    pub geometry_bytes: usize,
+    /// The memory taken by distinct materials, in bytes, not counting their textures.
+    pub material_bytes: usize,
+    /// The memory taken by distinct textures, in bytes.
+    pub texture_bytes: usize,
+    /// The addresses of the materials counted so far, so shared materials are counted once.
+    materials: HashSet<usize>,
+    /// The addresses of the textures counted so far, so shared textures are counted once.
+    textures: HashSet<usize>,
+    /// The addresses of the objects placed by instances counted so far, so shared objects are
+    /// counted once.
+    instanced: HashSet<usize>,
+}
+
+impl SceneStatistics {
+    /// Creates empty `SceneStatistics`.
+    ///
+    /// # Returns
+    ///
+    /// A new `SceneStatistics` instance.
+    pub fn new() -> SceneStatistics {
+        SceneStatistics::default()
+    }
+
+    /// Counts a primitive of the scene.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the type of the primitive.
+    /// * `bytes` - The memory taken by the primitive.
+    pub fn add_primitive(&mut self, name: &str, bytes: usize) {
+        *self.primitives.entry(name.to_string()).or_insert(0) += 1;
+        self.add_geometry(bytes);
+    }
+
+    /// Counts memory taken by objects that are not primitives themselves, such as transformations
+    /// or groups, and by the boxes holding them.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The memory taken by the object.
+    pub fn add_geometry(&mut self, bytes: usize) {
+        self.geometry_bytes += bytes + size_of::<Box<()>>();
+    }
+
+    /// Counts an instance of a shared object.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - The address of the shared object, identifying other instances of it.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the shared object had not been counted yet, so it should be counted too.
+    pub fn add_instance(&mut self, address: usize) -> bool {
+        self.instances += 1;
+        self.instanced.insert(address)
+    }
+
+    /// Counts a material, unless the same material was counted before.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - The address of the material, identifying shared materials.
+    /// * `bytes` - The memory taken by the material.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the material had not been counted yet, so its textures should be counted too.
+    pub fn add_material(&mut self, address: usize, bytes: usize) -> bool {
+        if !self.materials.insert(address) {
+            return false;
+        }
+        self.material_bytes += bytes;
+        true
+    }
+
+    /// Counts a texture, unless the same texture was counted before.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - The address of the texture, identifying shared textures.
+    /// * `bytes` - The memory taken by the texture, including its texels.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the texture had not been counted yet, so the textures it wraps should be
+    /// counted too.
+    pub fn add_texture(&mut self, address: usize, bytes: usize) -> bool {
+        if !self.textures.insert(address) {
+            return false;
+        }
+        self.texture_bytes += bytes;
+        true
+    }
+
+    /// Returns the total number of primitives.
+    pub fn primitive_count(&self) -> usize {
+        self.primitives.values().sum()
+    }
+
+    /// Estimates the peak memory taken by a render of the scene.
+    ///
+    /// # Arguments
+    ///
+    /// * `framebuffer_bytes` - The memory taken by the images held while rendering.
+    ///
+    /// # Returns
+    ///
+    /// The estimated peak memory, in bytes.
+    pub fn peak_bytes(&self, framebuffer_bytes: usize) -> usize {
+        self.geometry_bytes + self.material_bytes + self.texture_bytes + framebuffer_bytes
+    }
+
+    /// Prints the statistics.
+    ///
+    /// # Arguments
+    ///
+    /// * `framebuffer_bytes` - The memory taken by the images held while rendering.
+    pub fn print(&self, framebuffer_bytes: usize) {
+        println!(
+            "Scene: {} objects tested per ray, {} primitives, {} lights",
+            self.objects,
+            self.primitive_count(),
+            self.lights
+        );
+        for (name, count) in &self.primitives {
+            println!("  {}: {}", name, count);
+        }
+        if self.bvh_nodes == 0 {
+            println!("  BVH nodes: none, every object is tested by every ray");
+        } else {
+            println!("  BVH nodes: {}", self.bvh_nodes);
+        }
+        if self.instances > 0 {
+            println!(
+                "  Instances: {} of {} shared objects, counted once",
+                self.instances,
+                self.instanced.len()
+            );
+        }
+        println!(
+            "Memory: geometry {:.2} MiB, materials {:.2} MiB, textures {:.2} MiB ({} distinct), framebuffer {:.2} MiB",
+            self.geometry_bytes as f64 / MEBIBYTE,
+            self.material_bytes as f64 / MEBIBYTE,
+            self.texture_bytes as f64 / MEBIBYTE,
+            self.textures.len(),
+            framebuffer_bytes as f64 / MEBIBYTE
+        );
+        println!(
+            "Estimated peak memory: {:.2} MiB",
+            self.peak_bytes(framebuffer_bytes) as f64 / MEBIBYTE
+        );
+    }
+}
+
+/// The width of the longest bar printed for a path length histogram, in characters.
+const HISTOGRAM_BAR_WIDTH: usize = 40;
+
+/// Counts how many bounces the paths of a render made before they ended, so the maximum depth can
+/// be chosen from what the scene needs instead of being guessed.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PathLengthHistogram {
+    /// The number of paths that ended after each number of bounces, from zero bounces up to the
+    /// maximum depth. Paths counted in the last bucket were cut off by the depth limit.
+    counts: Vec<u64>,
+}
+
+impl PathLengthHistogram {
+    /// Creates an empty `PathLengthHistogram`.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_depth` - The maximum depth of the render, the longest possible path.
+    ///
+    /// # Returns
+    ///
+    /// A new `PathLengthHistogram` instance.
+    pub fn new(max_depth: u32) -> PathLengthHistogram {
+        PathLengthHistogram {
+            counts: vec![0; max_depth as usize + 1],
+        }
+    }
+
+    /// Counts a path.
+    ///
+    /// # Arguments
+    ///
+    /// * `bounces` - The number of bounces of the path, at most the maximum depth.
+    pub fn add(&mut self, bounces: u32) {
+        let last = self.counts.len() - 1;
+        self.counts[(bounces as usize).min(last)] += 1;
+    }
+
+    /// Adds the paths counted by another histogram of the same render.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The other histogram.
+    pub fn merge(&mut self, other: &PathLengthHistogram) {
+        if other.counts.len() > self.counts.len() {
+            self.counts.resize(other.counts.len(), 0);
+        }
+        for (count, other) in self.counts.iter_mut().zip(&other.counts) {
+            *count += other;
+        }
+    }
+
+    /// Returns the number of paths counted.
+    pub fn total(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// Returns the average number of bounces of the paths, or zero if none was counted.
+    pub fn mean(&self) -> f64 {
+        let bounces: u64 = self
+            .counts
+            .iter()
+            .enumerate()
+            .map(|(length, count)| length as u64 * count)
+            .sum();
+        bounces as f64 / self.total().max(1) as f64
+    }
+
+    /// Returns the fraction of the paths cut off by the depth limit, or zero if none was counted.
+    pub fn truncated_fraction(&self) -> f64 {
+        self.counts.last().copied().unwrap_or(0) as f64 / self.total().max(1) as f64
+    }
+
+    /// Prints the histogram, up to the longest path counted. A large share of paths cut off at
+    /// the maximum depth means that it darkens the image, while a share close to zero means that
+    /// it can be lowered.
+    pub fn print(&self) {
+        let total = self.total().max(1) as f64;
+        println!(
+            "Path lengths: mean {:.2} bounces, {:.1}% cut off at the maximum depth of {}",
+            self.mean(),
+            100.0 * self.truncated_fraction(),
+            self.counts.len() - 1
+        );
+        let longest = self
+            .counts
+            .iter()
+            .rposition(|&count| count > 0)
+            .unwrap_or(0);
+        let largest = self.counts.iter().copied().max().unwrap_or(0).max(1) as f64;
+        for (length, &count) in self.counts[..=longest].iter().enumerate() {
+            let bar = (count as f64 / largest * HISTOGRAM_BAR_WIDTH as f64).round() as usize;
+            println!(
+                "  {:>3} bounces: {:>6.2}% {}",
+                length,
+                100.0 * count as f64 / total,
+                "#".repeat(bar)
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PathLengthHistogram;
+    use crate::hit::Hittable;
+    use crate::material::Lambertian;
+    use crate::scene::Scene;
+    use crate::shapes::box_quad::BoxQuad;
+    use crate::shapes::sphere::Sphere;
+    use crate::texture::ImageTexture;
+    use crate::transformation::Translate;
+    use crate::vector3::Vector3;
+    use image::DynamicImage;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_scene_statistics() {
+        let texture = Arc::new(ImageTexture::from_image(&DynamicImage::new_rgb8(4, 4)));
+        let material = Arc::new(Lambertian::from_texture(Box::new(texture.clone())));
+        let other = Arc::new(Lambertian::from_texture(Box::new(texture)));
+        let origin = Vector3::new(0.0, 0.0, 0.0);
+
+        let objects: Vec<Box<dyn Hittable>> = vec![
+            Box::new(Sphere::new(origin, 1.0, material.clone())),
+            Box::new(Sphere::new(origin, 2.0, material.clone())),
+            Box::new(Translate::new(
+                Arc::new(BoxQuad::new(origin, Vector3::new(1.0, 1.0, 1.0), other)),
+                Vector3::new(5.0, 0.0, 0.0),
+            )),
+        ];
+        let statistics = Scene::new(objects).statistics();
+
+        assert_eq!(statistics.objects, 3);
+        assert_eq!(statistics.primitive_count(), 8);
+        assert_eq!(statistics.primitives["Sphere"], 2);
+        assert_eq!(statistics.primitives["Quad"], 6);
+        assert_eq!(statistics.bvh_nodes, 0);
+
+        // Both materials share one texture, so its 16 texels are only counted once.
+        assert_eq!(statistics.materials.len(), 2);
+        assert_eq!(statistics.textures.len(), 1);
+        assert!(statistics.texture_bytes >= 16 * std::mem::size_of::<Vector3>());
+        assert!(statistics.texture_bytes < 2 * 16 * std::mem::size_of::<Vector3>());
+        assert_eq!(
+            statistics.peak_bytes(100),
+            statistics.geometry_bytes + statistics.material_bytes + statistics.texture_bytes + 100
+        );
+    }
+
+    #[test]
+    fn test_path_length_histogram() {
+        let mut histogram = PathLengthHistogram::new(4);
+        assert_eq!(
+            (histogram.mean(), histogram.truncated_fraction()),
+            (0.0, 0.0)
+        );
+        for bounces in [0, 1, 1, 2] {
+            histogram.add(bounces);
+        }
+        let mut other = PathLengthHistogram::new(4);
+        other.add(4);
+        other.add(9);
+        histogram.merge(&other);
+
+        assert_eq!(histogram.total(), 6);
+        assert!((histogram.mean() - 12.0 / 6.0).abs() < 1e-12);
+        assert!((histogram.truncated_fraction() - 2.0 / 6.0).abs() < 1e-12);
+    }
+}
@@ -1,23 +1,189 @@
-#![allow(unused)]
+use crate::hit::HitRecord;
+use crate::perlin::Perlin;
+use crate::ray::Ray;
+use crate::statistics::SceneStatistics;
 use crate::vector3::Vector3;
-use image::{DynamicImage, GenericImageView, ImageReader};
+use image::{DynamicImage, ImageReader};
+use std::f64::consts::PI;
 use std::fmt::Debug;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
-/// A trait for textures that can be applied to materials
-pub trait Texture: Send + Sync + Debug {
-    /// Returns the color value of the texture at the given coordinates and point.
+/// Describes the surface point at which a texture is evaluated.
+#[derive(Debug, Clone, Copy)]
+pub struct ShadingContext {
+    /// The u-coordinate for texture mapping.
+    pub u: f64,
+    /// The v-coordinate for texture mapping.
+    pub v: f64,
+    /// The point in 3D space.
+    pub point: Vector3,
+    /// The point in the space of the primitive, before any transformation.
+    pub object_point: Vector3,
+    /// The normal vector at the point, facing against the incoming ray.
+    pub normal: Vector3,
+    /// Indicates whether the point is on the front face of the object.
+    pub front_face: bool,
+    /// The direction of the incoming ray.
+    pub direction: Vector3,
+    /// The scene time at which the point is shaded.
+    pub time: f64,
+    /// The fraction of the hemisphere above the point blocked by nearby objects, or 0 unless a
+    /// texture of the material reads surface queries.
+    pub occlusion: f64,
+    /// How sharply the surface bends around the point, from -1 in creases to 1 on edges, or 0
+    /// unless a texture of the material reads surface queries.
+    pub curvature: f64,
+    /// The random value between 0 and 1 of the instance the point belongs to, or 0 outside of
+    /// instances.
+    pub variation: f64,
+    /// The color tint of the instance the point belongs to, or white outside of instances.
+    pub tint: Vector3,
+    /// The extent of the footprint of the ray around the point along the u- and v-coordinates,
+    /// or 0 where the footprint is not tracked, which selects the finest level of mipmaps.
+    pub footprint: (f64, f64),
+}
+
+impl ShadingContext {
+    /// Creates a new `ShadingContext` from a ray and the record of the point it hit.
+    ///
+    /// # Arguments
+    ///
+    /// * `ray` - The incoming ray.
+    /// * `hit_record` - The record of the hit point.
+    ///
+    /// # Returns
+    ///
+    /// A new `ShadingContext` instance.
+    pub fn new(ray: &Ray, hit_record: &HitRecord) -> ShadingContext {
+        // The footprint spans fewer texture coordinates where they change faster.
+        let width = ray.footprint_at(hit_record.t);
+        let extent = |derivative: Vector3| {
+            let length = derivative.length();
+            if length > 0.0 {
+                width / length
+            } else {
+                0.0
+            }
+        };
+        ShadingContext {
+            u: hit_record.u,
+            v: hit_record.v,
+            point: hit_record.poz,
+            object_point: hit_record.object_poz,
+            normal: hit_record.normal,
+            front_face: hit_record.front_face,
+            direction: ray.direction,
+            time: ray.time,
+            occlusion: hit_record.occlusion,
+            curvature: hit_record.curvature,
+            variation: hit_record.variation,
+            tint: hit_record.tint,
+            footprint: (extent(hit_record.tangent), extent(hit_record.bitangent)),
+        }
+    }
+
+    /// Creates a new `ShadingContext` holding only texture coordinates, for evaluations that are
+    /// not tied to a hit point.
     ///
     /// # Arguments
     ///
     /// * `u` - The u-coordinate for texture mapping.
     /// * `v` - The v-coordinate for texture mapping.
-    /// * `point` - The point in 3D space.
+    ///
+    /// # Returns
+    ///
+    /// A new `ShadingContext` instance.
+    pub fn from_uv(u: f64, v: f64) -> ShadingContext {
+        ShadingContext {
+            u,
+            v,
+            ..ShadingContext::default()
+        }
+    }
+
+    /// Returns the coordinates of the shading point in the given texture space.
+    /// In UV space, the texture coordinates are returned as the x and y components.
+    ///
+    /// # Arguments
+    ///
+    /// * `space` - The space in which a texture is evaluated.
+    ///
+    /// # Returns
+    ///
+    /// The coordinates of the point as a `Vector3`.
+    pub fn position(&self, space: TextureSpace) -> Vector3 {
+        match space {
+            TextureSpace::Uv => Vector3::new(self.u, self.v, 0.0),
+            TextureSpace::Object => self.object_point,
+            TextureSpace::World => self.point,
+        }
+    }
+}
+
+impl Default for ShadingContext {
+    /// Creates a `ShadingContext` at the origin, outside of any instance.
+    fn default() -> Self {
+        ShadingContext {
+            u: 0.0,
+            v: 0.0,
+            point: Vector3::default(),
+            object_point: Vector3::default(),
+            normal: Vector3::default(),
+            front_face: false,
+            direction: Vector3::default(),
+            time: 0.0,
+            occlusion: 0.0,
+            curvature: 0.0,
+            variation: 0.0,
+            tint: Vector3::new(1.0, 1.0, 1.0),
+            footprint: (0.0, 0.0),
+        }
+    }
+}
+
+/// The space in which a procedural texture is evaluated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TextureSpace {
+    /// The texture coordinates of the surface, so patterns follow the surface parametrization.
+    Uv,
+    /// The space of the primitive, so patterns move with translated and rotated objects.
+    Object,
+    /// World space, so patterns stay fixed while objects move through them.
+    #[default]
+    World,
+}
+
+/// A trait for textures that can be applied to materials
+pub trait Texture: Send + Sync + Debug {
+    /// Returns the color value of the texture at the given shading point.
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - The shading context of the point.
     ///
     /// # Returns
     ///
     /// The color value as a `Vector3`.
-    fn value(&self, u: f64, v: f64, point: &Vector3) -> Vector3;
+    fn value(&self, context: &ShadingContext) -> Vector3;
+
+    /// Returns `true` if the texture reads the occlusion or curvature of the shading point, which
+    /// are then queried from the scene before the point is shaded.
+    fn surface_queries(&self) -> bool {
+        false
+    }
+
+    /// Counts the memory taken by the texture, once per shared texture.
+    ///
+    /// # Arguments
+    ///
+    /// * `statistics` - The statistics to add the texture to.
+    fn collect_statistics(&self, statistics: &mut SceneStatistics) {
+        statistics.add_texture(
+            self as *const Self as *const () as usize,
+            std::mem::size_of_val(self),
+        );
+    }
 }
 
 #[derive(Debug)]
@@ -47,14 +213,12 @@ impl Texture for SolidTexture {
     ///
     /// # Arguments
     ///
-    /// * `_u` - The u-coordinate for texture mapping (unused).
-    /// * `_v` - The v-coordinate for texture mapping (unused).
-    /// * `_point` - The point in 3D space (unused).
+    /// * `_context` - The shading context of the point (unused).
     ///
     /// # Returns
     ///
     /// The color value as a `Vector3`.
-    fn value(&self, _u: f64, _v: f64, _point: &Vector3) -> Vector3 {
+    fn value(&self, _context: &ShadingContext) -> Vector3 {
         self.albedo
     }
 }
@@ -68,6 +232,8 @@ pub struct CheckerTexture {
     odd: Box<dyn Texture>,
     /// The texture for the even squares.
     even: Box<dyn Texture>,
+    /// The space in which the pattern is evaluated.
+    space: TextureSpace,
 }
 
 impl CheckerTexture {
@@ -87,6 +253,7 @@ impl CheckerTexture {
             scale,
             odd: Box::new(SolidTexture::new(odd)),
             even: Box::new(SolidTexture::new(even)),
+            space: TextureSpace::World,
         }
     }
 
@@ -106,44 +273,177 @@ impl CheckerTexture {
         odd: Box<dyn Texture>,
         even: Box<dyn Texture>,
     ) -> CheckerTexture {
-        CheckerTexture { scale, odd, even }
+        CheckerTexture {
+            scale,
+            odd,
+            even,
+            space: TextureSpace::World,
+        }
+    }
+
+    /// Sets the space in which the pattern is evaluated. In UV space, `scale` is the number of
+    /// squares along each texture coordinate.
+    ///
+    /// # Arguments
+    ///
+    /// * `space` - The space in which the pattern is evaluated.
+    ///
+    /// # Returns
+    ///
+    /// The `CheckerTexture` evaluated in the given space.
+    pub fn with_space(mut self, space: TextureSpace) -> CheckerTexture {
+        self.space = space;
+        self
     }
 }
 
 impl Texture for CheckerTexture {
-    /// Returns the color value of the checkerboard texture at the given coordinates and point.
+    /// Returns the color value of the checkerboard texture at the given shading point.
     ///
     /// # Arguments
     ///
-    /// * `u` - The u-coordinate for texture mapping.
-    /// * `v` - The v-coordinate for texture mapping.
-    /// * `p` - The point in 3D space.
+    /// * `context` - The shading context of the point.
     ///
     /// # Returns
     ///
     /// The color value as a `Vector3`.
-    fn value(&self, u: f64, v: f64, p: &Vector3) -> Vector3 {
+    fn value(&self, context: &ShadingContext) -> Vector3 {
+        let p = context.position(self.space);
         let x = (self.scale * p.x).floor() as i32;
         let y = (self.scale * p.y).floor() as i32;
         let z = (self.scale * p.z).floor() as i32;
 
         if (x + y + z) % 2 == 0 {
-            self.even.value(u, v, p)
+            self.even.value(context)
         } else {
-            self.odd.value(u, v, p)
+            self.odd.value(context)
+        }
+    }
+
+    fn surface_queries(&self) -> bool {
+        self.odd.surface_queries() || self.even.surface_queries()
+    }
+
+    fn collect_statistics(&self, statistics: &mut SceneStatistics) {
+        if statistics.add_texture(self as *const Self as usize, std::mem::size_of_val(self)) {
+            self.odd.collect_statistics(statistics);
+            self.even.collect_statistics(statistics);
+        }
+    }
+}
+
+/// How texture coordinates outside of the unit interval are mapped back onto an image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WrapMode {
+    /// The image is tiled, so coordinates wrap around, as needed across the seam of a sphere.
+    Repeat,
+    /// Coordinates are clamped, so the border texels are extended, as needed at the poles of a sphere.
+    Clamp,
+    /// The image is tiled with every other tile mirrored.
+    Mirror,
+}
+
+impl WrapMode {
+    /// Maps a texel index onto a valid index of an axis of the image.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The texel index, possibly outside of the image.
+    /// * `size` - The number of texels along the axis.
+    ///
+    /// # Returns
+    ///
+    /// The wrapped texel index, between 0 and `size - 1`.
+    fn wrap(self, index: i64, size: u32) -> u32 {
+        let size = size as i64;
+        let wrapped = match self {
+            WrapMode::Repeat => index.rem_euclid(size),
+            WrapMode::Clamp => index.clamp(0, size - 1),
+            WrapMode::Mirror => {
+                let period = index.rem_euclid(2 * size);
+                if period < size {
+                    period
+                } else {
+                    2 * size - 1 - period
+                }
+            }
+        };
+        wrapped as u32
+    }
+}
+
+#[derive(Debug)]
+/// A level of the mipmaps of an image texture, half as wide and high as the level before it.
+struct MipLevel {
+    /// The width of the level in texels.
+    width: u32,
+    /// The height of the level in texels.
+    height: u32,
+    /// The linear colors of the texels, stored like those of the image.
+    texels: Vec<Vector3>,
+}
+
+impl MipLevel {
+    /// Averages the texels of a level into the next, smaller one. Every texel of the new level
+    /// covers a block of two by two texels, or three at the edges of levels of odd size.
+    ///
+    /// # Arguments
+    ///
+    /// * `width` - The width of the level in texels.
+    /// * `height` - The height of the level in texels.
+    /// * `texels` - The texels of the level.
+    ///
+    /// # Returns
+    ///
+    /// The next level.
+    fn downsample(width: u32, height: u32, texels: &[Vector3]) -> MipLevel {
+        let (next_width, next_height) = ((width / 2).max(1), (height / 2).max(1));
+        let span = |index: u32, size: u32, next_size: u32| {
+            index * size / next_size..(index + 1) * size / next_size
+        };
+        let mut next = Vec::with_capacity((next_width * next_height) as usize);
+        for j in 0..next_height {
+            for i in 0..next_width {
+                let (columns, rows) = (span(i, width, next_width), span(j, height, next_height));
+                let count = (columns.len() * rows.len()) as f64;
+                let sum = rows
+                    .flat_map(|y| columns.clone().map(move |x| (y * width + x) as usize))
+                    .fold(Vector3::default(), |sum, index| sum + texels[index]);
+                next.push(sum / count);
+            }
+        }
+        MipLevel {
+            width: next_width,
+            height: next_height,
+            texels: next,
         }
     }
 }
 
 #[derive(Debug)]
-/// Represents an image texture.
+/// Represents an image texture, sampled with bilinear filtering, and with trilinear filtering
+/// between mipmaps when they are built.
 pub struct ImageTexture {
-    /// The image data.
-    data: DynamicImage,
+    /// The width of the image in texels.
+    width: u32,
+    /// The height of the image in texels.
+    height: u32,
+    /// The linear colors of the texels, stored row by row starting from the top-left texel.
+    texels: Vec<Vector3>,
+    /// The opacity of the texels, between 0 and 1, stored like `texels`.
+    alphas: Vec<f64>,
+    /// The wrap mode along the u-coordinate.
+    wrap_u: WrapMode,
+    /// The wrap mode along the v-coordinate.
+    wrap_v: WrapMode,
+    /// The levels of the mipmaps after the image itself, from the largest down to a single
+    /// texel, or none unless built with `with_mipmaps`.
+    mipmaps: Vec<MipLevel>,
 }
 
 impl ImageTexture {
     /// Creates a new `ImageTexture` from the given file name.
+    /// The texture repeats along u and is clamped along v, which suits images mapped onto spheres.
     ///
     /// # Arguments
     ///
@@ -153,18 +453,281 @@ impl ImageTexture {
     ///
     /// A new `ImageTexture` instance.
     pub fn new(file_name: &str) -> ImageTexture {
+        ImageTexture::from_image(&Self::open(file_name))
+    }
+
+    /// Creates a new `ImageTexture` from the given file name that repeats along both u and v,
+    /// which suits tiles such as bricks or planks laid over a surface with a `UvTransform`.
+    ///
+    /// # Arguments
+    ///
+    /// * `file_name` - The name of the image file.
+    ///
+    /// # Returns
+    ///
+    /// A new `ImageTexture` instance.
+    pub fn tiled(file_name: &str) -> ImageTexture {
+        ImageTexture::new(file_name).with_wrap(WrapMode::Repeat, WrapMode::Repeat)
+    }
+
+    /// Creates a new `ImageTexture` from the given file name, holding data such as a normal map
+    /// rather than colors, so its values are used as stored without gamma decoding.
+    /// The texture repeats along u and is clamped along v, like `new`.
+    ///
+    /// # Arguments
+    ///
+    /// * `file_name` - The name of the image file.
+    ///
+    /// # Returns
+    ///
+    /// A new `ImageTexture` instance.
+    pub fn new_data(file_name: &str) -> ImageTexture {
+        ImageTexture::from_data_image(&Self::open(file_name))
+    }
+
+    /// Opens and decodes the image file with the given name, or returns an empty image if it
+    /// cannot be found.
+    ///
+    /// # Arguments
+    ///
+    /// * `file_name` - The name of the image file.
+    ///
+    /// # Returns
+    ///
+    /// The decoded image.
+    fn open(file_name: &str) -> DynamicImage {
         if let Some(path) = Self::find_file(file_name) {
             let image_reader = ImageReader::open(path).expect("Failed to open image file");
-            let image_data = image_reader.decode().expect("Failed to decode image");
-            ImageTexture { data: image_data }
+            image_reader.decode().expect("Failed to decode image")
         } else {
             eprintln!("Failed to find image file");
-            ImageTexture {
-                data: DynamicImage::new_rgb8(0, 0),
+            DynamicImage::new_rgb8(0, 0)
+        }
+    }
+
+    /// Creates a new `ImageTexture` from decoded image data.
+    /// The texture repeats along u and is clamped along v, which suits images mapped onto spheres.
+    ///
+    /// # Arguments
+    ///
+    /// * `image` - The image data, with colors encoded with gamma 2.2, or linear colors for high
+    ///   dynamic range images.
+    ///
+    /// # Returns
+    ///
+    /// A new `ImageTexture` instance.
+    pub fn from_image(image: &DynamicImage) -> ImageTexture {
+        ImageTexture::decode(image, 2.2)
+    }
+
+    /// Creates a new `ImageTexture` from decoded image data holding values rather than colors,
+    /// such as a normal map, so the values are used as stored without gamma decoding.
+    /// The texture repeats along u and is clamped along v, like `from_image`.
+    ///
+    /// # Arguments
+    ///
+    /// * `image` - The image data.
+    ///
+    /// # Returns
+    ///
+    /// A new `ImageTexture` instance.
+    pub fn from_data_image(image: &DynamicImage) -> ImageTexture {
+        ImageTexture::decode(image, 1.0)
+    }
+
+    /// Converts decoded image data into texels with the given gamma. High dynamic range images,
+    /// such as Radiance HDR and OpenEXR files, store linear floating point values and are kept as
+    /// they are, including values above 1.
+    ///
+    /// # Arguments
+    ///
+    /// * `image` - The image data.
+    /// * `gamma` - The gamma with which the colors are encoded, or 1 for linear data.
+    ///
+    /// # Returns
+    ///
+    /// A new `ImageTexture` instance.
+    fn decode(image: &DynamicImage, gamma: f64) -> ImageTexture {
+        if let DynamicImage::ImageRgb32F(_) | DynamicImage::ImageRgba32F(_) = image {
+            let rgba = image.to_rgba32f();
+            return ImageTexture {
+                width: image.width(),
+                height: image.height(),
+                texels: rgba
+                    .pixels()
+                    .map(|pixel| Vector3::new(pixel[0] as f64, pixel[1] as f64, pixel[2] as f64))
+                    .collect(),
+                alphas: rgba.pixels().map(|pixel| pixel[3] as f64).collect(),
+                wrap_u: WrapMode::Repeat,
+                wrap_v: WrapMode::Clamp,
+                mipmaps: Vec::new(),
+            };
+        }
+
+        let rgba = image.to_rgba8();
+
+        // Convert texture from Gamma to Linear colors
+        let texels = rgba
+            .pixels()
+            .map(|pixel| {
+                Vector3::new(
+                    (pixel[0] as f64 / 255.0).powf(gamma),
+                    (pixel[1] as f64 / 255.0).powf(gamma),
+                    (pixel[2] as f64 / 255.0).powf(gamma),
+                )
+            })
+            .collect();
+        let alphas = rgba.pixels().map(|pixel| pixel[3] as f64 / 255.0).collect();
+
+        ImageTexture {
+            width: image.width(),
+            height: image.height(),
+            texels,
+            alphas,
+            wrap_u: WrapMode::Repeat,
+            wrap_v: WrapMode::Clamp,
+            mipmaps: Vec::new(),
+        }
+    }
+
+    /// Sets the wrap modes of the texture.
+    ///
+    /// # Arguments
+    ///
+    /// * `wrap_u` - The wrap mode along the u-coordinate.
+    /// * `wrap_v` - The wrap mode along the v-coordinate.
+    ///
+    /// # Returns
+    ///
+    /// The `ImageTexture` using the given wrap modes.
+    pub fn with_wrap(mut self, wrap_u: WrapMode, wrap_v: WrapMode) -> ImageTexture {
+        self.wrap_u = wrap_u;
+        self.wrap_v = wrap_v;
+        self
+    }
+
+    /// Builds the mipmaps of the texture, so distant or grazing surfaces blend the two levels
+    /// whose texels are closest in size to the footprint of the ray instead of aliasing.
+    /// Without mipmaps, the image is always sampled at full resolution.
+    ///
+    /// # Returns
+    ///
+    /// The `ImageTexture` with its mipmaps.
+    pub fn with_mipmaps(mut self) -> ImageTexture {
+        self.mipmaps.clear();
+        loop {
+            let (width, height, texels) = self.level(self.mipmaps.len());
+            if width <= 1 && height <= 1 {
+                break;
+            }
+            let next = MipLevel::downsample(width, height, texels);
+            self.mipmaps.push(next);
+        }
+        self
+    }
+
+    /// Returns a level of the mipmaps, the image itself being the first.
+    ///
+    /// # Arguments
+    ///
+    /// * `level` - The index of the level, at most the number of mipmaps.
+    ///
+    /// # Returns
+    ///
+    /// The width and height of the level and its texels.
+    fn level(&self, level: usize) -> (u32, u32, &[Vector3]) {
+        match level {
+            0 => (self.width, self.height, &self.texels),
+            _ => {
+                let mip = &self.mipmaps[level - 1];
+                (mip.width, mip.height, &mip.texels)
             }
         }
     }
 
+    /// Samples a level of the mipmaps with bilinear filtering.
+    ///
+    /// # Arguments
+    ///
+    /// * `level` - The index of the level, the image itself being the first.
+    /// * `u` - The u-coordinate for texture mapping.
+    /// * `v` - The v-coordinate for texture mapping.
+    ///
+    /// # Returns
+    ///
+    /// The color value as a `Vector3`.
+    fn sample(&self, level: usize, u: f64, v: f64) -> Vector3 {
+        let (width, height, texels) = self.level(level);
+        self.bilinear(u, v, width, height)
+            .iter()
+            .fold(Vector3::new(0.0, 0.0, 0.0), |sum, &(index, weight)| {
+                sum + texels[index] * weight
+            })
+    }
+
+    /// Returns the opacity of the texture at the given texture coordinates, filtered like the
+    /// color at full resolution. Images without an alpha channel are fully opaque.
+    ///
+    /// # Arguments
+    ///
+    /// * `u` - The u-coordinate for texture mapping.
+    /// * `v` - The v-coordinate for texture mapping.
+    ///
+    /// # Returns
+    ///
+    /// The opacity, between 0 and 1.
+    pub fn alpha(&self, u: f64, v: f64) -> f64 {
+        if self.height == 0 {
+            return 1.0;
+        }
+
+        self.bilinear(u, v, self.width, self.height)
+            .iter()
+            .map(|&(index, weight)| self.alphas[index] * weight)
+            .sum()
+    }
+
+    /// Finds the four texels around the given texture coordinates and their bilinear weights.
+    /// The neighbours of border texels are found through the wrap modes, so no seam appears where
+    /// a repeating texture meets itself.
+    ///
+    /// # Arguments
+    ///
+    /// * `u` - The u-coordinate for texture mapping.
+    /// * `v` - The v-coordinate for texture mapping.
+    /// * `width` - The width in texels of the level of the mipmaps being sampled.
+    /// * `height` - The height in texels of the level of the mipmaps being sampled.
+    ///
+    /// # Returns
+    ///
+    /// The indices of the four texels and their weights, which sum to one.
+    fn bilinear(&self, u: f64, v: f64, width: u32, height: u32) -> [(usize, f64); 4] {
+        // Image rows go from top to bottom while v goes from bottom to top. Texel centers lie at
+        // half-integer positions.
+        let x = u * width as f64 - 0.5;
+        let y = (1.0 - v) * height as f64 - 0.5;
+
+        let i = x.floor();
+        let j = y.floor();
+        let fx = x - i;
+        let fy = y - j;
+        let i = i as i64;
+        let j = j as i64;
+
+        let index = |i: i64, j: i64| {
+            let i = self.wrap_u.wrap(i, width);
+            let j = self.wrap_v.wrap(j, height);
+            (j * width + i) as usize
+        };
+
+        [
+            (index(i, j), (1.0 - fx) * (1.0 - fy)),
+            (index(i + 1, j), fx * (1.0 - fy)),
+            (index(i, j + 1), (1.0 - fx) * fy),
+            (index(i + 1, j + 1), fx * fy),
+        ]
+    }
+
     /// Finds the file with the given name in various directories.
     ///
     /// # Arguments
@@ -194,34 +757,1011 @@ impl ImageTexture {
 }
 
 impl Texture for ImageTexture {
-    /// Returns the color value of the image texture at the given coordinates and point.
+    /// Returns the color value of the image texture at the given shading point.
+    /// The four texels around the point are blended with bilinear filtering. With mipmaps, the
+    /// two levels whose texels are closest in size to the footprint of the ray are sampled and
+    /// blended, which is trilinear filtering.
     ///
     /// # Arguments
     ///
-    /// * `u` - The u-coordinate for texture mapping.
-    /// * `v` - The v-coordinate for texture mapping.
-    /// * `p` - The point in 3D space.
+    /// * `context` - The shading context of the point.
     ///
     /// # Returns
     ///
     /// The color value as a `Vector3`.
-    fn value(&self, mut u: f64, mut v: f64, p: &Vector3) -> Vector3 {
-        if self.data.height() == 0 {
+    fn value(&self, context: &ShadingContext) -> Vector3 {
+        if self.height == 0 {
             return Vector3::new(0.0, 1.0, 1.0);
         }
 
-        u = u.clamp(0.0, 1.0);
-        v = 1.0 - v.clamp(0.0, 1.0);
+        // The number of texels the footprint covers along the axis where it covers the most, in
+        // powers of two, is the level whose texels match it.
+        let (du, dv) = context.footprint;
+        let level = (du * self.width as f64)
+            .max(dv * self.height as f64)
+            .log2()
+            .max(0.0)
+            .min(self.mipmaps.len() as f64);
+        let fine = level.floor();
+        let blend = level - fine;
+        let color = self.sample(fine as usize, context.u, context.v);
+        if blend > 0.0 {
+            color * (1.0 - blend) + self.sample(fine as usize + 1, context.u, context.v) * blend
+        } else {
+            color
+        }
+    }
+
+    /// Counts the memory taken by the texture, including its texels, their opacity and its
+    /// mipmaps.
+    ///
+    /// # Arguments
+    ///
+    /// * `statistics` - The statistics to add the texture to.
+    fn collect_statistics(&self, statistics: &mut SceneStatistics) {
+        let bytes = std::mem::size_of_val(self)
+            + self.texels.len() * std::mem::size_of::<Vector3>()
+            + self.alphas.len() * std::mem::size_of::<f64>()
+            + self
+                .mipmaps
+                .iter()
+                .map(|mip| mip.texels.len() * std::mem::size_of::<Vector3>())
+                .sum::<usize>();
+        statistics.add_texture(self as *const Self as usize, bytes);
+    }
+}
+
+impl<T: Texture + ?Sized> Texture for Arc<T> {
+    /// Returns the color value of the shared texture, so one texture can be used in several places.
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - The shading context of the point.
+    ///
+    /// # Returns
+    ///
+    /// The color value as a `Vector3`.
+    fn value(&self, context: &ShadingContext) -> Vector3 {
+        self.as_ref().value(context)
+    }
+
+    fn collect_statistics(&self, statistics: &mut SceneStatistics) {
+        self.as_ref().collect_statistics(statistics);
+    }
+}
 
-        let i = (u * (self.data.width() as f64)) as u32;
-        let j = (v * (self.data.height() as f64)) as u32;
+#[derive(Debug)]
+/// Represents a texture whose texture coordinates scroll over time, such as flowing water.
+pub struct ScrollingTexture {
+    /// The texture being scrolled.
+    texture: Box<dyn Texture>,
+    /// The change of the u-coordinate per unit of scene time.
+    speed_u: f64,
+    /// The change of the v-coordinate per unit of scene time.
+    speed_v: f64,
+}
 
-        let pixel = self.data.get_pixel(i, j);
-        let r_srgb = pixel[0] as f64 / 255.0;
-        let g_srgb = pixel[1] as f64 / 255.0;
-        let b_srgb = pixel[2] as f64 / 255.0;
+impl ScrollingTexture {
+    /// Creates a new `ScrollingTexture`.
+    ///
+    /// # Arguments
+    ///
+    /// * `texture` - The texture being scrolled.
+    /// * `speed_u` - The change of the u-coordinate per unit of scene time.
+    /// * `speed_v` - The change of the v-coordinate per unit of scene time.
+    ///
+    /// # Returns
+    ///
+    /// A new `ScrollingTexture` instance.
+    pub fn new(texture: Box<dyn Texture>, speed_u: f64, speed_v: f64) -> ScrollingTexture {
+        ScrollingTexture {
+            texture,
+            speed_u,
+            speed_v,
+        }
+    }
+}
 
-        // Convert texture from Gamma to Linear colors
-        Vector3::new(r_srgb.powf(2.2), g_srgb.powf(2.2), b_srgb.powf(2.2))
+impl Texture for ScrollingTexture {
+    /// Returns the color value of the scrolled texture, with the texture coordinates shifted by the
+    /// scene time and wrapped back into the unit square.
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - The shading context of the point.
+    ///
+    /// # Returns
+    ///
+    /// The color value as a `Vector3`.
+    fn value(&self, context: &ShadingContext) -> Vector3 {
+        let shifted = ShadingContext {
+            u: (context.u + self.speed_u * context.time).rem_euclid(1.0),
+            v: (context.v + self.speed_v * context.time).rem_euclid(1.0),
+            ..*context
+        };
+        self.texture.value(&shifted)
+    }
+
+    fn surface_queries(&self) -> bool {
+        self.texture.surface_queries()
+    }
+
+    fn collect_statistics(&self, statistics: &mut SceneStatistics) {
+        if statistics.add_texture(self as *const Self as usize, std::mem::size_of_val(self)) {
+            self.texture.collect_statistics(statistics);
+        }
+    }
+}
+
+/// A transformation of texture coordinates: a scale, then a rotation about the origin, then an
+/// offset. It is applied to the coordinates of the shading point before the texture is looked
+/// up, so a larger scale repeats the texture more often.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UvTransform {
+    /// The factors by which the u- and v-coordinates are multiplied.
+    scale: (f64, f64),
+    /// The angle in degrees by which the scaled coordinates are rotated counterclockwise.
+    rotation: f64,
+    /// The amounts added to the u- and v-coordinates after the rotation.
+    offset: (f64, f64),
+}
+
+impl UvTransform {
+    /// Creates a new `UvTransform` leaving texture coordinates unchanged.
+    ///
+    /// # Returns
+    ///
+    /// A new `UvTransform` instance.
+    pub fn new() -> UvTransform {
+        UvTransform {
+            scale: (1.0, 1.0),
+            rotation: 0.0,
+            offset: (0.0, 0.0),
+        }
+    }
+
+    /// Sets the factors by which the texture coordinates are multiplied.
+    ///
+    /// # Arguments
+    ///
+    /// * `u` - The factor of the u-coordinate, the number of times the texture repeats along it.
+    /// * `v` - The factor of the v-coordinate, the number of times the texture repeats along it.
+    ///
+    /// # Returns
+    ///
+    /// The `UvTransform` with the given scale.
+    pub fn with_scale(mut self, u: f64, v: f64) -> UvTransform {
+        self.scale = (u, v);
+        self
+    }
+
+    /// Sets the angle by which the scaled texture coordinates are rotated.
+    ///
+    /// # Arguments
+    ///
+    /// * `degrees` - The angle in degrees, counterclockwise from the u-axis towards the v-axis.
+    ///
+    /// # Returns
+    ///
+    /// The `UvTransform` with the given rotation.
+    pub fn with_rotation(mut self, degrees: f64) -> UvTransform {
+        self.rotation = degrees;
+        self
+    }
+
+    /// Sets the amounts added to the texture coordinates after they are scaled and rotated.
+    ///
+    /// # Arguments
+    ///
+    /// * `u` - The amount added to the u-coordinate.
+    /// * `v` - The amount added to the v-coordinate.
+    ///
+    /// # Returns
+    ///
+    /// The `UvTransform` with the given offset.
+    pub fn with_offset(mut self, u: f64, v: f64) -> UvTransform {
+        self.offset = (u, v);
+        self
+    }
+
+    /// Transforms texture coordinates.
+    ///
+    /// # Arguments
+    ///
+    /// * `u` - The u-coordinate of the shading point.
+    /// * `v` - The v-coordinate of the shading point.
+    ///
+    /// # Returns
+    ///
+    /// The transformed coordinates, which are not wrapped, so textures apply their own wrapping.
+    pub fn apply(&self, u: f64, v: f64) -> (f64, f64) {
+        let (u, v) = (u * self.scale.0, v * self.scale.1);
+        let (sin, cos) = self.rotation.to_radians().sin_cos();
+        (
+            cos * u - sin * v + self.offset.0,
+            sin * u + cos * v + self.offset.1,
+        )
+    }
+
+    /// Transforms the extent of the footprint of a ray in texture coordinates, which grows with
+    /// the scale and, when rotated, spreads over both coordinates.
+    ///
+    /// # Arguments
+    ///
+    /// * `footprint` - The extent of the footprint along the u- and v-coordinates.
+    ///
+    /// # Returns
+    ///
+    /// The extent of the footprint along the transformed coordinates.
+    pub fn footprint(&self, footprint: (f64, f64)) -> (f64, f64) {
+        let (u, v) = (
+            footprint.0 * self.scale.0.abs(),
+            footprint.1 * self.scale.1.abs(),
+        );
+        let (sin, cos) = self.rotation.to_radians().sin_cos();
+        (cos.abs() * u + sin.abs() * v, sin.abs() * u + cos.abs() * v)
+    }
+}
+
+impl Default for UvTransform {
+    /// Creates a `UvTransform` leaving texture coordinates unchanged.
+    fn default() -> Self {
+        UvTransform::new()
+    }
+}
+
+#[derive(Debug)]
+/// Represents any texture looked up at transformed texture coordinates, to tile, rotate or shift
+/// it on a surface without changing the texture coordinates of the surface.
+pub struct TransformedTexture {
+    /// The texture being transformed.
+    texture: Box<dyn Texture>,
+    /// The transformation of the texture coordinates.
+    transform: UvTransform,
+}
+
+impl TransformedTexture {
+    /// Creates a new `TransformedTexture`.
+    ///
+    /// # Arguments
+    ///
+    /// * `texture` - The texture being transformed.
+    /// * `transform` - The transformation of the texture coordinates.
+    ///
+    /// # Returns
+    ///
+    /// A new `TransformedTexture` instance.
+    pub fn new(texture: Box<dyn Texture>, transform: UvTransform) -> TransformedTexture {
+        TransformedTexture { texture, transform }
+    }
+}
+
+impl Texture for TransformedTexture {
+    /// Returns the color value of the texture at the transformed texture coordinates. Textures
+    /// evaluated in object or world space are unaffected.
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - The shading context of the point.
+    ///
+    /// # Returns
+    ///
+    /// The color value as a `Vector3`.
+    fn value(&self, context: &ShadingContext) -> Vector3 {
+        let (u, v) = self.transform.apply(context.u, context.v);
+        let footprint = self.transform.footprint(context.footprint);
+        self.texture.value(&ShadingContext {
+            u,
+            v,
+            footprint,
+            ..*context
+        })
+    }
+
+    fn surface_queries(&self) -> bool {
+        self.texture.surface_queries()
+    }
+
+    fn collect_statistics(&self, statistics: &mut SceneStatistics) {
+        if statistics.add_texture(self as *const Self as usize, std::mem::size_of_val(self)) {
+            self.texture.collect_statistics(statistics);
+        }
+    }
+}
+
+#[derive(Debug)]
+/// Represents a marble-like texture built from Perlin turbulence, drifting through space over time.
+pub struct NoiseTexture {
+    /// The noise generator.
+    noise: Perlin,
+    /// The frequency of the pattern.
+    scale: f64,
+    /// The velocity at which the pattern drifts through space.
+    velocity: Vector3,
+}
+
+impl NoiseTexture {
+    /// Creates a new `NoiseTexture`.
+    ///
+    /// # Arguments
+    ///
+    /// * `scale` - The frequency of the pattern.
+    /// * `velocity` - The velocity at which the pattern drifts, zero for a static pattern.
+    ///
+    /// # Returns
+    ///
+    /// A new `NoiseTexture` instance.
+    pub fn new(scale: f64, velocity: Vector3) -> NoiseTexture {
+        NoiseTexture {
+            noise: Perlin::new(0),
+            scale,
+            velocity,
+        }
+    }
+}
+
+impl Texture for NoiseTexture {
+    /// Returns the color value of the noise texture at the given shading point and time.
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - The shading context of the point.
+    ///
+    /// # Returns
+    ///
+    /// The color value as a `Vector3`.
+    fn value(&self, context: &ShadingContext) -> Vector3 {
+        let p = context.point - self.velocity * context.time;
+        let phase = self.scale * p.z + 10.0 * self.noise.turbulence(&p, 7);
+        Vector3::new(0.5, 0.5, 0.5) * (1.0 + phase.sin())
+    }
+}
+
+#[derive(Debug)]
+/// Represents a texture whose brightness pulses over time, such as a flickering screen.
+pub struct PulseTexture {
+    /// The texture being modulated.
+    texture: Box<dyn Texture>,
+    /// The number of pulses per unit of scene time.
+    frequency: f64,
+    /// The smallest brightness factor reached during a pulse.
+    minimum: f64,
+}
+
+impl PulseTexture {
+    /// Creates a new `PulseTexture`.
+    ///
+    /// # Arguments
+    ///
+    /// * `texture` - The texture being modulated.
+    /// * `frequency` - The number of pulses per unit of scene time.
+    /// * `minimum` - The smallest brightness factor reached during a pulse, between 0 and 1.
+    ///
+    /// # Returns
+    ///
+    /// A new `PulseTexture` instance.
+    pub fn new(texture: Box<dyn Texture>, frequency: f64, minimum: f64) -> PulseTexture {
+        PulseTexture {
+            texture,
+            frequency,
+            minimum: minimum.clamp(0.0, 1.0),
+        }
+    }
+}
+
+impl Texture for PulseTexture {
+    /// Returns the color value of the modulated texture at the given shading point and time.
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - The shading context of the point.
+    ///
+    /// # Returns
+    ///
+    /// The color value as a `Vector3`.
+    fn value(&self, context: &ShadingContext) -> Vector3 {
+        let wave = 0.5 * (1.0 + (2.0 * PI * self.frequency * context.time).cos());
+        let brightness = self.minimum + (1.0 - self.minimum) * wave;
+        self.texture.value(context) * brightness
+    }
+
+    fn surface_queries(&self) -> bool {
+        self.texture.surface_queries()
+    }
+
+    fn collect_statistics(&self, statistics: &mut SceneStatistics) {
+        if statistics.add_texture(self as *const Self as usize, std::mem::size_of_val(self)) {
+            self.texture.collect_statistics(statistics);
+        }
+    }
+}
+
+/// The properties of a surface point that can drive a `SurfaceMaskTexture`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SurfaceQuery {
+    /// The fraction of the hemisphere above the point blocked by nearby objects, high in corners
+    /// and crevices where dirt gathers.
+    Occlusion,
+    /// How sharply the surface bends around the point, positive on edges and corners that wear
+    /// and negative in creases.
+    Curvature,
+    /// The cosine between a direction and the normal on the side the surface is seen from,
+    /// such as straight up for dust settling on top surfaces or north for moss.
+    Facing(Vector3),
+    /// The random value of the instance the point belongs to, so some of many instances of one
+    /// object show the overlay, such as a few trees of a forest turning red.
+    Variation,
+}
+
+#[derive(Debug)]
+/// Represents a texture laying an overlay over a base where a property of the surface lies in a
+/// range, for procedural wear and tear: worn edges, dust on top surfaces or moss on north faces.
+pub struct SurfaceMaskTexture {
+    /// The property of the surface driving the mask.
+    query: SurfaceQuery,
+    /// The texture seen where the mask is off.
+    base: Box<dyn Texture>,
+    /// The texture seen where the mask is on.
+    overlay: Box<dyn Texture>,
+    /// The values of the property at which the mask starts and ends turning on.
+    range: (f64, f64),
+}
+
+impl SurfaceMaskTexture {
+    /// Creates a new `SurfaceMaskTexture`. The mask turns on smoothly as the property goes
+    /// through the range, and is inverted if the range goes down.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The property of the surface driving the mask.
+    /// * `base` - The texture seen where the mask is off.
+    /// * `overlay` - The texture seen where the mask is on.
+    /// * `range` - The values of the property at which the mask starts and ends turning on.
+    ///
+    /// # Returns
+    ///
+    /// A new `SurfaceMaskTexture` instance.
+    pub fn new(
+        query: SurfaceQuery,
+        base: Box<dyn Texture>,
+        overlay: Box<dyn Texture>,
+        range: (f64, f64),
+    ) -> SurfaceMaskTexture {
+        let query = match query {
+            SurfaceQuery::Facing(direction) => SurfaceQuery::Facing(direction.normalize()),
+            query => query,
+        };
+        SurfaceMaskTexture {
+            query,
+            base,
+            overlay,
+            range,
+        }
+    }
+
+    /// Returns the strength of the mask at a shading point.
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - The shading context of the point.
+    ///
+    /// # Returns
+    ///
+    /// The strength, between 0 where the base is seen and 1 where the overlay is.
+    fn mask(&self, context: &ShadingContext) -> f64 {
+        let value = match self.query {
+            SurfaceQuery::Occlusion => context.occlusion,
+            SurfaceQuery::Curvature => context.curvature,
+            SurfaceQuery::Facing(direction) => context.normal.dot(&direction),
+            SurfaceQuery::Variation => context.variation,
+        };
+        let (start, end) = self.range;
+        if start == end {
+            return if value >= end { 1.0 } else { 0.0 };
+        }
+        let x = ((value - start) / (end - start)).clamp(0.0, 1.0);
+        x * x * (3.0 - 2.0 * x)
+    }
+}
+
+impl Texture for SurfaceMaskTexture {
+    /// Returns the color value of the base and overlay blended by the mask.
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - The shading context of the point.
+    ///
+    /// # Returns
+    ///
+    /// The color value as a `Vector3`.
+    fn value(&self, context: &ShadingContext) -> Vector3 {
+        let mask = self.mask(context);
+        if mask <= 0.0 {
+            return self.base.value(context);
+        }
+        if mask >= 1.0 {
+            return self.overlay.value(context);
+        }
+        self.base.value(context) * (1.0 - mask) + self.overlay.value(context) * mask
+    }
+
+    fn surface_queries(&self) -> bool {
+        matches!(
+            self.query,
+            SurfaceQuery::Occlusion | SurfaceQuery::Curvature
+        ) || self.base.surface_queries()
+            || self.overlay.surface_queries()
+    }
+
+    fn collect_statistics(&self, statistics: &mut SceneStatistics) {
+        if statistics.add_texture(self as *const Self as usize, std::mem::size_of_val(self)) {
+            self.base.collect_statistics(statistics);
+            self.overlay.collect_statistics(statistics);
+        }
+    }
+}
+
+#[derive(Debug)]
+/// Represents a texture multiplied by the color tint of the instance it is seen on, so many
+/// instances of one object sharing a material each get their own shade.
+pub struct TintedTexture {
+    /// The texture being tinted.
+    texture: Box<dyn Texture>,
+}
+
+impl TintedTexture {
+    /// Creates a new `TintedTexture`.
+    ///
+    /// # Arguments
+    ///
+    /// * `texture` - The texture being tinted, seen unchanged outside of instances.
+    ///
+    /// # Returns
+    ///
+    /// A new `TintedTexture` instance.
+    pub fn new(texture: Box<dyn Texture>) -> TintedTexture {
+        TintedTexture { texture }
+    }
+}
+
+impl Texture for TintedTexture {
+    /// Returns the color value of the texture multiplied by the tint of the instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - The shading context of the point.
+    ///
+    /// # Returns
+    ///
+    /// The color value as a `Vector3`.
+    fn value(&self, context: &ShadingContext) -> Vector3 {
+        self.texture.value(context) * context.tint
+    }
+
+    fn surface_queries(&self) -> bool {
+        self.texture.surface_queries()
+    }
+
+    fn collect_statistics(&self, statistics: &mut SceneStatistics) {
+        if statistics.add_texture(self as *const Self as usize, std::mem::size_of_val(self)) {
+            self.texture.collect_statistics(statistics);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Lambertian;
+
+    #[test]
+    fn test_shading_context_from_hit() {
+        let material = Lambertian::new(Vector3::new(0.5, 0.5, 0.5));
+        let ray = Ray::with_time(
+            Vector3::new(0.0, 0.0, 5.0),
+            Vector3::new(0.0, 0.0, -2.0),
+            0.25,
+        );
+        let mut record = HitRecord::new(4.0, Vector3::new(0.0, 0.0, 1.0), &material, 0.3, 0.7);
+        record.set_face_normal(&ray, &Vector3::new(0.0, 0.0, 1.0));
+
+        let context = ShadingContext::new(&ray, &record);
+        assert_eq!(context.u, 0.3);
+        assert_eq!(context.v, 0.7);
+        assert_eq!(context.point, Vector3::new(0.0, 0.0, 1.0));
+        assert_eq!(context.normal, Vector3::new(0.0, 0.0, 1.0));
+        assert!(context.front_face);
+        assert_eq!(context.direction, Vector3::new(0.0, 0.0, -1.0));
+        assert_eq!(context.time, 0.25);
+    }
+
+    #[test]
+    fn test_checker_uses_point() {
+        let checker = CheckerTexture::new(
+            1.0,
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(1.0, 1.0, 1.0),
+        );
+        let even = ShadingContext {
+            point: Vector3::new(0.5, 0.5, 0.5),
+            ..ShadingContext::default()
+        };
+        let odd = ShadingContext {
+            point: Vector3::new(1.5, 0.5, 0.5),
+            ..ShadingContext::default()
+        };
+
+        assert_eq!(checker.value(&even), Vector3::new(1.0, 1.0, 1.0));
+        assert_eq!(checker.value(&odd), Vector3::new(0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn test_scrolling_texture_wraps() {
+        let checker = Box::new(CheckerTexture::new(
+            1.0,
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(1.0, 1.0, 1.0),
+        ));
+        let texture = ScrollingTexture::new(Box::new(UvProbe), 0.5, -0.25);
+        let context = ShadingContext {
+            u: 0.75,
+            v: 0.1,
+            time: 1.0,
+            ..ShadingContext::default()
+        };
+        let value = texture.value(&context);
+        assert!((value.x - 0.25).abs() < 1e-12);
+        assert!((value.y - 0.85).abs() < 1e-12);
+
+        // Textures that ignore the coordinates are unaffected.
+        let still = ScrollingTexture::new(checker, 0.5, 0.5);
+        assert_eq!(still.value(&context), Vector3::new(1.0, 1.0, 1.0),);
+    }
+
+    #[test]
+    fn test_noise_texture_drifts_with_time() {
+        let texture = NoiseTexture::new(4.0, Vector3::new(1.0, 0.0, 0.0));
+        let at = |point: Vector3, time: f64| {
+            texture.value(&ShadingContext {
+                point,
+                time,
+                ..ShadingContext::default()
+            })
+        };
+
+        let p = Vector3::new(0.3, 0.4, 0.5);
+        let moved = at(p + Vector3::new(2.0, 0.0, 0.0), 2.0);
+        assert!((at(p, 0.0) - moved).length() < 1e-9);
+    }
+
+    #[test]
+    fn test_pulse_texture_period() {
+        let texture = PulseTexture::new(
+            Box::new(SolidTexture::new(Vector3::new(2.0, 2.0, 2.0))),
+            2.0,
+            0.25,
+        );
+        let at = |time: f64| {
+            texture.value(&ShadingContext {
+                time,
+                ..ShadingContext::default()
+            })
+        };
+
+        assert!((at(0.0).x - 2.0).abs() < 1e-12);
+        assert!((at(0.25).x - 0.5).abs() < 1e-12);
+        assert!((at(0.5).x - 2.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_checker_spaces_follow_moved_objects() {
+        use crate::hit::Hittable;
+        use crate::shapes::sphere::Sphere;
+        use crate::transformation::Translate;
+        use std::sync::Arc;
+
+        let material = Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5)));
+        let sphere = Arc::new(Sphere::new(Vector3::new(0.0, 0.0, 0.0), 1.0, material));
+        let offset = Vector3::new(0.5, 0.0, 0.0);
+        let moved = Translate::new(sphere.clone(), offset);
+
+        let ray = Ray::new(Vector3::new(0.3, 0.2, 5.0), Vector3::new(0.0, 0.0, -1.0));
+        let moved_ray = Ray::new(ray.origin + offset, ray.direction);
+        let record = sphere.hit(&ray, (0.001, f64::INFINITY)).unwrap();
+        let moved_record = moved.hit(&moved_ray, (0.001, f64::INFINITY)).unwrap();
+        let context = ShadingContext::new(&ray, &record);
+        let moved_context = ShadingContext::new(&moved_ray, &moved_record);
+
+        for space in [TextureSpace::Uv, TextureSpace::Object] {
+            let position = context.position(space);
+            let moved_position = moved_context.position(space);
+            assert!((position - moved_position).length() < 1e-9, "{:?}", space);
+        }
+        let world_shift =
+            moved_context.position(TextureSpace::World) - context.position(TextureSpace::World);
+        assert!((world_shift - offset).length() < 1e-9);
+    }
+
+    #[test]
+    fn test_uv_checker() {
+        let checker = CheckerTexture::new(
+            4.0,
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(1.0, 1.0, 1.0),
+        )
+        .with_space(TextureSpace::Uv);
+
+        assert_eq!(
+            checker.value(&ShadingContext::from_uv(0.1, 0.1)),
+            Vector3::new(1.0, 1.0, 1.0)
+        );
+        assert_eq!(
+            checker.value(&ShadingContext::from_uv(0.3, 0.1)),
+            Vector3::new(0.0, 0.0, 0.0)
+        );
+        assert_eq!(
+            checker.value(&ShadingContext::from_uv(0.3, 0.3)),
+            Vector3::new(1.0, 1.0, 1.0)
+        );
+    }
+
+    #[test]
+    fn test_mipmaps_follow_footprint() {
+        // A checkerboard of black and white texels averages to gray in the coarser levels.
+        let image = image::RgbImage::from_fn(8, 4, |x, y| {
+            let value = if (x + y) % 2 == 0 { 255 } else { 0 };
+            image::Rgb([value, value, value])
+        });
+        let texture = ImageTexture::from_data_image(&DynamicImage::ImageRgb8(image));
+        let mipmapped = gradient_image().with_mipmaps();
+        assert_eq!(mipmapped.mipmaps.len(), 2);
+        assert_eq!(mipmapped.level(2).0, 1);
+        let texture = texture.with_mipmaps();
+        assert_eq!(texture.mipmaps.len(), 3);
+        assert_eq!(
+            (texture.mipmaps[0].width, texture.mipmaps[0].height),
+            (4, 2)
+        );
+
+        // The center of the top-left texel.
+        let mut context = ShadingContext::from_uv(1.0 / 16.0, 7.0 / 8.0);
+        assert_eq!(texture.value(&context), Vector3::new(1.0, 1.0, 1.0));
+
+        // A footprint of a few texels reads a coarser level.
+        context.footprint = (4.0 / 8.0, 0.0);
+        let gray = texture.value(&context);
+        assert!(
+            (gray - Vector3::new(0.5, 0.5, 0.5)).length() < 1e-9,
+            "{:?}",
+            gray
+        );
+
+        // Between levels, the two closest levels are blended.
+        context.footprint = (1.5 / 8.0, 0.0);
+        let blended = texture.value(&context).x;
+        assert!(blended > 0.5 && blended < 1.0, "{}", blended);
+    }
+
+    #[test]
+    fn test_footprint_from_differentials() {
+        let material = Lambertian::new(Vector3::new(0.5, 0.5, 0.5));
+        let mut ray = Ray::new(Vector3::new(0.0, 0.0, 0.0), Vector3::new(0.0, 0.0, -1.0));
+        ray.spread = 0.01;
+        let mut record = HitRecord::new(10.0, ray.point_at(10.0), &material, 0.5, 0.5);
+        record.set_tangents(Vector3::new(4.0, 0.0, 0.0), Vector3::new(0.0, 2.0, 0.0));
+
+        let context = ShadingContext::new(&ray, &record);
+        assert!((context.footprint.0 - 0.025).abs() < 1e-12);
+        assert!((context.footprint.1 - 0.05).abs() < 1e-12);
+
+        let transformed = UvTransform::new()
+            .with_scale(2.0, 2.0)
+            .with_rotation(90.0)
+            .footprint(context.footprint);
+        assert!((transformed.0 - 0.1).abs() < 1e-12 && (transformed.1 - 0.05).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_transformed_texture() {
+        let transform = UvTransform::new()
+            .with_scale(2.0, 4.0)
+            .with_rotation(90.0)
+            .with_offset(0.5, 0.25);
+        let (u, v) = transform.apply(0.25, 0.125);
+        assert!((u - 0.0).abs() < 1e-12 && (v - 0.75).abs() < 1e-12);
+        assert_eq!(UvTransform::default().apply(0.3, 0.7), (0.3, 0.7));
+
+        // A checker of one square per unit, scaled twice as much along u, has square cells on a
+        // sphere, whose u-coordinate goes around twice as far as its v-coordinate.
+        let checker = TransformedTexture::new(
+            Box::new(
+                CheckerTexture::new(
+                    1.0,
+                    Vector3::new(0.0, 0.0, 0.0),
+                    Vector3::new(1.0, 1.0, 1.0),
+                )
+                .with_space(TextureSpace::Uv),
+            ),
+            UvTransform::new().with_scale(8.0, 4.0),
+        );
+        let white = Vector3::new(1.0, 1.0, 1.0);
+        assert_eq!(checker.value(&ShadingContext::from_uv(0.1, 0.1)), white);
+        assert_eq!(
+            checker.value(&ShadingContext::from_uv(0.2, 0.1)),
+            Vector3::default()
+        );
+        assert_eq!(checker.value(&ShadingContext::from_uv(0.2, 0.3)), white);
+    }
+
+    /// Builds a 4 by 2 image whose top row goes from black to white and whose bottom row is red.
+    fn gradient_image() -> ImageTexture {
+        let mut image = image::RgbImage::new(4, 2);
+        for x in 0..4 {
+            let level = (x * 85) as u8;
+            image.put_pixel(x, 0, image::Rgb([level, level, level]));
+            image.put_pixel(x, 1, image::Rgb([255, 0, 0]));
+        }
+        ImageTexture::from_image(&DynamicImage::ImageRgb8(image))
+    }
+
+    #[test]
+    fn test_image_texture_repeat_across_seam() {
+        let texture = gradient_image();
+        let top_row = |u: f64| texture.value(&ShadingContext::from_uv(u, 0.75));
+
+        // Both sides of the seam blend the first and the last column.
+        let expected = (Vector3::new(0.0, 0.0, 0.0) + Vector3::new(1.0, 1.0, 1.0)) * 0.5;
+        assert!((top_row(0.0) - expected).length() < 1e-12);
+        assert!((top_row(1.0) - expected).length() < 1e-12);
+        assert!((top_row(1e-9) - top_row(1.0 - 1e-9)).length() < 1e-6);
+        assert!((top_row(0.3) - top_row(1.3)).length() < 1e-12);
+        assert!((top_row(0.3) - top_row(-0.7)).length() < 1e-12);
+    }
+
+    #[test]
+    fn test_image_texture_clamp_and_mirror() {
+        let texture = gradient_image().with_wrap(WrapMode::Clamp, WrapMode::Clamp);
+        let black = texture.value(&ShadingContext::from_uv(0.0, 0.75));
+        assert!(black.length() < 1e-12);
+        let white = texture.value(&ShadingContext::from_uv(1.0, 0.75));
+        assert!((white - Vector3::new(1.0, 1.0, 1.0)).length() < 1e-12);
+
+        // Beyond the top of the image, the top row is extended instead of blending in the bottom row.
+        let top = texture.value(&ShadingContext::from_uv(1.0, 1.0));
+        assert!((top - Vector3::new(1.0, 1.0, 1.0)).length() < 1e-12);
+
+        let mirrored = gradient_image().with_wrap(WrapMode::Mirror, WrapMode::Clamp);
+        let inside = mirrored.value(&ShadingContext::from_uv(0.8, 0.75));
+        let reflected = mirrored.value(&ShadingContext::from_uv(1.2, 0.75));
+        assert!((inside - reflected).length() < 1e-12);
+    }
+
+    #[test]
+    fn test_data_image_is_not_gamma_decoded() {
+        let mut image = image::RgbImage::new(1, 1);
+        image.put_pixel(0, 0, image::Rgb([128, 128, 255]));
+        let image = DynamicImage::ImageRgb8(image);
+        let context = ShadingContext::from_uv(0.5, 0.5);
+
+        let data = ImageTexture::from_data_image(&image).value(&context);
+        assert!((data - Vector3::new(128.0 / 255.0, 128.0 / 255.0, 1.0)).length() < 1e-12);
+        let color = ImageTexture::from_image(&image).value(&context);
+        assert!(color.x < 0.25);
+    }
+
+    #[test]
+    fn test_hdr_image_keeps_bright_values() {
+        let mut image = image::Rgb32FImage::new(2, 1);
+        image.put_pixel(0, 0, image::Rgb([8.0, 0.5, 0.0]));
+        image.put_pixel(1, 0, image::Rgb([8.0, 0.5, 0.0]));
+        let texture = ImageTexture::from_image(&DynamicImage::ImageRgb32F(image));
+
+        let value = texture.value(&ShadingContext::from_uv(0.3, 0.5));
+        assert!((value - Vector3::new(8.0, 0.5, 0.0)).length() < 1e-6);
+        assert_eq!(texture.alpha(0.3, 0.5), 1.0);
+    }
+
+    #[test]
+    fn test_tiled_image_repeats_along_both_axes() {
+        let tiled = ImageTexture::tiled("earthmap.jpg");
+        let sphere = ImageTexture::new("earthmap.jpg");
+        let value =
+            |texture: &ImageTexture, u: f64, v: f64| texture.value(&ShadingContext::from_uv(u, v));
+        assert!((value(&tiled, 1.3, 2.2) - value(&tiled, 0.3, 0.2)).length() < 1e-9);
+        assert!((value(&sphere, 0.3, 2.2) - value(&sphere, 0.3, 1.0)).length() < 1e-9);
+    }
+
+    #[test]
+    fn test_wrap_mode_indices() {
+        assert_eq!(WrapMode::Repeat.wrap(-1, 4), 3);
+        assert_eq!(WrapMode::Repeat.wrap(4, 4), 0);
+        assert_eq!(WrapMode::Clamp.wrap(-1, 4), 0);
+        assert_eq!(WrapMode::Clamp.wrap(4, 4), 3);
+        assert_eq!(WrapMode::Mirror.wrap(-1, 4), 0);
+        assert_eq!(WrapMode::Mirror.wrap(4, 4), 3);
+        assert_eq!(WrapMode::Mirror.wrap(8, 4), 0);
+    }
+
+    #[test]
+    fn test_surface_mask_texture() {
+        let black = || -> Box<dyn Texture> { Box::new(SolidTexture::new(Vector3::default())) };
+        let white =
+            || -> Box<dyn Texture> { Box::new(SolidTexture::new(Vector3::new(1.0, 1.0, 1.0))) };
+        let context = |occlusion: f64, normal: Vector3| ShadingContext {
+            occlusion,
+            curvature: -occlusion,
+            normal,
+            ..ShadingContext::default()
+        };
+        let up = Vector3::new(0.0, 1.0, 0.0);
+
+        // The mask turns on smoothly through the range, halfway in its middle.
+        let dirt = SurfaceMaskTexture::new(SurfaceQuery::Occlusion, black(), white(), (0.2, 0.6));
+        assert!(dirt.surface_queries());
+        assert_eq!(dirt.value(&context(0.1, up)).x, 0.0);
+        assert!((dirt.value(&context(0.4, up)).x - 0.5).abs() < 1e-12);
+        assert_eq!(dirt.value(&context(0.9, up)).x, 1.0);
+
+        // A range going down inverts the mask.
+        let creases =
+            SurfaceMaskTexture::new(SurfaceQuery::Curvature, black(), white(), (0.0, -0.5));
+        assert_eq!(creases.value(&context(0.0, up)).x, 0.0);
+        assert_eq!(creases.value(&context(0.6, up)).x, 1.0);
+
+        // Facing needs no scene queries, and its direction does not have to be normalized.
+        let dust = SurfaceMaskTexture::new(
+            SurfaceQuery::Facing(Vector3::new(0.0, 5.0, 0.0)),
+            black(),
+            white(),
+            (0.5, 1.0),
+        );
+        assert!(!dust.surface_queries());
+        assert_eq!(dust.value(&context(0.0, up)).x, 1.0);
+        assert_eq!(dust.value(&context(0.0, -up)).x, 0.0);
+        let nested = ScrollingTexture::new(Box::new(dirt), 0.0, 0.0);
+        assert!(nested.surface_queries());
+    }
+
+    #[test]
+    fn test_instance_textures() {
+        let gray =
+            || -> Box<dyn Texture> { Box::new(SolidTexture::new(Vector3::new(0.5, 0.5, 0.5))) };
+        let context = ShadingContext {
+            variation: 0.8,
+            tint: Vector3::new(1.0, 0.5, 0.0),
+            ..ShadingContext::default()
+        };
+
+        // Outside of instances the tint is white, leaving the texture unchanged.
+        let tinted = TintedTexture::new(gray());
+        assert_eq!(tinted.value(&context), Vector3::new(0.5, 0.25, 0.0));
+        assert_eq!(
+            tinted.value(&ShadingContext::from_uv(0.3, 0.7)),
+            Vector3::new(0.5, 0.5, 0.5)
+        );
+
+        // The variation picks between the base and the overlay without scene queries.
+        let autumn = SurfaceMaskTexture::new(
+            SurfaceQuery::Variation,
+            gray(),
+            Box::new(tinted),
+            (0.7, 0.7),
+        );
+        assert!(!autumn.surface_queries());
+        assert_eq!(autumn.value(&context), Vector3::new(0.5, 0.25, 0.0));
+        assert_eq!(
+            autumn.value(&ShadingContext::default()),
+            Vector3::new(0.5, 0.5, 0.5)
+        );
+    }
+
+    /// A texture returning its texture coordinates as a color.
+    #[derive(Debug)]
+    struct UvProbe;
+
+    impl Texture for UvProbe {
+        fn value(&self, context: &ShadingContext) -> Vector3 {
+            Vector3::new(context.u, context.v, 0.0)
+        }
     }
 }
@@ -1,5 +1,9 @@
+use crate::aabb::Aabb;
 use crate::hit::{HitRecord, Hittable};
+use crate::material_table::MaterialTable;
 use crate::ray::Ray;
+use crate::shapes::skin::{hermite, keyframes_around};
+use crate::statistics::SceneStatistics;
 use crate::vector3::Vector3;
 use std::sync::Arc;
 
@@ -38,8 +42,9 @@ impl Hittable for Translate {
     /// # Returns
     ///
     /// An `Option` containing the `HitRecord` if an intersection is found, or `None` if no intersection is found.
-    fn hit(&self, ray: &Ray, interval: (f64, f64)) -> Option<HitRecord> {
-        let ray_offset = Ray::new(ray.origin - self.offset, ray.direction);
+    fn hit(&self, ray: &Ray, interval: (f64, f64)) -> Option<HitRecord<'_>> {
+        let mut ray_offset = Ray::with_time(ray.origin - self.offset, ray.direction, ray.time);
+        ray_offset.primary = ray.primary;
 
         if let Some(mut hit_record) = self.object.hit(&ray_offset, interval) {
             hit_record.poz += self.offset;
@@ -48,6 +53,48 @@ impl Hittable for Translate {
             None
         }
     }
+
+    fn is_emissive(&self) -> bool {
+        self.object.is_emissive()
+    }
+
+    fn power(&self) -> Vector3 {
+        self.object.power()
+    }
+
+    fn pdf_value(&self, origin: &Vector3, direction: &Vector3) -> f64 {
+        self.object.pdf_value(&(*origin - self.offset), direction)
+    }
+
+    fn random(&self, origin: &Vector3) -> Vector3 {
+        self.object.random(&(*origin - self.offset))
+    }
+
+    fn collect_materials(&self, table: &mut MaterialTable) {
+        self.object.collect_materials(table);
+    }
+
+    fn collect_statistics(&self, statistics: &mut SceneStatistics) {
+        statistics.add_geometry(std::mem::size_of_val(self));
+        self.object.collect_statistics(statistics);
+    }
+
+    fn closest_point(&self, point: &Vector3) -> Option<Vector3> {
+        self.object
+            .closest_point(&(*point - self.offset))
+            .map(|closest| closest + self.offset)
+    }
+
+    fn point_at_time(&self, point: &Vector3, from: f64, to: f64) -> Vector3 {
+        self.object.point_at_time(&(*point - self.offset), from, to) + self.offset
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.object.bounding_box().map(|bounds| Aabb {
+            min: bounds.min + self.offset,
+            max: bounds.max + self.offset,
+        })
+    }
 }
 
 /// Represents a rotation transformation around the Y-axis applied to a hittable object.
@@ -84,6 +131,42 @@ impl RotateY {
     }
 }
 
+impl RotateY {
+    /// Rotates a vector from world space into the space of the object.
+    ///
+    /// # Arguments
+    ///
+    /// * `v` - The vector in world space.
+    ///
+    /// # Returns
+    ///
+    /// The vector in object space.
+    fn to_object(&self, v: &Vector3) -> Vector3 {
+        Vector3::new(
+            (self.cos_theta * v.x) - (self.sin_theta * v.z),
+            v.y,
+            (self.sin_theta * v.x) + (self.cos_theta * v.z),
+        )
+    }
+
+    /// Rotates a vector from the space of the object back into world space.
+    ///
+    /// # Arguments
+    ///
+    /// * `v` - The vector in object space.
+    ///
+    /// # Returns
+    ///
+    /// The vector in world space.
+    fn to_world(&self, v: &Vector3) -> Vector3 {
+        Vector3::new(
+            (self.cos_theta * v.x) + (self.sin_theta * v.z),
+            v.y,
+            (-self.sin_theta * v.x) + (self.cos_theta * v.z),
+        )
+    }
+}
+
 impl Hittable for RotateY {
     /// Checks if a ray hits the rotated object within a given interval.
     ///
@@ -95,37 +178,1174 @@ impl Hittable for RotateY {
     /// # Returns
     ///
     /// An `Option` containing the `HitRecord` if an intersection is found, or `None` if no intersection is found.
-    fn hit(&self, ray: &Ray, interval: (f64, f64)) -> Option<HitRecord> {
-        let origin = Vector3::new(
-            (self.cos_theta * ray.origin.x) - (self.sin_theta * ray.origin.z),
-            ray.origin.y,
-            (self.sin_theta * ray.origin.x) + (self.cos_theta * ray.origin.z),
-        );
-
-        let direction = Vector3::new(
-            (self.cos_theta * ray.direction.x) - (self.sin_theta * ray.direction.z),
-            ray.direction.y,
-            (self.sin_theta * ray.direction.x) + (self.cos_theta * ray.direction.z),
+    fn hit(&self, ray: &Ray, interval: (f64, f64)) -> Option<HitRecord<'_>> {
+        let mut rotated_ray = Ray::with_time(
+            self.to_object(&ray.origin),
+            self.to_object(&ray.direction),
+            ray.time,
         );
-
-        let rotated_ray = Ray::new(origin, direction);
+        rotated_ray.primary = ray.primary;
 
         if let Some(mut hit_record) = self.object.hit(&rotated_ray, interval) {
-            hit_record.poz = Vector3::new(
-                (self.cos_theta * hit_record.poz.x) + (self.sin_theta * hit_record.poz.z),
-                hit_record.poz.y,
-                (-self.sin_theta * hit_record.poz.x) + (self.cos_theta * hit_record.poz.z),
-            );
-
-            hit_record.normal = Vector3::new(
-                (self.cos_theta * hit_record.normal.x) + (self.sin_theta * hit_record.normal.z),
-                hit_record.normal.y,
-                (-self.sin_theta * hit_record.normal.x) + (self.cos_theta * hit_record.normal.z),
-            );
+            hit_record.poz = self.to_world(&hit_record.poz);
+            hit_record.normal = self.to_world(&hit_record.normal);
+            hit_record.tangent = self.to_world(&hit_record.tangent);
+            hit_record.bitangent = self.to_world(&hit_record.bitangent);
+            hit_record.shading_offset = self.to_world(&hit_record.shading_offset);
 
             Some(hit_record)
         } else {
             None
         }
     }
+
+    fn is_emissive(&self) -> bool {
+        self.object.is_emissive()
+    }
+
+    fn power(&self) -> Vector3 {
+        self.object.power()
+    }
+
+    fn pdf_value(&self, origin: &Vector3, direction: &Vector3) -> f64 {
+        self.object
+            .pdf_value(&self.to_object(origin), &self.to_object(direction))
+    }
+
+    fn random(&self, origin: &Vector3) -> Vector3 {
+        self.to_world(&self.object.random(&self.to_object(origin)))
+    }
+
+    fn collect_materials(&self, table: &mut MaterialTable) {
+        self.object.collect_materials(table);
+    }
+
+    fn collect_statistics(&self, statistics: &mut SceneStatistics) {
+        statistics.add_geometry(std::mem::size_of_val(self));
+        self.object.collect_statistics(statistics);
+    }
+
+    fn closest_point(&self, point: &Vector3) -> Option<Vector3> {
+        self.object
+            .closest_point(&self.to_object(point))
+            .map(|closest| self.to_world(&closest))
+    }
+
+    fn point_at_time(&self, point: &Vector3, from: f64, to: f64) -> Vector3 {
+        self.to_world(&self.object.point_at_time(&self.to_object(point), from, to))
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let corners = self.object.bounding_box()?.corners();
+        Some(Aabb::from_points(
+            &corners.map(|corner| self.to_world(&corner)),
+        ))
+    }
+}
+
+/// Computes the rows of the matrix rotating vectors around an axis through the origin, with
+/// Rodrigues' rotation formula: cos I + sin [k]x + (1 - cos) k k^T.
+///
+/// # Arguments
+///
+/// * `axis` - The axis of the rotation, which does not have to be normalized.
+/// * `angle` - The rotation angle in degrees, counterclockwise when the axis points towards the
+///   viewer.
+///
+/// # Returns
+///
+/// The rows of the rotation matrix.
+fn rotation_rows(axis: Vector3, angle: f64) -> [Vector3; 3] {
+    let k = axis.normalize();
+    let (sin_theta, cos_theta) = angle.to_radians().sin_cos();
+    let t = 1.0 - cos_theta;
+    [
+        Vector3::new(
+            cos_theta + t * k.x * k.x,
+            t * k.x * k.y - sin_theta * k.z,
+            t * k.x * k.z + sin_theta * k.y,
+        ),
+        Vector3::new(
+            t * k.y * k.x + sin_theta * k.z,
+            cos_theta + t * k.y * k.y,
+            t * k.y * k.z - sin_theta * k.x,
+        ),
+        Vector3::new(
+            t * k.z * k.x - sin_theta * k.y,
+            t * k.z * k.y + sin_theta * k.x,
+            cos_theta + t * k.z * k.z,
+        ),
+    ]
+}
+
+/// Represents a rotation around an arbitrary axis through the origin applied to a hittable object,
+/// for orientations that a rotation around the Y-axis alone cannot give, such as a tilted box.
+pub struct Rotate {
+    /// The hittable object to which the rotation is applied.
+    object: Arc<dyn Hittable>,
+    /// The rows of the matrix rotating vectors from the space of the object into world space.
+    rows: [Vector3; 3],
+}
+
+impl Rotate {
+    /// Creates a new `Rotate` instance, turning the object counterclockwise around the axis when
+    /// the axis points towards the viewer.
+    ///
+    /// # Arguments
+    ///
+    /// * `object` - The hittable object to which the rotation is applied.
+    /// * `axis` - The axis of the rotation, which does not have to be normalized.
+    /// * `angle` - The rotation angle in degrees.
+    ///
+    /// # Returns
+    ///
+    /// A new `Rotate` instance.
+    pub fn new(object: Arc<dyn Hittable>, axis: Vector3, angle: f64) -> Self {
+        Rotate {
+            object,
+            rows: rotation_rows(axis, angle),
+        }
+    }
+
+    /// Creates a new `Rotate` instance around the X-axis.
+    ///
+    /// # Arguments
+    ///
+    /// * `object` - The hittable object to which the rotation is applied.
+    /// * `angle` - The rotation angle in degrees.
+    ///
+    /// # Returns
+    ///
+    /// A new `Rotate` instance.
+    pub fn around_x(object: Arc<dyn Hittable>, angle: f64) -> Self {
+        Self::new(object, Vector3::new(1.0, 0.0, 0.0), angle)
+    }
+
+    /// Creates a new `Rotate` instance around the Z-axis.
+    ///
+    /// # Arguments
+    ///
+    /// * `object` - The hittable object to which the rotation is applied.
+    /// * `angle` - The rotation angle in degrees.
+    ///
+    /// # Returns
+    ///
+    /// A new `Rotate` instance.
+    pub fn around_z(object: Arc<dyn Hittable>, angle: f64) -> Self {
+        Self::new(object, Vector3::new(0.0, 0.0, 1.0), angle)
+    }
+
+    /// Rotates a vector from world space into the space of the object, with the transpose of the
+    /// rotation matrix.
+    ///
+    /// # Arguments
+    ///
+    /// * `v` - The vector in world space.
+    ///
+    /// # Returns
+    ///
+    /// The vector in object space.
+    fn to_object(&self, v: &Vector3) -> Vector3 {
+        v.x * self.rows[0] + v.y * self.rows[1] + v.z * self.rows[2]
+    }
+
+    /// Rotates a vector from the space of the object back into world space.
+    ///
+    /// # Arguments
+    ///
+    /// * `v` - The vector in object space.
+    ///
+    /// # Returns
+    ///
+    /// The vector in world space.
+    fn to_world(&self, v: &Vector3) -> Vector3 {
+        Vector3::new(
+            self.rows[0].dot(v),
+            self.rows[1].dot(v),
+            self.rows[2].dot(v),
+        )
+    }
+}
+
+impl Hittable for Rotate {
+    /// Checks if a ray hits the rotated object within a given interval.
+    ///
+    /// # Arguments
+    ///
+    /// * `ray` - The ray to test for intersection.
+    /// * `interval` - The range of distances to consider for intersections.
+    ///
+    /// # Returns
+    ///
+    /// An `Option` containing the `HitRecord` if an intersection is found, or `None` if no intersection is found.
+    fn hit(&self, ray: &Ray, interval: (f64, f64)) -> Option<HitRecord<'_>> {
+        let mut rotated_ray = Ray::with_time(
+            self.to_object(&ray.origin),
+            self.to_object(&ray.direction),
+            ray.time,
+        );
+        rotated_ray.primary = ray.primary;
+
+        let mut hit_record = self.object.hit(&rotated_ray, interval)?;
+        hit_record.poz = self.to_world(&hit_record.poz);
+        hit_record.normal = self.to_world(&hit_record.normal);
+        hit_record.tangent = self.to_world(&hit_record.tangent);
+        hit_record.bitangent = self.to_world(&hit_record.bitangent);
+        hit_record.shading_offset = self.to_world(&hit_record.shading_offset);
+        Some(hit_record)
+    }
+
+    fn is_emissive(&self) -> bool {
+        self.object.is_emissive()
+    }
+
+    fn power(&self) -> Vector3 {
+        self.object.power()
+    }
+
+    fn pdf_value(&self, origin: &Vector3, direction: &Vector3) -> f64 {
+        self.object
+            .pdf_value(&self.to_object(origin), &self.to_object(direction))
+    }
+
+    fn random(&self, origin: &Vector3) -> Vector3 {
+        self.to_world(&self.object.random(&self.to_object(origin)))
+    }
+
+    fn collect_materials(&self, table: &mut MaterialTable) {
+        self.object.collect_materials(table);
+    }
+
+    fn collect_statistics(&self, statistics: &mut SceneStatistics) {
+        statistics.add_geometry(std::mem::size_of_val(self));
+        self.object.collect_statistics(statistics);
+    }
+
+    fn closest_point(&self, point: &Vector3) -> Option<Vector3> {
+        self.object
+            .closest_point(&self.to_object(point))
+            .map(|closest| self.to_world(&closest))
+    }
+
+    fn point_at_time(&self, point: &Vector3, from: f64, to: f64) -> Vector3 {
+        self.to_world(&self.object.point_at_time(&self.to_object(point), from, to))
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let corners = self.object.bounding_box()?.corners();
+        Some(Aabb::from_points(
+            &corners.map(|corner| self.to_world(&corner)),
+        ))
+    }
+}
+
+/// Multiplies two rotation matrices given by their rows.
+///
+/// # Arguments
+///
+/// * `a` - The rows of the rotation applied second.
+/// * `b` - The rows of the rotation applied first.
+///
+/// # Returns
+///
+/// The rows of the combined rotation.
+fn multiply_rows(a: &[Vector3; 3], b: &[Vector3; 3]) -> [Vector3; 3] {
+    let columns = [
+        Vector3::new(b[0].x, b[1].x, b[2].x),
+        Vector3::new(b[0].y, b[1].y, b[2].y),
+        Vector3::new(b[0].z, b[1].z, b[2].z),
+    ];
+    a.map(|row| {
+        Vector3::new(
+            row.dot(&columns[0]),
+            row.dot(&columns[1]),
+            row.dot(&columns[2]),
+        )
+    })
+}
+
+/// Represents a rotation, uniform scale and offset taking points from the space of an object into
+/// world space.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Placement {
+    /// The rows of the matrix rotating vectors from the space of the object into world space.
+    rows: [Vector3; 3],
+    /// The uniform scale applied after the rotation.
+    scale: f64,
+    /// The offset applied after the scale.
+    offset: Vector3,
+}
+
+impl Placement {
+    /// Creates a new `Placement` moving the object by an offset, without rotation or scale.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - The position of the origin of the object in world space.
+    ///
+    /// # Returns
+    ///
+    /// A new `Placement` instance.
+    pub fn new(offset: Vector3) -> Placement {
+        Placement {
+            rows: [
+                Vector3::new(1.0, 0.0, 0.0),
+                Vector3::new(0.0, 1.0, 0.0),
+                Vector3::new(0.0, 0.0, 1.0),
+            ],
+            scale: 1.0,
+            offset,
+        }
+    }
+
+    /// Rotates the object around an axis through its origin, after the rotation of the placement.
+    ///
+    /// # Arguments
+    ///
+    /// * `axis` - The axis of the rotation, which does not have to be normalized.
+    /// * `angle` - The rotation angle in degrees.
+    ///
+    /// # Returns
+    ///
+    /// The `Placement` with the rotation applied.
+    pub fn rotated(mut self, axis: Vector3, angle: f64) -> Placement {
+        self.rows = multiply_rows(&rotation_rows(axis, angle), &self.rows);
+        self
+    }
+
+    /// Scales the object uniformly around its origin, after the scale of the placement.
+    ///
+    /// # Arguments
+    ///
+    /// * `scale` - The scale factor, greater than zero.
+    ///
+    /// # Returns
+    ///
+    /// The `Placement` with the scale applied.
+    pub fn scaled(mut self, scale: f64) -> Placement {
+        self.scale *= scale;
+        self
+    }
+
+    /// Returns the position of the origin of the object in world space.
+    pub fn offset(&self) -> Vector3 {
+        self.offset
+    }
+
+    /// Combines the placement with the placement of an object inside the placed space, such as a
+    /// child of a node in a hierarchy.
+    ///
+    /// # Arguments
+    ///
+    /// * `child` - The placement of the inner object relative to this placement.
+    ///
+    /// # Returns
+    ///
+    /// The placement of the inner object in world space.
+    pub fn then(&self, child: &Placement) -> Placement {
+        Placement {
+            rows: multiply_rows(&self.rows, &child.rows),
+            scale: self.scale * child.scale,
+            offset: self.point_to_world(&child.offset),
+        }
+    }
+
+    /// Rotates a direction from world space into the space of the object.
+    ///
+    /// # Arguments
+    ///
+    /// * `v` - The direction in world space.
+    ///
+    /// # Returns
+    ///
+    /// The direction in object space.
+    fn direction_to_object(&self, v: &Vector3) -> Vector3 {
+        v.x * self.rows[0] + v.y * self.rows[1] + v.z * self.rows[2]
+    }
+
+    /// Rotates a direction from the space of the object into world space.
+    ///
+    /// # Arguments
+    ///
+    /// * `v` - The direction in object space.
+    ///
+    /// # Returns
+    ///
+    /// The direction in world space.
+    fn direction_to_world(&self, v: &Vector3) -> Vector3 {
+        Vector3::new(
+            self.rows[0].dot(v),
+            self.rows[1].dot(v),
+            self.rows[2].dot(v),
+        )
+    }
+
+    /// Moves a point from world space into the space of the object.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The point in world space.
+    ///
+    /// # Returns
+    ///
+    /// The point in object space.
+    fn point_to_object(&self, point: &Vector3) -> Vector3 {
+        self.direction_to_object(&(*point - self.offset)) / self.scale
+    }
+
+    /// Moves a point from the space of the object into world space.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The point in object space.
+    ///
+    /// # Returns
+    ///
+    /// The point in world space.
+    pub fn point_to_world(&self, point: &Vector3) -> Vector3 {
+        self.scale * self.direction_to_world(point) + self.offset
+    }
+
+    /// Checks if a ray hits the placed object within a given interval. Distances along the ray
+    /// in the space of the object are shorter by the scale.
+    ///
+    /// # Arguments
+    ///
+    /// * `object` - The object, in its own space.
+    /// * `ray` - The ray to test for intersection.
+    /// * `interval` - The range of distances to consider for intersections.
+    ///
+    /// # Returns
+    ///
+    /// An `Option` containing the `HitRecord` in world space if an intersection is found, or
+    /// `None` if no intersection is found.
+    fn hit<'a>(
+        &self,
+        object: &'a dyn Hittable,
+        ray: &Ray,
+        interval: (f64, f64),
+    ) -> Option<HitRecord<'a>> {
+        let mut object_ray = Ray::with_time(
+            self.point_to_object(&ray.origin),
+            self.direction_to_object(&ray.direction),
+            ray.time,
+        );
+        object_ray.primary = ray.primary;
+
+        let object_interval = (interval.0 / self.scale, interval.1 / self.scale);
+        let mut hit_record = object.hit(&object_ray, object_interval)?;
+        hit_record.t *= self.scale;
+        hit_record.poz = self.point_to_world(&hit_record.poz);
+        hit_record.normal = self.direction_to_world(&hit_record.normal);
+        hit_record.tangent = self.direction_to_world(&hit_record.tangent);
+        hit_record.bitangent = self.direction_to_world(&hit_record.bitangent);
+        hit_record.shading_offset =
+            self.scale * self.direction_to_world(&hit_record.shading_offset);
+        Some(hit_record)
+    }
+}
+
+/// Represents one placement of an object shared with other placements, such as one of many trees
+/// of a forest: the object is rotated, scaled uniformly and moved without being copied, so
+/// repeated geometry is built and stored once however many times it appears.
+pub struct Instance {
+    /// The shared object, in its own space.
+    object: Arc<dyn Hittable>,
+    /// The rotation, scale and offset of the object.
+    placement: Placement,
+    /// The random value between 0 and 1 handed to the textures of the object, if any.
+    variation: Option<f64>,
+    /// The color tint handed to the textures of the object.
+    tint: Vector3,
+}
+
+impl Instance {
+    /// Creates a new `Instance` of an object moved by an offset, without rotation or scale.
+    ///
+    /// # Arguments
+    ///
+    /// * `object` - The shared object.
+    /// * `offset` - The position of the origin of the object in world space.
+    ///
+    /// # Returns
+    ///
+    /// A new `Instance` instance.
+    pub fn new(object: Arc<dyn Hittable>, offset: Vector3) -> Self {
+        Instance {
+            object,
+            placement: Placement::new(offset),
+            variation: None,
+            tint: Vector3::new(1.0, 1.0, 1.0),
+        }
+    }
+
+    /// Creates a new `Instance` of an object in a placement, such as one combined from the nodes
+    /// of a hierarchy.
+    ///
+    /// # Arguments
+    ///
+    /// * `object` - The shared object.
+    /// * `placement` - The rotation, scale and offset of the object.
+    ///
+    /// # Returns
+    ///
+    /// A new `Instance` instance.
+    pub fn placed(object: Arc<dyn Hittable>, placement: Placement) -> Self {
+        Instance {
+            placement,
+            ..Instance::new(object, placement.offset)
+        }
+    }
+
+    /// Rotates the object around an axis through its origin, after any rotation set before.
+    ///
+    /// # Arguments
+    ///
+    /// * `axis` - The axis of the rotation, which does not have to be normalized.
+    /// * `angle` - The rotation angle in degrees.
+    ///
+    /// # Returns
+    ///
+    /// The `Instance` with the rotation applied.
+    pub fn with_rotation(mut self, axis: Vector3, angle: f64) -> Self {
+        self.placement = self.placement.rotated(axis, angle);
+        self
+    }
+
+    /// Scales the object uniformly around its origin.
+    ///
+    /// # Arguments
+    ///
+    /// * `scale` - The scale factor, greater than zero.
+    ///
+    /// # Returns
+    ///
+    /// The `Instance` with the scale applied.
+    pub fn with_scale(mut self, scale: f64) -> Self {
+        self.placement.scale *= scale;
+        self
+    }
+
+    /// Sets the random value read by the textures of the object through `SurfaceQuery::Variation`,
+    /// replacing the values of any instances within the object.
+    ///
+    /// # Arguments
+    ///
+    /// * `variation` - The value, between 0 and 1.
+    ///
+    /// # Returns
+    ///
+    /// The `Instance` with the value set.
+    pub fn with_variation(mut self, variation: f64) -> Self {
+        self.variation = Some(variation.clamp(0.0, 1.0));
+        self
+    }
+
+    /// Tints the textures of the object wrapped in a `TintedTexture`, on top of the tint of any
+    /// instances within the object.
+    ///
+    /// # Arguments
+    ///
+    /// * `tint` - The color multiplying the textures.
+    ///
+    /// # Returns
+    ///
+    /// The `Instance` with the tint applied.
+    pub fn with_tint(mut self, tint: Vector3) -> Self {
+        self.tint = self.tint * tint;
+        self
+    }
+
+    /// Gives the instance a random value and scales it by a random factor, both drawn from a
+    /// seed, so many instances of one object do not look cloned and a scene built again with the
+    /// same seeds looks the same.
+    ///
+    /// # Arguments
+    ///
+    /// * `seed` - The seed of the random draws, such as the index of the instance.
+    /// * `scale_jitter` - The largest relative change of the scale, between 0 and 1.
+    ///
+    /// # Returns
+    ///
+    /// The `Instance` with the random value and scale applied.
+    pub fn with_jitter(self, seed: u64, scale_jitter: f64) -> Self {
+        let mut rng = fastrand::Rng::with_seed(seed);
+        let variation = rng.f64();
+        let scale = 1.0 + scale_jitter.clamp(0.0, 1.0) * (2.0 * rng.f64() - 1.0);
+        self.with_variation(variation).with_scale(scale)
+    }
+}
+
+impl Hittable for Instance {
+    /// Checks if a ray hits the placed object within a given interval. Distances along the ray
+    /// in the space of the object are shorter by the scale.
+    ///
+    /// # Arguments
+    ///
+    /// * `ray` - The ray to test for intersection.
+    /// * `interval` - The range of distances to consider for intersections.
+    ///
+    /// # Returns
+    ///
+    /// An `Option` containing the `HitRecord` if an intersection is found, or `None` if no intersection is found.
+    fn hit(&self, ray: &Ray, interval: (f64, f64)) -> Option<HitRecord<'_>> {
+        let mut hit_record = self.placement.hit(self.object.as_ref(), ray, interval)?;
+        if let Some(variation) = self.variation {
+            hit_record.variation = variation;
+        }
+        hit_record.tint = hit_record.tint * self.tint;
+        Some(hit_record)
+    }
+
+    fn is_emissive(&self) -> bool {
+        self.object.is_emissive()
+    }
+
+    fn power(&self) -> Vector3 {
+        let scale = self.placement.scale;
+        self.object.power() * (scale * scale)
+    }
+
+    fn pdf_value(&self, origin: &Vector3, direction: &Vector3) -> f64 {
+        // Solid angles are unchanged by rotating and scaling the object and the origin together.
+        self.object.pdf_value(
+            &self.placement.point_to_object(origin),
+            &self.placement.direction_to_object(direction),
+        )
+    }
+
+    fn random(&self, origin: &Vector3) -> Vector3 {
+        let placement = &self.placement;
+        let direction = self.object.random(&placement.point_to_object(origin));
+        placement.scale * placement.direction_to_world(&direction)
+    }
+
+    fn collect_materials(&self, table: &mut MaterialTable) {
+        if table.add_instance(Arc::as_ptr(&self.object) as *const () as usize) {
+            self.object.collect_materials(table);
+        }
+    }
+
+    fn collect_statistics(&self, statistics: &mut SceneStatistics) {
+        statistics.add_geometry(std::mem::size_of_val(self));
+        if statistics.add_instance(Arc::as_ptr(&self.object) as *const () as usize) {
+            self.object.collect_statistics(statistics);
+        }
+    }
+
+    fn closest_point(&self, point: &Vector3) -> Option<Vector3> {
+        self.object
+            .closest_point(&self.placement.point_to_object(point))
+            .map(|closest| self.placement.point_to_world(&closest))
+    }
+
+    fn point_at_time(&self, point: &Vector3, from: f64, to: f64) -> Vector3 {
+        let placement = &self.placement;
+        let moved = self
+            .object
+            .point_at_time(&placement.point_to_object(point), from, to);
+        placement.point_to_world(&moved)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let corners = self.object.bounding_box()?.corners();
+        Some(Aabb::from_points(
+            &corners.map(|corner| self.placement.point_to_world(&corner)),
+        ))
+    }
+}
+
+/// Represents the placement of a moving object at one moment of its motion.
+#[derive(Debug, Clone, Copy)]
+pub struct MotionKey {
+    /// The scene time of the keyframe.
+    pub time: f64,
+    /// The position of the origin of the object in world space.
+    pub offset: Vector3,
+    /// The axis around which the object is rotated.
+    pub axis: Vector3,
+    /// The rotation angle around the axis in degrees.
+    pub angle: f64,
+    /// The uniform scale of the object.
+    pub scale: f64,
+}
+
+impl MotionKey {
+    /// Creates a new `MotionKey` placing the object at an offset, without rotation or scale.
+    ///
+    /// # Arguments
+    ///
+    /// * `time` - The scene time of the keyframe.
+    /// * `offset` - The position of the origin of the object in world space.
+    ///
+    /// # Returns
+    ///
+    /// A new `MotionKey` instance.
+    pub fn new(time: f64, offset: Vector3) -> MotionKey {
+        MotionKey {
+            time,
+            offset,
+            axis: Vector3::new(0.0, 1.0, 0.0),
+            angle: 0.0,
+            scale: 1.0,
+        }
+    }
+
+    /// Sets the rotation of the object at the keyframe. Angles are not limited to a turn, so
+    /// keyframes can spin an object several times between them.
+    ///
+    /// # Arguments
+    ///
+    /// * `axis` - The axis of the rotation, which does not have to be normalized.
+    /// * `angle` - The rotation angle in degrees.
+    ///
+    /// # Returns
+    ///
+    /// The updated `MotionKey` instance.
+    pub fn with_rotation(mut self, axis: Vector3, angle: f64) -> MotionKey {
+        self.axis = axis;
+        self.angle = angle;
+        self
+    }
+
+    /// Sets the uniform scale of the object at the keyframe.
+    ///
+    /// # Arguments
+    ///
+    /// * `scale` - The scale factor, greater than zero.
+    ///
+    /// # Returns
+    ///
+    /// The updated `MotionKey` instance.
+    pub fn with_scale(mut self, scale: f64) -> MotionKey {
+        self.scale = scale;
+        self
+    }
+}
+
+/// Represents an object moving along a curve of keyframed placements, so rays spread over the
+/// shutter interval see it blurred along its real trajectory, such as the arc of a thrown ball or
+/// the sweep of a spinning propeller, rather than along a straight line.
+///
+/// Positions follow a Catmull-Rom spline through the offsets of the keyframes, while the angles
+/// and scales are interpolated linearly. Before the first and after the last keyframe the object
+/// stays still. Moving objects are not sampled as lights.
+pub struct Motion {
+    /// The moving object, in its own space.
+    object: Arc<dyn Hittable>,
+    /// The keyframes, sorted by time.
+    keys: Vec<MotionKey>,
+    /// The times of the keyframes, for searching the ones around a time.
+    times: Vec<f64>,
+}
+
+impl Motion {
+    /// The number of placements per segment between keyframes at which the bounding box
+    /// follows the curve.
+    const BOUNDING_STEPS: usize = 16;
+
+    /// Creates a new `Motion` of an object through keyframes.
+    ///
+    /// # Arguments
+    ///
+    /// * `object` - The moving object, in its own space.
+    /// * `keys` - The keyframes of the motion, in any order.
+    ///
+    /// # Returns
+    ///
+    /// A new `Motion` instance.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `keys` is empty.
+    pub fn new(object: Arc<dyn Hittable>, mut keys: Vec<MotionKey>) -> Motion {
+        assert!(!keys.is_empty(), "a motion needs at least one keyframe");
+        keys.sort_by(|a, b| a.time.total_cmp(&b.time));
+        let times = keys.iter().map(|key| key.time).collect();
+        Motion {
+            object,
+            keys,
+            times,
+        }
+    }
+
+    /// Returns the velocity of the offset at a keyframe, from its neighbours as Catmull-Rom
+    /// splines do, or one-sided at the first and last keyframe.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the keyframe.
+    ///
+    /// # Returns
+    ///
+    /// The velocity in world units per unit of scene time.
+    fn velocity(&self, index: usize) -> Vector3 {
+        let before = &self.keys[index.saturating_sub(1)];
+        let after = &self.keys[(index + 1).min(self.keys.len() - 1)];
+        if after.time > before.time {
+            (after.offset - before.offset) / (after.time - before.time)
+        } else {
+            Vector3::default()
+        }
+    }
+
+    /// Interpolates the placement of the object at a scene time.
+    ///
+    /// # Arguments
+    ///
+    /// * `time` - The scene time.
+    ///
+    /// # Returns
+    ///
+    /// The placement of the object.
+    fn placement_at(&self, time: f64) -> Placement {
+        let (previous, next, s) = keyframes_around(&self.times, time);
+        let (a, b) = (&self.keys[previous], &self.keys[next]);
+        if previous == next {
+            return Placement {
+                scale: a.scale,
+                ..Placement::new(a.offset).rotated(a.axis, a.angle)
+            };
+        }
+
+        let span = b.time - a.time;
+        let (out_velocity, in_velocity) = (self.velocity(previous), self.velocity(next));
+        let offset = Vector3::new(
+            hermite(
+                a.offset.x,
+                out_velocity.x,
+                b.offset.x,
+                in_velocity.x,
+                span,
+                s,
+            ),
+            hermite(
+                a.offset.y,
+                out_velocity.y,
+                b.offset.y,
+                in_velocity.y,
+                span,
+                s,
+            ),
+            hermite(
+                a.offset.z,
+                out_velocity.z,
+                b.offset.z,
+                in_velocity.z,
+                span,
+                s,
+            ),
+        );
+        let axis = a.axis.normalize() * (1.0 - s) + b.axis.normalize() * s;
+        let axis = if axis.is_near_zero() { a.axis } else { axis };
+        Placement {
+            scale: a.scale * (1.0 - s) + b.scale * s,
+            ..Placement::new(offset).rotated(axis, a.angle * (1.0 - s) + b.angle * s)
+        }
+    }
+}
+
+impl Hittable for Motion {
+    /// Checks if a ray hits the object placed where it is at the time of the ray.
+    ///
+    /// # Arguments
+    ///
+    /// * `ray` - The ray to test for intersection.
+    /// * `interval` - The range of distances to consider for intersections.
+    ///
+    /// # Returns
+    ///
+    /// An `Option` containing the `HitRecord` if an intersection is found, or `None` if no intersection is found.
+    fn hit(&self, ray: &Ray, interval: (f64, f64)) -> Option<HitRecord<'_>> {
+        self.placement_at(ray.time)
+            .hit(self.object.as_ref(), ray, interval)
+    }
+
+    /// Follows a point on the surface from its placement at one time into its placement at
+    /// another, through the parts of the object moving on their own.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The point of the surface at the first time.
+    /// * `from` - The scene time at which the point is given.
+    /// * `to` - The scene time to follow the point to.
+    ///
+    /// # Returns
+    ///
+    /// The position of the same point of the surface at the second time.
+    fn point_at_time(&self, point: &Vector3, from: f64, to: f64) -> Vector3 {
+        let local = self.placement_at(from).point_to_object(point);
+        let moved = self.object.point_at_time(&local, from, to);
+        self.placement_at(to).point_to_world(&moved)
+    }
+
+    fn collect_materials(&self, table: &mut MaterialTable) {
+        self.object.collect_materials(table);
+    }
+
+    fn collect_statistics(&self, statistics: &mut SceneStatistics) {
+        statistics.add_geometry(
+            std::mem::size_of_val(self)
+                + self.keys.len() * (std::mem::size_of::<MotionKey>() + std::mem::size_of::<f64>()),
+        );
+        self.object.collect_statistics(statistics);
+    }
+
+    /// Returns a box enclosing the object at every time of its motion. The object may take any
+    /// rotation, so it is bounded by the sphere around its origin reaching its farthest corner,
+    /// at placements along the curve close enough that the sphere covers the steps between them.
+    fn bounding_box(&self) -> Option<Aabb> {
+        let corners = self.object.bounding_box()?.corners();
+        let reach = corners
+            .iter()
+            .map(|corner| corner.length())
+            .fold(0.0, f64::max);
+
+        let first = self.keys[0].time;
+        let last = self.keys[self.keys.len() - 1].time;
+        let steps = Self::BOUNDING_STEPS * self.keys.len();
+        let placements: Vec<Placement> = (0..=steps)
+            .map(|i| self.placement_at(first + (last - first) * i as f64 / steps as f64))
+            .collect();
+        let step = placements
+            .windows(2)
+            .map(|pair| (pair[1].offset - pair[0].offset).length())
+            .fold(0.0, f64::max);
+
+        let points: Vec<Vector3> = placements
+            .iter()
+            .flat_map(|placement| {
+                let radius = Vector3::new(1.0, 1.0, 1.0) * (reach * placement.scale + step);
+                [placement.offset - radius, placement.offset + radius]
+            })
+            .collect();
+        Some(Aabb::from_points(&points))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Lambertian;
+    use crate::shapes::box_quad::BoxQuad;
+    use crate::shapes::sphere::Sphere;
+
+    #[test]
+    fn test_rotate_matches_rotate_y() {
+        let material = Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5)));
+        let object: Arc<dyn Hittable> = Arc::new(BoxQuad::new(
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(1.0, 2.0, 3.0),
+            material,
+        ));
+        let rotate = Rotate::new(object.clone(), Vector3::new(0.0, 2.0, 0.0), 30.0);
+        let rotate_y = RotateY::new(object, 30.0);
+
+        for direction in [
+            Vector3::new(0.0, 0.0, -1.0),
+            Vector3::new(0.3, -0.2, -1.0),
+            Vector3::new(-1.0, 0.1, -0.4),
+        ] {
+            let ray = Ray::new(Vector3::new(0.5, 1.0, 1.5) - 6.0 * direction, direction);
+            let expected = rotate_y.hit(&ray, (0.001, f64::INFINITY)).unwrap();
+            let record = rotate.hit(&ray, (0.001, f64::INFINITY)).unwrap();
+            assert!((record.t - expected.t).abs() < 1e-9);
+            assert!((record.poz - expected.poz).length() < 1e-9);
+            assert!((record.normal - expected.normal).length() < 1e-9);
+        }
+        let bounds = rotate.bounding_box().unwrap();
+        let expected = rotate_y.bounding_box().unwrap();
+        assert!((bounds.min - expected.min).length() < 1e-9);
+        assert!((bounds.max - expected.max).length() < 1e-9);
+    }
+
+    #[test]
+    fn test_rotate_around_x_and_z() {
+        let material = Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5)));
+        let sphere: Arc<dyn Hittable> =
+            Arc::new(Sphere::new(Vector3::new(0.0, 0.0, -5.0), 1.0, material));
+
+        // A quarter turn around the X-axis takes the sphere from -z to +y.
+        let around_x = Rotate::around_x(sphere.clone(), 90.0);
+        let up = Ray::new(Vector3::new(0.0, 0.0, 0.0), Vector3::new(0.0, 1.0, 0.0));
+        let record = around_x.hit(&up, (0.001, f64::INFINITY)).unwrap();
+        assert!((record.t - 4.0).abs() < 1e-9);
+        assert!((record.normal - Vector3::new(0.0, -1.0, 0.0)).length() < 1e-9);
+        let forward = Ray::new(Vector3::new(0.0, 0.0, 0.0), Vector3::new(0.0, 0.0, -1.0));
+        assert!(around_x.hit(&forward, (0.001, f64::INFINITY)).is_none());
+
+        // Around the Z-axis the sphere stays in place while its surface turns.
+        let around_z = Rotate::around_z(sphere, 90.0);
+        let record = around_z.hit(&forward, (0.001, f64::INFINITY)).unwrap();
+        assert!((record.t - 4.0).abs() < 1e-9);
+        let bounds = around_z.bounding_box().unwrap();
+        assert!((bounds.min - Vector3::new(-1.0, -1.0, -6.0)).length() < 1e-9);
+        assert!((bounds.max - Vector3::new(1.0, 1.0, -4.0)).length() < 1e-9);
+    }
+
+    #[test]
+    fn test_instance() {
+        let material = Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5)));
+        let object: Arc<dyn Hittable> = Arc::new(BoxQuad::new(
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(1.0, 2.0, 3.0),
+            material.clone(),
+        ));
+
+        // Without a scale an instance matches the rotation and translation wrappers.
+        let instance = Instance::new(object.clone(), Vector3::new(4.0, -1.0, 2.0))
+            .with_rotation(Vector3::new(0.0, 0.0, 1.0), 40.0)
+            .with_rotation(Vector3::new(1.0, 1.0, 0.0), 25.0);
+        let wrapped = Translate::new(
+            Arc::new(Rotate::new(
+                Arc::new(Rotate::around_z(object, 40.0)),
+                Vector3::new(1.0, 1.0, 0.0),
+                25.0,
+            )),
+            Vector3::new(4.0, -1.0, 2.0),
+        );
+        for direction in [Vector3::new(0.0, 0.0, -1.0), Vector3::new(0.3, -0.2, -1.0)] {
+            let ray = Ray::new(Vector3::new(4.2, 0.0, 3.0) - 8.0 * direction, direction);
+            let expected = wrapped.hit(&ray, (0.001, f64::INFINITY)).unwrap();
+            let record = instance.hit(&ray, (0.001, f64::INFINITY)).unwrap();
+            assert!((record.t - expected.t).abs() < 1e-9);
+            assert!((record.poz - expected.poz).length() < 1e-9);
+            assert!((record.normal - expected.normal).length() < 1e-9);
+        }
+
+        // A scaled sphere is hit at the scaled distance, and only within the scaled interval.
+        let sphere: Arc<dyn Hittable> =
+            Arc::new(Sphere::new(Vector3::new(0.0, 0.0, 0.0), 1.0, material));
+        let instance = Instance::new(sphere.clone(), Vector3::new(0.0, 0.0, -10.0)).with_scale(3.0);
+        let ray = Ray::new(Vector3::new(0.0, 0.0, 0.0), Vector3::new(0.0, 0.0, -1.0));
+        let record = instance.hit(&ray, (0.001, f64::INFINITY)).unwrap();
+        assert!((record.t - 7.0).abs() < 1e-9);
+        assert!((record.poz - Vector3::new(0.0, 0.0, -7.0)).length() < 1e-9);
+        assert!((record.normal - Vector3::new(0.0, 0.0, 1.0)).length() < 1e-9);
+        assert!(instance.hit(&ray, (0.001, 6.5)).is_none());
+        let bounds = instance.bounding_box().unwrap();
+        assert!((bounds.min - Vector3::new(-3.0, -3.0, -13.0)).length() < 1e-9);
+
+        // The shared object is counted once however many instances there are.
+        let mut statistics = SceneStatistics::new();
+        for i in 0..10 {
+            Instance::new(sphere.clone(), Vector3::new(i as f64, 0.0, 0.0))
+                .collect_statistics(&mut statistics);
+        }
+        assert_eq!(statistics.instances, 10);
+        assert_eq!(statistics.primitive_count(), 1);
+    }
+
+    #[test]
+    fn test_instance_attributes() {
+        let material = Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5)));
+        let sphere: Arc<dyn Hittable> =
+            Arc::new(Sphere::new(Vector3::new(0.0, 0.0, 0.0), 1.0, material));
+        let ray = Ray::new(Vector3::new(0.0, 0.0, 5.0), Vector3::new(0.0, 0.0, -1.0));
+
+        let plain = Instance::new(sphere.clone(), Vector3::default());
+        let record = plain.hit(&ray, (0.001, f64::INFINITY)).unwrap();
+        assert_eq!(record.variation, 0.0);
+        assert_eq!(record.tint, Vector3::new(1.0, 1.0, 1.0));
+
+        // The outer instance replaces the value of the inner one and multiplies its tint.
+        let inner: Arc<dyn Hittable> = Arc::new(
+            Instance::new(sphere.clone(), Vector3::default())
+                .with_variation(0.2)
+                .with_tint(Vector3::new(1.0, 0.5, 0.5)),
+        );
+        let outer = Instance::new(inner, Vector3::default())
+            .with_variation(0.9)
+            .with_tint(Vector3::new(0.5, 1.0, 1.0));
+        let record = outer.hit(&ray, (0.001, f64::INFINITY)).unwrap();
+        assert_eq!(record.variation, 0.9);
+        assert_eq!(record.tint, Vector3::new(0.5, 0.5, 0.5));
+
+        // Jitter draws the same value and scale from the same seed, and differs between seeds.
+        let jittered =
+            |seed| Instance::new(sphere.clone(), Vector3::default()).with_jitter(seed, 0.3);
+        let (a, b) = (jittered(1), jittered(1));
+        assert_eq!(a.variation, b.variation);
+        assert_eq!(a.placement.scale, b.placement.scale);
+        assert!((0.7..=1.3).contains(&a.placement.scale));
+        let scales: Vec<f64> = (0..20).map(|seed| jittered(seed).placement.scale).collect();
+        assert!(
+            scales.iter().any(|&scale| scale < 0.95) && scales.iter().any(|&scale| scale > 1.05)
+        );
+    }
+
+    #[test]
+    fn test_motion_follows_curve() {
+        let material = Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5)));
+        let sphere: Arc<dyn Hittable> =
+            Arc::new(Sphere::new(Vector3::new(0.0, 0.0, 0.0), 1.0, material));
+        let motion = Motion::new(
+            sphere,
+            vec![
+                MotionKey::new(2.0, Vector3::new(2.0, 0.0, 0.0)),
+                MotionKey::new(0.0, Vector3::new(-2.0, 0.0, 0.0)),
+                MotionKey::new(1.0, Vector3::new(0.0, 2.0, 0.0)).with_scale(2.0),
+            ],
+        );
+        let down = Vector3::new(0.0, 0.0, -1.0);
+
+        // The sphere passes through its keyframes with their scale.
+        let ray = Ray::with_time(Vector3::new(0.0, 2.0, 5.0), down, 1.0);
+        let record = motion.hit(&ray, (0.001, f64::INFINITY)).unwrap();
+        assert!((record.t - 3.0).abs() < 1e-9);
+
+        // Between the keyframes it rises above the straight line joining them.
+        let ray = Ray::with_time(Vector3::new(-1.0, 1.25, 5.0), down, 0.5);
+        let record = motion.hit(&ray, (0.001, f64::INFINITY)).unwrap();
+        assert!((record.t - 3.5).abs() < 1e-9);
+        let straight = Ray::with_time(Vector3::new(-1.0, 1.0, 5.0), down, 0.5);
+        let record = motion.hit(&straight, (0.001, f64::INFINITY)).unwrap();
+        assert!(record.t > 3.5);
+
+        // Outside of the keyframes the sphere stays at the closest one.
+        let ray = Ray::with_time(Vector3::new(2.0, 0.0, 5.0), down, 7.0);
+        let record = motion.hit(&ray, (0.001, f64::INFINITY)).unwrap();
+        assert!((record.t - 4.0).abs() < 1e-9);
+
+        let bounds = motion.bounding_box().unwrap();
+        for i in 0..=100 {
+            let time = 2.0 * i as f64 / 100.0;
+            let placement = motion.placement_at(time);
+            let top = placement.offset + Vector3::new(0.0, placement.scale, 0.0);
+            assert!(top.y <= bounds.max.y && placement.offset.x >= bounds.min.x);
+        }
+    }
+
+    #[test]
+    fn test_motion_spins_past_a_turn() {
+        let material = Arc::new(Lambertian::new(Vector3::new(0.5, 0.5, 0.5)));
+        let object: Arc<dyn Hittable> = Arc::new(BoxQuad::new(
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(3.0, 0.2, 0.5),
+            material,
+        ));
+        let axis = Vector3::new(0.0, 1.0, 0.0);
+        let motion = Motion::new(
+            object.clone(),
+            vec![
+                MotionKey::new(0.0, Vector3::new(1.0, 0.0, 0.0)),
+                MotionKey::new(1.0, Vector3::new(1.0, 0.0, 0.0)).with_rotation(axis, 720.0),
+            ],
+        );
+
+        // An eighth of the way through two turns, the object has turned by a quarter.
+        let expected = Instance::new(object, Vector3::new(1.0, 0.0, 0.0)).with_rotation(axis, 90.0);
+        for origin in [Vector3::new(1.2, 5.0, -1.5), Vector3::new(0.0, 0.1, -2.0)] {
+            let direction = Vector3::new(1.25, 0.1, -1.0) - origin;
+            let ray = Ray::with_time(origin, direction, 0.125);
+            let record = motion.hit(&ray, (0.001, f64::INFINITY)).unwrap();
+            let reference = expected.hit(&ray, (0.001, f64::INFINITY)).unwrap();
+            assert!((record.t - reference.t).abs() < 1e-9);
+            assert!((record.normal - reference.normal).length() < 1e-9);
+        }
+
+        // The box covers the object whichever way it points.
+        let bounds = motion.bounding_box().unwrap();
+        assert!(bounds.min.z <= -2.0 && bounds.max.z >= 2.0);
+    }
 }
@@ -0,0 +1,77 @@
+//! Units of length, declared by scenes for their coordinates and by assets for theirs, so assets
+//! modeled at any scale can be placed in a scene at their real size.
+
+/// Represents the length of one unit of the coordinates of a scene or an asset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Unit {
+    /// Millimeters, common for CAD models and the Cornell box.
+    Millimeters,
+    /// Centimeters, common for models from modeling programs.
+    Centimeters,
+    /// Meters, the unit assumed when none is declared.
+    #[default]
+    Meters,
+    /// Kilometers, for terrains and landscapes.
+    Kilometers,
+    /// Inches.
+    Inches,
+    /// Feet.
+    Feet,
+}
+
+impl Unit {
+    /// Returns the length of one unit.
+    ///
+    /// # Returns
+    ///
+    /// The length in meters.
+    pub fn meters(self) -> f64 {
+        match self {
+            Unit::Millimeters => 0.001,
+            Unit::Centimeters => 0.01,
+            Unit::Meters => 1.0,
+            Unit::Kilometers => 1000.0,
+            Unit::Inches => 0.0254,
+            Unit::Feet => 0.3048,
+        }
+    }
+
+    /// Returns the name of the unit, for messages.
+    pub fn name(self) -> &'static str {
+        match self {
+            Unit::Millimeters => "millimeters",
+            Unit::Centimeters => "centimeters",
+            Unit::Meters => "meters",
+            Unit::Kilometers => "kilometers",
+            Unit::Inches => "inches",
+            Unit::Feet => "feet",
+        }
+    }
+
+    /// Returns the factor turning lengths in this unit into lengths in another.
+    ///
+    /// # Arguments
+    ///
+    /// * `unit` - The unit to convert to.
+    ///
+    /// # Returns
+    ///
+    /// The number of `unit`s in one of this unit.
+    pub fn to(self, unit: Unit) -> f64 {
+        self.meters() / unit.meters()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unit_conversion() {
+        assert_eq!(Unit::default(), Unit::Meters);
+        assert!((Unit::Centimeters.to(Unit::Meters) - 0.01).abs() < 1e-15);
+        assert!((Unit::Meters.to(Unit::Millimeters) - 1000.0).abs() < 1e-9);
+        assert!((Unit::Feet.to(Unit::Inches) - 12.0).abs() < 1e-12);
+        assert_eq!(Unit::Kilometers.to(Unit::Kilometers), 1.0);
+    }
+}
@@ -1,21 +1,5 @@
 use crate::vector3::Vector3;
 
-/// Converts a linear color component to a gamma-corrected component.
-///
-/// # Arguments
-///
-/// * `linear_component` - The linear color component.
-///
-/// # Returns
-///
-/// The gamma-corrected color component.
-pub fn linear_to_gamma(linear_component: f64) -> f64 {
-    if linear_component > 0.0 {
-        return linear_component.powf(1.0 / 2.2);
-    }
-    0.0
-}
-
 /// Reflects a vector off a surface with a given normal.
 ///
 /// # Arguments
@@ -106,16 +106,16 @@ impl Vector3 {
         )
     }
 
-    /// Generates a random vector within a unit disk.
+    /// Generates a random vector within a unit disk, spread evenly over its area.
     ///
     /// # Returns
     ///
     /// A random vector within a unit disk.
     pub fn random_in_unit_disk() -> Vector3 {
         let theta = fastrand::f64() * std::f64::consts::PI * 2.0;
-        let x = theta.cos();
-        let y = theta.sin();
-        Vector3::new(x, y, 0.0)
+        // The area within a radius grows with its square.
+        let radius = fastrand::f64().sqrt();
+        Vector3::new(radius * theta.cos(), radius * theta.sin(), 0.0)
     }
 
     /// Generates a random vector within a unit sphere.
@@ -151,6 +151,35 @@ impl Vector3 {
         }
     }
 
+    /// Generates a random unit vector uniformly distributed over the unit sphere.
+    ///
+    /// # Returns
+    ///
+    /// A random unit vector.
+    pub fn random_unit_vector() -> Vector3 {
+        let z = 1.0 - 2.0 * fastrand::f64();
+        let phi = 2.0 * std::f64::consts::PI * fastrand::f64();
+        let r = (1.0 - z * z).max(0.0).sqrt();
+        Vector3::new(r * phi.cos(), r * phi.sin(), z)
+    }
+
+    /// Generates a random direction on the hemisphere around the z-axis, distributed
+    /// proportionally to the cosine of its angle with the z-axis.
+    ///
+    /// # Returns
+    ///
+    /// A random cosine-distributed unit vector.
+    pub fn random_cosine_direction() -> Vector3 {
+        let r1 = fastrand::f64();
+        let r2 = fastrand::f64();
+
+        let phi = 2.0 * std::f64::consts::PI * r1;
+        let x = phi.cos() * r2.sqrt();
+        let y = phi.sin() * r2.sqrt();
+        let z = (1.0 - r2).sqrt();
+        Vector3::new(x, y, z)
+    }
+
     /// Checks if the vector is near zero in all components.
     ///
     /// # Returns
@@ -162,6 +191,15 @@ impl Vector3 {
         (self.x.abs() < s) && (self.y.abs() < s) && (self.z.abs() < s)
     }
 
+    /// Computes the relative luminance of the vector interpreted as a linear RGB color.
+    ///
+    /// # Returns
+    ///
+    /// The luminance of the color.
+    pub fn luminance(&self) -> f64 {
+        0.2126 * self.x + 0.7152 * self.y + 0.0722 * self.z
+    }
+
     pub fn max(&self) -> f64 {
         let mut max = self.x;
         max = max.max(self.y);
@@ -468,4 +506,17 @@ mod tests {
         let v = Vector3::random_in_unit_sphere();
         assert!(0.999 <= v.length() && v.length() <= 1.0);
     }
+
+    #[test]
+    fn test_random_unit_vector() {
+        let v = Vector3::random_unit_vector();
+        assert!((v.length() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_random_cosine_direction() {
+        let v = Vector3::random_cosine_direction();
+        assert!((v.length() - 1.0).abs() < 1e-9);
+        assert!(v.z >= 0.0);
+    }
 }